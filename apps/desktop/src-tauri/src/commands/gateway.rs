@@ -259,6 +259,154 @@ fn map_domain_event_to_ui(event: &DomainEvent) -> (&'static str, serde_json::Val
                 "removed": removed,
             }),
         ),
+        DomainEvent::StateChanged {
+            space_id,
+            server_id,
+            from,
+            to,
+        } => (
+            "server-state-changed",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "from": from,
+                "to": to,
+            }),
+        ),
+        DomainEvent::ServerRestarting {
+            space_id,
+            server_id,
+            attempt,
+            max_restarts,
+            delay_ms,
+        } => (
+            "server-restarting",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "attempt": attempt,
+                "max_restarts": max_restarts,
+                "delay_ms": delay_ms,
+            }),
+        ),
+        DomainEvent::ServerConnected {
+            space_id,
+            server_id,
+            startup_ms,
+            resolve_ms,
+            spawn_ms,
+            handshake_ms,
+        } => (
+            "server-connected",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "startup_ms": startup_ms,
+                "resolve_ms": resolve_ms,
+                "spawn_ms": spawn_ms,
+                "handshake_ms": handshake_ms,
+            }),
+        ),
+        DomainEvent::ServerRecovered {
+            space_id,
+            server_id,
+        } => (
+            "server-recovered",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+            }),
+        ),
+        DomainEvent::ServerReconnecting {
+            space_id,
+            server_id,
+            attempt,
+            max_attempts,
+            delay_ms,
+        } => (
+            "server-reconnecting",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "attempt": attempt,
+                "max_attempts": max_attempts,
+                "delay_ms": delay_ms,
+            }),
+        ),
+        DomainEvent::ServerCircuitChanged {
+            space_id,
+            server_id,
+            state,
+            failures,
+            retry_after_ms,
+        } => (
+            "server-circuit-changed",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "state": state,
+                "failures": failures,
+                "retry_after_ms": retry_after_ms,
+            }),
+        ),
+        DomainEvent::ServerHealthChanged {
+            space_id,
+            server_id,
+            health,
+            failures,
+            message,
+        } => (
+            "server-health-changed",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "health": health,
+                "failures": failures,
+                "message": message,
+            }),
+        ),
+        DomainEvent::ServerAuthExpired {
+            space_id,
+            server_id,
+            message,
+        } => (
+            "server-auth-expired",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "message": message,
+            }),
+        ),
+        DomainEvent::ServerExited {
+            space_id,
+            server_id,
+            code,
+            signal,
+            message,
+        } => (
+            "server-exited",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "code": code,
+                "signal": signal,
+                "message": message,
+            }),
+        ),
+        DomainEvent::ServerFailed {
+            space_id,
+            server_id,
+            restarts,
+            message,
+        } => (
+            "server-failed",
+            serde_json::json!({
+                "space_id": space_id,
+                "server_id": server_id,
+                "restarts": restarts,
+                "message": message,
+            }),
+        ),
 
         // Feature set events
         DomainEvent::FeatureSetCreated {
@@ -466,20 +614,26 @@ fn create_gateway_dependencies(
     _app_handle: tauri::AppHandle,
 ) -> Result<mcpmux_gateway::GatewayDependencies, String> {
     // Load JWT signing secret (DPAPI on Windows, keychain elsewhere)
-    let jwt_secret = match mcpmux_storage::create_jwt_secret_provider(app_state.data_dir()) {
+    let jwt_provider = mcpmux_storage::create_jwt_secret_provider(app_state.data_dir());
+    let (jwt_secret, previous_jwt_secret) = match jwt_provider {
         Ok(provider) => match provider.get_or_create_secret() {
             Ok(secret) => {
                 info!("[Gateway] JWT signing secret loaded");
-                Some(secret)
+                // Tokens signed before a rotation stay valid for its grace period
+                let previous = provider.previous_secret().unwrap_or_else(|e| {
+                    warn!("[Gateway] Failed to load previous JWT secret: {}", e);
+                    None
+                });
+                (Some(secret), previous)
             }
             Err(e) => {
                 warn!("[Gateway] Failed to load JWT secret: {}", e);
-                None
+                (None, None)
             }
         },
         Err(e) => {
             warn!("[Gateway] Failed to create JWT secret provider: {}", e);
-            None
+            (None, None)
         }
     };
 
@@ -490,6 +644,7 @@ fn create_gateway_dependencies(
         .with_backend_oauth_repo(app_state.backend_oauth_repository.clone())
         .with_feature_repo(app_state.server_feature_repository_core.clone())
         .with_feature_set_repo(app_state.feature_set_repository.clone())
+        .with_space_repo(app_state.space_repository.clone())
         .with_server_discovery(app_state.server_discovery.clone())
         .with_log_manager(app_state.server_log_manager.clone())
         .with_database(app_state.database())
@@ -499,6 +654,9 @@ fn create_gateway_dependencies(
     if let Some(secret) = jwt_secret {
         builder = builder.with_jwt_secret(secret);
     }
+    if let Some(previous) = previous_jwt_secret {
+        builder = builder.with_previous_jwt_secret(previous);
+    }
 
     builder.build().map_err(|e: String| e)
 }
@@ -785,10 +943,20 @@ pub async fn connect_server(
         .ok_or("Pool service not initialized")?;
     drop(state); // Release lock before async work
 
+    // Servers inherit their space's env
+    let space_env = app_state
+        .space_service
+        .get(&space_uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|space| space.space_env)
+        .unwrap_or_default();
+
     // Build transport config from cached definition + input values
     let transport = mcpmux_gateway::pool::transport::resolution::build_transport_config(
         &server_definition.transport,
         &installed,
+        &space_env,
         Some(app_state.data_dir()),
     );
 
@@ -810,7 +978,7 @@ pub async fn connect_server(
 
             Ok(())
         }
-        ConnectionResult::Failed { error } => {
+        ConnectionResult::Failed { error } | ConnectionResult::CircuitOpen { error, .. } => {
             error!(
                 "[Gateway] Failed to connect server {}: {}",
                 server_id, error
@@ -1049,6 +1217,7 @@ pub async fn connect_all_enabled_servers(
             let transport = mcpmux_gateway::pool::transport::resolution::build_transport_config(
                 &server_definition.transport,
                 &installed,
+                &space.space_env,
                 Some(app_state.data_dir()),
             );
 
@@ -1098,7 +1267,7 @@ pub async fn connect_all_enabled_servers(
             ConnectionResult::OAuthRequired { auth_url: _ } => {
                 result.oauth_required += 1;
             }
-            ConnectionResult::Failed { error } => {
+            ConnectionResult::Failed { error } | ConnectionResult::CircuitOpen { error, .. } => {
                 result.failed += 1;
                 result.errors.push(format!("{}: {}", server_id, error));
             }
@@ -1117,20 +1286,12 @@ pub async fn connect_all_enabled_servers(
 #[tauri::command]
 pub async fn get_pool_stats(
     gateway_state: State<'_, Arc<RwLock<GatewayAppState>>>,
-) -> Result<PoolStatsResponse, String> {
+) -> Result<mcpmux_gateway::PoolStats, String> {
     let state = gateway_state.read().await;
 
-    let stats = match &state.pool_service {
+    Ok(match &state.pool_service {
         Some(pool) => pool.stats(),
         None => mcpmux_gateway::PoolStats::default(),
-    };
-
-    Ok(PoolStatsResponse {
-        total_instances: stats.total_instances,
-        connected_instances: stats.connected_instances,
-        total_space_server_mappings: stats.connecting_instances
-            + stats.failed_instances
-            + stats.oauth_pending_instances,
     })
 }
 
@@ -1161,11 +1322,3 @@ pub struct RefreshResult {
     /// Number of refresh attempts that failed
     pub refresh_failed: usize,
 }
-
-/// Pool statistics response
-#[derive(Debug, Serialize)]
-pub struct PoolStatsResponse {
-    pub total_instances: usize,
-    pub connected_instances: usize,
-    pub total_space_server_mappings: usize,
-}
@@ -1,7 +1,7 @@
 //! Tauri commands for server log management
 
 use crate::state::AppState;
-use mcpmux_core::{AppSettingsService, LogLevel, ServerLog};
+use mcpmux_core::{AppSettingsService, LogLevel, LogSource, ServerLog};
 use serde::Serialize;
 use tauri::State;
 use tracing::{info, warn};
@@ -47,11 +47,12 @@ pub async fn get_server_logs(
     server_id: String,
     limit: Option<usize>,
     level_filter: Option<String>,
+    source_filter: Option<Vec<String>>,
     state: State<'_, AppState>,
 ) -> Result<Vec<ServerLogEntry>, String> {
     info!(
-        "[Logs] Getting logs for server {} (limit: {:?}, filter: {:?})",
-        server_id, limit, level_filter
+        "[Logs] Getting logs for server {} (limit: {:?}, filter: {:?}, sources: {:?})",
+        server_id, limit, level_filter, source_filter
     );
 
     let space_id = get_default_space_id(&state).await?;
@@ -59,17 +60,33 @@ pub async fn get_server_logs(
     // Parse level filter
     let level = level_filter.and_then(|s| LogLevel::parse(&s));
 
-    // Get logs
+    // Parse source filter; an unknown name would otherwise filter out everything
+    let sources: Option<Vec<LogSource>> = source_filter
+        .map(|names| {
+            names
+                .iter()
+                .map(|s| LogSource::parse(s).ok_or_else(|| format!("Unknown log source: {}", s)))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+
+    // Get logs (damaged lines are skipped and logged by the manager)
     let logs = state
         .server_log_manager
-        .read_logs(&space_id, &server_id, limit.unwrap_or(100), level)
+        .read_logs(
+            &space_id,
+            &server_id,
+            limit.unwrap_or(100),
+            level,
+            sources.as_deref(),
+        )
         .await
         .map_err(|e| {
             warn!("[Logs] Failed to read logs for {}: {}", server_id, e);
             format!("Failed to read logs: {}", e)
         })?;
 
-    Ok(logs.into_iter().map(ServerLogEntry::from).collect())
+    Ok(logs.entries.into_iter().map(ServerLogEntry::from).collect())
 }
 
 /// Clear logs for a server
@@ -103,6 +120,8 @@ pub async fn get_server_log_file(
 ) -> Result<String, String> {
     let space_id = get_default_space_id(&state).await?;
 
+    // External viewers read the file directly, so write out buffered lines first
+    state.server_log_manager.flush().await;
     let path = state.server_log_manager.get_log_file(&space_id, &server_id);
 
     Ok(path.to_string_lossy().to_string())
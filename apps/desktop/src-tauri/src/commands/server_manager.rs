@@ -134,6 +134,7 @@ pub async fn enable_server_v2(
     let transport = build_transport_config(
         &server_definition.transport,
         &installed,
+        &space_env(&app_state, &space_uuid).await?,
         Some(app_state.data_dir()),
     );
 
@@ -166,7 +167,7 @@ pub async fn enable_server_v2(
 
             Ok(())
         }
-        ConnectionResult::Failed { error } => {
+        ConnectionResult::Failed { error } | ConnectionResult::CircuitOpen { error, .. } => {
             manager.set_error(&key, error.clone()).await;
 
             // Mark features unavailable - connection failed
@@ -226,8 +227,8 @@ pub async fn disable_server_v2(
         .oauth_manager()
         .cancel_flow_for_space(space_uuid, &server_id);
 
-    // Update state to disconnected (not connected, but not cleared either)
-    manager.set_disconnected(&key).await;
+    // Update state to disabled (not connected, but not cleared either)
+    manager.set_disabled(&key).await;
 
     // Update database - just mark as disabled
     app_state
@@ -323,6 +324,7 @@ pub async fn start_auth_v2(
     let transport = build_transport_config(
         &server_definition.transport,
         &installed,
+        &space_env(&app_state, &space_uuid).await?,
         Some(app_state.data_dir()),
     );
     let ctx = ConnectionContext::new(space_uuid, server_id.clone(), transport);
@@ -338,7 +340,7 @@ pub async fn start_auth_v2(
             manager.open_browser(&auth_url);
             Ok(())
         }
-        ConnectionResult::Failed { error } => {
+        ConnectionResult::Failed { error } | ConnectionResult::CircuitOpen { error, .. } => {
             manager.set_error(&key, error.clone()).await;
             Err(error)
         }
@@ -608,3 +610,17 @@ pub async fn disconnect_server_v2(
 
     Ok(())
 }
+
+/// Env of a space, which every server in it inherits
+async fn space_env(
+    app_state: &AppState,
+    space_id: &Uuid,
+) -> Result<HashMap<String, String>, String> {
+    Ok(app_state
+        .space_service
+        .get(space_id)
+        .await
+        .map_err(|e| format!("Failed to get space: {}", e))?
+        .map(|space| space.space_env)
+        .unwrap_or_default())
+}
@@ -309,6 +309,7 @@ pub fn run() {
             let credential_repo = app_state.credential_repository.clone();
             let backend_oauth_repo = app_state.backend_oauth_repository.clone();
             let feature_set_repo = app_state.feature_set_repository.clone();
+            let space_repo = app_state.space_repository.clone();
             let feature_repo = app_state.server_feature_repository_core.clone();
             let server_discovery = app_state.server_discovery.clone();
             let server_log_manager = app_state.server_log_manager.clone();
@@ -339,20 +340,26 @@ pub fn run() {
                 info!("Auto-starting gateway on {}", url);
 
                 // Load JWT signing secret (DPAPI on Windows, keychain elsewhere)
-                let jwt_secret = match mcpmux_storage::create_jwt_secret_provider(&app_data_dir) {
+                let jwt_provider = mcpmux_storage::create_jwt_secret_provider(&app_data_dir);
+                let (jwt_secret, previous_jwt_secret) = match jwt_provider {
                     Ok(provider) => match provider.get_or_create_secret() {
                         Ok(secret) => {
                             info!("[Gateway] JWT signing secret loaded");
-                            Some(secret)
+                            // Tokens signed before a rotation stay valid for its grace period
+                            let previous = provider.previous_secret().unwrap_or_else(|e| {
+                                warn!("[Gateway] Failed to load previous JWT secret: {}", e);
+                                None
+                            });
+                            (Some(secret), previous)
                         }
                         Err(e) => {
                             warn!("[Gateway] Failed to load JWT secret: {}. Token signing disabled.", e);
-                            None
+                            (None, None)
                         }
                     },
                     Err(e) => {
                         warn!("[Gateway] Failed to create JWT secret provider: {}. Token signing disabled.", e);
-                        None
+                        (None, None)
                     }
                 };
 
@@ -363,6 +370,7 @@ pub fn run() {
                     .with_backend_oauth_repo(backend_oauth_repo)
                     .with_feature_repo(feature_repo)
                     .with_feature_set_repo(feature_set_repo)
+                    .with_space_repo(space_repo)
                     .with_server_discovery(server_discovery)
                     .with_log_manager(server_log_manager)
                     .with_database(db_for_gateway)
@@ -372,6 +380,9 @@ pub fn run() {
                 if let Some(secret) = jwt_secret {
                     deps_builder = deps_builder.with_jwt_secret(secret);
                 }
+                if let Some(previous) = previous_jwt_secret {
+                    deps_builder = deps_builder.with_previous_jwt_secret(previous);
+                }
 
                 let dependencies = match deps_builder.build() {
                     Ok(deps) => deps,
@@ -456,7 +467,8 @@ pub fn run() {
                                                 warn!("[OAuth Handler] Still requires OAuth after completion: {}", server_id);
                                                 sm.set_auth_required(&key, Some("OAuth still required".to_string())).await;
                                             }
-                                            ConnectionResult::Failed { error } => {
+                                            ConnectionResult::Failed { error }
+                                            | ConnectionResult::CircuitOpen { error, .. } => {
                                                 error!("[OAuth Handler] Reconnection failed for {}: {}", server_id, error);
                                                 sm.set_error(&key, error).await;
                                             }
@@ -486,6 +498,16 @@ pub fn run() {
                 let _refresh_handle = server_manager_arc.clone().start_periodic_refresh();
                 info!("[Gateway] Periodic refresh service started");
 
+                // Re-prompt for OAuth when a rejected token can't be refreshed
+                let _auth_watch_handle = server_manager_arc.clone().start_auth_expiry_watch();
+
+                // Show supervisor restarts and SSE reconnects as connection states
+                let _recovery_watch_handle = server_manager_arc.clone().start_recovery_watch();
+
+                // Ping connected HTTP servers so outages show before a tool call fails
+                let _health_handle = pool_service.clone().start_health_checks();
+                info!("[Gateway] HTTP health checks started");
+
                 // Note: Auto-connect happens in the frontend via useEffect calling connect_all_enabled_servers
                 // This keeps the backend service clean and follows React best practices
 
@@ -848,6 +870,44 @@ pub fn run() {
             commands::get_startup_settings,
             commands::update_startup_settings,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running McpMux application");
+        .build(tauri::generate_context!())
+        .expect("error while building McpMux application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                drain_server_pool(app_handle);
+                flush_server_logs(app_handle);
+            }
+        });
+}
+
+/// Write out buffered server log lines, including those from draining the pool.
+fn flush_server_logs(app_handle: &tauri::AppHandle) {
+    if let Some(app_state) = app_handle.try_state::<AppState>() {
+        tauri::async_runtime::block_on(app_state.server_log_manager.flush());
+    }
+}
+
+/// How long server processes get to exit cleanly when the app quits.
+const EXIT_DRAIN_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Shut down all server processes before exiting.
+///
+/// Dropping the pool would force-kill them, which can leave orphans behind
+/// (e.g. docker containers started by a wrapper script).
+fn drain_server_pool(app_handle: &tauri::AppHandle) {
+    let Some(gateway_state) = app_handle.try_state::<Arc<RwLock<GatewayAppState>>>() else {
+        return;
+    };
+    tauri::async_runtime::block_on(async {
+        let pool = gateway_state.read().await.pool_service.clone();
+        if let Some(pool) = pool {
+            let unclean = pool.drain(EXIT_DRAIN_GRACE).await;
+            if !unclean.is_empty() {
+                warn!(
+                    "[App] {} server(s) had to be force-killed on exit",
+                    unclean.len()
+                );
+            }
+        }
+    });
 }
@@ -11,13 +11,13 @@ use mcpmux_core::{
     SpaceService,
 };
 use mcpmux_storage::{
-    Database, FieldEncryptor, SqliteAppSettingsRepository, SqliteCredentialRepository,
-    SqliteFeatureSetRepository, SqliteInboundMcpClientRepository, SqliteInstalledServerRepository,
-    SqliteOutboundOAuthRepository, SqliteServerFeatureRepository, SqliteSpaceRepository,
+    Database, DbPool, FieldEncryptor, SqliteAppSettingsRepository, SqliteAuditRepository,
+    SqliteCredentialRepository, SqliteFeatureSetRepository, SqliteInboundMcpClientRepository,
+    SqliteInstalledServerRepository, SqliteOutboundOAuthRepository, SqliteServerFeatureRepository,
+    SqliteSpaceRepository,
 };
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tracing::info;
 
 /// Global application state accessible from commands.
@@ -32,6 +32,8 @@ pub struct AppState {
     pub gateway_port_service: Arc<GatewayPortService>,
     /// Service for managing spaces
     pub space_service: SpaceService,
+    /// Space repository (with encryption for sensitive space env)
+    pub space_repository: Arc<dyn SpaceRepository>,
     /// Service for managing clients (auto-grants, etc.)
     pub client_service: ClientService,
     /// Server discovery service for loading servers from API/bundled/user spaces
@@ -55,9 +57,9 @@ pub struct AppState {
     /// Field encryptor (used for credential repository creation)
     #[allow(dead_code)]
     pub encryptor: Arc<FieldEncryptor>,
-    /// Shared database connection (kept alive for the app lifetime)
+    /// Database connection pool (kept alive for the app lifetime)
     #[allow(dead_code)]
-    db: Arc<Mutex<Database>>,
+    db: DbPool,
 }
 
 impl AppState {
@@ -80,18 +82,31 @@ impl AppState {
         info!("Opening database at {:?}", db_path);
 
         let db = Database::open(&db_path)?;
-        let db = Arc::new(Mutex::new(db));
+
+        // Fail early with a clear error if the key doesn't belong to this database.
+        // A key file copied in from another install is reported as such, unless
+        // the user opted into migrating it.
+        let allow_migration =
+            std::env::var_os(mcpmux_storage::ALLOW_KEY_MIGRATION_ENV_VAR).is_some();
+        db.verify_install_id(key_provider.install_id()?.as_deref(), allow_migration)?;
+        db.verify_key_fingerprint(&encryptor)?;
+
+        // Verified and migrated; serve repositories from a pool from here on
+        drop(db);
+        let db = DbPool::open(&db_path)?;
 
         // Initialize repositories
         let space_repository: Arc<dyn SpaceRepository> =
-            Arc::new(SqliteSpaceRepository::new(db.clone()));
+            Arc::new(SqliteSpaceRepository::new(db.clone()).with_encryptor(encryptor.clone()));
 
         let installed_server_repository: Arc<dyn InstalledServerRepository> = Arc::new(
             SqliteInstalledServerRepository::new(db.clone(), encryptor.clone()),
         );
 
+        let audit_repository = Arc::new(SqliteAuditRepository::new(db.clone()));
         let credential_repository: Arc<dyn CredentialRepository> = Arc::new(
-            SqliteCredentialRepository::new(db.clone(), encryptor.clone()),
+            SqliteCredentialRepository::new(db.clone(), encryptor.clone())
+                .with_audit(audit_repository, "desktop"),
         );
 
         let backend_oauth_repository: Arc<dyn OutboundOAuthRepository> =
@@ -115,7 +130,7 @@ impl AppState {
 
         // Create services
         let space_service = SpaceService::with_feature_set_repository(
-            space_repository,
+            space_repository.clone(),
             feature_set_repository.clone(),
         );
         let client_service =
@@ -142,7 +157,9 @@ impl AppState {
             base_dir: data_dir.join("logs"),
             max_file_size: 10 * 1024 * 1024, // 10MB
             max_files: 30,                   // 30 files
+            max_age: None,                   // age retention runs via cleanup_logs_older_than
             compress: true,
+            ..Default::default()
         };
         let server_log_manager = Arc::new(ServerLogManager::new(log_config));
 
@@ -154,6 +171,7 @@ impl AppState {
             settings_repository,
             gateway_port_service,
             space_service,
+            space_repository,
             client_service,
             server_discovery,
             server_log_manager,
@@ -169,8 +187,8 @@ impl AppState {
         })
     }
 
-    /// Get the shared database connection for use by other components (e.g., gateway)
-    pub fn database(&self) -> Arc<Mutex<Database>> {
+    /// Get the database connection pool for use by other components (e.g., gateway)
+    pub fn database(&self) -> DbPool {
         self.db.clone()
     }
 
@@ -11,6 +11,16 @@
  * - `server-status-changed` - Connection status updates
  * - `server-auth-progress` - OAuth countdown timer
  * - `server-features-refreshed` - Features discovered/updated
+ * - `server-connected` - STDIO server started, with startup timing breakdown
+ * - `server-state-changed` - Connection state transition (from/to)
+ * - `server-restarting` - Crashed stdio server being restarted
+ * - `server-recovered` - Restarted stdio server completed its handshake again
+ * - `server-reconnecting` - HTTP server's SSE stream being re-established
+ * - `server-circuit-changed` - Server temporarily disabled after repeated connect failures
+ * - `server-health-changed` - HTTP server stopped or resumed answering health checks
+ * - `server-auth-expired` - OAuth token rejected and could not be refreshed
+ * - `server-exited` - STDIO server process exited unexpectedly (exit code or signal)
+ * - `server-failed` - Server gave up after repeated crashes or reconnects
  * - `feature-set-changed` - Feature set create/update/delete
  * - `client-changed` - Client registration/update/delete
  * - `grants-changed` - Grant/revoke permissions
@@ -54,6 +64,16 @@ export type DomainEventChannel =
   | 'server-status-changed'
   | 'server-auth-progress'
   | 'server-features-refreshed'
+  | 'server-connected'
+  | 'server-state-changed'
+  | 'server-restarting'
+  | 'server-recovered'
+  | 'server-reconnecting'
+  | 'server-circuit-changed'
+  | 'server-health-changed'
+  | 'server-auth-expired'
+  | 'server-exited'
+  | 'server-failed'
   | 'feature-set-changed'
   | 'client-changed'
   | 'grants-changed'
@@ -118,6 +138,100 @@ export interface ServerFeaturesRefreshedPayload extends DomainEventPayload {
   removed: string[];
 }
 
+/** Server connected payload (STDIO startup timing) */
+export interface ServerConnectedPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  startup_ms: number;
+  resolve_ms: number;
+  spawn_ms: number;
+  handshake_ms: number;
+}
+
+/** Server connection state */
+export type ConnectionState =
+  | 'idle'
+  | 'connecting'
+  | 'connected'
+  | 'reconnecting'
+  | 'oauth_pending'
+  | 'failed'
+  | 'disabled';
+
+/** Server state changed payload */
+export interface ServerStateChangedPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  from: ConnectionState;
+  to: ConnectionState;
+}
+
+/** Server restarting payload */
+export interface ServerRestartingPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  attempt: number;
+  max_restarts: number;
+  delay_ms: number;
+}
+
+/** Server recovered payload */
+export interface ServerRecoveredPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+}
+
+/** Server reconnecting payload */
+export interface ServerReconnectingPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  attempt: number;
+  max_attempts: number;
+  delay_ms: number;
+}
+
+/** Server circuit breaker payload */
+export interface ServerCircuitChangedPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  state: 'closed' | 'open' | 'half_open';
+  failures: number;
+  retry_after_ms?: number | null;
+}
+
+/** Server health check payload */
+export interface ServerHealthChangedPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  health: 'healthy' | 'unhealthy';
+  failures: number;
+  message?: string | null;
+}
+
+/** Server auth expired payload */
+export interface ServerAuthExpiredPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  message: string;
+}
+
+/** Server process exited payload */
+export interface ServerExitedPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  code: number | null;
+  signal: number | null;
+  message: string;
+}
+
+/** Server failed payload */
+export interface ServerFailedPayload extends DomainEventPayload {
+  space_id: string;
+  server_id: string;
+  restarts: number;
+  message: string;
+}
+
 /** Feature set event payloads */
 export interface FeatureSetChangedPayload extends DomainEventPayload {
   action: 'created' | 'updated' | 'deleted' | 'members_changed';
@@ -167,6 +281,16 @@ export interface PayloadTypeMap {
   'server-status-changed': ServerStatusChangedPayload;
   'server-auth-progress': ServerAuthProgressPayload;
   'server-features-refreshed': ServerFeaturesRefreshedPayload;
+  'server-connected': ServerConnectedPayload;
+  'server-state-changed': ServerStateChangedPayload;
+  'server-restarting': ServerRestartingPayload;
+  'server-recovered': ServerRecoveredPayload;
+  'server-reconnecting': ServerReconnectingPayload;
+  'server-circuit-changed': ServerCircuitChangedPayload;
+  'server-health-changed': ServerHealthChangedPayload;
+  'server-auth-expired': ServerAuthExpiredPayload;
+  'server-exited': ServerExitedPayload;
+  'server-failed': ServerFailedPayload;
   'feature-set-changed': FeatureSetChangedPayload;
   'client-changed': ClientChangedPayload;
   'grants-changed': GrantsChangedPayload;
@@ -196,6 +320,16 @@ const ALL_CHANNELS: DomainEventChannel[] = [
   'server-status-changed',
   'server-auth-progress',
   'server-features-refreshed',
+  'server-connected',
+  'server-state-changed',
+  'server-restarting',
+  'server-recovered',
+  'server-reconnecting',
+  'server-circuit-changed',
+  'server-health-changed',
+  'server-auth-expired',
+  'server-exited',
+  'server-failed',
   'feature-set-changed',
   'client-changed',
   'grants-changed',
@@ -186,13 +186,45 @@ export async function connectAllEnabledServers(): Promise<BulkConnectResult> {
   return invoke('connect_all_enabled_servers');
 }
 
+/**
+ * Connection state of a pooled server.
+ */
+export type ServerConnectionState =
+  | 'disconnected'
+  | 'connecting'
+  | 'reconnecting'
+  | 'connected'
+  | 'failed'
+  | 'oauth_pending';
+
+/**
+ * Statistics for one pooled server.
+ */
+export interface ServerStats {
+  space_id: string;
+  server_id: string;
+  state: ServerConnectionState;
+  last_error: string | null;
+  connect_count: number;
+  uptime_secs: number | null;
+  consecutive_failures: number;
+  requests_served: number;
+  in_flight_requests: number;
+  queued_requests: number;
+}
+
 /**
  * Pool statistics.
  */
 export interface PoolStats {
   total_instances: number;
   connected_instances: number;
-  total_space_server_mappings: number;
+  connecting_instances: number;
+  reconnecting_instances: number;
+  failed_instances: number;
+  oauth_pending_instances: number;
+  queued_requests: number;
+  servers: ServerStats[];
 }
 
 /**
@@ -13,16 +13,21 @@ export interface ServerLogEntry {
 
 /**
  * Get recent logs for a server.
+ *
+ * Filters are applied before `limit`, so e.g. asking for 100 warnings
+ * returns up to 100 warnings rather than the warnings among the last 100 lines.
  */
 export async function getServerLogs(
   serverId: string,
   limit?: number,
-  levelFilter?: string
+  levelFilter?: string,
+  sourceFilter?: string[]
 ): Promise<ServerLogEntry[]> {
   return invoke('get_server_logs', {
     serverId,
     limit,
     levelFilter,
+    sourceFilter,
   });
 }
 
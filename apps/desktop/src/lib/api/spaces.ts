@@ -12,6 +12,9 @@ export interface Space {
   sort_order: number;
   created_at: string;
   updated_at: string;
+  deleted_at?: string; // only set on soft-deleted spaces
+  version: number; // pass back unchanged when updating
+  tags: string[];
 }
 
 /**
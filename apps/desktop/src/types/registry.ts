@@ -13,6 +13,8 @@ export interface InputDefinition {
   /** Predefined options for select input type */
   options?: { value: string; label: string; description?: string }[];
   secret?: boolean;
+  /** Hand the value to stdio servers via a private file or fd instead of argv/env */
+  sensitive?: 'file' | 'fd';
   placeholder?: string;
   /** URL to obtain credentials/values */
   obtain_url?: string;
@@ -31,10 +33,34 @@ export interface TransportMetadata {
   inputs: InputDefinition[];
 }
 
+/** Client certificate / CA settings for HTTP transports (paths or inline PEM) */
+export interface TlsConfig {
+  client_cert?: string;
+  client_key?: string;
+  ca_cert?: string;
+  ca_only?: boolean;
+}
+
 /** Transport configuration */
 export type TransportConfig =
-  | { type: 'stdio'; command: string; args: string[]; env: Record<string, string>; metadata: TransportMetadata }
-  | { type: 'http'; url: string; headers: Record<string, string>; metadata: TransportMetadata };
+  | {
+      type: 'stdio';
+      command: string;
+      args: string[];
+      env: Record<string, string>;
+      connect_timeout_secs?: number;
+      metadata: TransportMetadata;
+    }
+  | {
+      type: 'http';
+      url: string;
+      headers: Record<string, string>;
+      proxy?: string;
+      tls?: TlsConfig;
+      connect_timeout_secs?: number;
+      request_timeout_secs?: number;
+      metadata: TransportMetadata;
+    };
 
 /** Server source */
 export type ServerSource =
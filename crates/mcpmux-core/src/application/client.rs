@@ -30,17 +30,17 @@ impl ClientAppService {
 
     /// List all clients
     pub async fn list(&self) -> Result<Vec<Client>> {
-        self.client_repo.list().await
+        Ok(self.client_repo.list().await?)
     }
 
     /// Get a client by ID
     pub async fn get(&self, id: Uuid) -> Result<Option<Client>> {
-        self.client_repo.get(&id).await
+        Ok(self.client_repo.get(&id).await?)
     }
 
     /// Get a client by access key
     pub async fn get_by_access_key(&self, key: &str) -> Result<Option<Client>> {
-        self.client_repo.get_by_access_key(key).await
+        Ok(self.client_repo.get_by_access_key(key).await?)
     }
 
     /// Create a new client
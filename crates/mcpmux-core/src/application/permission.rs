@@ -37,17 +37,17 @@ impl PermissionAppService {
 
     /// List all feature sets
     pub async fn list_feature_sets(&self) -> Result<Vec<FeatureSet>> {
-        self.feature_set_repo.list().await
+        Ok(self.feature_set_repo.list().await?)
     }
 
     /// List feature sets for a space
     pub async fn list_feature_sets_for_space(&self, space_id: &str) -> Result<Vec<FeatureSet>> {
-        self.feature_set_repo.list_by_space(space_id).await
+        Ok(self.feature_set_repo.list_by_space(space_id).await?)
     }
 
     /// Get a feature set with its members
     pub async fn get_feature_set(&self, id: &str) -> Result<Option<FeatureSet>> {
-        self.feature_set_repo.get_with_members(id).await
+        Ok(self.feature_set_repo.get_with_members(id).await?)
     }
 
     /// Create a feature set
@@ -279,9 +279,10 @@ impl PermissionAppService {
 
     /// Get members of a feature set
     pub async fn get_feature_members(&self, feature_set_id: &str) -> Result<Vec<FeatureSetMember>> {
-        self.feature_set_repo
+        Ok(self
+            .feature_set_repo
             .get_feature_members(feature_set_id)
-            .await
+            .await?)
     }
 
     // ========================================================================
@@ -389,7 +390,9 @@ impl PermissionAppService {
             .as_ref()
             .ok_or_else(|| anyhow!("Client repository not configured"))?;
 
-        client_repo.get_grants_for_space(&client_id, space_id).await
+        Ok(client_repo
+            .get_grants_for_space(&client_id, space_id)
+            .await?)
     }
 
     /// Set all grants for a client in a space (replaces existing)
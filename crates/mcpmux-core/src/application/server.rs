@@ -42,17 +42,20 @@ impl ServerAppService {
 
     /// List all installed servers
     pub async fn list(&self) -> Result<Vec<InstalledServer>> {
-        self.server_repo.list().await
+        Ok(self.server_repo.list().await?)
     }
 
     /// List servers for a specific space
     pub async fn list_for_space(&self, space_id: &str) -> Result<Vec<InstalledServer>> {
-        self.server_repo.list_for_space(space_id).await
+        Ok(self.server_repo.list_for_space(space_id).await?)
     }
 
     /// Get a server by space and server ID
     pub async fn get(&self, space_id: &str, server_id: &str) -> Result<Option<InstalledServer>> {
-        self.server_repo.get_by_server_id(space_id, server_id).await
+        Ok(self
+            .server_repo
+            .get_by_server_id(space_id, server_id)
+            .await?)
     }
 
     /// Install a server from registry
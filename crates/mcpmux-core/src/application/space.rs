@@ -35,17 +35,17 @@ impl SpaceAppService {
 
     /// List all spaces
     pub async fn list(&self) -> Result<Vec<Space>> {
-        self.space_repo.list().await
+        Ok(self.space_repo.list().await?)
     }
 
     /// Get a space by ID
     pub async fn get(&self, id: Uuid) -> Result<Option<Space>> {
-        self.space_repo.get(&id).await
+        Ok(self.space_repo.get(&id).await?)
     }
 
     /// Get the active (default) space
     pub async fn get_active(&self) -> Result<Option<Space>> {
-        self.space_repo.get_default().await
+        Ok(self.space_repo.get_default().await?)
     }
 
     /// Create a new space
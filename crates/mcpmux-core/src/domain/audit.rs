@@ -0,0 +1,114 @@
+//! Audit log entries for security-relevant events
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kind of audited event.
+///
+/// Named `<target>_<event>`, e.g. credential events and refused commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    CredentialCreated,
+    /// A credential's secret value was decrypted and handed out
+    CredentialRead,
+    CredentialUpdated,
+    CredentialDeleted,
+    /// A STDIO server's command was refused by the command policy
+    CommandDenied,
+}
+
+impl AuditAction {
+    /// Convert to database string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CredentialCreated => "credential_created",
+            Self::CredentialRead => "credential_read",
+            Self::CredentialUpdated => "credential_updated",
+            Self::CredentialDeleted => "credential_deleted",
+            Self::CommandDenied => "command_denied",
+        }
+    }
+
+    /// Parse from database string representation.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "credential_created" => Some(Self::CredentialCreated),
+            "credential_read" => Some(Self::CredentialRead),
+            "credential_updated" => Some(Self::CredentialUpdated),
+            "credential_deleted" => Some(Self::CredentialDeleted),
+            "command_denied" => Some(Self::CommandDenied),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One append-only audit log record.
+///
+/// Never holds secret values; `detail` is free-form context such as the
+/// number of credentials a listing returned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    /// Who acted, e.g. "desktop" or "gateway"
+    pub actor: String,
+    pub action: AuditAction,
+    /// What was acted on; for credentials `<space_id>/<server_id>/<type>`,
+    /// for commands `<space_id>/<server_id>`
+    pub target_id: String,
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    /// A new entry timestamped now.
+    pub fn new(
+        actor: impl Into<String>,
+        action: AuditAction,
+        target_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            actor: actor.into(),
+            action,
+            target_id: target_id.into(),
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Which audit entries to list. Empty fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditFilter {
+    /// Only these actions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<AuditAction>,
+    /// Entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Entries before this time
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    /// Whether `entry` passes the filter.
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        (self.actions.is_empty() || self.actions.contains(&entry.action))
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp < until)
+    }
+}
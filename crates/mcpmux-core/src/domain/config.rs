@@ -34,6 +34,13 @@ pub struct UserServerEntry {
     pub url: Option<String>,
     pub headers: Option<HashMap<String, String>>,
 
+    // --- Timeouts (seconds) ---
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    /// HTTP only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_timeout_secs: Option<u64>,
+
     // --- Common Metadata ---
     pub name: Option<String>,
     pub description: Option<String>,
@@ -177,6 +184,10 @@ impl UserServerEntry {
             TransportConfig::Http {
                 url: url.clone(),
                 headers: self.headers.clone().unwrap_or_default(),
+                proxy: None,
+                tls: None,
+                connect_timeout_secs: self.connect_timeout_secs,
+                request_timeout_secs: self.request_timeout_secs,
                 metadata: TransportMetadata::default(),
             }
         } else if let Some(cmd) = &self.command {
@@ -185,6 +196,7 @@ impl UserServerEntry {
                 command: cmd.clone(),
                 args: self.args.clone().unwrap_or_default(),
                 env: self.env.clone().unwrap_or_default(),
+                connect_timeout_secs: self.connect_timeout_secs,
                 metadata: TransportMetadata::default(),
             }
         } else {
@@ -193,6 +205,7 @@ impl UserServerEntry {
                 command: String::new(),
                 args: vec![],
                 env: HashMap::new(),
+                connect_timeout_secs: None,
                 metadata: TransportMetadata::default(),
             }
         };
@@ -258,6 +271,7 @@ impl UserServerEntry {
                     placeholder: None,
                     obtain_url: None,
                     obtain_instructions: None,
+                    sensitive: None,
                 });
         }
 
@@ -295,6 +309,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -320,6 +336,8 @@ mod tests {
             env: None,
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -346,6 +364,8 @@ mod tests {
             env: None,
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -374,6 +394,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -406,6 +428,8 @@ mod tests {
             ])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -432,6 +456,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -449,6 +475,7 @@ mod tests {
                     placeholder: None,
                     obtain_url: None,
                     obtain_instructions: None,
+                    sensitive: None,
                 }]),
                 publisher: None,
             }),
@@ -547,6 +574,8 @@ mod tests {
                 "Authorization".to_string(),
                 "Bearer token".to_string(),
             )])),
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -580,6 +609,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -613,6 +644,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -638,6 +671,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -700,6 +735,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -717,6 +754,7 @@ mod tests {
                     placeholder: None,
                     obtain_url: None,
                     obtain_instructions: None,
+                    sensitive: None,
                 }]),
                 publisher: None,
             }),
@@ -744,6 +782,8 @@ mod tests {
             )])),
             url: None,
             headers: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             name: None,
             description: None,
             icon: None,
@@ -772,6 +812,7 @@ mod tests {
             placeholder: None,
             obtain_url: None,
             obtain_instructions: None,
+            sensitive: None,
         };
 
         let json = serde_json::to_string(&input).unwrap();
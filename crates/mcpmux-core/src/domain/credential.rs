@@ -11,6 +11,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use uuid::Uuid;
+use zeroize::Zeroize;
 
 /// Type of credential entry.
 ///
@@ -69,7 +70,8 @@ impl fmt::Display for CredentialType {
 /// Individual credential entry — one per (space, server, type).
 ///
 /// The `value` field contains the secret (token, key, password) in plaintext
-/// at the domain level. Encryption is handled by the storage layer.
+/// at the domain level and is wiped from memory when the credential is dropped.
+/// Encryption is handled by the storage layer.
 ///
 /// Metadata fields (expires_at, token_type, scope) are non-sensitive and
 /// stored as plaintext in the database for queryability.
@@ -104,6 +106,16 @@ pub struct Credential {
 
     /// When the credential was last used
     pub last_used: Option<DateTime<Utc>>,
+
+    /// Version of the stored row, bumped on every save. 0 for a credential
+    /// not loaded from storage, which overwrites whatever is stored.
+    pub version: i64,
+}
+
+impl Drop for Credential {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
 }
 
 impl Credential {
@@ -121,6 +133,7 @@ impl Credential {
             created_at: now,
             updated_at: now,
             last_used: None,
+            version: 0,
         }
     }
 
@@ -143,6 +156,7 @@ impl Credential {
             created_at: now,
             updated_at: now,
             last_used: None,
+            version: 0,
         }
     }
 
@@ -165,6 +179,7 @@ impl Credential {
             created_at: now,
             updated_at: now,
             last_used: None,
+            version: 0,
         }
     }
 
@@ -182,6 +197,71 @@ impl Credential {
     }
 }
 
+/// One credential of a bulk import, as read from a JSON or CSV export.
+///
+/// Fields are kept as strings so that a malformed row is reported for that
+/// row instead of failing the whole file at parse time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NewCredential {
+    pub space_id: String,
+    /// Server the credential is for
+    pub server_id: String,
+    /// Database name of the type, e.g. "api_key"
+    pub credential_type: String,
+    pub value: String,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub token_type: Option<String>,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+/// How a bulk import handles failing items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Import every valid item and report the rest
+    #[default]
+    BestEffort,
+    /// Import nothing if any item fails
+    AllOrNothing,
+}
+
+/// Outcome of one item of a bulk import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportItemResult {
+    /// Position of the item in the input
+    pub index: usize,
+    /// Why the item was rejected; `None` if it was imported
+    pub error: Option<String>,
+}
+
+/// Outcome of a bulk import, one result per input item in input order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub results: Vec<ImportItemResult>,
+    /// Whether anything was written. False when an all-or-nothing import
+    /// had failures, even for items whose result has no error.
+    pub committed: bool,
+}
+
+impl ImportReport {
+    /// Number of items written.
+    pub fn imported(&self) -> usize {
+        if self.committed {
+            self.results.iter().filter(|r| r.error.is_none()).count()
+        } else {
+            0
+        }
+    }
+
+    /// Items that were rejected.
+    pub fn failures(&self) -> impl Iterator<Item = &ImportItemResult> {
+        self.results.iter().filter(|r| r.error.is_some())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,6 +137,110 @@ impl ConnectionStatus {
     }
 }
 
+/// Lifecycle state of a server connection
+///
+/// Owned by the gateway's `ServerManager`, which announces every change with
+/// [`DomainEvent::StateChanged`]. Unlike [`ConnectionStatus`] it tells a
+/// first connect from a recovery, and a stopped server from a disabled one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// Enabled but not started, or stopped without being disabled
+    #[default]
+    Idle,
+    /// First connect in progress
+    Connecting,
+    /// Connected and ready
+    Connected,
+    /// Connection lost; the process supervisor or SSE retry is bringing it back
+    Reconnecting,
+    /// Waiting for the user to complete OAuth
+    #[serde(rename = "oauth_pending")]
+    OAuthPending,
+    /// Connect failed or recovery gave up
+    Failed,
+    /// Disabled by the user
+    Disabled,
+}
+
+impl ConnectionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Connecting => "connecting",
+            Self::Connected => "connected",
+            Self::Reconnecting => "reconnecting",
+            Self::OAuthPending => "oauth_pending",
+            Self::Failed => "failed",
+            Self::Disabled => "disabled",
+        }
+    }
+
+    /// Check if a connect or recovery is in progress
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Connecting | Self::Reconnecting)
+    }
+}
+
+impl From<ConnectionStatus> for ConnectionState {
+    /// The state a status implies; `Disconnected` maps to `Idle`, since a
+    /// status can't tell whether the server was disabled
+    fn from(status: ConnectionStatus) -> Self {
+        match status {
+            ConnectionStatus::Connected | ConnectionStatus::Refreshing => Self::Connected,
+            ConnectionStatus::Disconnected => Self::Idle,
+            ConnectionStatus::Error => Self::Failed,
+            ConnectionStatus::OAuthRequired | ConnectionStatus::Authenticating => {
+                Self::OAuthPending
+            }
+            ConnectionStatus::Connecting => Self::Connecting,
+        }
+    }
+}
+
+/// State of a server's connect circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Connects are attempted normally
+    #[default]
+    Closed,
+    /// Too many consecutive failures; connects fail fast until the cooldown ends
+    Open,
+    /// Cooldown over; a single trial connect decides whether to close again
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Result of the periodic health checks on a connected HTTP server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerHealth {
+    /// Answering health checks
+    #[default]
+    Healthy,
+    /// Failed too many health checks in a row
+    Unhealthy,
+}
+
+impl ServerHealth {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Unhealthy => "unhealthy",
+        }
+    }
+}
+
 // ============================================================================
 // DOMAIN EVENT ENUM
 // ============================================================================
@@ -254,6 +358,118 @@ pub enum DomainEvent {
         removed: Vec<String>,
     },
 
+    /// A stdio server process started and completed the MCP handshake
+    ///
+    /// Sent alongside `ServerStatusChanged`, with a breakdown of where the
+    /// startup time went so slow starts can be attributed.
+    ServerConnected {
+        space_id: Uuid,
+        server_id: String,
+        /// Wall-clock time from the start of the connect to a completed handshake
+        startup_ms: u64,
+        /// Resolving the command on the shell PATH
+        resolve_ms: u64,
+        /// Preparing the environment and spawning the process
+        spawn_ms: u64,
+        /// From spawn until the server answered `initialize`
+        handshake_ms: u64,
+    },
+
+    /// A server's connection state changed
+    ///
+    /// Emitted once per transition, so `from` always matches the `to` of the
+    /// previous event for the same server.
+    StateChanged {
+        space_id: Uuid,
+        server_id: String,
+        from: ConnectionState,
+        to: ConnectionState,
+    },
+
+    /// A crashed stdio server is being restarted by its supervisor
+    ServerRestarting {
+        space_id: Uuid,
+        server_id: String,
+        /// Restart attempt number since the server was last stable (1-based)
+        attempt: u32,
+        /// Maximum attempts before the supervisor gives up
+        max_restarts: u32,
+        /// Backoff before this attempt, in milliseconds
+        delay_ms: u64,
+    },
+
+    /// A restarted stdio server completed the replayed MCP handshake
+    ServerRecovered { space_id: Uuid, server_id: String },
+
+    /// An HTTP server's SSE stream dropped and is being re-established
+    ServerReconnecting {
+        space_id: Uuid,
+        server_id: String,
+        /// Reconnect attempt number for this outage (1-based)
+        attempt: u32,
+        /// Maximum attempts before giving up
+        max_attempts: u32,
+        /// Backoff before this attempt, in milliseconds
+        delay_ms: u64,
+    },
+
+    /// A server's connect circuit breaker changed state
+    ///
+    /// While `Open` the server is temporarily disabled: connects fail fast
+    /// without spawning or contacting it.
+    ServerCircuitChanged {
+        space_id: Uuid,
+        server_id: String,
+        state: CircuitState,
+        /// Consecutive connect failures that led to this state
+        failures: u32,
+        /// Time until a trial connect is allowed (set when `Open`)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
+
+    /// A connected HTTP server started or stopped answering health checks
+    ServerHealthChanged {
+        space_id: Uuid,
+        server_id: String,
+        health: ServerHealth,
+        /// Consecutive failed checks that led to this state
+        failures: u32,
+        /// Error from the last failed check (set when `Unhealthy`)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+
+    /// An OAuth server rejected its access token and the refresh grant failed,
+    /// so the user has to authorize again
+    ServerAuthExpired {
+        space_id: Uuid,
+        server_id: String,
+        message: String,
+    },
+
+    /// A stdio server's process exited without being asked to
+    ServerExited {
+        space_id: Uuid,
+        server_id: String,
+        /// Exit code, if the process exited normally
+        code: Option<i32>,
+        /// Signal that killed the process (Unix only)
+        signal: Option<i32>,
+        /// Human-readable description, e.g. "exited with code 1"
+        message: String,
+    },
+
+    /// A server kept crashing (stdio) or could not be reconnected (HTTP) and
+    /// will not be retried again
+    ServerFailed {
+        space_id: Uuid,
+        server_id: String,
+        /// Number of restarts / reconnects attempted before giving up
+        restarts: u32,
+        message: String,
+    },
+
     // ════════════════════════════════════════════════════════════════════════
     // FEATURE SETS
     // ════════════════════════════════════════════════════════════════════════
@@ -378,6 +594,16 @@ impl DomainEvent {
             Self::ServerStatusChanged { .. } => "server_status_changed",
             Self::ServerAuthProgress { .. } => "server_auth_progress",
             Self::ServerFeaturesRefreshed { .. } => "server_features_refreshed",
+            Self::ServerConnected { .. } => "server_connected",
+            Self::StateChanged { .. } => "state_changed",
+            Self::ServerRestarting { .. } => "server_restarting",
+            Self::ServerRecovered { .. } => "server_recovered",
+            Self::ServerReconnecting { .. } => "server_reconnecting",
+            Self::ServerCircuitChanged { .. } => "server_circuit_changed",
+            Self::ServerHealthChanged { .. } => "server_health_changed",
+            Self::ServerAuthExpired { .. } => "server_auth_expired",
+            Self::ServerExited { .. } => "server_exited",
+            Self::ServerFailed { .. } => "server_failed",
             Self::FeatureSetCreated { .. } => "feature_set_created",
             Self::FeatureSetUpdated { .. } => "feature_set_updated",
             Self::FeatureSetDeleted { .. } => "feature_set_deleted",
@@ -441,6 +667,16 @@ impl DomainEvent {
             | Self::ServerStatusChanged { space_id, .. }
             | Self::ServerAuthProgress { space_id, .. }
             | Self::ServerFeaturesRefreshed { space_id, .. }
+            | Self::ServerConnected { space_id, .. }
+            | Self::StateChanged { space_id, .. }
+            | Self::ServerRestarting { space_id, .. }
+            | Self::ServerRecovered { space_id, .. }
+            | Self::ServerReconnecting { space_id, .. }
+            | Self::ServerCircuitChanged { space_id, .. }
+            | Self::ServerHealthChanged { space_id, .. }
+            | Self::ServerAuthExpired { space_id, .. }
+            | Self::ServerExited { space_id, .. }
+            | Self::ServerFailed { space_id, .. }
             | Self::FeatureSetCreated { space_id, .. }
             | Self::FeatureSetUpdated { space_id, .. }
             | Self::FeatureSetDeleted { space_id, .. }
@@ -475,6 +711,16 @@ impl DomainEvent {
             | Self::ServerStatusChanged { server_id, .. }
             | Self::ServerAuthProgress { server_id, .. }
             | Self::ServerFeaturesRefreshed { server_id, .. }
+            | Self::ServerConnected { server_id, .. }
+            | Self::StateChanged { server_id, .. }
+            | Self::ServerRestarting { server_id, .. }
+            | Self::ServerRecovered { server_id, .. }
+            | Self::ServerReconnecting { server_id, .. }
+            | Self::ServerCircuitChanged { server_id, .. }
+            | Self::ServerHealthChanged { server_id, .. }
+            | Self::ServerAuthExpired { server_id, .. }
+            | Self::ServerExited { server_id, .. }
+            | Self::ServerFailed { server_id, .. }
             | Self::ToolsChanged { server_id, .. }
             | Self::PromptsChanged { server_id, .. }
             | Self::ResourcesChanged { server_id, .. } => Some(server_id),
@@ -633,4 +879,28 @@ mod tests {
         assert!(ConnectionStatus::Connected.is_terminal());
         assert!(!ConnectionStatus::Connecting.is_terminal());
     }
+
+    #[test]
+    fn test_connection_state() {
+        assert_eq!(
+            ConnectionState::from(ConnectionStatus::Authenticating),
+            ConnectionState::OAuthPending
+        );
+        assert_eq!(
+            ConnectionState::from(ConnectionStatus::Refreshing),
+            ConnectionState::Connected
+        );
+        assert!(ConnectionState::Reconnecting.is_transient());
+
+        let event = DomainEvent::StateChanged {
+            space_id: Uuid::new_v4(),
+            server_id: "github".to_string(),
+            from: ConnectionState::Connecting,
+            to: ConnectionState::OAuthPending,
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"type\":\"state_changed\""));
+        assert!(json.contains("\"to\":\"oauth_pending\""));
+        assert_eq!(event.server_id(), Some("github"));
+    }
 }
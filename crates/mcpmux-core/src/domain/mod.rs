@@ -5,6 +5,7 @@
 //! - Value Objects (ConnectionStatus, FeatureType, etc.)
 //! - Domain Events (DomainEvent enum for event-driven architecture)
 
+mod audit;
 mod client;
 pub mod config;
 mod credential;
@@ -18,9 +19,13 @@ mod server_log;
 mod space;
 
 // Export event types first (ConnectionStatus is defined here)
-pub use event::{ConnectionStatus, DiscoveredCapabilities, DomainEvent, DomainEventEnvelope};
+pub use event::{
+    CircuitState, ConnectionState, ConnectionStatus, DiscoveredCapabilities, DomainEvent,
+    DomainEventEnvelope, ServerHealth,
+};
 
 // Export entities (installed_server re-exports ConnectionStatus from event)
+pub use audit::*;
 pub use client::*;
 pub use config::*;
 pub use credential::*;
@@ -106,6 +106,9 @@ pub enum TransportConfig {
         args: Vec<String>,
         #[serde(default)]
         env: HashMap<String, String>,
+        /// Seconds allowed for start-up and handshake, overriding the default for the command
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        connect_timeout_secs: Option<u64>,
         #[serde(default)]
         metadata: TransportMetadata,
     },
@@ -113,11 +116,42 @@ pub enum TransportConfig {
         url: String,
         #[serde(default)]
         headers: HashMap<String, String>,
+        /// Proxy URL overriding `HTTP(S)_PROXY`, or `"none"` to connect directly
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        proxy: Option<String>,
+        /// Client certificate and CA settings for mutual TLS
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tls: Option<TlsConfig>,
+        /// Seconds allowed for connecting and the MCP handshake
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        connect_timeout_secs: Option<u64>,
+        /// Seconds a request may wait for data from the server; unset means no limit
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_timeout_secs: Option<u64>,
         #[serde(default)]
         metadata: TransportMetadata,
     },
 }
 
+/// TLS settings for an HTTP server.
+///
+/// Each PEM field takes either a file path or the PEM text itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// Client certificate chain, for servers that require mutual TLS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+    /// Private key for `client_cert`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+    /// Extra root CA bundle, for servers with a private CA
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    /// Trust only `ca_cert`, not the built-in root certificates
+    #[serde(default)]
+    pub ca_only: bool,
+}
+
 impl TransportConfig {
     /// Get metadata reference for this transport
     pub fn metadata(&self) -> &TransportMetadata {
@@ -152,6 +186,21 @@ pub struct InputDefinition {
     // Additional helpful metadata for acquiring credentials
     pub obtain_url: Option<String>,
     pub obtain_instructions: Option<String>,
+
+    /// Keep the value out of a stdio server's argv and env; `${input:ID}`
+    /// and `ID_FILE` point at where it was delivered instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensitive: Option<SecretDelivery>,
+}
+
+/// How a sensitive input value reaches a stdio server process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretDelivery {
+    /// A temp file readable only by the user (0600), removed once the process exits
+    File,
+    /// A pipe inherited by the process as `/dev/fd/N` (Unix only; `File` elsewhere)
+    Fd,
 }
 
 fn default_input_type() -> String {
@@ -3,6 +3,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Server log entry (stored as JSON Lines)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,7 +100,8 @@ pub enum LogSource {
     SseEvent,
     /// Connection events
     Connection,
-    /// OAuth flow
+    /// OAuth flow. Older log files spell it `o-auth`.
+    #[serde(rename = "oauth", alias = "o-auth")]
     OAuth,
     /// MCP protocol logging notifications (notifications/message from server)
     Server,
@@ -119,6 +121,21 @@ impl LogSource {
             Self::Server => "server",
         }
     }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "app" => Some(Self::App),
+            "stdout" => Some(Self::Stdout),
+            "stderr" => Some(Self::Stderr),
+            "http-request" => Some(Self::HttpRequest),
+            "http-response" => Some(Self::HttpResponse),
+            "sse-event" => Some(Self::SseEvent),
+            "connection" => Some(Self::Connection),
+            "oauth" => Some(Self::OAuth),
+            "server" => Some(Self::Server),
+            _ => None,
+        }
+    }
 }
 
 /// Configuration for log rotation
@@ -133,8 +150,18 @@ pub struct LogConfig {
     /// Maximum number of rotated files to keep
     pub max_files: usize,
 
+    /// Maximum age of log data; older rotated files are deleted and the
+    /// active file is rotated once it has been open this long (`None` = no limit)
+    pub max_age: Option<Duration>,
+
     /// Whether to compress rotated files
     pub compress: bool,
+
+    /// Buffered bytes per server that trigger a write to disk
+    pub flush_bytes: usize,
+
+    /// Longest time an entry stays buffered before it is written
+    pub flush_interval: Duration,
 }
 
 impl Default for LogConfig {
@@ -143,7 +170,10 @@ impl Default for LogConfig {
             base_dir: PathBuf::from("logs"),
             max_file_size: 10 * 1024 * 1024, // 10MB
             max_files: 30,                   // 30 files
+            max_age: None,
             compress: true,
+            flush_bytes: 64 * 1024,
+            flush_interval: Duration::from_secs(1),
         }
     }
 }
@@ -168,6 +198,50 @@ mod tests {
         assert_eq!(deserialized.message, "Test message");
     }
 
+    #[test]
+    fn test_log_source_round_trips() {
+        let sources = [
+            LogSource::App,
+            LogSource::Stdout,
+            LogSource::Stderr,
+            LogSource::HttpRequest,
+            LogSource::HttpResponse,
+            LogSource::SseEvent,
+            LogSource::Connection,
+            LogSource::OAuth,
+            LogSource::Server,
+        ];
+        for source in sources {
+            let json = serde_json::to_string(&source).unwrap();
+            assert_eq!(json, format!("\"{}\"", source.as_str()));
+            assert_eq!(serde_json::from_str::<LogSource>(&json).unwrap(), source);
+            assert_eq!(LogSource::parse(source.as_str()), Some(source));
+        }
+        // As written by older versions
+        assert_eq!(
+            serde_json::from_str::<LogSource>("\"o-auth\"").unwrap(),
+            LogSource::OAuth
+        );
+    }
+
+    #[test]
+    fn test_stdout_log_parses_alongside_older_entries() {
+        // Lines as written before stdout capture existed, and one after
+        let lines = [
+            r#"{"ts":"2024-01-01T00:00:00Z","lvl":"info","src":"connection","msg":"Connecting"}"#,
+            r#"{"ts":"2024-01-01T00:00:01Z","lvl":"warn","src":"stderr","msg":"deprecated"}"#,
+            r#"{"ts":"2024-01-01T00:00:02Z","lvl":"info","src":"stdout","msg":"Server ready"}"#,
+        ];
+        let sources: Vec<LogSource> = lines
+            .iter()
+            .map(|line| serde_json::from_str::<ServerLog>(line).unwrap().source)
+            .collect();
+        assert_eq!(
+            sources,
+            vec![LogSource::Connection, LogSource::Stderr, LogSource::Stdout]
+        );
+    }
+
     #[test]
     fn test_log_level_ordering() {
         assert!(LogLevel::Trace < LogLevel::Debug);
@@ -1,13 +1,22 @@
 //! Space entity - isolated environment for MCP configuration
 
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 use uuid::Uuid;
 
+use crate::REDACTED;
+
 /// Space represents an isolated environment with its own credentials and server configs.
 ///
 /// Examples: "Work", "Personal", "Client Project"
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Serializes and debug-prints with the values of sensitive env vars masked,
+/// since spaces are handed to the UI and logged.
+#[derive(Clone, Deserialize)]
 pub struct Space {
     /// Unique identifier
     pub id: Uuid,
@@ -32,6 +41,53 @@ pub struct Space {
 
     /// Last update timestamp
     pub updated_at: DateTime<Utc>,
+
+    /// When the space was soft-deleted (only set on spaces from `list_deleted`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+
+    /// Version of the stored row, bumped on every update; updates must pass
+    /// the version they loaded
+    #[serde(default = "initial_version")]
+    pub version: i64,
+
+    /// Tags for grouping spaces ("work", "client-x"), unique ignoring ASCII
+    /// case. Changed with `SpaceRepository::add_tag`/`remove_tag`; `update`
+    /// leaves them alone.
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Environment variables every server in the space starts with; a
+    /// server's own env wins on conflict. Changed with
+    /// `SpaceRepository::set_env`/`remove_env`; `update` leaves them alone.
+    #[serde(default)]
+    pub space_env: HashMap<String, String>,
+
+    /// Names in `space_env` whose values are sensitive, stored encrypted
+    /// like credentials.
+    #[serde(default)]
+    pub sensitive_env: HashSet<String>,
+}
+
+/// How a tag filter combines several tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Spaces with at least one of the tags
+    #[default]
+    Any,
+    /// Spaces with every one of the tags
+    All,
+}
+
+/// Trim a tag, or `None` if nothing is left.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    (!tag.is_empty()).then(|| tag.to_string())
+}
+
+fn initial_version() -> i64 {
+    1
 }
 
 impl Space {
@@ -47,6 +103,11 @@ impl Space {
             sort_order: 0,
             created_at: now,
             updated_at: now,
+            deleted_at: None,
+            version: initial_version(),
+            tags: Vec::new(),
+            space_env: HashMap::new(),
+            sensitive_env: HashSet::new(),
         }
     }
 
@@ -62,11 +123,110 @@ impl Space {
         self
     }
 
+    /// Create a new space with a tag (ignored if empty or already present)
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        if let Some(tag) = normalize_tag(tag) {
+            if !self.has_tag(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self
+    }
+
+    /// Create a new space with an env var for all its servers
+    pub fn with_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.sensitive_env.remove(&name);
+        self.space_env.insert(name, value.into());
+        self
+    }
+
+    /// Create a new space with a sensitive env var for all its servers
+    pub fn with_sensitive_env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let name = name.into();
+        self.sensitive_env.insert(name.clone());
+        self.space_env.insert(name, value.into());
+        self
+    }
+
+    /// Whether the space has `tag`, ignoring ASCII case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+    }
+
+    /// Whether the space's tags satisfy `tags` combined with `mode`.
+    /// An empty `tags` matches no space.
+    pub fn matches_tags(&self, tags: &[String], mode: TagMatch) -> bool {
+        !tags.is_empty()
+            && match mode {
+                TagMatch::Any => tags.iter().any(|t| self.has_tag(t)),
+                TagMatch::All => tags.iter().all(|t| self.has_tag(t)),
+            }
+    }
+
     /// Mark as default space
     pub fn set_default(mut self) -> Self {
         self.is_default = true;
         self
     }
+
+    /// `space_env` with the values of sensitive vars replaced by [`REDACTED`]
+    pub fn masked_env(&self) -> HashMap<&str, &str> {
+        self.space_env
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.sensitive_env.contains(name) {
+                    REDACTED
+                } else {
+                    value.as_str()
+                };
+                (name.as_str(), value)
+            })
+            .collect()
+    }
+}
+
+impl Serialize for Space {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Space", 13)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("icon", &self.icon)?;
+        state.serialize_field("description", &self.description)?;
+        state.serialize_field("is_default", &self.is_default)?;
+        state.serialize_field("sort_order", &self.sort_order)?;
+        state.serialize_field("created_at", &self.created_at)?;
+        state.serialize_field("updated_at", &self.updated_at)?;
+        match &self.deleted_at {
+            Some(deleted_at) => state.serialize_field("deleted_at", deleted_at)?,
+            None => state.skip_field("deleted_at")?,
+        }
+        state.serialize_field("version", &self.version)?;
+        state.serialize_field("tags", &self.tags)?;
+        state.serialize_field("space_env", &self.masked_env())?;
+        state.serialize_field("sensitive_env", &self.sensitive_env)?;
+        state.end()
+    }
+}
+
+impl fmt::Debug for Space {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Space")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("icon", &self.icon)
+            .field("description", &self.description)
+            .field("is_default", &self.is_default)
+            .field("sort_order", &self.sort_order)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("deleted_at", &self.deleted_at)
+            .field("version", &self.version)
+            .field("tags", &self.tags)
+            .field("space_env", &self.masked_env())
+            .field("sensitive_env", &self.sensitive_env)
+            .finish()
+    }
 }
 
 impl Default for Space {
@@ -89,4 +249,38 @@ mod tests {
         assert_eq!(space.icon, Some("💼".to_string()));
         assert!(!space.is_default);
     }
+
+    #[test]
+    fn test_tags() {
+        let space = Space::new("Work")
+            .with_tag("Work")
+            .with_tag(" work ")
+            .with_tag("")
+            .with_tag("client-x");
+        assert_eq!(space.tags, vec!["Work", "client-x"]);
+        assert!(space.has_tag("WORK"));
+
+        let tags = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(space.matches_tags(&tags(&["personal", "work"]), TagMatch::Any));
+        assert!(!space.matches_tags(&tags(&["personal", "work"]), TagMatch::All));
+        assert!(space.matches_tags(&tags(&["CLIENT-X", "work"]), TagMatch::All));
+        assert!(!space.matches_tags(&[], TagMatch::Any));
+    }
+
+    #[test]
+    fn test_sensitive_env_is_masked() {
+        let space = Space::new("Work")
+            .with_env("REGION", "eu-west-1")
+            .with_sensitive_env("API_TOKEN", "tok_secret");
+
+        let json = serde_json::to_value(&space).unwrap();
+        assert!(!json.to_string().contains("tok_secret"));
+        assert_eq!(json["space_env"]["API_TOKEN"], REDACTED);
+        assert_eq!(json["space_env"]["REGION"], "eu-west-1");
+        assert_eq!(json["sensitive_env"][0], "API_TOKEN");
+
+        let debug = format!("{:?}", space);
+        assert!(!debug.contains("tok_secret"));
+        assert!(debug.contains("eu-west-1"));
+    }
 }
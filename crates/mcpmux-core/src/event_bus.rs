@@ -36,11 +36,16 @@
 //!
 //! // Consumers receive asynchronously
 //! while let Ok(event) = ui_receiver.recv().await { ... }
+//!
+//! // Subscribers that only care about one server skip everything else
+//! let filter = EventFilter::new().space(space_id).server("github");
+//! let mut panel_receiver = event_bus.subscribe_filtered(filter);
 //! ```
 
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::{debug, warn};
+use uuid::Uuid;
 
 use crate::DomainEvent;
 
@@ -83,6 +88,11 @@ impl EventBus {
         EventReceiver::new(self.sender.subscribe())
     }
 
+    /// Subscribe to receive only events matching `filter`
+    pub fn subscribe_filtered(&self, filter: EventFilter) -> FilteredEventReceiver {
+        FilteredEventReceiver::new(self.sender.subscribe(), filter)
+    }
+
     /// Get the raw broadcast sender (for compatibility with existing code)
     pub fn raw_sender(&self) -> broadcast::Sender<DomainEvent> {
         self.sender.clone()
@@ -207,6 +217,103 @@ impl EventReceiver {
     }
 }
 
+/// Event Filter - Predicates selecting the events a subscriber cares about
+///
+/// An empty filter matches everything. Each predicate that is set must match;
+/// events without a space or server never match a space or server predicate.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    space_id: Option<Uuid>,
+    server_id: Option<String>,
+    kinds: Option<Vec<&'static str>>,
+}
+
+impl EventFilter {
+    /// Create a filter matching every event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match events scoped to this space
+    pub fn space(mut self, space_id: Uuid) -> Self {
+        self.space_id = Some(space_id);
+        self
+    }
+
+    /// Only match events scoped to this server
+    pub fn server(mut self, server_id: impl Into<String>) -> Self {
+        self.server_id = Some(server_id.into());
+        self
+    }
+
+    /// Only match events of these kinds (see [`DomainEvent::type_name`])
+    pub fn kinds(mut self, kinds: &[&'static str]) -> Self {
+        self.kinds = Some(kinds.to_vec());
+        self
+    }
+
+    /// Check whether an event passes every predicate
+    pub fn matches(&self, event: &DomainEvent) -> bool {
+        if let Some(space_id) = self.space_id {
+            if event.space_id() != Some(space_id) {
+                return false;
+            }
+        }
+        if let Some(server_id) = &self.server_id {
+            if event.server_id() != Some(server_id.as_str()) {
+                return false;
+            }
+        }
+        if let Some(kinds) = &self.kinds {
+            if !kinds.contains(&event.type_name()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Filtered Event Receiver - Receives only events matching an [`EventFilter`]
+///
+/// Wraps a raw broadcast receiver, so it also works with the gateway's
+/// `event_tx` channel. Non-matching events are dropped without returning.
+pub struct FilteredEventReceiver {
+    receiver: EventReceiver,
+    filter: EventFilter,
+}
+
+impl FilteredEventReceiver {
+    /// Wrap a broadcast receiver with a filter
+    pub fn new(receiver: broadcast::Receiver<DomainEvent>, filter: EventFilter) -> Self {
+        Self {
+            receiver: EventReceiver::new(receiver),
+            filter,
+        }
+    }
+
+    /// Receive the next matching event (async)
+    ///
+    /// Returns `None` if the channel is closed.
+    pub async fn recv(&mut self) -> Option<DomainEvent> {
+        loop {
+            let event = self.receiver.recv().await?;
+            if self.filter.matches(&event) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Try to receive a matching event without blocking
+    pub fn try_recv(&mut self) -> Option<DomainEvent> {
+        loop {
+            let event = self.receiver.try_recv()?;
+            if self.filter.matches(&event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
 /// Shared event bus for application-wide use
 ///
 /// Use this when you need a singleton event bus across the application.
@@ -224,7 +331,6 @@ pub fn create_shared_event_bus() -> SharedEventBus {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use uuid::Uuid;
 
     #[tokio::test]
     async fn test_event_bus_basic() {
@@ -287,4 +393,44 @@ mod tests {
         let count = sender.emit(DomainEvent::GatewayStopped);
         assert_eq!(count, 0);
     }
+
+    #[tokio::test]
+    async fn test_filtered_subscriber() {
+        let bus = EventBus::new();
+        let sender = bus.sender();
+        let space_id = Uuid::new_v4();
+        let mut receiver =
+            bus.subscribe_filtered(EventFilter::new().space(space_id).server("github"));
+
+        // Other servers, other spaces and unscoped events are skipped
+        sender.emit(DomainEvent::ServerEnabled {
+            space_id,
+            server_id: "slack".to_string(),
+        });
+        sender.emit(DomainEvent::ServerEnabled {
+            space_id: Uuid::new_v4(),
+            server_id: "github".to_string(),
+        });
+        sender.emit(DomainEvent::GatewayStopped);
+        assert!(receiver.try_recv().is_none());
+
+        sender.emit(DomainEvent::ServerDisabled {
+            space_id,
+            server_id: "github".to_string(),
+        });
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.type_name(), "server_disabled");
+    }
+
+    #[test]
+    fn test_filter_kinds() {
+        let filter = EventFilter::new().kinds(&["tools_changed", "prompts_changed"]);
+        let tools = DomainEvent::ToolsChanged {
+            space_id: Uuid::new_v4(),
+            server_id: "github".to_string(),
+        };
+        assert!(filter.matches(&tools));
+        assert!(!filter.matches(&DomainEvent::GatewayStopped));
+        assert!(EventFilter::new().matches(&DomainEvent::GatewayStopped));
+    }
 }
@@ -31,7 +31,8 @@ pub use application::{
     ServerAppService, SpaceAppService,
 };
 pub use event_bus::{
-    create_shared_event_bus, EventBus, EventReceiver, EventSender, SharedEventBus,
+    create_shared_event_bus, EventBus, EventFilter, EventReceiver, EventSender,
+    FilteredEventReceiver, SharedEventBus,
 };
 
 use std::path::{Path, PathBuf};
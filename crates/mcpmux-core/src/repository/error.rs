@@ -0,0 +1,174 @@
+//! Typed errors for repository operations
+
+use super::StaleWrite;
+
+/// Error returned by repository operations.
+///
+/// Lets callers tell "not there" from "already exists" from "try again"
+/// without string matching. It converts into `anyhow::Error` with `?`, and
+/// any `anyhow::Error` converts back: errors that wrap a [`StorageError`],
+/// [`StaleWrite`] or (with the `sqlite` feature) a `rusqlite::Error` are
+/// classified, everything else becomes [`StorageError::Other`].
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    /// The record to read or change doesn't exist.
+    #[error("{0} not found")]
+    NotFound(String),
+    /// A unique constraint rejected the write. `field` is the constrained
+    /// column as SQLite reports it (`table.column`, comma-separated for
+    /// composite keys).
+    #[error("Unique constraint violated on {field}")]
+    UniqueViolation { field: String },
+    /// The write is based on an outdated version of the record.
+    #[error(transparent)]
+    Stale(#[from] StaleWrite),
+    /// The database is locked by another connection; retrying may succeed.
+    #[error("Database is busy")]
+    Busy,
+    /// The database was opened read-only and the operation writes.
+    #[error("Database is open read-only")]
+    ReadOnly,
+    /// The database file is damaged or not a database.
+    #[error("Database is corrupt: {0}")]
+    Corrupt(String),
+    /// Encrypting or decrypting a field failed (wrong key, tampered data).
+    #[error(transparent)]
+    Crypto(anyhow::Error),
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl StorageError {
+    /// Whether the operation may succeed if retried.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, StorageError::Busy)
+    }
+}
+
+impl From<anyhow::Error> for StorageError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<StorageError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        if let Some(stale) = err.downcast_ref::<StaleWrite>() {
+            return StorageError::Stale(*stale);
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(classified) = err
+            .downcast_ref::<rusqlite::Error>()
+            .and_then(classify_sqlite)
+        {
+            return classified;
+        }
+        StorageError::Other(err)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(err: serde_json::Error) -> Self {
+        StorageError::Other(err.into())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for StorageError {
+    fn from(err: rusqlite::Error) -> Self {
+        classify_sqlite(&err).unwrap_or_else(|| StorageError::Other(err.into()))
+    }
+}
+
+/// Map the SQLite errors callers can act on; `None` for the rest.
+#[cfg(feature = "sqlite")]
+fn classify_sqlite(err: &rusqlite::Error) -> Option<StorageError> {
+    use rusqlite::ErrorCode;
+
+    match err {
+        rusqlite::Error::QueryReturnedNoRows => Some(StorageError::NotFound("Row".to_string())),
+        rusqlite::Error::SqliteFailure(failure, message) => match failure.code {
+            ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked => Some(StorageError::Busy),
+            ErrorCode::ReadOnly => Some(StorageError::ReadOnly),
+            ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase => {
+                Some(StorageError::Corrupt(err.to_string()))
+            }
+            ErrorCode::ConstraintViolation
+                if matches!(
+                    failure.extended_code,
+                    rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE
+                        | rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY
+                ) =>
+            {
+                // SQLite only names the columns in the message:
+                // "UNIQUE constraint failed: spaces.name"
+                let field = message
+                    .as_deref()
+                    .and_then(|m| m.split_once(": "))
+                    .map(|(_, columns)| columns.to_string())
+                    .unwrap_or_default();
+                Some(StorageError::UniqueViolation { field })
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anyhow_round_trip_keeps_classification() {
+        let err: anyhow::Error = StorageError::Busy.into();
+        assert!(matches!(StorageError::from(err), StorageError::Busy));
+
+        let err = anyhow::Error::from(StaleWrite { current_version: 3 }).context("saving");
+        assert!(matches!(
+            StorageError::from(err),
+            StorageError::Stale(StaleWrite { current_version: 3 })
+        ));
+
+        let err = StorageError::from(anyhow::anyhow!("disk on fire"));
+        assert!(matches!(&err, StorageError::Other(_)));
+        assert_eq!(err.to_string(), "disk on fire");
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_errors_are_classified() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (name TEXT PRIMARY KEY, n INTEGER NOT NULL);")
+            .unwrap();
+        conn.execute("INSERT INTO t VALUES ('a', 1)", []).unwrap();
+
+        let err = conn
+            .execute("INSERT INTO t VALUES ('a', 2)", [])
+            .unwrap_err();
+        match StorageError::from(err) {
+            StorageError::UniqueViolation { field } => assert_eq!(field, "t.name"),
+            other => panic!("expected UniqueViolation, got {:?}", other),
+        }
+
+        // Other constraints aren't uniqueness problems
+        let err = conn
+            .execute("INSERT INTO t VALUES ('b', NULL)", [])
+            .unwrap_err();
+        assert!(matches!(StorageError::from(err), StorageError::Other(_)));
+
+        conn.pragma_update(None, "query_only", "ON").unwrap();
+        let err = conn
+            .execute("INSERT INTO t VALUES ('c', 3)", [])
+            .unwrap_err();
+        assert!(matches!(StorageError::from(err), StorageError::ReadOnly));
+
+        let err = conn
+            .query_row("SELECT n FROM t WHERE name = 'z'", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap_err();
+        assert!(matches!(
+            StorageError::from(anyhow::Error::from(err).context("loading")),
+            StorageError::NotFound(_)
+        ));
+    }
+}
@@ -3,33 +3,68 @@
 //! These traits define the interface for data storage without specifying
 //! the implementation (SQLite, in-memory, etc.)
 
+mod error;
+mod page;
+
 use async_trait::async_trait;
 use uuid::Uuid;
 
 use crate::domain::{
-    Client, Credential, CredentialType, FeatureSet, FeatureSetMember, InstalledServer, MemberMode,
-    OutboundOAuthRegistration, ServerFeature, Space,
+    AuditEntry, AuditFilter, Client, Credential, CredentialType, FeatureSet, FeatureSetMember,
+    InstalledServer, MemberMode, OutboundOAuthRegistration, ServerFeature, Space, TagMatch,
 };
 
+pub use error::StorageError;
+pub use page::{Page, PageCursor};
+
 /// Result type for repository operations
-pub type RepoResult<T> = anyhow::Result<T>;
+pub type RepoResult<T> = Result<T, StorageError>;
+
+/// A write based on an outdated version of a record: someone else changed it
+/// since it was loaded. Reload, merge and retry.
+///
+/// Returned as [`StorageError::Stale`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Stale write: the record was changed since it was loaded (now at version {current_version})"
+)]
+pub struct StaleWrite {
+    pub current_version: i64,
+}
+
+/// Whether an upsert created a record or overwrote an existing one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upserted {
+    Inserted,
+    Updated,
+}
 
 /// Space repository trait
 #[async_trait]
 pub trait SpaceRepository: Send + Sync {
-    /// Get all spaces
+    /// Get all spaces (excluding soft-deleted ones)
     async fn list(&self) -> RepoResult<Vec<Space>>;
 
-    /// Get a space by ID
+    /// Get one page of spaces, ordered by ID, and the cursor of the next page
+    /// (`None` on the last page).
+    ///
+    /// The default implementation pages over [`list`](Self::list).
+    async fn list_page(&self, page: &Page) -> RepoResult<(Vec<Space>, Option<PageCursor>)> {
+        page::paginate(self.list().await?, page, |space| vec![space.id.to_string()])
+    }
+
+    /// Get a space by ID (`None` if it was soft-deleted)
     async fn get(&self, id: &Uuid) -> RepoResult<Option<Space>>;
 
     /// Create a new space
     async fn create(&self, space: &Space) -> RepoResult<()>;
 
-    /// Update a space
+    /// Update a space, provided its stored version is still `space.version`
+    /// (fails with [`StaleWrite`] otherwise). The stored version becomes
+    /// `space.version + 1`.
     async fn update(&self, space: &Space) -> RepoResult<()>;
 
-    /// Delete a space
+    /// Soft-delete a space and its credentials; see [`restore`](Self::restore)
     async fn delete(&self, id: &Uuid) -> RepoResult<()>;
 
     /// Get the default space
@@ -37,6 +72,48 @@ pub trait SpaceRepository: Send + Sync {
 
     /// Set a space as default
     async fn set_default(&self, id: &Uuid) -> RepoResult<()>;
+
+    /// Undo a soft delete, restoring the credentials deleted with the space
+    async fn restore(&self, id: &Uuid) -> RepoResult<()>;
+
+    /// Get soft-deleted spaces, most recently deleted first
+    async fn list_deleted(&self) -> RepoResult<Vec<Space>>;
+
+    /// Permanently remove spaces soft-deleted more than `age` ago, with
+    /// everything in them. Returns the number of spaces removed.
+    async fn purge_older_than(&self, age: std::time::Duration) -> RepoResult<usize>;
+
+    /// Tag a space. Returns false if it already had the tag (ignoring case).
+    async fn add_tag(&self, id: &Uuid, tag: &str) -> RepoResult<bool>;
+
+    /// Untag a space (ignoring case). Returns false if it didn't have the tag.
+    async fn remove_tag(&self, id: &Uuid, tag: &str) -> RepoResult<bool>;
+
+    /// Set an env var inherited by every server in a space, replacing any
+    /// previous value. `sensitive` values are stored encrypted.
+    async fn set_env(&self, id: &Uuid, name: &str, value: &str, sensitive: bool) -> RepoResult<()>;
+
+    /// Remove a space env var. Returns false if it wasn't set.
+    async fn remove_env(&self, id: &Uuid, name: &str) -> RepoResult<bool>;
+
+    /// Get one page of the spaces matching `tags` combined with `mode`,
+    /// ordered by ID, and the cursor of the next page.
+    ///
+    /// The default implementation pages over [`list`](Self::list).
+    async fn list_spaces_by_tag(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+        page: &Page,
+    ) -> RepoResult<(Vec<Space>, Option<PageCursor>)> {
+        let spaces = self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|space| space.matches_tags(tags, mode))
+            .collect();
+        page::paginate(spaces, page, |space| vec![space.id.to_string()])
+    }
 }
 
 /// InstalledServer repository trait
@@ -282,12 +359,52 @@ pub trait CredentialRepository: Send + Sync {
         credential_type: &CredentialType,
     ) -> RepoResult<Option<Credential>>;
 
+    /// Like [`get`](Self::get), but treats an expired credential as missing.
+    async fn get_unexpired(
+        &self,
+        space_id: &Uuid,
+        server_id: &str,
+        credential_type: &CredentialType,
+    ) -> RepoResult<Option<Credential>> {
+        Ok(self
+            .get(space_id, server_id, credential_type)
+            .await?
+            .filter(|cred| !cred.is_expired()))
+    }
+
     /// Get all credentials for a (space, server) combination
     async fn get_all(&self, space_id: &Uuid, server_id: &str) -> RepoResult<Vec<Credential>>;
 
     /// Save a credential (upsert by space_id + server_id + credential_type)
+    ///
+    /// A credential with a non-zero `version` only overwrites a stored row
+    /// of that version, failing with [`StaleWrite`] otherwise.
     async fn save(&self, credential: &Credential) -> RepoResult<()>;
 
+    /// Save a credential whatever the stored version, returning whether a row
+    /// was inserted or overwritten.
+    ///
+    /// For callers that don't know or care whether the credential exists yet.
+    /// The default implementation checks with [`get`](Self::get) first, so it
+    /// isn't atomic; implementations should override it with a single write.
+    async fn upsert(&self, credential: &Credential) -> RepoResult<Upserted> {
+        let existing = self
+            .get(
+                &credential.space_id,
+                &credential.server_id,
+                &credential.credential_type,
+            )
+            .await?;
+        let mut credential = credential.clone();
+        credential.version = 0;
+        self.save(&credential).await?;
+        Ok(if existing.is_some() {
+            Upserted::Updated
+        } else {
+            Upserted::Inserted
+        })
+    }
+
     /// Delete a specific credential by type
     async fn delete(
         &self,
@@ -303,8 +420,48 @@ pub trait CredentialRepository: Send + Sync {
     /// Returns true if tokens were cleared
     async fn clear_tokens(&self, space_id: &Uuid, server_id: &str) -> RepoResult<bool>;
 
+    /// Delete every expired credential in one pass, returning how many were removed.
+    ///
+    /// An expired access token is kept while its server still has an
+    /// unexpired refresh token, since the refresh flow needs both rows.
+    async fn sweep_expired(&self) -> RepoResult<usize>;
+
     /// List all credentials for a space
     async fn list_for_space(&self, space_id: &Uuid) -> RepoResult<Vec<Credential>>;
+
+    /// Get one page of a space's credentials, ordered by (server, type), and
+    /// the cursor of the next page (`None` on the last page).
+    ///
+    /// The default implementation pages over [`list_for_space`](Self::list_for_space).
+    async fn list_for_space_page(
+        &self,
+        space_id: &Uuid,
+        page: &Page,
+    ) -> RepoResult<(Vec<Credential>, Option<PageCursor>)> {
+        page::paginate(self.list_for_space(space_id).await?, page, |cred| {
+            vec![
+                cred.server_id.clone(),
+                cred.credential_type.as_str().to_string(),
+            ]
+        })
+    }
+}
+
+/// Audit log repository trait
+///
+/// Append-only: there is deliberately no way to update or delete entries.
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// Append an entry
+    async fn record(&self, entry: &AuditEntry) -> RepoResult<()>;
+
+    /// Get one page of entries matching `filter`, newest first, and the
+    /// cursor of the next page (`None` on the last page)
+    async fn list_audit(
+        &self,
+        filter: &AuditFilter,
+        page: &Page,
+    ) -> RepoResult<(Vec<AuditEntry>, Option<PageCursor>)>;
 }
 
 /// Outbound OAuth Client repository (OUTBOUND)
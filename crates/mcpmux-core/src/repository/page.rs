@@ -0,0 +1,149 @@
+//! Cursor-based pagination for repository listings
+//!
+//! Listings are keyset-paginated: rows are ordered by a unique key, and a
+//! [`PageCursor`] holds the key of the last row returned. The next page starts
+//! strictly after that key, so rows inserted or deleted between requests never
+//! make a page skip or repeat a row that existed throughout.
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Opaque position in a paginated listing.
+///
+/// Obtained from a previous page and passed back unchanged; the encoding is
+/// private to the repository that produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PageCursor(String);
+
+impl PageCursor {
+    /// Encode a row's sort key. For repository implementations.
+    pub fn from_key<S: AsRef<str>>(key: &[S]) -> Self {
+        let parts: Vec<&str> = key.iter().map(AsRef::as_ref).collect();
+        let json = serde_json::to_vec(&parts).expect("string list always serializes");
+        Self(URL_SAFE_NO_PAD.encode(json))
+    }
+
+    /// Decode the sort key. For repository implementations.
+    pub fn key(&self) -> Result<Vec<String>> {
+        URL_SAFE_NO_PAD
+            .decode(&self.0)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| anyhow!("Invalid page cursor"))
+    }
+}
+
+/// Which page of a listing to return.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Page {
+    /// Start after this cursor; `None` for the first page.
+    pub after: Option<PageCursor>,
+    /// Maximum rows to return (at least 1).
+    pub limit: usize,
+}
+
+impl Page {
+    /// The first `limit` rows.
+    pub fn first(limit: usize) -> Self {
+        Self { after: None, limit }
+    }
+
+    /// The `limit` rows after `cursor`.
+    pub fn after(cursor: PageCursor, limit: usize) -> Self {
+        Self {
+            after: Some(cursor),
+            limit,
+        }
+    }
+
+    /// The limit, with 0 treated as 1.
+    pub fn effective_limit(&self) -> usize {
+        self.limit.max(1)
+    }
+}
+
+/// Paginate an in-memory listing by `key`, the way the SQL implementations do.
+///
+/// Used by the default trait methods so repositories that only implement
+/// `list` still support paging.
+pub(crate) fn paginate<T>(
+    mut items: Vec<T>,
+    page: &Page,
+    key: impl Fn(&T) -> Vec<String>,
+) -> super::RepoResult<(Vec<T>, Option<PageCursor>)> {
+    let after = page.after.as_ref().map(PageCursor::key).transpose()?;
+    items.sort_by_key(|item| key(item));
+    let limit = page.effective_limit();
+
+    let mut rest = items
+        .into_iter()
+        .filter(|item| after.as_ref().is_none_or(|after| key(item) > *after));
+    let rows: Vec<T> = rest.by_ref().take(limit).collect();
+    let next = match (rest.next(), rows.last()) {
+        (Some(_), Some(last)) => Some(PageCursor::from_key(&key(last))),
+        _ => None,
+    };
+    Ok((rows, next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = PageCursor::from_key(&["github", "api_key"]);
+        assert_eq!(cursor.key().unwrap(), vec!["github", "api_key"]);
+        assert!(!cursor.0.contains("github"));
+
+        let json = serde_json::to_string(&cursor).unwrap();
+        let parsed: PageCursor = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, cursor);
+
+        assert!(PageCursor("not a cursor!".to_string()).key().is_err());
+    }
+
+    #[test]
+    fn test_paginate_walks_all_rows_once() {
+        let items: Vec<u32> = vec![5, 1, 4, 2, 3];
+        let key = |n: &u32| vec![n.to_string()];
+
+        let (first, next) = paginate(items.clone(), &Page::first(2), key).unwrap();
+        assert_eq!(first, vec![1, 2]);
+        let (second, next) = paginate(items.clone(), &Page::after(next.unwrap(), 2), key).unwrap();
+        assert_eq!(second, vec![3, 4]);
+        let (third, next) = paginate(items, &Page::after(next.unwrap(), 2), key).unwrap();
+        assert_eq!(third, vec![5]);
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn test_paginate_stable_across_inserts() {
+        let key = |n: &u32| vec![n.to_string()];
+        let (first, next) = paginate(vec![1, 3, 5, 7], &Page::first(2), key).unwrap();
+        assert_eq!(first, vec![1, 3]);
+
+        // Rows inserted before and after the cursor
+        let (second, _) = paginate(
+            vec![0, 1, 2, 3, 4, 5, 7],
+            &Page::after(next.unwrap(), 2),
+            key,
+        )
+        .unwrap();
+        assert_eq!(second, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_exact_fit_has_no_next_page() {
+        let key = |n: &u32| vec![n.to_string()];
+        let (rows, next) = paginate(vec![1, 2], &Page::first(2), key).unwrap();
+        assert_eq!(rows, vec![1, 2]);
+        assert!(next.is_none());
+
+        let (rows, _) = paginate(vec![1, 2], &Page::first(0), key).unwrap();
+        assert_eq!(rows, vec![1]);
+    }
+}
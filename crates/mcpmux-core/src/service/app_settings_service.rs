@@ -130,17 +130,17 @@ impl AppSettingsService {
         let serialized = serde_json::to_string(value)?;
         // Remove quotes for simple string values to keep storage clean
         let clean_value = serialized.trim_matches('"');
-        self.repository.set(key, clean_value).await
+        Ok(self.repository.set(key, clean_value).await?)
     }
 
     /// Set a raw string value.
     pub async fn set_string(&self, key: &str, value: &str) -> anyhow::Result<()> {
-        self.repository.set(key, value).await
+        Ok(self.repository.set(key, value).await?)
     }
 
     /// Delete a setting.
     pub async fn delete(&self, key: &str) -> anyhow::Result<()> {
-        self.repository.delete(key).await
+        Ok(self.repository.delete(key).await?)
     }
 
     // =========================================================================
@@ -160,15 +160,16 @@ impl AppSettingsService {
     /// Set the gateway port.
     pub async fn set_gateway_port(&self, port: u16) -> anyhow::Result<()> {
         info!("[Settings] Setting gateway port to {}", port);
-        self.repository
+        Ok(self
+            .repository
             .set(keys::gateway::PORT, &port.to_string())
-            .await
+            .await?)
     }
 
     /// Clear the gateway port (revert to default/dynamic).
     pub async fn clear_gateway_port(&self) -> anyhow::Result<()> {
         info!("[Settings] Clearing gateway port setting");
-        self.repository.delete(keys::gateway::PORT).await
+        Ok(self.repository.delete(keys::gateway::PORT).await?)
     }
 
     /// Get whether gateway should auto-start (default: true).
@@ -182,12 +183,13 @@ impl AppSettingsService {
     /// Set gateway auto-start preference.
     pub async fn set_gateway_auto_start(&self, auto_start: bool) -> anyhow::Result<()> {
         info!("[Settings] Setting gateway auto_start to {}", auto_start);
-        self.repository
+        Ok(self
+            .repository
             .set(
                 keys::gateway::AUTO_START,
                 if auto_start { "true" } else { "false" },
             )
-            .await
+            .await?)
     }
 
     // =========================================================================
@@ -207,9 +209,10 @@ impl AppSettingsService {
     /// Set the preferred OAuth callback port.
     pub async fn set_oauth_callback_port(&self, port: u16) -> anyhow::Result<()> {
         info!("[Settings] Setting OAuth callback port to {}", port);
-        self.repository
+        Ok(self
+            .repository
             .set(keys::oauth::CALLBACK_PORT, &port.to_string())
-            .await
+            .await?)
     }
 
     // =========================================================================
@@ -226,7 +229,7 @@ impl AppSettingsService {
     /// Set the UI theme preference.
     pub async fn set_theme(&self, theme: &str) -> anyhow::Result<()> {
         info!("[Settings] Setting theme to {}", theme);
-        self.repository.set(keys::ui::THEME, theme).await
+        Ok(self.repository.set(keys::ui::THEME, theme).await?)
     }
 
     /// Get window state (position, size, maximized).
@@ -239,7 +242,7 @@ impl AppSettingsService {
     /// Set window state.
     pub async fn set_window_state<T: Serialize>(&self, state: &T) -> anyhow::Result<()> {
         let json = serde_json::to_string(state)?;
-        self.repository.set(keys::ui::WINDOW_STATE, &json).await
+        Ok(self.repository.set(keys::ui::WINDOW_STATE, &json).await?)
     }
 
     // =========================================================================
@@ -259,9 +262,10 @@ impl AppSettingsService {
     /// Set the log retention period in days.
     pub async fn set_log_retention_days(&self, days: u32) -> anyhow::Result<()> {
         info!("[Settings] Setting log retention to {} days", days);
-        self.repository
+        Ok(self
+            .repository
             .set(keys::logs::RETENTION_DAYS, &days.to_string())
-            .await
+            .await?)
     }
 
     // =========================================================================
@@ -270,18 +274,19 @@ impl AppSettingsService {
 
     /// List all settings (for debugging/export).
     pub async fn list_all(&self) -> anyhow::Result<Vec<(String, String)>> {
-        self.repository.list().await
+        Ok(self.repository.list().await?)
     }
 
     /// List settings by namespace prefix.
     pub async fn list_by_prefix(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>> {
-        self.repository.list_by_prefix(prefix).await
+        Ok(self.repository.list_by_prefix(prefix).await?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::repository::RepoResult;
     use async_trait::async_trait;
     use std::collections::HashMap;
     use tokio::sync::RwLock;
@@ -301,11 +306,11 @@ mod tests {
 
     #[async_trait]
     impl AppSettingsRepository for InMemorySettingsRepository {
-        async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        async fn get(&self, key: &str) -> RepoResult<Option<String>> {
             Ok(self.data.read().await.get(key).cloned())
         }
 
-        async fn set(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        async fn set(&self, key: &str, value: &str) -> RepoResult<()> {
             self.data
                 .write()
                 .await
@@ -313,19 +318,19 @@ mod tests {
             Ok(())
         }
 
-        async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        async fn delete(&self, key: &str) -> RepoResult<()> {
             self.data.write().await.remove(key);
             Ok(())
         }
 
-        async fn list(&self) -> anyhow::Result<Vec<(String, String)>> {
+        async fn list(&self) -> RepoResult<Vec<(String, String)>> {
             let data = self.data.read().await;
             let mut items: Vec<_> = data.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
             items.sort_by(|a, b| a.0.cmp(&b.0));
             Ok(items)
         }
 
-        async fn list_by_prefix(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>> {
+        async fn list_by_prefix(&self, prefix: &str) -> RepoResult<Vec<(String, String)>> {
             let data = self.data.read().await;
             let mut items: Vec<_> = data
                 .iter()
@@ -134,9 +134,10 @@ impl ClientService {
         client_id: &Uuid,
         space_id: &str,
     ) -> Result<Vec<String>> {
-        self.client_repository
+        Ok(self
+            .client_repository
             .get_grants_for_space(client_id, space_id)
-            .await
+            .await?)
     }
 
     /// Get effective feature set IDs for a client in a space.
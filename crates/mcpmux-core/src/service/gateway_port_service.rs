@@ -224,6 +224,7 @@ impl GatewayPortService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::repository::RepoResult;
     use async_trait::async_trait;
     use std::collections::HashMap;
     use tokio::sync::RwLock;
@@ -242,21 +243,21 @@ mod tests {
 
     #[async_trait]
     impl AppSettingsRepository for InMemorySettings {
-        async fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        async fn get(&self, key: &str) -> RepoResult<Option<String>> {
             Ok(self.data.read().await.get(key).cloned())
         }
-        async fn set(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        async fn set(&self, key: &str, value: &str) -> RepoResult<()> {
             self.data
                 .write()
                 .await
                 .insert(key.to_string(), value.to_string());
             Ok(())
         }
-        async fn delete(&self, key: &str) -> anyhow::Result<()> {
+        async fn delete(&self, key: &str) -> RepoResult<()> {
             self.data.write().await.remove(key);
             Ok(())
         }
-        async fn list(&self) -> anyhow::Result<Vec<(String, String)>> {
+        async fn list(&self) -> RepoResult<Vec<(String, String)>> {
             Ok(self
                 .data
                 .read()
@@ -265,7 +266,7 @@ mod tests {
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect())
         }
-        async fn list_by_prefix(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>> {
+        async fn list_by_prefix(&self, prefix: &str) -> RepoResult<Vec<(String, String)>> {
             Ok(self
                 .data
                 .read()
@@ -8,6 +8,7 @@ mod client_install;
 mod client_service;
 mod config_export;
 pub mod gateway_port_service;
+mod log_redactor;
 mod permission_service;
 mod registry_api_client;
 mod server_discovery;
@@ -23,6 +24,7 @@ pub use gateway_port_service::{
     allocate_dynamic_port, is_port_available, GatewayPortService, PortAllocationError,
     PortResolution, DEFAULT_GATEWAY_PORT,
 };
+pub use log_redactor::{known_secret_values, LogRedactor, MIN_KNOWN_SECRET_LEN, REDACTED};
 pub use permission_service::*;
 pub use registry_api_client::*;
 pub use server_discovery::*;
@@ -1,23 +1,118 @@
 //! Server log manager - file-based logging per server
-
-use crate::{LogConfig, LogLevel, ServerLog};
+//!
+//! Each server writes JSON lines to `current.log`. When that file exceeds
+//! `max_file_size` (or has been open longer than `max_age`) it is renamed to a
+//! timestamped file, optionally gzipped, and rotated files beyond `max_files`
+//! or older than `max_age` are deleted. [`ServerLogManager::read_logs`] reads
+//! across the active and rotated files.
+//!
+//! Reading tolerates damage from a crash mid-write: invalid UTF-8 becomes
+//! U+FFFD, a record that a cut-off line ran into is recovered, and lines that
+//! still don't parse are skipped and counted in [`LogReadResult::skipped`]
+//! rather than failing the read.
+//!
+//! Every entry passes through a [`LogRedactor`] on the way in, masking the
+//! server's registered secret values and common token patterns.
+//!
+//! Live views use [`ServerLogManager::subscribe`] to receive entries as they
+//! are appended, after a [`ServerLogManager::read_logs`] backfill.
+//!
+//! [`ServerLogManager::append`] never waits for the disk: entries go into a
+//! bounded queue that a background task writes out in batches, so a slow disk
+//! can't stall the stderr readers feeding it. When the queue is full the
+//! oldest entries are dropped and counted in a warning.
+//!
+//! The writer buffers each server's lines and writes them out once
+//! `LogConfig::flush_bytes` have collected or the oldest has waited
+//! `LogConfig::flush_interval`. [`ServerLogManager::flush`] writes everything
+//! out immediately; reads and searches call it first, so they always include
+//! the latest entries. Dropping the manager writes out what is left.
+
+use super::log_redactor::{known_secret_values, LogRedactor};
+use crate::{LogConfig, LogLevel, LogSource, ServerLog};
 use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use std::collections::HashMap;
-use std::io::Write;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::fs::{File, OpenOptions};
 use tokio::io::AsyncWriteExt;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::{broadcast, Mutex, Notify, RwLock};
 use tracing::{debug, info, warn};
 
+/// Capacity of each server's live log channel. Slow subscribers that fall
+/// further behind get `RecvError::Lagged` and skip ahead.
+const SUBSCRIBER_BUFFER: usize = 1024;
+
+/// Default number of entries waiting for the disk before the oldest are dropped.
+pub const DEFAULT_APPEND_QUEUE_CAPACITY: usize = 8192;
+
+/// Open log writers per `space_id/server_id`
+type WriterMap = Arc<RwLock<HashMap<String, Arc<Mutex<ServerLogWriter>>>>>;
+
+/// Entries read by [`ServerLogManager::read_logs`]
+#[derive(Debug, Clone, Default)]
+pub struct LogReadResult {
+    /// Entries in chronological order
+    pub entries: Vec<ServerLog>,
+    /// Damaged lines passed over while reading
+    pub skipped: usize,
+}
+
+/// Options for [`ServerLogManager::search`]
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression instead of a plain substring
+    pub regex: bool,
+    /// Match case exactly (default: case-insensitive)
+    pub case_sensitive: bool,
+    /// Entries to include before and after each match (within the same file)
+    pub context_lines: usize,
+    /// Stop after this many matches
+    pub max_results: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            case_sensitive: false,
+            context_lines: 2,
+            max_results: 100,
+        }
+    }
+}
+
+/// A log entry matching a search, with its surrounding entries
+#[derive(Debug, Clone)]
+pub struct LogSearchMatch {
+    pub entry: ServerLog,
+    /// Preceding entries, oldest first
+    pub before: Vec<ServerLog>,
+    /// Following entries, oldest first
+    pub after: Vec<ServerLog>,
+}
+
 /// Server log manager
 pub struct ServerLogManager {
     config: LogConfig,
-    writers: Arc<RwLock<HashMap<String, Arc<Mutex<ServerLogWriter>>>>>,
+    writers: WriterMap,
+    /// Entries waiting for the background writer
+    queue: Arc<AppendQueue>,
+    queue_capacity: usize,
+    /// Whether the background writer has been spawned (on the first append)
+    writer_started: AtomicBool,
+    redactor: LogRedactor,
+    /// Known secret values per `space_id/server_id`
+    secrets: RwLock<HashMap<String, Arc<Vec<String>>>>,
+    /// Live subscribers per `space_id/server_id`
+    subscribers: std::sync::Mutex<HashMap<String, broadcast::Sender<ServerLog>>>,
 }
 
 impl ServerLogManager {
@@ -26,6 +121,42 @@ impl ServerLogManager {
         Self {
             config,
             writers: Arc::new(RwLock::new(HashMap::new())),
+            queue: Arc::new(AppendQueue::default()),
+            queue_capacity: DEFAULT_APPEND_QUEUE_CAPACITY,
+            writer_started: AtomicBool::new(false),
+            redactor: LogRedactor::default(),
+            secrets: RwLock::new(HashMap::new()),
+            subscribers: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the default secret redactor
+    pub fn with_redactor(mut self, redactor: LogRedactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Set how many entries may wait for the disk before the oldest are dropped
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity.max(1);
+        self
+    }
+
+    /// Register values (e.g. a server's configured env var values) that must
+    /// never appear in that server's logs. Replaces any previous registration.
+    pub async fn register_secrets(
+        &self,
+        space_id: &str,
+        server_id: &str,
+        values: impl IntoIterator<Item = String>,
+    ) {
+        let key = format!("{}/{}", space_id, server_id);
+        let values = known_secret_values(values);
+        let mut secrets = self.secrets.write().await;
+        if values.is_empty() {
+            secrets.remove(&key);
+        } else {
+            secrets.insert(key, Arc::new(values));
         }
     }
 
@@ -37,7 +168,8 @@ impl ServerLogManager {
 
     /// Get or create a log writer for a server
     async fn get_writer(
-        &self,
+        writers: &WriterMap,
+        config: &LogConfig,
         space_id: &str,
         server_id: &str,
     ) -> Result<Arc<Mutex<ServerLogWriter>>> {
@@ -45,14 +177,14 @@ impl ServerLogManager {
 
         // Fast path: writer exists
         {
-            let readers = self.writers.read().await;
+            let readers = writers.read().await;
             if let Some(writer) = readers.get(&key) {
                 return Ok(writer.clone());
             }
         }
 
         // Slow path: create new writer
-        let mut writers = self.writers.write().await;
+        let mut writers = writers.write().await;
 
         // Double-check (another thread might have created it)
         if let Some(writer) = writers.get(&key) {
@@ -61,68 +193,249 @@ impl ServerLogManager {
 
         // Create log directory with sanitized server ID
         let safe_server_id = Self::sanitize_server_id(server_id);
-        let log_dir = self.config.base_dir.join(space_id).join(safe_server_id);
+        let log_dir = config.base_dir.join(space_id).join(safe_server_id);
         let _: () = tokio::fs::create_dir_all(&log_dir)
             .await
             .context("Failed to create log directory")?;
 
-        let writer = Arc::new(Mutex::new(
-            ServerLogWriter::new(log_dir, &self.config).await?,
-        ));
+        let writer = Arc::new(Mutex::new(ServerLogWriter::new(log_dir, config).await?));
 
         writers.insert(key, writer.clone());
         Ok(writer)
     }
 
+    /// Subscribe to entries appended for a server from now on.
+    ///
+    /// Earlier entries are not replayed; call [`read_logs`](Self::read_logs)
+    /// for the initial view.
+    pub fn subscribe(&self, space_id: &str, server_id: &str) -> broadcast::Receiver<ServerLog> {
+        let key = format!("{}/{}", space_id, server_id);
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        subscribers
+            .entry(key)
+            .or_insert_with(|| broadcast::channel(SUBSCRIBER_BUFFER).0)
+            .subscribe()
+    }
+
+    /// Push an entry to live subscribers, dropping channels nobody listens to.
+    fn publish(&self, key: &str, log: &ServerLog) {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(tx) = subscribers.get(key) {
+            if tx.receiver_count() == 0 {
+                subscribers.remove(key);
+            } else {
+                let _ = tx.send(log.clone());
+            }
+        }
+    }
+
     /// Append a log entry
-    pub async fn append(&self, space_id: &str, server_id: &str, log: ServerLog) -> Result<()> {
-        let writer = self.get_writer(space_id, server_id).await?;
-        let mut w = writer.lock().await;
-        w.write(log).await
+    ///
+    /// Returns once the entry is queued and published to subscribers; the
+    /// background writer puts it on disk. Write failures are logged there.
+    pub async fn append(&self, space_id: &str, server_id: &str, mut log: ServerLog) -> Result<()> {
+        let key = format!("{}/{}", space_id, server_id);
+        let known = self
+            .secrets
+            .read()
+            .await
+            .get(&key)
+            .cloned()
+            .unwrap_or_default();
+        self.redactor.redact_log(&mut log, &known);
+
+        self.start_writer();
+        {
+            let mut pending = self.queue.lock();
+            // Publish while holding the queue lock so subscribers see entries in file order
+            self.publish(&key, &log);
+            if pending.entries.len() >= self.queue_capacity {
+                pending.entries.pop_front();
+                pending.dropped += 1;
+            }
+            pending.entries.push_back(QueuedLog {
+                space_id: space_id.to_string(),
+                server_id: server_id.to_string(),
+                log,
+            });
+        }
+        self.queue.wake.notify_one();
+        Ok(())
+    }
+
+    /// Spawn the background writer if it isn't running yet.
+    fn start_writer(&self) {
+        if !self.writer_started.swap(true, Ordering::AcqRel) {
+            tokio::spawn(write_queued(
+                self.queue.clone(),
+                self.writers.clone(),
+                self.config.clone(),
+            ));
+        }
+    }
+
+    /// Write every entry appended so far to disk.
+    pub async fn flush(&self) {
+        loop {
+            let idle = self.queue.idle.notified();
+            tokio::pin!(idle);
+            // Register before checking, so a notification in between isn't missed
+            idle.as_mut().enable();
+            {
+                let pending = self.queue.lock();
+                if pending.entries.is_empty() && !pending.writing {
+                    break;
+                }
+            }
+            idle.await;
+        }
+        flush_writers(&self.writers, true).await;
     }
 
     /// Read recent logs (tail behavior)
+    ///
+    /// Starts from `current.log` and continues into rotated files (newest
+    /// first, including compressed ones) until `limit` entries are found.
+    /// Entries below `min_level` or from sources not in `sources` are skipped
+    /// before counting towards `limit`. Damaged lines are skipped and counted.
     pub async fn read_logs(
         &self,
         space_id: &str,
         server_id: &str,
         limit: usize,
-        level_filter: Option<LogLevel>,
-    ) -> Result<Vec<ServerLog>> {
+        min_level: Option<LogLevel>,
+        sources: Option<&[LogSource]>,
+    ) -> Result<LogReadResult> {
+        self.flush().await;
         let safe_server_id = Self::sanitize_server_id(server_id);
         let log_dir = self.config.base_dir.join(space_id).join(safe_server_id);
-        let current_log = log_dir.join("current.log");
 
-        if !current_log.exists() {
-            return Ok(vec![]);
+        if !log_dir.exists() {
+            return Ok(LogReadResult::default());
         }
 
-        // Read file and parse JSON lines
-        let content: String = tokio::fs::read_to_string(&current_log).await?;
-        let mut logs: Vec<ServerLog> = content
-            .lines()
-            .rev() // Start from end (most recent)
-            .take(limit * 2) // Take more in case of filtering
-            .filter_map(|line| {
-                serde_json::from_str(line)
-                    .map_err(|e| {
-                        debug!("Failed to parse log line: {}", e);
-                        e
-                    })
-                    .ok()
-            })
-            .filter(|log: &ServerLog| level_filter.is_none_or(|lvl| log.level >= lvl))
-            .take(limit)
-            .collect();
+        let mut logs = Vec::new();
+        let mut skipped = 0;
+        'files: for path in log_files_newest_first(&log_dir).await? {
+            let content = match read_log_file(&path).await {
+                Ok(content) => content,
+                Err(e) => {
+                    // Rotated files can vanish (compression, retention) while we list them
+                    debug!("Skipping unreadable log file {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            // Start from end (most recent)
+            for line in content.lines().rev() {
+                let log = match parse_log_line(line) {
+                    LogLine::Entry(log) => log,
+                    LogLine::Blank => continue,
+                    LogLine::Damaged => {
+                        debug!("Skipping damaged log line in {:?}", path);
+                        skipped += 1;
+                        continue;
+                    }
+                };
+                if min_level.is_none_or(|lvl| log.level >= lvl)
+                    && sources.is_none_or(|sources| sources.contains(&log.source))
+                {
+                    logs.push(log);
+                    if logs.len() >= limit {
+                        break 'files;
+                    }
+                }
+            }
+        }
 
+        if skipped > 0 {
+            warn!(
+                "Skipped {} damaged lines reading logs of {}/{}",
+                skipped, space_id, server_id
+            );
+        }
         logs.reverse(); // Return in chronological order
-        Ok(logs)
+        Ok(LogReadResult {
+            entries: logs,
+            skipped,
+        })
+    }
+
+    /// Search a server's logs, including rotated files.
+    ///
+    /// Matches the query against each entry's message and metadata. Files are
+    /// scanned newest first, one at a time, until `max_results` matches are
+    /// found; the matches are returned in chronological order.
+    pub async fn search(
+        &self,
+        space_id: &str,
+        server_id: &str,
+        query: &str,
+        options: SearchOptions,
+    ) -> Result<Vec<LogSearchMatch>> {
+        let pattern = if options.regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+        let matcher = RegexBuilder::new(&pattern)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .with_context(|| format!("Invalid search pattern: {}", query))?;
+        self.flush().await;
+
+        let safe_server_id = Self::sanitize_server_id(server_id);
+        let log_dir = self.config.base_dir.join(space_id).join(safe_server_id);
+        if !log_dir.exists() || options.max_results == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut matches = Vec::new();
+        for path in log_files_newest_first(&log_dir).await? {
+            let content = match read_log_file(&path).await {
+                Ok(content) => content,
+                Err(e) => {
+                    debug!("Skipping unreadable log file {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let entries: Vec<ServerLog> = content
+                .lines()
+                .filter_map(|line| match parse_log_line(line) {
+                    LogLine::Entry(log) => Some(log),
+                    LogLine::Blank | LogLine::Damaged => None,
+                })
+                .collect();
+
+            for (i, entry) in entries.iter().enumerate().rev() {
+                if !log_matches(&matcher, entry) {
+                    continue;
+                }
+                let start = i.saturating_sub(options.context_lines);
+                let end = (i + 1 + options.context_lines).min(entries.len());
+                matches.push(LogSearchMatch {
+                    entry: entry.clone(),
+                    before: entries[start..i].to_vec(),
+                    after: entries[i + 1..end].to_vec(),
+                });
+                if matches.len() >= options.max_results {
+                    matches.reverse();
+                    return Ok(matches);
+                }
+            }
+        }
+
+        matches.reverse(); // Return in chronological order
+        Ok(matches)
     }
 
     /// Clear logs for a server
     pub async fn clear_logs(&self, space_id: &str, server_id: &str) -> Result<()> {
         let key = format!("{}/{}", space_id, server_id);
 
+        // Queued entries would otherwise recreate the directory
+        self.flush().await;
+
         // Close writer if open
         {
             let mut writers = self.writers.write().await;
@@ -260,13 +573,153 @@ impl ServerLogManager {
     }
 }
 
+impl Drop for ServerLogManager {
+    fn drop(&mut self) {
+        // Let the writer finish what is queued, then exit
+        self.queue.lock().closed = true;
+        self.queue.wake.notify_one();
+    }
+}
+
+/// Entries waiting for the background writer, shared with [`ServerLogManager`]
+#[derive(Default)]
+struct AppendQueue {
+    pending: std::sync::Mutex<PendingLogs>,
+    /// Signalled when entries are queued or the manager is dropped
+    wake: Notify,
+    /// Signalled when the writer has written everything queued
+    idle: Notify,
+}
+
+impl AppendQueue {
+    fn lock(&self) -> std::sync::MutexGuard<'_, PendingLogs> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[derive(Default)]
+struct PendingLogs {
+    entries: VecDeque<QueuedLog>,
+    /// Entries dropped on overflow since the writer last reported them
+    dropped: u64,
+    /// The writer is writing a batch it has taken off the queue
+    writing: bool,
+    /// The manager is gone; the writer exits once the queue is empty
+    closed: bool,
+}
+
+struct QueuedLog {
+    space_id: String,
+    server_id: String,
+    log: ServerLog,
+}
+
+/// Background task writing queued entries to disk, a batch at a time.
+async fn write_queued(queue: Arc<AppendQueue>, writers: WriterMap, config: LogConfig) {
+    // Whether some writer holds lines that are waiting for `flush_interval`
+    let mut buffered = false;
+    loop {
+        let (batch, dropped, closed) = {
+            let mut pending = queue.lock();
+            pending.writing = !pending.entries.is_empty();
+            (
+                std::mem::take(&mut pending.entries),
+                std::mem::take(&mut pending.dropped),
+                pending.closed,
+            )
+        };
+
+        if dropped > 0 {
+            warn!(
+                "[ServerLog] Log writer fell behind; dropped {} oldest entries",
+                dropped
+            );
+        }
+        if batch.is_empty() {
+            queue.idle.notify_waiters();
+            if closed {
+                flush_writers(&writers, true).await;
+                return;
+            }
+            if buffered {
+                let _ = tokio::time::timeout(config.flush_interval, queue.wake.notified()).await;
+            } else {
+                queue.wake.notified().await;
+            }
+            buffered = flush_writers(&writers, false).await;
+            continue;
+        }
+
+        let mut touched: HashMap<String, Option<Arc<Mutex<ServerLogWriter>>>> = HashMap::new();
+        for entry in batch {
+            let key = format!("{}/{}", entry.space_id, entry.server_id);
+            if !touched.contains_key(&key) {
+                let opened = ServerLogManager::get_writer(
+                    &writers,
+                    &config,
+                    &entry.space_id,
+                    &entry.server_id,
+                )
+                .await;
+                let writer = match opened {
+                    Ok(writer) => Some(writer),
+                    Err(e) => {
+                        warn!("[ServerLog] Failed to open log for {}: {}", key, e);
+                        None
+                    }
+                };
+                touched.insert(key.clone(), writer);
+            }
+            let Some(Some(writer)) = touched.get(&key) else {
+                continue;
+            };
+            if let Err(e) = writer.lock().await.write(entry.log).await {
+                warn!("[ServerLog] Failed to write log for {}: {}", key, e);
+            }
+        }
+        drop(touched);
+        buffered = flush_writers(&writers, false).await;
+    }
+}
+
+/// Write out buffered lines: all of them with `force`, otherwise those that
+/// have waited `flush_interval`. Returns whether any lines are still buffered.
+async fn flush_writers(writers: &WriterMap, force: bool) -> bool {
+    let open: Vec<_> = writers.read().await.values().cloned().collect();
+    let now = Instant::now();
+    let mut buffered = false;
+    for writer in open {
+        let mut writer = writer.lock().await;
+        if force || writer.flush_due(now) {
+            if let Err(e) = writer.flush().await {
+                warn!(
+                    "[ServerLog] Failed to flush log in {:?}: {}",
+                    writer.log_dir, e
+                );
+            }
+        }
+        buffered |= !writer.buffer.is_empty();
+    }
+    buffered
+}
+
 /// Writer for a single server's logs
 struct ServerLogWriter {
     log_dir: PathBuf,
     current_file: File,
+    /// Size of `current.log` including buffered lines
     current_size: u64,
+    /// Lines not yet written to `current.log`
+    buffer: Vec<u8>,
+    /// When the oldest buffered line was added
+    buffered_since: Option<Instant>,
+    flush_bytes: usize,
+    flush_interval: Duration,
+    /// When the data in `current.log` started accumulating
+    current_started: SystemTime,
     max_file_size: u64,
     max_files: usize,
+    max_age: Option<Duration>,
     compress: bool,
 }
 
@@ -280,15 +733,38 @@ impl ServerLogWriter {
             .await
             .context("Failed to open log file")?;
 
-        let current_size = file.metadata().await?.len();
+        let metadata = file.metadata().await?;
+        let current_size = metadata.len();
+        let current_started = metadata.created().unwrap_or_else(|_| SystemTime::now());
 
-        Ok(Self {
+        let writer = Self {
             log_dir,
             current_file: file,
             current_size,
+            buffer: Vec::new(),
+            buffered_since: None,
+            flush_bytes: config.flush_bytes,
+            flush_interval: config.flush_interval,
+            current_started,
             max_file_size: config.max_file_size,
             max_files: config.max_files,
+            max_age: config.max_age,
             compress: config.compress,
+        };
+
+        // Apply retention to files left over from earlier runs
+        writer.cleanup_old_files().await?;
+        Ok(writer)
+    }
+
+    /// Whether `current.log` has been collecting entries longer than `max_age`.
+    fn current_expired(&self) -> bool {
+        self.max_age.is_some_and(|max_age| {
+            self.current_size > 0
+                && self
+                    .current_started
+                    .elapsed()
+                    .is_ok_and(|elapsed| elapsed >= max_age)
         })
     }
 
@@ -300,15 +776,37 @@ impl ServerLogWriter {
         let line_len = line.len() as u64;
 
         // Check if we need to rotate
-        if self.current_size + line_len > self.max_file_size {
+        if self.current_size + line_len > self.max_file_size || self.current_expired() {
+            self.flush().await?;
             self.rotate().await?;
         }
 
-        // Write line
-        self.current_file.write_all(line.as_bytes()).await?;
-        self.current_file.flush().await?;
+        self.buffer.extend_from_slice(line.as_bytes());
+        self.buffered_since.get_or_insert_with(Instant::now);
         self.current_size += line_len;
 
+        if self.buffer.len() >= self.flush_bytes {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Whether the oldest buffered line has waited `flush_interval`.
+    fn flush_due(&self, now: Instant) -> bool {
+        self.buffered_since
+            .is_some_and(|since| now.duration_since(since) >= self.flush_interval)
+    }
+
+    /// Write buffered lines to `current.log`.
+    async fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        // Clear the buffer even on failure; retrying would duplicate a partial write
+        let buffer = std::mem::take(&mut self.buffer);
+        self.buffered_since = None;
+        self.current_file.write_all(&buffer).await?;
+        self.current_file.flush().await?;
         Ok(())
     }
 
@@ -318,10 +816,16 @@ impl ServerLogWriter {
         // Close current file
         self.current_file.shutdown().await?;
 
-        // Rename current.log to timestamped file
+        // Rename current.log to timestamped file. Millisecond precision keeps
+        // names unique (and sortable) when a noisy server rotates quickly.
         let current_path = self.log_dir.join("current.log");
-        let timestamp = chrono::Utc::now().format("%Y-%m-%d-%H%M%S");
-        let rotated_path = self.log_dir.join(format!("{}.log", timestamp));
+        let timestamp = chrono::Utc::now().format("%Y-%m-%d-%H%M%S%.3f");
+        let mut rotated_path = self.log_dir.join(format!("{}.log", timestamp));
+        let mut suffix = 1;
+        while rotated_path.exists() || rotated_path.with_extension("log.gz").exists() {
+            rotated_path = self.log_dir.join(format!("{}-{}.log", timestamp, suffix));
+            suffix += 1;
+        }
 
         tokio::fs::rename(&current_path, &rotated_path).await?;
 
@@ -346,6 +850,7 @@ impl ServerLogWriter {
             .await?;
 
         self.current_size = 0;
+        self.current_started = SystemTime::now();
 
         Ok(())
     }
@@ -370,6 +875,20 @@ impl ServerLogWriter {
         // Sort by modification time (oldest first)
         log_files.sort_by_key(|(_, modified)| *modified);
 
+        // Remove files past the age limit
+        if let Some(max_age) = self.max_age {
+            if let Some(cutoff) = SystemTime::now().checked_sub(max_age) {
+                let expired = log_files.partition_point(|(_, modified)| *modified < cutoff);
+                for (path, _) in log_files.drain(..expired) {
+                    if let Err(e) = tokio::fs::remove_file(&path).await {
+                        warn!("Failed to remove expired log file {:?}: {}", path, e);
+                    } else {
+                        debug!("Removed expired log file: {:?}", path);
+                    }
+                }
+            }
+        }
+
         // Remove oldest files if we exceed max_files
         if log_files.len() > self.max_files {
             let to_remove = log_files.len() - self.max_files;
@@ -386,6 +905,27 @@ impl ServerLogWriter {
     }
 }
 
+impl Drop for ServerLogWriter {
+    fn drop(&mut self) {
+        // Only left over if the writer task was cancelled, e.g. on runtime shutdown
+        if self.buffer.is_empty() {
+            return;
+        }
+        let path = self.log_dir.join("current.log");
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&self.buffer));
+        if let Err(e) = written {
+            warn!(
+                "[ServerLog] Failed to write buffered log lines to {:?}: {}",
+                path, e
+            );
+        }
+    }
+}
+
 /// Check if a path is a removable log file (not `current.log`)
 fn is_removable_log_file(path: &Path) -> bool {
     let name = match path.file_name().and_then(|n| n.to_str()) {
@@ -395,6 +935,107 @@ fn is_removable_log_file(path: &Path) -> bool {
     name != "current.log" && (name.ends_with(".log") || name.ends_with(".log.gz"))
 }
 
+/// List a server's log files, `current.log` first and then rotated files from
+/// newest to oldest.
+///
+/// Rotated names start with their rotation timestamp, so name order is
+/// chronological. While a rotated file is being compressed both `X.log` and
+/// `X.log.gz` can exist; only the uncompressed one is returned.
+async fn log_files_newest_first(log_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut rotated: Vec<(String, PathBuf)> = Vec::new();
+    let mut entries = tokio::fs::read_dir(log_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let stem = match name.strip_suffix(".log.gz") {
+            Some(stem) => stem,
+            None => match name.strip_suffix(".log") {
+                Some(stem) if name != "current.log" => stem,
+                _ => continue,
+            },
+        };
+        rotated.push((stem.to_string(), path));
+    }
+
+    // Newest first; for equal stems `X.log` sorts after `X.log.gz`, so
+    // reverse order puts the uncompressed file first and dedup keeps it
+    rotated.sort();
+    rotated.reverse();
+    rotated.dedup_by(|a, b| a.0 == b.0);
+
+    let current = log_dir.join("current.log");
+    let mut files = Vec::with_capacity(rotated.len() + 1);
+    if current.exists() {
+        files.push(current);
+    }
+    files.extend(rotated.into_iter().map(|(_, path)| path));
+    Ok(files)
+}
+
+/// Whether a search pattern matches an entry's message or metadata
+fn log_matches(matcher: &Regex, log: &ServerLog) -> bool {
+    matcher.is_match(&log.message)
+        || log
+            .metadata
+            .as_ref()
+            .is_some_and(|meta| matcher.is_match(&meta.to_string()))
+}
+
+/// Read a log file, transparently decompressing `.gz` files
+async fn read_log_file(path: &Path) -> Result<String> {
+    let data = tokio::fs::read(path).await?;
+    if path.extension().and_then(|e| e.to_str()) != Some("gz") {
+        return Ok(String::from_utf8_lossy(&data).into_owned());
+    }
+
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        let mut content = Vec::new();
+        if let Err(e) = GzDecoder::new(data.as_slice()).read_to_end(&mut content) {
+            // A truncated archive still yields the lines before the damage
+            if content.is_empty() {
+                return Err(e.into());
+            }
+            warn!(
+                "Reading what is left of damaged log archive {:?}: {}",
+                path, e
+            );
+        }
+        Ok::<_, anyhow::Error>(String::from_utf8_lossy(&content).into_owned())
+    })
+    .await?
+}
+
+/// One line of a log file.
+enum LogLine {
+    Entry(ServerLog),
+    /// Empty, or only the NUL padding a crash can leave behind
+    Blank,
+    /// Not a log entry, even after repair
+    Damaged,
+}
+
+/// Parse one JSON line of a log file.
+///
+/// A write cut off by a crash leaves a partial entry without its newline, so
+/// the next entry written ends up on the same line; that entry is recovered
+/// from the end of the line.
+fn parse_log_line(line: &str) -> LogLine {
+    let line = line.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    if line.is_empty() {
+        return LogLine::Blank;
+    }
+    if let Ok(log) = serde_json::from_str(line) {
+        return LogLine::Entry(log);
+    }
+    line.match_indices("{\"")
+        .filter(|(start, _)| *start > 0)
+        .find_map(|(start, _)| serde_json::from_str(&line[start..]).ok())
+        .map_or(LogLine::Damaged, LogLine::Entry)
+}
+
 /// Check if a directory is empty
 async fn dir_is_empty(path: &Path) -> bool {
     match tokio::fs::read_dir(path).await {
@@ -410,16 +1051,19 @@ async fn compress_log_file(path: &Path) -> Result<()> {
     // Read original file
     let content = tokio::fs::read(path).await?;
 
-    // Compress using blocking IO in a separate task
-    let gz_path_clone = gz_path.clone();
+    // Compress using blocking IO in a separate task. Write to a temporary
+    // name first so readers never see a partially written archive.
+    let tmp_path = path.with_extension("log.gz.tmp");
+    let tmp_path_clone = tmp_path.clone();
     tokio::task::spawn_blocking(move || {
-        let file = std::fs::File::create(&gz_path_clone)?;
+        let file = std::fs::File::create(&tmp_path_clone)?;
         let mut encoder = GzEncoder::new(file, Compression::default());
         encoder.write_all(&content)?;
         encoder.finish()?;
         Ok::<_, anyhow::Error>(())
     })
     .await??;
+    tokio::fs::rename(&tmp_path, &gz_path).await?;
 
     // Remove original file
     tokio::fs::remove_file(path).await?;
@@ -431,7 +1075,6 @@ async fn compress_log_file(path: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::LogSource;
 
     #[tokio::test]
     async fn test_log_manager_basic() {
@@ -440,7 +1083,9 @@ mod tests {
             base_dir: temp_dir.path().to_path_buf(),
             max_file_size: 1024,
             max_files: 5,
+            max_age: None,
             compress: false,
+            ..Default::default()
         };
 
         let manager = ServerLogManager::new(config);
@@ -457,9 +1102,10 @@ mod tests {
 
         // Read logs
         let logs = manager
-            .read_logs("space1", "server1", 5, None)
+            .read_logs("space1", "server1", 5, None, None)
             .await
-            .unwrap();
+            .unwrap()
+            .entries;
         assert_eq!(logs.len(), 5);
         assert_eq!(logs[0].message, "Test message 5");
         assert_eq!(logs[4].message, "Test message 9");
@@ -472,7 +1118,9 @@ mod tests {
             base_dir: temp_dir.path().to_path_buf(),
             max_file_size: 1024 * 1024,
             max_files: 5,
+            max_age: None,
             compress: false,
+            ..Default::default()
         };
 
         let manager = ServerLogManager::new(config);
@@ -513,9 +1161,10 @@ mod tests {
 
         // Filter by warn and above
         let logs = manager
-            .read_logs("space1", "server1", 10, Some(LogLevel::Warn))
+            .read_logs("space1", "server1", 10, Some(LogLevel::Warn), None)
             .await
-            .unwrap();
+            .unwrap()
+            .entries;
         assert_eq!(logs.len(), 2);
         assert_eq!(logs[0].message, "Warn msg");
         assert_eq!(logs[1].message, "Error msg");
@@ -528,7 +1177,9 @@ mod tests {
             base_dir: temp_dir.path().to_path_buf(),
             max_file_size: 1024 * 1024,
             max_files: 100,
+            max_age: None,
             compress: false,
+            ..Default::default()
         };
         let manager = ServerLogManager::new(config);
 
@@ -575,7 +1226,9 @@ mod tests {
             base_dir: temp_dir.path().to_path_buf(),
             max_file_size: 1024 * 1024,
             max_files: 100,
+            max_age: None,
             compress: false,
+            ..Default::default()
         };
         let manager = ServerLogManager::new(config);
 
@@ -626,7 +1279,9 @@ mod tests {
             base_dir: temp_dir.path().to_path_buf(),
             max_file_size: 1024 * 1024,
             max_files: 100,
+            max_age: None,
             compress: false,
+            ..Default::default()
         };
         let manager = ServerLogManager::new(config);
 
@@ -659,7 +1314,9 @@ mod tests {
             base_dir: temp_dir.path().to_path_buf(),
             max_file_size: 1024 * 1024,
             max_files: 100,
+            max_age: None,
             compress: false,
+            ..Default::default()
         };
         let manager = ServerLogManager::new(config);
 
@@ -680,4 +1337,528 @@ mod tests {
         assert!(!server_dir.exists());
         assert!(!temp_dir.path().join("space1").exists());
     }
+
+    #[tokio::test]
+    async fn test_read_logs_across_rotated_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            max_file_size: 512,
+            max_files: 100,
+            max_age: None,
+            compress: false,
+            ..Default::default()
+        };
+        let manager = ServerLogManager::new(config);
+
+        for i in 0..40 {
+            let log = ServerLog::new(LogLevel::Info, LogSource::App, format!("message {}", i));
+            manager.append("space1", "server1", log).await.unwrap();
+        }
+        manager.flush().await;
+
+        let server_dir = temp_dir.path().join("space1").join("server1");
+        let mut rotated = tokio::fs::read_dir(&server_dir).await.unwrap();
+        let mut rotated_count = 0;
+        while let Some(entry) = rotated.next_entry().await.unwrap() {
+            if is_removable_log_file(&entry.path()) {
+                rotated_count += 1;
+            }
+        }
+        assert!(rotated_count > 1, "expected several rotations");
+
+        let logs = manager
+            .read_logs("space1", "server1", 100, None, None)
+            .await
+            .unwrap()
+            .entries;
+        let messages: Vec<_> = logs.iter().map(|l| l.message.clone()).collect();
+        let expected: Vec<_> = (0..40).map(|i| format!("message {}", i)).collect();
+        assert_eq!(messages, expected);
+
+        let logs = manager
+            .read_logs("space1", "server1", 25, None, None)
+            .await
+            .unwrap()
+            .entries;
+        assert_eq!(logs.len(), 25);
+        assert_eq!(logs[0].message, "message 15");
+        assert_eq!(logs[24].message, "message 39");
+    }
+
+    #[tokio::test]
+    async fn test_read_logs_from_compressed_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let server_dir = temp_dir.path().join("space1").join("server1");
+        tokio::fs::create_dir_all(&server_dir).await.unwrap();
+
+        let line = |msg: &str| {
+            let mut line =
+                serde_json::to_string(&ServerLog::new(LogLevel::Info, LogSource::App, msg))
+                    .unwrap();
+            line.push('\n');
+            line
+        };
+
+        let older = server_dir.join("2025-01-01-000000.000.log");
+        tokio::fs::write(&older, line("oldest")).await.unwrap();
+        compress_log_file(&older).await.unwrap();
+        assert!(server_dir.join("2025-01-01-000000.000.log.gz").exists());
+
+        tokio::fs::write(server_dir.join("2025-01-02-000000.000.log"), line("middle"))
+            .await
+            .unwrap();
+        tokio::fs::write(server_dir.join("current.log"), line("newest"))
+            .await
+            .unwrap();
+
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let logs = manager
+            .read_logs("space1", "server1", 10, None, None)
+            .await
+            .unwrap()
+            .entries;
+        let messages: Vec<_> = logs.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(messages, ["oldest", "middle", "newest"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_logs_skips_damaged_lines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let server_dir = temp_dir.path().join("space1").join("server1");
+        tokio::fs::create_dir_all(&server_dir).await.unwrap();
+
+        let line = |msg: &str| {
+            serde_json::to_string(&ServerLog::new(LogLevel::Info, LogSource::App, msg)).unwrap()
+        };
+        let cut_off = &line("lost")[..20];
+
+        let mut content = Vec::new();
+        content.extend(format!("{}\n", line("first")).into_bytes());
+        // Invalid UTF-8 inside a message
+        content.extend(line("bad XX byte").replace("XX", "\u{1}").into_bytes());
+        let bad = content.iter().rposition(|b| *b == 1).unwrap();
+        content[bad] = 0xff;
+        content.push(b'\n');
+        content.extend(b"not json at all\n");
+        // A write cut off by a crash, with the next entry appended after it
+        content.extend(format!("{}{}\n", cut_off, line("after crash")).into_bytes());
+        content.extend(b"\0\0\0\n");
+        // Tail cut off by a crash
+        content.extend(cut_off.as_bytes());
+        tokio::fs::write(server_dir.join("current.log"), content)
+            .await
+            .unwrap();
+
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+        let read = manager
+            .read_logs("space1", "server1", 10, None, None)
+            .await
+            .unwrap();
+        let messages: Vec<_> = read.entries.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(messages, ["first", "bad \u{fffd} byte", "after crash"]);
+        assert_eq!(read.skipped, 2);
+    }
+
+    #[tokio::test]
+    async fn test_rotation_applies_max_files_and_max_age() {
+        use filetime::FileTime;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let server_dir = temp_dir.path().join("space1").join("server1");
+        tokio::fs::create_dir_all(&server_dir).await.unwrap();
+
+        // Left over from an earlier run, well past the age limit
+        let expired = server_dir.join("2020-01-01-000000.log");
+        tokio::fs::write(&expired, b"expired").await.unwrap();
+        let long_ago =
+            FileTime::from_system_time(SystemTime::now() - Duration::from_secs(10 * 86400));
+        filetime::set_file_mtime(&expired, long_ago).unwrap();
+
+        let config = LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            max_file_size: 256,
+            max_files: 2,
+            max_age: Some(Duration::from_secs(86400)),
+            compress: false,
+            ..Default::default()
+        };
+        let manager = ServerLogManager::new(config);
+
+        for i in 0..30 {
+            let log = ServerLog::new(LogLevel::Info, LogSource::App, format!("message {}", i));
+            manager.append("space1", "server1", log).await.unwrap();
+        }
+        manager.flush().await;
+
+        assert!(!expired.exists(), "expired file should be deleted");
+        let mut entries = tokio::fs::read_dir(&server_dir).await.unwrap();
+        let mut rotated = 0;
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            if is_removable_log_file(&entry.path()) {
+                rotated += 1;
+            }
+        }
+        assert_eq!(rotated, 2);
+        assert!(server_dir.join("current.log").exists());
+    }
+
+    #[tokio::test]
+    async fn test_append_redacts_registered_secrets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+
+        manager
+            .register_secrets(
+                "space1",
+                "server1",
+                vec!["configured-token-1234".to_string()],
+            )
+            .await;
+        manager
+            .append(
+                "space1",
+                "server1",
+                ServerLog::new(
+                    LogLevel::Info,
+                    LogSource::Stderr,
+                    "auth with configured-token-1234, Bearer abc.def.ghi",
+                ),
+            )
+            .await
+            .unwrap();
+        // Other servers don't share the registration
+        manager
+            .append(
+                "space1",
+                "server2",
+                ServerLog::new(LogLevel::Info, LogSource::Stderr, "configured-token-1234"),
+            )
+            .await
+            .unwrap();
+
+        let logs = manager
+            .read_logs("space1", "server1", 10, None, None)
+            .await
+            .unwrap()
+            .entries;
+        assert_eq!(logs[0].message, "auth with ***, Bearer ***");
+
+        let raw = tokio::fs::read_to_string(manager.get_log_file("space1", "server1"))
+            .await
+            .unwrap();
+        assert!(!raw.contains("configured-token-1234"));
+
+        let logs = manager
+            .read_logs("space1", "server2", 10, None, None)
+            .await
+            .unwrap()
+            .entries;
+        assert_eq!(logs[0].message, "configured-token-1234");
+    }
+
+    #[tokio::test]
+    async fn test_read_logs_filters_before_limit() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+
+        // A few warnings buried under lots of info output
+        for i in 0..50 {
+            let (level, source) = match i % 10 {
+                0 => (LogLevel::Warn, LogSource::Stderr),
+                5 => (LogLevel::Error, LogSource::Connection),
+                _ => (LogLevel::Info, LogSource::Stderr),
+            };
+            let log = ServerLog::new(level, source, format!("message {}", i));
+            manager.append("space1", "server1", log).await.unwrap();
+        }
+
+        let logs = manager
+            .read_logs("space1", "server1", 4, Some(LogLevel::Warn), None)
+            .await
+            .unwrap()
+            .entries;
+        let messages: Vec<_> = logs.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            ["message 30", "message 35", "message 40", "message 45"]
+        );
+
+        let logs = manager
+            .read_logs(
+                "space1",
+                "server1",
+                3,
+                Some(LogLevel::Warn),
+                Some(&[LogSource::Stderr]),
+            )
+            .await
+            .unwrap()
+            .entries;
+        let messages: Vec<_> = logs.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(messages, ["message 20", "message 30", "message 40"]);
+
+        let logs = manager
+            .read_logs(
+                "space1",
+                "server1",
+                100,
+                None,
+                Some(&[LogSource::Connection]),
+            )
+            .await
+            .unwrap()
+            .entries;
+        assert_eq!(logs.len(), 5);
+        assert!(logs.iter().all(|l| l.source == LogSource::Connection));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_new_entries() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+
+        manager
+            .append(
+                "space1",
+                "server1",
+                ServerLog::new(LogLevel::Info, LogSource::Stderr, "before subscribe"),
+            )
+            .await
+            .unwrap();
+
+        let mut rx = manager.subscribe("space1", "server1");
+        let mut other = manager.subscribe("space1", "server2");
+
+        manager
+            .append(
+                "space1",
+                "server1",
+                ServerLog::new(LogLevel::Warn, LogSource::Stderr, "Bearer abc.def"),
+            )
+            .await
+            .unwrap();
+
+        // Only new entries, already redacted
+        let log = rx.recv().await.unwrap();
+        assert_eq!(log.message, "Bearer ***");
+        assert_eq!(log.level, LogLevel::Warn);
+        assert!(rx.try_recv().is_err());
+        assert!(other.try_recv().is_err());
+
+        // Channels without receivers are dropped on the next append
+        drop(rx);
+        manager
+            .append(
+                "space1",
+                "server1",
+                ServerLog::new(LogLevel::Info, LogSource::Stderr, "nobody listening"),
+            )
+            .await
+            .unwrap();
+        assert!(!manager
+            .subscribers
+            .lock()
+            .unwrap()
+            .contains_key("space1/server1"));
+    }
+
+    #[tokio::test]
+    async fn test_search_across_rotated_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            max_file_size: 512,
+            max_files: 100,
+            max_age: None,
+            compress: false,
+            ..Default::default()
+        });
+
+        for i in 0..30 {
+            let message = if i % 7 == 3 {
+                format!("handled REQ-42 step {}", i)
+            } else {
+                format!("message {}", i)
+            };
+            let log = ServerLog::new(LogLevel::Info, LogSource::Stderr, message);
+            manager.append("space1", "server1", log).await.unwrap();
+        }
+        manager
+            .append(
+                "space1",
+                "server1",
+                ServerLog::new(LogLevel::Info, LogSource::Stderr, "tool call")
+                    .with_metadata(serde_json::json!({"requestId": "req-42"})),
+            )
+            .await
+            .unwrap();
+
+        // Case-insensitive substring, matching metadata too
+        let matches = manager
+            .search("space1", "server1", "req-42", SearchOptions::default())
+            .await
+            .unwrap();
+        let messages: Vec<_> = matches.iter().map(|m| m.entry.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "handled REQ-42 step 3",
+                "handled REQ-42 step 10",
+                "handled REQ-42 step 17",
+                "handled REQ-42 step 24",
+                "tool call",
+            ]
+        );
+
+        // Case-sensitive regex with a result cap keeps the newest matches
+        let matches = manager
+            .search(
+                "space1",
+                "server1",
+                r"REQ-\d+ step 1\d",
+                SearchOptions {
+                    regex: true,
+                    case_sensitive: true,
+                    context_lines: 1,
+                    max_results: 1,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entry.message, "handled REQ-42 step 17");
+
+        // Invalid regex is an error
+        let result = manager
+            .search(
+                "space1",
+                "server1",
+                "(",
+                SearchOptions {
+                    regex: true,
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_context_lines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        });
+
+        for message in ["a", "b", "needle", "c", "d", "e"] {
+            let log = ServerLog::new(LogLevel::Info, LogSource::Stderr, message);
+            manager.append("space1", "server1", log).await.unwrap();
+        }
+
+        let matches = manager
+            .search("space1", "server1", "NEEDLE", SearchOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        let before: Vec<_> = matches[0]
+            .before
+            .iter()
+            .map(|l| l.message.as_str())
+            .collect();
+        let after: Vec<_> = matches[0]
+            .after
+            .iter()
+            .map(|l| l.message.as_str())
+            .collect();
+        assert_eq!(before, ["a", "b"]);
+        assert_eq!(after, ["c", "d"]);
+
+        let matches = manager
+            .search("space1", "missing", "needle", SearchOptions::default())
+            .await
+            .unwrap();
+        assert!(matches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_append_queue_drops_oldest_on_overflow() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = ServerLogManager::new(LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            ..Default::default()
+        })
+        .with_queue_capacity(3);
+
+        // The writer task can't run until this task yields, so the queue overflows
+        for i in 0..5 {
+            let log = ServerLog::new(LogLevel::Info, LogSource::Stderr, format!("line {}", i));
+            manager.append("space1", "server1", log).await.unwrap();
+        }
+
+        let logs = manager
+            .read_logs("space1", "server1", 10, None, None)
+            .await
+            .unwrap()
+            .entries;
+        let messages: Vec<_> = logs.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(messages, ["line 2", "line 3", "line 4"]);
+
+        // Once drained, appends are written again
+        let log = ServerLog::new(LogLevel::Info, LogSource::Stderr, "line 5");
+        manager.append("space1", "server1", log).await.unwrap();
+        manager.flush().await;
+        let raw = tokio::fs::read_to_string(manager.get_log_file("space1", "server1"))
+            .await
+            .unwrap();
+        assert_eq!(raw.lines().count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_buffered_lines_flush_on_interval_and_drop() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = LogConfig {
+            base_dir: temp_dir.path().to_path_buf(),
+            flush_interval: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let manager = ServerLogManager::new(config.clone());
+        let path = manager.get_log_file("space1", "server1");
+        let raw = || async { tokio::fs::read_to_string(&path).await.unwrap_or_default() };
+
+        let log = ServerLog::new(LogLevel::Info, LogSource::Stderr, "buffered");
+        manager.append("space1", "server1", log).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(raw().await.is_empty(), "below both thresholds");
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(raw().await.contains("buffered"));
+
+        // Dropping the manager writes out the tail
+        let manager = ServerLogManager::new(LogConfig {
+            flush_interval: Duration::from_secs(3600),
+            ..config
+        });
+        let log = ServerLog::new(LogLevel::Info, LogSource::Stderr, "tail");
+        manager.append("space1", "server1", log).await.unwrap();
+        drop(manager);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(raw().await.contains("tail"));
+    }
 }
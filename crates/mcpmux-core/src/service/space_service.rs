@@ -35,12 +35,12 @@ impl SpaceService {
 
     /// List all spaces
     pub async fn list(&self) -> anyhow::Result<Vec<Space>> {
-        self.repository.list().await
+        Ok(self.repository.list().await?)
     }
 
     /// Get a space by ID
     pub async fn get(&self, id: &Uuid) -> anyhow::Result<Option<Space>> {
-        self.repository.get(id).await
+        Ok(self.repository.get(id).await?)
     }
 
     /// Create a new space
@@ -88,16 +88,16 @@ impl SpaceService {
                 anyhow::bail!("Cannot delete the default space");
             }
         }
-        self.repository.delete(id).await
+        Ok(self.repository.delete(id).await?)
     }
 
     /// Get the active (default) space
     pub async fn get_active(&self) -> anyhow::Result<Option<Space>> {
-        self.repository.get_default().await
+        Ok(self.repository.get_default().await?)
     }
 
     /// Set the active space
     pub async fn set_active(&self, id: &Uuid) -> anyhow::Result<()> {
-        self.repository.set_default(id).await
+        Ok(self.repository.set_default(id).await?)
     }
 }
@@ -391,7 +391,7 @@ pub async fn oauth_auth_middleware(
 
     // Get base URL and JWT secret
     let base_url = gateway_state.base_url.clone();
-    let Some(secret) = gateway_state.get_jwt_secret() else {
+    if !gateway_state.has_jwt_secret() {
         warn!("[Auth] No JWT secret configured - rejecting all requests");
         return unauthorized_response_with_url(
             &base_url,
@@ -411,7 +411,7 @@ pub async fn oauth_auth_middleware(
             let token = &auth[7..];
 
             // Validate token
-            match validate_token(token, secret) {
+            match gateway_state.validate_token(token) {
                 Some(claims) => {
                     debug!("[Auth] Valid token for client: {}", claims.client_id);
 
@@ -517,4 +517,35 @@ mod jwt_tests {
         let claims = validate_token(&token, secret);
         assert!(claims.is_none());
     }
+
+    #[test]
+    fn test_previous_secret_grace_period() {
+        use crate::server::GatewayState;
+        use mcpmux_storage::PreviousJwtSecret;
+        use zeroize::Zeroizing;
+
+        let (old, new) = ([1u8; 32], [2u8; 32]);
+        let token = create_access_token("test_client", None, 3600, &old);
+
+        let (tx, _) = tokio::sync::broadcast::channel(1);
+        let mut state = GatewayState::new(tx);
+        state.set_jwt_secret(Zeroizing::new(new));
+        assert!(state.validate_token(&token).is_none());
+
+        // Tokens signed before the rotation validate during the grace period
+        state.set_previous_jwt_secret(PreviousJwtSecret {
+            secret: Zeroizing::new(old),
+            expires_at: chrono::Utc::now() + chrono::Duration::hours(1),
+        });
+        assert!(state.validate_token(&token).is_some());
+        let fresh = create_access_token("test_client", None, 3600, &new);
+        assert!(state.validate_token(&fresh).is_some());
+
+        // ...but not after it
+        state.set_previous_jwt_secret(PreviousJwtSecret {
+            secret: Zeroizing::new(old),
+            expires_at: chrono::Utc::now() - chrono::Duration::seconds(1),
+        });
+        assert!(state.validate_token(&token).is_none());
+    }
 }
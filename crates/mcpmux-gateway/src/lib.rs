@@ -29,7 +29,10 @@ pub use server::{
 // Pool module - SOLID architecture
 pub use pool::{
     // Types
+    CachedCapabilities,
     CachedFeatures,
+    CommandPolicy,
+    CommandPolicyError,
     // Server Manager (event-driven orchestrator)
     ConnectResult,
     ConnectionContext,
@@ -63,10 +66,12 @@ pub use pool::{
     RoutedResource,
     RoutedTool,
     RoutingService,
+    ServerConnectionState,
     ServerInstance,
     ServerKey,
     ServerManager,
     ServerState,
+    ServerStats,
     ServiceFactory,
     TokenService,
     TransportConnectResult,
@@ -14,7 +14,6 @@ use axum::{
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
-use crate::auth::validate_token;
 use crate::logging::TraceContext;
 use crate::server::ServiceContainer;
 
@@ -59,22 +58,20 @@ pub async fn mcp_oauth_middleware(
     };
 
     // Verify JWT and extract claims
-    let jwt_secret = {
+    let verified = {
         let state = services.gateway_state.read().await;
-        match state.get_jwt_secret() {
-            Some(secret) => secret.to_vec(),
-            None => {
-                warn!(trace_id = %trace_id, "JWT secret not configured");
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Server not configured for authentication",
-                )
-                    .into_response();
-            }
+        if !state.has_jwt_secret() {
+            warn!(trace_id = %trace_id, "JWT secret not configured");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Server not configured for authentication",
+            )
+                .into_response();
         }
+        state.validate_token(token)
     };
 
-    let claims = match validate_token(token, &jwt_secret) {
+    let claims = match verified {
         Some(claims) => claims,
         None => {
             warn!(trace_id = %trace_id, "Token verification failed");
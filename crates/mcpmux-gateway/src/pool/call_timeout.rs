@@ -0,0 +1,135 @@
+//! Timeouts for outbound tool calls
+//!
+//! A server can connect fine and then hang on one particular tool call.
+//! [`ToolCallTimeouts`] bounds how long the routing service waits for each
+//! call: a global default, overridable per server, and per tool for
+//! long-running tools that need longer or no limit at all. A call that runs
+//! out of time fails with [`ToolCallTimeout`]; whatever the server sends back
+//! later is discarded.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Timeout for tool calls to servers without one of their own.
+pub const DEFAULT_TOOL_CALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a tool call may take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallTimeout {
+    After(Duration),
+    /// Wait as long as the server takes.
+    Unlimited,
+}
+
+impl CallTimeout {
+    /// The timeout as a duration, `None` if unlimited.
+    pub fn duration(self) -> Option<Duration> {
+        match self {
+            CallTimeout::After(timeout) => Some(timeout),
+            CallTimeout::Unlimited => None,
+        }
+    }
+}
+
+impl Default for CallTimeout {
+    fn default() -> Self {
+        CallTimeout::After(DEFAULT_TOOL_CALL_TIMEOUT)
+    }
+}
+
+/// A tool call that got no response within its timeout.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Tool '{tool}' on server '{server_id}' timed out after {timeout:?}")]
+pub struct ToolCallTimeout {
+    pub server_id: String,
+    pub tool: String,
+    pub timeout: Duration,
+}
+
+/// Per-server and per-tool timeouts for tool calls, keyed by server id.
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallTimeouts {
+    default: CallTimeout,
+    servers: HashMap<String, CallTimeout>,
+    tools: HashMap<(String, String), CallTimeout>,
+}
+
+impl ToolCallTimeouts {
+    /// [`DEFAULT_TOOL_CALL_TIMEOUT`] for every call.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time out calls to servers without a timeout of their own.
+    pub fn with_default(mut self, timeout: CallTimeout) -> Self {
+        self.default = timeout;
+        self
+    }
+
+    /// Time out calls to one server.
+    pub fn with_server_timeout(
+        mut self,
+        server_id: impl Into<String>,
+        timeout: CallTimeout,
+    ) -> Self {
+        self.servers.insert(server_id.into(), timeout);
+        self
+    }
+
+    /// Time out calls to one tool, e.g. to give a long-running tool more time.
+    pub fn with_tool_timeout(
+        mut self,
+        server_id: impl Into<String>,
+        tool: impl Into<String>,
+        timeout: CallTimeout,
+    ) -> Self {
+        self.tools.insert((server_id.into(), tool.into()), timeout);
+        self
+    }
+
+    /// Timeout for calling `tool` on `server_id`: the tool's own, else the
+    /// server's, else the default.
+    pub fn timeout_for(&self, server_id: &str, tool: &str) -> CallTimeout {
+        self.tools
+            .get(&(server_id.to_string(), tool.to_string()))
+            .or_else(|| self.servers.get(server_id))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_timeout_wins() {
+        let timeouts = ToolCallTimeouts::new()
+            .with_server_timeout("slow", CallTimeout::After(Duration::from_secs(300)))
+            .with_tool_timeout("slow", "export", CallTimeout::Unlimited);
+
+        assert_eq!(
+            timeouts.timeout_for("github", "search"),
+            CallTimeout::After(DEFAULT_TOOL_CALL_TIMEOUT)
+        );
+        assert_eq!(
+            timeouts.timeout_for("slow", "search"),
+            CallTimeout::After(Duration::from_secs(300))
+        );
+        assert_eq!(
+            timeouts.timeout_for("slow", "export"),
+            CallTimeout::Unlimited
+        );
+        assert_eq!(CallTimeout::Unlimited.duration(), None);
+    }
+
+    #[test]
+    fn test_default_can_be_changed() {
+        let timeouts =
+            ToolCallTimeouts::new().with_default(CallTimeout::After(Duration::from_secs(5)));
+        assert_eq!(
+            timeouts.timeout_for("github", "search").duration(),
+            Some(Duration::from_secs(5))
+        );
+    }
+}
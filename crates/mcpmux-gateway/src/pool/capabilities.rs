@@ -0,0 +1,147 @@
+//! Last-known server capabilities
+//!
+//! Showing a server's tools shouldn't mean reconnecting to it. After each
+//! successful handshake [`ConnectionService`](super::ConnectionService)
+//! records the features the server advertised in a [`CapabilitiesCache`],
+//! keyed by (space, server) and tagged with the hash of the config they came
+//! from. [`PoolService::cached_capabilities`](super::PoolService::cached_capabilities)
+//! hands back the last-known list so the UI can render it straight away and
+//! refresh in the background once the entry is stale.
+//!
+//! An entry is dropped when the server is reconnected or removed from the
+//! pool, and when it is connected with a config whose hash differs.
+
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use uuid::Uuid;
+
+use super::features::CachedFeatures;
+
+/// How long cached capabilities count as fresh.
+pub const DEFAULT_CAPABILITIES_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Capabilities a server advertised on its last successful handshake.
+#[derive(Debug, Clone)]
+pub struct CachedCapabilities {
+    pub features: CachedFeatures,
+    /// [`ResolvedTransport::config_hash`](super::ResolvedTransport::config_hash)
+    /// of the config the server was connected with.
+    pub config_hash: u64,
+    pub cached_at: Instant,
+    expires_at: Instant,
+}
+
+impl CachedCapabilities {
+    /// Whether the TTL has passed and the list should be refreshed.
+    pub fn is_stale(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+}
+
+/// Per-server capabilities from the last successful handshake.
+#[derive(Debug)]
+pub struct CapabilitiesCache {
+    ttl: Duration,
+    entries: DashMap<(Uuid, String), CachedCapabilities>,
+}
+
+impl Default for CapabilitiesCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPABILITIES_TTL)
+    }
+}
+
+impl CapabilitiesCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: DashMap::new(),
+        }
+    }
+
+    /// Record what a server advertised when connected with a config hashing
+    /// to `config_hash`.
+    pub fn insert(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+        config_hash: u64,
+        features: CachedFeatures,
+    ) {
+        let cached_at = Instant::now();
+        self.entries.insert(
+            (space_id, server_id.to_string()),
+            CachedCapabilities {
+                features,
+                config_hash,
+                cached_at,
+                expires_at: cached_at + self.ttl,
+            },
+        );
+    }
+
+    /// Last-known capabilities of a server, stale or not.
+    pub fn get(&self, space_id: Uuid, server_id: &str) -> Option<CachedCapabilities> {
+        self.entries
+            .get(&(space_id, server_id.to_string()))
+            .map(|entry| entry.clone())
+    }
+
+    /// Drop a server's entry if it came from a config other than the one
+    /// hashing to `config_hash`.
+    pub fn invalidate_if_changed(&self, space_id: Uuid, server_id: &str, config_hash: u64) {
+        self.entries
+            .remove_if(&(space_id, server_id.to_string()), |_, cached| {
+                cached.config_hash != config_hash
+            });
+    }
+
+    /// Drop a server's entry.
+    pub fn invalidate(&self, space_id: Uuid, server_id: &str) {
+        self.entries.remove(&(space_id, server_id.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_returns_last_known_until_invalidated() {
+        let cache = CapabilitiesCache::default();
+        let space_id = Uuid::new_v4();
+        assert!(cache.get(space_id, "github").is_none());
+
+        cache.insert(space_id, "github", 1, CachedFeatures::default());
+        let cached = cache.get(space_id, "github").unwrap();
+        assert_eq!(cached.config_hash, 1);
+        assert!(!cached.is_stale());
+        assert!(cache.get(Uuid::new_v4(), "github").is_none());
+
+        cache.invalidate(space_id, "github");
+        assert!(cache.get(space_id, "github").is_none());
+    }
+
+    #[test]
+    fn test_stale_entries_are_still_returned() {
+        let cache = CapabilitiesCache::new(Duration::ZERO);
+        let space_id = Uuid::new_v4();
+
+        cache.insert(space_id, "github", 1, CachedFeatures::default());
+        assert!(cache.get(space_id, "github").unwrap().is_stale());
+    }
+
+    #[test]
+    fn test_config_change_invalidates() {
+        let cache = CapabilitiesCache::default();
+        let space_id = Uuid::new_v4();
+        cache.insert(space_id, "github", 1, CachedFeatures::default());
+
+        cache.invalidate_if_changed(space_id, "github", 1);
+        assert!(cache.get(space_id, "github").is_some());
+
+        cache.invalidate_if_changed(space_id, "github", 2);
+        assert!(cache.get(space_id, "github").is_none());
+    }
+}
@@ -0,0 +1,294 @@
+//! Circuit breaker for server connects
+//!
+//! When a server is down, every tool call would otherwise trigger another
+//! connect attempt, spawning a process or hitting the network each time.
+//! [`CircuitBreaker`] counts consecutive connect failures per (space, server)
+//! and, past a threshold, *opens*: connects fail fast for a cooldown period.
+//! Once the cooldown ends a single trial connect is let through (*half-open*).
+//! Success closes the circuit again; failure reopens it for another cooldown.
+
+use std::time::{Duration, Instant};
+
+use dashmap::DashMap;
+use mcpmux_core::CircuitState;
+use uuid::Uuid;
+
+/// Thresholds for [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive connect failures that open the circuit.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a trial connect is allowed.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A change of circuit state, reported to the UI as
+/// [`DomainEvent::ServerCircuitChanged`](mcpmux_core::DomainEvent::ServerCircuitChanged).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitTransition {
+    pub state: CircuitState,
+    /// Consecutive failures at the time of the change.
+    pub failures: u32,
+    /// Time until a trial connect is allowed (set when opening).
+    pub retry_in: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    state: CircuitState,
+    failures: u32,
+    /// When the circuit opened, or when the current half-open trial started.
+    since: Option<Instant>,
+}
+
+/// Per-server connect circuit breaker.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: DashMap<(Uuid, String), Circuit>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> CircuitBreakerConfig {
+        self.config
+    }
+
+    /// Current state of a server's circuit.
+    pub fn state(&self, space_id: Uuid, server_id: &str) -> CircuitState {
+        self.circuits
+            .get(&(space_id, server_id.to_string()))
+            .map(|c| c.state)
+            .unwrap_or_default()
+    }
+
+    /// Ask whether a connect attempt may go ahead.
+    ///
+    /// Returns `Err(retry_in)` while the circuit is open. When the cooldown
+    /// has passed, the first caller becomes the half-open trial and gets the
+    /// `HalfOpen` transition; later callers are rejected until it finishes.
+    pub fn admit(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+    ) -> Result<Option<CircuitTransition>, Duration> {
+        self.admit_at(space_id, server_id, Instant::now())
+    }
+
+    fn admit_at(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+        now: Instant,
+    ) -> Result<Option<CircuitTransition>, Duration> {
+        let Some(mut circuit) = self.circuits.get_mut(&(space_id, server_id.to_string())) else {
+            return Ok(None);
+        };
+        if circuit.state == CircuitState::Closed {
+            return Ok(None);
+        }
+
+        // A trial that never reported back (e.g. its caller was cancelled)
+        // expires after one cooldown, so the circuit can't get stuck.
+        let elapsed = circuit.since.map_or(self.config.cooldown, |since| {
+            now.saturating_duration_since(since)
+        });
+        if elapsed < self.config.cooldown {
+            return Err(self.config.cooldown - elapsed);
+        }
+
+        let was_half_open = circuit.state == CircuitState::HalfOpen;
+        circuit.state = CircuitState::HalfOpen;
+        circuit.since = Some(now);
+        Ok((!was_half_open).then_some(CircuitTransition {
+            state: CircuitState::HalfOpen,
+            failures: circuit.failures,
+            retry_in: None,
+        }))
+    }
+
+    /// Record a successful connect, closing the circuit.
+    pub fn record_success(&self, space_id: Uuid, server_id: &str) -> Option<CircuitTransition> {
+        let (_, circuit) = self.circuits.remove(&(space_id, server_id.to_string()))?;
+        (circuit.state != CircuitState::Closed).then_some(CircuitTransition {
+            state: CircuitState::Closed,
+            failures: 0,
+            retry_in: None,
+        })
+    }
+
+    /// Record a failed connect, opening the circuit once the threshold is hit.
+    pub fn record_failure(&self, space_id: Uuid, server_id: &str) -> Option<CircuitTransition> {
+        self.record_failure_at(space_id, server_id, Instant::now())
+    }
+
+    fn record_failure_at(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+        now: Instant,
+    ) -> Option<CircuitTransition> {
+        let mut circuit = self
+            .circuits
+            .entry((space_id, server_id.to_string()))
+            .or_default();
+        circuit.failures = circuit.failures.saturating_add(1);
+
+        let opens = match circuit.state {
+            CircuitState::Closed => circuit.failures >= self.config.failure_threshold,
+            CircuitState::HalfOpen => true,
+            // A connect started before the circuit opened; keep the cooldown
+            CircuitState::Open => false,
+        };
+        if !opens {
+            return None;
+        }
+
+        circuit.state = CircuitState::Open;
+        circuit.since = Some(now);
+        Some(CircuitTransition {
+            state: CircuitState::Open,
+            failures: circuit.failures,
+            retry_in: Some(self.config.cooldown),
+        })
+    }
+
+    /// Forget a server's failures, e.g. when the user disconnects or disables it.
+    pub fn reset(&self, space_id: Uuid, server_id: &str) {
+        self.circuits.remove(&(space_id, server_id.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = breaker();
+        let space = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert_eq!(breaker.record_failure_at(space, "srv", now), None);
+        assert_eq!(breaker.record_failure_at(space, "srv", now), None);
+        assert_eq!(breaker.admit_at(space, "srv", now), Ok(None));
+
+        let opened = breaker.record_failure_at(space, "srv", now).unwrap();
+        assert_eq!(opened.state, CircuitState::Open);
+        assert_eq!(opened.failures, 3);
+        assert_eq!(opened.retry_in, Some(Duration::from_secs(30)));
+
+        assert_eq!(
+            breaker.admit_at(space, "srv", now + Duration::from_secs(10)),
+            Err(Duration::from_secs(20))
+        );
+        // Other servers and spaces are unaffected
+        assert_eq!(breaker.admit_at(space, "other", now), Ok(None));
+        assert_eq!(breaker.admit_at(Uuid::new_v4(), "srv", now), Ok(None));
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breaker = breaker();
+        let space = Uuid::new_v4();
+        let now = Instant::now();
+
+        breaker.record_failure_at(space, "srv", now);
+        breaker.record_failure_at(space, "srv", now);
+        assert_eq!(breaker.record_success(space, "srv"), None);
+        assert_eq!(breaker.record_failure_at(space, "srv", now), None);
+        assert_eq!(breaker.state(space, "srv"), CircuitState::Closed);
+    }
+
+    #[test]
+    fn test_half_open_allows_single_trial() {
+        let breaker = breaker();
+        let space = Uuid::new_v4();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(space, "srv", now);
+        }
+
+        let after_cooldown = now + Duration::from_secs(30);
+        let trial = breaker.admit_at(space, "srv", after_cooldown).unwrap();
+        assert_eq!(trial.map(|t| t.state), Some(CircuitState::HalfOpen));
+        assert!(breaker.admit_at(space, "srv", after_cooldown).is_err());
+
+        let closed = breaker.record_success(space, "srv").unwrap();
+        assert_eq!(closed.state, CircuitState::Closed);
+        assert_eq!(breaker.admit_at(space, "srv", after_cooldown), Ok(None));
+    }
+
+    #[test]
+    fn test_failed_trial_reopens() {
+        let breaker = breaker();
+        let space = Uuid::new_v4();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(space, "srv", now);
+        }
+
+        let trial_at = now + Duration::from_secs(31);
+        breaker.admit_at(space, "srv", trial_at).unwrap();
+        let reopened = breaker.record_failure_at(space, "srv", trial_at).unwrap();
+        assert_eq!(reopened.state, CircuitState::Open);
+        assert_eq!(reopened.failures, 4);
+        assert_eq!(
+            breaker.admit_at(space, "srv", trial_at + Duration::from_secs(1)),
+            Err(Duration::from_secs(29))
+        );
+    }
+
+    #[test]
+    fn test_abandoned_trial_expires() {
+        let breaker = breaker();
+        let space = Uuid::new_v4();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(space, "srv", now);
+        }
+
+        let trial_at = now + Duration::from_secs(30);
+        breaker.admit_at(space, "srv", trial_at).unwrap();
+        // The trial never reports back; after another cooldown a new one is allowed
+        assert_eq!(
+            breaker.admit_at(space, "srv", trial_at + Duration::from_secs(30)),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let breaker = breaker();
+        let space = Uuid::new_v4();
+        let now = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure_at(space, "srv", now);
+        }
+        breaker.reset(space, "srv");
+        assert_eq!(breaker.state(space, "srv"), CircuitState::Closed);
+        assert_eq!(breaker.admit_at(space, "srv", now), Ok(None));
+    }
+}
@@ -0,0 +1,286 @@
+//! Command policy for STDIO servers
+//!
+//! Shared and enterprise deployments may not want users running arbitrary
+//! binaries as MCP servers. A [`CommandPolicy`] lists the paths a server's
+//! command may resolve to (an allowlist) and the paths it must not (a
+//! denylist). [`ConnectionService`](super::ConnectionService) checks every
+//! STDIO server's command against it before the transport spawns anything,
+//! failing the connect and recording an audit entry when it is refused.
+//!
+//! Entries and commands are compared as normalized paths, so an entry for a
+//! directory covers everything in it. Normalizing makes paths absolute,
+//! resolves `.`, `..` and symlinks, and case-folds on platforms whose
+//! filesystems ignore case by default (Windows, macOS), so none of those can
+//! be used to slip past an entry.
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use super::transport::{shell_env, CommandResolver, ResolveError, WhichResolver};
+
+/// Whether paths are compared case-insensitively.
+const CASE_INSENSITIVE_PATHS: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Why a command was refused.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CommandPolicyError {
+    /// The command resolves to a denied path.
+    #[error("Command '{command}' ({}) is denied by the command policy", path.display())]
+    Denied { command: String, path: PathBuf },
+    /// The command resolves to a path outside the allowlist.
+    #[error("Command '{command}' ({}) is not allowed by the command policy", path.display())]
+    NotAllowed { command: String, path: PathBuf },
+    /// The command couldn't be resolved, so it can't be shown to be allowed.
+    #[error("Command '{0}' could not be resolved to check the command policy")]
+    Unresolved(String),
+}
+
+/// Allow and deny lists of paths that STDIO server commands may resolve to.
+///
+/// Denied paths win over allowed ones. With no allowed paths, anything not
+/// denied is allowed.
+#[derive(Clone)]
+pub struct CommandPolicy {
+    allow: Vec<PathBuf>,
+    deny: Vec<PathBuf>,
+    resolver: Arc<dyn CommandResolver>,
+}
+
+impl Default for CommandPolicy {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            resolver: Arc::new(WhichResolver),
+        }
+    }
+}
+
+impl std::fmt::Debug for CommandPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CommandPolicy")
+            .field("allow", &self.allow)
+            .field("deny", &self.deny)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CommandPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow commands resolving to `path`, or to anything under it if it is
+    /// a directory.
+    pub fn allow(mut self, path: impl AsRef<Path>) -> Self {
+        self.allow.push(normalize_path(path.as_ref()));
+        self
+    }
+
+    /// Deny commands resolving to `path`, or to anything under it if it is a
+    /// directory.
+    pub fn deny(mut self, path: impl AsRef<Path>) -> Self {
+        self.deny.push(normalize_path(path.as_ref()));
+        self
+    }
+
+    /// Resolve commands with `resolver` instead of [`WhichResolver`].
+    pub fn with_resolver(mut self, resolver: Arc<dyn CommandResolver>) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Whether the policy restricts nothing.
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Resolve `command` as the STDIO transport will, using the user's shell
+    /// PATH, and check the binary it names.
+    ///
+    /// A command that can't be resolved only passes a policy without an
+    /// allowlist; the transport then reports it as not found.
+    pub fn check_command(&self, command: &str) -> Result<(), CommandPolicyError> {
+        let shell_path = shell_env::get_shell_path();
+        self.check_command_in(command, shell_path.as_deref())
+    }
+
+    fn check_command_in(
+        &self,
+        command: &str,
+        shell_path: Option<&OsString>,
+    ) -> Result<(), CommandPolicyError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        match self.resolver.resolve(command, shell_path) {
+            Ok(path) => self.check_path(command, &path),
+            Err(ResolveError::NotFound(_)) if self.allow.is_empty() => Ok(()),
+            Err(_) => Err(CommandPolicyError::Unresolved(command.to_string())),
+        }
+    }
+
+    /// Check the binary `command` resolved to.
+    pub fn check_path(&self, command: &str, path: &Path) -> Result<(), CommandPolicyError> {
+        let normalized = normalize_path(path);
+        let covers = |entry: &PathBuf| normalized.starts_with(entry);
+        if self.deny.iter().any(covers) {
+            return Err(CommandPolicyError::Denied {
+                command: command.to_string(),
+                path: normalized,
+            });
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(covers) {
+            return Err(CommandPolicyError::NotAllowed {
+                command: command.to_string(),
+                path: normalized,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Canonical form of `path` for comparison.
+///
+/// Symlinks can only be resolved for paths that exist; others are cleaned up
+/// lexically.
+fn normalize_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+    let resolved = std::fs::canonicalize(&absolute).unwrap_or_else(|_| clean_path(&absolute));
+    if CASE_INSENSITIVE_PATHS {
+        PathBuf::from(resolved.to_string_lossy().to_lowercase())
+    } else {
+        resolved
+    }
+}
+
+/// Drop `.` components and apply `..` ones without touching the filesystem.
+fn clean_path(path: &Path) -> PathBuf {
+    let mut cleaned = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                cleaned.pop();
+            }
+            other => cleaned.push(other),
+        }
+    }
+    cleaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch directory with an executable-looking file in `bin/`.
+    fn scratch() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mcpmux-policy-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::create_dir_all(dir.join("other")).unwrap();
+        std::fs::write(dir.join("bin").join("server"), "").unwrap();
+        std::fs::write(dir.join("other").join("tool"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_empty_policy_allows_everything() {
+        let policy = CommandPolicy::new();
+        assert!(policy.is_empty());
+        assert_eq!(policy.check_path("sh", Path::new("/bin/sh")), Ok(()));
+    }
+
+    #[test]
+    fn test_allowlist_covers_directories() {
+        let dir = scratch();
+        let policy = CommandPolicy::new().allow(dir.join("bin"));
+
+        assert_eq!(policy.check_path("server", &dir.join("bin/server")), Ok(()));
+        assert!(matches!(
+            policy.check_path("tool", &dir.join("other/tool")),
+            Err(CommandPolicyError::NotAllowed { .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_deny_wins_over_allow() {
+        let dir = scratch();
+        let policy = CommandPolicy::new().allow(&dir).deny(dir.join("other"));
+
+        assert_eq!(policy.check_path("server", &dir.join("bin/server")), Ok(()));
+        assert!(matches!(
+            policy.check_path("tool", &dir.join("other/tool")),
+            Err(CommandPolicyError::Denied { .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_dot_segments_do_not_bypass() {
+        let dir = scratch();
+        let policy = CommandPolicy::new().deny(dir.join("other/tool"));
+
+        let sneaky = dir.join("bin/./../other/./tool");
+        assert!(matches!(
+            policy.check_path("tool", &sneaky),
+            Err(CommandPolicyError::Denied { .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_do_not_bypass() {
+        let dir = scratch();
+        let policy = CommandPolicy::new().deny(dir.join("other/tool"));
+
+        let link = dir.join("bin/innocent");
+        std::os::unix::fs::symlink(dir.join("other/tool"), &link).unwrap();
+        assert!(matches!(
+            policy.check_path("innocent", &link),
+            Err(CommandPolicyError::Denied { .. })
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_case_folding_matches_platform() {
+        let dir = scratch();
+        let policy = CommandPolicy::new().deny(dir.join("other/tool"));
+
+        let shouted = dir.join("other/TOOL");
+        let result = policy.check_path("TOOL", &shouted);
+        if CASE_INSENSITIVE_PATHS {
+            assert!(matches!(result, Err(CommandPolicyError::Denied { .. })));
+        } else {
+            assert_eq!(result, Ok(()));
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unresolved_command_fails_only_with_allowlist() {
+        let missing = "this_command_surely_does_not_exist_xyz";
+        let nowhere = OsString::from("/nonexistent/path");
+
+        let deny_only = CommandPolicy::new().deny("/opt/blocked");
+        assert_eq!(deny_only.check_command_in(missing, Some(&nowhere)), Ok(()));
+
+        let allow = CommandPolicy::new().allow("/opt/allowed");
+        assert_eq!(
+            allow.check_command_in(missing, Some(&nowhere)),
+            Err(CommandPolicyError::Unresolved(missing.to_string()))
+        );
+    }
+}
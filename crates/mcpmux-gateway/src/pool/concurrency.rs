@@ -0,0 +1,227 @@
+//! Per-server limits on concurrent tool calls
+//!
+//! Some MCP servers handle one request at a time and fall over when several
+//! arrive together. [`ConcurrencyLimiter`] caps the calls in flight to each
+//! server id; calls beyond the cap queue until one finishes instead of being
+//! sent. Servers are unbounded unless a limit is configured.
+//!
+//! Limits can be changed while calls are running, without reconnecting:
+//! raising one lets queued calls through at once, lowering one holds new
+//! calls back until enough running ones have finished.
+
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+#[derive(Debug)]
+struct SlotState {
+    limit: Option<usize>,
+    in_flight: usize,
+    queued: usize,
+}
+
+/// Calls to one server: how many may run, how many are, and how many wait.
+#[derive(Debug)]
+struct Slots {
+    state: Mutex<SlotState>,
+    /// Woken whenever a slot may have opened up.
+    freed: Notify,
+}
+
+impl Slots {
+    fn new(limit: Option<usize>) -> Self {
+        Self {
+            state: Mutex::new(SlotState {
+                limit,
+                in_flight: 0,
+                queued: 0,
+            }),
+            freed: Notify::new(),
+        }
+    }
+}
+
+/// A running call's slot, given back when dropped.
+#[derive(Debug)]
+pub struct InFlightPermit {
+    slots: Arc<Slots>,
+}
+
+impl Drop for InFlightPermit {
+    fn drop(&mut self) {
+        self.slots.state.lock().in_flight -= 1;
+        self.slots.freed.notify_waiters();
+    }
+}
+
+/// Counts a waiting call as queued until it gets a slot or gives up.
+struct Queued<'a>(&'a Slots);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.state.lock().queued -= 1;
+    }
+}
+
+/// Caps on concurrent tool calls, keyed by server id.
+#[derive(Debug, Default)]
+pub struct ConcurrencyLimiter {
+    default_limit: Option<usize>,
+    servers: DashMap<String, Arc<Slots>>,
+}
+
+impl ConcurrencyLimiter {
+    /// A limiter with no limits configured; every call goes straight through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow at most `limit` concurrent calls to one server.
+    pub fn with_limit(self, server_id: impl Into<String>, limit: usize) -> Self {
+        self.set_limit(server_id, Some(limit));
+        self
+    }
+
+    /// Allow at most `limit` concurrent calls to servers without a limit of
+    /// their own.
+    pub fn with_default_limit(mut self, limit: usize) -> Self {
+        self.default_limit = Some(limit);
+        self
+    }
+
+    /// Change one server's limit, `None` for unbounded, taking effect for
+    /// calls already queued.
+    ///
+    /// A limit of 0 is treated as 1, so calls are held back but never stuck.
+    pub fn set_limit(&self, server_id: impl Into<String>, limit: Option<usize>) {
+        let limit = limit.map(|limit| limit.max(1));
+        let slots = self.slots(&server_id.into());
+        slots.state.lock().limit = limit;
+        slots.freed.notify_waiters();
+    }
+
+    /// Current limit for a server, `None` if unbounded.
+    pub fn limit(&self, server_id: &str) -> Option<usize> {
+        match self.servers.get(server_id) {
+            Some(slots) => slots.state.lock().limit,
+            None => self.default_limit,
+        }
+    }
+
+    /// Calls to a server that are running.
+    pub fn in_flight(&self, server_id: &str) -> usize {
+        self.servers
+            .get(server_id)
+            .map_or(0, |slots| slots.state.lock().in_flight)
+    }
+
+    /// Calls to a server waiting for a slot.
+    pub fn queued(&self, server_id: &str) -> usize {
+        self.servers
+            .get(server_id)
+            .map_or(0, |slots| slots.state.lock().queued)
+    }
+
+    fn slots(&self, server_id: &str) -> Arc<Slots> {
+        self.servers
+            .entry(server_id.to_string())
+            .or_insert_with(|| Arc::new(Slots::new(self.default_limit)))
+            .clone()
+    }
+
+    /// Wait for a slot to call `server_id`, held until the permit is dropped.
+    pub async fn acquire(&self, server_id: &str) -> InFlightPermit {
+        let slots = self.slots(server_id);
+        let mut queued = None;
+        loop {
+            // Registered before checking, so a slot freed in between isn't missed
+            let freed = slots.freed.notified();
+            tokio::pin!(freed);
+            freed.as_mut().enable();
+            {
+                let mut state = slots.state.lock();
+                if state.limit.is_none_or(|limit| state.in_flight < limit) {
+                    state.in_flight += 1;
+                    drop(state);
+                    drop(queued);
+                    return InFlightPermit {
+                        slots: Arc::clone(&slots),
+                    };
+                }
+                if queued.is_none() {
+                    state.queued += 1;
+                    queued = Some(Queued(&slots));
+                }
+            }
+            freed.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_unconfigured_is_unbounded() {
+        let limiter = ConcurrencyLimiter::new();
+        let permits: Vec<_> =
+            futures::future::join_all((0..10).map(|_| limiter.acquire("s"))).await;
+        assert_eq!(limiter.in_flight("s"), 10);
+        assert_eq!(limiter.limit("s"), None);
+        drop(permits);
+        assert_eq!(limiter.in_flight("s"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_excess_calls_queue_until_a_slot_frees() {
+        let limiter = Arc::new(ConcurrencyLimiter::new().with_limit("single", 1));
+        let first = limiter.acquire("single").await;
+
+        let waiting = tokio::spawn({
+            let limiter = Arc::clone(&limiter);
+            async move {
+                let _permit = limiter.acquire("single").await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(limiter.queued("single"), 1);
+        assert!(!waiting.is_finished());
+
+        drop(first);
+        waiting.await.unwrap();
+        assert_eq!(limiter.queued("single"), 0);
+        assert_eq!(limiter.in_flight("single"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_raising_limit_releases_queued_calls() {
+        let limiter = Arc::new(ConcurrencyLimiter::new().with_default_limit(1));
+        let _first = limiter.acquire("s").await;
+
+        let waiting = tokio::spawn({
+            let limiter = Arc::clone(&limiter);
+            async move { limiter.acquire("s").await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(limiter.queued("s"), 1);
+
+        limiter.set_limit("s", Some(2));
+        let _second = waiting.await.unwrap();
+        assert_eq!(limiter.in_flight("s"), 2);
+        assert_eq!(limiter.queued("s"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_wait_leaves_queue() {
+        let limiter = ConcurrencyLimiter::new().with_limit("s", 1);
+        let _first = limiter.acquire("s").await;
+
+        let timed_out = tokio::time::timeout(Duration::from_millis(20), limiter.acquire("s")).await;
+        assert!(timed_out.is_err());
+        assert_eq!(limiter.queued("s"), 0);
+    }
+}
@@ -4,6 +4,9 @@
 //! - Connecting to MCP servers using the appropriate transport
 //! - Disconnecting from servers (clearing tokens on logout)
 //! - Managing OAuth flow initiation
+//! - Failing fast for servers that keep failing to connect (see [`CircuitBreaker`])
+//! - Refusing STDIO commands the [`CommandPolicy`] doesn't permit
+//! - Remembering what each server advertised (see [`CapabilitiesCache`])
 //!
 //! Uses TokenService for token management and TransportFactory for transport creation.
 
@@ -11,21 +14,24 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
-use mcpmux_core::{CredentialRepository, OutboundOAuthRepository, ServerLogManager};
+use mcpmux_core::{
+    AuditAction, AuditEntry, AuditRepository, CircuitState, CredentialRepository, DomainEvent,
+    OutboundOAuthRepository, ServerLogManager,
+};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+use super::capabilities::CapabilitiesCache;
+use super::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitTransition};
+use super::command_policy::CommandPolicy;
 use super::features::{CachedFeatures, FeatureService};
 use super::instance::{DiscoveredFeatures, McpClientConnection, ServerInstance};
-use super::oauth::{OAuthInitResult, OutboundOAuthManager};
+use super::oauth::{OAuthChallenge, OAuthInitResult, OutboundOAuthManager};
 use super::token::TokenService;
 use super::transport::{
     ResolvedTransport, TransportConnectResult, TransportFactory, TransportType,
 };
 
-/// Default connection timeout
-const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
-
 /// Result of a connection attempt
 #[derive(Debug)]
 pub enum ConnectionResult {
@@ -46,6 +52,13 @@ pub enum ConnectionResult {
         /// Error message
         error: String,
     },
+    /// Not attempted: the server failed too often and is temporarily disabled
+    CircuitOpen {
+        /// Error message
+        error: String,
+        /// Time until a trial connect is allowed
+        retry_in: Duration,
+    },
 }
 
 /// Connection Service handles server connection lifecycle
@@ -56,8 +69,13 @@ pub struct ConnectionService {
     backend_oauth_repo: Arc<dyn OutboundOAuthRepository>,
     prefix_cache: Arc<crate::services::PrefixCacheService>,
     log_manager: Option<Arc<ServerLogManager>>,
-    connect_timeout: Duration,
+    /// Overrides the per-transport default for servers without their own timeout
+    connect_timeout: Option<Duration>,
     event_tx: Option<tokio::sync::broadcast::Sender<mcpmux_core::DomainEvent>>,
+    circuit_breaker: CircuitBreaker,
+    command_policy: Option<Arc<CommandPolicy>>,
+    audit_repo: Option<Arc<dyn AuditRepository>>,
+    capabilities: CapabilitiesCache,
 }
 
 impl ConnectionService {
@@ -75,8 +93,12 @@ impl ConnectionService {
             backend_oauth_repo,
             prefix_cache,
             log_manager: None,
-            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            connect_timeout: None,
             event_tx: None,
+            circuit_breaker: CircuitBreaker::default(),
+            command_policy: None,
+            audit_repo: None,
+            capabilities: CapabilitiesCache::default(),
         }
     }
 
@@ -93,11 +115,42 @@ impl ConnectionService {
         self
     }
 
+    /// Use `timeout` for servers that don't set their own, instead of the
+    /// default for their transport kind.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
-        self.connect_timeout = timeout;
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = CircuitBreaker::new(config);
+        self
+    }
+
+    /// Treat cached capabilities as stale after `ttl` instead of
+    /// [`DEFAULT_CAPABILITIES_TTL`](super::DEFAULT_CAPABILITIES_TTL).
+    pub fn with_capabilities_ttl(mut self, ttl: Duration) -> Self {
+        self.capabilities = CapabilitiesCache::new(ttl);
+        self
+    }
+
+    /// Refuse to spawn STDIO servers whose command `policy` doesn't permit.
+    pub fn with_command_policy(mut self, policy: Arc<CommandPolicy>) -> Self {
+        self.command_policy = Some(policy);
         self
     }
 
+    /// Record refused commands in `audit_repo`.
+    pub fn with_audit_repo(mut self, audit_repo: Arc<dyn AuditRepository>) -> Self {
+        self.audit_repo = Some(audit_repo);
+        self
+    }
+
+    /// Capabilities servers advertised on their last successful handshake.
+    pub fn capabilities(&self) -> &CapabilitiesCache {
+        &self.capabilities
+    }
+
     /// Get the OAuth manager for checking pending flows
     pub fn oauth_manager(&self) -> Arc<OutboundOAuthManager> {
         self.oauth_manager.clone()
@@ -113,6 +166,160 @@ impl ConnectionService {
         self.log_manager.clone()
     }
 
+    /// Current circuit breaker state for a server
+    pub fn circuit_state(&self, space_id: Uuid, server_id: &str) -> CircuitState {
+        self.circuit_breaker.state(space_id, server_id)
+    }
+
+    /// Close a server's circuit and forget its failures
+    ///
+    /// Called when the user disconnects or disables the server, so the next
+    /// explicit connect isn't blocked by an old outage.
+    pub fn reset_circuit(&self, space_id: Uuid, server_id: &str) {
+        self.circuit_breaker.reset(space_id, server_id);
+    }
+
+    /// Check the circuit breaker before connecting
+    ///
+    /// Returns `CircuitOpen` if connects to this server currently fail fast.
+    async fn check_circuit(&self, space_id: &Uuid, server_id: &str) -> Option<ConnectionResult> {
+        match self.circuit_breaker.admit(*space_id, server_id) {
+            Ok(transition) => {
+                if let Some(transition) = transition {
+                    self.report_circuit(space_id, server_id, transition).await;
+                }
+                None
+            }
+            Err(retry_in) => {
+                debug!(
+                    "[ConnectionService] Circuit open for {}/{}, not connecting (retry in {:?})",
+                    space_id, server_id, retry_in
+                );
+                Some(ConnectionResult::CircuitOpen {
+                    error: format!(
+                        "Server temporarily disabled after repeated failures. Retrying in {}s.",
+                        retry_in.as_secs().max(1)
+                    ),
+                    retry_in,
+                })
+            }
+        }
+    }
+
+    /// Feed a connect outcome to the circuit breaker
+    ///
+    /// `OAuthRequired` counts as success: the server is up and answering.
+    async fn record_circuit(&self, space_id: &Uuid, server_id: &str, result: &ConnectionResult) {
+        let transition = match result {
+            ConnectionResult::Connected { .. } | ConnectionResult::OAuthRequired { .. } => {
+                self.circuit_breaker.record_success(*space_id, server_id)
+            }
+            ConnectionResult::Failed { .. } => {
+                self.circuit_breaker.record_failure(*space_id, server_id)
+            }
+            ConnectionResult::CircuitOpen { .. } => None,
+        };
+        if let Some(transition) = transition {
+            self.report_circuit(space_id, server_id, transition).await;
+        }
+    }
+
+    /// Log a circuit state change and emit it as a domain event
+    async fn report_circuit(
+        &self,
+        space_id: &Uuid,
+        server_id: &str,
+        transition: CircuitTransition,
+    ) {
+        let (level, message) = match transition.state {
+            CircuitState::Open => (
+                mcpmux_core::LogLevel::Warn,
+                format!(
+                    "{} consecutive connection failures - pausing connects for {}s",
+                    transition.failures,
+                    transition.retry_in.unwrap_or_default().as_secs()
+                ),
+            ),
+            CircuitState::HalfOpen => (
+                mcpmux_core::LogLevel::Info,
+                "Cooldown over - trying to connect again".to_string(),
+            ),
+            CircuitState::Closed => (
+                mcpmux_core::LogLevel::Info,
+                "Server recovered - connects resumed".to_string(),
+            ),
+        };
+        info!(
+            "[ConnectionService] Circuit for {}/{} is now {}",
+            space_id,
+            server_id,
+            transition.state.as_str()
+        );
+        self.log_connection_event(
+            space_id,
+            server_id,
+            level,
+            message,
+            Some(serde_json::json!({ "circuit": transition.state.as_str() })),
+        )
+        .await;
+
+        if let Some(tx) = &self.event_tx {
+            let _ = tx.send(DomainEvent::ServerCircuitChanged {
+                space_id: *space_id,
+                server_id: server_id.to_string(),
+                state: transition.state,
+                failures: transition.failures,
+                retry_after_ms: transition.retry_in.map(|d| d.as_millis() as u64),
+            });
+        }
+    }
+
+    /// Check a STDIO server's command against the command policy.
+    ///
+    /// Returns the failure to report if it is refused, after logging it and
+    /// recording an audit entry.
+    async fn check_command_policy(
+        &self,
+        space_id: &Uuid,
+        server_id: &str,
+        config: &ResolvedTransport,
+    ) -> Option<ConnectionResult> {
+        let (Some(policy), ResolvedTransport::Stdio { command, .. }) =
+            (&self.command_policy, config)
+        else {
+            return None;
+        };
+        let error = policy.check_command(command).err()?.to_string();
+        warn!(
+            "[ConnectionService] Refusing to start {}/{}: {}",
+            space_id, server_id, error
+        );
+
+        if let Some(audit_repo) = &self.audit_repo {
+            let entry = AuditEntry::new(
+                "gateway",
+                AuditAction::CommandDenied,
+                format!("{}/{}", space_id, server_id),
+            )
+            .with_detail(error.clone());
+            if let Err(e) = audit_repo.record(&entry).await {
+                warn!("[ConnectionService] Failed to audit refused command: {}", e);
+            }
+        }
+
+        self.log_connection_event(
+            space_id,
+            server_id,
+            mcpmux_core::LogLevel::Error,
+            format!("Connection failed: {}", error),
+            Some(serde_json::json!({ "error": &error, "command": command })),
+        )
+        .await;
+
+        Some(ConnectionResult::Failed { error })
+    }
+
     /// Helper method to log connection events to server-specific log files
     async fn log_connection_event(
         &self,
@@ -148,6 +355,21 @@ impl ConnectionService {
         &self,
         ctx: &super::ConnectionContext,
         feature_service: &FeatureService,
+    ) -> ConnectionResult {
+        if let Some(rejected) = self.check_circuit(&ctx.space_id, &ctx.server_id).await {
+            return rejected;
+        }
+        let result = self.try_connect(ctx, feature_service).await;
+        self.record_circuit(&ctx.space_id, &ctx.server_id, &result)
+            .await;
+        result
+    }
+
+    /// Internal: connect without circuit breaker checks
+    async fn try_connect(
+        &self,
+        ctx: &super::ConnectionContext,
+        feature_service: &FeatureService,
     ) -> ConnectionResult {
         let space_id = ctx.space_id;
         let server_id = &ctx.server_id;
@@ -194,6 +416,17 @@ impl ConnectionService {
         )
         .await;
 
+        if let Some(refused) = self
+            .check_command_policy(&space_id, server_id, &final_config)
+            .await
+        {
+            return refused;
+        }
+
+        let config_hash = final_config.config_hash();
+        self.capabilities
+            .invalidate_if_changed(space_id, server_id, config_hash);
+
         // Create transport
         let transport = TransportFactory::create(
             &final_config,
@@ -214,7 +447,11 @@ impl ConnectionService {
                     .discover_and_cache(&space_id.to_string(), server_id, &client)
                     .await
                 {
-                    Ok(f) => f,
+                    Ok(f) => {
+                        self.capabilities
+                            .insert(space_id, server_id, config_hash, f.clone());
+                        f
+                    }
                     Err(e) => {
                         warn!("[ConnectionService] Feature discovery failed: {}", e);
 
@@ -260,7 +497,10 @@ impl ConnectionService {
                     features,
                 }
             }
-            TransportConnectResult::OAuthRequired { server_url } => {
+            TransportConnectResult::OAuthRequired {
+                server_url,
+                challenge,
+            } => {
                 // Log OAuth requirement to server log
                 self.log_connection_event(
                     &space_id,
@@ -271,8 +511,14 @@ impl ConnectionService {
                 )
                 .await;
 
-                self.handle_oauth_required(space_id, server_id, &server_url, auto_reconnect)
-                    .await
+                self.handle_oauth_required(
+                    space_id,
+                    server_id,
+                    &server_url,
+                    challenge,
+                    auto_reconnect,
+                )
+                .await
             }
             TransportConnectResult::Failed(error) => {
                 // Log connection failure to server log
@@ -296,6 +542,24 @@ impl ConnectionService {
         ctx: &super::ConnectionContext,
         instance: &Arc<ServerInstance>,
         feature_service: &FeatureService,
+    ) -> ConnectionResult {
+        if let Some(rejected) = self.check_circuit(&ctx.space_id, &ctx.server_id).await {
+            return rejected;
+        }
+        let result = self
+            .try_connect_with_instance(ctx, instance, feature_service)
+            .await;
+        self.record_circuit(&ctx.space_id, &ctx.server_id, &result)
+            .await;
+        result
+    }
+
+    /// Internal: connect with an existing instance without circuit breaker checks
+    async fn try_connect_with_instance(
+        &self,
+        ctx: &super::ConnectionContext,
+        instance: &Arc<ServerInstance>,
+        feature_service: &FeatureService,
     ) -> ConnectionResult {
         let space_id = ctx.space_id;
         let server_id = &ctx.server_id;
@@ -340,6 +604,20 @@ impl ConnectionService {
 
         instance.mark_connecting();
 
+        if let Some(refused) = self
+            .check_command_policy(&space_id, server_id, config)
+            .await
+        {
+            if let ConnectionResult::Failed { error } = &refused {
+                instance.mark_failed(error.clone());
+            }
+            return refused;
+        }
+
+        let config_hash = config.config_hash();
+        self.capabilities
+            .invalidate_if_changed(space_id, server_id, config_hash);
+
         // Create transport
         let transport = TransportFactory::create(
             config,
@@ -360,7 +638,11 @@ impl ConnectionService {
                     .discover_and_cache(&space_id.to_string(), server_id, &client)
                     .await
                 {
-                    Ok(f) => f,
+                    Ok(f) => {
+                        self.capabilities
+                            .insert(space_id, server_id, config_hash, f.clone());
+                        f
+                    }
                     Err(e) => {
                         warn!("[ConnectionService] Feature discovery failed: {}", e);
                         CachedFeatures::default()
@@ -387,7 +669,7 @@ impl ConnectionService {
                 };
 
                 let connection = match config.transport_type() {
-                    TransportType::Stdio => McpClientConnection::Stdio { client },
+                    TransportType::Stdio => McpClientConnection::Stdio { client, transport },
                     TransportType::Http => McpClientConnection::Http { client },
                 };
 
@@ -405,10 +687,19 @@ impl ConnectionService {
                     features,
                 }
             }
-            TransportConnectResult::OAuthRequired { server_url } => {
+            TransportConnectResult::OAuthRequired {
+                server_url,
+                challenge,
+            } => {
                 instance.mark_oauth_pending();
-                self.handle_oauth_required(space_id, server_id, &server_url, auto_reconnect)
-                    .await
+                self.handle_oauth_required(
+                    space_id,
+                    server_id,
+                    &server_url,
+                    challenge,
+                    auto_reconnect,
+                )
+                .await
             }
             TransportConnectResult::Failed(error) => {
                 instance.mark_failed(error.clone());
@@ -462,6 +753,24 @@ impl ConnectionService {
         server_id: &str,
         instance: &Arc<ServerInstance>,
         feature_service: &FeatureService,
+    ) -> ConnectionResult {
+        if let Some(rejected) = self.check_circuit(&space_id, server_id).await {
+            return rejected;
+        }
+        let result = self
+            .try_reconnect_after_oauth(space_id, server_id, instance, feature_service)
+            .await;
+        self.record_circuit(&space_id, server_id, &result).await;
+        result
+    }
+
+    /// Internal: reconnect after OAuth without circuit breaker checks
+    async fn try_reconnect_after_oauth(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+        instance: &Arc<ServerInstance>,
+        feature_service: &FeatureService,
     ) -> ConnectionResult {
         info!(
             "[ConnectionService] Reconnecting {}/{} after OAuth",
@@ -495,6 +804,10 @@ impl ConnectionService {
             TransportType::Http => ResolvedTransport::Http {
                 url: server_url.clone(),
                 headers: std::collections::HashMap::new(),
+                proxy: None,
+                tls: None,
+                connect_timeout: None,
+                request_timeout: None,
             },
             TransportType::Stdio => {
                 // Should not happen for OAuth, but fallback to Http if somehow we got here
@@ -502,10 +815,17 @@ impl ConnectionService {
                 ResolvedTransport::Http {
                     url: server_url.clone(),
                     headers: std::collections::HashMap::new(),
+                    proxy: None,
+                    tls: None,
+                    connect_timeout: None,
+                    request_timeout: None,
                 }
             }
         };
 
+        // Whatever was cached before authorization may no longer be accurate
+        self.capabilities.invalidate(space_id, server_id);
+
         // Create transport with credential repositories (will inject OAuth token via CredentialStore)
         let transport = TransportFactory::create(
             &config,
@@ -526,7 +846,15 @@ impl ConnectionService {
                     .discover_and_cache(&space_id.to_string(), server_id, &client)
                     .await
                 {
-                    Ok(f) => f,
+                    Ok(f) => {
+                        self.capabilities.insert(
+                            space_id,
+                            server_id,
+                            config.config_hash(),
+                            f.clone(),
+                        );
+                        f
+                    }
                     Err(e) => {
                         warn!(
                             "[ConnectionService] Feature discovery failed after OAuth: {}",
@@ -556,7 +884,7 @@ impl ConnectionService {
                 };
 
                 let connection = match config.transport_type() {
-                    TransportType::Stdio => McpClientConnection::Stdio { client },
+                    TransportType::Stdio => McpClientConnection::Stdio { client, transport },
                     TransportType::Http => McpClientConnection::Http { client },
                 };
 
@@ -592,13 +920,22 @@ impl ConnectionService {
     }
 
     /// Handle OAuth required - initiate OAuth flow (only for manual connects, not auto-reconnect)
+    ///
+    /// `challenge` is set when the transport already started the flow.
     async fn handle_oauth_required(
         &self,
         space_id: Uuid,
         server_id: &str,
         server_url: &str,
+        challenge: Option<OAuthChallenge>,
         auto_reconnect: bool,
     ) -> ConnectionResult {
+        if let Some(challenge) = challenge {
+            return ConnectionResult::OAuthRequired {
+                auth_url: challenge.auth_url,
+            };
+        }
+
         if auto_reconnect {
             // Auto-reconnect: just return OAuthRequired without starting flow or opening browser
             debug!(
@@ -629,7 +966,7 @@ impl ConnectionService {
             )
             .await
         {
-            Ok(OAuthInitResult::Initiated { auth_url }) => {
+            Ok(OAuthInitResult::Initiated(OAuthChallenge { auth_url, .. })) => {
                 // Note: OAuth manager also logs this, but we log from connection service for consistency
                 self.log_connection_event(
                     &space_id,
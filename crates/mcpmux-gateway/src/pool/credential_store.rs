@@ -291,6 +291,7 @@ fn build_token_response(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mcpmux_core::RepoResult;
     use std::sync::Arc;
 
     // Mock implementations for testing
@@ -314,7 +315,7 @@ mod tests {
             space_id: &Uuid,
             server_id: &str,
             credential_type: &CredentialType,
-        ) -> anyhow::Result<Option<Credential>> {
+        ) -> RepoResult<Option<Credential>> {
             let creds = self.credentials.read().await;
             Ok(creds
                 .iter()
@@ -326,11 +327,7 @@ mod tests {
                 .cloned())
         }
 
-        async fn get_all(
-            &self,
-            space_id: &Uuid,
-            server_id: &str,
-        ) -> anyhow::Result<Vec<Credential>> {
+        async fn get_all(&self, space_id: &Uuid, server_id: &str) -> RepoResult<Vec<Credential>> {
             let creds = self.credentials.read().await;
             Ok(creds
                 .iter()
@@ -339,7 +336,7 @@ mod tests {
                 .collect())
         }
 
-        async fn save(&self, credential: &Credential) -> anyhow::Result<()> {
+        async fn save(&self, credential: &Credential) -> RepoResult<()> {
             let mut creds = self.credentials.write().await;
             // Upsert: remove existing with same key, then insert
             creds.retain(|c| {
@@ -356,7 +353,7 @@ mod tests {
             space_id: &Uuid,
             server_id: &str,
             credential_type: &CredentialType,
-        ) -> anyhow::Result<()> {
+        ) -> RepoResult<()> {
             let mut creds = self.credentials.write().await;
             creds.retain(|c| {
                 !(c.space_id == *space_id
@@ -366,13 +363,13 @@ mod tests {
             Ok(())
         }
 
-        async fn delete_all(&self, space_id: &Uuid, server_id: &str) -> anyhow::Result<()> {
+        async fn delete_all(&self, space_id: &Uuid, server_id: &str) -> RepoResult<()> {
             let mut creds = self.credentials.write().await;
             creds.retain(|c| !(c.space_id == *space_id && c.server_id == server_id));
             Ok(())
         }
 
-        async fn clear_tokens(&self, space_id: &Uuid, server_id: &str) -> anyhow::Result<bool> {
+        async fn clear_tokens(&self, space_id: &Uuid, server_id: &str) -> RepoResult<bool> {
             let mut creds = self.credentials.write().await;
             let before = creds.len();
             creds.retain(|c| {
@@ -383,7 +380,23 @@ mod tests {
             Ok(creds.len() < before)
         }
 
-        async fn list_for_space(&self, space_id: &Uuid) -> anyhow::Result<Vec<Credential>> {
+        async fn sweep_expired(&self) -> RepoResult<usize> {
+            let mut creds = self.credentials.write().await;
+            let refreshable: Vec<_> = creds
+                .iter()
+                .filter(|c| c.credential_type == CredentialType::RefreshToken && !c.is_expired())
+                .map(|c| (c.space_id, c.server_id.clone()))
+                .collect();
+            let before = creds.len();
+            creds.retain(|c| {
+                !c.is_expired()
+                    || (c.credential_type == CredentialType::AccessToken
+                        && refreshable.contains(&(c.space_id, c.server_id.clone())))
+            });
+            Ok(before - creds.len())
+        }
+
+        async fn list_for_space(&self, space_id: &Uuid) -> RepoResult<Vec<Credential>> {
             let creds = self.credentials.read().await;
             Ok(creds
                 .iter()
@@ -416,16 +429,16 @@ mod tests {
             &self,
             _space_id: &Uuid,
             _server_id: &str,
-        ) -> anyhow::Result<Option<OutboundOAuthRegistration>> {
+        ) -> RepoResult<Option<OutboundOAuthRegistration>> {
             Ok(self.registration.read().await.clone())
         }
 
-        async fn save(&self, registration: &OutboundOAuthRegistration) -> anyhow::Result<()> {
+        async fn save(&self, registration: &OutboundOAuthRegistration) -> RepoResult<()> {
             *self.registration.write().await = Some(registration.clone());
             Ok(())
         }
 
-        async fn delete(&self, _space_id: &Uuid, _server_id: &str) -> anyhow::Result<()> {
+        async fn delete(&self, _space_id: &Uuid, _server_id: &str) -> RepoResult<()> {
             *self.registration.write().await = None;
             Ok(())
         }
@@ -433,7 +446,7 @@ mod tests {
         async fn list_for_space(
             &self,
             _space_id: &Uuid,
-        ) -> anyhow::Result<Vec<OutboundOAuthRegistration>> {
+        ) -> RepoResult<Vec<OutboundOAuthRegistration>> {
             Ok(vec![])
         }
     }
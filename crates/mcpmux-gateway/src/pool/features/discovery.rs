@@ -116,15 +116,17 @@ impl FeatureDiscoveryService {
 
     /// Mark all features for a server as unavailable (on disconnect)
     pub async fn mark_unavailable(&self, space_id: &str, server_id: &str) -> Result<()> {
-        self.feature_repo
+        Ok(self
+            .feature_repo
             .mark_unavailable(space_id, server_id)
-            .await
+            .await?)
     }
 
     /// Delete all features for a server (on uninstall)
     pub async fn delete_for_server(&self, space_id: &str, server_id: &str) -> Result<()> {
-        self.feature_repo
+        Ok(self
+            .feature_repo
             .delete_for_server(space_id, server_id)
-            .await
+            .await?)
     }
 }
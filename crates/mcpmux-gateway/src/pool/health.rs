@@ -0,0 +1,174 @@
+//! Health checks for HTTP servers
+//!
+//! A remote server can stop answering while its connection still looks fine
+//! to the pool, and the first sign is usually a user's tool call failing.
+//! [`PoolService::start_health_checks`](super::PoolService::start_health_checks)
+//! sends each connected HTTP server an MCP `ping` on an interval and feeds the
+//! outcome to [`HealthTracker`], which counts consecutive failures per
+//! (space, server). Past a threshold the server is marked unhealthy; the next
+//! successful check marks it healthy again.
+
+use std::time::Duration;
+
+use dashmap::DashMap;
+use mcpmux_core::ServerHealth;
+use uuid::Uuid;
+
+/// Interval and thresholds for health checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthCheckConfig {
+    /// Time between checks of each server.
+    pub interval: Duration,
+    /// How long a server gets to answer a check.
+    pub timeout: Duration,
+    /// Consecutive failed checks that mark a server unhealthy.
+    pub failure_threshold: u32,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            failure_threshold: 3,
+        }
+    }
+}
+
+/// A change of health, reported to the UI as
+/// [`DomainEvent::ServerHealthChanged`](mcpmux_core::DomainEvent::ServerHealthChanged).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthTransition {
+    pub health: ServerHealth,
+    /// Consecutive failed checks at the time of the change.
+    pub failures: u32,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    health: ServerHealth,
+    failures: u32,
+}
+
+/// Per-server health state, driven by check outcomes.
+#[derive(Debug, Default)]
+pub struct HealthTracker {
+    config: HealthCheckConfig,
+    servers: DashMap<(Uuid, String), Health>,
+}
+
+impl HealthTracker {
+    pub fn new(config: HealthCheckConfig) -> Self {
+        Self {
+            config,
+            servers: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> HealthCheckConfig {
+        self.config
+    }
+
+    /// Current health of a server. Servers never checked count as healthy.
+    pub fn health(&self, space_id: Uuid, server_id: &str) -> ServerHealth {
+        self.servers
+            .get(&(space_id, server_id.to_string()))
+            .map(|h| h.health)
+            .unwrap_or_default()
+    }
+
+    /// Record a successful check, marking the server healthy.
+    pub fn record_success(&self, space_id: Uuid, server_id: &str) -> Option<HealthTransition> {
+        let (_, health) = self.servers.remove(&(space_id, server_id.to_string()))?;
+        (health.health != ServerHealth::Healthy).then_some(HealthTransition {
+            health: ServerHealth::Healthy,
+            failures: 0,
+        })
+    }
+
+    /// Record a failed check, marking the server unhealthy once the threshold is hit.
+    pub fn record_failure(&self, space_id: Uuid, server_id: &str) -> Option<HealthTransition> {
+        let mut health = self
+            .servers
+            .entry((space_id, server_id.to_string()))
+            .or_default();
+        health.failures = health.failures.saturating_add(1);
+
+        if health.health == ServerHealth::Unhealthy
+            || health.failures < self.config.failure_threshold
+        {
+            return None;
+        }
+        health.health = ServerHealth::Unhealthy;
+        Some(HealthTransition {
+            health: ServerHealth::Unhealthy,
+            failures: health.failures,
+        })
+    }
+
+    /// Forget a server's check history, e.g. when it is disconnected.
+    pub fn reset(&self, space_id: Uuid, server_id: &str) {
+        self.servers.remove(&(space_id, server_id.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> HealthTracker {
+        HealthTracker::new(HealthCheckConfig {
+            failure_threshold: 2,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_unhealthy_after_threshold() {
+        let tracker = tracker();
+        let space = Uuid::new_v4();
+
+        assert_eq!(tracker.record_failure(space, "srv"), None);
+        assert_eq!(tracker.health(space, "srv"), ServerHealth::Healthy);
+
+        let unhealthy = tracker.record_failure(space, "srv").unwrap();
+        assert_eq!(unhealthy.health, ServerHealth::Unhealthy);
+        assert_eq!(unhealthy.failures, 2);
+        assert_eq!(tracker.health(space, "srv"), ServerHealth::Unhealthy);
+
+        // Further failures don't report again
+        assert_eq!(tracker.record_failure(space, "srv"), None);
+        // Other servers and spaces are unaffected
+        assert_eq!(tracker.health(space, "other"), ServerHealth::Healthy);
+        assert_eq!(tracker.health(Uuid::new_v4(), "srv"), ServerHealth::Healthy);
+    }
+
+    #[test]
+    fn test_success_recovers() {
+        let tracker = tracker();
+        let space = Uuid::new_v4();
+
+        tracker.record_failure(space, "srv");
+        // A success below the threshold just resets the count
+        assert_eq!(tracker.record_success(space, "srv"), None);
+        assert_eq!(tracker.record_failure(space, "srv"), None);
+
+        tracker.record_failure(space, "srv");
+        let recovered = tracker.record_success(space, "srv").unwrap();
+        assert_eq!(recovered.health, ServerHealth::Healthy);
+        assert_eq!(tracker.health(space, "srv"), ServerHealth::Healthy);
+        assert_eq!(tracker.record_success(space, "srv"), None);
+    }
+
+    #[test]
+    fn test_reset() {
+        let tracker = tracker();
+        let space = Uuid::new_v4();
+        tracker.record_failure(space, "srv");
+        tracker.record_failure(space, "srv");
+
+        tracker.reset(space, "srv");
+        assert_eq!(tracker.health(space, "srv"), ServerHealth::Healthy);
+        assert_eq!(tracker.record_failure(space, "srv"), None);
+    }
+}
@@ -4,7 +4,7 @@
 //! No sharing between spaces - this is a security boundary.
 
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use std::sync::Arc;
 
@@ -20,6 +20,8 @@ use uuid::Uuid;
 // Re-export TransportType from mcpmux-core as the single source of truth
 pub use mcpmux_core::TransportType;
 
+use super::transport::Transport;
+
 /// Type alias for the MCP client service
 pub type McpClient = RunningService<RoleClient, McpClientHandler>;
 
@@ -332,6 +334,8 @@ pub struct InstanceStats {
     pub consecutive_failures: u32,
     /// Total requests served
     pub requests_served: u64,
+    /// Number of successful connects, including reconnects
+    pub connect_count: u64,
     /// Last error message
     pub last_error: Option<String>,
 }
@@ -344,6 +348,7 @@ impl Default for InstanceStats {
             last_attempt: None,
             consecutive_failures: 0,
             requests_served: 0,
+            connect_count: 0,
             last_error: None,
         }
     }
@@ -368,8 +373,13 @@ pub struct ServerInstance {
 
 /// The actual MCP client connection.
 pub enum McpClientConnection {
-    /// STDIO transport - child process with MCP client
-    Stdio { client: McpClient },
+    /// STDIO transport - child process with MCP client.
+    ///
+    /// The transport is kept so the child process can be shut down gracefully.
+    Stdio {
+        client: McpClient,
+        transport: Box<dyn Transport>,
+    },
     /// HTTP transport - streamable HTTP
     Http { client: McpClient },
 }
@@ -378,7 +388,7 @@ impl McpClientConnection {
     /// Get the MCP client for issuing requests.
     pub fn client(&self) -> Option<&McpClient> {
         match self {
-            Self::Stdio { client } => Some(client),
+            Self::Stdio { client, .. } => Some(client),
             Self::Http { client } => Some(client),
         }
     }
@@ -419,6 +429,7 @@ impl ServerInstance {
         let mut stats = self.stats.write();
         stats.state = InstanceState::Connected;
         stats.connected_at = Some(Instant::now());
+        stats.connect_count += 1;
         stats.consecutive_failures = 0;
         stats.last_error = None;
 
@@ -426,6 +437,26 @@ impl ServerInstance {
         *self.client.write() = Some(connection);
     }
 
+    /// Disconnect, giving a STDIO server process up to `grace` to exit cleanly.
+    ///
+    /// The client is dropped first so the server sees its stdin close before
+    /// being signalled. Dropping an instance without calling this still kills
+    /// the process, just not gracefully.
+    ///
+    /// Returns `false` if the process had to be force-killed.
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        let connection = self.client.write().take();
+        self.stats.write().state = InstanceState::Disconnected;
+
+        match connection {
+            Some(McpClientConnection::Stdio { client, transport }) => {
+                drop(client);
+                transport.shutdown(grace).await
+            }
+            _ => true,
+        }
+    }
+
     /// Update state to failed.
     pub fn mark_failed(&self, error: String) {
         let mut stats = self.stats.write();
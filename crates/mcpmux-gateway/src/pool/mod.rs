@@ -8,15 +8,25 @@
 //! - **ConnectionService**: Handles connect/disconnect lifecycle
 //! - **FeatureService**: Discovers and caches MCP features
 //! - **RoutingService**: Dispatches requests with permission filtering
+//! - **CircuitBreaker**: Fails connects fast for servers that keep failing
+//! - **CallRateLimiter**: Caps the rate of tool calls sent to each server
+//! - **HealthTracker**: Marks HTTP servers unhealthy when they stop answering pings
 //! - **PoolService**: Orchestrates all services
 
+mod call_timeout;
+mod capabilities;
+mod circuit_breaker;
+mod command_policy;
+mod concurrency;
 mod connection;
 mod context;
 mod credential_store;
 mod features;
+mod health;
 mod instance;
 mod oauth;
 mod oauth_utils;
+mod rate_limit;
 mod routing;
 mod server_manager;
 mod service;
@@ -36,16 +46,30 @@ pub use instance::{
 // OAuth
 pub use credential_store::DatabaseCredentialStore;
 pub use oauth::{
-    OAuthCallback, OAuthCompleteEvent, OAuthInitResult, OAuthTokenInfo, OutboundOAuthManager,
+    OAuthCallback, OAuthChallenge, OAuthCompleteEvent, OAuthInitResult, OAuthTokenInfo,
+    OutboundOAuthManager,
 };
 
 // SOLID Services
+pub use call_timeout::{CallTimeout, ToolCallTimeout, ToolCallTimeouts, DEFAULT_TOOL_CALL_TIMEOUT};
+pub use capabilities::{CachedCapabilities, CapabilitiesCache, DEFAULT_CAPABILITIES_TTL};
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitTransition};
+pub use command_policy::{CommandPolicy, CommandPolicyError};
+pub use concurrency::{ConcurrencyLimiter, InFlightPermit};
 pub use connection::{ConnectionResult, ConnectionService};
 pub use features::{CachedFeatures, FeatureService};
+pub use health::{HealthCheckConfig, HealthTracker, HealthTransition};
+pub use rate_limit::{CallRateLimiter, RateLimit, RateLimitAction};
 pub use routing::{RoutedPrompt, RoutedResource, RoutedTool, RoutingService};
-pub use service::{InstalledServerInfo, PoolService, PoolStats, ReconnectResult};
+pub use service::{
+    InstalledServerInfo, PoolService, PoolStats, ReconnectResult, ServerConnectionState,
+    ServerStats,
+};
 pub use token::TokenService;
-pub use transport::{ResolvedTransport, Transport, TransportConnectResult, TransportFactory};
+pub use transport::{
+    ResolvedTransport, SecretBinding, SecretReferenceUnresolved, SecretSource, Transport,
+    TransportConnectResult, TransportFactory, ValidationCheck, ValidationResult,
+};
 
 // Server Manager (Event-driven orchestrator)
 pub use server_manager::{ConnectResult, ConnectionStatus, ServerKey, ServerManager, ServerState};
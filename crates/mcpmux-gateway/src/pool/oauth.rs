@@ -47,11 +47,25 @@ pub struct OAuthCallback {
     pub error_description: Option<String>,
 }
 
+/// What the UI needs to drive a started OAuth flow
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct OAuthChallenge {
+    /// Authorization URL to open in the browser
+    pub auth_url: String,
+    /// Scopes requested in `auth_url`
+    pub scopes: Vec<String>,
+    /// Loopback URI the authorization server redirects back to
+    pub redirect_uri: String,
+    /// OAuth `state`; also the handle to the flow's PKCE verifier, which never
+    /// leaves the gateway. The callback must echo it back.
+    pub state: String,
+}
+
 /// Result of initiating OAuth flow
 #[derive(Debug)]
 pub enum OAuthInitResult {
-    /// OAuth flow initiated - browser should open auth_url
-    Initiated { auth_url: String },
+    /// OAuth flow initiated - browser should open the challenge's auth_url
+    Initiated(OAuthChallenge),
     /// Already have valid credentials
     AlreadyAuthorized,
     /// OAuth not supported by server
@@ -731,7 +745,7 @@ impl OutboundOAuthManager {
         server_id: &str,
     ) -> Option<OAuthTokenInfo> {
         // Load access token row
-        let access_cred = match credential_repo
+        let mut access_cred = match credential_repo
             .get(&space_id, server_id, &CredentialType::AccessToken)
             .await
         {
@@ -751,18 +765,19 @@ impl OutboundOAuthManager {
             .get(&space_id, server_id, &CredentialType::RefreshToken)
             .await
         {
-            Ok(Some(cred)) => Some(cred.value),
+            Ok(Some(mut cred)) => Some(std::mem::take(&mut cred.value)),
             _ => None,
         };
 
         Some(OAuthTokenInfo {
-            access_token: access_cred.value,
+            access_token: std::mem::take(&mut access_cred.value),
             refresh_token,
             expires_at: access_cred.expires_at,
             token_type: access_cred
                 .token_type
+                .take()
                 .unwrap_or_else(|| "Bearer".to_string()),
-            scope: access_cred.scope,
+            scope: access_cred.scope.take(),
         })
     }
 
@@ -1648,7 +1663,83 @@ impl OutboundOAuthManager {
             info!("[OAuth] Callback handler completed for {}", server_id_clone);
         });
 
-        Ok(OAuthInitResult::Initiated { auth_url })
+        let scopes = Self::extract_scopes_from_url(&auth_url);
+        Ok(OAuthInitResult::Initiated(OAuthChallenge {
+            auth_url,
+            scopes,
+            redirect_uri,
+            state,
+        }))
+    }
+
+    /// Complete a pending flow with the code and state from the redirect.
+    ///
+    /// `state` must match the flow started for this server; anything else is
+    /// rejected without touching the flow, so a forged redirect can't bind
+    /// someone else's authorization to it (CSRF). Waits for the token
+    /// exchange, which stores the tokens through the flow's credential store.
+    pub async fn complete_flow(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+        code: &str,
+        state: &str,
+    ) -> Result<()> {
+        let key = (space_id, server_id.to_string());
+        let expected = self
+            .active_by_server
+            .get(&key)
+            .map(|s| s.value().clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!("No OAuth flow in progress for {}/{}", space_id, server_id)
+            })?;
+        if expected != state {
+            warn!(
+                "[OAuth] State mismatch for {}/{}; rejecting callback",
+                space_id, server_id
+            );
+            self.log(
+                &space_id.to_string(),
+                server_id,
+                LogLevel::Warn,
+                "Rejected OAuth callback with mismatched state".to_string(),
+                None,
+            )
+            .await;
+            anyhow::bail!("OAuth state mismatch");
+        }
+
+        // Subscribe before forwarding so the completion can't be missed
+        let mut completions = self.completion_tx.subscribe();
+        self.handle_callback(OAuthCallback {
+            code: Some(code.to_string()),
+            state: state.to_string(),
+            error: None,
+            error_description: None,
+        })
+        .map_err(|e| anyhow::anyhow!(e))?;
+
+        let wait = async {
+            loop {
+                match completions.recv().await {
+                    Ok(event) if event.space_id == space_id && event.server_id == server_id => {
+                        return match event.error {
+                            None if event.success => Ok(()),
+                            error => Err(anyhow::anyhow!(
+                                error.unwrap_or_else(|| "OAuth flow failed".to_string())
+                            )),
+                        };
+                    }
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                        anyhow::bail!("OAuth manager shut down")
+                    }
+                }
+            }
+        };
+        tokio::time::timeout(self.timeout, wait)
+            .await
+            .map_err(|_| anyhow::anyhow!("Timed out waiting for token exchange"))?
     }
 
     /// Extract state parameter from auth URL
@@ -1660,6 +1751,18 @@ impl OutboundOAuthManager {
             .map(|(_, v)| v.to_string())
     }
 
+    /// Extract the space-separated `scope` parameter from auth URL
+    fn extract_scopes_from_url(url: &str) -> Vec<String> {
+        url::Url::parse(url)
+            .ok()
+            .and_then(|url| {
+                url.query_pairs()
+                    .find(|(k, _)| k == "scope")
+                    .map(|(_, v)| v.split_whitespace().map(str::to_string).collect())
+            })
+            .unwrap_or_default()
+    }
+
     /// Get the AuthorizationManager from a completed OAuth flow
     pub async fn get_authorized_manager(
         &self,
@@ -1684,3 +1787,78 @@ impl Default for OutboundOAuthManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_complete_flow_rejects_mismatched_state() {
+        let manager = OutboundOAuthManager::new();
+        let space_id = Uuid::new_v4();
+        let mut callback_rx = manager.register_pending_flow(
+            "expected-state".to_string(),
+            space_id,
+            "srv".to_string(),
+            "https://example.com/mcp".to_string(),
+        );
+
+        let err = manager
+            .complete_flow(space_id, "srv", "code", "forged-state")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("state mismatch"));
+        // The real flow is untouched
+        assert!(manager.is_pending(space_id, "srv"));
+        assert!(callback_rx.try_recv().is_err());
+
+        let err = manager
+            .complete_flow(space_id, "other", "code", "expected-state")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No OAuth flow in progress"));
+    }
+
+    #[tokio::test]
+    async fn test_complete_flow_waits_for_exchange() {
+        let manager = OutboundOAuthManager::new();
+        let space_id = Uuid::new_v4();
+        let callback_rx = manager.register_pending_flow(
+            "state-1".to_string(),
+            space_id,
+            "srv".to_string(),
+            "https://example.com/mcp".to_string(),
+        );
+
+        // Stand in for the task that exchanges the code
+        let completion_tx = manager.completion_tx.clone();
+        tokio::spawn(async move {
+            let callback = callback_rx.await.unwrap();
+            assert_eq!(callback.code.as_deref(), Some("the-code"));
+            completion_tx
+                .send(OAuthCompleteEvent {
+                    space_id,
+                    server_id: "srv".to_string(),
+                    success: false,
+                    error: Some("Token exchange failed: invalid_grant".to_string()),
+                })
+                .unwrap();
+        });
+
+        let err = manager
+            .complete_flow(space_id, "srv", "the-code", "state-1")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid_grant"));
+    }
+
+    #[test]
+    fn test_extract_scopes_from_url() {
+        let url = "https://auth.example.com/authorize?state=abc&scope=read%20write+admin";
+        assert_eq!(
+            OutboundOAuthManager::extract_scopes_from_url(url),
+            vec!["read", "write", "admin"]
+        );
+        assert!(OutboundOAuthManager::extract_scopes_from_url("https://x.test/a").is_empty());
+    }
+}
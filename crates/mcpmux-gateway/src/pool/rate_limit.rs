@@ -0,0 +1,255 @@
+//! Per-server rate limiting of outbound tool calls
+//!
+//! A client stuck in a loop can send thousands of calls per second, which a
+//! downstream server (or the API behind it) may answer by rate-limiting or
+//! banning us. [`CallRateLimiter`] keeps a token bucket per server id: calls
+//! within `max_calls_per_sec` plus a burst allowance go straight through,
+//! calls beyond it are delayed or rejected.
+//!
+//! Servers without a configured limit are not tracked at all. Each bucket has
+//! its own lock, held only to reserve a token, so calls to different servers
+//! never wait on each other.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tokio::time::Instant;
+use tracing::debug;
+
+/// What to do with a call that exceeds its server's rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitAction {
+    /// Wait for a token, rejecting the call if that would take longer than `max_wait`.
+    Delay { max_wait: Duration },
+    /// Reject immediately.
+    Reject,
+}
+
+/// Rate limit for one server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Sustained rate.
+    pub max_calls_per_sec: f64,
+    /// Calls allowed back to back before the rate applies.
+    pub burst: u32,
+    pub action: RateLimitAction,
+}
+
+impl RateLimit {
+    /// Delay excess calls by up to 10s.
+    pub fn new(max_calls_per_sec: f64, burst: u32) -> Self {
+        Self {
+            max_calls_per_sec,
+            burst: burst.max(1),
+            action: RateLimitAction::Delay {
+                max_wait: Duration::from_secs(10),
+            },
+        }
+    }
+
+    pub fn with_action(mut self, action: RateLimitAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    /// May go negative: delayed calls reserve tokens that haven't refilled yet.
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst),
+            refilled_at: now,
+        }
+    }
+
+    /// Take a token, returning how long the caller must wait for it, or
+    /// `Err(wait)` if the call is rejected.
+    fn reserve(&mut self, now: Instant) -> Result<Duration, Duration> {
+        let rate = self.limit.max_calls_per_sec;
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate).min(f64::from(self.limit.burst));
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(Duration::ZERO);
+        }
+
+        let wait = Duration::from_secs_f64((1.0 - self.tokens) / rate);
+        match self.limit.action {
+            RateLimitAction::Delay { max_wait } if wait <= max_wait => {
+                self.tokens -= 1.0;
+                Ok(wait)
+            }
+            _ => Err(wait),
+        }
+    }
+}
+
+/// Token-bucket rate limiter for tool calls, keyed by server id.
+#[derive(Debug, Default)]
+pub struct CallRateLimiter {
+    limits: HashMap<String, RateLimit>,
+    default_limit: Option<RateLimit>,
+    buckets: DashMap<String, Mutex<Bucket>>,
+}
+
+impl CallRateLimiter {
+    /// A limiter with no limits configured; every call goes through.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit calls to one server.
+    pub fn with_limit(mut self, server_id: impl Into<String>, limit: RateLimit) -> Self {
+        self.limits.insert(server_id.into(), limit);
+        self
+    }
+
+    /// Limit calls to servers without a limit of their own.
+    pub fn with_default_limit(mut self, limit: RateLimit) -> Self {
+        self.default_limit = Some(limit);
+        self
+    }
+
+    fn limit_for(&self, server_id: &str) -> Option<RateLimit> {
+        self.limits.get(server_id).copied().or(self.default_limit)
+    }
+
+    /// Wait until a call to `server_id` is allowed.
+    ///
+    /// Returns an error if the call exceeds the limit and can't be delayed.
+    pub async fn acquire(&self, server_id: &str) -> Result<()> {
+        let Some(limit) = self.limit_for(server_id) else {
+            return Ok(());
+        };
+        if limit.max_calls_per_sec <= 0.0 {
+            return Err(anyhow!("Calls to server '{}' are disabled", server_id));
+        }
+
+        let now = Instant::now();
+        let reserved = self
+            .buckets
+            .entry(server_id.to_string())
+            .or_insert_with(|| Mutex::new(Bucket::new(limit, now)))
+            .lock()
+            .reserve(now);
+
+        match reserved {
+            Ok(wait) if wait.is_zero() => Ok(()),
+            Ok(wait) => {
+                debug!(
+                    "[RateLimiter] Delaying call to {} by {:?} (limit {}/s)",
+                    server_id, wait, limit.max_calls_per_sec
+                );
+                tokio::time::sleep(wait).await;
+                Ok(())
+            }
+            Err(wait) => Err(anyhow!(
+                "Rate limit exceeded for server '{}' ({} calls/sec, burst {}). Retry in {}ms.",
+                server_id,
+                limit.max_calls_per_sec,
+                limit.burst,
+                wait.as_millis().max(1)
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unconfigured_is_noop() {
+        let limiter = CallRateLimiter::new();
+        for _ in 0..1000 {
+            limiter.acquire("srv").await.unwrap();
+        }
+        assert!(limiter.buckets.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_then_delay() {
+        let limiter = CallRateLimiter::new().with_limit("srv", RateLimit::new(10.0, 3));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            limiter.acquire("srv").await.unwrap();
+        }
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // Further calls are paced at 100ms apart
+        limiter.acquire("srv").await.unwrap();
+        limiter.acquire("srv").await.unwrap();
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= Duration::from_millis(199) && elapsed <= Duration::from_millis(201),
+            "{:?}",
+            elapsed
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reject_mode() {
+        let limit = RateLimit::new(1.0, 2).with_action(RateLimitAction::Reject);
+        let limiter = CallRateLimiter::new().with_limit("srv", limit);
+
+        limiter.acquire("srv").await.unwrap();
+        limiter.acquire("srv").await.unwrap();
+        let err = limiter.acquire("srv").await.unwrap_err().to_string();
+        assert!(
+            err.contains("Rate limit exceeded for server 'srv'"),
+            "{}",
+            err
+        );
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        limiter.acquire("srv").await.unwrap();
+    }
+
+    #[test]
+    fn test_delay_beyond_max_wait_rejects() {
+        let limit = RateLimit::new(1.0, 1).with_action(RateLimitAction::Delay {
+            max_wait: Duration::from_millis(1500),
+        });
+        let now = Instant::now();
+        let mut bucket = Bucket::new(limit, now);
+
+        assert_eq!(bucket.reserve(now), Ok(Duration::ZERO));
+        // Reserves the token due in 1s
+        assert_eq!(bucket.reserve(now), Ok(Duration::from_secs(1)));
+        // The next one is 2s away, beyond max_wait; nothing is reserved
+        assert_eq!(bucket.reserve(now), Err(Duration::from_secs(2)));
+        assert_eq!(
+            bucket.reserve(now + Duration::from_secs(1)),
+            Ok(Duration::from_secs(1))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_servers_are_independent() {
+        let limit = RateLimit::new(1.0, 1).with_action(RateLimitAction::Reject);
+        let limiter = CallRateLimiter::new()
+            .with_limit("a", limit)
+            .with_default_limit(limit);
+
+        limiter.acquire("a").await.unwrap();
+        assert!(limiter.acquire("a").await.is_err());
+        // Default limit applies to other servers, each with its own bucket
+        limiter.acquire("b").await.unwrap();
+        limiter.acquire("c").await.unwrap();
+        assert!(limiter.acquire("b").await.is_err());
+    }
+}
@@ -2,13 +2,13 @@
 //!
 //! RoutingService handles:
 //! - Listing tools/prompts/resources filtered by client grants
-//! - Dispatching tool calls to the correct backend server
+//! - Dispatching tool calls to the correct backend server, within its rate
+//!   limit, concurrency limit and call timeout
 //! - Handling 401 errors with automatic token refresh and retry
 //!
 //! Uses FeatureService for permission resolution and TokenService for refresh.
 
 use std::sync::Arc;
-use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use mcpmux_core::{FeatureType, LogLevel, LogSource, ServerLog, ServerLogManager};
@@ -17,6 +17,7 @@ use serde_json::Value;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+use super::call_timeout::ToolCallTimeout;
 use super::connection::ConnectionResult;
 use super::features::FeatureService;
 use super::service::PoolService;
@@ -54,9 +55,6 @@ pub struct ToolCallResult {
     pub is_error: bool,
 }
 
-/// Default timeout for MCP tool calls (60 seconds)
-const TOOL_CALL_TIMEOUT: Duration = Duration::from_secs(60);
-
 /// RoutingService dispatches requests to backend MCP servers
 pub struct RoutingService {
     feature_service: Arc<FeatureService>,
@@ -274,6 +272,12 @@ impl RoutingService {
                 .get_instance(space_id, &server_id)
                 .ok_or_else(|| anyhow!("Server not connected: {}", server_id))?;
 
+            // Delays or rejects calls over the server's configured rate
+            pool.rate_limiter().acquire(&server_id).await?;
+            // Queues behind other calls if the server is at its concurrency limit
+            let _permit = pool.concurrency_limiter().acquire(&server_id).await;
+            let timeout = pool.call_timeouts().timeout_for(&server_id, &tool_name);
+
             // We need to get the service handle (peer) which is cloneable
             // But we don't have direct access to it via with_client easily because with_client
             // passes &McpClient (RunningService).
@@ -284,17 +288,25 @@ impl RoutingService {
             match client_handle {
                 Some(client) => {
                     let params = CallToolRequestParams {
-                        name: tool_name.into(),
+                        name: tool_name.clone().into(),
                         arguments: args.as_object().cloned(),
                         task: None,
                         meta: None,
                     };
 
-                    // Wrap call_tool with timeout to prevent hanging
-                    let res = tokio::time::timeout(TOOL_CALL_TIMEOUT, client.call_tool(params))
-                        .await
-                        .map_err(|_| anyhow!("Tool call timed out after {:?}", TOOL_CALL_TIMEOUT))?
-                        .map_err(|e| anyhow!("MCP call failed: {}", e))?;
+                    // Bound the call so a hanging server can't block the caller
+                    let call = client.call_tool(params);
+                    let res = match timeout.duration() {
+                        Some(limit) => tokio::time::timeout(limit, call).await.map_err(|_| {
+                            ToolCallTimeout {
+                                server_id: server_id.clone(),
+                                tool: tool_name.clone(),
+                                timeout: limit,
+                            }
+                        })?,
+                        None => call.await,
+                    }
+                    .map_err(|e| anyhow!("MCP call failed: {}", e))?;
 
                     let content: Vec<Value> = res
                         .content
@@ -317,7 +329,11 @@ impl RoutingService {
         // automatically on every HTTP request when needed.
         info!(
             "[RoutingService] Executing tool call: {} on {} (timeout: {:?})",
-            actual_tool_name, server_id, TOOL_CALL_TIMEOUT
+            actual_tool_name,
+            server_id,
+            self.pool_service
+                .call_timeouts()
+                .timeout_for(&server_id, &actual_tool_name)
         );
 
         let call_start = std::time::Instant::now();
@@ -6,6 +6,8 @@
 //! - No UI polling: all updates via events
 //!
 //! State machine with race condition prevention:
+//! - connection: `ConnectionState` per server, changed only in `set_state`,
+//!   which emits `DomainEvent::StateChanged` for every transition
 //! - flow_id: Monotonic counter, incremented on state-changing operations
 //! - connect_lock, auth_lock, refresh_lock: Prevent concurrent operations
 //! - Stale callbacks/timeouts validated against flow_id
@@ -15,7 +17,10 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
-use mcpmux_core::{DiscoveredCapabilities, DomainEvent};
+use mcpmux_core::{
+    ConnectionState, DiscoveredCapabilities, DomainEvent, EventFilter, FilteredEventReceiver,
+    ServerHealth,
+};
 use tokio::sync::{broadcast, Mutex, OwnedMutexGuard, RwLock};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, trace, warn};
@@ -123,6 +128,8 @@ pub struct AuthFlowState {
 pub struct ServerState {
     /// Current connection status
     pub status: ConnectionStatus,
+    /// Lifecycle state; only changed through `ServerManager::set_state`
+    connection: ConnectionState,
     /// Monotonic counter for race condition prevention
     pub flow_id: u64,
     /// Whether user has successfully connected before (for Connect vs Reconnect button)
@@ -156,6 +163,7 @@ impl Default for ServerState {
     fn default() -> Self {
         Self {
             status: ConnectionStatus::Disconnected,
+            connection: ConnectionState::Idle,
             flow_id: 0,
             has_connected_before: false,
             features: None,
@@ -340,6 +348,42 @@ impl ServerManager {
         }
     }
 
+    /// Set the status along with the connection state it implies
+    fn set_status(&self, key: &ServerKey, state: &mut ServerState, status: ConnectionStatus) {
+        let to = ConnectionState::from(self.to_core_status(status));
+        self.set_state(key, state, status, to);
+    }
+
+    /// Set the status and connection state, emitting `StateChanged` if the
+    /// connection state moved
+    fn set_state(
+        &self,
+        key: &ServerKey,
+        state: &mut ServerState,
+        status: ConnectionStatus,
+        to: ConnectionState,
+    ) {
+        state.status = status;
+        let from = std::mem::replace(&mut state.connection, to);
+        if from == to {
+            return;
+        }
+
+        debug!(
+            server_id = %key.server_id,
+            space_id = %key.space_id,
+            from = from.as_str(),
+            to = to.as_str(),
+            "[ServerManager] Connection state changed"
+        );
+        self.emit(DomainEvent::StateChanged {
+            space_id: key.space_id,
+            server_id: key.server_id.clone(),
+            from,
+            to,
+        });
+    }
+
     /// Get or create server state
     fn get_or_create_state(
         &self,
@@ -384,7 +428,7 @@ impl ServerManager {
 
         // Atomic state change
         state.flow_id += 1;
-        state.status = ConnectionStatus::Connecting;
+        self.set_status(&key, &mut state, ConnectionStatus::Connecting);
         state.connect_lock = Some(connect_guard);
         state.error = None;
         let flow_id = state.flow_id;
@@ -479,7 +523,12 @@ impl ServerManager {
         // Clear features
         state.features = None;
 
-        state.status = ConnectionStatus::Disconnected;
+        self.set_state(
+            key,
+            &mut state,
+            ConnectionStatus::Disconnected,
+            ConnectionState::Disabled,
+        );
         state.error = None;
 
         // Capture what we need for after dropping lock
@@ -615,7 +664,7 @@ impl ServerManager {
 
         // Atomic state change
         state.flow_id += 1;
-        state.status = ConnectionStatus::Authenticating;
+        self.set_status(key, &mut state, ConnectionStatus::Authenticating);
         state.auth_lock = Some(auth_guard);
         let flow_id = state.flow_id;
 
@@ -675,7 +724,7 @@ impl ServerManager {
         // Release auth lock
         state.auth_lock = None;
 
-        state.status = ConnectionStatus::AuthRequired;
+        self.set_status(key, &mut state, ConnectionStatus::AuthRequired);
 
         info!(
             server_id = %key.server_id,
@@ -733,7 +782,7 @@ impl ServerManager {
 
         match result {
             Ok(ConnectResult::Connected { features }) => {
-                state.status = ConnectionStatus::Connected;
+                self.set_status(key, &mut state, ConnectionStatus::Connected);
                 state.features = Some(features.clone());
                 state.error = None;
 
@@ -757,7 +806,7 @@ impl ServerManager {
                 });
             }
             Ok(ConnectResult::AuthRequired) => {
-                state.status = ConnectionStatus::AuthRequired;
+                self.set_status(key, &mut state, ConnectionStatus::AuthRequired);
                 state.error = None;
 
                 info!(
@@ -778,7 +827,7 @@ impl ServerManager {
                 });
             }
             Err(e) => {
-                state.status = ConnectionStatus::Error;
+                self.set_status(key, &mut state, ConnectionStatus::Error);
                 state.error = Some(e.clone());
 
                 error!(
@@ -832,7 +881,7 @@ impl ServerManager {
         }
         state.auth_lock = None;
 
-        state.status = ConnectionStatus::AuthRequired;
+        self.set_status(key, &mut state, ConnectionStatus::AuthRequired);
         state.error = Some("Authentication timed out".to_string());
 
         warn!(
@@ -877,7 +926,7 @@ impl ServerManager {
         }
         state.auth_lock = None;
 
-        state.status = ConnectionStatus::AuthRequired;
+        self.set_status(key, &mut state, ConnectionStatus::AuthRequired);
         state.error = Some(error.to_string());
 
         error!(
@@ -940,7 +989,7 @@ impl ServerManager {
         );
 
         // Set to Connecting while we exchange tokens and connect
-        state.status = ConnectionStatus::Connecting;
+        self.set_status(key, &mut state, ConnectionStatus::Connecting);
         let flow_id_for_connect = state.flow_id;
 
         self.emit(DomainEvent::ServerStatusChanged {
@@ -974,7 +1023,7 @@ impl ServerManager {
         let mut state = entry.write().await;
 
         state.flow_id += 1;
-        state.status = ConnectionStatus::Connecting;
+        self.set_status(key, &mut state, ConnectionStatus::Connecting);
         state.error = None;
 
         self.emit(DomainEvent::ServerStatusChanged {
@@ -993,7 +1042,7 @@ impl ServerManager {
         let entry = self.get_or_create_state(key.clone());
         let mut state = entry.write().await;
 
-        state.status = ConnectionStatus::Connected;
+        self.set_status(key, &mut state, ConnectionStatus::Connected);
         state.has_connected_before = true;
         state.features = Some(features.clone());
         state.error = None;
@@ -1060,7 +1109,7 @@ impl ServerManager {
         let entry = self.get_or_create_state(key.clone());
         let mut state = entry.write().await;
 
-        state.status = ConnectionStatus::AuthRequired;
+        self.set_status(key, &mut state, ConnectionStatus::AuthRequired);
         state.error = message.clone();
         state.connect_lock = None;
 
@@ -1083,7 +1132,7 @@ impl ServerManager {
         let mut state = entry.write().await;
 
         state.flow_id += 1;
-        state.status = ConnectionStatus::Authenticating;
+        self.set_status(key, &mut state, ConnectionStatus::Authenticating);
         state.error = None;
 
         let now = Instant::now();
@@ -1113,7 +1162,7 @@ impl ServerManager {
         let entry = self.get_or_create_state(key.clone());
         let mut state = entry.write().await;
 
-        state.status = ConnectionStatus::Error;
+        self.set_status(key, &mut state, ConnectionStatus::Error);
         state.error = Some(error.clone());
         state.connect_lock = None;
         state.auth_lock = None;
@@ -1134,6 +1183,16 @@ impl ServerManager {
 
     /// Update server state to Disconnected
     pub async fn set_disconnected(&self, key: &ServerKey) {
+        self.stop(key, ConnectionState::Idle).await;
+    }
+
+    /// Update server state to Disconnected after the user disabled the server
+    pub async fn set_disabled(&self, key: &ServerKey) {
+        self.stop(key, ConnectionState::Disabled).await;
+    }
+
+    /// Clear a stopped server's connection, ending in connection state `to`
+    async fn stop(&self, key: &ServerKey, to: ConnectionState) {
         let entry = self.get_or_create_state(key.clone());
         let mut state = entry.write().await;
 
@@ -1155,7 +1214,7 @@ impl ServerManager {
             .map(|f| !f.resources.is_empty())
             .unwrap_or(false);
 
-        state.status = ConnectionStatus::Disconnected;
+        self.set_state(key, &mut state, ConnectionStatus::Disconnected, to);
         state.error = None;
         state.features = None;
         state.auth = None;
@@ -1229,6 +1288,14 @@ impl ServerManager {
         None
     }
 
+    /// Get a server's connection state (`Idle` if it was never tracked)
+    pub async fn connection_state(&self, key: &ServerKey) -> ConnectionState {
+        match self.states.get(key) {
+            Some(entry) => entry.read().await.connection,
+            None => ConnectionState::Idle,
+        }
+    }
+
     /// Check if server is in a specific status
     pub async fn is_status(&self, key: &ServerKey, expected: ConnectionStatus) -> bool {
         if let Some(entry) = self.states.get(key) {
@@ -1368,7 +1435,7 @@ impl ServerManager {
 
                 let has_changes = !added.is_empty() || !removed.is_empty();
 
-                state.status = ConnectionStatus::Connected;
+                self.set_status(key, &mut state, ConnectionStatus::Connected);
                 state.features = Some(new_features.clone());
                 state.error = None;
 
@@ -1431,7 +1498,7 @@ impl ServerManager {
                     || e.contains("401")
                     || e.contains("unauthorized")
                 {
-                    state.status = ConnectionStatus::AuthRequired;
+                    self.set_status(key, &mut state, ConnectionStatus::AuthRequired);
                     state.error = Some(e.clone());
 
                     self.emit(DomainEvent::ServerStatusChanged {
@@ -1444,7 +1511,7 @@ impl ServerManager {
                         features: None,
                     });
                 } else {
-                    state.status = ConnectionStatus::Error;
+                    self.set_status(key, &mut state, ConnectionStatus::Error);
                     state.error = Some(e.clone());
 
                     self.emit(DomainEvent::ServerStatusChanged {
@@ -1470,6 +1537,106 @@ impl ServerManager {
         Err("Not implemented".to_string())
     }
 
+    /// Mark servers as needing OAuth when their token can no longer be
+    /// refreshed (call this once at startup)
+    ///
+    /// Listens for [`DomainEvent::ServerAuthExpired`] from HTTP transports, so
+    /// the UI re-prompts instead of tool calls failing with a 401.
+    pub fn start_auth_expiry_watch(self: Arc<Self>) -> JoinHandle<()> {
+        let filter = EventFilter::new().kinds(&["server_auth_expired"]);
+        let mut events = FilteredEventReceiver::new(self.subscribe(), filter);
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let DomainEvent::ServerAuthExpired {
+                    space_id,
+                    server_id,
+                    message,
+                } = event
+                {
+                    let key = ServerKey::new(space_id, server_id);
+                    // A flow the user already started supersedes this
+                    if self.is_status(&key, ConnectionStatus::Authenticating).await {
+                        continue;
+                    }
+                    self.set_auth_required(&key, Some(message)).await;
+                }
+            }
+        })
+    }
+
+    /// Track transport recovery in the connection state (call this once at
+    /// startup)
+    ///
+    /// Supervisor restarts and SSE reconnects move a connected server to
+    /// `Reconnecting`; a completed restart or passing health check moves it
+    /// back, and giving up marks it as failed.
+    pub fn start_recovery_watch(self: Arc<Self>) -> JoinHandle<()> {
+        let filter = EventFilter::new().kinds(&[
+            "server_restarting",
+            "server_reconnecting",
+            "server_recovered",
+            "server_health_changed",
+            "server_failed",
+        ]);
+        let mut events = FilteredEventReceiver::new(self.subscribe(), filter);
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                let (Some(space_id), Some(server_id)) = (event.space_id(), event.server_id())
+                else {
+                    continue;
+                };
+                let key = ServerKey::new(space_id, server_id);
+                match event {
+                    DomainEvent::ServerRestarting { .. }
+                    | DomainEvent::ServerReconnecting { .. } => {
+                        self.transition_if(
+                            &key,
+                            ConnectionState::Connected,
+                            ConnectionState::Reconnecting,
+                        )
+                        .await;
+                    }
+                    DomainEvent::ServerRecovered { .. }
+                    | DomainEvent::ServerHealthChanged {
+                        health: ServerHealth::Healthy,
+                        ..
+                    } => {
+                        self.transition_if(
+                            &key,
+                            ConnectionState::Reconnecting,
+                            ConnectionState::Connected,
+                        )
+                        .await;
+                    }
+                    DomainEvent::ServerFailed { message, .. } => {
+                        // Only a live connection can give up; a disable or
+                        // reconnect since then has already moved on
+                        let state = self.connection_state(&key).await;
+                        if matches!(
+                            state,
+                            ConnectionState::Connected | ConnectionState::Reconnecting
+                        ) {
+                            self.set_error(&key, message).await;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        })
+    }
+
+    /// Move a server from `from` to `to`, keeping its status
+    async fn transition_if(&self, key: &ServerKey, from: ConnectionState, to: ConnectionState) {
+        let Some(entry) = self.states.get(key) else {
+            return;
+        };
+        let mut state = entry.write().await;
+        if state.connection == from {
+            let status = state.status;
+            self.set_state(key, &mut state, status, to);
+        }
+    }
+
     /// Start periodic refresh loop (call this once at startup)
     ///
     /// Runs every REFRESH_INTERVAL (60s) and refreshes features for all connected servers
@@ -9,23 +9,37 @@
 //! - Coordinating connect/disconnect operations
 //! - Bulk connect on startup (reconnect_all_enabled)
 //! - Providing access to server instances for routing
+//! - Periodic health checks of HTTP servers (see [`HealthTracker`])
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use dashmap::DashMap;
+use mcpmux_core::{CircuitState, DomainEvent, ServerHealth};
+use rmcp::model::ClientRequest;
+use serde::Serialize;
 use serde_json::Value;
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
+use super::call_timeout::ToolCallTimeouts;
+use super::capabilities::CachedCapabilities;
+use super::concurrency::ConcurrencyLimiter;
 use super::connection::{ConnectionResult, ConnectionService};
 use super::context::ConnectionContext;
 use super::features::{CachedFeatures, FeatureService};
+use super::health::{HealthCheckConfig, HealthTracker, HealthTransition};
 use super::instance::{InstanceKey, InstanceState, ServerInstance};
 use super::oauth::OutboundOAuthManager;
+use super::rate_limit::CallRateLimiter;
 use super::token::TokenService;
 use super::transport::{ResolvedTransport, TransportType};
 
+/// How long STDIO servers get to exit cleanly on disconnect before being killed.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
 /// Check if an error string indicates an authentication/authorization failure
 fn is_auth_error(error_str: &str) -> bool {
     let lower = error_str.to_lowercase();
@@ -63,13 +77,110 @@ impl ReconnectResult {
 }
 
 /// Pool statistics
-#[derive(Debug, Clone, Default)]
+///
+/// A point-in-time snapshot, serializable for the desktop app and admin APIs.
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PoolStats {
     pub total_instances: usize,
     pub connected_instances: usize,
+    /// Instances connecting for the first time
     pub connecting_instances: usize,
+    /// Instances connecting again after having been connected
+    pub reconnecting_instances: usize,
     pub failed_instances: usize,
     pub oauth_pending_instances: usize,
+    /// Tool calls waiting for a server's concurrency limit, across all servers
+    pub queued_requests: usize,
+    /// Per-server breakdown, ordered by space then server ID
+    pub servers: Vec<ServerStats>,
+}
+
+impl PoolStats {
+    fn record(&mut self, server: ServerStats) {
+        self.total_instances += 1;
+        match server.state {
+            ServerConnectionState::Connected => self.connected_instances += 1,
+            ServerConnectionState::Connecting => self.connecting_instances += 1,
+            ServerConnectionState::Reconnecting => self.reconnecting_instances += 1,
+            ServerConnectionState::Failed => self.failed_instances += 1,
+            ServerConnectionState::OAuthPending => self.oauth_pending_instances += 1,
+            ServerConnectionState::Disconnected => {}
+        }
+        self.queued_requests += server.queued_requests;
+        self.servers.push(server);
+    }
+}
+
+/// Connection state of a pooled server, as reported by [`PoolStats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerConnectionState {
+    Disconnected,
+    Connecting,
+    /// Connecting again after an earlier successful connect
+    Reconnecting,
+    Connected,
+    Failed,
+    #[serde(rename = "oauth_pending")]
+    OAuthPending,
+}
+
+/// Statistics for one pooled server
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerStats {
+    pub space_id: Uuid,
+    pub server_id: String,
+    pub state: ServerConnectionState,
+    /// Most recent connect or request error, cleared on connect
+    pub last_error: Option<String>,
+    /// Successful connects, including reconnects
+    pub connect_count: u64,
+    /// Seconds since the current connection was established
+    pub uptime_secs: Option<u64>,
+    pub consecutive_failures: u32,
+    pub requests_served: u64,
+    /// Tool calls to this server currently running
+    pub in_flight_requests: usize,
+    /// Tool calls waiting for this server's concurrency limit
+    pub queued_requests: usize,
+}
+
+impl ServerStats {
+    fn from_instance(
+        space_id: Uuid,
+        instance: &ServerInstance,
+        concurrency: &ConcurrencyLimiter,
+    ) -> Self {
+        // Copy out under the lock; nothing below awaits
+        let stats = instance.stats.read().clone();
+        let state = match stats.state {
+            InstanceState::Disconnected => ServerConnectionState::Disconnected,
+            InstanceState::Connecting if stats.connect_count > 0 => {
+                ServerConnectionState::Reconnecting
+            }
+            InstanceState::Connecting => ServerConnectionState::Connecting,
+            InstanceState::Connected => ServerConnectionState::Connected,
+            InstanceState::Failed => ServerConnectionState::Failed,
+            InstanceState::OAuthPending => ServerConnectionState::OAuthPending,
+        };
+        let uptime_secs = match (state, stats.connected_at) {
+            (ServerConnectionState::Connected, Some(at)) => Some(at.elapsed().as_secs()),
+            _ => None,
+        };
+
+        Self {
+            space_id,
+            server_id: instance.server_id.clone(),
+            state,
+            last_error: stats.last_error,
+            connect_count: stats.connect_count,
+            uptime_secs,
+            consecutive_failures: stats.consecutive_failures,
+            requests_served: stats.requests_served,
+            in_flight_requests: concurrency.in_flight(&instance.server_id),
+            queued_requests: concurrency.queued(&instance.server_id),
+        }
+    }
 }
 
 /// Pool Service - main orchestrator for server connections
@@ -82,6 +193,16 @@ pub struct PoolService {
     feature_service: Arc<FeatureService>,
     /// Token service (exposed for routing)
     token_service: Arc<TokenService>,
+    /// Per-server limit on outbound tool calls
+    rate_limiter: CallRateLimiter,
+    /// Per-server limit on concurrent tool calls
+    concurrency: ConcurrencyLimiter,
+    /// How long outbound tool calls may take
+    call_timeouts: ToolCallTimeouts,
+    /// Health of connected HTTP servers
+    health: HealthTracker,
+    /// Sender for health change events
+    event_tx: Option<tokio::sync::broadcast::Sender<DomainEvent>>,
 }
 
 impl PoolService {
@@ -95,9 +216,55 @@ impl PoolService {
             connection_service,
             feature_service,
             token_service,
+            rate_limiter: CallRateLimiter::new(),
+            concurrency: ConcurrencyLimiter::new(),
+            call_timeouts: ToolCallTimeouts::new(),
+            health: HealthTracker::default(),
+            event_tx: None,
         }
     }
 
+    pub fn with_rate_limiter(mut self, rate_limiter: CallRateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    pub fn with_concurrency_limiter(mut self, concurrency: ConcurrencyLimiter) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn with_call_timeouts(mut self, call_timeouts: ToolCallTimeouts) -> Self {
+        self.call_timeouts = call_timeouts;
+        self
+    }
+
+    pub fn with_health_check(mut self, config: HealthCheckConfig) -> Self {
+        self.health = HealthTracker::new(config);
+        self
+    }
+
+    pub fn with_event_tx(mut self, event_tx: tokio::sync::broadcast::Sender<DomainEvent>) -> Self {
+        self.event_tx = Some(event_tx);
+        self
+    }
+
+    /// Get the rate limiter for outbound tool calls
+    pub fn rate_limiter(&self) -> &CallRateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Get the concurrency limiter for outbound tool calls, e.g. to change a
+    /// server's limit at runtime
+    pub fn concurrency_limiter(&self) -> &ConcurrencyLimiter {
+        &self.concurrency
+    }
+
+    /// Get the timeouts for outbound tool calls
+    pub fn call_timeouts(&self) -> &ToolCallTimeouts {
+        &self.call_timeouts
+    }
+
     /// Get the token service for token operations
     pub fn token_service(&self) -> Arc<TokenService> {
         self.token_service.clone()
@@ -296,9 +463,9 @@ impl PoolService {
 
         // Use proper InstanceKey constructors that include the URL
         let instance_key = match &ctx.transport {
-            ResolvedTransport::Stdio { command, args, env } => {
-                InstanceKey::stdio(ctx.space_id, command, args, env)
-            }
+            ResolvedTransport::Stdio {
+                command, args, env, ..
+            } => InstanceKey::stdio(ctx.space_id, command, args, env),
             ResolvedTransport::Http { url, headers, .. } => {
                 InstanceKey::http(ctx.space_id, url, headers)
             }
@@ -320,7 +487,7 @@ impl PoolService {
             .await;
 
         // If connection failed completely, remove the instance
-        if let ConnectionResult::Failed { .. } = &result {
+        if let ConnectionResult::Failed { .. } | ConnectionResult::CircuitOpen { .. } = &result {
             self.instances.remove(&key);
         }
 
@@ -328,15 +495,74 @@ impl PoolService {
     }
 
     /// Remove instance only (for disable - keeps tokens)
+    ///
+    /// The server process is shut down gracefully in the background.
     pub fn remove_instance(&self, space_id: Uuid, server_id: &str) {
         let key = (space_id, server_id.to_string());
+        self.connection_service.reset_circuit(space_id, server_id);
+        self.connection_service
+            .capabilities()
+            .invalidate(space_id, server_id);
+        self.health.reset(space_id, server_id);
 
-        if let Some((_, _instance)) = self.instances.remove(&key) {
+        if let Some((_, instance)) = self.instances.remove(&key) {
             info!(
                 "[PoolService] Removed instance for {}/{} (tokens preserved)",
                 space_id, server_id
             );
+            // Without a runtime the instance is just dropped, which hard-kills the process
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                runtime.spawn(async move { instance.shutdown(SHUTDOWN_GRACE).await });
+            }
+        }
+    }
+
+    /// Remove instance and wait for its server process to shut down
+    async fn shutdown_instance(&self, space_id: Uuid, server_id: &str) {
+        let key = (space_id, server_id.to_string());
+
+        if let Some((_, instance)) = self.instances.remove(&key) {
+            info!(
+                "[PoolService] Shutting down instance for {}/{}",
+                space_id, server_id
+            );
+            instance.shutdown(SHUTDOWN_GRACE).await;
+        }
+    }
+
+    /// Shut down every instance, e.g. before the app exits.
+    ///
+    /// All server processes are asked to exit at once and get up to `grace` to
+    /// do so before being force-killed. Returns the `(space_id, server_id)` of
+    /// servers that didn't exit cleanly.
+    pub async fn drain(&self, grace: Duration) -> Vec<(Uuid, String)> {
+        let keys: Vec<_> = self.instances.iter().map(|e| e.key().clone()).collect();
+        let instances: Vec<_> = keys
+            .into_iter()
+            .filter_map(|key| self.instances.remove(&key))
+            .collect();
+        info!("[PoolService] Draining {} instance(s)", instances.len());
+
+        let results = futures::future::join_all(
+            instances
+                .iter()
+                .map(|(_, instance)| instance.shutdown(grace)),
+        )
+        .await;
+
+        let unclean: Vec<_> = instances
+            .into_iter()
+            .zip(results)
+            .filter(|(_, clean)| !clean)
+            .map(|((key, _), _)| key)
+            .collect();
+        for (space_id, server_id) in &unclean {
+            warn!(
+                "[PoolService] {}/{} did not exit cleanly within {:?}",
+                space_id, server_id, grace
+            );
         }
+        unclean
     }
 
     /// Disconnect a server (logout - clears tokens but keeps DCR)
@@ -346,8 +572,10 @@ impl PoolService {
             .oauth_manager()
             .cancel_flow_for_space(space_id, server_id);
 
-        // Remove instance
-        self.remove_instance(space_id, server_id);
+        // Remove instance, letting the server process exit cleanly
+        self.shutdown_instance(space_id, server_id).await;
+        self.connection_service.reset_circuit(space_id, server_id);
+        self.health.reset(space_id, server_id);
 
         // Disconnect through connection service (clears tokens, marks features unavailable)
         self.connection_service
@@ -369,6 +597,16 @@ impl PoolService {
             .unwrap_or(false)
     }
 
+    /// Connect circuit breaker state for a server
+    pub fn circuit_state(&self, space_id: Uuid, server_id: &str) -> CircuitState {
+        self.connection_service.circuit_state(space_id, server_id)
+    }
+
+    /// Health check state for a server (`Healthy` until checks say otherwise)
+    pub fn server_health(&self, space_id: Uuid, server_id: &str) -> ServerHealth {
+        self.health.health(space_id, server_id)
+    }
+
     /// Get all instances for a space
     pub fn instances_for_space(&self, space_id: Uuid) -> Vec<Arc<ServerInstance>> {
         self.instances
@@ -379,23 +617,39 @@ impl PoolService {
     }
 
     /// Get pool statistics
+    ///
+    /// Only takes short read locks, so it is cheap enough to poll.
     pub fn stats(&self) -> PoolStats {
-        let mut stats = PoolStats::default();
+        let mut servers: Vec<ServerStats> = self
+            .instances
+            .iter()
+            .map(|entry| {
+                ServerStats::from_instance(entry.key().0, entry.value(), &self.concurrency)
+            })
+            .collect();
+        servers.sort_by(|a, b| (a.space_id, &a.server_id).cmp(&(b.space_id, &b.server_id)));
 
-        for entry in self.instances.iter() {
-            stats.total_instances += 1;
-            match entry.value().state() {
-                InstanceState::Connected => stats.connected_instances += 1,
-                InstanceState::Connecting => stats.connecting_instances += 1,
-                InstanceState::Failed => stats.failed_instances += 1,
-                InstanceState::OAuthPending => stats.oauth_pending_instances += 1,
-                InstanceState::Disconnected => {}
-            }
+        let mut stats = PoolStats::default();
+        for server in servers {
+            stats.record(server);
         }
-
         stats
     }
 
+    /// Last-known capabilities of a server, without connecting to it.
+    ///
+    /// Returned even when stale, so callers can show them right away and
+    /// refresh in the background if [`CachedCapabilities::is_stale`].
+    pub fn cached_capabilities(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+    ) -> Option<CachedCapabilities> {
+        self.connection_service
+            .capabilities()
+            .get(space_id, server_id)
+    }
+
     /// Reconnect an existing instance (e.g., after OAuth completes)
     ///
     /// This is called when OAuth flow completes to reconnect with the new token.
@@ -509,7 +763,8 @@ impl PoolService {
                 ConnectionResult::OAuthRequired { .. } => {
                     result.needs_oauth.push((server.server_id, server.space_id));
                 }
-                ConnectionResult::Failed { error } => {
+                ConnectionResult::Failed { error }
+                | ConnectionResult::CircuitOpen { error, .. } => {
                     result
                         .failed
                         .push((server.server_id, server.space_id, error));
@@ -528,6 +783,131 @@ impl PoolService {
         result
     }
 
+    /// Start the periodic health check loop (call this once at startup)
+    ///
+    /// Every interval, each connected HTTP server is sent an MCP `ping`.
+    /// STDIO servers are skipped: their supervisor already notices a dead process.
+    pub fn start_health_checks(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.health.config().interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+
+                let targets: Vec<_> = self
+                    .instances
+                    .iter()
+                    .filter(|entry| {
+                        entry.value().transport_type == TransportType::Http
+                            && entry.value().is_healthy()
+                    })
+                    .filter_map(|entry| {
+                        let peer = entry.value().with_client(|c| c.peer().clone())?;
+                        Some((entry.key().0, entry.key().1.clone(), peer))
+                    })
+                    .collect();
+
+                if targets.is_empty() {
+                    continue;
+                }
+                debug!(
+                    count = targets.len(),
+                    "[PoolService] Health checking HTTP servers"
+                );
+
+                futures::future::join_all(targets.into_iter().map(
+                    |(space_id, server_id, peer)| {
+                        let this = &self;
+                        async move { this.check_health(space_id, &server_id, peer).await }
+                    },
+                ))
+                .await;
+            }
+        })
+    }
+
+    /// Ping one server and record the outcome
+    async fn check_health(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+        peer: rmcp::Peer<rmcp::RoleClient>,
+    ) {
+        let timeout = self.health.config().timeout;
+        let ping = peer.send_request(ClientRequest::PingRequest(Default::default()));
+        let error = match tokio::time::timeout(timeout, ping).await {
+            Ok(Ok(_)) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(_) => Some(format!("No response to ping within {:?}", timeout)),
+        };
+
+        let transition = match &error {
+            None => self.health.record_success(space_id, server_id),
+            Some(e) => {
+                debug!(
+                    "[PoolService] Health check failed for {}/{}: {}",
+                    space_id, server_id, e
+                );
+                self.health.record_failure(space_id, server_id)
+            }
+        };
+        if let Some(transition) = transition {
+            self.report_health(space_id, server_id, transition, error)
+                .await;
+        }
+    }
+
+    /// Log a health change and emit it as a domain event
+    async fn report_health(
+        &self,
+        space_id: Uuid,
+        server_id: &str,
+        transition: HealthTransition,
+        error: Option<String>,
+    ) {
+        let (level, message) = match transition.health {
+            ServerHealth::Unhealthy => (
+                mcpmux_core::LogLevel::Warn,
+                format!(
+                    "{} consecutive health checks failed - marking server unhealthy",
+                    transition.failures
+                ),
+            ),
+            ServerHealth::Healthy => (
+                mcpmux_core::LogLevel::Info,
+                "Health check succeeded - server is healthy again".to_string(),
+            ),
+        };
+        info!(
+            "[PoolService] {}/{} is now {}",
+            space_id,
+            server_id,
+            transition.health.as_str()
+        );
+        if let Some(log_manager) = self.connection_service.log_manager() {
+            let log =
+                mcpmux_core::ServerLog::new(level, mcpmux_core::LogSource::Connection, message)
+                    .with_metadata(serde_json::json!({
+                        "health": transition.health.as_str(),
+                        "error": &error,
+                    }));
+            let _ = log_manager
+                .append(&space_id.to_string(), server_id, log)
+                .await;
+        }
+
+        if let Some(tx) = &self.event_tx {
+            let _ = tx.send(DomainEvent::ServerHealthChanged {
+                space_id,
+                server_id: server_id.to_string(),
+                health: transition.health,
+                failures: transition.failures,
+                message: error,
+            });
+        }
+    }
+
     /// Get the server URL for an instance (for OAuth token refresh).
     /// Returns None for STDIO transports or if instance not found.
     pub fn get_server_url(&self, space_id: Uuid, server_id: &str) -> Option<String> {
@@ -544,3 +924,78 @@ pub struct InstalledServerInfo {
     pub requires_oauth: bool,
     pub has_credentials: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    fn instance(server_id: &str) -> ServerInstance {
+        ServerInstance::new(
+            InstanceKey::http(Uuid::nil(), "https://example.com/mcp", &Default::default()),
+            server_id.to_string(),
+            TransportType::Http,
+        )
+    }
+
+    #[test]
+    fn test_server_stats_reports_reconnecting() {
+        let server = instance("srv");
+        server.mark_connecting();
+        let stats = ServerStats::from_instance(Uuid::nil(), &server, &ConcurrencyLimiter::new());
+        assert_eq!(stats.state, ServerConnectionState::Connecting);
+        assert_eq!(stats.uptime_secs, None);
+
+        // Only a server that has connected before is reconnecting
+        server.stats.write().connect_count = 1;
+        server.mark_connecting();
+        let stats = ServerStats::from_instance(Uuid::nil(), &server, &ConcurrencyLimiter::new());
+        assert_eq!(stats.state, ServerConnectionState::Reconnecting);
+
+        server.mark_failed("connection refused".to_string());
+        let stats = ServerStats::from_instance(Uuid::nil(), &server, &ConcurrencyLimiter::new());
+        assert_eq!(stats.state, ServerConnectionState::Failed);
+        assert_eq!(stats.last_error.as_deref(), Some("connection refused"));
+        assert_eq!(stats.connect_count, 1);
+    }
+
+    #[test]
+    fn test_pool_stats_aggregates_servers() {
+        let connected = instance("a");
+        {
+            let mut stats = connected.stats.write();
+            stats.state = InstanceState::Connected;
+            stats.connected_at = Some(std::time::Instant::now());
+            stats.connect_count = 1;
+        }
+        let failed = instance("b");
+        failed.mark_failed("boom".to_string());
+
+        // One call to "a" running and one waiting behind its limit
+        let limiter = ConcurrencyLimiter::new().with_limit("a", 1);
+        let _running = limiter.acquire("a").now_or_never().unwrap();
+        let mut waiting = Box::pin(limiter.acquire("a"));
+        assert!((&mut waiting).now_or_never().is_none());
+
+        let mut stats = PoolStats::default();
+        stats.record(ServerStats::from_instance(
+            Uuid::nil(),
+            &connected,
+            &limiter,
+        ));
+        stats.record(ServerStats::from_instance(Uuid::nil(), &failed, &limiter));
+
+        assert_eq!(stats.total_instances, 2);
+        assert_eq!(stats.connected_instances, 1);
+        assert_eq!(stats.failed_instances, 1);
+        assert_eq!(stats.servers[0].uptime_secs, Some(0));
+        assert_eq!(stats.servers[0].in_flight_requests, 1);
+        assert_eq!(stats.servers[0].queued_requests, 1);
+        assert_eq!(stats.servers[1].in_flight_requests, 0);
+        assert_eq!(stats.queued_requests, 1);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["servers"][1]["state"], "failed");
+        assert_eq!(json["reconnecting_instances"], 0);
+    }
+}
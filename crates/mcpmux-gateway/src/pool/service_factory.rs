@@ -64,17 +64,24 @@ impl ServiceFactory {
         let oauth_manager = Arc::new(oauth_manager);
 
         // ConnectionService - manages connect/disconnect lifecycle
-        let connection_service = Arc::new(
-            ConnectionService::new(
-                token_service.clone(),
-                oauth_manager.clone(),
-                deps.credential_repo.clone(),
-                deps.backend_oauth_repo.clone(),
-                prefix_cache.clone(),
-            )
-            .with_log_manager(deps.log_manager.clone())
-            .with_event_tx(event_tx.clone()),
-        );
+        let mut connection_service = ConnectionService::new(
+            token_service.clone(),
+            oauth_manager.clone(),
+            deps.credential_repo.clone(),
+            deps.backend_oauth_repo.clone(),
+            prefix_cache.clone(),
+        )
+        .with_log_manager(deps.log_manager.clone())
+        .with_event_tx(event_tx.clone());
+
+        // Restrict spawnable commands and audit refusals if configured
+        if let Some(ref command_policy) = deps.command_policy {
+            connection_service = connection_service.with_command_policy(command_policy.clone());
+        }
+        if let Some(ref audit_repo) = deps.audit_repo {
+            connection_service = connection_service.with_audit_repo(audit_repo.clone());
+        }
+        let connection_service = Arc::new(connection_service);
 
         // FeatureService - discovers and caches MCP features
         let feature_service = Arc::new(FeatureService::new(
@@ -86,7 +93,7 @@ impl ServiceFactory {
         // ServerManager - event-driven orchestrator for server state
         // No longer has circular dependency with PoolService
         let server_manager = Arc::new(ServerManager::new(
-            event_tx,
+            event_tx.clone(),
             feature_service.clone(),
             connection_service.clone(),
             prefix_cache.clone(),
@@ -94,11 +101,14 @@ impl ServiceFactory {
 
         // PoolService - connection pool orchestrator
         // No longer needs ServerManager reference
-        let pool_service = Arc::new(PoolService::new(
-            connection_service.clone(),
-            feature_service.clone(),
-            token_service.clone(),
-        ));
+        let pool_service = Arc::new(
+            PoolService::new(
+                connection_service.clone(),
+                feature_service.clone(),
+                token_service.clone(),
+            )
+            .with_event_tx(event_tx),
+        );
 
         // RoutingService - handles request dispatch
         // NOTE: No longer needs token_service - RMCP's AuthClient handles token refresh per-request
@@ -0,0 +1,297 @@
+//! Resource limits for STDIO server processes
+//!
+//! A runaway server shouldn't be able to take the app or the host down with
+//! it. [`ResourceLimits`] are opt-in per server and applied on Unix with
+//! `setrlimit` in the child between `fork` and `exec`, so they cover whatever
+//! the command goes on to run (`npx`, `uvx`, ...). Windows has no rlimits;
+//! there they are ignored with a warning.
+//!
+//! A process that hits its CPU time limit is killed with `SIGXCPU`; one that
+//! hits its address space limit fails to allocate and usually aborts or
+//! crashes. Both are recognised from the exit status and logged with the limit
+//! responsible. Running out of file descriptors only makes calls fail with
+//! `EMFILE`, which the server reports itself, if at all.
+
+use std::process::ExitStatus;
+use std::time::Duration;
+
+use tokio::process::Command;
+
+/// Optional caps on a STDIO server process. `None` leaves a limit inherited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Maximum virtual address space, in bytes (`RLIMIT_AS`).
+    pub max_address_space: Option<u64>,
+    /// Maximum number of open file descriptors (`RLIMIT_NOFILE`).
+    pub max_open_files: Option<u64>,
+    /// Maximum CPU time, rounded up to whole seconds (`RLIMIT_CPU`).
+    pub max_cpu_time: Option<Duration>,
+}
+
+/// A single rlimit resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Limit {
+    AddressSpace,
+    OpenFiles,
+    CpuTime,
+}
+
+impl Limit {
+    fn name(self) -> &'static str {
+        match self {
+            Limit::AddressSpace => "max address space",
+            Limit::OpenFiles => "max open files",
+            Limit::CpuTime => "max CPU time",
+        }
+    }
+
+    /// Current limits of this process, which the child inherits.
+    #[cfg(unix)]
+    fn get(self) -> std::io::Result<libc::rlimit> {
+        let mut limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: getrlimit only writes to `limit`.
+        let rc = unsafe {
+            match self {
+                Limit::AddressSpace => libc::getrlimit(libc::RLIMIT_AS, &mut limit),
+                Limit::OpenFiles => libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit),
+                Limit::CpuTime => libc::getrlimit(libc::RLIMIT_CPU, &mut limit),
+            }
+        };
+        if rc != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(limit)
+    }
+
+    /// Set the limit for the calling process. Async-signal-safe.
+    #[cfg(unix)]
+    fn set(self, limit: &libc::rlimit) -> std::io::Result<()> {
+        // SAFETY: setrlimit only reads `limit`.
+        let rc = unsafe {
+            match self {
+                Limit::AddressSpace => libc::setrlimit(libc::RLIMIT_AS, limit),
+                Limit::OpenFiles => libc::setrlimit(libc::RLIMIT_NOFILE, limit),
+                Limit::CpuTime => libc::setrlimit(libc::RLIMIT_CPU, limit),
+            }
+        };
+        if rc != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl ResourceLimits {
+    /// Whether no limit is set.
+    pub fn is_empty(&self) -> bool {
+        self.requested().next().is_none()
+    }
+
+    /// CPU time limit rounded up to whole seconds.
+    fn cpu_secs(&self) -> Option<u64> {
+        self.max_cpu_time
+            .map(|t| t.as_secs() + u64::from(t.subsec_nanos() > 0))
+    }
+
+    /// The configured limits, with CPU time in whole seconds.
+    fn requested(&self) -> impl Iterator<Item = (Limit, u64)> {
+        [
+            (Limit::AddressSpace, self.max_address_space),
+            (Limit::OpenFiles, self.max_open_files),
+            (Limit::CpuTime, self.cpu_secs()),
+        ]
+        .into_iter()
+        .filter_map(|(limit, value)| value.map(|value| (limit, value)))
+    }
+
+    /// Check the limits against the system hard limits, which an
+    /// unprivileged process cannot raise.
+    pub fn validate(&self) -> Result<(), String> {
+        #[cfg(unix)]
+        self.rlimits()?;
+        #[cfg(not(unix))]
+        if let Some((limit, _)) = self.requested().find(|(_, value)| *value == 0) {
+            return Err(format!("{} must be greater than zero", limit.name()));
+        }
+        Ok(())
+    }
+
+    /// Validated rlimits to install in the child.
+    #[cfg(unix)]
+    fn rlimits(&self) -> Result<Vec<(Limit, libc::rlimit)>, String> {
+        self.requested()
+            .map(|(limit, value)| {
+                if value == 0 {
+                    return Err(format!("{} must be greater than zero", limit.name()));
+                }
+                let current = limit
+                    .get()
+                    .map_err(|e| format!("Failed to read {} limit: {}", limit.name(), e))?;
+                let hard = current.rlim_max;
+                if hard != libc::RLIM_INFINITY && value > hard {
+                    return Err(format!(
+                        "{} of {} exceeds the system hard limit of {}",
+                        limit.name(),
+                        value,
+                        hard
+                    ));
+                }
+                // The hard limit is lowered too, so the server can't lift the cap.
+                // For CPU time it is a second higher: SIGXCPU at the soft limit
+                // identifies the cause, SIGKILL at the hard one is a backstop.
+                let max = match limit {
+                    Limit::CpuTime => value.saturating_add(1).min(hard),
+                    _ => value,
+                };
+                Ok((
+                    limit,
+                    libc::rlimit {
+                        rlim_cur: value,
+                        rlim_max: max,
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    /// Validate the limits and have `cmd` install them in the child before it
+    /// execs. Does nothing off Unix.
+    pub(super) fn apply(&self, cmd: &mut Command) -> Result<(), String> {
+        #[cfg(unix)]
+        {
+            let rlimits = self.rlimits()?;
+            if rlimits.is_empty() {
+                return Ok(());
+            }
+            // SAFETY: the hook runs between fork and exec and only calls
+            // setrlimit, which is async-signal-safe, without allocating.
+            unsafe {
+                cmd.pre_exec(move || {
+                    for (limit, value) in &rlimits {
+                        limit.set(value)?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = cmd;
+        Ok(())
+    }
+
+    /// The limit a process most likely hit, judging by how it exited.
+    pub(super) fn exceeded(&self, status: ExitStatus) -> Option<String> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            let described = |limit: Limit, value: Option<u64>, unit: &str| {
+                value.map(|value| format!("{} limit ({}{})", limit.name(), value, unit))
+            };
+            match status.signal()? {
+                libc::SIGXCPU => described(Limit::CpuTime, self.cpu_secs(), "s"),
+                libc::SIGABRT | libc::SIGSEGV | libc::SIGBUS => {
+                    described(Limit::AddressSpace, self.max_address_space, " bytes")
+                }
+                _ => None,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = status;
+            None
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    fn sh(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        cmd
+    }
+
+    #[tokio::test]
+    async fn test_open_files_limit_applies_to_child() {
+        let limits = ResourceLimits {
+            max_open_files: Some(32),
+            ..Default::default()
+        };
+        let mut cmd = sh("ulimit -n");
+        limits.apply(&mut cmd).unwrap();
+
+        let output = cmd.output().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "32");
+    }
+
+    #[tokio::test]
+    async fn test_cpu_limit_is_reported() {
+        let limits = ResourceLimits {
+            max_cpu_time: Some(Duration::from_millis(500)),
+            ..Default::default()
+        };
+        let mut cmd = sh("while :; do :; done");
+        limits.apply(&mut cmd).unwrap();
+
+        let status = tokio::time::timeout(Duration::from_secs(10), cmd.status())
+            .await
+            .expect("CPU limit was not enforced")
+            .unwrap();
+        assert_eq!(
+            limits.exceeded(status).as_deref(),
+            Some("max CPU time limit (1s)")
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ResourceLimits::default().is_empty());
+        assert!(ResourceLimits::default().validate().is_ok());
+
+        let zero = ResourceLimits {
+            max_open_files: Some(0),
+            ..Default::default()
+        };
+        assert!(!zero.is_empty());
+        assert!(zero.validate().unwrap_err().contains("greater than zero"));
+
+        let hard = Limit::OpenFiles.get().unwrap().rlim_max;
+        if hard != libc::RLIM_INFINITY {
+            let too_high = ResourceLimits {
+                max_open_files: Some(hard + 1),
+                ..Default::default()
+            };
+            assert!(too_high.validate().unwrap_err().contains("hard limit"));
+        }
+    }
+
+    #[test]
+    fn test_exceeded_ignores_clean_exits() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let limits = ResourceLimits {
+            max_address_space: Some(1 << 30),
+            ..Default::default()
+        };
+        assert_eq!(limits.exceeded(ExitStatus::from_raw(0)), None);
+        // Killed by a signal the limits don't explain
+        assert_eq!(limits.exceeded(ExitStatus::from_raw(libc::SIGTERM)), None);
+        assert_eq!(
+            limits
+                .exceeded(ExitStatus::from_raw(libc::SIGABRT))
+                .as_deref(),
+            Some("max address space limit (1073741824 bytes)")
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! Liveness checks without a tool call
+//!
+//! Health checks and the UI want to know whether a connection is up right
+//! now, cheaply. [`Transport::is_alive`](super::Transport::is_alive) answers
+//! that. Transports that probe actively keep the peer of their last
+//! successful connect in a [`LastPeer`] and send it an MCP `ping`: a peer whose
+//! client has gone away, or that doesn't answer within [`PING_TIMEOUT`],
+//! counts as dead.
+
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rmcp::model::ClientRequest;
+use rmcp::{Peer, RoleClient};
+
+use super::TransportConnectResult;
+
+/// How long a connected server gets to answer a liveness ping.
+pub(super) const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Peer of the last successful connect, kept for liveness pings.
+#[derive(Default)]
+pub(super) struct LastPeer(Mutex<Option<Peer<RoleClient>>>);
+
+impl LastPeer {
+    /// Remember the peer if `result` is a connection, replacing any earlier one.
+    pub(super) fn record(&self, result: &TransportConnectResult) {
+        if let TransportConnectResult::Connected(client) = result {
+            *self.0.lock() = Some(client.peer().clone());
+        }
+    }
+
+    /// Forget the peer, e.g. once the server has been stopped.
+    pub(super) fn clear(&self) {
+        self.0.lock().take();
+    }
+
+    /// Whether the peer answers a `ping` within `timeout`; `false` if there
+    /// has been no connection.
+    pub(super) async fn ping(&self, timeout: Duration) -> bool {
+        let Some(peer) = self.0.lock().clone() else {
+            return false;
+        };
+        let ping = peer.send_request(ClientRequest::PingRequest(Default::default()));
+        matches!(tokio::time::timeout(timeout, ping).await, Ok(Ok(_)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_no_connection_is_not_alive() {
+        let peer = LastPeer::default();
+        peer.record(&TransportConnectResult::Failed("refused".to_string()));
+        assert!(!peer.ping(PING_TIMEOUT).await);
+    }
+}
@@ -5,31 +5,63 @@
 //! modifying existing code.
 
 mod http;
+mod limits;
+mod liveness;
+mod process;
+mod proxy;
+mod refresh;
 pub mod resolution;
+mod resolver;
+mod retry;
+mod secrets;
 pub mod shell_env;
+mod stderr;
 mod stdio;
+mod supervisor;
+mod tls;
+mod validation;
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use mcpmux_core::{CredentialRepository, OutboundOAuthRepository, ServerLogManager};
+use mcpmux_core::{CredentialRepository, OutboundOAuthRepository, ServerLogManager, TlsConfig};
 use uuid::Uuid;
 
-pub use http::HttpTransport;
-pub use stdio::{configure_child_process_platform, StdioTransport};
+pub use http::{HttpTransport, DEFAULT_HTTP_CONNECT_TIMEOUT};
+pub use limits::ResourceLimits;
+pub use proxy::{display_proxy, ProxySettings, NO_PROXY_OVERRIDE};
+pub use refresh::RefreshingAuthClient;
+pub use resolver::{AllowlistResolver, CommandResolver, ResolveError, WhichResolver};
+pub use retry::{is_retryable_failure, ConnectRetryPolicy, RetryingTransport};
+pub use secrets::{SecretBinding, SecretReferenceUnresolved, SecretSource};
+pub use stderr::{StderrClassifier, StderrLimits};
+pub use stdio::{
+    configure_child_process_platform, default_stdio_connect_timeout, register_command_hint,
+    StdioTransport, UnknownVarMode,
+};
+pub use supervisor::RestartPolicy;
+pub use validation::{CheckOutcome, ValidationCheck, ValidationResult};
 
 // Re-export TransportType from mcpmux-core as the single source of truth
 pub use mcpmux_core::TransportType;
 
 use super::instance::{McpClient, McpClientHandler};
+use super::oauth::OAuthChallenge;
 
 /// Result of a transport connection attempt
 pub enum TransportConnectResult {
     /// Successfully connected
     Connected(McpClient),
     /// OAuth required - returns server URL for OAuth flow
-    OAuthRequired { server_url: String },
+    OAuthRequired {
+        server_url: String,
+        /// Set when the transport started the flow itself (see
+        /// [`HttpTransport::with_oauth_manager`]); finish it with
+        /// [`Transport::complete_oauth`]. `None` leaves starting it to the caller.
+        challenge: Option<OAuthChallenge>,
+    },
     /// Connection failed
     Failed(String),
 }
@@ -48,11 +80,83 @@ pub trait Transport: Send + Sync {
 
     /// Get a description for logging
     fn description(&self) -> String;
+
+    /// Gracefully stop whatever the transport is running locally.
+    ///
+    /// Waits up to `grace` for a clean exit before forcing it, returning
+    /// `false` if it had to be forced. The default does nothing, which suits
+    /// transports without a local process.
+    async fn shutdown(&self, _grace: std::time::Duration) -> bool {
+        true
+    }
+
+    /// Finish an OAuth flow from [`TransportConnectResult::OAuthRequired`] with
+    /// the `code` and `state` from the redirect, storing the tokens.
+    ///
+    /// Fails if `state` isn't the one issued for this server's flow. The
+    /// default fails outright, which suits transports without OAuth.
+    async fn complete_oauth(&self, _code: &str, _state: &str) -> anyhow::Result<()> {
+        anyhow::bail!("{} does not support OAuth", self.description())
+    }
+
+    /// Check the config without leaving anything running, reporting each
+    /// step separately.
+    ///
+    /// The default connects, records whether the handshake succeeded, and
+    /// shuts down again.
+    async fn validate(&self) -> ValidationResult {
+        let mut result = ValidationResult::default();
+        validation::check_handshake(self, &mut result).await;
+        result
+    }
+
+    /// Whether the connection from the last [`connect`](Self::connect) is up
+    /// right now, without making a tool call.
+    ///
+    /// The default trusts the last connect and reports `true`; transports
+    /// opt into an active probe by overriding it.
+    async fn is_alive(&self) -> bool {
+        true
+    }
+}
+
+/// Lets boxed transports (e.g. from [`TransportFactory::create`]) be wrapped,
+/// as in `RetryingTransport::new(TransportFactory::create(..), policy)`.
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    async fn connect(&self) -> TransportConnectResult {
+        (**self).connect().await
+    }
+
+    fn transport_type(&self) -> TransportType {
+        (**self).transport_type()
+    }
+
+    fn description(&self) -> String {
+        (**self).description()
+    }
+
+    async fn shutdown(&self, grace: std::time::Duration) -> bool {
+        (**self).shutdown(grace).await
+    }
+
+    async fn complete_oauth(&self, code: &str, state: &str) -> anyhow::Result<()> {
+        (**self).complete_oauth(code, state).await
+    }
+
+    async fn validate(&self) -> ValidationResult {
+        (**self).validate().await
+    }
+
+    async fn is_alive(&self) -> bool {
+        (**self).is_alive().await
+    }
 }
 
 /// Resolved transport configuration ready for connection.
 ///
-/// All placeholders like `${input:API_KEY}` have been replaced with actual values.
+/// All placeholders like `${input:API_KEY}` have been replaced with actual values,
+/// except those of sensitive inputs, which the stdio transport delivers itself.
 /// This is the runtime representation, distinct from `mcpmux_core::TransportConfig`
 /// which is the registry/template format.
 #[derive(Debug, Clone)]
@@ -61,10 +165,22 @@ pub enum ResolvedTransport {
         command: String,
         args: Vec<String>,
         env: HashMap<String, String>,
+        /// Sensitive inputs kept out of `args` and `env`
+        secrets: Vec<SecretBinding>,
+        /// Per-server start-up timeout
+        connect_timeout: Option<Duration>,
     },
     Http {
         url: String,
         headers: HashMap<String, String>,
+        /// Proxy override; `None` uses the `HTTP(S)_PROXY` env vars
+        proxy: Option<String>,
+        /// Client certificate / CA settings
+        tls: Option<TlsConfig>,
+        /// Per-server connect and handshake timeout
+        connect_timeout: Option<Duration>,
+        /// Per-server limit on waiting for response data
+        request_timeout: Option<Duration>,
     },
 }
 
@@ -85,6 +201,28 @@ impl ResolvedTransport {
         }
     }
 
+    /// Timeout for connecting to this server.
+    ///
+    /// The server's own setting wins, then `fallback` (a gateway-wide override),
+    /// then the default for the transport kind: generous for docker/podman,
+    /// which may pull an image first, and short for local interpreters.
+    pub fn connect_timeout(&self, fallback: Option<Duration>) -> Duration {
+        match self {
+            ResolvedTransport::Stdio {
+                command,
+                connect_timeout,
+                ..
+            } => connect_timeout
+                .or(fallback)
+                .unwrap_or_else(|| default_stdio_connect_timeout(command)),
+            ResolvedTransport::Http {
+                connect_timeout, ..
+            } => connect_timeout
+                .or(fallback)
+                .unwrap_or(DEFAULT_HTTP_CONNECT_TIMEOUT),
+        }
+    }
+
     /// Generate a config hash for instance keying (excludes auth tokens)
     pub fn config_hash(&self) -> u64 {
         use std::collections::hash_map::DefaultHasher;
@@ -92,7 +230,13 @@ impl ResolvedTransport {
 
         let mut hasher = DefaultHasher::new();
         match self {
-            ResolvedTransport::Stdio { command, args, env } => {
+            ResolvedTransport::Stdio {
+                command,
+                args,
+                env,
+                secrets,
+                ..
+            } => {
                 "stdio".hash(&mut hasher);
                 command.hash(&mut hasher);
                 args.hash(&mut hasher);
@@ -102,10 +246,23 @@ impl ResolvedTransport {
                     k.hash(&mut hasher);
                     v.hash(&mut hasher);
                 }
+                // Which inputs are sensitive, not their values
+                for secret in secrets {
+                    secret.input_id.hash(&mut hasher);
+                    secret.delivery.hash(&mut hasher);
+                }
             }
-            ResolvedTransport::Http { url, headers } => {
+            ResolvedTransport::Http {
+                url,
+                headers,
+                proxy,
+                tls,
+                ..
+            } => {
                 "http".hash(&mut hasher);
                 url.hash(&mut hasher);
+                proxy.hash(&mut hasher);
+                tls.hash(&mut hasher);
                 let mut header_pairs: Vec<_> = headers.iter().collect();
                 header_pairs.sort_by_key(|(k, _)| *k);
                 for (k, v) in header_pairs {
@@ -128,7 +285,11 @@ impl TransportFactory {
     /// Create a transport from configuration
     ///
     /// For HTTP transports, the repositories are used to create a DatabaseCredentialStore
-    /// that enables automatic token refresh via RMCP's AuthClient.
+    /// that enables automatic token refresh via RMCP's AuthClient. STDIO transports read
+    /// sensitive inputs without a value from the credential repository.
+    ///
+    /// `connect_timeout` overrides the per-kind default for servers that don't
+    /// set their own; see [`ResolvedTransport::connect_timeout`].
     #[allow(clippy::too_many_arguments)]
     pub fn create(
         config: &ResolvedTransport,
@@ -137,31 +298,60 @@ impl TransportFactory {
         credential_repo: Arc<dyn CredentialRepository>,
         backend_oauth_repo: Arc<dyn OutboundOAuthRepository>,
         log_manager: Option<Arc<ServerLogManager>>,
-        connect_timeout: std::time::Duration,
+        connect_timeout: Option<Duration>,
         event_tx: Option<tokio::sync::broadcast::Sender<mcpmux_core::DomainEvent>>,
     ) -> Box<dyn Transport> {
+        let connect_timeout = config.connect_timeout(connect_timeout);
         match config {
-            ResolvedTransport::Stdio { command, args, env } => Box::new(StdioTransport::new(
-                command.clone(),
-                args.clone(),
-                env.clone(),
-                space_id,
-                server_id,
-                log_manager,
-                connect_timeout,
-                event_tx,
-            )),
-            ResolvedTransport::Http { url, headers } => Box::new(HttpTransport::new(
-                url.clone(),
-                headers.clone(),
-                space_id,
-                server_id,
-                credential_repo,
-                backend_oauth_repo,
-                log_manager,
-                connect_timeout,
-                event_tx,
-            )),
+            ResolvedTransport::Stdio {
+                command,
+                args,
+                env,
+                secrets,
+                ..
+            } => Box::new(
+                StdioTransport::new(
+                    command.clone(),
+                    args.clone(),
+                    env.clone(),
+                    space_id,
+                    server_id,
+                    log_manager,
+                    connect_timeout,
+                    event_tx,
+                )
+                .with_secrets(secrets.clone(), credential_repo),
+            ),
+            ResolvedTransport::Http {
+                url,
+                headers,
+                proxy,
+                tls,
+                request_timeout,
+                ..
+            } => {
+                let mut transport = HttpTransport::new(
+                    url.clone(),
+                    headers.clone(),
+                    space_id,
+                    server_id,
+                    credential_repo,
+                    backend_oauth_repo,
+                    log_manager,
+                    connect_timeout,
+                    event_tx,
+                );
+                if let Some(proxy) = proxy {
+                    transport = transport.with_proxy(proxy.clone());
+                }
+                if let Some(tls) = tls {
+                    transport = transport.with_tls(tls.clone());
+                }
+                if let Some(request_timeout) = request_timeout {
+                    transport = transport.with_request_timeout(*request_timeout);
+                }
+                Box::new(transport)
+            }
         }
     }
 }
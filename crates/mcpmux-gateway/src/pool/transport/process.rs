@@ -0,0 +1,337 @@
+//! Shared ownership of a STDIO server's child process
+//!
+//! The MCP client's stdout reader and the [`StdioTransport`](super::StdioTransport)
+//! both hold a [`ProcessHandle`]. As long as either is alive the child keeps
+//! running; once both are dropped the child is dropped too and `kill_on_drop`
+//! hard-kills it. That is the crash-cleanup path. Normal shutdown goes through
+//! [`ProcessHandle::shutdown`], which gives the process a chance to exit cleanly.
+//!
+//! The reader also signals [`ProcessHandle::stdout_closed`] when the stream
+//! ends, so the transport can collect the exit status of a server that died.
+
+use std::pin::Pin;
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::process::Child;
+use tokio::sync::Notify;
+use tracing::{debug, warn};
+
+/// Slot holding the currently running child process.
+#[derive(Default)]
+pub(super) struct ProcessHandle {
+    child: Mutex<Option<Child>>,
+    shutting_down: AtomicBool,
+    stdout_closed: Arc<Notify>,
+}
+
+impl ProcessHandle {
+    /// Store the running child, replacing (and killing) any previous one.
+    pub(super) fn set(&self, child: Child) {
+        *self.child.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+    }
+
+    /// Take the child out of the slot.
+    pub(super) fn take(&self) -> Option<Child> {
+        self.child.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Exit status of the child, if it has exited.
+    pub(super) fn exit_status(&self) -> Option<ExitStatus> {
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        child.as_mut()?.try_wait().ok().flatten()
+    }
+
+    /// Whether a child is running, i.e. one is in the slot and hasn't exited.
+    pub(super) fn is_running(&self) -> bool {
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        child
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(None)))
+    }
+
+    /// Notified when the client's stdout reader reaches EOF or is dropped.
+    ///
+    /// Separate from the handle so waiting on it doesn't keep the child alive.
+    pub(super) fn stdout_closed(&self) -> Arc<Notify> {
+        Arc::clone(&self.stdout_closed)
+    }
+
+    /// Whether [`shutdown`](Self::shutdown) has been requested.
+    pub(super) fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Acquire)
+    }
+
+    /// Gracefully stop the child, force-killing it after `grace`.
+    ///
+    /// Returns `None` if no process was running.
+    pub(super) async fn shutdown(&self, grace: Duration) -> Option<std::io::Result<Termination>> {
+        self.shutting_down.store(true, Ordering::Release);
+        let mut child = self.take()?;
+        Some(terminate(&mut child, grace).await)
+    }
+}
+
+/// How a process exited, in the shape of [`DomainEvent::ServerExited`].
+///
+/// [`DomainEvent::ServerExited`]: mcpmux_core::DomainEvent::ServerExited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ExitInfo {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    /// e.g. "exited with code 1" or "killed by SIGSEGV"
+    pub message: String,
+}
+
+impl ExitInfo {
+    pub(super) fn from_status(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+
+        let code = status.code();
+        let message = match (code, signal) {
+            (Some(code), _) => format!("exited with code {code}"),
+            (None, Some(signal)) => match signal_name(signal) {
+                Some(name) => format!("killed by {name}"),
+                None => format!("killed by signal {signal}"),
+            },
+            (None, None) => format!("exited ({status})"),
+        };
+        Self {
+            code,
+            signal,
+            message,
+        }
+    }
+
+    /// Whether the process ended in a way worth more than a warning.
+    pub(super) fn is_failure(&self) -> bool {
+        self.code != Some(0)
+    }
+
+    /// Log line for the exit, naming the resource limit that caused it, if any.
+    pub(super) fn log_message(&self, exceeded: Option<&str>) -> String {
+        match exceeded {
+            Some(limit) => format!(
+                "Server process {} after exceeding its {}",
+                self.message, limit
+            ),
+            None => format!("Server process {}", self.message),
+        }
+    }
+}
+
+/// Name of a common Unix signal.
+fn signal_name(signal: i32) -> Option<&'static str> {
+    #[cfg(unix)]
+    {
+        let name = match signal {
+            libc::SIGHUP => "SIGHUP",
+            libc::SIGINT => "SIGINT",
+            libc::SIGQUIT => "SIGQUIT",
+            libc::SIGILL => "SIGILL",
+            libc::SIGABRT => "SIGABRT",
+            libc::SIGBUS => "SIGBUS",
+            libc::SIGFPE => "SIGFPE",
+            libc::SIGKILL => "SIGKILL",
+            libc::SIGSEGV => "SIGSEGV",
+            libc::SIGPIPE => "SIGPIPE",
+            libc::SIGTERM => "SIGTERM",
+            libc::SIGXCPU => "SIGXCPU",
+            libc::SIGXFSZ => "SIGXFSZ",
+            _ => return None,
+        };
+        Some(name)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        None
+    }
+}
+
+/// How a child process ended after [`terminate`].
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Termination {
+    pub status: ExitStatus,
+    /// Whether it outlived the grace period and had to be force-killed
+    pub forced: bool,
+}
+
+/// Ask `child` to exit, then force-kill it if it hasn't within `grace`.
+///
+/// On Unix the child's process group receives `SIGTERM`, so wrappers such as
+/// `npx` pass it on to the real server. Windows has no equivalent signal for
+/// console-less processes; there the server is expected to exit once its
+/// stdin closes, which happens when the MCP client is dropped.
+pub(super) async fn terminate(child: &mut Child, grace: Duration) -> std::io::Result<Termination> {
+    if let Some(status) = child.try_wait()? {
+        return Ok(Termination {
+            status,
+            forced: false,
+        });
+    }
+
+    #[cfg(unix)]
+    signal_process_group(child, libc::SIGTERM);
+
+    match tokio::time::timeout(grace, child.wait()).await {
+        Ok(status) => {
+            debug!("Child process exited within grace period");
+            Ok(Termination {
+                status: status?,
+                forced: false,
+            })
+        }
+        Err(_) => {
+            warn!(
+                "Child process did not exit within {:?}, force-killing",
+                grace
+            );
+            #[cfg(unix)]
+            signal_process_group(child, libc::SIGKILL);
+            child.kill().await?;
+            Ok(Termination {
+                status: child.wait().await?,
+                forced: true,
+            })
+        }
+    }
+}
+
+/// Send `signal` to the child's process group, falling back to the child itself.
+///
+/// `configure_child_process_platform` makes every child a process group
+/// leader, so the group id equals the child's pid.
+#[cfg(unix)]
+fn signal_process_group(child: &Child, signal: libc::c_int) {
+    let Some(pid) = child.id().and_then(|pid| libc::pid_t::try_from(pid).ok()) else {
+        return;
+    };
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    unsafe {
+        if libc::kill(-pid, signal) != 0 {
+            libc::kill(pid, signal);
+        }
+    }
+}
+
+/// Stdout reader handed to the MCP client, keeping the child process alive.
+pub(super) struct ProcessReader<R> {
+    inner: R,
+    process: Arc<ProcessHandle>,
+}
+
+impl<R> ProcessReader<R> {
+    pub(super) fn new(inner: R, process: Arc<ProcessHandle>) -> Self {
+        Self { inner, process }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ProcessReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let eof = matches!(&poll, Poll::Ready(Ok(())))
+            && buf.filled().len() == filled
+            && buf.remaining() > 0;
+        if eof || matches!(&poll, Poll::Ready(Err(_))) {
+            self.process.stdout_closed.notify_one();
+        }
+        poll
+    }
+}
+
+impl<R> Drop for ProcessReader<R> {
+    fn drop(&mut self) {
+        self.process.stdout_closed.notify_one();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+    use tokio::process::Command;
+
+    fn spawn_sh(script: &str) -> Child {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", script])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .process_group(0);
+        cmd.spawn().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_sends_sigterm() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let handle = ProcessHandle::default();
+        handle.set(spawn_sh("sleep 30"));
+
+        let termination = handle
+            .shutdown(Duration::from_secs(5))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(termination.status.signal(), Some(libc::SIGTERM));
+        assert!(!termination.forced);
+        assert!(handle.is_shutting_down());
+        assert!(handle.take().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_force_kills_after_grace() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let handle = ProcessHandle::default();
+        handle.set(spawn_sh("trap '' TERM; sleep 30"));
+        // Give the shell a moment to install its trap
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let termination = handle
+            .shutdown(Duration::from_millis(200))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(termination.status.signal(), Some(libc::SIGKILL));
+        assert!(termination.forced);
+    }
+
+    #[test]
+    fn test_exit_info() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let exited = ExitInfo::from_status(ExitStatus::from_raw(1 << 8));
+        assert_eq!(exited.code, Some(1));
+        assert_eq!(exited.signal, None);
+        assert_eq!(exited.message, "exited with code 1");
+        assert!(exited.is_failure());
+
+        let crashed = ExitInfo::from_status(ExitStatus::from_raw(libc::SIGSEGV));
+        assert_eq!(crashed.code, None);
+        assert_eq!(crashed.signal, Some(libc::SIGSEGV));
+        assert_eq!(crashed.message, "killed by SIGSEGV");
+
+        assert!(!ExitInfo::from_status(ExitStatus::from_raw(0)).is_failure());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_without_process() {
+        let handle = ProcessHandle::default();
+        assert!(handle.shutdown(Duration::from_millis(10)).await.is_none());
+    }
+}
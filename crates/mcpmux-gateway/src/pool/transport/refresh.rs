@@ -0,0 +1,371 @@
+//! Token refresh on 401 for OAuth HTTP servers
+//!
+//! RMCP's [`AuthClient`] refreshes the access token once its recorded expiry
+//! passes, but servers also reject tokens early: revoked sessions, rotated
+//! signing keys, clock skew. [`RefreshingAuthClient`] catches the 401, runs a
+//! refresh grant and retries the request once. The new tokens are persisted by
+//! [`DatabaseCredentialStore`](crate::pool::DatabaseCredentialStore), which
+//! keeps the refresh token encrypted at rest.
+//!
+//! When the refresh grant fails, [`DomainEvent::ServerAuthExpired`] is emitted
+//! so the server is marked as needing OAuth and the UI prompts again.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::stream::BoxStream;
+use mcpmux_core::{DomainEvent, LogLevel, LogSource, ServerLog, ServerLogManager};
+use oauth2::TokenResponse;
+use rmcp::model::ClientJsonRpcMessage;
+use rmcp::transport::auth::{AuthClient, AuthError};
+use rmcp::transport::streamable_http_client::{
+    StreamableHttpClient, StreamableHttpError, StreamableHttpPostResponse,
+};
+use sse_stream::{Error as SseError, Sse};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// [`AuthClient`] that refreshes the token and retries once when a request is
+/// rejected with 401.
+#[derive(Clone)]
+pub struct RefreshingAuthClient<C> {
+    auth: AuthClient<C>,
+    server: Arc<ServerContext>,
+}
+
+/// Where refresh outcomes are reported.
+struct ServerContext {
+    space_id: Uuid,
+    server_id: String,
+    log_manager: Option<Arc<ServerLogManager>>,
+    event_tx: Option<tokio::sync::broadcast::Sender<DomainEvent>>,
+}
+
+impl<C> RefreshingAuthClient<C> {
+    pub fn new(
+        auth: AuthClient<C>,
+        space_id: Uuid,
+        server_id: impl Into<String>,
+        log_manager: Option<Arc<ServerLogManager>>,
+        event_tx: Option<tokio::sync::broadcast::Sender<DomainEvent>>,
+    ) -> Self {
+        Self {
+            auth,
+            server: Arc::new(ServerContext {
+                space_id,
+                server_id: server_id.into(),
+                log_manager,
+                event_tx,
+            }),
+        }
+    }
+
+    /// Get a token newer than `rejected`, running a refresh grant unless a
+    /// concurrent request already did.
+    async fn refresh(&self, rejected: &str) -> Result<String, AuthError> {
+        let manager = self.auth.auth_manager.lock().await;
+        // Refresh tokens may be single-use, so don't spend one twice
+        if let Ok(current) = manager.get_access_token().await {
+            if current != rejected {
+                return Ok(current);
+            }
+        }
+
+        match manager.refresh_token().await {
+            Ok(tokens) => {
+                info!(
+                    server_id = %self.server.server_id,
+                    "Access token rejected, refreshed it"
+                );
+                self.server
+                    .log(
+                        LogLevel::Info,
+                        "Access token rejected (401), refreshed and retrying".to_string(),
+                    )
+                    .await;
+                Ok(tokens.access_token().secret().to_string())
+            }
+            Err(e) => {
+                let message = format!("Token refresh failed, re-authorization required: {}", e);
+                warn!(server_id = %self.server.server_id, "{}", message);
+                self.server.log(LogLevel::Warn, message.clone()).await;
+                if let Some(tx) = &self.server.event_tx {
+                    let _ = tx.send(DomainEvent::ServerAuthExpired {
+                        space_id: self.server.space_id,
+                        server_id: self.server.server_id.clone(),
+                        message,
+                    });
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<C> RefreshingAuthClient<C>
+where
+    C: StreamableHttpClient + Send + Sync,
+{
+    /// Run `send` with our token, refreshing and retrying once on a 401.
+    ///
+    /// A caller-supplied `auth_header` isn't ours to refresh and is sent as is.
+    async fn send_with_refresh<T, F, Fut>(
+        &self,
+        auth_header: Option<String>,
+        send: F,
+    ) -> Result<T, StreamableHttpError<C::Error>>
+    where
+        F: Fn(Option<String>) -> Fut + Send,
+        Fut: Future<Output = Result<T, StreamableHttpError<C::Error>>> + Send,
+    {
+        if auth_header.is_some() {
+            return send(auth_header).await;
+        }
+
+        let token = self.auth.get_access_token().await?;
+        match send(Some(token.clone())).await {
+            Err(StreamableHttpError::AuthRequired(_)) => {}
+            result => return result,
+        }
+        let token = self.refresh(&token).await?;
+        send(Some(token)).await
+    }
+}
+
+impl ServerContext {
+    async fn log(&self, level: LogLevel, message: String) {
+        if let Some(log_manager) = &self.log_manager {
+            let log = ServerLog::new(level, LogSource::OAuth, message);
+            if let Err(e) = log_manager
+                .append(&self.space_id.to_string(), &self.server_id, log)
+                .await
+            {
+                error!("Failed to write log: {}", e);
+            }
+        }
+    }
+}
+
+impl<C> StreamableHttpClient for RefreshingAuthClient<C>
+where
+    C: StreamableHttpClient + Send + Sync,
+{
+    type Error = C::Error;
+
+    async fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        session_id: Option<Arc<str>>,
+        auth_header: Option<String>,
+    ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+        self.send_with_refresh(auth_header, |token| {
+            self.auth.http_client.post_message(
+                uri.clone(),
+                message.clone(),
+                session_id.clone(),
+                token,
+            )
+        })
+        .await
+    }
+
+    async fn delete_session(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        auth_header: Option<String>,
+    ) -> Result<(), StreamableHttpError<Self::Error>> {
+        self.send_with_refresh(auth_header, |token| {
+            self.auth
+                .http_client
+                .delete_session(uri.clone(), session_id.clone(), token)
+        })
+        .await
+    }
+
+    async fn get_stream(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        last_event_id: Option<String>,
+        auth_header: Option<String>,
+    ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>> {
+        self.send_with_refresh(auth_header, |token| {
+            self.auth.http_client.get_stream(
+                uri.clone(),
+                session_id.clone(),
+                last_event_id.clone(),
+                token,
+            )
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use oauth2::basic::BasicTokenType;
+    use oauth2::{AccessToken, EmptyExtraTokenFields, StandardTokenResponse};
+    use rmcp::transport::auth::{AuthorizationManager, CredentialStore, StoredCredentials};
+    use rmcp::transport::streamable_http_client::AuthRequiredError;
+    use std::sync::Mutex;
+
+    /// Credential store whose access token the test can swap out.
+    #[derive(Clone)]
+    struct SharedStore(Arc<Mutex<String>>);
+
+    #[async_trait]
+    impl CredentialStore for SharedStore {
+        async fn load(&self) -> Result<Option<StoredCredentials>, AuthError> {
+            let token = self.0.lock().unwrap().clone();
+            Ok(Some(StoredCredentials {
+                client_id: "client".to_string(),
+                token_response: Some(StandardTokenResponse::new(
+                    AccessToken::new(token),
+                    BasicTokenType::Bearer,
+                    EmptyExtraTokenFields {},
+                )),
+                granted_scopes: Vec::new(),
+            }))
+        }
+
+        async fn save(&self, _credentials: StoredCredentials) -> Result<(), AuthError> {
+            Ok(())
+        }
+
+        async fn clear(&self) -> Result<(), AuthError> {
+            Ok(())
+        }
+    }
+
+    /// Rejects the token "stale" with a 401 and records the tokens it was sent.
+    /// With `rotate` set, it first swaps the stored token for "fresh", as a
+    /// concurrent refresh would.
+    #[derive(Clone)]
+    struct FakeServer {
+        seen: Arc<Mutex<Vec<Option<String>>>>,
+        store: SharedStore,
+        rotate: bool,
+    }
+
+    impl StreamableHttpClient for FakeServer {
+        type Error = std::io::Error;
+
+        async fn post_message(
+            &self,
+            _uri: Arc<str>,
+            _message: ClientJsonRpcMessage,
+            _session_id: Option<Arc<str>>,
+            _auth_header: Option<String>,
+        ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+            unimplemented!()
+        }
+
+        async fn delete_session(
+            &self,
+            _uri: Arc<str>,
+            _session_id: Arc<str>,
+            auth_header: Option<String>,
+        ) -> Result<(), StreamableHttpError<Self::Error>> {
+            self.seen.lock().unwrap().push(auth_header.clone());
+            if auth_header.as_deref() != Some("stale") {
+                return Ok(());
+            }
+            if self.rotate {
+                *self.store.0.lock().unwrap() = "fresh".to_string();
+            }
+            Err(StreamableHttpError::AuthRequired(AuthRequiredError {
+                www_authenticate_header: "Bearer error=\"invalid_token\"".to_string(),
+            }))
+        }
+
+        async fn get_stream(
+            &self,
+            _uri: Arc<str>,
+            _session_id: Arc<str>,
+            _last_event_id: Option<String>,
+            _auth_header: Option<String>,
+        ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>>
+        {
+            unimplemented!()
+        }
+    }
+
+    async fn client(
+        rotate: bool,
+    ) -> (
+        RefreshingAuthClient<FakeServer>,
+        FakeServer,
+        tokio::sync::broadcast::Receiver<DomainEvent>,
+    ) {
+        let store = SharedStore(Arc::new(Mutex::new("stale".to_string())));
+        let server = FakeServer {
+            seen: Arc::default(),
+            store: store.clone(),
+            rotate,
+        };
+        // No OAuth client is configured, so refresh grants fail
+        let mut manager = AuthorizationManager::new("https://example.com/mcp")
+            .await
+            .unwrap();
+        manager.set_credential_store(store);
+
+        let (tx, rx) = tokio::sync::broadcast::channel(8);
+        let client = RefreshingAuthClient::new(
+            AuthClient::new(server.clone(), manager),
+            Uuid::new_v4(),
+            "srv",
+            None,
+            Some(tx),
+        );
+        (client, server, rx)
+    }
+
+    #[tokio::test]
+    async fn test_retries_once_with_newer_token() {
+        let (client, server, mut events) = client(true).await;
+
+        client
+            .delete_session("https://example.com/mcp".into(), "session".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            *server.seen.lock().unwrap(),
+            vec![Some("stale".to_string()), Some("fresh".to_string())]
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_reports_auth_expired() {
+        let (client, server, mut events) = client(false).await;
+
+        let err = client
+            .delete_session("https://example.com/mcp".into(), "session".into(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StreamableHttpError::Auth(_)));
+        // No retry without a new token
+        assert_eq!(server.seen.lock().unwrap().len(), 1);
+        assert!(matches!(
+            events.try_recv().unwrap(),
+            DomainEvent::ServerAuthExpired { server_id, .. } if server_id == "srv"
+        ));
+
+        // A caller-supplied header is passed through untouched
+        let err = client
+            .delete_session(
+                "https://example.com/mcp".into(),
+                "session".into(),
+                Some("stale".to_string()),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StreamableHttpError::AuthRequired(_)));
+        assert_eq!(server.seen.lock().unwrap().len(), 2);
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -3,10 +3,11 @@
 //! Handles building the actual runtime transport configuration from
 //! the static registry definition and user-specific installation settings.
 
-use super::ResolvedTransport;
-use mcpmux_core::{InstalledServer, TransportConfig as RegistryConfig};
+use super::{ResolvedTransport, SecretBinding, SecretSource};
+use mcpmux_core::{CredentialType, InstalledServer, TransportConfig as RegistryConfig};
 use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
 
 const MCP_STATE_DIR_ENV: &str = "MCP_STATE_DIR";
 
@@ -33,10 +34,41 @@ fn merge_input_defaults(
     merged
 }
 
+/// Take the values of sensitive inputs out of `values`, so they are resolved
+/// into neither args nor env, and describe how to deliver them instead.
+///
+/// A sensitive input without a value is read from the server's stored API key.
+fn take_secret_bindings(
+    registry_transport: &RegistryConfig,
+    values: &mut HashMap<String, String>,
+) -> Vec<SecretBinding> {
+    registry_transport
+        .metadata()
+        .inputs
+        .iter()
+        .filter_map(|input| {
+            let delivery = input.sensitive?;
+            let source = match values.remove(&input.id) {
+                Some(value) => SecretSource::Value(value),
+                None => SecretSource::Credential(CredentialType::ApiKey),
+            };
+            Some(SecretBinding {
+                input_id: input.id.clone(),
+                delivery,
+                source,
+            })
+        })
+        .collect()
+}
+
 /// Build transport config from registry transport and installed server
+///
+/// `space_env` is the env of the server's space; STDIO servers start from it,
+/// and their own env wins on conflict.
 pub fn build_transport_config(
     registry_transport: &RegistryConfig,
     installed: &InstalledServer,
+    space_env: &HashMap<String, String>,
     base_state_dir: Option<&Path>,
 ) -> ResolvedTransport {
     tracing::debug!(
@@ -47,12 +79,18 @@ pub fn build_transport_config(
     );
 
     // Merge user-provided values with defaults from input definitions
-    let effective_values = merge_input_defaults(registry_transport, &installed.input_values);
+    let mut effective_values = merge_input_defaults(registry_transport, &installed.input_values);
 
     match registry_transport {
         RegistryConfig::Stdio {
-            command, args, env, ..
+            command,
+            args,
+            env,
+            connect_timeout_secs,
+            ..
         } => {
+            let secrets = take_secret_bindings(registry_transport, &mut effective_values);
+
             let resolved_command = resolve_placeholders(command, &effective_values);
             let mut resolved_args: Vec<String> = args
                 .iter()
@@ -62,8 +100,9 @@ pub fn build_transport_config(
             // Append user's extra args
             resolved_args.extend(installed.args_append.clone());
 
-            // Build env from registry + input values + env_overrides
-            let mut resolved_env = HashMap::new();
+            // Build env from space env + registry + input values + env_overrides
+            // 0. Start with the space's env; everything below wins over it
+            let mut resolved_env = space_env.clone();
 
             // 1. Start with registry env
             for (k, v) in env {
@@ -99,9 +138,19 @@ pub fn build_transport_config(
                 command: resolved_command,
                 args: resolved_args,
                 env: resolved_env,
+                secrets,
+                connect_timeout: connect_timeout_secs.map(Duration::from_secs),
             }
         }
-        RegistryConfig::Http { url, headers, .. } => {
+        RegistryConfig::Http {
+            url,
+            headers,
+            proxy,
+            tls,
+            connect_timeout_secs,
+            request_timeout_secs,
+            ..
+        } => {
             let resolved_url = resolve_placeholders(url, &effective_values);
 
             // Resolve headers from registry
@@ -116,6 +165,10 @@ pub fn build_transport_config(
             ResolvedTransport::Http {
                 url: resolved_url,
                 headers: resolved_headers,
+                proxy: proxy.clone(),
+                tls: tls.clone(),
+                connect_timeout: connect_timeout_secs.map(Duration::from_secs),
+                request_timeout: request_timeout_secs.map(Duration::from_secs),
             }
         }
     }
@@ -157,7 +210,7 @@ fn resolve_placeholders(template: &str, input_values: &HashMap<String, String>)
 #[cfg(test)]
 mod tests {
     use super::*;
-    use mcpmux_core::{InputDefinition, TransportMetadata};
+    use mcpmux_core::{InputDefinition, SecretDelivery, TransportMetadata};
 
     fn make_installed(input_values: HashMap<String, String>) -> InstalledServer {
         InstalledServer::new("test-space", "test-server").with_inputs(input_values)
@@ -175,6 +228,7 @@ mod tests {
             placeholder: None,
             obtain_url: None,
             obtain_instructions: None,
+            sensitive: None,
         }
     }
 
@@ -184,6 +238,7 @@ mod tests {
             command: "node".to_string(),
             args: vec!["server.js".to_string()],
             env: HashMap::from([("LOG_LEVEL".to_string(), "${input:LOG_LEVEL}".to_string())]),
+            connect_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![make_input("LOG_LEVEL", Some("info"))],
             },
@@ -191,7 +246,7 @@ mod tests {
 
         let installed = make_installed(HashMap::new()); // No user values
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Stdio { env, .. } => {
@@ -208,6 +263,7 @@ mod tests {
             command: "node".to_string(),
             args: vec![],
             env: HashMap::from([("LOG_LEVEL".to_string(), "${input:LOG_LEVEL}".to_string())]),
+            connect_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![make_input("LOG_LEVEL", Some("info"))],
             },
@@ -218,7 +274,7 @@ mod tests {
             "debug".to_string(),
         )]));
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Stdio { env, .. } => {
@@ -235,6 +291,7 @@ mod tests {
             command: "node".to_string(),
             args: vec!["--port".to_string(), "${input:PORT}".to_string()],
             env: HashMap::new(),
+            connect_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![make_input("PORT", Some("8080"))],
             },
@@ -242,7 +299,7 @@ mod tests {
 
         let installed = make_installed(HashMap::new());
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Stdio { args, .. } => {
@@ -253,12 +310,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sensitive_inputs_stay_out_of_args_and_env() {
+        let mut token = make_input("TOKEN", None);
+        token.sensitive = Some(SecretDelivery::File);
+        let mut api_key = make_input("API_KEY", None);
+        api_key.sensitive = Some(SecretDelivery::Fd);
+        let transport = RegistryConfig::Stdio {
+            command: "node".to_string(),
+            args: vec!["--token".to_string(), "${input:TOKEN}".to_string()],
+            env: HashMap::from([("KEY".to_string(), "${input:API_KEY}".to_string())]),
+            connect_timeout_secs: None,
+            metadata: TransportMetadata {
+                inputs: vec![token, api_key],
+            },
+        };
+
+        let installed = make_installed(HashMap::from([(
+            "TOKEN".to_string(),
+            "tok-123".to_string(),
+        )]));
+
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
+
+        match resolved {
+            ResolvedTransport::Stdio {
+                args, env, secrets, ..
+            } => {
+                // Placeholders are left for the transport to fill in
+                assert_eq!(args[1], "${input:TOKEN}");
+                assert_eq!(env.get("KEY"), Some(&"${input:API_KEY}".to_string()));
+                assert!(!env.contains_key("TOKEN"));
+                assert!(!env.values().any(|v| v == "tok-123"));
+
+                assert_eq!(secrets.len(), 2);
+                assert_eq!(secrets[0].input_id, "TOKEN");
+                assert_eq!(secrets[0].delivery, SecretDelivery::File);
+                assert!(secrets[0].source == SecretSource::Value("tok-123".to_string()));
+                assert_eq!(secrets[1].input_id, "API_KEY");
+                assert_eq!(secrets[1].delivery, SecretDelivery::Fd);
+                assert!(secrets[1].source == SecretSource::Credential(CredentialType::ApiKey));
+            }
+            _ => panic!("Expected Stdio transport"),
+        }
+    }
+
     #[test]
     fn test_default_resolves_in_command() {
         let transport = RegistryConfig::Stdio {
             command: "${input:BINARY_PATH}".to_string(),
             args: vec![],
             env: HashMap::new(),
+            connect_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![make_input("BINARY_PATH", Some("/usr/local/bin/mcp"))],
             },
@@ -266,7 +369,7 @@ mod tests {
 
         let installed = make_installed(HashMap::new());
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Stdio { command, .. } => {
@@ -281,6 +384,10 @@ mod tests {
         let transport = RegistryConfig::Http {
             url: "https://api.example.com/${input:API_VERSION}/mcp".to_string(),
             headers: HashMap::new(),
+            proxy: None,
+            tls: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![make_input("API_VERSION", Some("v2"))],
             },
@@ -288,7 +395,7 @@ mod tests {
 
         let installed = make_installed(HashMap::new());
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Http { url, .. } => {
@@ -303,6 +410,10 @@ mod tests {
         let transport = RegistryConfig::Http {
             url: "https://api.example.com/mcp".to_string(),
             headers: HashMap::from([("X-Api-Key".to_string(), "${input:API_KEY}".to_string())]),
+            proxy: None,
+            tls: None,
+            connect_timeout_secs: None,
+            request_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![make_input("API_KEY", Some("default-key"))],
             },
@@ -310,7 +421,7 @@ mod tests {
 
         let installed = make_installed(HashMap::new());
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Http { headers, .. } => {
@@ -320,6 +431,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_connect_timeouts() {
+        let http = RegistryConfig::Http {
+            url: "https://api.example.com/mcp".to_string(),
+            headers: HashMap::new(),
+            proxy: None,
+            tls: None,
+            connect_timeout_secs: Some(5),
+            request_timeout_secs: Some(90),
+            metadata: TransportMetadata::default(),
+        };
+        let installed = make_installed(HashMap::new());
+
+        let resolved = build_transport_config(&http, &installed, &HashMap::new(), None);
+        match &resolved {
+            ResolvedTransport::Http {
+                request_timeout, ..
+            } => assert_eq!(*request_timeout, Some(Duration::from_secs(90))),
+            _ => panic!("Expected Http transport"),
+        }
+        // The server's own setting beats a gateway-wide override
+        let fallback = Some(Duration::from_secs(45));
+        assert_eq!(resolved.connect_timeout(fallback), Duration::from_secs(5));
+
+        let docker = RegistryConfig::Stdio {
+            command: "docker".to_string(),
+            args: vec![],
+            env: HashMap::new(),
+            connect_timeout_secs: None,
+            metadata: TransportMetadata::default(),
+        };
+        let resolved = build_transport_config(&docker, &installed, &HashMap::new(), None);
+        assert_eq!(resolved.connect_timeout(fallback), Duration::from_secs(45));
+        assert!(resolved.connect_timeout(None) > Duration::from_secs(60));
+    }
+
     #[test]
     fn test_multiple_defaults_some_overridden() {
         let transport = RegistryConfig::Stdio {
@@ -330,6 +477,7 @@ mod tests {
                 ("PORT".to_string(), "${input:PORT}".to_string()),
                 ("API_KEY".to_string(), "${input:API_KEY}".to_string()),
             ]),
+            connect_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![
                     make_input("LOG_LEVEL", Some("info")),
@@ -345,7 +493,7 @@ mod tests {
             ("API_KEY".to_string(), "secret123".to_string()),
         ]));
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Stdio { env, .. } => {
@@ -360,12 +508,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_space_env_is_inherited_and_server_env_wins() {
+        let transport = RegistryConfig::Stdio {
+            command: "node".to_string(),
+            args: vec![],
+            env: HashMap::from([("LOG_LEVEL".to_string(), "warn".to_string())]),
+            connect_timeout_secs: None,
+            metadata: TransportMetadata::default(),
+        };
+        let mut installed = make_installed(HashMap::new());
+        installed
+            .env_overrides
+            .insert("REGION".to_string(), "eu".to_string());
+        let space_env = HashMap::from([
+            ("HTTP_PROXY".to_string(), "http://proxy:3128".to_string()),
+            ("LOG_LEVEL".to_string(), "debug".to_string()),
+            ("REGION".to_string(), "us".to_string()),
+        ]);
+
+        let resolved = build_transport_config(&transport, &installed, &space_env, None);
+
+        match resolved {
+            ResolvedTransport::Stdio { env, .. } => {
+                assert_eq!(
+                    env.get("HTTP_PROXY"),
+                    Some(&"http://proxy:3128".to_string())
+                );
+                // Registry env and user overrides both beat the space
+                assert_eq!(env.get("LOG_LEVEL"), Some(&"warn".to_string()));
+                assert_eq!(env.get("REGION"), Some(&"eu".to_string()));
+            }
+            _ => panic!("Expected Stdio transport"),
+        }
+    }
+
     #[test]
     fn test_no_default_leaves_placeholder_unresolved() {
         let transport = RegistryConfig::Stdio {
             command: "node".to_string(),
             args: vec![],
             env: HashMap::from([("API_KEY".to_string(), "${input:API_KEY}".to_string())]),
+            connect_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![make_input("API_KEY", None)],
             },
@@ -373,7 +557,7 @@ mod tests {
 
         let installed = make_installed(HashMap::new());
 
-        let resolved = build_transport_config(&transport, &installed, None);
+        let resolved = build_transport_config(&transport, &installed, &HashMap::new(), None);
 
         match resolved {
             ResolvedTransport::Stdio { env, .. } => {
@@ -390,6 +574,7 @@ mod tests {
             command: "node".to_string(),
             args: vec![],
             env: HashMap::new(),
+            connect_timeout_secs: None,
             metadata: TransportMetadata {
                 inputs: vec![
                     make_input("A", Some("default_a")),
@@ -0,0 +1,260 @@
+//! Command resolution for STDIO servers
+//!
+//! Before spawning, [`StdioTransport`](super::StdioTransport) turns the
+//! configured `command` into the path of a binary with a [`CommandResolver`].
+//! The default, [`WhichResolver`], searches PATH like a shell would. Tests can
+//! inject a deterministic resolver, and sandboxed setups an
+//! [`AllowlistResolver`] that refuses anything not explicitly permitted.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Why a command couldn't be resolved.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ResolveError {
+    /// No binary of that name was found.
+    #[error("Command not found: {0}. Ensure it's installed and in PATH.")]
+    NotFound(String),
+    /// The resolver refuses to run this command.
+    #[error("Command not permitted: {0}")]
+    CommandNotPermitted(String),
+}
+
+/// Turns a configured command into the binary to spawn.
+pub trait CommandResolver: Send + Sync {
+    /// Resolve `command`, searching `shell_path` (the user's login shell
+    /// PATH) when given instead of the app's own PATH.
+    fn resolve(
+        &self,
+        command: &str,
+        shell_path: Option<&OsString>,
+    ) -> Result<PathBuf, ResolveError>;
+}
+
+/// Resolves commands with `which`, also trying a `.exe` suffix.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhichResolver;
+
+impl CommandResolver for WhichResolver {
+    fn resolve(
+        &self,
+        command: &str,
+        shell_path: Option<&OsString>,
+    ) -> Result<PathBuf, ResolveError> {
+        resolve_command(command, shell_path)
+            .map_err(|_| ResolveError::NotFound(command.to_string()))
+    }
+}
+
+/// Resolves only commands on an explicit list.
+///
+/// Entries are either bare names (`npx`), which permit the command given by
+/// that name and looked up as usual, or paths, which permit whatever command
+/// resolves to exactly that binary. A command given as a path needs a path
+/// entry, so `/tmp/npx` isn't allowed by `npx`.
+pub struct AllowlistResolver {
+    names: HashSet<String>,
+    paths: HashSet<PathBuf>,
+    inner: Arc<dyn CommandResolver>,
+}
+
+impl AllowlistResolver {
+    /// Permit the `allowed` names and paths, resolved with [`WhichResolver`].
+    pub fn new<I, S>(allowed: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut names = HashSet::new();
+        let mut paths = HashSet::new();
+        for entry in allowed {
+            let entry = entry.as_ref();
+            if is_bare_name(entry) {
+                names.insert(strip_exe(entry).to_string());
+            } else {
+                paths.insert(PathBuf::from(entry));
+            }
+        }
+        Self {
+            names,
+            paths,
+            inner: Arc::new(WhichResolver),
+        }
+    }
+
+    /// Look permitted commands up with `inner` instead of [`WhichResolver`].
+    pub fn with_resolver(mut self, inner: Arc<dyn CommandResolver>) -> Self {
+        self.inner = inner;
+        self
+    }
+}
+
+impl CommandResolver for AllowlistResolver {
+    fn resolve(
+        &self,
+        command: &str,
+        shell_path: Option<&OsString>,
+    ) -> Result<PathBuf, ResolveError> {
+        if is_bare_name(command) && self.names.contains(strip_exe(command)) {
+            return self.inner.resolve(command, shell_path);
+        }
+        // Whether a refused command exists is none of the caller's business
+        match self.inner.resolve(command, shell_path) {
+            Ok(path) if self.paths.contains(&path) => Ok(path),
+            _ => Err(ResolveError::CommandNotPermitted(command.to_string())),
+        }
+    }
+}
+
+/// Whether `command` is a name to look up rather than a path.
+fn is_bare_name(command: &str) -> bool {
+    !command.contains(['/', '\\'])
+}
+
+fn strip_exe(name: &str) -> &str {
+    name.strip_suffix(".exe").unwrap_or(name)
+}
+
+/// Resolve a command binary using the shell-resolved PATH when available.
+///
+/// Falls back to the standard `which::which()` (which uses the process PATH)
+/// if no shell PATH was resolved.
+fn resolve_command(command: &str, shell_path: Option<&OsString>) -> Result<PathBuf, which::Error> {
+    if let Some(path) = shell_path {
+        which::which_in(command, Some(path), ".")
+            .or_else(|_| which::which_in(format!("{}.exe", command), Some(path), "."))
+    } else {
+        which::which(command).or_else(|_| which::which(format!("{}.exe", command)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::shell_env;
+    use super::*;
+    use std::path::Path;
+
+    // ── resolve_command tests ──────────────────────────────────────
+
+    #[test]
+    fn test_resolve_command_finds_sh_with_shell_path() {
+        // /bin/sh exists on every Unix system
+        #[cfg(unix)]
+        {
+            let path = OsString::from("/bin:/usr/bin");
+            let result = resolve_command("sh", Some(&path));
+            assert!(result.is_ok(), "Should find 'sh' in /bin:/usr/bin");
+        }
+    }
+
+    #[test]
+    fn test_resolve_command_finds_command_without_shell_path() {
+        // Without shell_path, falls back to which::which (uses process PATH)
+        #[cfg(unix)]
+        {
+            let result = resolve_command("sh", None);
+            assert!(result.is_ok(), "Should find 'sh' via process PATH");
+        }
+    }
+
+    #[test]
+    fn test_resolve_command_returns_error_for_nonexistent() {
+        let fake_path = OsString::from("/nonexistent/path");
+        let result = resolve_command("this_command_surely_does_not_exist_xyz", Some(&fake_path));
+        assert!(result.is_err(), "Should fail for nonexistent command");
+    }
+
+    #[test]
+    fn test_resolve_command_not_found_in_restricted_path() {
+        // Even if 'sh' exists, it shouldn't be found if PATH points elsewhere
+        let path = OsString::from("/tmp/empty_dir_that_does_not_exist");
+        let result = resolve_command("sh", Some(&path));
+        assert!(
+            result.is_err(),
+            "Should not find 'sh' in a path that doesn't contain it"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_command_with_full_shell_path() {
+        // Use the actual shell-resolved PATH to find a real command
+        if let Some(shell_path) = shell_env::get_shell_path() {
+            let result = resolve_command("sh", Some(&shell_path));
+            assert!(result.is_ok(), "Should find 'sh' using resolved shell PATH");
+        }
+    }
+
+    // ── allowlist tests ────────────────────────────────────────────
+
+    /// Resolves every command to `/fake/bin/<name>`, or a path to itself.
+    struct FakeResolver;
+
+    impl CommandResolver for FakeResolver {
+        fn resolve(
+            &self,
+            command: &str,
+            _shell_path: Option<&OsString>,
+        ) -> Result<PathBuf, ResolveError> {
+            if command == "missing" {
+                return Err(ResolveError::NotFound(command.to_string()));
+            }
+            if is_bare_name(command) {
+                Ok(Path::new("/fake/bin").join(command))
+            } else {
+                Ok(PathBuf::from(command))
+            }
+        }
+    }
+
+    fn allowlist(allowed: &[&str]) -> AllowlistResolver {
+        AllowlistResolver::new(allowed).with_resolver(Arc::new(FakeResolver))
+    }
+
+    #[test]
+    fn test_allowlist_permits_listed_names() {
+        let resolver = allowlist(&["npx", "uvx.exe"]);
+        assert_eq!(
+            resolver.resolve("npx", None),
+            Ok(PathBuf::from("/fake/bin/npx"))
+        );
+        assert_eq!(
+            resolver.resolve("uvx", None),
+            Ok(PathBuf::from("/fake/bin/uvx"))
+        );
+        assert_eq!(
+            resolver.resolve("missing", None),
+            Err(ResolveError::CommandNotPermitted("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_allowlist_rejects_unlisted_commands() {
+        let resolver = allowlist(&["npx"]);
+        assert_eq!(
+            resolver.resolve("bash", None),
+            Err(ResolveError::CommandNotPermitted("bash".to_string()))
+        );
+        // A path with a listed basename is still a different binary
+        assert_eq!(
+            resolver.resolve("/tmp/npx", None),
+            Err(ResolveError::CommandNotPermitted("/tmp/npx".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_allowlist_path_entries_match_resolved_binary() {
+        let resolver = allowlist(&["/fake/bin/node", "/opt/tools/server"]);
+        assert_eq!(
+            resolver.resolve("node", None),
+            Ok(PathBuf::from("/fake/bin/node"))
+        );
+        assert_eq!(
+            resolver.resolve("/opt/tools/server", None),
+            Ok(PathBuf::from("/opt/tools/server"))
+        );
+        assert!(resolver.resolve("python", None).is_err());
+    }
+}
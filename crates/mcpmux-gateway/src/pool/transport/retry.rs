@@ -0,0 +1,288 @@
+//! Connect retries for transient failures
+//!
+//! [`RetryingTransport`] wraps another [`Transport`] and retries `connect`
+//! with exponential backoff and jitter when the failure looks transient (a
+//! Docker daemon still starting, a flaky network). Configuration errors such as
+//! a missing command fail immediately, and `OAuthRequired` is passed straight
+//! through so the user is prompted without delay.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+use tracing::{debug, warn};
+
+use super::{Transport, TransportConnectResult, TransportType, ValidationResult};
+
+/// Failure messages that retrying cannot fix.
+const PERMANENT_FAILURES: &[&str] = &[
+    "Command not found",
+    "Working directory not found",
+    "Invalid URL",
+    "Invalid proxy URL",
+    "Invalid TLS config",
+    "Invalid header",
+    "Invalid token format",
+    "Failed to build HTTP client",
+];
+
+/// Whether a `TransportConnectResult::Failed` message is worth retrying.
+pub fn is_retryable_failure(message: &str) -> bool {
+    !PERMANENT_FAILURES
+        .iter()
+        .any(|prefix| message.starts_with(prefix))
+}
+
+/// Backoff and budget for connect retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectRetryPolicy {
+    /// Retries after the first attempt before giving up.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further retry.
+    pub base_backoff: Duration,
+    /// Upper bound on the delay between retries.
+    pub max_backoff: Duration,
+}
+
+impl Default for ConnectRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ConnectRetryPolicy {
+    /// Delay before the given (1-based) retry.
+    ///
+    /// The exponential delay is jittered into its upper half so several
+    /// servers failing together don't retry in lockstep.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(31);
+        let delay = self
+            .base_backoff
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_backoff);
+        let half = delay / 2;
+        half + half.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+}
+
+/// Transport wrapper that retries transient connect failures.
+pub struct RetryingTransport<T> {
+    inner: T,
+    policy: ConnectRetryPolicy,
+}
+
+impl<T: Transport> RetryingTransport<T> {
+    pub fn new(inner: T, policy: ConnectRetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// The wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<T: Transport> Transport for RetryingTransport<T> {
+    async fn connect(&self) -> TransportConnectResult {
+        let mut retry = 0;
+        loop {
+            match self.inner.connect().await {
+                TransportConnectResult::Failed(err)
+                    if retry < self.policy.max_retries && is_retryable_failure(&err) =>
+                {
+                    retry += 1;
+                    let delay = self.policy.backoff(retry);
+                    warn!(
+                        transport = %self.inner.description(),
+                        retry = retry,
+                        delay_ms = delay.as_millis() as u64,
+                        "Connect failed, retrying: {}",
+                        err
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                TransportConnectResult::Failed(err) => {
+                    debug!(
+                        transport = %self.inner.description(),
+                        retries = retry,
+                        "Connect failed, not retrying: {}",
+                        err
+                    );
+                    return TransportConnectResult::Failed(err);
+                }
+                other => return other,
+            }
+        }
+    }
+
+    fn transport_type(&self) -> TransportType {
+        self.inner.transport_type()
+    }
+
+    fn description(&self) -> String {
+        self.inner.description()
+    }
+
+    async fn shutdown(&self, grace: Duration) -> bool {
+        self.inner.shutdown(grace).await
+    }
+
+    async fn complete_oauth(&self, code: &str, state: &str) -> anyhow::Result<()> {
+        self.inner.complete_oauth(code, state).await
+    }
+
+    /// Validates once: a dry run should report transient failures, not hide them.
+    async fn validate(&self) -> ValidationResult {
+        self.inner.validate().await
+    }
+
+    async fn is_alive(&self) -> bool {
+        self.inner.is_alive().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Returns scripted results, then `Failed("exhausted")`.
+    struct ScriptedTransport {
+        results: Mutex<Vec<TransportConnectResult>>,
+        calls: Mutex<u32>,
+    }
+
+    impl ScriptedTransport {
+        fn new(mut results: Vec<TransportConnectResult>) -> Self {
+            results.reverse();
+            Self {
+                results: Mutex::new(results),
+                calls: Mutex::new(0),
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Transport for ScriptedTransport {
+        async fn connect(&self) -> TransportConnectResult {
+            *self.calls.lock().unwrap() += 1;
+            self.results
+                .lock()
+                .unwrap()
+                .pop()
+                .unwrap_or_else(|| TransportConnectResult::Failed("exhausted".to_string()))
+        }
+
+        fn transport_type(&self) -> TransportType {
+            TransportType::Stdio
+        }
+
+        fn description(&self) -> String {
+            "scripted".to_string()
+        }
+    }
+
+    fn failed(message: &str) -> TransportConnectResult {
+        TransportConnectResult::Failed(message.to_string())
+    }
+
+    #[test]
+    fn test_is_retryable_failure() {
+        assert!(is_retryable_failure("Connection timeout (30s)."));
+        assert!(is_retryable_failure(
+            "MCP handshake failed: connection closed. Ensure Docker Desktop is running."
+        ));
+        assert!(is_retryable_failure(
+            "HTTP connection failed: error sending request"
+        ));
+        assert!(!is_retryable_failure(
+            "Command not found: uvx. Ensure it's installed and in PATH."
+        ));
+        assert!(!is_retryable_failure(
+            "Invalid URL: relative URL without a base"
+        ));
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let policy = ConnectRetryPolicy {
+            max_retries: 10,
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+        };
+        for _ in 0..50 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = policy.backoff(3);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1000));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_transient_failures_until_success() {
+        let inner = ScriptedTransport::new(vec![
+            failed("Connection timeout (30s)."),
+            failed("MCP handshake failed: early eof."),
+            TransportConnectResult::OAuthRequired {
+                server_url: "https://example.com".to_string(),
+                challenge: None,
+            },
+        ]);
+        let transport = RetryingTransport::new(inner, ConnectRetryPolicy::default());
+
+        let result = transport.connect().await;
+        assert!(matches!(
+            result,
+            TransportConnectResult::OAuthRequired { .. }
+        ));
+        assert_eq!(transport.inner().calls(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_after_budget() {
+        let inner = ScriptedTransport::new(vec![]);
+        let policy = ConnectRetryPolicy {
+            max_retries: 2,
+            ..Default::default()
+        };
+        let transport = RetryingTransport::new(inner, policy);
+
+        let result = transport.connect().await;
+        assert!(matches!(result, TransportConnectResult::Failed(ref e) if e == "exhausted"));
+        assert_eq!(transport.inner().calls(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_permanent_failure_and_oauth_not_retried() {
+        let inner = ScriptedTransport::new(vec![failed("Command not found: nope.")]);
+        let transport = RetryingTransport::new(inner, ConnectRetryPolicy::default());
+        assert!(matches!(
+            transport.connect().await,
+            TransportConnectResult::Failed(_)
+        ));
+        assert_eq!(transport.inner().calls(), 1);
+
+        let inner = ScriptedTransport::new(vec![TransportConnectResult::OAuthRequired {
+            server_url: "https://example.com".to_string(),
+            challenge: None,
+        }]);
+        let transport = RetryingTransport::new(inner, ConnectRetryPolicy::default());
+        assert!(matches!(
+            transport.connect().await,
+            TransportConnectResult::OAuthRequired { .. }
+        ));
+        assert_eq!(transport.inner().calls(), 1);
+    }
+}
@@ -0,0 +1,519 @@
+//! Sensitive input delivery for STDIO server processes
+//!
+//! A process's command line is readable by every local user via `ps` or
+//! `/proc/<pid>/cmdline`, and its environment ends up in `/proc/<pid>/environ`
+//! and crash reports. Inputs marked `sensitive` in the server definition are
+//! therefore left out of the resolved args and env (see the `resolution`
+//! module) and carried as [`SecretBinding`]s instead. Right before spawning,
+//! each value is taken from the installed server's inputs, or decrypted from
+//! the [`CredentialRepository`] when none was given, and delivered per its
+//! [`SecretDelivery`]:
+//!
+//! - `File`: written to a new file in the temp dir that only the user can
+//!   read (`0600` on Unix), removed once the process exits.
+//! - `Fd`: written into a pipe whose read end the child inherits, readable
+//!   once as `/dev/fd/N`. Unix only; elsewhere, for supervised servers (a
+//!   restarted child couldn't re-read the pipe) and for values larger than
+//!   the pipe buffer, a file is used instead.
+//!
+//! The child finds where a value was delivered in place of `${input:ID}` in
+//! its args and env, and in an `ID_FILE` env var.
+//!
+//! Env values may also reference a credential stored for any server in the
+//! same space as `${secret:SERVER_ID/CREDENTIAL_TYPE}` (`${secret:SERVER_ID}`
+//! for its API key), so a token shared by several servers lives in one
+//! encrypted place. References are decrypted right before spawning and
+//! substituted into the env; one that can't be resolved fails the connect
+//! with [`SecretReferenceUnresolved`].
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use mcpmux_core::{CredentialRepository, CredentialType, SecretDelivery};
+use tokio::process::Command;
+use tracing::warn;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+/// Largest value sent through a pipe. Writes up to `PIPE_BUF` never block, so
+/// the pipe can be filled before the reader exists.
+#[cfg(unix)]
+const MAX_FD_SECRET_LEN: usize = 4096;
+
+/// Where a sensitive input value comes from.
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecretSource {
+    /// Given with the installed server's input values.
+    Value(String),
+    /// The server's stored credential of this type, decrypted at connect.
+    Credential(CredentialType),
+}
+
+/// A sensitive input to hand to a STDIO server outside its args and env.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretBinding {
+    /// Input ID, as referenced by `${input:ID}`.
+    pub input_id: String,
+    pub delivery: SecretDelivery,
+    pub source: SecretSource,
+}
+
+impl fmt::Debug for SecretBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match &self.source {
+            SecretSource::Value(_) => "value".to_string(),
+            SecretSource::Credential(credential_type) => credential_type.to_string(),
+        };
+        f.debug_struct("SecretBinding")
+            .field("input_id", &self.input_id)
+            .field("delivery", &self.delivery)
+            .field("source", &source)
+            .finish()
+    }
+}
+
+/// A `${secret:...}` reference in a server's env that couldn't be resolved.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Secret reference '${{secret:{reference}}}' could not be resolved: {reason}")]
+pub struct SecretReferenceUnresolved {
+    /// The reference, without `${secret:` and `}`.
+    pub reference: String,
+    pub reason: String,
+}
+
+/// Distinct `${secret:...}` references in `env` values.
+fn secret_references(env: &HashMap<String, String>) -> BTreeSet<String> {
+    let mut references = BTreeSet::new();
+    for value in env.values() {
+        let mut rest = value.as_str();
+        while let Some(start) = rest.find("${secret:") {
+            rest = &rest[start + "${secret:".len()..];
+            let Some(end) = rest.find('}') else {
+                break;
+            };
+            references.insert(rest[..end].to_string());
+            rest = &rest[end + 1..];
+        }
+    }
+    references
+}
+
+/// Split a reference into the server and type of the credential it names.
+fn parse_reference(reference: &str) -> Option<(&str, CredentialType)> {
+    let (server_id, credential_type) = match reference.split_once('/') {
+        Some((server_id, credential_type)) => (server_id, CredentialType::parse(credential_type)?),
+        None => (reference, CredentialType::ApiKey),
+    };
+    (!server_id.is_empty()).then_some((server_id, credential_type))
+}
+
+/// Decrypt the credential named by each `${secret:...}` reference in `env`,
+/// looking them up in the server's own space.
+pub(super) async fn fetch_secret_references(
+    env: &HashMap<String, String>,
+    credentials: Option<&Arc<dyn CredentialRepository>>,
+    space_id: Uuid,
+) -> Result<Vec<(String, Zeroizing<String>)>, SecretReferenceUnresolved> {
+    let mut values = Vec::new();
+    for reference in secret_references(env) {
+        let unresolved = |reason: String| SecretReferenceUnresolved {
+            reference: reference.clone(),
+            reason,
+        };
+        let (server_id, credential_type) = parse_reference(&reference).ok_or_else(|| {
+            unresolved("expected SERVER_ID or SERVER_ID/CREDENTIAL_TYPE".to_string())
+        })?;
+        let repo = credentials.ok_or_else(|| unresolved("no credential store".to_string()))?;
+        let value = match repo.get(&space_id, server_id, &credential_type).await {
+            Ok(Some(credential)) => Zeroizing::new(credential.value.clone()),
+            Ok(None) => {
+                return Err(unresolved(format!(
+                    "no {} credential stored for server '{}'",
+                    credential_type, server_id
+                )))
+            }
+            Err(e) => return Err(unresolved(format!("failed to read credential: {}", e))),
+        };
+        values.push((reference, value));
+    }
+    Ok(values)
+}
+
+/// Substitute fetched `${secret:...}` references in `env` values.
+pub(super) fn apply_secret_references(
+    values: &[(String, Zeroizing<String>)],
+    env: &mut HashMap<String, String>,
+) {
+    for (reference, value) in values {
+        let placeholder = format!("${{secret:{}}}", reference);
+        for env_value in env.values_mut() {
+            if env_value.contains(&placeholder) {
+                *env_value = env_value.replace(&placeholder, value);
+            }
+        }
+    }
+}
+
+/// Look up the value of each binding, reading stored credentials just now
+/// so they aren't held in plaintext any longer than the spawn needs.
+pub(super) async fn fetch_secrets(
+    bindings: &[SecretBinding],
+    credentials: Option<&Arc<dyn CredentialRepository>>,
+    space_id: Uuid,
+    server_id: &str,
+) -> Result<Vec<(SecretBinding, Zeroizing<String>)>, String> {
+    let mut values = Vec::with_capacity(bindings.len());
+    for binding in bindings {
+        let value = match &binding.source {
+            SecretSource::Value(value) => Zeroizing::new(value.clone()),
+            SecretSource::Credential(credential_type) => {
+                let repo = credentials.ok_or_else(|| {
+                    format!(
+                        "No credential store to read sensitive input '{}' from",
+                        binding.input_id
+                    )
+                })?;
+                match repo.get(&space_id, server_id, credential_type).await {
+                    Ok(Some(credential)) => Zeroizing::new(credential.value.clone()),
+                    Ok(None) => {
+                        return Err(format!(
+                            "No {} credential stored for sensitive input '{}'",
+                            credential_type, binding.input_id
+                        ))
+                    }
+                    Err(e) => {
+                        return Err(format!(
+                            "Failed to read credential for sensitive input '{}': {}",
+                            binding.input_id, e
+                        ))
+                    }
+                }
+            }
+        };
+        values.push((binding.clone(), value));
+    }
+    Ok(values)
+}
+
+/// Secrets delivered for one server process.
+///
+/// Dropping it removes the files and closes the pipes, so it is kept for as
+/// long as the process runs.
+#[derive(Default)]
+pub(super) struct DeliveredSecrets {
+    /// Input ID and the path the child reads its value from.
+    locations: Vec<(String, String)>,
+    files: Vec<PathBuf>,
+    #[cfg(unix)]
+    fds: Vec<std::os::fd::OwnedFd>,
+}
+
+impl DeliveredSecrets {
+    /// Write each value to a file or pipe. `allow_fd` is off for processes
+    /// that may be spawned more than once.
+    pub(super) fn deliver(
+        values: &[(SecretBinding, Zeroizing<String>)],
+        allow_fd: bool,
+    ) -> std::io::Result<Self> {
+        let mut delivered = Self::default();
+        for (binding, value) in values {
+            let location = match binding.delivery {
+                #[cfg(unix)]
+                SecretDelivery::Fd if allow_fd && value.len() <= MAX_FD_SECRET_LEN => {
+                    use std::os::fd::AsRawFd;
+
+                    let fd = write_pipe(value)?;
+                    let location = format!("/dev/fd/{}", fd.as_raw_fd());
+                    delivered.fds.push(fd);
+                    location
+                }
+                _ => {
+                    let path = write_file(value)?;
+                    let location = path.to_string_lossy().to_string();
+                    delivered.files.push(path);
+                    location
+                }
+            };
+            delivered
+                .locations
+                .push((binding.input_id.clone(), location));
+        }
+        #[cfg(not(unix))]
+        let _ = allow_fd;
+        Ok(delivered)
+    }
+
+    /// Point `${input:ID}` references in `args` and `env` at the delivered
+    /// values, and set `ID_FILE` for each.
+    pub(super) fn apply(&self, args: &mut [String], env: &mut HashMap<String, String>) {
+        for (input_id, location) in &self.locations {
+            let placeholder = format!("${{input:{}}}", input_id);
+            for value in args.iter_mut().chain(env.values_mut()) {
+                if value.contains(&placeholder) {
+                    *value = value.replace(&placeholder, location);
+                }
+            }
+            env.insert(format!("{}_FILE", input_id), location.clone());
+        }
+    }
+
+    /// Have `cmd` pass the pipes on to the child. Does nothing off Unix.
+    pub(super) fn inherit(&self, cmd: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsRawFd;
+
+            if self.fds.is_empty() {
+                return;
+            }
+            let fds: Vec<i32> = self.fds.iter().map(|fd| fd.as_raw_fd()).collect();
+            // SAFETY: the hook runs between fork and exec and only calls
+            // fcntl, which is async-signal-safe, without allocating.
+            unsafe {
+                cmd.pre_exec(move || {
+                    for fd in &fds {
+                        set_cloexec(*fd, false)?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = cmd;
+    }
+}
+
+impl Drop for DeliveredSecrets {
+    fn drop(&mut self) {
+        for path in &self.files {
+            if let Err(e) = std::fs::remove_file(path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!(path = %path.display(), error = %e, "Failed to remove secret file");
+                }
+            }
+        }
+    }
+}
+
+/// Write `value` to a new file only the current user can read.
+///
+/// On Windows the temp dir is already per-user.
+fn write_file(value: &str) -> std::io::Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!("mcpmux-secret-{}", Uuid::new_v4()));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    if let Err(e) = file.write_all(value.as_bytes()) {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+/// Fill a pipe with `value` and return its read end. The write end is closed,
+/// so the reader sees EOF right after the value.
+#[cfg(unix)]
+fn write_pipe(value: &str) -> std::io::Result<std::os::fd::OwnedFd> {
+    use std::os::fd::{FromRawFd, OwnedFd};
+
+    let mut fds = [0; 2];
+    // SAFETY: pipe only writes two descriptors into `fds`.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: both descriptors were just opened and nothing else owns them.
+    let (read, write) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+    // Only the server this is delivered for may inherit the read end
+    set_cloexec(fds[0], true)?;
+    set_cloexec(fds[1], true)?;
+    std::fs::File::from(write).write_all(value.as_bytes())?;
+    Ok(read)
+}
+
+/// Set or clear `FD_CLOEXEC` on `fd`. Async-signal-safe.
+#[cfg(unix)]
+fn set_cloexec(fd: i32, enabled: bool) -> std::io::Result<()> {
+    // SAFETY: fcntl on a descriptor we own; F_GETFD/F_SETFD take no pointers.
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFD);
+        if flags < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let flags = if enabled {
+            flags | libc::FD_CLOEXEC
+        } else {
+            flags & !libc::FD_CLOEXEC
+        };
+        if libc::fcntl(fd, libc::F_SETFD, flags) < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(input_id: &str, delivery: SecretDelivery, value: &str) -> SecretBinding {
+        SecretBinding {
+            input_id: input_id.to_string(),
+            delivery,
+            source: SecretSource::Value(value.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_without_credential_store_fails() {
+        let bindings = vec![SecretBinding {
+            input_id: "API_KEY".to_string(),
+            delivery: SecretDelivery::File,
+            source: SecretSource::Credential(CredentialType::ApiKey),
+        }];
+
+        let err = fetch_secrets(&bindings, None, Uuid::new_v4(), "server")
+            .await
+            .unwrap_err();
+        assert!(err.contains("API_KEY"));
+    }
+
+    #[test]
+    fn test_secret_references_are_found_and_parsed() {
+        let env = HashMap::from([
+            (
+                "AUTH".to_string(),
+                "Bearer ${secret:github/access_token}".to_string(),
+            ),
+            ("KEY".to_string(), "${secret:openai}".to_string()),
+            (
+                "OTHER".to_string(),
+                "${input:KEY} ${secret:unterminated".to_string(),
+            ),
+        ]);
+        let references: Vec<_> = secret_references(&env).into_iter().collect();
+        assert_eq!(references, vec!["github/access_token", "openai"]);
+
+        assert_eq!(
+            parse_reference("github/access_token"),
+            Some(("github", CredentialType::AccessToken))
+        );
+        assert_eq!(
+            parse_reference("openai"),
+            Some(("openai", CredentialType::ApiKey))
+        );
+        assert_eq!(parse_reference("github/password"), None);
+        assert_eq!(parse_reference("/api_key"), None);
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_secret_reference_names_it() {
+        let env = HashMap::from([("TOKEN".to_string(), "${secret:github}".to_string())]);
+        let err = fetch_secret_references(&env, None, Uuid::new_v4())
+            .await
+            .unwrap_err();
+        assert_eq!(err.reference, "github");
+        assert!(err.to_string().contains("${secret:github}"));
+
+        let env = HashMap::from([("TOKEN".to_string(), "${secret:github/bogus}".to_string())]);
+        let err = fetch_secret_references(&env, None, Uuid::new_v4())
+            .await
+            .unwrap_err();
+        assert_eq!(err.reference, "github/bogus");
+    }
+
+    #[test]
+    fn test_secret_references_are_substituted() {
+        let mut env = HashMap::from([
+            (
+                "AUTH".to_string(),
+                "Bearer ${secret:github/access_token}".to_string(),
+            ),
+            ("PLAIN".to_string(), "value".to_string()),
+        ]);
+        apply_secret_references(
+            &[(
+                "github/access_token".to_string(),
+                Zeroizing::new("gho_1".to_string()),
+            )],
+            &mut env,
+        );
+        assert_eq!(env["AUTH"], "Bearer gho_1");
+        assert_eq!(env["PLAIN"], "value");
+    }
+
+    #[tokio::test]
+    async fn test_file_delivery_is_private_and_removed_on_drop() {
+        let bindings = vec![binding("API_KEY", SecretDelivery::File, "sk-123")];
+        let values = fetch_secrets(&bindings, None, Uuid::new_v4(), "server")
+            .await
+            .unwrap();
+        let delivered = DeliveredSecrets::deliver(&values, true).unwrap();
+
+        let mut args = vec!["--key-file".to_string(), "${input:API_KEY}".to_string()];
+        let mut env = HashMap::new();
+        delivered.apply(&mut args, &mut env);
+
+        let path = PathBuf::from(&args[1]);
+        assert_eq!(env.get("API_KEY_FILE"), Some(&args[1]));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "sk-123");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        drop(delivered);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_fd_delivery_reaches_child() {
+        let bindings = vec![binding("TOKEN", SecretDelivery::Fd, "tok-456")];
+        let values = fetch_secrets(&bindings, None, Uuid::new_v4(), "server")
+            .await
+            .unwrap();
+        let delivered = DeliveredSecrets::deliver(&values, true).unwrap();
+
+        let mut args = vec!["${input:TOKEN}".to_string()];
+        let mut env = HashMap::new();
+        delivered.apply(&mut args, &mut env);
+        assert!(args[0].starts_with("/dev/fd/"));
+
+        let mut cmd = Command::new("cat");
+        cmd.args(&args);
+        delivered.inherit(&mut cmd);
+        let output = cmd.output().await.unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "tok-456");
+    }
+
+    #[tokio::test]
+    async fn test_fd_delivery_falls_back_to_file_when_disallowed() {
+        let bindings = vec![binding("TOKEN", SecretDelivery::Fd, "tok-456")];
+        let values = fetch_secrets(&bindings, None, Uuid::new_v4(), "server")
+            .await
+            .unwrap();
+        let delivered = DeliveredSecrets::deliver(&values, false).unwrap();
+
+        let mut env = HashMap::new();
+        delivered.apply(&mut [], &mut env);
+        let path = env.get("TOKEN_FILE").unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "tok-456");
+    }
+
+    #[test]
+    fn test_debug_hides_value() {
+        let debug = format!("{:?}", binding("API_KEY", SecretDelivery::File, "sk-123"));
+        assert!(debug.contains("API_KEY"));
+        assert!(!debug.contains("sk-123"));
+    }
+}
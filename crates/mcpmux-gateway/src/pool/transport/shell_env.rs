@@ -5,17 +5,43 @@
 //! This means tools installed via Homebrew (`/opt/homebrew/bin`), nvm, Volta, fnm,
 //! or standard `/usr/local/bin` are invisible to the app.
 //!
+//! On Windows the registry PATH is inherited, but entries added in the user's
+//! PowerShell `$PROFILE` (pyenv-win, scoop shims, ...) are not.
+//!
 //! This module resolves the user's full login shell PATH by spawning their default
-//! shell with login flags and reading back `$PATH`. The result is cached for the
-//! lifetime of the process.
+//! shell with login flags (PowerShell with its profile on Windows) and reading back
+//! `$PATH`. The result is cached until [`refresh_shell_path`] is called, e.g.
+//! after the user installs a new tool.
 
 use std::ffi::OsString;
-use std::sync::OnceLock;
-#[cfg(unix)]
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+#[cfg(any(unix, windows))]
 use tracing::{debug, info, warn};
 
-/// Cached shell PATH, resolved once on first access.
-static SHELL_PATH: OnceLock<Option<OsString>> = OnceLock::new();
+/// Cached shell PATH. The outer `None` means it hasn't been resolved yet.
+static SHELL_PATH: RwLock<Option<Option<Arc<OsString>>>> = RwLock::new(None);
+
+/// Held while resolving, so concurrent first callers spawn one shell.
+static RESOLVING: Mutex<()> = Mutex::new(());
+
+/// Env var overriding [`DEFAULT_SHELL_TIMEOUT`], in milliseconds.
+pub const SHELL_TIMEOUT_ENV: &str = "MCPMUX_SHELL_TIMEOUT_MS";
+
+/// How long a shell may take to print its PATH before we kill it and fall
+/// back to the process PATH.
+///
+/// Startup files that wait for input or block on a network mount would
+/// otherwise freeze the first stdio connect forever.
+pub const DEFAULT_SHELL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Separator between PATH entries.
+#[cfg(unix)]
+const PATH_SEPARATOR: char = ':';
+#[cfg(windows)]
+const PATH_SEPARATOR: char = ';';
 
 /// Get the user's full shell PATH.
 ///
@@ -23,46 +49,221 @@ static SHELL_PATH: OnceLock<Option<OsString>> = OnceLock::new();
 /// fully-initialized `$PATH`, including entries added by `.zshrc`, `.bashrc`,
 /// `.profile`, nvm, Volta, Homebrew, etc.
 ///
-/// On Windows, this returns `None` because Windows GUI apps inherit the full
-/// system + user PATH from the registry (no shell sourcing needed).
+/// On Windows, this runs PowerShell with the user's profile loaded and reads
+/// back `$env:PATH`, picking up entries the profile adds on top of the
+/// registry PATH the app inherited.
 ///
-/// The result is cached after the first call.
-pub fn get_shell_path() -> Option<&'static OsString> {
-    SHELL_PATH
-        .get_or_init(|| {
-            #[cfg(unix)]
-            {
-                resolve_unix_shell_path()
-            }
-            #[cfg(not(unix))]
-            {
-                None
-            }
-        })
-        .as_ref()
+/// Returns `None` if the shell fails or doesn't answer within the shell
+/// timeout (see [`shell_timeout`]). The result is cached after the first call;
+/// later calls return the cached value until [`refresh_shell_path`].
+pub fn get_shell_path() -> Option<Arc<OsString>> {
+    if let Some(cached) = SHELL_PATH.read().as_ref() {
+        return cached.clone();
+    }
+
+    let _resolving = RESOLVING.lock();
+    // Another caller may have resolved it while we waited
+    if let Some(cached) = SHELL_PATH.read().as_ref() {
+        return cached.clone();
+    }
+    let resolved = resolve_shell_path().map(Arc::new);
+    *SHELL_PATH.write() = Some(resolved.clone());
+    resolved
+}
+
+/// Re-run the shell PATH resolution and replace the cached value.
+///
+/// Connects already in progress keep the PATH they started with; later ones
+/// see the new value. Returns the new PATH.
+pub fn refresh_shell_path() -> Option<Arc<OsString>> {
+    let _resolving = RESOLVING.lock();
+    let resolved = resolve_shell_path().map(Arc::new);
+    *SHELL_PATH.write() = Some(resolved.clone());
+    resolved
+}
+
+/// The cached shell PATH, for display. Doesn't spawn a shell: returns `None`
+/// if the PATH hasn't been resolved yet or resolution failed.
+pub fn current_shell_path() -> Option<Arc<OsString>> {
+    SHELL_PATH.read().clone().flatten()
+}
+
+fn resolve_shell_path() -> Option<OsString> {
+    #[cfg(unix)]
+    {
+        resolve_unix_shell_path()
+    }
+    #[cfg(windows)]
+    {
+        resolve_windows_shell_path()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        None
+    }
+}
+
+/// Time allowed for the shell to report its PATH: [`SHELL_TIMEOUT_ENV`] if set
+/// to a positive number of milliseconds, otherwise [`DEFAULT_SHELL_TIMEOUT`].
+pub fn shell_timeout() -> Duration {
+    parse_timeout(std::env::var(SHELL_TIMEOUT_ENV).ok().as_deref())
+}
+
+fn parse_timeout(value: Option<&str>) -> Duration {
+    value
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_SHELL_TIMEOUT)
+}
+
+/// Shell families that need different flags or syntax to print `$PATH`.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShellKind {
+    /// sh, bash, zsh, dash, ksh and anything unrecognised
+    Posix,
+    Fish,
+    Nushell,
+}
+
+#[cfg(unix)]
+impl ShellKind {
+    /// Detect the shell family from a shell path such as `$SHELL`.
+    fn detect(shell: &str) -> Self {
+        let name = shell.rsplit('/').next().unwrap_or(shell);
+        // Login shells are sometimes reported as `-zsh`
+        match name.trim_start_matches('-') {
+            "fish" => ShellKind::Fish,
+            "nu" | "nushell" => ShellKind::Nushell,
+            _ => ShellKind::Posix,
+        }
+    }
+
+    /// Flag sets to try, in order, each followed by [`Self::print_path_script`].
+    ///
+    /// POSIX shells try an interactive login shell first, since `-i` sources
+    /// `~/.zshrc` / `~/.bashrc` where nvm/Volta/fnm init lives. fish reads
+    /// `config.fish` for every shell and nushell its config for `-l`, so one
+    /// login shell is enough for them.
+    fn flag_sets(self) -> &'static [&'static [&'static str]] {
+        match self {
+            ShellKind::Posix => &[&["-l", "-i", "-c"], &["-l", "-c"]],
+            ShellKind::Fish | ShellKind::Nushell => &[&["-l", "-c"]],
+        }
+    }
+
+    /// Command printing PATH as a `:`-separated string.
+    ///
+    /// fish and nushell keep PATH as a list, so it's joined explicitly.
+    fn print_path_script(self) -> &'static str {
+        match self {
+            ShellKind::Posix => r#"printf "%s" "$PATH""#,
+            ShellKind::Fish => "string join : $PATH",
+            ShellKind::Nushell => r#"$env.PATH | str join ":""#,
+        }
+    }
 }
 
 /// Resolve the full PATH from the user's login shell on Unix.
 ///
 /// Strategy:
 /// 1. Read `$SHELL` to find the user's default shell (falls back to `/bin/sh`)
-/// 2. Spawn `$SHELL -l -i -c 'printf "%s" "$PATH"'` to get the fully-initialized PATH
+/// 2. Spawn it with login flags and a command printing PATH, e.g.
+///    `$SHELL -l -i -c 'printf "%s" "$PATH"'` for bash/zsh (see [`ShellKind`])
 ///    - `-l` (login): sources `/etc/profile`, `~/.zprofile` / `~/.bash_profile`
 ///    - `-i` (interactive): sources `~/.zshrc` / `~/.bashrc` (where nvm/Volta/fnm init lives)
 ///    - `printf` avoids trailing newlines that `echo` might add
-/// 3. If `-i` fails (some shells reject it in non-terminal contexts), retry with just `-l`
+/// 3. If `-i` fails (some shells reject it in non-terminal contexts), retry with just `-l`;
+///    if it timed out, don't: the login-only shell sources the same slow files
 /// 4. Merge the resolved PATH with the current process PATH to avoid losing any entries
 #[cfg(unix)]
 fn resolve_unix_shell_path() -> Option<OsString> {
     let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
-    info!("[ShellEnv] Resolving PATH from login shell: {}", shell);
+    let kind = ShellKind::detect(&shell);
+    info!(
+        "[ShellEnv] Resolving PATH from login shell: {} ({:?})",
+        shell, kind
+    );
 
-    // Try interactive login shell first (gets nvm/Volta/fnm paths from .zshrc/.bashrc)
-    let shell_path = try_resolve_path_from_shell(&shell, &["-l", "-i", "-c"]).or_else(|| {
-        debug!("[ShellEnv] Interactive shell failed, trying login-only");
-        try_resolve_path_from_shell(&shell, &["-l", "-c"])
-    });
+    let started = std::time::Instant::now();
+    let mut shell_path = None;
+    for (attempt, flags) in kind.flag_sets().iter().enumerate() {
+        if attempt > 0 {
+            if started.elapsed() >= shell_timeout() {
+                break;
+            }
+            debug!("[ShellEnv] Shell failed, retrying with flags {:?}", flags);
+        }
+        shell_path = try_resolve_path_from_shell(&shell, flags);
+        if shell_path.is_some() {
+            break;
+        }
+    }
+
+    merge_with_process_path(shell_path)
+}
+
+/// Resolve the PATH seen by the user's PowerShell profile on Windows.
+///
+/// Runs `powershell.exe` *with* its profile (so `$PROFILE` edits apply) and
+/// prints `$env:PATH` as the last line of output; anything the profile itself
+/// writes comes before it and is ignored. Output is forced to UTF-8 so
+/// non-ASCII directory names survive the console code page.
+#[cfg(windows)]
+fn resolve_windows_shell_path() -> Option<OsString> {
+    use std::os::windows::process::CommandExt;
+    use std::process::{Command, Stdio};
 
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    info!("[ShellEnv] Resolving PATH from PowerShell profile");
+
+    let mut cmd = Command::new("powershell.exe");
+    cmd.args([
+        "-NoLogo",
+        "-NonInteractive",
+        "-Command",
+        "[Console]::OutputEncoding = [Text.Encoding]::UTF8; Write-Output $env:PATH",
+    ]);
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::null()); // Suppress profile errors
+
+    let timeout = shell_timeout();
+    let shell_path = match output_with_timeout(cmd, timeout) {
+        Ok(Some(output)) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string),
+        Ok(Some(output)) => {
+            debug!("[ShellEnv] PowerShell exited with status {}", output.status);
+            None
+        }
+        Ok(None) => {
+            warn!(
+                "[ShellEnv] PowerShell did not finish within {:?}; your profile may be slow \
+                 or blocked. Set {} to allow more time",
+                timeout, SHELL_TIMEOUT_ENV
+            );
+            None
+        }
+        Err(e) => {
+            debug!("[ShellEnv] Failed to spawn PowerShell: {}", e);
+            None
+        }
+    };
+
+    merge_with_process_path(shell_path)
+}
+
+/// Merge a shell-resolved PATH with the current process PATH (to keep any
+/// paths the app already has), or `None` if the shell gave us nothing.
+#[cfg(any(unix, windows))]
+fn merge_with_process_path(shell_path: Option<String>) -> Option<OsString> {
     let shell_path = match shell_path {
         Some(p) if !p.is_empty() => p,
         _ => {
@@ -71,14 +272,13 @@ fn resolve_unix_shell_path() -> Option<OsString> {
         }
     };
 
-    // Merge: shell PATH + current process PATH (to keep any paths the app already has)
     let current_path = std::env::var("PATH").unwrap_or_default();
     let merged = merge_paths(&shell_path, &current_path);
 
     info!(
         "[ShellEnv] Resolved PATH ({} entries, shell had {} entries)",
-        merged.split(':').count(),
-        shell_path.split(':').count()
+        merged.split(PATH_SEPARATOR).count(),
+        shell_path.split(PATH_SEPARATOR).count()
     );
     debug!("[ShellEnv] PATH = {}", merged);
 
@@ -87,27 +287,29 @@ fn resolve_unix_shell_path() -> Option<OsString> {
 
 /// Try to resolve PATH by running the user's shell with the given flags.
 ///
-/// Uses `printf "%s" "$PATH"` instead of `echo $PATH` to avoid:
+/// The command run depends on the shell (see [`ShellKind::print_path_script`]).
+/// For POSIX shells it's `printf "%s" "$PATH"` instead of `echo $PATH`, to avoid:
 /// - Trailing newlines from echo
 /// - Shell-specific echo behavior differences
 #[cfg(unix)]
 fn try_resolve_path_from_shell(shell: &str, flags: &[&str]) -> Option<String> {
     use std::process::{Command, Stdio};
 
-    // Build command: $SHELL <flags> 'printf "%s" "$PATH"'
+    // Build command: $SHELL <flags> '<print PATH>'
     let mut cmd = Command::new(shell);
     for flag in flags {
         cmd.arg(flag);
     }
-    cmd.arg(r#"printf "%s" "$PATH""#);
+    cmd.arg(ShellKind::detect(shell).print_path_script());
 
     // Prevent the child from inheriting stdin (avoids tty issues)
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::null()); // Suppress shell startup warnings
 
-    match cmd.output() {
-        Ok(output) if output.status.success() => {
+    let timeout = shell_timeout();
+    match output_with_timeout(cmd, timeout) {
+        Ok(Some(output)) if output.status.success() => {
             let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
             if path.is_empty() {
                 debug!("[ShellEnv] Shell returned empty PATH");
@@ -116,13 +318,21 @@ fn try_resolve_path_from_shell(shell: &str, flags: &[&str]) -> Option<String> {
                 Some(path)
             }
         }
-        Ok(output) => {
+        Ok(Some(output)) => {
             debug!(
                 "[ShellEnv] Shell exited with status {} (flags: {:?})",
                 output.status, flags
             );
             None
         }
+        Ok(None) => {
+            warn!(
+                "[ShellEnv] Shell '{}' did not finish within {:?} (flags: {:?}); your shell \
+                 startup files may be slow or blocked. Set {} to allow more time",
+                shell, timeout, flags, SHELL_TIMEOUT_ENV
+            );
+            None
+        }
         Err(e) => {
             debug!("[ShellEnv] Failed to spawn shell '{}': {}", shell, e);
             None
@@ -130,24 +340,84 @@ fn try_resolve_path_from_shell(shell: &str, flags: &[&str]) -> Option<String> {
     }
 }
 
+/// Run `cmd` and collect its stdout, killing it if it runs past `timeout`.
+///
+/// Returns `Ok(None)` on timeout. stdout is read on a separate thread so a
+/// chatty child can't block on a full pipe while we wait; if the shell exits
+/// but a background process it started keeps the pipe open, that counts as
+/// a timeout too.
+#[cfg(any(unix, windows))]
+fn output_with_timeout(
+    mut cmd: std::process::Command,
+    timeout: Duration,
+) -> std::io::Result<Option<std::process::Output>> {
+    use std::io::Read;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    let deadline = Instant::now() + timeout;
+    let mut child = cmd.spawn()?;
+
+    let (tx, rx) = mpsc::channel();
+    let stdout = child.stdout.take();
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut stdout) = stdout {
+            let _ = stdout.read_to_end(&mut buf);
+        }
+        let _ = tx.send(buf);
+    });
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    Ok(rx
+        .recv_timeout(remaining)
+        .ok()
+        .map(|stdout| std::process::Output {
+            status,
+            stdout,
+            stderr: Vec::new(),
+        }))
+}
+
 /// Merge two PATH strings, preserving order and deduplicating.
 ///
 /// The `primary` PATH takes precedence (its entries appear first).
 /// Entries from `secondary` are appended only if not already present.
-#[cfg(unix)]
+/// On Windows entries are compared case-insensitively.
+#[cfg(any(unix, windows))]
 fn merge_paths(primary: &str, secondary: &str) -> String {
     use std::collections::HashSet;
 
     let mut seen = HashSet::new();
     let mut merged = Vec::new();
 
-    for entry in primary.split(':').chain(secondary.split(':')) {
-        if !entry.is_empty() && seen.insert(entry.to_string()) {
+    for entry in primary
+        .split(PATH_SEPARATOR)
+        .chain(secondary.split(PATH_SEPARATOR))
+    {
+        let key = if cfg!(windows) {
+            entry.to_lowercase()
+        } else {
+            entry.to_string()
+        };
+        if !entry.is_empty() && seen.insert(key) {
             merged.push(entry.to_string());
         }
     }
 
-    merged.join(":")
+    merged.join(&PATH_SEPARATOR.to_string())
 }
 
 #[cfg(test)]
@@ -214,13 +484,16 @@ mod tests {
 
     // ── get_shell_path tests ───────────────────────────────────────
 
+    /// Serializes tests that compare cached values against refreshes.
+    static CACHE_TEST_LOCK: Mutex<()> = Mutex::new(());
+
     #[cfg(unix)]
     #[test]
     fn test_get_shell_path_returns_something() {
         // On any Unix system with a shell, this should succeed
         let path = get_shell_path();
         assert!(path.is_some(), "Should resolve shell PATH on Unix");
-        let path_str = path.unwrap().to_string_lossy();
+        let path_str = path.as_deref().unwrap().to_string_lossy();
         assert!(
             path_str.contains("/usr/bin") || path_str.contains("/bin"),
             "PATH should contain standard directories: {}",
@@ -231,13 +504,28 @@ mod tests {
     #[cfg(unix)]
     #[test]
     fn test_get_shell_path_is_cached() {
-        // Calling twice should return the exact same reference (OnceLock)
+        let _guard = CACHE_TEST_LOCK.lock();
+        // Calling twice should return the exact same value
         let first = get_shell_path();
         let second = get_shell_path();
         assert!(first.is_some());
         assert!(second.is_some());
-        // Same pointer — verifies caching via OnceLock
-        assert!(std::ptr::eq(first.unwrap(), second.unwrap()));
+        // Same pointer — verifies caching
+        assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refresh_shell_path_replaces_cache() {
+        let _guard = CACHE_TEST_LOCK.lock();
+        let before = get_shell_path().unwrap();
+        assert!(Arc::ptr_eq(&current_shell_path().unwrap(), &before));
+
+        let refreshed = refresh_shell_path().unwrap();
+        assert!(!Arc::ptr_eq(&refreshed, &before));
+        assert_eq!(refreshed, before);
+        assert!(Arc::ptr_eq(&get_shell_path().unwrap(), &refreshed));
+        assert!(Arc::ptr_eq(&current_shell_path().unwrap(), &refreshed));
     }
 
     #[cfg(unix)]
@@ -279,6 +567,48 @@ mod tests {
         }
     }
 
+    // ── ShellKind tests ────────────────────────────────────────────
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_kind_detect() {
+        assert_eq!(ShellKind::detect("/bin/bash"), ShellKind::Posix);
+        assert_eq!(ShellKind::detect("/bin/zsh"), ShellKind::Posix);
+        assert_eq!(ShellKind::detect("-zsh"), ShellKind::Posix);
+        assert_eq!(ShellKind::detect("/opt/homebrew/bin/fish"), ShellKind::Fish);
+        assert_eq!(ShellKind::detect("fish"), ShellKind::Fish);
+        assert_eq!(ShellKind::detect("/usr/local/bin/nu"), ShellKind::Nushell);
+        assert_eq!(ShellKind::detect("/usr/bin/fishy"), ShellKind::Posix);
+        assert_eq!(ShellKind::detect(""), ShellKind::Posix);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_posix_invocation() {
+        let kind = ShellKind::detect("/bin/zsh");
+        assert_eq!(
+            kind.flag_sets(),
+            &[&["-l", "-i", "-c"][..], &["-l", "-c"][..]]
+        );
+        assert_eq!(kind.print_path_script(), r#"printf "%s" "$PATH""#);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fish_invocation() {
+        let kind = ShellKind::detect("/usr/bin/fish");
+        assert_eq!(kind.flag_sets(), &[&["-l", "-c"][..]]);
+        assert_eq!(kind.print_path_script(), "string join : $PATH");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nushell_invocation() {
+        let kind = ShellKind::detect("/usr/bin/nu");
+        assert_eq!(kind.flag_sets(), &[&["-l", "-c"][..]]);
+        assert_eq!(kind.print_path_script(), r#"$env.PATH | str join ":""#);
+    }
+
     // ── try_resolve_path_from_shell tests ──────────────────────────
 
     #[cfg(unix)]
@@ -303,6 +633,53 @@ mod tests {
         assert!(result.is_none(), "Should fail for nonexistent shell");
     }
 
+    // ── shell_timeout tests ────────────────────────────────────────
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout(None), DEFAULT_SHELL_TIMEOUT);
+        assert_eq!(parse_timeout(Some("500")), Duration::from_millis(500));
+        assert_eq!(parse_timeout(Some(" 8000 ")), Duration::from_secs(8));
+        assert_eq!(parse_timeout(Some("0")), DEFAULT_SHELL_TIMEOUT);
+        assert_eq!(parse_timeout(Some("3s")), DEFAULT_SHELL_TIMEOUT);
+    }
+
+    // ── output_with_timeout tests ──────────────────────────────────
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_with_timeout_kills_hung_shell() {
+        let mut cmd = std::process::Command::new("/bin/sh");
+        cmd.args(["-c", "sleep 30"]);
+        cmd.stdout(std::process::Stdio::piped());
+
+        let start = std::time::Instant::now();
+        let output = output_with_timeout(cmd, Duration::from_millis(200)).unwrap();
+        assert!(output.is_none(), "Hung shell should time out");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_with_timeout_collects_stdout() {
+        let mut cmd = std::process::Command::new("/bin/sh");
+        cmd.args(["-c", "printf hello"]);
+        cmd.stdout(std::process::Stdio::piped());
+
+        let output = output_with_timeout(cmd, DEFAULT_SHELL_TIMEOUT)
+            .unwrap()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hello");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_merge_paths_windows_case_insensitive() {
+        let result = merge_paths(r"C:\Tools;C:\Windows", r"c:\windows;D:\bin");
+        assert_eq!(result, r"C:\Tools;C:\Windows;D:\bin");
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_try_resolve_shell_invalid_flags() {
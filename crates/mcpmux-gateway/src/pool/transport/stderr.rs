@@ -0,0 +1,479 @@
+//! Log level classification for STDIO server output
+//!
+//! Child process stderr (and non-protocol stdout) arrives as plain text lines.
+//! A [`StderrClassifier`] assigns each line a [`LogLevel`] from an ordered list
+//! of regex rules so the desktop log viewer can filter and colour it.
+//!
+//! Lines that are JSON objects (newline-delimited structured logs, as emitted
+//! by pino, winston, structlog and friends) are unpacked instead: `level` /
+//! `severity`, `msg` / `message` and `timestamp` map onto the [`ServerLog`]
+//! fields, and any other keys are kept as metadata.
+//!
+//! [`StderrLimits`] bound how much a single server can push into the log:
+//! overlong lines are truncated while being read, and lines beyond a per-interval
+//! budget are dropped and reported in one summary entry.
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use mcpmux_core::{LogLevel, LogSource, ServerLog};
+use regex::Regex;
+use serde_json::{Map, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// Appended to lines cut off at [`StderrLimits::max_line_bytes`].
+pub(super) const TRUNCATED_MARKER: &str = "…[truncated]";
+
+lazy_static! {
+    /// Shared default classifier, avoiding a regex compile per transport.
+    static ref DEFAULT_RULES: Vec<(Regex, LogLevel)> = vec![
+        (Regex::new(r"(?i)error|panic|fatal").unwrap(), LogLevel::Error),
+        (Regex::new(r"(?i)warn").unwrap(), LogLevel::Warn),
+        (Regex::new(r"(?i)debug|trace").unwrap(), LogLevel::Debug),
+    ];
+}
+
+/// Ordered regex rules mapping output lines to log levels.
+///
+/// Rules are tried in order and the first match wins; lines matching no rule
+/// get the fallback level.
+#[derive(Debug, Clone)]
+pub struct StderrClassifier {
+    rules: Vec<(Regex, LogLevel)>,
+    fallback: LogLevel,
+}
+
+impl StderrClassifier {
+    /// Create a classifier from ordered rules and a fallback level.
+    pub fn new(rules: Vec<(Regex, LogLevel)>, fallback: LogLevel) -> Self {
+        Self { rules, fallback }
+    }
+
+    /// Classify a single line.
+    pub fn classify(&self, line: &str) -> LogLevel {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(line))
+            .map(|(_, level)| *level)
+            .unwrap_or(self.fallback)
+    }
+
+    /// Build the log entry for one line of server output.
+    ///
+    /// JSON object lines have their known fields mapped; a recognised level
+    /// field takes precedence over the classifier rules. Anything else is
+    /// logged verbatim with a classified level.
+    pub(super) fn log_entry(&self, line: &str, source: LogSource) -> ServerLog {
+        match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(fields)) => self.structured_entry(line, fields, source),
+            _ => ServerLog::new(self.classify(line), source, line),
+        }
+    }
+
+    fn structured_entry(
+        &self,
+        line: &str,
+        mut fields: Map<String, Value>,
+        source: LogSource,
+    ) -> ServerLog {
+        let level = take_field(&mut fields, &["level", "severity"]).and_then(|v| json_level(&v));
+        let message = match take_field(&mut fields, &["msg", "message"]) {
+            Some(Value::String(message)) => message,
+            Some(other) => other.to_string(),
+            None => line.to_string(),
+        };
+        let timestamp = take_field(&mut fields, &["timestamp"]).and_then(|v| json_timestamp(&v));
+
+        let mut log = ServerLog::new(
+            level.unwrap_or_else(|| self.classify(&message)),
+            source,
+            message,
+        );
+        if let Some(timestamp) = timestamp {
+            log.timestamp = timestamp;
+        }
+        if !fields.is_empty() {
+            log = log.with_metadata(Value::Object(fields));
+        }
+        log
+    }
+}
+
+/// Remove and return the first of `keys` present in `fields`.
+fn take_field(fields: &mut Map<String, Value>, keys: &[&str]) -> Option<Value> {
+    keys.iter().find_map(|key| fields.remove(*key))
+}
+
+/// Map a structured log level onto [`LogLevel`].
+///
+/// Accepts the usual level names (case-insensitive, including aliases such as
+/// `warning` and `critical`) and pino/bunyan numeric levels.
+fn json_level(value: &Value) -> Option<LogLevel> {
+    match value {
+        Value::String(name) => match name.to_lowercase().as_str() {
+            "trace" => Some(LogLevel::Trace),
+            "debug" => Some(LogLevel::Debug),
+            "info" | "information" | "notice" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" | "err" | "fatal" | "critical" | "crit" | "panic" | "alert" | "emergency" => {
+                Some(LogLevel::Error)
+            }
+            _ => None,
+        },
+        Value::Number(n) => match n.as_u64()? {
+            0..=10 => Some(LogLevel::Trace),
+            11..=20 => Some(LogLevel::Debug),
+            21..=30 => Some(LogLevel::Info),
+            31..=40 => Some(LogLevel::Warn),
+            _ => Some(LogLevel::Error),
+        },
+        _ => None,
+    }
+}
+
+/// Parse an RFC 3339 string or a Unix epoch in milliseconds.
+fn json_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|ts| ts.with_timezone(&Utc)),
+        Value::Number(n) => DateTime::from_timestamp_millis(n.as_i64()?),
+        _ => None,
+    }
+}
+
+impl Default for StderrClassifier {
+    /// Case-insensitive English keywords: error/panic/fatal, warn, debug/trace,
+    /// falling back to Info.
+    fn default() -> Self {
+        Self::new(DEFAULT_RULES.clone(), LogLevel::Info)
+    }
+}
+
+/// Bounds on captured stderr output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StderrLimits {
+    /// Longest line kept; the rest of the line is discarded.
+    pub max_line_bytes: usize,
+    /// Lines logged per interval before further lines are dropped.
+    pub max_lines_per_interval: u32,
+    /// Length of the rate-limiting window.
+    pub interval: Duration,
+}
+
+impl Default for StderrLimits {
+    /// 16 KiB lines, 500 lines per second.
+    fn default() -> Self {
+        Self {
+            max_line_bytes: 16 * 1024,
+            max_lines_per_interval: 500,
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Read one line into `buf` (without the newline), keeping at most `max_bytes`.
+///
+/// Unlike `read_line`, the remainder of an overlong line is consumed and
+/// discarded rather than buffered. Returns `None` at EOF, otherwise whether the
+/// line was truncated.
+pub(super) async fn read_capped_line<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max_bytes: usize,
+) -> std::io::Result<Option<bool>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    buf.clear();
+    let mut truncated = false;
+    let mut read_any = false;
+
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(read_any.then_some(truncated));
+        }
+        read_any = true;
+
+        let newline = available.iter().position(|&b| b == b'\n');
+        let chunk = &available[..newline.unwrap_or(available.len())];
+        let room = max_bytes.saturating_sub(buf.len());
+        if chunk.len() > room {
+            truncated = true;
+        }
+        buf.extend_from_slice(&chunk[..chunk.len().min(room)]);
+
+        let used = newline.map_or(available.len(), |i| i + 1);
+        reader.consume(used);
+        if newline.is_some() {
+            return Ok(Some(truncated));
+        }
+    }
+}
+
+/// Fixed-window line budget.
+pub(super) struct RateGuard {
+    limits: StderrLimits,
+    window_start: Instant,
+    admitted: u32,
+    dropped: u64,
+}
+
+impl RateGuard {
+    pub(super) fn new(limits: StderrLimits, now: Instant) -> Self {
+        Self {
+            limits,
+            window_start: now,
+            admitted: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Decide whether a line arriving at `now` may be logged.
+    ///
+    /// Returns the admission decision and, when a window with dropped lines
+    /// has just closed, the entry summarising them.
+    pub(super) fn admit(&mut self, now: Instant) -> (bool, Option<ServerLog>) {
+        let mut summary = None;
+        if now.duration_since(self.window_start) >= self.limits.interval {
+            summary = self.flush();
+            self.window_start = now;
+            self.admitted = 0;
+        }
+
+        if self.admitted < self.limits.max_lines_per_interval {
+            self.admitted += 1;
+            (true, summary)
+        } else {
+            self.dropped += 1;
+            (false, summary)
+        }
+    }
+
+    /// Summary entry for lines dropped so far, resetting the count.
+    pub(super) fn flush(&mut self) -> Option<ServerLog> {
+        if self.dropped == 0 {
+            return None;
+        }
+        let dropped = std::mem::take(&mut self.dropped);
+        Some(ServerLog::new(
+            LogLevel::Warn,
+            LogSource::Stderr,
+            format!(
+                "Dropped {} stderr lines (limit is {} per {:?})",
+                dropped, self.limits.max_lines_per_interval, self.limits.interval
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_stderr_line(line: &str) -> LogLevel {
+        StderrClassifier::default().classify(line)
+    }
+
+    #[test]
+    fn test_classify_stderr_error() {
+        assert_eq!(
+            classify_stderr_line("ERROR: something failed"),
+            LogLevel::Error
+        );
+        assert_eq!(
+            classify_stderr_line("fatal: not a git repository"),
+            LogLevel::Error
+        );
+        assert_eq!(
+            classify_stderr_line("thread 'main' panicked"),
+            LogLevel::Error
+        );
+    }
+
+    #[test]
+    fn test_classify_stderr_warn() {
+        assert_eq!(
+            classify_stderr_line("WARN: deprecated feature"),
+            LogLevel::Warn
+        );
+        assert_eq!(
+            classify_stderr_line("Warning: something is off"),
+            LogLevel::Warn
+        );
+    }
+
+    #[test]
+    fn test_classify_stderr_debug() {
+        assert_eq!(
+            classify_stderr_line("DEBUG: internal state"),
+            LogLevel::Debug
+        );
+        assert_eq!(
+            classify_stderr_line("trace: verbose output"),
+            LogLevel::Debug
+        );
+    }
+
+    #[test]
+    fn test_classify_stderr_info_default() {
+        assert_eq!(
+            classify_stderr_line("Server listening on port 3000"),
+            LogLevel::Info
+        );
+    }
+
+    #[test]
+    fn test_custom_rules_first_match_wins() {
+        let classifier = StderrClassifier::new(
+            vec![
+                (Regex::new(r"level=(error|fatal)").unwrap(), LogLevel::Error),
+                (Regex::new(r"level=warn").unwrap(), LogLevel::Warn),
+                (Regex::new(r"^\[avertissement\]").unwrap(), LogLevel::Warn),
+                (Regex::new(r"level=").unwrap(), LogLevel::Debug),
+            ],
+            LogLevel::Info,
+        );
+
+        assert_eq!(
+            classifier.classify("ts=1 level=error msg=boom"),
+            LogLevel::Error
+        );
+        assert_eq!(classifier.classify("level=warn msg=slow"), LogLevel::Warn);
+        assert_eq!(
+            classifier.classify("[avertissement] disque plein"),
+            LogLevel::Warn
+        );
+        // Earlier rules take precedence over the catch-all `level=`
+        assert_eq!(classifier.classify("level=info msg=ok"), LogLevel::Debug);
+        // Keywords from the default ruleset no longer apply
+        assert_eq!(classifier.classify("an error occurred"), LogLevel::Info);
+    }
+
+    #[test]
+    fn test_log_entry_plain_line() {
+        let log = StderrClassifier::default().log_entry("WARN: low disk", LogSource::Stderr);
+        assert_eq!(log.level, LogLevel::Warn);
+        assert_eq!(log.source, LogSource::Stderr);
+        assert_eq!(log.message, "WARN: low disk");
+        assert!(log.metadata.is_none());
+    }
+
+    #[test]
+    fn test_log_entry_json_fields() {
+        let line =
+            r#"{"level":"warn","msg":"cache miss","timestamp":"2025-01-02T03:04:05Z","key":"a"}"#;
+        let log = StderrClassifier::default().log_entry(line, LogSource::Stderr);
+
+        assert_eq!(log.level, LogLevel::Warn);
+        assert_eq!(log.message, "cache miss");
+        assert_eq!(log.timestamp.to_rfc3339(), "2025-01-02T03:04:05+00:00");
+        assert_eq!(log.metadata, Some(serde_json::json!({"key": "a"})));
+    }
+
+    #[test]
+    fn test_log_entry_json_level_beats_classifier() {
+        // The message mentions "error" but the structured level says info
+        let line = r#"{"severity":"INFO","message":"recovered from error"}"#;
+        let log = StderrClassifier::default().log_entry(line, LogSource::Stderr);
+        assert_eq!(log.level, LogLevel::Info);
+        assert_eq!(log.message, "recovered from error");
+        assert!(log.metadata.is_none());
+    }
+
+    #[test]
+    fn test_log_entry_json_pino_levels() {
+        let line = r#"{"level":50,"time":1700000000000,"msg":"boom"}"#;
+        let log = StderrClassifier::default().log_entry(line, LogSource::Stderr);
+        assert_eq!(log.level, LogLevel::Error);
+        assert_eq!(log.message, "boom");
+        assert_eq!(
+            log.metadata,
+            Some(serde_json::json!({"time": 1700000000000u64}))
+        );
+
+        let line = r#"{"level":30,"timestamp":1700000000000,"msg":"ok"}"#;
+        let log = StderrClassifier::default().log_entry(line, LogSource::Stderr);
+        assert_eq!(log.level, LogLevel::Info);
+        assert_eq!(log.timestamp.timestamp_millis(), 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_log_entry_json_without_known_fields() {
+        let line = r#"{"event":"fatal shutdown"}"#;
+        let log = StderrClassifier::default().log_entry(line, LogSource::Stderr);
+        // Falls back to the raw line and classifier rules
+        assert_eq!(log.level, LogLevel::Error);
+        assert_eq!(log.message, line);
+    }
+
+    #[test]
+    fn test_log_entry_non_object_json() {
+        let log = StderrClassifier::default().log_entry("[1, 2, 3]", LogSource::Stderr);
+        assert_eq!(log.message, "[1, 2, 3]");
+        assert_eq!(log.level, LogLevel::Info);
+        assert!(log.metadata.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_capped_line_truncates_and_resyncs() {
+        let mut input = vec![b'x'; 100];
+        input.extend_from_slice(b"\nshort\r\nlast");
+        // Tiny buffer so the long line spans several fill_buf calls
+        let mut reader = tokio::io::BufReader::with_capacity(7, input.as_slice());
+        let mut buf = Vec::new();
+
+        assert_eq!(
+            read_capped_line(&mut reader, &mut buf, 10).await.unwrap(),
+            Some(true)
+        );
+        assert_eq!(buf, vec![b'x'; 10]);
+
+        assert_eq!(
+            read_capped_line(&mut reader, &mut buf, 10).await.unwrap(),
+            Some(false)
+        );
+        assert_eq!(buf, b"short\r");
+
+        assert_eq!(
+            read_capped_line(&mut reader, &mut buf, 10).await.unwrap(),
+            Some(false)
+        );
+        assert_eq!(buf, b"last");
+
+        assert_eq!(
+            read_capped_line(&mut reader, &mut buf, 10).await.unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rate_guard_drops_and_summarises() {
+        let limits = StderrLimits {
+            max_line_bytes: 1024,
+            max_lines_per_interval: 2,
+            interval: Duration::from_secs(1),
+        };
+        let start = Instant::now();
+        let mut guard = RateGuard::new(limits, start);
+
+        assert!(matches!(guard.admit(start), (true, None)));
+        assert!(matches!(guard.admit(start), (true, None)));
+        assert!(matches!(guard.admit(start), (false, None)));
+        assert!(matches!(
+            guard.admit(start + Duration::from_millis(500)),
+            (false, None)
+        ));
+
+        // Next window admits again and reports what was dropped
+        let (admitted, summary) = guard.admit(start + Duration::from_secs(1));
+        assert!(admitted);
+        let summary = summary.unwrap();
+        assert_eq!(summary.level, LogLevel::Warn);
+        assert!(summary.message.starts_with("Dropped 2 stderr lines"));
+
+        assert!(guard.flush().is_none());
+    }
+}
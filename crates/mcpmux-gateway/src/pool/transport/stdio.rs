@@ -7,23 +7,69 @@
 //! server log manager, making terminal output visible in the desktop log
 //! viewer. This works generically for any runtime (npx, node, docker, python,
 //! etc.). These logs are internal to the desktop app and are never exposed
-//! externally via the HTTP gateway.
+//! externally via the HTTP gateway. Each line's level comes from a
+//! [`StderrClassifier`], configurable via
+//! [`StdioTransport::with_stderr_classifier`].
+//!
+//! Stdout carries the MCP protocol, so it is left alone by default. With
+//! [`StdioTransport::with_stdout_capture`] enabled, stdout is routed through a
+//! line filter instead: JSON-RPC frames are forwarded to the MCP client
+//! unchanged, and anything else (startup banners, stray `print` calls) is
+//! logged with [`LogSource::Stdout`].
+//!
+//! With a [`RestartPolicy`] set, the child is run under a supervisor that
+//! re-spawns it after unexpected exits (see the `supervisor` module).
+//!
+//! [`ResourceLimits`] cap the child's memory, file descriptors and CPU time on
+//! Unix (see the `limits` module).
+//!
+//! Sensitive inputs never appear in the child's args or env: they are handed
+//! over in a private file or an inherited pipe (see the `secrets` module).
+//! `${secret:...}` references in env values are decrypted from the credential
+//! store only when spawning.
+//!
+//! Each successful connect reports how long the server took to start, split
+//! into command resolution, spawn and handshake, as
+//! [`DomainEvent::ServerConnected`] and in the connection log.
+//!
+//! [`StdioTransport::resolved_env`] shows the environment the child gets,
+//! shell PATH included and secrets masked, and it is logged at debug level on
+//! connect, for "works in my terminal but not in the app" reports.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use mcpmux_core::{LogLevel, LogSource, ServerLog, ServerLogManager};
-use rmcp::transport::{ConfigureCommandExt, TokioChildProcess};
+use futures::FutureExt;
+use mcpmux_core::{
+    known_secret_values, CredentialRepository, DomainEvent, LogLevel, LogRedactor, LogSource,
+    ServerLog, ServerLogManager, REDACTED,
+};
+use parking_lot::{Mutex, RwLock};
 use rmcp::ServiceExt;
-use tokio::io::AsyncBufReadExt;
-use tokio::process::{ChildStderr, Command};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader, DuplexStream};
+use tokio::process::{ChildStderr, ChildStdin, ChildStdout, Command};
+use tokio::task::AbortHandle;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use super::limits::ResourceLimits;
+use super::liveness::{LastPeer, PING_TIMEOUT};
+use super::process::{ExitInfo, ProcessHandle, ProcessReader};
+use super::resolver::{CommandResolver, ResolveError, WhichResolver};
+use super::secrets::{
+    apply_secret_references, fetch_secret_references, fetch_secrets, DeliveredSecrets,
+    SecretBinding,
+};
 use super::shell_env;
+use super::stderr::{
+    read_capped_line, RateGuard, StderrClassifier, StderrLimits, TRUNCATED_MARKER,
+};
+use super::supervisor::{RestartPolicy, Supervisor};
+use super::validation::{self, ValidationCheck, ValidationResult};
 use super::TransportType;
 use super::{create_client_handler, Transport, TransportConnectResult};
 
@@ -49,43 +95,154 @@ pub fn configure_child_process_platform(cmd: &mut Command) {
     }
 }
 
-/// Returns a helpful hint for common runtime-dependent commands when they fail.
-fn command_hint(command: &str) -> &'static str {
-    let cmd = command.rsplit(['/', '\\']).next().unwrap_or(command);
-    if cmd == "docker" || cmd == "docker.exe" || cmd.starts_with("docker-") {
-        " Ensure Docker Desktop is installed and running."
+/// Built-in hints for runtime-dependent commands, keyed by basename without `.exe`.
+const COMMAND_HINTS: &[(&[&str], &str)] = &[
+    (
+        &["docker"],
+        "Ensure Docker Desktop is installed and running.",
+    ),
+    (
+        &["uv", "uvx"],
+        "Install uv: https://docs.astral.sh/uv/getting-started/installation/",
+    ),
+    (
+        &["bun", "bunx"],
+        "Install Bun: https://bun.sh/docs/installation",
+    ),
+    (
+        &["deno"],
+        "Install Deno: https://docs.deno.com/runtime/getting_started/installation/",
+    ),
+    (
+        &["podman"],
+        "Ensure the Podman machine is running (`podman machine start`).",
+    ),
+];
+
+/// Hints added with [`register_command_hint`], checked before [`COMMAND_HINTS`].
+static EXTRA_COMMAND_HINTS: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+
+/// Strip directories and a `.exe` suffix: `C:\bin\uvx.exe` -> `uvx`.
+fn command_basename(command: &str) -> &str {
+    let name = command.rsplit(['/', '\\']).next().unwrap_or(command);
+    name.strip_suffix(".exe").unwrap_or(name)
+}
+
+/// Register a hint shown when `command` (a basename such as `"pnpm"`) fails to
+/// start or connect. Replaces any hint previously registered or built in for it.
+///
+/// Meant to be called at startup by apps that know about extra runtimes.
+pub fn register_command_hint(command: &str, hint: impl Into<String>) {
+    let command = command_basename(command).to_string();
+    let hint = hint.into();
+    let mut hints = EXTRA_COMMAND_HINTS.write();
+    match hints.iter_mut().find(|(name, _)| *name == command) {
+        Some(entry) => entry.1 = hint,
+        None => hints.push((command, hint)),
+    }
+}
+
+/// Hint for a docker server that failed while the daemon isn't answering.
+const DOCKER_DAEMON_UNAVAILABLE: &str = "Docker daemon unavailable: Docker is installed but not \
+     running. Start Docker Desktop (or the Docker service) and try again.";
+
+/// How long `docker info` may take before the daemon is considered down.
+const DOCKER_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Returns a helpful hint for common runtime-dependent commands when they fail,
+/// with a leading space, or an empty string if there is none.
+fn command_hint(command: &str) -> String {
+    let mut name = command_basename(command);
+    // docker-compose, docker-credential-*, ...
+    if name.starts_with("docker-") {
+        name = "docker";
+    }
+
+    if let Some((_, hint)) = EXTRA_COMMAND_HINTS.read().iter().find(|(n, _)| n == name) {
+        return format!(" {}", hint);
+    }
+    COMMAND_HINTS
+        .iter()
+        .find(|(names, _)| names.contains(&name))
+        .map(|(_, hint)| format!(" {}", hint))
+        .unwrap_or_default()
+}
+
+/// Start-up timeout for container runtimes, which may pull an image first.
+const CONTAINER_CONNECT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Start-up timeout for local interpreters running a script that's already on disk.
+const INTERPRETER_CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Start-up timeout for everything else, including package runners like
+/// `npx` and `uvx` that may download the server before starting it.
+const STDIO_CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Interpreters that start a local script without fetching anything.
+const INTERPRETERS: &[&str] = &["node", "deno", "bun", "python", "python3", "ruby", "php"];
+
+/// Connect timeout for a stdio server that doesn't set its own, based on `command`.
+pub fn default_stdio_connect_timeout(command: &str) -> Duration {
+    let name = command_basename(command);
+    let base = name.split('-').next().unwrap_or(name);
+    if base == "docker" || base == "podman" {
+        CONTAINER_CONNECT_TIMEOUT
+    } else if INTERPRETERS.contains(&name) {
+        INTERPRETER_CONNECT_TIMEOUT
     } else {
-        ""
+        STDIO_CONNECT_TIMEOUT
     }
 }
 
 /// Spawn an async task that reads lines from the child process stderr
 /// and logs them to the server log manager.
 ///
-/// The task runs until the stderr stream is closed (child process exits)
-/// or an I/O error occurs.
+/// Lines longer than `limits.max_line_bytes` are truncated, and lines over the
+/// per-interval budget are dropped with a summary entry.
+///
+/// The task runs until the stderr stream is closed (child process exits),
+/// an I/O error occurs, or it is aborted through the returned handle. No task
+/// is started without a log manager.
 fn spawn_stderr_reader(
     stderr: ChildStderr,
     log_manager: Option<Arc<ServerLogManager>>,
+    classifier: Arc<StderrClassifier>,
+    limits: StderrLimits,
     space_id: Uuid,
     server_id: String,
-) {
-    let Some(log_manager) = log_manager else {
-        return;
-    };
+) -> Option<AbortHandle> {
+    let log_manager = log_manager?;
 
     let space_id_str = space_id.to_string();
 
-    tokio::spawn(async move {
-        let reader = tokio::io::BufReader::new(stderr);
-        let mut lines = reader.lines();
+    let task = tokio::spawn(async move {
+        let mut reader = tokio::io::BufReader::new(stderr);
+        let mut buf = Vec::new();
+        let mut guard = RateGuard::new(limits, std::time::Instant::now());
 
         loop {
-            match lines.next_line().await {
-                Ok(Some(line)) if line.is_empty() => continue,
-                Ok(Some(line)) => {
-                    let level = classify_stderr_line(&line);
-                    let log = ServerLog::new(level, LogSource::Stderr, &line);
+            match read_capped_line(&mut reader, &mut buf, limits.max_line_bytes).await {
+                Ok(Some(truncated)) => {
+                    let line = String::from_utf8_lossy(&buf);
+                    let line = line.trim_end_matches('\r');
+                    if line.is_empty() {
+                        continue;
+                    }
+
+                    let (admitted, summary) = guard.admit(std::time::Instant::now());
+                    if let Some(summary) = summary {
+                        let _ = log_manager.append(&space_id_str, &server_id, summary).await;
+                    }
+                    if !admitted {
+                        continue;
+                    }
+
+                    let log = if truncated {
+                        let line = format!("{line}{TRUNCATED_MARKER}");
+                        classifier.log_entry(&line, LogSource::Stderr)
+                    } else {
+                        classifier.log_entry(line, LogSource::Stderr)
+                    };
                     let _ = log_manager.append(&space_id_str, &server_id, log).await;
                 }
                 Ok(None) => {
@@ -103,23 +260,138 @@ fn spawn_stderr_reader(
                 }
             }
         }
+
+        if let Some(summary) = guard.flush() {
+            let _ = log_manager.append(&space_id_str, &server_id, summary).await;
+        }
     });
+    Some(task.abort_handle())
 }
 
-/// Classify a stderr line into a log level based on content heuristics.
-fn classify_stderr_line(line: &str) -> LogLevel {
-    let lower = line.to_lowercase();
-    if lower.contains("error") || lower.contains("panic") || lower.contains("fatal") {
-        LogLevel::Error
-    } else if lower.contains("warn") {
-        LogLevel::Warn
-    } else if lower.contains("debug") || lower.contains("trace") {
-        LogLevel::Debug
-    } else {
-        LogLevel::Info
+/// The stderr reader of the current child, kept so it ends with the
+/// connection even if the process lingers.
+#[derive(Default)]
+struct StderrTask(Mutex<Option<AbortHandle>>);
+
+impl StderrTask {
+    /// Track the reader of a newly spawned child. A previous child's reader is
+    /// left to drain what that child wrote before it exited.
+    fn set(&self, handle: Option<AbortHandle>) {
+        *self.0.lock() = handle;
+    }
+
+    /// Stop the current reader, if any.
+    fn abort(&self) {
+        if let Some(handle) = self.0.lock().as_ref() {
+            handle.abort();
+        }
+    }
+
+    /// Whether the tracked reader has finished; `None` if there is none.
+    #[cfg(test)]
+    fn is_finished(&self) -> Option<bool> {
+        self.0.lock().as_ref().map(AbortHandle::is_finished)
+    }
+}
+
+/// How often a server whose stdout closed is checked for having exited.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Buffer size of the in-memory pipe between the stdout filter and the MCP client.
+const STDOUT_FILTER_BUFFER: usize = 64 * 1024;
+
+/// Spawn an async task that splits child process stdout into JSON-RPC frames
+/// and log output.
+///
+/// Lines that parse as JSON-RPC are written to `frames` byte-for-byte; other
+/// non-blank lines are logged with [`LogSource::Stdout`]. The task ends when
+/// stdout closes or the MCP client drops its end of `frames`.
+fn spawn_stdout_filter<R>(
+    stdout: R,
+    mut frames: DuplexStream,
+    log_manager: Option<Arc<ServerLogManager>>,
+    classifier: Arc<StderrClassifier>,
+    space_id: Uuid,
+    server_id: String,
+) where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let space_id_str = space_id.to_string();
+
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(stdout);
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line).await {
+                Ok(0) => {
+                    debug!(server_id = %server_id, "Stdout filter finished (stream closed)");
+                    break;
+                }
+                Ok(_) if is_jsonrpc_frame(&line) => {
+                    if let Err(e) = frames.write_all(&line).await {
+                        debug!(
+                            server_id = %server_id,
+                            error = %e,
+                            "Stdout filter stopped (client closed)"
+                        );
+                        break;
+                    }
+                }
+                Ok(_) => {
+                    let text = String::from_utf8_lossy(&line);
+                    let text = text.trim_end();
+                    if text.trim_start().is_empty() {
+                        continue;
+                    }
+                    if let Some(log_manager) = &log_manager {
+                        let log = classifier.log_entry(text, LogSource::Stdout);
+                        let _ = log_manager.append(&space_id_str, &server_id, log).await;
+                    }
+                }
+                Err(e) => {
+                    debug!(
+                        server_id = %server_id,
+                        error = %e,
+                        "Stdout filter stopped"
+                    );
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Whether a stdout line is a JSON-RPC message (or batch) meant for the client.
+pub(super) fn is_jsonrpc_frame(line: &[u8]) -> bool {
+    match serde_json::from_slice::<serde_json::Value>(line) {
+        Ok(serde_json::Value::Object(map)) => map.contains_key("jsonrpc"),
+        Ok(serde_json::Value::Array(items)) => !items.is_empty(),
+        _ => false,
     }
 }
 
+/// How `${VAR}` / `$VAR` references to unset variables are expanded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownVarMode {
+    /// Keep the reference as written, e.g. `${MISSING}`.
+    #[default]
+    Literal,
+    /// Replace the reference with an empty string, like a shell does.
+    Empty,
+}
+
+/// Client-side streams of a spawned child process.
+enum SpawnedProcess {
+    /// Stdout handed to the client as-is.
+    Direct(ProcessReader<ChildStdout>, ChildStdin),
+    /// Stdout routed through [`spawn_stdout_filter`].
+    Filtered(ProcessReader<DuplexStream>, ChildStdin),
+    /// Bridged through a restart [`Supervisor`].
+    Supervised(DuplexStream),
+}
+
 /// STDIO transport for child process MCP servers
 pub struct StdioTransport {
     command: String,
@@ -130,6 +402,19 @@ pub struct StdioTransport {
     log_manager: Option<Arc<ServerLogManager>>,
     connect_timeout: Duration,
     event_tx: Option<tokio::sync::broadcast::Sender<mcpmux_core::DomainEvent>>,
+    capture_stdout: bool,
+    stderr_classifier: Arc<StderrClassifier>,
+    stderr_limits: StderrLimits,
+    restart_policy: Option<RestartPolicy>,
+    resource_limits: ResourceLimits,
+    cwd: Option<PathBuf>,
+    unknown_vars: UnknownVarMode,
+    secrets: Vec<SecretBinding>,
+    credentials: Option<Arc<dyn CredentialRepository>>,
+    resolver: Arc<dyn CommandResolver>,
+    process: Arc<ProcessHandle>,
+    stderr_task: Arc<StderrTask>,
+    last_peer: LastPeer,
 }
 
 impl StdioTransport {
@@ -153,7 +438,345 @@ impl StdioTransport {
             log_manager,
             connect_timeout,
             event_tx,
+            capture_stdout: false,
+            stderr_classifier: Arc::default(),
+            stderr_limits: StderrLimits::default(),
+            restart_policy: None,
+            resource_limits: ResourceLimits::default(),
+            cwd: None,
+            unknown_vars: UnknownVarMode::default(),
+            secrets: Vec::new(),
+            credentials: None,
+            resolver: Arc::new(WhichResolver),
+            process: Arc::default(),
+            stderr_task: Arc::default(),
+            last_peer: LastPeer::default(),
+        }
+    }
+
+    /// Run the server process in `cwd` instead of inheriting the app's working directory.
+    pub fn with_cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    /// Find the command's binary with `resolver` instead of [`WhichResolver`].
+    pub fn with_command_resolver(mut self, resolver: Arc<dyn CommandResolver>) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Choose how `${VAR}` references to unset variables are expanded.
+    pub fn with_unknown_var_mode(mut self, mode: UnknownVarMode) -> Self {
+        self.unknown_vars = mode;
+        self
+    }
+
+    /// Deliver `secrets` to the server process outside its args and env,
+    /// reading those without a value from `credentials` at connect.
+    pub fn with_secrets(
+        mut self,
+        secrets: Vec<SecretBinding>,
+        credentials: Arc<dyn CredentialRepository>,
+    ) -> Self {
+        self.secrets = secrets;
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Restart the server process with backoff if it exits after connecting.
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = Some(policy);
+        self
+    }
+
+    /// Cap the server process's resources (Unix only; ignored elsewhere).
+    ///
+    /// Limits above the system hard limits fail the connect.
+    pub fn with_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.resource_limits = limits;
+        self
+    }
+
+    /// Log non-JSON-RPC stdout lines instead of handing them to the MCP client.
+    ///
+    /// Useful for servers (notably Python ones) that print startup messages to
+    /// stdout before the protocol stream begins.
+    pub fn with_stdout_capture(mut self, enabled: bool) -> Self {
+        self.capture_stdout = enabled;
+        self
+    }
+
+    /// Classify captured output lines with `classifier` instead of the
+    /// default English keyword rules.
+    pub fn with_stderr_classifier(mut self, classifier: StderrClassifier) -> Self {
+        self.stderr_classifier = Arc::new(classifier);
+        self
+    }
+
+    /// Override the line length and rate limits applied to captured stderr.
+    pub fn with_stderr_limits(mut self, limits: StderrLimits) -> Self {
+        self.stderr_limits = limits;
+        self
+    }
+
+    /// Start the async stderr reader if we got a handle.
+    fn capture_stderr(&self, stderr: Option<ChildStderr>) {
+        if let Some(stderr) = stderr {
+            self.stderr_task.set(spawn_stderr_reader(
+                stderr,
+                self.log_manager.clone(),
+                Arc::clone(&self.stderr_classifier),
+                self.stderr_limits,
+                self.space_id,
+                self.server_id.clone(),
+            ));
+        } else {
+            warn!(
+                server_id = %self.server_id,
+                "No stderr handle available - process logs will not be captured"
+            );
+        }
+    }
+
+    /// Spawn the child under a [`Supervisor`] that restarts it per `policy`.
+    ///
+    /// Returns the bridge stream the MCP client should be served over.
+    /// `secrets` are kept until supervision ends, for restarted children.
+    fn spawn_supervised(
+        &self,
+        policy: RestartPolicy,
+        command_path: PathBuf,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+        secrets: DeliveredSecrets,
+    ) -> std::io::Result<DuplexStream> {
+        let cwd = self.cwd.clone();
+        let log_manager = self.log_manager.clone();
+        let classifier = Arc::clone(&self.stderr_classifier);
+        let limits = self.stderr_limits;
+        let resource_limits = self.resource_limits;
+        let space_id = self.space_id;
+        let server_id = self.server_id.clone();
+        let stderr_task = Arc::clone(&self.stderr_task);
+        let spawn = move || {
+            let mut cmd = build_command(&command_path, &args, &env, cwd.as_deref());
+            secrets.inherit(&mut cmd);
+            resource_limits
+                .apply(&mut cmd)
+                .map_err(std::io::Error::other)?;
+            cmd.stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            let mut child = cmd.spawn()?;
+            if let Some(stderr) = child.stderr.take() {
+                stderr_task.set(spawn_stderr_reader(
+                    stderr,
+                    log_manager.clone(),
+                    Arc::clone(&classifier),
+                    limits,
+                    space_id,
+                    server_id.clone(),
+                ));
+            }
+            Ok(child)
+        };
+
+        let child = spawn()?;
+        let supervisor = Supervisor {
+            policy,
+            spawn: Box::new(spawn),
+            process: Arc::clone(&self.process),
+            capture_stdout: self.capture_stdout,
+            classifier: Arc::clone(&self.stderr_classifier),
+            limits: self.resource_limits,
+            space_id: self.space_id,
+            server_id: self.server_id.clone(),
+            log_manager: self.log_manager.clone(),
+            event_tx: self.event_tx.clone(),
+        };
+        Ok(supervisor.start(child))
+    }
+
+    /// Spawn the child and hand its stdio to the client, filtering stdout if
+    /// capture is enabled. `secrets` are kept until the child exits.
+    fn spawn_direct(
+        &self,
+        mut cmd: Command,
+        secrets: DeliveredSecrets,
+    ) -> std::io::Result<SpawnedProcess> {
+        secrets.inherit(&mut cmd);
+        self.resource_limits
+            .apply(&mut cmd)
+            .map_err(std::io::Error::other)?;
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = cmd.spawn()?;
+
+        let (stdin, stdout) = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => (stdin, stdout),
+            _ => {
+                return Err(std::io::Error::other(
+                    "child process stdio handles unavailable",
+                ))
+            }
+        };
+        self.capture_stderr(child.stderr.take());
+        self.process.set(child);
+        self.spawn_exit_watch(secrets);
+
+        if !self.capture_stdout {
+            let reader = ProcessReader::new(stdout, Arc::clone(&self.process));
+            return Ok(SpawnedProcess::Direct(reader, stdin));
+        }
+
+        let (frames_reader, frames_writer) = tokio::io::duplex(STDOUT_FILTER_BUFFER);
+        spawn_stdout_filter(
+            stdout,
+            frames_writer,
+            self.log_manager.clone(),
+            Arc::clone(&self.stderr_classifier),
+            self.space_id,
+            self.server_id.clone(),
+        );
+        let reader = ProcessReader::new(frames_reader, Arc::clone(&self.process));
+        Ok(SpawnedProcess::Filtered(reader, stdin))
+    }
+
+    /// Gracefully stop the server process.
+    ///
+    /// Sends `SIGTERM` (on Windows, relies on stdin having been closed by
+    /// dropping the client), waits up to `grace` for a clean exit, and only
+    /// then force-kills. Does nothing if the process isn't running.
+    ///
+    /// Returns `false` if the process had to be force-killed or couldn't be
+    /// stopped. The stderr reader is stopped either way.
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        let result = self.process.shutdown(grace).await;
+        self.stderr_task.abort();
+        self.last_peer.clear();
+        match result {
+            Some(Ok(termination)) => {
+                let status = termination.status;
+                info!(server_id = %self.server_id, %status, "STDIO server stopped");
+                let message = if termination.forced {
+                    format!("Server process killed after {grace:?} grace period ({status})")
+                } else {
+                    format!("Server process stopped ({status})")
+                };
+                self.log(LogLevel::Info, LogSource::Connection, message)
+                    .await;
+                !termination.forced
+            }
+            Some(Err(e)) => {
+                warn!(server_id = %self.server_id, error = %e, "Failed to stop STDIO server");
+                false
+            }
+            None => {
+                debug!(server_id = %self.server_id, "No STDIO process to stop");
+                true
+            }
+        }
+    }
+
+    /// Once the directly spawned child's stdout closes, collect its exit
+    /// status and report it with [`DomainEvent::ServerExited`]. Supervised
+    /// children are reported by the supervisor instead.
+    ///
+    /// Exits caused by [`shutdown`](Self::shutdown) aren't reported.
+    /// `secrets` are removed once the child is gone either way.
+    fn spawn_exit_watch(&self, secrets: DeliveredSecrets) {
+        // Weak, so the watch doesn't keep the child alive past the transport
+        let process = Arc::downgrade(&self.process);
+        let stdout_closed = self.process.stdout_closed();
+        let limits = self.resource_limits;
+        let log_manager = self.log_manager.clone();
+        let event_tx = self.event_tx.clone();
+        let space_id = self.space_id;
+        let server_id = self.server_id.clone();
+
+        tokio::spawn(async move {
+            stdout_closed.notified().await;
+            // The process may close stdout a moment before it exits
+            let status = loop {
+                let Some(process) = process.upgrade() else {
+                    return;
+                };
+                if process.is_shutting_down() {
+                    return;
+                }
+                if let Some(status) = process.exit_status() {
+                    break status;
+                }
+                drop(process);
+                tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+            };
+            drop(secrets);
+
+            let exit = ExitInfo::from_status(status);
+            let message = exit.log_message(limits.exceeded(status).as_deref());
+            let level = if exit.is_failure() {
+                error!(server_id = %server_id, "{}", message);
+                LogLevel::Error
+            } else {
+                warn!(server_id = %server_id, "{}", message);
+                LogLevel::Warn
+            };
+            if let Some(log_manager) = log_manager {
+                let log = ServerLog::new(level, LogSource::Connection, message);
+                let _ = log_manager
+                    .append(&space_id.to_string(), &server_id, log)
+                    .await;
+            }
+            if let Some(tx) = event_tx {
+                let _ = tx.send(DomainEvent::ServerExited {
+                    space_id,
+                    server_id,
+                    code: exit.code,
+                    signal: exit.signal,
+                    message: exit.message,
+                });
+            }
+        });
+    }
+
+    /// The environment the server process gets: the app's own environment
+    /// overlaid with the configured env, with the shell PATH injected and
+    /// `${VAR}` references expanded.
+    ///
+    /// Values of variables named like secrets (`API_KEY`, `GITHUB_TOKEN`)
+    /// and anything shaped like a token are masked. Sensitive inputs aren't
+    /// part of it; they are delivered at connect.
+    pub fn resolved_env(&self) -> BTreeMap<String, String> {
+        let shell_path = shell_env::get_shell_path();
+        let mut env = self.env.clone();
+        inject_shell_path(&mut env, shell_path.as_deref());
+        let (_, env) = expand_command_vars(&[], &env, self.unknown_vars);
+        masked_env(&env)
+    }
+
+    /// Names of variables referenced in args or env that are set neither in
+    /// env nor in the app's environment.
+    fn unset_vars(&self) -> Vec<String> {
+        let unset = std::cell::RefCell::new(Vec::<String>::new());
+        let lookup = |name: &str| {
+            let found = self
+                .env
+                .get(name)
+                .cloned()
+                .or_else(|| std::env::var(name).ok());
+            if found.is_none() && !unset.borrow().iter().any(|n| n == name) {
+                unset.borrow_mut().push(name.to_string());
+            }
+            found
+        };
+        for value in self.args.iter().chain(self.env.values()) {
+            expand_vars(value, &lookup, self.unknown_vars);
         }
+        let mut unset = unset.into_inner();
+        unset.sort();
+        unset
     }
 
     /// Log a message to the server log manager.
@@ -179,28 +802,83 @@ impl Transport for StdioTransport {
             "Connecting to STDIO server"
         );
 
+        // Sensitive inputs are read now rather than kept on the transport
+        let secrets = fetch_secrets(
+            &self.secrets,
+            self.credentials.as_ref(),
+            self.space_id,
+            &self.server_id,
+        )
+        .await;
+        let secret_references =
+            fetch_secret_references(&self.env, self.credentials.as_ref(), self.space_id).await;
+
+        // Configured env values are typically credentials, both as written
+        // and with their ${VAR} references resolved; keep them out of
+        // everything logged for this server, starting with the args below
+        let (_, resolved_env) = expand_command_vars(&[], &self.env, self.unknown_vars);
+        let known = known_secret_values(
+            self.env
+                .values()
+                .chain(resolved_env.values())
+                .cloned()
+                .chain(secrets.iter().flatten().map(|(_, value)| value.to_string()))
+                .chain(
+                    secret_references
+                        .iter()
+                        .flatten()
+                        .map(|(_, value)| value.to_string()),
+                ),
+        );
+        if let Some(log_manager) = &self.log_manager {
+            log_manager
+                .register_secrets(
+                    &self.space_id.to_string(),
+                    &self.server_id,
+                    known.iter().cloned(),
+                )
+                .await;
+        }
+
         // Log connection attempt
         self.log(
             LogLevel::Info,
             LogSource::Connection,
-            format!("Connecting to server: {} {:?}", self.command, self.args),
+            format!(
+                "Connecting to server: {} {:?}",
+                self.command,
+                redact_args(&self.args, &known)
+            ),
         )
         .await;
 
+        let (secrets, secret_references) = match secrets
+            .and_then(|secrets| Ok((secrets, secret_references.map_err(|e| e.to_string())?)))
+        {
+            Ok(fetched) => fetched,
+            Err(err) => {
+                error!(server_id = %self.server_id, "{}", err);
+                self.log(LogLevel::Error, LogSource::Connection, err.clone())
+                    .await;
+                return TransportConnectResult::Failed(err);
+            }
+        };
+
+        let started = Instant::now();
+
         // Resolve the user's full shell PATH (cached after first call).
         // On macOS/Linux, GUI apps have a minimal PATH that doesn't include
         // Homebrew, nvm, Volta, fnm, or /usr/local/bin — this fixes that.
         let shell_path = shell_env::get_shell_path();
 
         // Validate command exists, using the shell-resolved PATH when available
-        let command_path = match resolve_command(&self.command, shell_path) {
+        let command_path = match self.resolver.resolve(&self.command, shell_path.as_deref()) {
             Ok(path) => path,
-            Err(_) => {
-                let hint = command_hint(&self.command);
-                let err = format!(
-                    "Command not found: {}. Ensure it's installed and in PATH.{hint}",
-                    self.command
-                );
+            Err(e) => {
+                let err = match e {
+                    ResolveError::NotFound(_) => format!("{e}{}", command_hint(&self.command)),
+                    ResolveError::CommandNotPermitted(_) => e.to_string(),
+                };
                 error!(server_id = %self.server_id, "{}", err);
                 self.log(LogLevel::Error, LogSource::Connection, err.clone())
                     .await;
@@ -208,53 +886,92 @@ impl Transport for StdioTransport {
             }
         };
 
+        let resolved = Instant::now();
         debug!(
             server_id = %self.server_id,
             path = ?command_path,
             "Found command"
         );
 
+        // Validate the working directory up front; spawn would only report ENOENT
+        if let Some(cwd) = &self.cwd {
+            if !cwd.is_dir() {
+                let err = format!("Working directory not found: {}", cwd.display());
+                error!(server_id = %self.server_id, "{}", err);
+                self.log(LogLevel::Error, LogSource::Connection, err.clone())
+                    .await;
+                return TransportConnectResult::Failed(err);
+            }
+        }
+
+        #[cfg(not(unix))]
+        if !self.resource_limits.is_empty() {
+            warn!(
+                server_id = %self.server_id,
+                "Resource limits are only supported on Unix; ignoring them"
+            );
+        }
+        if let Err(e) = self.resource_limits.validate() {
+            let err = format!("Invalid resource limits: {e}");
+            error!(server_id = %self.server_id, "{}", err);
+            self.log(LogLevel::Error, LogSource::Connection, err.clone())
+                .await;
+            return TransportConnectResult::Failed(err);
+        }
+
         // Build the child process environment:
         // - Start with user-configured env vars (from resolution.rs)
         // - Inject the shell-resolved PATH so child processes can find
         //   their own dependencies (e.g., npx needs to find node)
-        let args = self.args.clone();
+        // - Expand ${VAR} / $VAR references against the merged environment
+        // - Point sensitive input references at where their values were put
+        // - Substitute ${secret:...} references, after logging the env
         let mut env = self.env.clone();
-        inject_shell_path(&mut env, shell_path);
-
-        let (transport, child_stderr) =
-            match TokioChildProcess::builder(Command::new(&command_path).configure(move |cmd| {
-                cmd.args(&args).envs(&env).kill_on_drop(true);
-                configure_child_process_platform(cmd);
-            }))
-            .stderr(Stdio::piped())
-            .spawn()
-            {
-                Ok(result) => result,
-                Err(e) => {
-                    let hint = command_hint(&self.command);
-                    let err = format!("Failed to spawn process: {e}.{hint}");
-                    error!(server_id = %self.server_id, "{}", err);
-                    self.log(LogLevel::Error, LogSource::Connection, err.clone())
-                        .await;
-                    return TransportConnectResult::Failed(err);
-                }
-            };
+        inject_shell_path(&mut env, shell_path.as_deref());
+        let (mut args, mut env) = expand_command_vars(&self.args, &env, self.unknown_vars);
+        debug!(
+            server_id = %self.server_id,
+            env = ?masked_env(&env),
+            "Resolved server environment"
+        );
 
-        // Start the async stderr reader if we got a handle
-        if let Some(stderr) = child_stderr {
-            spawn_stderr_reader(
-                stderr,
-                self.log_manager.clone(),
-                self.space_id,
-                self.server_id.clone(),
-            );
+        // A pipe can only be read once, so supervised children get files
+        let delivered = match DeliveredSecrets::deliver(&secrets, self.restart_policy.is_none()) {
+            Ok(delivered) => delivered,
+            Err(e) => {
+                let err = format!("Failed to deliver sensitive inputs: {e}");
+                error!(server_id = %self.server_id, "{}", err);
+                self.log(LogLevel::Error, LogSource::Connection, err.clone())
+                    .await;
+                return TransportConnectResult::Failed(err);
+            }
+        };
+        drop(secrets);
+        delivered.apply(&mut args, &mut env);
+        apply_secret_references(&secret_references, &mut env);
+        drop(secret_references);
+
+        let spawned = if let Some(policy) = self.restart_policy {
+            self.spawn_supervised(policy, command_path.clone(), args, env.clone(), delivered)
+                .map(SpawnedProcess::Supervised)
         } else {
-            warn!(
-                server_id = %self.server_id,
-                "No stderr handle available - process logs will not be captured"
-            );
-        }
+            self.spawn_direct(
+                build_command(&command_path, &args, &env, self.cwd.as_deref()),
+                delivered,
+            )
+        };
+        let process = match spawned {
+            Ok(process) => process,
+            Err(e) => {
+                let hint = failure_hint(&self.command, &command_path, &env).await;
+                let err = format!("Failed to spawn process: {e}.{hint}");
+                error!(server_id = %self.server_id, "{}", err);
+                self.log(LogLevel::Error, LogSource::Connection, err.clone())
+                    .await;
+                return TransportConnectResult::Failed(err);
+            }
+        };
+        let spawned_at = Instant::now();
 
         // Create client handler
         let client_handler = create_client_handler(
@@ -265,11 +982,18 @@ impl Transport for StdioTransport {
         );
 
         // Connect with timeout
-        let connect_future = client_handler.serve(transport);
+        let connect_future = match process {
+            SpawnedProcess::Direct(stdout, stdin) => client_handler.serve((stdout, stdin)).boxed(),
+            SpawnedProcess::Filtered(stdout, stdin) => {
+                client_handler.serve((stdout, stdin)).boxed()
+            }
+            SpawnedProcess::Supervised(bridge) => client_handler.serve(bridge).boxed(),
+        };
         let client = match tokio::time::timeout(self.connect_timeout, connect_future).await {
             Ok(Ok(client)) => client,
             Ok(Err(e)) => {
-                let hint = command_hint(&self.command);
+                self.stderr_task.abort();
+                let hint = failure_hint(&self.command, &command_path, &env).await;
                 let err = format!("MCP handshake failed: {e}.{hint}");
                 error!(server_id = %self.server_id, "{}", err);
                 self.log(LogLevel::Error, LogSource::Connection, err.clone())
@@ -277,7 +1001,8 @@ impl Transport for StdioTransport {
                 return TransportConnectResult::Failed(err);
             }
             Err(_) => {
-                let hint = command_hint(&self.command);
+                self.stderr_task.abort();
+                let hint = failure_hint(&self.command, &command_path, &env).await;
                 let err = format!("Connection timeout ({:?}).{hint}", self.connect_timeout);
                 error!(server_id = %self.server_id, "{}", err);
                 self.log(LogLevel::Error, LogSource::Connection, err.clone())
@@ -286,19 +1011,58 @@ impl Transport for StdioTransport {
             }
         };
 
+        let connected = Instant::now();
+        let ms = |d: Duration| d.as_millis() as u64;
+        let (startup_ms, resolve_ms, spawn_ms, handshake_ms) = (
+            ms(connected - started),
+            ms(resolved - started),
+            ms(spawned_at - resolved),
+            ms(connected - spawned_at),
+        );
+
         info!(
             server_id = %self.server_id,
+            startup_ms, resolve_ms, spawn_ms, handshake_ms,
             "STDIO server connected"
         );
 
-        self.log(
-            LogLevel::Info,
-            LogSource::Connection,
-            "Server connected successfully".to_string(),
-        )
-        .await;
+        if let Some(log_manager) = &self.log_manager {
+            let log = ServerLog::new(
+                LogLevel::Info,
+                LogSource::Connection,
+                format!(
+                    "Server connected successfully in {startup_ms}ms (command resolution \
+                     {resolve_ms}ms, spawn {spawn_ms}ms, handshake {handshake_ms}ms)"
+                ),
+            )
+            .with_metadata(serde_json::json!({
+                "startup_ms": startup_ms,
+                "resolve_ms": resolve_ms,
+                "spawn_ms": spawn_ms,
+                "handshake_ms": handshake_ms,
+            }));
+            if let Err(e) = log_manager
+                .append(&self.space_id.to_string(), &self.server_id, log)
+                .await
+            {
+                error!("Failed to write log: {}", e);
+            }
+        }
+
+        if let Some(tx) = &self.event_tx {
+            let _ = tx.send(DomainEvent::ServerConnected {
+                space_id: self.space_id,
+                server_id: self.server_id.clone(),
+                startup_ms,
+                resolve_ms,
+                spawn_ms,
+                handshake_ms,
+            });
+        }
 
-        TransportConnectResult::Connected(client)
+        let result = TransportConnectResult::Connected(client);
+        self.last_peer.record(&result);
+        result
     }
 
     fn transport_type(&self) -> TransportType {
@@ -308,22 +1072,290 @@ impl Transport for StdioTransport {
     fn description(&self) -> String {
         format!("stdio:{}", self.command)
     }
+
+    async fn shutdown(&self, grace: Duration) -> bool {
+        StdioTransport::shutdown(self, grace).await
+    }
+
+    /// Checks the server process hasn't exited, then pings it.
+    async fn is_alive(&self) -> bool {
+        self.process.is_running() && self.last_peer.ping(PING_TIMEOUT).await
+    }
+
+    /// Check the command, working directory and environment, and only if
+    /// they pass, start the server for a handshake and stop it again.
+    async fn validate(&self) -> ValidationResult {
+        let mut result = ValidationResult::default();
+
+        let shell_path = shell_env::get_shell_path();
+        match self.resolver.resolve(&self.command, shell_path.as_deref()) {
+            Ok(_) => result.pass(ValidationCheck::CommandFound),
+            Err(e @ ResolveError::NotFound(_)) => result.fail(
+                ValidationCheck::CommandFound,
+                format!("{e}{}", command_hint(&self.command)),
+            ),
+            Err(e) => result.fail(ValidationCheck::CommandFound, e.to_string()),
+        }
+
+        match &self.cwd {
+            Some(cwd) if !cwd.is_dir() => result.fail(
+                ValidationCheck::WorkingDirectory,
+                format!("Working directory not found: {}", cwd.display()),
+            ),
+            _ => result.pass(ValidationCheck::WorkingDirectory),
+        }
+
+        let unset = self.unset_vars();
+        if !unset.is_empty() {
+            result.fail(
+                ValidationCheck::Environment,
+                format!("Referenced variables are not set: {}", unset.join(", ")),
+            );
+        } else if let Err(e) = self.resource_limits.validate() {
+            result.fail(
+                ValidationCheck::Environment,
+                format!("Invalid resource limits: {e}"),
+            );
+        } else {
+            result.pass(ValidationCheck::Environment);
+        }
+
+        if result.is_ok() {
+            validation::check_handshake(self, &mut result).await;
+        }
+        result
+    }
+}
+
+/// Build the command for a child process with its args, env, working
+/// directory and platform flags.
+fn build_command(
+    command_path: &Path,
+    args: &[String],
+    env: &HashMap<String, String>,
+    cwd: Option<&Path>,
+) -> Command {
+    let mut cmd = Command::new(command_path);
+    cmd.args(args).envs(env).kill_on_drop(true);
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    configure_child_process_platform(&mut cmd);
+    cmd
 }
 
-/// Resolve a command binary using the shell-resolved PATH when available.
+/// Words in an option name that mark its value as a secret, as in `--api-key`.
+const SECRET_OPTION_WORDS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "apikey",
+    "api-key",
+    "api_key",
+    "access-key",
+    "access_key",
+    "private-key",
+    "private_key",
+    "credential",
+    "authorization",
+];
+
+/// Whether an option such as `--api-key` or `-password`, or a variable such
+/// as `API_KEY`, takes a secret.
+fn is_secret_option(option: &str) -> bool {
+    let name = option.trim_start_matches('-').to_ascii_lowercase();
+    SECRET_OPTION_WORDS.iter().any(|word| name.contains(word))
+}
+
+/// `args` as shown in the connection log.
 ///
-/// Falls back to the standard `which::which()` (which uses the process PATH)
-/// if no shell PATH was resolved.
-fn resolve_command(
-    command: &str,
-    shell_path: Option<&std::ffi::OsString>,
-) -> Result<std::path::PathBuf, which::Error> {
-    if let Some(path) = shell_path {
-        which::which_in(command, Some(path), ".")
-            .or_else(|_| which::which_in(format!("{}.exe", command), Some(path), "."))
-    } else {
-        which::which(command).or_else(|_| which::which(format!("{}.exe", command)))
+/// Masks `known` values and common token shapes (see [`LogRedactor`]), plus
+/// the value of any option named like a secret, whether passed as
+/// `--token abc` or `--token=abc`.
+fn redact_args(args: &[String], known: &[String]) -> Vec<String> {
+    let redactor = LogRedactor::default();
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut secret_next = false;
+    for arg in args {
+        let is_option = arg.starts_with('-');
+        if std::mem::take(&mut secret_next) && !is_option {
+            redacted.push(REDACTED.to_string());
+            continue;
+        }
+        if is_option {
+            match arg.split_once('=') {
+                Some((option, _)) if is_secret_option(option) => {
+                    redacted.push(format!("{option}={REDACTED}"));
+                    continue;
+                }
+                Some(_) => {}
+                None => secret_next = is_secret_option(arg),
+            }
+        }
+        redacted.push(redactor.redact(arg, known));
+    }
+    redacted
+}
+
+/// The app's environment overlaid with `env`, as the child inherits it, with
+/// secret values masked.
+fn masked_env(env: &HashMap<String, String>) -> BTreeMap<String, String> {
+    let redactor = LogRedactor::default();
+    let inherited = std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    inherited
+        .chain(
+            env.iter()
+                .map(|(name, value)| (name.clone(), value.clone())),
+        )
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .map(|(name, value)| {
+            let value = if is_secret_option(&name) {
+                REDACTED.to_string()
+            } else {
+                redactor.redact(&value, &[])
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// Hint for a command that was found but failed to start or connect.
+///
+/// `docker` is probed with `docker info` (same env as the server, so
+/// `DOCKER_HOST` / `DOCKER_CONTEXT` apply): if the daemon doesn't answer, the
+/// hint says so instead of the generic one, which also covers "not installed".
+async fn failure_hint(command: &str, command_path: &Path, env: &HashMap<String, String>) -> String {
+    if command_basename(command) == "docker"
+        && !docker_daemon_available(command_path, env, DOCKER_PROBE_TIMEOUT).await
+    {
+        return format!(" {}", DOCKER_DAEMON_UNAVAILABLE);
+    }
+    command_hint(command)
+}
+
+/// Whether `docker info` succeeds within `timeout`.
+async fn docker_daemon_available(
+    docker: &Path,
+    env: &HashMap<String, String>,
+    timeout: Duration,
+) -> bool {
+    let mut cmd = build_command(docker, &["info".to_string()], env, None);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    match tokio::time::timeout(timeout, cmd.status()).await {
+        Ok(Ok(status)) => status.success(),
+        Ok(Err(e)) => {
+            debug!("docker info could not be run: {}", e);
+            false
+        }
+        Err(_) => {
+            debug!("docker info did not answer within {:?}", timeout);
+            false
+        }
+    }
+}
+
+/// Expand variable references in `args` and `env` values.
+///
+/// Lookups use the child's merged environment: `env` first, then the app's
+/// own environment, which the child inherits. Env values are expanded first,
+/// against the unexpanded map (references between them are not chased), and
+/// args are then expanded against the result, matching what the child sees.
+fn expand_command_vars(
+    args: &[String],
+    env: &HashMap<String, String>,
+    mode: UnknownVarMode,
+) -> (Vec<String>, HashMap<String, String>) {
+    fn lookup_in(env: &HashMap<String, String>) -> impl Fn(&str) -> Option<String> + '_ {
+        |name| env.get(name).cloned().or_else(|| std::env::var(name).ok())
+    }
+
+    let expanded_env: HashMap<String, String> = env
+        .iter()
+        .map(|(key, value)| (key.clone(), expand_vars(value, &lookup_in(env), mode)))
+        .collect();
+    let args = args
+        .iter()
+        .map(|arg| expand_vars(arg, &lookup_in(&expanded_env), mode))
+        .collect();
+    (args, expanded_env)
+}
+
+/// Substitute `${VAR}` and `$VAR` in `value`; `\$` produces a literal `$`.
+///
+/// Anything that isn't a well-formed reference (`$` followed by a non-name
+/// character, an unterminated `${`, or a braced name with other characters
+/// such as `${input:x}`) is left as-is.
+pub(super) fn expand_vars(
+    value: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    mode: UnknownVarMode,
+) -> String {
+    fn is_name_start(c: char) -> bool {
+        c.is_ascii_alphabetic() || c == '_'
+    }
+    fn is_name_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
+    }
+
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(pos) = rest.find(['$', '\\']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if let Some(after) = tail.strip_prefix("\\$") {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        if let Some(after) = tail.strip_prefix('\\') {
+            out.push('\\');
+            rest = after;
+            continue;
+        }
+
+        let after_dollar = &tail[1..];
+        let (name, consumed) = if let Some(braced) = after_dollar.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end)
+                    if braced[..end].starts_with(is_name_start)
+                        && braced[..end].chars().all(is_name_char) =>
+                {
+                    (&braced[..end], end + 3)
+                }
+                _ => ("", 0),
+            }
+        } else if after_dollar.starts_with(is_name_start) {
+            let end = after_dollar
+                .find(|c: char| !is_name_char(c))
+                .unwrap_or(after_dollar.len());
+            (&after_dollar[..end], end + 1)
+        } else {
+            ("", 0)
+        };
+
+        if consumed == 0 {
+            out.push('$');
+            rest = after_dollar;
+            continue;
+        }
+
+        match lookup(name) {
+            Some(found) => out.push_str(&found),
+            None if mode == UnknownVarMode::Empty => {}
+            None => out.push_str(&tail[..consumed]),
+        }
+        rest = &tail[consumed..];
     }
+
+    out.push_str(rest);
+    out
 }
 
 /// Inject the shell-resolved PATH into the child process environment.
@@ -349,57 +1381,6 @@ mod tests {
     use super::*;
     use std::ffi::OsString;
 
-    // ── resolve_command tests ──────────────────────────────────────
-
-    #[test]
-    fn test_resolve_command_finds_sh_with_shell_path() {
-        // /bin/sh exists on every Unix system
-        #[cfg(unix)]
-        {
-            let path = OsString::from("/bin:/usr/bin");
-            let result = resolve_command("sh", Some(&path));
-            assert!(result.is_ok(), "Should find 'sh' in /bin:/usr/bin");
-        }
-    }
-
-    #[test]
-    fn test_resolve_command_finds_command_without_shell_path() {
-        // Without shell_path, falls back to which::which (uses process PATH)
-        #[cfg(unix)]
-        {
-            let result = resolve_command("sh", None);
-            assert!(result.is_ok(), "Should find 'sh' via process PATH");
-        }
-    }
-
-    #[test]
-    fn test_resolve_command_returns_error_for_nonexistent() {
-        let fake_path = OsString::from("/nonexistent/path");
-        let result = resolve_command("this_command_surely_does_not_exist_xyz", Some(&fake_path));
-        assert!(result.is_err(), "Should fail for nonexistent command");
-    }
-
-    #[test]
-    fn test_resolve_command_not_found_in_restricted_path() {
-        // Even if 'sh' exists, it shouldn't be found if PATH points elsewhere
-        let path = OsString::from("/tmp/empty_dir_that_does_not_exist");
-        let result = resolve_command("sh", Some(&path));
-        assert!(
-            result.is_err(),
-            "Should not find 'sh' in a path that doesn't contain it"
-        );
-    }
-
-    #[cfg(unix)]
-    #[test]
-    fn test_resolve_command_with_full_shell_path() {
-        // Use the actual shell-resolved PATH to find a real command
-        if let Some(shell_path) = shell_env::get_shell_path() {
-            let result = resolve_command("sh", Some(shell_path));
-            assert!(result.is_ok(), "Should find 'sh' using resolved shell PATH");
-        }
-    }
-
     // ── inject_shell_path tests ────────────────────────────────────
 
     #[test]
@@ -461,8 +1442,169 @@ mod tests {
         assert_eq!(env.len(), 1, "Should only have PATH");
     }
 
+    // ── variable expansion tests ───────────────────────────────────
+
+    fn lookup_in(env: &HashMap<String, String>) -> impl Fn(&str) -> Option<String> + '_ {
+        |name| env.get(name).cloned()
+    }
+
+    #[test]
+    fn test_expand_vars_braced_and_bare() {
+        let env = HashMap::from([
+            ("GITHUB_TOKEN".to_string(), "ghp_123".to_string()),
+            ("HOME".to_string(), "/home/me".to_string()),
+        ]);
+        let lookup = lookup_in(&env);
+
+        assert_eq!(
+            expand_vars("--token ${GITHUB_TOKEN}", &lookup, UnknownVarMode::Literal),
+            "--token ghp_123"
+        );
+        assert_eq!(
+            expand_vars("$HOME/data:$HOME", &lookup, UnknownVarMode::Literal),
+            "/home/me/data:/home/me"
+        );
+        assert_eq!(
+            expand_vars("${HOME}suffix", &lookup, UnknownVarMode::Literal),
+            "/home/mesuffix"
+        );
+    }
+
+    #[test]
+    fn test_expand_vars_unknown_modes() {
+        let env = HashMap::new();
+        let lookup = lookup_in(&env);
+
+        assert_eq!(
+            expand_vars("a${MISSING}b$MISSING", &lookup, UnknownVarMode::Literal),
+            "a${MISSING}b$MISSING"
+        );
+        assert_eq!(
+            expand_vars("a${MISSING}b$MISSING", &lookup, UnknownVarMode::Empty),
+            "ab"
+        );
+    }
+
+    #[test]
+    fn test_expand_vars_escapes_and_malformed() {
+        let env = HashMap::from([("X".to_string(), "1".to_string())]);
+        let lookup = lookup_in(&env);
+
+        assert_eq!(
+            expand_vars(r"price \$X is ${X}", &lookup, UnknownVarMode::Empty),
+            "price $X is 1"
+        );
+        // Backslashes not escaping a `$` are preserved (Windows paths)
+        assert_eq!(
+            expand_vars(r"C:\tmp\run-$X", &lookup, UnknownVarMode::Literal),
+            r"C:\tmp\run-1"
+        );
+        // Not references: left untouched regardless of mode
+        for literal in ["$", "cost: $5", "${unterminated", "${input:api_key}", "${}"] {
+            assert_eq!(
+                expand_vars(literal, &lookup, UnknownVarMode::Empty),
+                literal
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_command_vars_uses_injected_path() {
+        let mut env = HashMap::new();
+        env.insert("DATA_DIR".to_string(), "$PATH/data".to_string());
+
+        let path = OsString::from("/usr/bin:/usr/local/bin");
+        inject_shell_path(&mut env, Some(&path));
+
+        let args = vec!["--path=${PATH}".to_string(), "${DATA_DIR}".to_string()];
+        let (args, env) = expand_command_vars(&args, &env, UnknownVarMode::Literal);
+
+        assert_eq!(args[0], "--path=/usr/bin:/usr/local/bin");
+        assert_eq!(args[1], "/usr/bin:/usr/local/bin/data");
+        assert_eq!(
+            env.get("DATA_DIR"),
+            Some(&"/usr/bin:/usr/local/bin/data".to_string())
+        );
+        assert_eq!(
+            env.get("PATH"),
+            Some(&"/usr/bin:/usr/local/bin".to_string())
+        );
+    }
+
     // ── command_hint tests ─────────────────────────────────────────
 
+    #[test]
+    fn test_redact_args_masks_secret_options() {
+        let args: Vec<String> = ["--token", "abc123", "--api-key=xyz", "--port", "3000"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            redact_args(&args, &[]),
+            vec!["--token", "***", "--api-key=***", "--port", "3000"]
+        );
+    }
+
+    #[test]
+    fn test_redact_args_masks_known_values_and_patterns() {
+        let known = known_secret_values(vec!["configured-secret-1".to_string()]);
+        let args: Vec<String> = [
+            "server.js",
+            "--header=X-Key: configured-secret-1",
+            "sk-proj-abcdefghijklmnop1234",
+            "--verbose",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(
+            redact_args(&args, &known),
+            vec!["server.js", "--header=X-Key: ***", "***", "--verbose"]
+        );
+    }
+
+    #[test]
+    fn test_redact_args_secret_option_without_value() {
+        let args: Vec<String> = ["--token", "--verbose"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(redact_args(&args, &[]), vec!["--token", "--verbose"]);
+    }
+
+    #[test]
+    fn test_resolved_env_includes_path_and_masks_secrets() {
+        let transport = StdioTransport::new(
+            "node".to_string(),
+            vec![],
+            HashMap::from([
+                ("LOG_LEVEL".to_string(), "debug".to_string()),
+                (
+                    "GITHUB_TOKEN".to_string(),
+                    "plain-looking-value".to_string(),
+                ),
+                (
+                    "HOME_COPY".to_string(),
+                    "${MCPMUX_TEST_RESOLVED_HOME}".to_string(),
+                ),
+            ]),
+            Uuid::new_v4(),
+            "test".to_string(),
+            None,
+            Duration::from_secs(5),
+            None,
+        );
+        std::env::set_var("MCPMUX_TEST_RESOLVED_HOME", "/home/test");
+
+        let env = transport.resolved_env();
+        assert_eq!(env["LOG_LEVEL"], "debug");
+        assert_eq!(env["GITHUB_TOKEN"], REDACTED);
+        assert_eq!(env["HOME_COPY"], "/home/test");
+        // Inherited from the app, as the child would
+        assert_eq!(env["MCPMUX_TEST_RESOLVED_HOME"], "/home/test");
+        assert!(env.contains_key("PATH"));
+    }
+
     #[test]
     fn test_command_hint_docker() {
         assert!(command_hint("docker").contains("Docker Desktop"));
@@ -476,53 +1618,288 @@ mod tests {
         assert_eq!(command_hint("python"), "");
     }
 
-    // ── classify_stderr_line tests ─────────────────────────────────
+    #[test]
+    fn test_command_hint_runtimes() {
+        assert!(command_hint("uvx").contains("Install uv"));
+        assert!(command_hint("/home/me/.local/bin/uv").contains("Install uv"));
+        assert!(command_hint("C:\\Users\\me\\.bun\\bin\\bunx.exe").contains("Install Bun"));
+        assert!(command_hint("deno.exe").contains("Install Deno"));
+        assert!(command_hint("podman").contains("Podman machine"));
+        assert!(command_hint("docker-compose").contains("Docker Desktop"));
+        assert!(command_hint("docker.exe").starts_with(' '));
+    }
 
     #[test]
-    fn test_classify_stderr_error() {
+    fn test_default_connect_timeout() {
+        let container = default_stdio_connect_timeout("docker");
+        assert_eq!(container, CONTAINER_CONNECT_TIMEOUT);
+        assert_eq!(default_stdio_connect_timeout("/usr/bin/podman"), container);
+        assert_eq!(default_stdio_connect_timeout("docker-compose"), container);
         assert_eq!(
-            classify_stderr_line("ERROR: something failed"),
-            LogLevel::Error
+            default_stdio_connect_timeout("C:\\bin\\docker.exe"),
+            container
         );
+
+        let interpreter = default_stdio_connect_timeout("node");
+        assert_eq!(interpreter, INTERPRETER_CONNECT_TIMEOUT);
         assert_eq!(
-            classify_stderr_line("fatal: not a git repository"),
-            LogLevel::Error
+            default_stdio_connect_timeout("/usr/bin/python3"),
+            interpreter
         );
+        assert!(interpreter < container);
+
+        assert_eq!(default_stdio_connect_timeout("npx"), STDIO_CONNECT_TIMEOUT);
         assert_eq!(
-            classify_stderr_line("thread 'main' panicked"),
-            LogLevel::Error
+            default_stdio_connect_timeout("./my-server"),
+            STDIO_CONNECT_TIMEOUT
         );
     }
 
-    #[test]
-    fn test_classify_stderr_warn() {
+    /// Write an executable `docker` script with the given body into a fresh dir.
+    #[cfg(unix)]
+    fn fake_docker(body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("mcpmux-docker-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("docker");
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_docker_daemon_probe() {
+        let env = HashMap::new();
+        let timeout = Duration::from_millis(500);
+
+        let running = fake_docker(r#"[ "$1" = info ] && exit 0; exit 1"#);
+        assert!(docker_daemon_available(&running, &env, timeout).await);
         assert_eq!(
-            classify_stderr_line("WARN: deprecated feature"),
-            LogLevel::Warn
+            failure_hint("docker", &running, &env).await,
+            command_hint("docker")
         );
+
+        let stopped = fake_docker("echo 'Cannot connect to the Docker daemon' >&2; exit 1");
+        assert!(!docker_daemon_available(&stopped, &env, timeout).await);
+        let hint = failure_hint("docker", &stopped, &env).await;
+        assert!(hint.contains("Docker daemon unavailable"), "{}", hint);
+        assert!(hint.contains("Docker Desktop"), "{}", hint);
+
+        let hung = fake_docker("sleep 30");
+        assert!(!docker_daemon_available(&hung, &env, timeout).await);
+
+        // Only docker itself is probed
         assert_eq!(
-            classify_stderr_line("Warning: something is off"),
-            LogLevel::Warn
+            failure_hint("uvx", &stopped, &env).await,
+            command_hint("uvx")
         );
+
+        for path in [running, stopped, hung] {
+            std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        }
     }
 
     #[test]
-    fn test_classify_stderr_debug() {
-        assert_eq!(
-            classify_stderr_line("DEBUG: internal state"),
-            LogLevel::Debug
+    fn test_register_command_hint() {
+        register_command_hint("test-runtime-xyz.exe", "Install xyz.");
+        assert_eq!(command_hint("/opt/test-runtime-xyz"), " Install xyz.");
+
+        register_command_hint("test-runtime-xyz", "Install xyz 2.");
+        assert_eq!(command_hint("test-runtime-xyz"), " Install xyz 2.");
+    }
+
+    // ── stdout filter tests ────────────────────────────────────────
+
+    #[test]
+    fn test_is_jsonrpc_frame() {
+        assert!(is_jsonrpc_frame(br#"{"jsonrpc":"2.0","id":1,"result":{}}"#));
+        assert!(is_jsonrpc_frame(
+            b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\r\n"
+        ));
+        assert!(is_jsonrpc_frame(
+            br#"[{"jsonrpc":"2.0","id":1,"result":{}}]"#
+        ));
+
+        assert!(!is_jsonrpc_frame(b"Starting server on stdio...\n"));
+        assert!(!is_jsonrpc_frame(br#"{"level":"info","msg":"ready"}"#));
+        assert!(!is_jsonrpc_frame(b"[]"));
+        assert!(!is_jsonrpc_frame(b"42"));
+    }
+
+    #[tokio::test]
+    async fn test_stdout_filter_forwards_only_frames() {
+        use tokio::io::AsyncReadExt;
+
+        let stdout: &'static [u8] = b"Loading model...\n\
+            {\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}\n\
+            \n\
+            WARNING: running in debug mode\n\
+            {\"jsonrpc\":\"2.0\",\"method\":\"notifications/tools/list_changed\"}\n";
+
+        let (mut frames_reader, frames_writer) = tokio::io::duplex(STDOUT_FILTER_BUFFER);
+        spawn_stdout_filter(
+            stdout,
+            frames_writer,
+            None,
+            Arc::default(),
+            Uuid::new_v4(),
+            "test-server".to_string(),
         );
+
+        let mut forwarded = String::new();
+        frames_reader.read_to_string(&mut forwarded).await.unwrap();
         assert_eq!(
-            classify_stderr_line("trace: verbose output"),
-            LogLevel::Debug
+            forwarded,
+            "{\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}\n\
+             {\"jsonrpc\":\"2.0\",\"method\":\"notifications/tools/list_changed\"}\n"
         );
     }
+    // ── exit reporting tests ───────────────────────────────────────
 
-    #[test]
-    fn test_classify_stderr_info_default() {
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unexpected_exit_is_reported() {
+        use tokio::io::AsyncReadExt;
+
+        let (event_tx, mut events) = tokio::sync::broadcast::channel(8);
+        let transport = StdioTransport::new(
+            "sh".to_string(),
+            Vec::new(),
+            HashMap::new(),
+            Uuid::new_v4(),
+            "crashy".to_string(),
+            None,
+            Duration::from_secs(5),
+            Some(event_tx),
+        );
+        let cmd = build_command(
+            Path::new("sh"),
+            &["-c".to_string(), "exit 3".to_string()],
+            &HashMap::new(),
+            None,
+        );
+        let Ok(SpawnedProcess::Direct(mut stdout, _stdin)) =
+            transport.spawn_direct(cmd, DeliveredSecrets::default())
+        else {
+            panic!("expected a direct process");
+        };
+        stdout.read_to_end(&mut Vec::new()).await.unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .expect("exit was not reported")
+            .unwrap();
+        match event {
+            DomainEvent::ServerExited {
+                server_id,
+                code,
+                signal,
+                message,
+                ..
+            } => {
+                assert_eq!(server_id, "crashy");
+                assert_eq!((code, signal), (Some(3), None));
+                assert_eq!(message, "exited with code 3");
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    // ── command resolver tests ─────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_connect_rejects_command_not_on_allowlist() {
+        use super::super::AllowlistResolver;
+
+        let transport = StdioTransport::new(
+            "sh".to_string(),
+            Vec::new(),
+            HashMap::new(),
+            Uuid::new_v4(),
+            "sandboxed".to_string(),
+            None,
+            Duration::from_secs(5),
+            None,
+        )
+        .with_command_resolver(Arc::new(AllowlistResolver::new(["node"])));
+
+        match transport.connect().await {
+            TransportConnectResult::Failed(err) => {
+                assert_eq!(err, "Command not permitted: sh");
+            }
+            _ => panic!("expected the command to be refused"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_each_check_without_spawning() {
+        let missing_dir = std::env::temp_dir().join(format!("mcpmux-missing-{}", Uuid::new_v4()));
+        let transport = StdioTransport::new(
+            "this_command_surely_does_not_exist_xyz".to_string(),
+            vec![
+                "--token".to_string(),
+                "${MCPMUX_TEST_UNSET_VAR}".to_string(),
+            ],
+            HashMap::from([("HOME_DIR".to_string(), "$HOME".to_string())]),
+            Uuid::new_v4(),
+            "broken".to_string(),
+            None,
+            Duration::from_secs(5),
+            None,
+        )
+        .with_cwd(&missing_dir);
+
+        let result = transport.validate().await;
+        assert!(!result.passed(ValidationCheck::CommandFound));
+        assert!(!result.passed(ValidationCheck::WorkingDirectory));
         assert_eq!(
-            classify_stderr_line("Server listening on port 3000"),
-            LogLevel::Info
+            result
+                .get(ValidationCheck::Environment)
+                .and_then(|outcome| outcome.message.as_deref()),
+            Some("Referenced variables are not set: MCPMUX_TEST_UNSET_VAR")
         );
+        assert!(result.get(ValidationCheck::Handshake).is_none());
+        assert!(transport.process.take().is_none());
+    }
+
+    // ── stderr reader lifetime tests ───────────────────────────────
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_failed_handshake_stops_stderr_reader() {
+        use mcpmux_core::LogConfig;
+
+        let dir = std::env::temp_dir().join(format!("mcpmux-stderr-test-{}", Uuid::new_v4()));
+        let log_manager = Arc::new(ServerLogManager::new(LogConfig {
+            base_dir: dir.clone(),
+            ..LogConfig::default()
+        }));
+        // Keeps stderr open but never answers the handshake
+        let transport = StdioTransport::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "echo starting >&2; sleep 30".to_string()],
+            HashMap::new(),
+            Uuid::new_v4(),
+            "silent".to_string(),
+            Some(log_manager),
+            Duration::from_millis(300),
+            None,
+        );
+
+        let result = transport.connect().await;
+        assert!(matches!(result, TransportConnectResult::Failed(_)));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while transport.stderr_task.is_finished() != Some(true) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("stderr reader still running after failed handshake");
+
+        transport.shutdown(Duration::from_secs(1)).await;
+        let _ = std::fs::remove_dir_all(dir);
     }
 }
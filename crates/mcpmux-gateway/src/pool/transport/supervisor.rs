@@ -0,0 +1,539 @@
+//! Restart supervision for STDIO servers
+//!
+//! The MCP client talks to an in-memory pipe rather than the child's stdio
+//! directly. A supervisor task bridges that pipe to the current child process;
+//! when the child exits unexpectedly it is re-spawned with exponential backoff
+//! and the client's original `initialize` handshake is replayed, so the
+//! existing client keeps working without the pool noticing.
+//!
+//! Requests that were in flight when the child died are lost and will time
+//! out on the client side.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use mcpmux_core::{DomainEvent, LogLevel, LogSource, ServerLog, ServerLogManager};
+use tokio::io::{
+    AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream, ReadHalf, WriteHalf,
+};
+use tokio::process::{Child, ChildStdin};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::limits::ResourceLimits;
+use super::process::{ExitInfo, ProcessHandle};
+use super::stderr::StderrClassifier;
+use super::stdio::is_jsonrpc_frame;
+
+/// Buffer size of the in-memory pipe between the supervisor and the MCP client.
+const BRIDGE_BUFFER: usize = 64 * 1024;
+
+/// Restart behaviour for crashed STDIO servers.
+///
+/// HTTP transports reuse it for SSE reconnection, where `max_restarts` bounds
+/// reconnect attempts per outage and `stable_period` is unused (the count
+/// resets as soon as a stream is re-established).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Restarts allowed before giving up, counted since the process was last stable.
+    pub max_restarts: u32,
+    /// Delay before the first restart; doubled for each further attempt.
+    pub base_backoff: Duration,
+    /// Upper bound on the delay between restarts.
+    pub max_backoff: Duration,
+    /// Uptime after which the process counts as stable and the backoff resets.
+    pub stable_period: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            base_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            stable_period: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Backoff before the given (1-based) restart attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        self.base_backoff
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_backoff)
+    }
+}
+
+/// Spawns a fresh child process with piped stdin and stdout.
+pub(super) type SpawnChild = Box<dyn Fn() -> std::io::Result<Child> + Send + Sync>;
+
+/// The client's `initialize` exchange, recorded so it can be replayed.
+#[derive(Default)]
+struct Handshake {
+    /// Raw `initialize` request line and its request id.
+    initialize: Option<(Vec<u8>, serde_json::Value)>,
+    /// Raw `notifications/initialized` line.
+    initialized: Option<Vec<u8>>,
+}
+
+impl Handshake {
+    /// Record handshake messages from a client line.
+    fn observe(&mut self, line: &[u8]) {
+        if self.is_complete() {
+            return;
+        }
+        let Ok(serde_json::Value::Object(message)) = serde_json::from_slice(line) else {
+            return;
+        };
+        match message.get("method").and_then(|m| m.as_str()) {
+            Some("initialize") => {
+                if let Some(id) = message.get("id") {
+                    self.initialize = Some((line.to_vec(), id.clone()));
+                }
+            }
+            Some("notifications/initialized") => self.initialized = Some(line.to_vec()),
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.initialize.is_some() && self.initialized.is_some()
+    }
+}
+
+/// Response id of a JSON-RPC response line, if it is one.
+fn response_id(line: &[u8]) -> Option<serde_json::Value> {
+    match serde_json::from_slice(line) {
+        Ok(serde_json::Value::Object(mut message)) if !message.contains_key("method") => {
+            message.remove("id")
+        }
+        _ => None,
+    }
+}
+
+/// Why a child's session ended.
+enum SessionEnd {
+    /// The MCP client closed its end of the bridge.
+    ClientClosed,
+    /// The child process closed stdout or stdin.
+    ChildExited,
+}
+
+/// Supervises a STDIO server child process on behalf of one MCP client.
+pub(super) struct Supervisor {
+    pub(super) policy: RestartPolicy,
+    pub(super) spawn: SpawnChild,
+    pub(super) process: Arc<ProcessHandle>,
+    pub(super) capture_stdout: bool,
+    pub(super) classifier: Arc<StderrClassifier>,
+    pub(super) limits: ResourceLimits,
+    pub(super) space_id: Uuid,
+    pub(super) server_id: String,
+    pub(super) log_manager: Option<Arc<ServerLogManager>>,
+    pub(super) event_tx: Option<broadcast::Sender<DomainEvent>>,
+}
+
+impl Supervisor {
+    /// Start supervising `child`, returning the stream the MCP client should use.
+    pub(super) fn start(self, child: Child) -> DuplexStream {
+        let (client_end, supervisor_end) = tokio::io::duplex(BRIDGE_BUFFER);
+        tokio::spawn(self.run(child, supervisor_end));
+        client_end
+    }
+
+    async fn run(self, mut child: Child, bridge: DuplexStream) {
+        let (client_rx, mut client_tx) = tokio::io::split(bridge);
+        let mut client_rx = BufReader::new(client_rx);
+        let mut handshake = Handshake::default();
+        let mut attempt = 0u32;
+        let mut replay = false;
+
+        loop {
+            let started = Instant::now();
+            let end = self
+                .run_session(
+                    child,
+                    &mut client_rx,
+                    &mut client_tx,
+                    &mut handshake,
+                    replay,
+                )
+                .await;
+
+            if matches!(end, SessionEnd::ClientClosed) {
+                // The child stays in the process handle: the transport either
+                // shuts it down gracefully or drops (and kills) it.
+                debug!(server_id = %self.server_id, "Supervisor finished (client closed)");
+                return;
+            }
+            if self.process.is_shutting_down() {
+                debug!(server_id = %self.server_id, "Supervisor finished (shutting down)");
+                return;
+            }
+            let Some(mut exited) = self.process.take() else {
+                return;
+            };
+            let _ = exited.start_kill();
+            let status = exited.wait().await;
+
+            if !handshake.is_complete() {
+                // Never connected: let the client's handshake fail instead of restarting
+                debug!(server_id = %self.server_id, "Child exited before handshake completed");
+                return;
+            }
+
+            let reason = match status {
+                Ok(status) => {
+                    let exit = ExitInfo::from_status(status);
+                    let reason = exit.log_message(self.limits.exceeded(status).as_deref());
+                    self.emit(DomainEvent::ServerExited {
+                        space_id: self.space_id,
+                        server_id: self.server_id.clone(),
+                        code: exit.code,
+                        signal: exit.signal,
+                        message: exit.message,
+                    });
+                    reason
+                }
+                Err(e) => format!("Server process exited ({e})"),
+            };
+            warn!(server_id = %self.server_id, "{}", reason);
+            self.log(LogLevel::Error, reason).await;
+
+            if started.elapsed() >= self.policy.stable_period {
+                attempt = 0;
+            }
+
+            child = loop {
+                attempt += 1;
+                if attempt > self.policy.max_restarts {
+                    let message = format!(
+                        "Server did not recover after {} restart attempts, giving up",
+                        self.policy.max_restarts
+                    );
+                    warn!(server_id = %self.server_id, "{}", message);
+                    self.log(LogLevel::Error, message.clone()).await;
+                    self.emit(DomainEvent::ServerFailed {
+                        space_id: self.space_id,
+                        server_id: self.server_id.clone(),
+                        restarts: self.policy.max_restarts,
+                        message,
+                    });
+                    return;
+                }
+
+                let delay = self.policy.backoff(attempt);
+                info!(
+                    server_id = %self.server_id,
+                    attempt = attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "Restarting STDIO server"
+                );
+                self.log(
+                    LogLevel::Warn,
+                    format!(
+                        "Restarting server in {:?} (attempt {}/{})",
+                        delay, attempt, self.policy.max_restarts
+                    ),
+                )
+                .await;
+                self.emit(DomainEvent::ServerRestarting {
+                    space_id: self.space_id,
+                    server_id: self.server_id.clone(),
+                    attempt,
+                    max_restarts: self.policy.max_restarts,
+                    delay_ms: delay.as_millis() as u64,
+                });
+                tokio::time::sleep(delay).await;
+                if self.process.is_shutting_down() {
+                    return;
+                }
+
+                match (self.spawn)() {
+                    Ok(child) => break child,
+                    Err(e) => {
+                        let err = format!("Failed to restart process: {e}");
+                        warn!(server_id = %self.server_id, "{}", err);
+                        self.log(LogLevel::Error, err).await;
+                    }
+                }
+            };
+            replay = true;
+        }
+    }
+
+    /// Bridge one child process until it or the client goes away.
+    ///
+    /// With `replay` set, the recorded handshake is sent to the new child first
+    /// and its `initialize` response is swallowed; client traffic is held back
+    /// until then.
+    async fn run_session(
+        &self,
+        mut child: Child,
+        client_rx: &mut BufReader<ReadHalf<DuplexStream>>,
+        client_tx: &mut WriteHalf<DuplexStream>,
+        handshake: &mut Handshake,
+        replay: bool,
+    ) -> SessionEnd {
+        let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            self.process.set(child);
+            return SessionEnd::ChildExited;
+        };
+        let mut stdout = BufReader::new(stdout);
+        self.process.set(child);
+
+        let mut pending_init = None;
+        if replay {
+            if let Some((request, id)) = &handshake.initialize {
+                if stdin.write_all(request).await.is_err() {
+                    return SessionEnd::ChildExited;
+                }
+                pending_init = Some(id.clone());
+            }
+        }
+
+        // Buffers persist across select iterations: read_until may be cancelled
+        // mid-line and resumes appending on the next call.
+        let mut client_line = Vec::new();
+        let mut child_line = Vec::new();
+
+        loop {
+            tokio::select! {
+                read = client_rx.read_until(b'\n', &mut client_line), if pending_init.is_none() => {
+                    match read {
+                        Ok(0) | Err(_) => return SessionEnd::ClientClosed,
+                        Ok(_) => {
+                            handshake.observe(&client_line);
+                            let written = stdin.write_all(&client_line).await;
+                            client_line.clear();
+                            if written.is_err() {
+                                return SessionEnd::ChildExited;
+                            }
+                        }
+                    }
+                }
+                read = stdout.read_until(b'\n', &mut child_line) => {
+                    match read {
+                        Ok(0) | Err(_) => return SessionEnd::ChildExited,
+                        Ok(_) => {
+                            let end = self
+                                .handle_child_line(
+                                    &child_line,
+                                    &mut stdin,
+                                    client_tx,
+                                    handshake,
+                                    &mut pending_init,
+                                )
+                                .await;
+                            child_line.clear();
+                            if let Some(end) = end {
+                                return end;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Route one stdout line from the child.
+    async fn handle_child_line(
+        &self,
+        line: &[u8],
+        stdin: &mut ChildStdin,
+        client_tx: &mut (impl AsyncWrite + Unpin),
+        handshake: &Handshake,
+        pending_init: &mut Option<serde_json::Value>,
+    ) -> Option<SessionEnd> {
+        if pending_init.is_some() && response_id(line) == *pending_init {
+            *pending_init = None;
+            if let Some(initialized) = &handshake.initialized {
+                if stdin.write_all(initialized).await.is_err() {
+                    return Some(SessionEnd::ChildExited);
+                }
+            }
+            info!(server_id = %self.server_id, "STDIO server restarted");
+            self.log(LogLevel::Info, "Server restarted successfully".to_string())
+                .await;
+            self.emit(DomainEvent::ServerRecovered {
+                space_id: self.space_id,
+                server_id: self.server_id.clone(),
+            });
+            return None;
+        }
+
+        if self.capture_stdout && !is_jsonrpc_frame(line) {
+            let text = String::from_utf8_lossy(line);
+            let text = text.trim();
+            if !text.is_empty() {
+                self.append(self.classifier.log_entry(text, LogSource::Stdout))
+                    .await;
+            }
+            return None;
+        }
+
+        if client_tx.write_all(line).await.is_err() {
+            return Some(SessionEnd::ClientClosed);
+        }
+        None
+    }
+
+    async fn log(&self, level: LogLevel, message: String) {
+        self.append(ServerLog::new(level, LogSource::Connection, message))
+            .await;
+    }
+
+    async fn append(&self, log: ServerLog) {
+        if let Some(log_manager) = &self.log_manager {
+            let _ = log_manager
+                .append(&self.space_id.to_string(), &self.server_id, log)
+                .await;
+        }
+    }
+
+    fn emit(&self, event: DomainEvent) {
+        if let Some(event_tx) = &self.event_tx {
+            let _ = event_tx.send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let policy = RestartPolicy {
+            max_restarts: 10,
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            stable_period: Duration::from_secs(60),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_handshake_records_initialize_exchange() {
+        let mut handshake = Handshake::default();
+        handshake.observe(br#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#);
+        assert!(!handshake.is_complete());
+        handshake.observe(br#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#);
+        assert!(handshake.is_complete());
+        assert_eq!(
+            handshake.initialize.as_ref().map(|(_, id)| id.clone()),
+            Some(serde_json::json!(0))
+        );
+
+        // Later messages don't overwrite the recorded handshake
+        handshake.observe(br#"{"jsonrpc":"2.0","id":7,"method":"initialize","params":{}}"#);
+        assert_eq!(
+            handshake.initialize.as_ref().map(|(_, id)| id.clone()),
+            Some(serde_json::json!(0))
+        );
+    }
+
+    #[test]
+    fn test_response_id() {
+        assert_eq!(
+            response_id(br#"{"jsonrpc":"2.0","id":3,"result":{}}"#),
+            Some(serde_json::json!(3))
+        );
+        assert_eq!(
+            response_id(br#"{"jsonrpc":"2.0","id":3,"method":"ping"}"#),
+            None
+        );
+        assert_eq!(response_id(b"not json"), None);
+    }
+
+    #[cfg(unix)]
+    fn piped_child(script: &'static str) -> std::io::Result<Child> {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", script])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true);
+        cmd.spawn()
+    }
+
+    /// A fake server that completes the handshake, then crashes.
+    #[cfg(unix)]
+    const CRASHING_SERVER: &str =
+        r#"read line; echo '{"jsonrpc":"2.0","id":0,"result":{}}'; read line; exit 1"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_supervisor_restarts_then_gives_up() {
+        use tokio::io::AsyncReadExt;
+
+        let (event_tx, mut events) = broadcast::channel(16);
+        let supervisor = Supervisor {
+            policy: RestartPolicy {
+                max_restarts: 2,
+                base_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(50),
+                stable_period: Duration::from_secs(60),
+            },
+            spawn: Box::new(|| piped_child(CRASHING_SERVER)),
+            process: Arc::default(),
+            capture_stdout: false,
+            classifier: Arc::default(),
+            limits: ResourceLimits::default(),
+            space_id: Uuid::new_v4(),
+            server_id: "crashy".to_string(),
+            log_manager: None,
+            event_tx: Some(event_tx),
+        };
+
+        let mut client = supervisor.start(piped_child(CRASHING_SERVER).unwrap());
+        client
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":0,\"method\":\"initialize\",\"params\":{}}\n")
+            .await
+            .unwrap();
+        client
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n")
+            .await
+            .unwrap();
+
+        // Only the original initialize response reaches the client; replayed
+        // responses are swallowed, and the bridge closes once the supervisor gives up.
+        let mut received = String::new();
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            client.read_to_string(&mut received),
+        )
+        .await
+        .expect("supervisor did not give up")
+        .unwrap();
+        assert_eq!(received, "{\"jsonrpc\":\"2.0\",\"id\":0,\"result\":{}}\n");
+
+        let mut attempts = Vec::new();
+        let mut exits = Vec::new();
+        let mut recoveries = 0;
+        loop {
+            match events.recv().await.unwrap() {
+                DomainEvent::ServerRestarting { attempt, .. } => attempts.push(attempt),
+                DomainEvent::ServerExited { code, .. } => exits.push(code),
+                DomainEvent::ServerRecovered { .. } => recoveries += 1,
+                DomainEvent::ServerFailed { restarts, .. } => {
+                    assert_eq!(restarts, 2);
+                    break;
+                }
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+        assert_eq!(attempts, vec![1, 2]);
+        assert_eq!(exits, vec![Some(1); 3]);
+        // Each restarted child completes the replayed handshake before crashing
+        assert_eq!(recoveries, 2);
+    }
+}
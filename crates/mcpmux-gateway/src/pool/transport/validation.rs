@@ -0,0 +1,137 @@
+//! Dry-run validation of server configs
+//!
+//! [`Transport::validate`](super::Transport::validate) checks a config the
+//! way connecting would, but leaves nothing running afterwards, and reports
+//! each step on its own so a "Test connection" button can say which part of
+//! the config is wrong rather than just that something is.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::{Transport, TransportConnectResult};
+
+/// How long a server started for validation gets to exit cleanly.
+pub(super) const VALIDATE_SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+/// One step [`Transport::validate`](super::Transport::validate) can check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationCheck {
+    /// STDIO: the command resolves to an executable.
+    CommandFound,
+    /// STDIO: the working directory exists.
+    WorkingDirectory,
+    /// STDIO: every variable referenced in args and env is set, and the
+    /// resource limits are valid.
+    Environment,
+    /// HTTP: the URL is valid and the server answers at it.
+    Reachable,
+    /// The server accepts our credentials, or needs none.
+    Auth,
+    /// The MCP handshake completed.
+    Handshake,
+}
+
+/// Outcome of one [`ValidationCheck`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckOutcome {
+    pub check: ValidationCheck,
+    pub passed: bool,
+    /// Why the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Step-by-step result of validating a server config.
+///
+/// Checks appear in the order they ran. Steps that depend on an earlier
+/// failed one aren't attempted, so they are missing rather than failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationResult {
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl ValidationResult {
+    /// Record `check` as passed.
+    pub fn pass(&mut self, check: ValidationCheck) {
+        self.checks.push(CheckOutcome {
+            check,
+            passed: true,
+            message: None,
+        });
+    }
+
+    /// Record `check` as failed because of `message`.
+    pub fn fail(&mut self, check: ValidationCheck, message: impl Into<String>) {
+        self.checks.push(CheckOutcome {
+            check,
+            passed: false,
+            message: Some(message.into()),
+        });
+    }
+
+    /// The outcome of `check`, if it ran.
+    pub fn get(&self, check: ValidationCheck) -> Option<&CheckOutcome> {
+        self.checks.iter().find(|outcome| outcome.check == check)
+    }
+
+    /// Whether `check` ran and passed.
+    pub fn passed(&self, check: ValidationCheck) -> bool {
+        self.get(check).is_some_and(|outcome| outcome.passed)
+    }
+
+    /// Whether every check that ran passed.
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|outcome| outcome.passed)
+    }
+}
+
+/// Connect with `transport`, record how the handshake went, and shut down
+/// whatever the connect started.
+pub(super) async fn check_handshake<T: Transport + ?Sized>(
+    transport: &T,
+    result: &mut ValidationResult,
+) {
+    match transport.connect().await {
+        TransportConnectResult::Connected(client) => {
+            result.pass(ValidationCheck::Handshake);
+            drop(client);
+        }
+        TransportConnectResult::OAuthRequired { .. } => {
+            result.fail(ValidationCheck::Auth, "Server requires OAuth authorization");
+        }
+        TransportConnectResult::Failed(err) => {
+            result.fail(ValidationCheck::Handshake, err);
+        }
+    }
+    transport.shutdown(VALIDATE_SHUTDOWN_GRACE).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_reports_each_check() {
+        let mut result = ValidationResult::default();
+        assert!(result.is_ok());
+
+        result.pass(ValidationCheck::CommandFound);
+        result.fail(ValidationCheck::Handshake, "timed out");
+
+        assert!(result.passed(ValidationCheck::CommandFound));
+        assert!(!result.passed(ValidationCheck::Handshake));
+        assert!(!result.passed(ValidationCheck::Auth));
+        assert!(result.get(ValidationCheck::Auth).is_none());
+        assert_eq!(
+            result
+                .get(ValidationCheck::Handshake)
+                .unwrap()
+                .message
+                .as_deref(),
+            Some("timed out")
+        );
+        assert!(!result.is_ok());
+    }
+}
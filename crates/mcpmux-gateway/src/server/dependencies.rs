@@ -6,14 +6,14 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use crate::pool::CommandPolicy;
 use crate::services::ClientMetadataService;
 use mcpmux_core::{
-    AppSettingsRepository, CimdMetadataFetcher, CredentialRepository, FeatureSetRepository,
-    InstalledServerRepository, OutboundOAuthRepository, ServerDiscoveryService,
-    ServerFeatureRepository, ServerLogManager, SpaceRepository,
+    AppSettingsRepository, AuditRepository, CimdMetadataFetcher, CredentialRepository,
+    FeatureSetRepository, InstalledServerRepository, OutboundOAuthRepository,
+    ServerDiscoveryService, ServerFeatureRepository, ServerLogManager, SpaceRepository,
 };
-use mcpmux_storage::{Database, InboundClientRepository};
-use tokio::sync::Mutex;
+use mcpmux_storage::{DbHandle, InboundClientRepository};
 
 /// Dependency container for Gateway
 ///
@@ -37,14 +37,20 @@ pub struct GatewayDependencies {
     pub client_metadata_service: Arc<ClientMetadataService>,
 
     // Database (for Gateway state persistence)
-    pub database: Arc<Mutex<Database>>,
+    pub database: DbHandle,
 
     // JWT signing secret (optional, for token issuance)
     pub jwt_secret: Option<zeroize::Zeroizing<[u8; mcpmux_storage::JWT_SECRET_SIZE]>>,
+    /// JWT signing secret retired by the last rotation (optional, for verification)
+    pub previous_jwt_secret: Option<mcpmux_storage::PreviousJwtSecret>,
     /// Base directory for transport state (optional)
     pub state_dir: Option<PathBuf>,
     /// App settings repository (for OAuth port persistence)
     pub settings_repo: Option<Arc<dyn AppSettingsRepository>>,
+    /// Restricts which commands STDIO servers may run (optional)
+    pub command_policy: Option<Arc<CommandPolicy>>,
+    /// Audit log for refused commands (optional)
+    pub audit_repo: Option<Arc<dyn AuditRepository>>,
 }
 
 impl GatewayDependencies {
@@ -62,7 +68,7 @@ impl GatewayDependencies {
         log_manager: Arc<ServerLogManager>,
         cimd_fetcher: Arc<CimdMetadataFetcher>,
         client_metadata_service: Arc<ClientMetadataService>,
-        database: Arc<Mutex<Database>>,
+        database: impl Into<DbHandle>,
         jwt_secret: Option<zeroize::Zeroizing<[u8; mcpmux_storage::JWT_SECRET_SIZE]>>,
         state_dir: Option<PathBuf>,
     ) -> Self {
@@ -78,10 +84,13 @@ impl GatewayDependencies {
             log_manager,
             cimd_fetcher,
             client_metadata_service,
-            database,
+            database: database.into(),
             jwt_secret,
+            previous_jwt_secret: None, // Use builder for this
             state_dir,
-            settings_repo: None, // Use builder for this
+            settings_repo: None,  // Use builder for this
+            command_policy: None, // Use builder for this
+            audit_repo: None,     // Use builder for this
         }
     }
 }
@@ -99,10 +108,13 @@ pub struct DependenciesBuilder {
     log_manager: Option<Arc<ServerLogManager>>,
     cimd_fetcher: Option<Arc<CimdMetadataFetcher>>,
     client_metadata_service: Option<Arc<ClientMetadataService>>,
-    database: Option<Arc<Mutex<Database>>>,
+    database: Option<DbHandle>,
     jwt_secret: Option<zeroize::Zeroizing<[u8; mcpmux_storage::JWT_SECRET_SIZE]>>,
+    previous_jwt_secret: Option<mcpmux_storage::PreviousJwtSecret>,
     state_dir: Option<PathBuf>,
     settings_repo: Option<Arc<dyn AppSettingsRepository>>,
+    command_policy: Option<Arc<CommandPolicy>>,
+    audit_repo: Option<Arc<dyn AuditRepository>>,
 }
 
 impl DependenciesBuilder {
@@ -121,8 +133,11 @@ impl DependenciesBuilder {
             client_metadata_service: None,
             database: None,
             jwt_secret: None,
+            previous_jwt_secret: None,
             state_dir: None,
             settings_repo: None,
+            command_policy: None,
+            audit_repo: None,
         }
     }
 
@@ -146,6 +161,13 @@ impl DependenciesBuilder {
         self
     }
 
+    /// Use `repo` for spaces instead of an unencrypted one on the database,
+    /// e.g. one that can read sensitive space env.
+    pub fn with_space_repo(mut self, repo: Arc<dyn SpaceRepository>) -> Self {
+        self.space_repo = Some(repo);
+        self
+    }
+
     pub fn with_feature_set_repo(mut self, repo: Arc<dyn FeatureSetRepository>) -> Self {
         self.feature_set_repo = Some(repo);
         self
@@ -161,8 +183,10 @@ impl DependenciesBuilder {
         self
     }
 
-    pub fn with_database(mut self, db: Arc<Mutex<Database>>) -> Self {
-        self.database = Some(db);
+    /// Database for repositories created here: a [`mcpmux_storage::DbPool`] or
+    /// a shared `Arc<Mutex<Database>>`.
+    pub fn with_database(mut self, db: impl Into<DbHandle>) -> Self {
+        self.database = Some(db.into());
         self
     }
 
@@ -174,6 +198,11 @@ impl DependenciesBuilder {
         self
     }
 
+    pub fn with_previous_jwt_secret(mut self, previous: mcpmux_storage::PreviousJwtSecret) -> Self {
+        self.previous_jwt_secret = Some(previous);
+        self
+    }
+
     pub fn with_state_dir(mut self, state_dir: PathBuf) -> Self {
         self.state_dir = Some(state_dir);
         self
@@ -184,6 +213,16 @@ impl DependenciesBuilder {
         self
     }
 
+    pub fn with_command_policy(mut self, policy: Arc<CommandPolicy>) -> Self {
+        self.command_policy = Some(policy);
+        self
+    }
+
+    pub fn with_audit_repo(mut self, repo: Arc<dyn AuditRepository>) -> Self {
+        self.audit_repo = Some(repo);
+        self
+    }
+
     pub fn build(self) -> Result<GatewayDependencies, String> {
         let database = self.database.ok_or("database is required")?;
 
@@ -236,8 +275,11 @@ impl DependenciesBuilder {
             client_metadata_service,
             database,
             jwt_secret: self.jwt_secret,
+            previous_jwt_secret: self.previous_jwt_secret,
             state_dir: self.state_dir,
             settings_repo: self.settings_repo,
+            command_policy: self.command_policy,
+            audit_repo: self.audit_repo,
         })
     }
 }
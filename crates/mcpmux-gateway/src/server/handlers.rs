@@ -656,7 +656,7 @@ pub async fn oauth_token(
             };
 
             // Validate the refresh token
-            let Some(claims) = crate::auth::validate_token(refresh_token, secret) else {
+            let Some(claims) = gateway_state.validate_token(refresh_token) else {
                 warn!("[OAuth] Invalid or expired refresh token");
                 return Err(token_error(
                     "invalid_grant",
@@ -103,6 +103,9 @@ impl GatewayServer {
         if let Some(jwt_secret) = dependencies.jwt_secret.clone() {
             state.set_jwt_secret(jwt_secret);
         }
+        if let Some(previous) = dependencies.previous_jwt_secret.clone() {
+            state.set_previous_jwt_secret(previous);
+        }
         let state = Arc::new(RwLock::new(state));
 
         // Set database and services in state (needs async, so we block here)
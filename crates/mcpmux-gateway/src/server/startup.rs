@@ -249,10 +249,20 @@ impl StartupOrchestrator {
             return Ok(ConnectOutcome::NeedsOAuth);
         }
 
+        // Servers inherit their space's env
+        let space_env = self
+            .dependencies
+            .space_repo
+            .get(&space_id)
+            .await?
+            .map(|space| space.space_env)
+            .unwrap_or_default();
+
         // Build transport config using cached definition
         let transport_config = crate::pool::transport::resolution::build_transport_config(
             &definition.transport,
             server,
+            &space_env,
             self.dependencies.state_dir.as_deref(),
         );
 
@@ -285,7 +295,7 @@ impl StartupOrchestrator {
                 self.server_manager.set_auth_required(&key, None).await;
                 Ok(ConnectOutcome::NeedsOAuth)
             }
-            ConnectionResult::Failed { error } => {
+            ConnectionResult::Failed { error } | ConnectionResult::CircuitOpen { error, .. } => {
                 // Explicitly set status to Error
                 self.server_manager.set_error(&key, error.clone()).await;
                 Err(anyhow::anyhow!("Connection failed: {}", error))
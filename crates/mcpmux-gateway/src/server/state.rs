@@ -8,15 +8,15 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tracing::{debug, info};
 use uuid::Uuid;
 use zeroize::Zeroizing;
 
 use super::handlers::PendingAuthorization;
+use crate::auth::TokenClaims;
 use crate::services::ClientMetadataService;
 use mcpmux_core::DomainEvent;
-use mcpmux_storage::{Database, InboundClientRepository, JWT_SECRET_SIZE};
+use mcpmux_storage::{DbHandle, InboundClientRepository, PreviousJwtSecret, JWT_SECRET_SIZE};
 use tokio::sync::broadcast;
 
 /// Client session in the gateway
@@ -55,8 +55,10 @@ pub struct GatewayState {
     pub clients_with_tokens: std::collections::HashSet<String>,
     /// JWT signing secret (for issuing access tokens)
     pub jwt_signing_secret: Option<Zeroizing<[u8; JWT_SECRET_SIZE]>>,
+    /// JWT signing secret retired by the last rotation (verification only)
+    pub previous_jwt_secret: Option<PreviousJwtSecret>,
     /// Database connection (for persistent OAuth storage)
-    db: Option<Arc<Mutex<Database>>>,
+    db: Option<DbHandle>,
     /// Inbound client repository (OAuth + MCP client unified storage)
     inbound_client_repository: Option<InboundClientRepository>,
     /// Client metadata service (CIMD + DCR resolution)
@@ -76,6 +78,7 @@ impl GatewayState {
             pending_authorizations: HashMap::new(),
             clients_with_tokens: std::collections::HashSet::new(),
             jwt_signing_secret: None,
+            previous_jwt_secret: None,
             db: None,
             inbound_client_repository: None,
             client_metadata_service: None,
@@ -107,7 +110,8 @@ impl GatewayState {
     }
 
     /// Set the database connection and create OAuth repository
-    pub fn set_database(&mut self, db: Arc<Mutex<Database>>) {
+    pub fn set_database(&mut self, db: impl Into<DbHandle>) {
+        let db = db.into();
         info!("[State] Database connection configured for OAuth persistence");
         self.inbound_client_repository = Some(InboundClientRepository::new(db.clone()));
         self.db = Some(db);
@@ -150,6 +154,32 @@ impl GatewayState {
         self.jwt_signing_secret.is_some()
     }
 
+    /// Keep accepting tokens signed with a rotated-out secret until it expires
+    pub fn set_previous_jwt_secret(&mut self, previous: PreviousJwtSecret) {
+        info!(
+            "[State] Previous JWT signing secret accepted until {}",
+            previous.expires_at
+        );
+        self.previous_jwt_secret = Some(previous);
+    }
+
+    /// Validate a token against the signing secret, falling back to the previous
+    /// secret while its grace period lasts
+    pub fn validate_token(&self, token: &str) -> Option<TokenClaims> {
+        let secret = self.get_jwt_secret()?;
+        if let Some(claims) = crate::auth::validate_token(token, secret) {
+            return Some(claims);
+        }
+
+        let previous = self
+            .previous_jwt_secret
+            .as_ref()
+            .filter(|previous| previous.expires_at > chrono::Utc::now())?;
+        let claims = crate::auth::validate_token(token, &*previous.secret)?;
+        debug!("[State] Token verified with previous JWT signing secret");
+        Some(claims)
+    }
+
     /// Store a pending authorization (for code -> token exchange)
     pub fn store_pending_authorization(&mut self, code: &str, auth: PendingAuthorization) {
         debug!(
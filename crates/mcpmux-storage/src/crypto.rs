@@ -1,89 +1,823 @@
 //! Field-level encryption for sensitive data.
 //!
-//! Uses AES-256-GCM for authenticated encryption of sensitive fields
-//! like credentials and tokens before storing in the database.
+//! Uses AES-256-GCM (or optionally ChaCha20-Poly1305, see [`CipherSuite`]) for
+//! authenticated encryption of sensitive fields like credentials and tokens
+//! before storing in the database.
+//!
+//! ## Ciphertext format
+//!
+//! Ciphertexts are hex-encoded and start with a small header so the format can
+//! evolve without breaking existing data. New data uses envelope encryption
+//! (version 2): every call generates a fresh random data key, seals the payload
+//! with it, and stores the data key wrapped under the master key:
+//!
+//! ```text
+//! [version: 1 byte][algorithm: 1 byte]
+//! [wrap nonce: 12 bytes][wrapped data key + tag: 48 bytes]
+//! [nonce: 12 bytes][ciphertext + tag]
+//! ```
+//!
+//! The master key only ever encrypts 32-byte data keys, and rotating it only
+//! needs the data keys rewrapped (see [`FieldEncryptor::rewrap`]).
+//!
+//! The algorithm byte records the [`CipherSuite`], so databases containing a mix
+//! of suites decrypt correctly. Its high bit is set when the wrap nonce came
+//! from a counter rather than the RNG (see [`NonceStrategy`]); decryption
+//! doesn't depend on it, but it shows which data a strategy wrote. Older
+//! formats still decrypt:
+//!
+//! - version 1: `[version][algorithm][nonce][ciphertext + tag]` sealed directly
+//!   under the master key.
+//! - version 0: `nonce + ciphertext + tag` (always AES-256-GCM), written before
+//!   the header existed.
+//!
+//! Fields written by [`FieldEncryptor::plaintext_unsafe`] (development only,
+//! behind the `dev-unsafe-crypto` feature) are `[0xff][plaintext]` and are
+//! refused by every other encryptor.
+//!
+//! ## Stream format
+//!
+//! Payloads too large to hold in memory twice go through
+//! [`FieldEncryptor::encrypt_stream`], which writes raw bytes rather than hex:
+//!
+//! ```text
+//! [version 3: 1 byte][algorithm: 1 byte]
+//! [wrap nonce: 12 bytes][wrapped data key + tag: 48 bytes][base nonce: 12 bytes]
+//! [chunk 0 ciphertext + tag][chunk 1 ciphertext + tag]...
+//! ```
+//!
+//! Every chunk holds [`STREAM_CHUNK_SIZE`] bytes of plaintext except the last,
+//! which is shorter (and empty if the plaintext fills the chunks exactly).
+//! Chunk nonces are the base nonce with the chunk counter and a final-chunk
+//! flag mixed in, so chunks can't be reordered, and a stream cut off at a
+//! chunk boundary is detected because no chunk was sealed as the last one.
+
+use std::fmt;
+use std::io::{Read, Write};
 
 use anyhow::{Context, Result};
-use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
+use argon2::{Argon2, Params, Version};
+use rand_core::RngCore;
+use ring::aead::{Aad, Algorithm, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, CHACHA20_POLY1305};
+use ring::hmac;
 use ring::rand::{SecureRandom, SystemRandom};
+use zeroize::Zeroizing;
+
+use crate::nonce::{NonceCounter, NonceStrategy};
 
 /// Size of the encryption key (32 bytes = 256 bits).
 pub const KEY_SIZE: usize = 32;
 
-/// Size of the nonce (12 bytes for AES-GCM).
+/// Argon2id cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB.
+    pub memory_kib: u32,
+    /// Number of passes over memory.
+    pub iterations: u32,
+    /// Degree of parallelism (lanes).
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB, 3 iterations, 1 lane — above the OWASP minimum for Argon2id.
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Size of the nonce (12 bytes for both AES-GCM and ChaCha20-Poly1305).
 const NONCE_SIZE: usize = 12;
 
+/// Size of the AEAD authentication tag (16 bytes for both suites).
+const TAG_SIZE: usize = 16;
+
+/// Ciphertext format with the payload sealed directly under the master key.
+const CIPHERTEXT_VERSION_1: u8 = 1;
+
+/// Envelope format written by [`FieldEncryptor::encrypt`].
+const CIPHERTEXT_VERSION_2: u8 = 2;
+
+/// Marks a field stored unencrypted by [`FieldEncryptor::plaintext_unsafe`].
+const PLAINTEXT_VERSION: u8 = 0xff;
+
+/// Size of a wrapped data key (nonce + key + tag).
+const WRAPPED_KEY_SIZE: usize = NONCE_SIZE + KEY_SIZE + TAG_SIZE;
+
+/// Size of a key fingerprint (truncated HMAC-SHA256).
+pub const FINGERPRINT_SIZE: usize = 8;
+
+/// HMAC message used to derive key fingerprints.
+const FINGERPRINT_CONTEXT: &[u8] = b"mcpmux-key-fingerprint-v1";
+
+/// Size of the versioned header (version byte + algorithm byte).
+const HEADER_SIZE: usize = 2;
+
+/// Algorithm byte flag for a wrap nonce from [`NonceStrategy::Counter`].
+const COUNTER_NONCE_FLAG: u8 = 0x80;
+
+/// Format written by [`FieldEncryptor::encrypt_stream`].
+const STREAM_VERSION: u8 = 3;
+
+/// Plaintext bytes per chunk of an encrypted stream.
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// AEAD cipher suite used for new ciphertexts.
+///
+/// Both suites use 256-bit keys, so the same master key works for either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CipherSuite {
+    /// AES-256-GCM (default; fastest on CPUs with AES-NI).
+    #[default]
+    Aes256Gcm,
+    /// ChaCha20-Poly1305 (faster on CPUs without AES acceleration).
+    ChaCha20Poly1305,
+}
+
+impl CipherSuite {
+    /// Algorithm id recorded in the ciphertext header.
+    pub fn id(self) -> u8 {
+        match self {
+            CipherSuite::Aes256Gcm => 1,
+            CipherSuite::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    /// Look up a suite by its ciphertext header id.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(CipherSuite::Aes256Gcm),
+            2 => Some(CipherSuite::ChaCha20Poly1305),
+            _ => None,
+        }
+    }
+
+    fn algorithm(self) -> &'static Algorithm {
+        match self {
+            CipherSuite::Aes256Gcm => &AES_256_GCM,
+            CipherSuite::ChaCha20Poly1305 => &CHACHA20_POLY1305,
+        }
+    }
+}
+
+/// Errors specific to the ciphertext format.
+///
+/// Returned wrapped in `anyhow::Error`; use `downcast_ref::<CryptoError>()` to match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    /// The ciphertext header carries a version this build cannot read.
+    UnsupportedCiphertextVersion(u8),
+    /// The ciphertext header names an unknown cipher algorithm.
+    UnsupportedAlgorithm(u8),
+    /// The authentication tag did not verify (wrong key, mismatched associated
+    /// data, or corrupted ciphertext).
+    AuthenticationFailed,
+    /// The master key does not match the one this database was encrypted with.
+    KeyMismatch,
+    /// The master key was created by a different install than the database,
+    /// e.g. a key file copied over from another machine.
+    KeyBelongsToDifferentInstall {
+        key_install_id: String,
+        database_install_id: String,
+    },
+    /// The field was stored unencrypted by a development build and a real
+    /// encryptor refuses to read it.
+    UnencryptedField,
+    /// An encrypted stream ended before its final chunk.
+    TruncatedStream,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::UnsupportedCiphertextVersion(version) => {
+                write!(f, "Unsupported ciphertext version: {}", version)
+            }
+            CryptoError::UnsupportedAlgorithm(algorithm) => {
+                write!(f, "Unsupported ciphertext algorithm: {}", algorithm)
+            }
+            CryptoError::AuthenticationFailed => {
+                write!(f, "Decryption failed - wrong key or corrupted data")
+            }
+            CryptoError::KeyMismatch => {
+                write!(f, "Encryption key does not match this database")
+            }
+            CryptoError::KeyBelongsToDifferentInstall {
+                key_install_id,
+                database_install_id,
+            } => write!(
+                f,
+                "Encryption key belongs to install {} but this database belongs to install {}",
+                key_install_id, database_install_id
+            ),
+            CryptoError::UnencryptedField => write!(
+                f,
+                "Field is stored unencrypted (written with dev-unsafe-crypto)"
+            ),
+            CryptoError::TruncatedStream => write!(f, "Encrypted stream is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
 /// Encryptor for sensitive field data.
+///
+/// New data is encrypted with the configured [`CipherSuite`]; existing data is
+/// decrypted with whichever suite its header names.
 pub struct FieldEncryptor {
-    key: LessSafeKey,
+    aes_key: LessSafeKey,
+    chacha_key: LessSafeKey,
+    suite: CipherSuite,
+    fingerprint: [u8; FINGERPRINT_SIZE],
     rng: SystemRandom,
+    /// Pass fields through unencrypted (see [`Self::plaintext_unsafe`]).
+    plaintext: bool,
+    /// Source of master key nonces under [`NonceStrategy::Counter`].
+    counter: Option<NonceCounter>,
 }
 
 impl FieldEncryptor {
-    /// Create a new encryptor with the given master key.
+    /// Create a new AES-256-GCM encryptor with the given master key.
     ///
     /// The key must be exactly 32 bytes (256 bits).
     pub fn new(master_key: &[u8; KEY_SIZE]) -> Result<Self> {
-        let unbound_key = UnboundKey::new(&AES_256_GCM, master_key)
-            .map_err(|_| anyhow::anyhow!("Failed to create encryption key"))?;
-        let key = LessSafeKey::new(unbound_key);
-        let rng = SystemRandom::new();
+        Self::with_suite(master_key, CipherSuite::default())
+    }
+
+    /// Create a new encryptor that encrypts with the given cipher suite.
+    pub fn with_suite(master_key: &[u8; KEY_SIZE], suite: CipherSuite) -> Result<Self> {
+        let make_key = |algorithm: &'static Algorithm| {
+            UnboundKey::new(algorithm, master_key)
+                .map(LessSafeKey::new)
+                .map_err(|_| anyhow::anyhow!("Failed to create encryption key"))
+        };
+
+        Ok(Self {
+            aes_key: make_key(&AES_256_GCM)?,
+            chacha_key: make_key(&CHACHA20_POLY1305)?,
+            suite,
+            fingerprint: key_fingerprint(master_key),
+            rng: SystemRandom::new(),
+            plaintext: false,
+            counter: None,
+        })
+    }
+
+    /// Use `strategy` for nonces under the master key.
+    ///
+    /// See the [`nonce`](crate::nonce) module for the tradeoffs. Data written
+    /// with either strategy decrypts with any encryptor for the same key.
+    pub fn with_nonce_strategy(mut self, strategy: NonceStrategy) -> Result<Self> {
+        self.counter = match strategy {
+            NonceStrategy::Random => None,
+            NonceStrategy::Counter(store) => {
+                Some(NonceCounter::new(store, self.fingerprint, &self.rng)?)
+            }
+        };
+        Ok(self)
+    }
+
+    /// The nonce strategy for the master key.
+    pub fn nonce_strategy(&self) -> NonceStrategy {
+        match &self.counter {
+            Some(counter) => NonceStrategy::Counter(counter.store()),
+            None => NonceStrategy::Random,
+        }
+    }
+
+    /// Create an encryptor that stores fields **unencrypted**.
+    ///
+    /// For local development and tests only, so the database can be inspected
+    /// without a keychain. Fields are marked with a distinct version byte so
+    /// they never pass for ciphertext: real encryptors refuse them with
+    /// [`CryptoError::UnencryptedField`], and this one refuses real
+    /// ciphertext. Associated data is ignored.
+    #[cfg(feature = "dev-unsafe-crypto")]
+    pub fn plaintext_unsafe() -> Result<Self> {
+        tracing::warn!(
+            "!!! FieldEncryptor::plaintext_unsafe: credentials and tokens are stored \
+             UNENCRYPTED. Never use this build with real data. !!!"
+        );
+        Ok(Self {
+            plaintext: true,
+            ..Self::new(&[0u8; KEY_SIZE])?
+        })
+    }
+
+    /// Whether this encryptor stores fields unencrypted.
+    pub fn is_plaintext(&self) -> bool {
+        self.plaintext
+    }
+
+    /// Fingerprint of the master key, safe to store alongside the data.
+    ///
+    /// Lets callers detect a wrong key up front (see
+    /// `Database::verify_key_fingerprint`) instead of failing per ciphertext.
+    pub fn key_fingerprint(&self) -> [u8; FINGERPRINT_SIZE] {
+        self.fingerprint
+    }
+
+    /// The cipher suite used for new ciphertexts.
+    pub fn suite(&self) -> CipherSuite {
+        self.suite
+    }
+
+    /// Header algorithm byte for new data: the suite id plus the nonce flag.
+    fn algorithm_byte(&self, suite: CipherSuite) -> u8 {
+        match self.counter {
+            Some(_) => suite.id() | COUNTER_NONCE_FLAG,
+            None => suite.id(),
+        }
+    }
 
-        Ok(Self { key, rng })
+    fn key_for(&self, suite: CipherSuite) -> &LessSafeKey {
+        match suite {
+            CipherSuite::Aes256Gcm => &self.aes_key,
+            CipherSuite::ChaCha20Poly1305 => &self.chacha_key,
+        }
     }
 
     /// Encrypt a plaintext string.
     ///
-    /// Returns the ciphertext as a hex-encoded string (nonce + ciphertext + tag).
+    /// Returns the ciphertext as a hex-encoded envelope
+    /// (version + algorithm + wrapped data key + nonce + ciphertext + tag).
     pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        self.encrypt_with_aad(plaintext.as_bytes(), &[])
+    }
+
+    /// Encrypt bytes bound to associated data.
+    ///
+    /// The same `aad` must be passed to [`decrypt_with_aad`](Self::decrypt_with_aad);
+    /// it is authenticated but not stored in the ciphertext.
+    pub fn encrypt_with_aad(&self, plaintext: &[u8], aad: &[u8]) -> Result<String> {
+        self.encrypt_envelope(plaintext, aad, &mut |buf: &mut [u8]| {
+            self.rng
+                .fill(buf)
+                .map_err(|_| anyhow::anyhow!("Failed to generate random bytes"))
+        })
+    }
+
+    /// Encrypt bytes bound to associated data, drawing the data key and
+    /// nonces from `rng` instead of the OS.
+    ///
+    /// Test-only, so tests can seed a known RNG and assert exact ciphertext:
+    /// a nonce repeated under the same key breaks AES-GCM. Under
+    /// [`NonceStrategy::Counter`] the wrap nonce still comes from the counter.
+    #[cfg(test)]
+    pub(crate) fn encrypt_with_rng(
+        &self,
+        plaintext: &[u8],
+        aad: &[u8],
+        rng: &mut impl RngCore,
+    ) -> Result<String> {
+        self.encrypt_envelope(plaintext, aad, &mut |buf: &mut [u8]| {
+            rng.try_fill_bytes(buf)
+                .map_err(|e| anyhow::anyhow!("Failed to generate random bytes: {}", e))
+        })
+    }
+
+    /// Build a version 2 envelope, filling the data key and random nonces
+    /// with `fill`.
+    fn encrypt_envelope(
+        &self,
+        plaintext: &[u8],
+        aad: &[u8],
+        fill: &mut dyn FnMut(&mut [u8]) -> Result<()>,
+    ) -> Result<String> {
+        if self.plaintext {
+            let mut result = vec![PLAINTEXT_VERSION];
+            result.extend_from_slice(plaintext);
+            return Ok(hex::encode(result));
+        }
+
+        let header = [CIPHERTEXT_VERSION_2, self.algorithm_byte(self.suite)];
+
+        // Fresh data key per record; only its wrapped form is stored
+        let mut dek = Zeroizing::new([0u8; KEY_SIZE]);
+        fill(&mut dek[..]).context("Failed to generate data key")?;
+        let wrap_nonce = match &self.counter {
+            Some(counter) => counter.next()?,
+            None => random_nonce(fill)?,
+        };
+
+        // The header is authenticated with the wrapped key so it can't be swapped
+        let wrapped = self.seal_with_nonce(self.key_for(self.suite), wrap_nonce, &*dek, &header)?;
+        let payload = self.seal_with_nonce(
+            &data_key(self.suite, &dek)?,
+            random_nonce(fill)?,
+            plaintext,
+            aad,
+        )?;
+
+        let mut result = header.to_vec();
+        result.extend_from_slice(&wrapped);
+        result.extend_from_slice(&payload);
+
+        Ok(hex::encode(result))
+    }
+
+    /// Seal a data key under the master key, with a nonce from the configured
+    /// strategy: `nonce + wrapped key + tag`.
+    fn wrap(&self, suite: CipherSuite, dek: &[u8; KEY_SIZE], header: &[u8]) -> Result<Vec<u8>> {
+        match &self.counter {
+            Some(counter) => {
+                self.seal_with_nonce(self.key_for(suite), counter.next()?, dek, header)
+            }
+            None => self.seal(self.key_for(suite), dek, header),
+        }
+    }
+
+    /// Seal `plaintext` under `key` with a fresh nonce: `nonce + ciphertext + tag`.
+    fn seal(&self, key: &LessSafeKey, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
         let mut nonce_bytes = [0u8; NONCE_SIZE];
         self.rng
             .fill(&mut nonce_bytes)
             .map_err(|_| anyhow::anyhow!("Failed to generate nonce"))?;
+        self.seal_with_nonce(key, nonce_bytes, plaintext, aad)
+    }
 
+    /// Seal `plaintext` under `key` with the given nonce.
+    fn seal_with_nonce(
+        &self,
+        key: &LessSafeKey,
+        nonce_bytes: [u8; NONCE_SIZE],
+        plaintext: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>> {
         let nonce = Nonce::assume_unique_for_key(nonce_bytes);
 
         // Encrypt in-place
-        let mut in_out = plaintext.as_bytes().to_vec();
-        self.key
-            .seal_in_place_append_tag(nonce, Aad::empty(), &mut in_out)
+        let mut in_out = plaintext.to_vec();
+        key.seal_in_place_append_tag(nonce, Aad::from(aad), &mut in_out)
             .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
 
         // Prepend nonce to ciphertext
-        let mut result = nonce_bytes.to_vec();
-        result.extend_from_slice(&in_out);
-
-        Ok(hex::encode(result))
+        let mut sealed = nonce_bytes.to_vec();
+        sealed.extend_from_slice(&in_out);
+        Ok(sealed)
     }
 
     /// Decrypt a hex-encoded ciphertext string.
     ///
-    /// Expects format: hex(nonce + ciphertext + tag)
-    pub fn decrypt(&self, ciphertext_hex: &str) -> Result<String> {
+    /// Dispatches on the version header. Blobs without a header (version 0) are
+    /// recognised by falling back to the legacy `nonce + ciphertext + tag` layout,
+    /// so an unknown tag on a blob long enough to be legacy fails with
+    /// [`CryptoError::AuthenticationFailed`]. The plaintext is wiped when the
+    /// returned buffer is dropped.
+    pub fn decrypt(&self, ciphertext_hex: &str) -> Result<Zeroizing<String>> {
+        into_utf8(self.decrypt_with_aad(ciphertext_hex, &[])?)
+    }
+
+    /// Decrypt a hex-encoded ciphertext that was bound to associated data.
+    ///
+    /// Fails with [`CryptoError::AuthenticationFailed`] if `aad` differs from the
+    /// value used at encryption time.
+    pub fn decrypt_with_aad(&self, ciphertext_hex: &str, aad: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
         let ciphertext = hex::decode(ciphertext_hex).context("Invalid hex encoding")?;
 
-        if ciphertext.len() < NONCE_SIZE + AES_256_GCM.tag_len() {
+        if self.plaintext {
+            return match ciphertext.split_first() {
+                Some((&PLAINTEXT_VERSION, plaintext)) => Ok(Zeroizing::new(plaintext.to_vec())),
+                _ => anyhow::bail!("Plaintext encryptor cannot decrypt encrypted fields"),
+            };
+        }
+
+        let versioned = match ciphertext.first() {
+            Some(&CIPHERTEXT_VERSION_1) => Some(self.open_v1(&ciphertext, aad)),
+            Some(&CIPHERTEXT_VERSION_2) => Some(self.open_v2(&ciphertext, aad)),
+            Some(&PLAINTEXT_VERSION) => Some(Err(CryptoError::UnencryptedField.into())),
+            _ => None,
+        };
+
+        let plaintext = match versioned {
+            Some(Ok(plaintext)) => plaintext,
+            // Legacy blobs start with a random nonce byte, so anything that
+            // doesn't open as a versioned blob gets one try as version 0.
+            versioned => match self.open_legacy(&ciphertext, aad) {
+                Ok(plaintext) => plaintext,
+                Err(legacy_err) => {
+                    return Err(match (versioned, ciphertext.first()) {
+                        (Some(Err(e)), _) => e,
+                        // Too short to be a legacy blob, so the tag is unknown
+                        (_, Some(&version))
+                            if ciphertext.len() >= HEADER_SIZE
+                                && ciphertext.len() < NONCE_SIZE + TAG_SIZE =>
+                        {
+                            CryptoError::UnsupportedCiphertextVersion(version).into()
+                        }
+                        // A legacy-shaped blob that doesn't open: wrong key or tampered
+                        _ => legacy_err,
+                    });
+                }
+            },
+        };
+
+        Ok(plaintext)
+    }
+
+    /// Open a version 2 envelope: unwrap the data key, then open the payload.
+    fn open_v2(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let (suite, dek) = self.unwrap_data_key(ciphertext)?;
+        open_with(
+            &data_key(suite, &dek)?,
+            &ciphertext[HEADER_SIZE + WRAPPED_KEY_SIZE..],
+            aad,
+        )
+    }
+
+    /// Unwrap the data key of a version 2 envelope with this master key.
+    fn unwrap_data_key(
+        &self,
+        ciphertext: &[u8],
+    ) -> Result<(CipherSuite, Zeroizing<[u8; KEY_SIZE]>)> {
+        if ciphertext.len() < HEADER_SIZE + WRAPPED_KEY_SIZE {
+            anyhow::bail!("Ciphertext too short");
+        }
+
+        let suite = CipherSuite::from_id(ciphertext[1] & !COUNTER_NONCE_FLAG)
+            .ok_or(CryptoError::UnsupportedAlgorithm(ciphertext[1]))?;
+
+        let (header, rest) = ciphertext.split_at(HEADER_SIZE);
+        let unwrapped = open_with(self.key_for(suite), &rest[..WRAPPED_KEY_SIZE], header)?;
+
+        let mut dek = Zeroizing::new([0u8; KEY_SIZE]);
+        dek.copy_from_slice(&unwrapped);
+        Ok((suite, dek))
+    }
+
+    /// Open a version 1 blob: `version + algorithm + nonce + ciphertext + tag`.
+    fn open_v1(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        if ciphertext.len() < HEADER_SIZE {
             anyhow::bail!("Ciphertext too short");
         }
 
-        // Extract nonce and ciphertext
-        let (nonce_bytes, encrypted) = ciphertext.split_at(NONCE_SIZE);
-        let nonce_array: [u8; NONCE_SIZE] = nonce_bytes
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("Invalid nonce"))?;
-        let nonce = Nonce::assume_unique_for_key(nonce_array);
+        let suite = CipherSuite::from_id(ciphertext[1])
+            .ok_or(CryptoError::UnsupportedAlgorithm(ciphertext[1]))?;
+
+        open_with(self.key_for(suite), &ciphertext[HEADER_SIZE..], aad)
+    }
+
+    /// Open a version 0 (pre-header) blob: `nonce + ciphertext + tag`.
+    fn open_legacy(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        open_with(self.key_for(CipherSuite::Aes256Gcm), ciphertext, aad)
+    }
+
+    /// Rewrap an envelope's data key under `new`'s master key.
+    ///
+    /// The payload is copied untouched, so this is cheap regardless of its size
+    /// and doesn't need the associated data. Only version 2 envelopes can be
+    /// rewrapped; older formats have to be decrypted and re-encrypted.
+    pub fn rewrap(&self, ciphertext_hex: &str, new: &FieldEncryptor) -> Result<String> {
+        let ciphertext = hex::decode(ciphertext_hex).context("Invalid hex encoding")?;
+        if ciphertext.first() != Some(&CIPHERTEXT_VERSION_2) {
+            anyhow::bail!("Only envelope-encrypted ciphertexts can be rewrapped");
+        }
+
+        let (suite, dek) = self.unwrap_data_key(&ciphertext)?;
+        let header = [CIPHERTEXT_VERSION_2, new.algorithm_byte(suite)];
+        let wrapped = new.wrap(suite, &dek, &header)?;
+
+        let rest = &ciphertext[HEADER_SIZE..];
+        let mut result = header.to_vec();
+        result.extend_from_slice(&wrapped);
+        result.extend_from_slice(&rest[WRAPPED_KEY_SIZE..]);
+
+        Ok(hex::encode(result))
+    }
+
+    /// Create the encryptor for a replacement master key.
+    ///
+    /// The current encryptor stays usable so callers can decrypt existing data
+    /// with it and re-encrypt with the returned one (see
+    /// `SqliteCredentialRepository::reencrypt_all`). Fails if `new_key` is the
+    /// key this encryptor already uses. The cipher suite and nonce strategy
+    /// are carried over.
+    pub fn rotate_key(&self, new_key: &[u8; KEY_SIZE]) -> Result<FieldEncryptor> {
+        let rotated = FieldEncryptor::with_suite(new_key, self.suite)?
+            .with_nonce_strategy(self.nonce_strategy())?;
+
+        // A probe encrypted under the new key only opens under the old one if
+        // both keys are identical.
+        let probe = rotated.encrypt("key-rotation-probe")?;
+        if self.decrypt(&probe).is_ok() {
+            anyhow::bail!("New master key is identical to the current key");
+        }
+
+        Ok(rotated)
+    }
+
+    /// Encrypt everything `reader` yields into `writer`, in chunks of
+    /// [`STREAM_CHUNK_SIZE`], returning the number of plaintext bytes.
+    ///
+    /// Memory use is bounded by one chunk whatever the payload size. The
+    /// output is binary (see the stream format in the module docs).
+    pub fn encrypt_stream(&self, mut reader: impl Read, mut writer: impl Write) -> Result<u64> {
+        if self.plaintext {
+            writer.write_all(&[PLAINTEXT_VERSION])?;
+            return Ok(std::io::copy(&mut reader, &mut writer)?);
+        }
+
+        let header = [STREAM_VERSION, self.algorithm_byte(self.suite)];
+        let mut dek = Zeroizing::new([0u8; KEY_SIZE]);
+        let mut base_nonce = [0u8; NONCE_SIZE];
+        self.rng
+            .fill(&mut *dek)
+            .and_then(|()| self.rng.fill(&mut base_nonce))
+            .map_err(|_| anyhow::anyhow!("Failed to generate data key"))?;
+
+        writer.write_all(&header)?;
+        writer.write_all(&self.wrap(self.suite, &dek, &header)?)?;
+        writer.write_all(&base_nonce)?;
+
+        let key = data_key(self.suite, &dek)?;
+        let mut buf = Zeroizing::new(Vec::with_capacity(STREAM_CHUNK_SIZE + TAG_SIZE));
+        let mut total = 0u64;
+        for counter in 0u32.. {
+            buf.clear();
+            (&mut reader)
+                .take(STREAM_CHUNK_SIZE as u64)
+                .read_to_end(&mut buf)?;
+            total += buf.len() as u64;
+
+            // Only a full chunk can be followed by more data
+            let last = buf.len() < STREAM_CHUNK_SIZE;
+            key.seal_in_place_append_tag(
+                chunk_nonce(&base_nonce, counter, last),
+                Aad::empty(),
+                &mut *buf,
+            )
+            .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+            writer.write_all(&buf)?;
+            if last {
+                writer.flush()?;
+                return Ok(total);
+            }
+        }
+        anyhow::bail!("Stream too long to encrypt")
+    }
+
+    /// Decrypt a stream written by [`encrypt_stream`](Self::encrypt_stream)
+    /// into `writer`, returning the number of plaintext bytes.
+    ///
+    /// Chunks are written out as soon as they verify, so on error `writer`
+    /// holds a prefix of the plaintext and must be discarded. A stream cut
+    /// short fails with [`CryptoError::TruncatedStream`].
+    pub fn decrypt_stream(&self, mut reader: impl Read, mut writer: impl Write) -> Result<u64> {
+        let mut version = [0u8; 1];
+        reader
+            .read_exact(&mut version)
+            .map_err(|_| CryptoError::TruncatedStream)?;
+
+        match (version[0], self.plaintext) {
+            (PLAINTEXT_VERSION, true) => return Ok(std::io::copy(&mut reader, &mut writer)?),
+            (_, true) => anyhow::bail!("Plaintext encryptor cannot decrypt encrypted fields"),
+            (PLAINTEXT_VERSION, false) => return Err(CryptoError::UnencryptedField.into()),
+            (STREAM_VERSION, false) => {}
+            (version, false) => {
+                return Err(CryptoError::UnsupportedCiphertextVersion(version).into())
+            }
+        }
+
+        let mut envelope = [0u8; HEADER_SIZE + WRAPPED_KEY_SIZE + NONCE_SIZE];
+        envelope[0] = STREAM_VERSION;
+        reader
+            .read_exact(&mut envelope[1..])
+            .map_err(|_| CryptoError::TruncatedStream)?;
+        let (suite, dek) = self.unwrap_data_key(&envelope)?;
+        let mut base_nonce = [0u8; NONCE_SIZE];
+        base_nonce.copy_from_slice(&envelope[HEADER_SIZE + WRAPPED_KEY_SIZE..]);
+
+        let key = data_key(suite, &dek)?;
+        let mut buf = Zeroizing::new(Vec::with_capacity(STREAM_CHUNK_SIZE + TAG_SIZE));
+        let mut total = 0u64;
+        for counter in 0u32.. {
+            buf.clear();
+            (&mut reader)
+                .take((STREAM_CHUNK_SIZE + TAG_SIZE) as u64)
+                .read_to_end(&mut buf)?;
+            if buf.len() < TAG_SIZE {
+                return Err(CryptoError::TruncatedStream.into());
+            }
+
+            // A full chunk is never the last one, so the stream must go on
+            let last = buf.len() < STREAM_CHUNK_SIZE + TAG_SIZE;
+            let plaintext = key
+                .open_in_place(
+                    chunk_nonce(&base_nonce, counter, last),
+                    Aad::empty(),
+                    &mut buf,
+                )
+                .map_err(|_| CryptoError::AuthenticationFailed)?;
+            writer.write_all(plaintext)?;
+            total += plaintext.len() as u64;
+            if last {
+                writer.flush()?;
+                return Ok(total);
+            }
+        }
+        anyhow::bail!("Stream too long to decrypt")
+    }
+}
+
+/// A nonce filled by `fill`.
+fn random_nonce(fill: &mut dyn FnMut(&mut [u8]) -> Result<()>) -> Result<[u8; NONCE_SIZE]> {
+    let mut nonce = [0u8; NONCE_SIZE];
+    fill(&mut nonce[..]).context("Failed to generate nonce")?;
+    Ok(nonce)
+}
+
+/// Nonce for chunk `counter` of a stream: the base nonce with the counter
+/// XORed into bytes 7..11 and the final-chunk flag into the last byte.
+fn chunk_nonce(base: &[u8; NONCE_SIZE], counter: u32, last: bool) -> Nonce {
+    let mut nonce = *base;
+    for (byte, counter_byte) in nonce[7..11].iter_mut().zip(counter.to_be_bytes()) {
+        *byte ^= counter_byte;
+    }
+    nonce[11] ^= u8::from(last);
+    Nonce::assume_unique_for_key(nonce)
+}
+
+/// Build the AEAD key for a record's data key.
+fn data_key(suite: CipherSuite, dek: &[u8; KEY_SIZE]) -> Result<LessSafeKey> {
+    UnboundKey::new(suite.algorithm(), dek)
+        .map(LessSafeKey::new)
+        .map_err(|_| anyhow::anyhow!("Failed to create data key"))
+}
+
+/// Open `nonce + ciphertext + tag` with `key`.
+fn open_with(key: &LessSafeKey, sealed: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if sealed.len() < NONCE_SIZE + key.algorithm().tag_len() {
+        anyhow::bail!("Ciphertext too short");
+    }
+
+    // Extract nonce and ciphertext
+    let (nonce_bytes, encrypted) = sealed.split_at(NONCE_SIZE);
+    let nonce_array: [u8; NONCE_SIZE] = nonce_bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid nonce"))?;
+    let nonce = Nonce::assume_unique_for_key(nonce_array);
 
-        // Decrypt in-place
-        let mut in_out = encrypted.to_vec();
-        let plaintext = self
-            .key
-            .open_in_place(nonce, Aad::empty(), &mut in_out)
-            .map_err(|_| anyhow::anyhow!("Decryption failed - wrong key or corrupted data"))?;
+    // Decrypt in-place, then drop the tag so the buffer holds just the plaintext
+    let mut in_out = Zeroizing::new(encrypted.to_vec());
+    let len = key
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| CryptoError::AuthenticationFailed)?
+        .len();
+    in_out.truncate(len);
 
-        String::from_utf8(plaintext.to_vec()).context("Decrypted data is not valid UTF-8")
+    Ok(in_out)
+}
+
+/// Reinterpret decrypted bytes as a string, moving the buffer rather than
+/// leaving an unwiped copy behind.
+pub(crate) fn into_utf8(mut bytes: Zeroizing<Vec<u8>>) -> Result<Zeroizing<String>> {
+    match String::from_utf8(std::mem::take(&mut *bytes)) {
+        Ok(text) => Ok(Zeroizing::new(text)),
+        Err(e) => {
+            // Hand the bytes back so they're still wiped
+            *bytes = e.into_bytes();
+            anyhow::bail!("Decrypted data is not valid UTF-8")
+        }
     }
 }
 
+/// Truncated HMAC-SHA256 of a fixed context string, keyed by the master key.
+fn key_fingerprint(master_key: &[u8; KEY_SIZE]) -> [u8; FINGERPRINT_SIZE] {
+    let tag = hmac::sign(
+        &hmac::Key::new(hmac::HMAC_SHA256, master_key),
+        FINGERPRINT_CONTEXT,
+    );
+    let mut fingerprint = [0u8; FINGERPRINT_SIZE];
+    fingerprint.copy_from_slice(&tag.as_ref()[..FINGERPRINT_SIZE]);
+    fingerprint
+}
+
+/// Derive a key from `passphrase` and `salt` with Argon2id.
+pub(crate) fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: KdfParams,
+) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_SIZE),
+    )
+    .map_err(|e| anyhow::anyhow!("Invalid Argon2id parameters: {}", e))?;
+
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+    Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut *key)
+        .map_err(|e| anyhow::anyhow!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
 /// Generate a random master key.
 pub fn generate_master_key() -> Result<[u8; KEY_SIZE]> {
     let rng = SystemRandom::new();
@@ -93,9 +827,85 @@ pub fn generate_master_key() -> Result<[u8; KEY_SIZE]> {
     Ok(key)
 }
 
+/// Generate a master key from `rng` instead of the OS.
+///
+/// Lets tests seed a known RNG for reproducible keys. Keys that protect real
+/// data come from [`generate_master_key`].
+pub fn generate_master_key_from(rng: &mut impl RngCore) -> Result<[u8; KEY_SIZE]> {
+    let mut key = [0u8; KEY_SIZE];
+    rng.try_fill_bytes(&mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to generate key: {}", e))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use rand_core::RngCore;
+
+    /// Deterministic RNG for tests: yields `seed`, `seed + 1`, ... as bytes.
+    pub(crate) struct CountingRng(pub u8);
+
+    impl RngCore for CountingRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest {
+                *byte = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> std::result::Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::test_util::CountingRng;
     use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_master_key_from_seeded_rng() {
+        let key = generate_master_key_from(&mut CountingRng(0)).unwrap();
+        let expected: Vec<u8> = (0..KEY_SIZE as u8).collect();
+        assert_eq!(key.as_slice(), expected.as_slice());
+        assert_eq!(generate_master_key_from(&mut CountingRng(0)).unwrap(), key);
+
+        // A known key still seals and opens with random nonces
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+        let ciphertext = encryptor.encrypt("token").unwrap();
+        let reopened =
+            FieldEncryptor::new(&generate_master_key_from(&mut CountingRng(0)).unwrap()).unwrap();
+        assert_eq!(*reopened.decrypt(&ciphertext).unwrap(), "token");
+    }
+
+    #[test]
+    fn test_encrypt_with_seeded_rng_is_exact() {
+        let key = generate_master_key_from(&mut CountingRng(0)).unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        // Data key, wrap nonce and payload nonce, in that order, from 0x40 up
+        let ciphertext = encryptor
+            .encrypt_with_rng(b"token", &[], &mut CountingRng(0x40))
+            .unwrap();
+        assert_eq!(
+            ciphertext,
+            "0201606162636465666768696a6baa9ad916ff05c528345fb3603ec08f0c7624dbdad031648efb\
+             f8da3720afa957b043011f2d84d1246d31003bb97e685f6c6d6e6f7071727374757677247caf9a\
+             24ec32508455b44d6a1722bf0a7cb31879"
+        );
+        assert_eq!(*encryptor.decrypt(&ciphertext).unwrap(), "token");
+    }
 
     #[test]
     fn test_encrypt_decrypt() {
@@ -113,7 +923,7 @@ mod tests {
 
         // Decrypt should return original
         let decrypted = encryptor.decrypt(&ciphertext).unwrap();
-        assert_eq!(decrypted, plaintext);
+        assert_eq!(*decrypted, plaintext);
     }
 
     #[test]
@@ -145,7 +955,395 @@ mod tests {
         assert_ne!(ciphertext1, ciphertext2);
 
         // Both should decrypt to the same value
-        assert_eq!(encryptor.decrypt(&ciphertext1).unwrap(), plaintext);
-        assert_eq!(encryptor.decrypt(&ciphertext2).unwrap(), plaintext);
+        assert_eq!(*encryptor.decrypt(&ciphertext1).unwrap(), plaintext);
+        assert_eq!(*encryptor.decrypt(&ciphertext2).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_rotate_key() {
+        let old_key = generate_master_key().unwrap();
+        let new_key = generate_master_key().unwrap();
+        let old = FieldEncryptor::new(&old_key).unwrap();
+        let new = old.rotate_key(&new_key).unwrap();
+
+        let ciphertext = new.encrypt("rotated").unwrap();
+        assert_eq!(*new.decrypt(&ciphertext).unwrap(), "rotated");
+        assert!(old.decrypt(&ciphertext).is_err());
+
+        // Rotating to the same key is rejected
+        assert!(old.rotate_key(&old_key).is_err());
+    }
+
+    #[test]
+    fn test_ciphertext_has_version_header() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        let bytes = hex::decode(encryptor.encrypt("versioned").unwrap()).unwrap();
+        assert_eq!(bytes[0], CIPHERTEXT_VERSION_2);
+        assert_eq!(bytes[1], CipherSuite::Aes256Gcm.id());
+    }
+
+    #[test]
+    fn test_envelope_uses_fresh_data_key() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        let a = hex::decode(encryptor.encrypt("same-data").unwrap()).unwrap();
+        let b = hex::decode(encryptor.encrypt("same-data").unwrap()).unwrap();
+
+        let (_, a_dek) = encryptor.unwrap_data_key(&a).unwrap();
+        let (_, b_dek) = encryptor.unwrap_data_key(&b).unwrap();
+        assert_ne!(&*a_dek, &*b_dek);
+        assert_ne!(&*a_dek, &key);
+        assert_eq!(
+            a.len(),
+            HEADER_SIZE + WRAPPED_KEY_SIZE + NONCE_SIZE + "same-data".len() + TAG_SIZE
+        );
+    }
+
+    #[test]
+    fn test_envelope_header_is_authenticated() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        // Claiming a different suite must not open the wrapped key
+        let mut bytes = hex::decode(encryptor.encrypt("data").unwrap()).unwrap();
+        bytes[1] = CipherSuite::ChaCha20Poly1305.id();
+        assert!(encryptor.decrypt(&hex::encode(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_rewrap() {
+        let old_key = generate_master_key().unwrap();
+        let new_key = generate_master_key().unwrap();
+        let old = FieldEncryptor::new(&old_key).unwrap();
+        let new = old.rotate_key(&new_key).unwrap();
+
+        let ciphertext = old.encrypt_with_aad(b"large payload", b"row-1").unwrap();
+        let rewrapped = old.rewrap(&ciphertext, &new).unwrap();
+
+        // Payload bytes are unchanged; only the wrapped key differs
+        let payload_offset = (HEADER_SIZE + WRAPPED_KEY_SIZE) * 2;
+        assert_eq!(&ciphertext[payload_offset..], &rewrapped[payload_offset..]);
+        assert_eq!(
+            *new.decrypt_with_aad(&rewrapped, b"row-1").unwrap(),
+            b"large payload"
+        );
+        assert!(old.decrypt_with_aad(&rewrapped, b"row-1").is_err());
+
+        // Rewrapping needs the key the envelope was wrapped with
+        assert!(new.rewrap(&ciphertext, &old).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_v1_ciphertext() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        // Build a version 1 blob sealed directly under the master key
+        let sealed = encryptor
+            .seal(&encryptor.aes_key, b"v1-secret", &[])
+            .unwrap();
+        let mut v1 = vec![CIPHERTEXT_VERSION_1, CipherSuite::Aes256Gcm.id()];
+        v1.extend_from_slice(&sealed);
+
+        assert_eq!(*encryptor.decrypt(&hex::encode(&v1)).unwrap(), "v1-secret");
+        // Only envelopes can be rewrapped
+        assert!(encryptor
+            .rewrap(&hex::encode(v1), &FieldEncryptor::new(&key).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_chacha20_poly1305_roundtrip() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::with_suite(&key, CipherSuite::ChaCha20Poly1305).unwrap();
+
+        let ciphertext = encryptor.encrypt("chacha-secret").unwrap();
+        let bytes = hex::decode(&ciphertext).unwrap();
+        assert_eq!(bytes[1], CipherSuite::ChaCha20Poly1305.id());
+        assert_eq!(*encryptor.decrypt(&ciphertext).unwrap(), "chacha-secret");
+    }
+
+    #[test]
+    fn test_mixed_suites_decrypt() {
+        let key = generate_master_key().unwrap();
+        let aes = FieldEncryptor::new(&key).unwrap();
+        let chacha = FieldEncryptor::with_suite(&key, CipherSuite::ChaCha20Poly1305).unwrap();
+
+        let aes_ciphertext = aes.encrypt("from-aes").unwrap();
+        let chacha_ciphertext = chacha.encrypt("from-chacha").unwrap();
+
+        // Either encryptor reads both suites as long as the key matches
+        assert_eq!(*chacha.decrypt(&aes_ciphertext).unwrap(), "from-aes");
+        assert_eq!(*aes.decrypt(&chacha_ciphertext).unwrap(), "from-chacha");
+    }
+
+    #[test]
+    fn test_key_fingerprint() {
+        let key1 = generate_master_key().unwrap();
+        let key2 = generate_master_key().unwrap();
+
+        let a = FieldEncryptor::new(&key1).unwrap();
+        let b = FieldEncryptor::with_suite(&key1, CipherSuite::ChaCha20Poly1305).unwrap();
+        let c = FieldEncryptor::new(&key2).unwrap();
+
+        // Same key gives the same fingerprint regardless of suite
+        assert_eq!(a.key_fingerprint(), b.key_fingerprint());
+        assert_ne!(a.key_fingerprint(), c.key_fingerprint());
+        // The fingerprint doesn't leak key bytes
+        assert_ne!(&a.key_fingerprint()[..], &key1[..FINGERPRINT_SIZE]);
+    }
+
+    #[test]
+    fn test_aad_binding() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        let ciphertext = encryptor.encrypt_with_aad(b"bound", b"row-1").unwrap();
+        assert_eq!(
+            *encryptor.decrypt_with_aad(&ciphertext, b"row-1").unwrap(),
+            b"bound"
+        );
+
+        // Different or missing AAD fails authentication
+        let err = encryptor
+            .decrypt_with_aad(&ciphertext, b"row-2")
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::AuthenticationFailed)
+        );
+        assert!(encryptor.decrypt(&ciphertext).is_err());
+    }
+
+    /// Build a pre-header blob: nonce + ciphertext + tag.
+    fn legacy_blob(encryptor: &FieldEncryptor, plaintext: &[u8]) -> String {
+        let nonce_bytes = [7u8; NONCE_SIZE];
+        let mut in_out = plaintext.to_vec();
+        encryptor
+            .aes_key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce_bytes),
+                Aad::empty(),
+                &mut in_out,
+            )
+            .unwrap();
+        let mut legacy = nonce_bytes.to_vec();
+        legacy.extend_from_slice(&in_out);
+        hex::encode(legacy)
+    }
+
+    #[test]
+    fn test_decrypt_legacy_ciphertext() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        let legacy = legacy_blob(&encryptor, b"legacy-secret");
+        assert_eq!(*encryptor.decrypt(&legacy).unwrap(), "legacy-secret");
+    }
+
+    #[test]
+    fn test_legacy_ciphertext_with_wrong_key_fails_authentication() {
+        let encryptor = FieldEncryptor::new(&generate_master_key().unwrap()).unwrap();
+        let other = FieldEncryptor::new(&generate_master_key().unwrap()).unwrap();
+
+        let legacy = legacy_blob(&encryptor, b"legacy-secret");
+        let err = other.decrypt(&legacy).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_unknown_version_is_rejected() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        // Too short for a legacy blob, so the tag can only be a version
+        let short = [0x7f; NONCE_SIZE + TAG_SIZE - 1];
+        let err = encryptor.decrypt(&hex::encode(short)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::UnsupportedCiphertextVersion(0x7f))
+        );
+
+        // Long enough to be legacy, so it's tried as one and fails to open
+        let mut bytes = hex::decode(encryptor.encrypt("data").unwrap()).unwrap();
+        bytes[0] = 0x7f;
+        let err = encryptor.decrypt(&hex::encode(bytes)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::AuthenticationFailed)
+        );
+    }
+
+    #[test]
+    fn test_decrypt_rejects_invalid_utf8() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        let ciphertext = encryptor.encrypt_with_aad(&[0xff, 0xfe], &[]).unwrap();
+        assert!(encryptor.decrypt(&ciphertext).is_err());
+        assert_eq!(
+            *encryptor.decrypt_with_aad(&ciphertext, &[]).unwrap(),
+            [0xff, 0xfe]
+        );
+    }
+
+    #[test]
+    fn test_unencrypted_field_is_rejected() {
+        let key = generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+        assert!(!encryptor.is_plaintext());
+
+        let field = hex::encode([&[PLAINTEXT_VERSION][..], b"secret-token-value"].concat());
+        let err = encryptor.decrypt(&field).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::UnencryptedField)
+        );
+    }
+
+    #[test]
+    fn test_counter_nonces() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(crate::FileNonceCounterStore::new(dir.path()).unwrap());
+        let key = generate_master_key().unwrap();
+        let counted = FieldEncryptor::new(&key)
+            .unwrap()
+            .with_nonce_strategy(NonceStrategy::Counter(store))
+            .unwrap();
+        let random = FieldEncryptor::new(&key).unwrap();
+
+        let first = hex::decode(counted.encrypt("one").unwrap()).unwrap();
+        let second = hex::decode(counted.encrypt("two").unwrap()).unwrap();
+        // The strategy is in the header, the counter in the wrap nonce
+        assert_eq!(first[1], CipherSuite::Aes256Gcm.id() | COUNTER_NONCE_FLAG);
+        let counter = |bytes: &[u8]| u64::from_be_bytes(bytes[6..14].try_into().unwrap());
+        assert_eq!(counter(&second), counter(&first) + 1);
+        assert_eq!(first[2..6], second[2..6]);
+
+        // Either encryptor reads the other's data
+        assert_eq!(*random.decrypt(&hex::encode(&first)).unwrap(), "one");
+        let plain = random.encrypt("three").unwrap();
+        assert_eq!(*counted.decrypt(&plain).unwrap(), "three");
+
+        // Rewrapping and rotation keep the new encryptor's strategy
+        let rotated = counted.rotate_key(&generate_master_key().unwrap()).unwrap();
+        let rewrapped = hex::decode(random.rewrap(&plain, &rotated).unwrap()).unwrap();
+        assert_eq!(
+            rewrapped[1],
+            CipherSuite::Aes256Gcm.id() | COUNTER_NONCE_FLAG
+        );
+        assert_eq!(*rotated.decrypt(&hex::encode(&rewrapped)).unwrap(), "three");
+
+        // Tampering with the flag is caught like any other header change
+        let mut tampered = first.clone();
+        tampered[1] &= !COUNTER_NONCE_FLAG;
+        assert!(random.decrypt(&hex::encode(&tampered)).is_err());
+    }
+
+    fn stream_roundtrip(encryptor: &FieldEncryptor, plaintext: &[u8]) -> Vec<u8> {
+        let mut encrypted = Vec::new();
+        let written = encryptor.encrypt_stream(plaintext, &mut encrypted).unwrap();
+        assert_eq!(written, plaintext.len() as u64);
+        encrypted
+    }
+
+    #[test]
+    fn test_stream_roundtrip() {
+        let encryptor = FieldEncryptor::new(&generate_master_key().unwrap()).unwrap();
+
+        for len in [
+            0,
+            1,
+            STREAM_CHUNK_SIZE - 1,
+            STREAM_CHUNK_SIZE,
+            STREAM_CHUNK_SIZE + 1,
+            3 * STREAM_CHUNK_SIZE + 100,
+        ] {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let encrypted = stream_roundtrip(&encryptor, &plaintext);
+            let chunks = len / STREAM_CHUNK_SIZE + 1;
+            assert_eq!(
+                encrypted.len(),
+                HEADER_SIZE + WRAPPED_KEY_SIZE + NONCE_SIZE + len + chunks * TAG_SIZE
+            );
+
+            let mut decrypted = Vec::new();
+            let read = encryptor
+                .decrypt_stream(encrypted.as_slice(), &mut decrypted)
+                .unwrap();
+            assert_eq!(read, len as u64);
+            assert!(decrypted == plaintext, "roundtrip of {} bytes", len);
+        }
+    }
+
+    #[test]
+    fn test_stream_tampering_is_detected() {
+        let encryptor = FieldEncryptor::new(&generate_master_key().unwrap()).unwrap();
+        let plaintext = vec![7u8; 2 * STREAM_CHUNK_SIZE + 10];
+        let encrypted = stream_roundtrip(&encryptor, &plaintext);
+        let start = HEADER_SIZE + WRAPPED_KEY_SIZE + NONCE_SIZE;
+        let chunk = STREAM_CHUNK_SIZE + TAG_SIZE;
+
+        let decrypt = |data: &[u8]| {
+            let err = encryptor.decrypt_stream(data, Vec::new()).unwrap_err();
+            err.downcast_ref::<CryptoError>().cloned()
+        };
+
+        // Cut off at a chunk boundary: every chunk left verifies, but none is final
+        assert_eq!(
+            decrypt(&encrypted[..start + 2 * chunk]),
+            Some(CryptoError::TruncatedStream)
+        );
+        assert_eq!(
+            decrypt(&encrypted[..start]),
+            Some(CryptoError::TruncatedStream)
+        );
+        // Cut off inside the last chunk
+        assert_eq!(
+            decrypt(&encrypted[..encrypted.len() - 1]),
+            Some(CryptoError::AuthenticationFailed)
+        );
+
+        // Swap the first two chunks
+        let mut swapped = encrypted[..start].to_vec();
+        swapped.extend_from_slice(&encrypted[start + chunk..start + 2 * chunk]);
+        swapped.extend_from_slice(&encrypted[start..start + chunk]);
+        swapped.extend_from_slice(&encrypted[start + 2 * chunk..]);
+        assert_eq!(decrypt(&swapped), Some(CryptoError::AuthenticationFailed));
+
+        // Flip a bit in the middle chunk
+        let mut flipped = encrypted.clone();
+        flipped[start + chunk + 5] ^= 1;
+        assert_eq!(decrypt(&flipped), Some(CryptoError::AuthenticationFailed));
+
+        // Wrong key
+        let other = FieldEncryptor::new(&generate_master_key().unwrap()).unwrap();
+        assert!(other
+            .decrypt_stream(encrypted.as_slice(), Vec::new())
+            .is_err());
+    }
+
+    #[cfg(feature = "dev-unsafe-crypto")]
+    #[test]
+    fn test_plaintext_unsafe_roundtrip() {
+        let plaintext = FieldEncryptor::plaintext_unsafe().unwrap();
+        assert!(plaintext.is_plaintext());
+
+        let field = plaintext.encrypt("my-secret-token").unwrap();
+        assert_eq!(hex::decode(&field).unwrap()[1..], *b"my-secret-token");
+        assert_eq!(*plaintext.decrypt(&field).unwrap(), "my-secret-token");
+
+        // Neither side reads the other's data
+        let real = FieldEncryptor::new(&generate_master_key().unwrap()).unwrap();
+        assert!(real.decrypt(&field).is_err());
+        assert!(plaintext
+            .decrypt(&real.encrypt("my-secret-token").unwrap())
+            .is_err());
     }
 }
@@ -1,8 +1,30 @@
 //! Database manager for SQLite storage.
 //!
-//! Note: We use standard SQLite (not SQLCipher) for simplicity.
 //! Sensitive data (credentials, tokens) is encrypted at the application level
-//! using the `crypto` module before being stored.
+//! using the `crypto` module before being stored. The rest of the database
+//! (space names, server URLs, schema) is plaintext on disk by default.
+//!
+//! ## Whole-database encryption
+//!
+//! With the `sqlcipher` feature, [`Database::open_encrypted`] opens the file
+//! through SQLCipher so every page is encrypted with the master key. Field
+//! encryption stays on top for defense in depth.
+//!
+//! Existing plaintext databases are converted once with
+//! [`Database::encrypt_existing`]. The conversion is one-way: there is no
+//! helper to decrypt the file again, and losing the master key means losing
+//! the whole database rather than just the credentials. Rotating the master
+//! key does not rekey an encrypted database.
+//!
+//! ## Read-only access
+//!
+//! [`Database::open_read_only`] is for inspection tooling running next to the
+//! app. The connection can't write (every repository write fails with
+//! [`StorageError::ReadOnly`](mcpmux_core::StorageError::ReadOnly)), never
+//! migrates, and as a WAL reader it neither blocks the app's writes nor waits
+//! for them. It deliberately doesn't use SQLite's `immutable` or `nolock`
+//! URI options: those skip the WAL and locking, which is only safe for files
+//! nobody is writing, and would let a live file be read mid-commit.
 //!
 //! ## Migration System
 //!
@@ -10,15 +32,44 @@
 //! the `migrations/` directory. Each migration is run exactly once, tracked
 //! via the `schema_migrations` table.
 //!
+//! Pending migrations are applied by [`Database::open`] inside a single
+//! exclusive transaction, so a second process opening the same file waits
+//! instead of racing, and a failing migration rolls back every migration of
+//! that run and surfaces as [`DatabaseError::MigrationFailed`].
+//!
 //! To add a new migration:
 //! 1. Create a new file: `migrations/NNN_description.sql`
 //! 2. Add the migration to the `MIGRATIONS` array below
 //! 3. The migration will auto-run on next app startup
 
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::{
+    params, Connection, DatabaseName, OpenFlags, OptionalExtension, Transaction,
+    TransactionBehavior,
+};
+use serde::Serialize;
+use std::fmt;
 use std::path::Path;
-use tracing::{debug, info};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+#[cfg(feature = "sqlcipher")]
+use crate::crypto::KEY_SIZE;
+use crate::crypto::{CryptoError, FieldEncryptor};
+
+/// `meta` table key holding the hex-encoded master key fingerprint.
+const KEY_FINGERPRINT_META_KEY: &str = "key_fingerprint";
+
+/// `meta` table key holding the install id of the master key.
+const INSTALL_ID_META_KEY: &str = "install_id";
+
+/// Prepared statements kept per connection. Repositories cache their hot
+/// queries; rusqlite's default of 16 is smaller than their combined set.
+pub(crate) const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// Environment variable that lets a key from another install take over the
+/// database (see [`Database::verify_install_id`]).
+pub const ALLOW_KEY_MIGRATION_ENV_VAR: &str = "MCPMUX_ALLOW_KEY_MIGRATION";
 
 /// A database migration with version number and SQL content.
 struct Migration {
@@ -27,16 +78,226 @@ struct Migration {
     sql: &'static str,
 }
 
+/// Errors from opening or migrating the database.
+///
+/// Returned wrapped in `anyhow::Error`; use `downcast_ref::<DatabaseError>()` to match.
+#[derive(Debug)]
+pub enum DatabaseError {
+    /// A migration failed to apply. The database was left at its previous version.
+    MigrationFailed {
+        version: i64,
+        source: rusqlite::Error,
+    },
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseError::MigrationFailed { version, source } => {
+                write!(f, "Failed to run migration {}: {}", version, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatabaseError::MigrationFailed { source, .. } => Some(source),
+        }
+    }
+}
+
 /// All migrations in order. Add new migrations here.
 ///
 /// Note: Migrations have been consolidated into a single clean initial migration.
 /// The schema includes cached_definition for offline operation and excludes
 /// runtime fields (connection_status, last_connected_at, last_error).
-const MIGRATIONS: &[Migration] = &[Migration {
-    version: 1,
-    name: "initial",
-    sql: include_str!("migrations/001_initial.sql"),
-}];
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        sql: include_str!("migrations/001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "meta",
+        sql: include_str!("migrations/002_meta.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "soft_delete",
+        sql: include_str!("migrations/003_soft_delete.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "audit_log",
+        sql: include_str!("migrations/004_audit_log.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "row_version",
+        sql: include_str!("migrations/005_row_version.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "space_tags",
+        sql: include_str!("migrations/006_space_tags.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "space_env",
+        sql: include_str!("migrations/007_space_env.sql"),
+    },
+];
+
+/// SQLite journal mode (`PRAGMA journal_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JournalMode {
+    /// Rollback journal, deleted after each transaction.
+    Delete,
+    /// Rollback journal, truncated after each transaction.
+    Truncate,
+    /// Write-ahead log: readers don't block the writer and vice versa.
+    #[default]
+    Wal,
+}
+
+impl JournalMode {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            JournalMode::Delete => "DELETE",
+            JournalMode::Truncate => "TRUNCATE",
+            JournalMode::Wal => "WAL",
+        }
+    }
+}
+
+/// SQLite durability level (`PRAGMA synchronous`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Synchronous {
+    Off,
+    /// Safe against corruption in WAL mode; a power loss may drop the last commits.
+    #[default]
+    Normal,
+    Full,
+}
+
+impl Synchronous {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Synchronous::Off => "OFF",
+            Synchronous::Normal => "NORMAL",
+            Synchronous::Full => "FULL",
+        }
+    }
+}
+
+/// How SQLite reclaims space freed by deletes (`PRAGMA auto_vacuum`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoVacuum {
+    /// Freed pages stay in the file until a full [`Database::vacuum`].
+    None,
+    /// Freed pages are truncated from the file at every commit.
+    Full,
+    /// Freed pages are kept until [`Database::incremental_vacuum`] trims them.
+    Incremental,
+}
+
+impl AutoVacuum {
+    fn as_str(self) -> &'static str {
+        match self {
+            AutoVacuum::None => "NONE",
+            AutoVacuum::Full => "FULL",
+            AutoVacuum::Incremental => "INCREMENTAL",
+        }
+    }
+
+    fn from_pragma(value: i64) -> Result<Self> {
+        match value {
+            0 => Ok(AutoVacuum::None),
+            1 => Ok(AutoVacuum::Full),
+            2 => Ok(AutoVacuum::Incremental),
+            other => anyhow::bail!("Unknown auto_vacuum mode {}", other),
+        }
+    }
+}
+
+/// Connection settings for [`Database::open_with_options`] and
+/// [`DbPool::open_with_options`](crate::DbPool::open_with_options).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbOptions {
+    /// How long to retry when another connection holds a lock before failing
+    /// with `SQLITE_BUSY`.
+    pub busy_timeout: Duration,
+    pub journal_mode: JournalMode,
+    pub synchronous: Synchronous,
+    /// Auto-vacuum mode for a new database file. An existing file switches
+    /// mode on its next [`Database::vacuum`]. `None` keeps the file's mode.
+    pub auto_vacuum: Option<AutoVacuum>,
+}
+
+impl Default for DbOptions {
+    /// WAL, `synchronous=NORMAL`, 5 second busy timeout, auto-vacuum unchanged.
+    fn default() -> Self {
+        Self {
+            busy_timeout: Duration::from_secs(5),
+            journal_mode: JournalMode::default(),
+            synchronous: Synchronous::default(),
+            auto_vacuum: None,
+        }
+    }
+}
+
+/// A row whose foreign key points at a missing parent row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ForeignKeyViolation {
+    /// Table containing the offending row.
+    pub table: String,
+    /// Rowid of the offending row (`None` for `WITHOUT ROWID` tables).
+    pub rowid: Option<i64>,
+    /// Table the foreign key refers to.
+    pub parent: String,
+}
+
+/// Result of [`Database::integrity_check`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IntegrityReport {
+    /// Problems reported by `PRAGMA integrity_check` (page, index and
+    /// constraint corruption).
+    pub integrity_errors: Vec<String>,
+    /// Rows reported by `PRAGMA foreign_key_check`.
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+}
+
+impl IntegrityReport {
+    /// Whether no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.integrity_errors.is_empty() && self.foreign_key_violations.is_empty()
+    }
+}
+
+/// Result of [`Database::vacuum`] and [`Database::incremental_vacuum`].
+///
+/// Sizes are the database's page count times its page size; they don't
+/// include an uncheckpointed WAL file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct VacuumReport {
+    /// Size in bytes before vacuuming.
+    pub size_before: u64,
+    /// Size in bytes afterwards.
+    pub size_after: u64,
+    /// Free pages still in the file afterwards. An incremental vacuum is
+    /// done once this reaches zero.
+    pub free_pages: u64,
+}
+
+impl VacuumReport {
+    /// Bytes given back to the filesystem.
+    pub fn reclaimed(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
 
 /// SQLite database wrapper.
 pub struct Database {
@@ -49,22 +310,123 @@ impl Database {
     /// If the database doesn't exist, it will be created.
     /// All pending migrations will be automatically applied.
     pub fn open(path: &Path) -> Result<Self> {
+        Self::open_with_options(path, DbOptions::default())
+    }
+
+    /// Open a database at the given path with explicit connection settings.
+    pub fn open_with_options(path: &Path, options: DbOptions) -> Result<Self> {
+        let conn = Self::open_connection(path)?;
+        Self::init(conn, path, options)
+    }
+
+    /// Open a SQLCipher-encrypted database, creating it if it doesn't exist.
+    ///
+    /// `key` is the master key from the [`MasterKeyProvider`](crate::MasterKeyProvider).
+    /// Fails with [`CryptoError::KeyMismatch`] if the file was encrypted with a
+    /// different key or is not encrypted at all (see [`Database::encrypt_existing`]).
+    #[cfg(feature = "sqlcipher")]
+    pub fn open_encrypted(path: &Path, key: &[u8; KEY_SIZE]) -> Result<Self> {
+        Self::open_encrypted_with_options(path, key, DbOptions::default())
+    }
+
+    /// Open a SQLCipher-encrypted database with explicit connection settings.
+    #[cfg(feature = "sqlcipher")]
+    pub fn open_encrypted_with_options(
+        path: &Path,
+        key: &[u8; KEY_SIZE],
+        options: DbOptions,
+    ) -> Result<Self> {
+        let conn = Self::open_connection(path)?;
+
+        // The key must be set before anything else touches the file
+        conn.pragma_update(None, "key", &*sqlcipher_key(key))?;
+
+        // A wrong key only shows up on the first read
+        if let Err(e) = conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |_| Ok(())) {
+            debug!("Failed to read encrypted database {:?}: {}", path, e);
+            return Err(CryptoError::KeyMismatch.into());
+        }
+
+        Self::init(conn, path, options)
+    }
+
+    /// Encrypt an existing plaintext database file in place with SQLCipher.
+    ///
+    /// The database is exported into a new encrypted file which then replaces
+    /// the original; afterwards it can only be opened with
+    /// [`Database::open_encrypted`]. Must not be called while the database is
+    /// open elsewhere.
+    #[cfg(feature = "sqlcipher")]
+    pub fn encrypt_existing(path: &Path, key: &[u8; KEY_SIZE]) -> Result<()> {
+        let encrypted_path = path.with_extension("encrypting");
+        if encrypted_path.exists() {
+            std::fs::remove_file(&encrypted_path)?;
+        }
+
+        {
+            let conn = Connection::open(path)
+                .with_context(|| format!("Failed to open database at {:?}", path))?;
+
+            // Fold the WAL into the main file so the export sees every commit
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+                .with_context(|| format!("{:?} is not a plaintext database", path))?;
+
+            conn.execute(
+                "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+                params![encrypted_path.to_string_lossy(), &*sqlcipher_key(key)],
+            )?;
+            conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+                .context("Failed to export database to SQLCipher")?;
+            conn.execute_batch("DETACH DATABASE encrypted;")?;
+        }
+
+        // Drop the plaintext WAL/SHM side files before swapping in the new file
+        for suffix in ["-wal", "-shm"] {
+            let mut side_file = path.as_os_str().to_owned();
+            side_file.push(suffix);
+            let side_file = std::path::PathBuf::from(side_file);
+            if side_file.exists() {
+                std::fs::remove_file(&side_file)?;
+            }
+        }
+
+        std::fs::rename(&encrypted_path, path)
+            .with_context(|| format!("Failed to replace {:?} with encrypted copy", path))?;
+
+        info!("Database at {:?} encrypted with SQLCipher", path);
+        Ok(())
+    }
+
+    /// Open a connection to `path`, creating the parent directory if needed.
+    fn open_connection(path: &Path) -> Result<Connection> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("Failed to create database directory: {:?}", parent))?;
         }
 
-        let conn = Connection::open(path)
-            .with_context(|| format!("Failed to open database at {:?}", path))?;
+        Connection::open(path).with_context(|| format!("Failed to open database at {:?}", path))
+    }
 
+    /// Apply connection settings and pending migrations.
+    fn init(conn: Connection, path: &Path, options: DbOptions) -> Result<Self> {
         // Enable foreign keys
         conn.pragma_update(None, "foreign_keys", "ON")?;
 
-        // Set journal mode to WAL for better concurrency
-        conn.pragma_update(None, "journal_mode", "WAL")?;
+        // Wait for locks held by other connections instead of failing immediately
+        conn.busy_timeout(options.busy_timeout)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
 
-        debug!("Opened database at {:?}", path);
+        // Only takes effect before the first table is created, or on VACUUM
+        if let Some(auto_vacuum) = options.auto_vacuum {
+            conn.pragma_update(None, "auto_vacuum", auto_vacuum.as_str())?;
+        }
+
+        // WAL by default so the gateway can write while the UI reads
+        conn.pragma_update(None, "journal_mode", options.journal_mode.as_str())?;
+        conn.pragma_update(None, "synchronous", options.synchronous.as_str())?;
+
+        debug!("Opened database at {:?} with {:?}", path, options);
 
         let db = Self { conn };
         db.run_migrations()?;
@@ -73,11 +435,16 @@ impl Database {
     }
 
     /// Open an in-memory database (for testing).
+    ///
+    /// Runs the same migrations as a file database. The data lives exactly as
+    /// long as this handle, which owns the only connection, so share it as
+    /// `Arc<Mutex<Database>>` like a file database rather than reopening.
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
 
         // Enable foreign keys
         conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
 
         debug!("Opened in-memory database");
 
@@ -87,54 +454,99 @@ impl Database {
         Ok(db)
     }
 
+    /// Open an existing database for reading only, e.g. to inspect the file
+    /// of a running app.
+    ///
+    /// Nothing is created or migrated, and writes through this handle fail.
+    /// Fails if the file doesn't exist or has a schema newer than this build.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .with_context(|| format!("Failed to open database at {:?} read-only", path))?;
+
+        // Also refuses writes to attached and temp databases
+        conn.pragma_update(None, "query_only", "ON")?;
+        conn.busy_timeout(DbOptions::default().busy_timeout)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+        let db = Self { conn };
+        let version = db
+            .schema_version()
+            .with_context(|| format!("{:?} is not an mcpmux database", path))?;
+        let latest = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
+        if version > latest {
+            anyhow::bail!(
+                "Database schema version {} is newer than this build supports ({})",
+                version,
+                latest
+            );
+        }
+
+        debug!("Opened database at {:?} read-only", path);
+        Ok(db)
+    }
+
+    /// Whether this handle was opened with [`Database::open_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.conn.is_readonly(DatabaseName::Main).unwrap_or(false)
+    }
+
     /// Run all pending database migrations.
     fn run_migrations(&self) -> Result<()> {
+        self.apply_migrations(MIGRATIONS)
+    }
+
+    /// Apply every migration in `migrations` newer than the current schema version.
+    ///
+    /// Runs in one exclusive transaction: either all pending migrations are
+    /// applied, or none are. Safe to call repeatedly.
+    fn apply_migrations(&self, migrations: &[Migration]) -> Result<()> {
+        // Exclusive lock so concurrent openers apply migrations one at a time
+        let tx = Transaction::new_unchecked(&self.conn, TransactionBehavior::Exclusive)?;
+
         // First, ensure the schema_migrations table exists
         self.ensure_migrations_table()?;
 
-        // Get current schema version
-        let current_version = self.get_schema_version();
+        // Read the version under the lock; another process may have just migrated
+        let current_version = self.schema_version()?;
 
         info!(
             "Current database schema version: {}, latest available: {}",
             current_version,
-            MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+            migrations.last().map(|m| m.version).unwrap_or(0)
         );
 
         // Run all migrations that haven't been applied yet
-        for migration in MIGRATIONS {
+        for migration in migrations {
             if migration.version > current_version {
                 info!(
                     "Running migration {} ({})...",
                     migration.version, migration.name
                 );
 
-                // Run migration in a transaction
-                let tx = self.conn.unchecked_transaction()?;
+                let result = self.conn.execute_batch(migration.sql).and_then(|_| {
+                    // Record that this migration was applied
+                    self.conn.execute(
+                        "INSERT OR REPLACE INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, datetime('now'))",
+                        params![migration.version, migration.name],
+                    )
+                });
 
-                if let Err(e) = self.conn.execute_batch(migration.sql) {
-                    tracing::error!(
+                if let Err(e) = result {
+                    error!(
                         "Migration {} ({}) failed with error: {}",
-                        migration.version,
-                        migration.name,
-                        e
+                        migration.version, migration.name, e
                     );
-                    return Err(anyhow::anyhow!(
-                        "Failed to run migration {} ({}): {}",
-                        migration.version,
-                        migration.name,
-                        e
-                    ));
+                    // Dropping the transaction rolls back the whole run
+                    return Err(DatabaseError::MigrationFailed {
+                        version: migration.version,
+                        source: e,
+                    }
+                    .into());
                 }
 
-                // Record that this migration was applied
-                self.conn.execute(
-                    "INSERT OR REPLACE INTO schema_migrations (version, name, applied_at) VALUES (?1, ?2, datetime('now'))",
-                    rusqlite::params![migration.version, migration.name],
-                )?;
-
-                tx.commit()?;
-
                 info!(
                     "Migration {} ({}) completed successfully",
                     migration.version, migration.name
@@ -142,6 +554,10 @@ impl Database {
             }
         }
 
+        tx.commit()?;
+
+        // Cached statements keep the column layout they were compiled against
+        self.conn.flush_prepared_statement_cache();
         Ok(())
     }
 
@@ -190,14 +606,316 @@ impl Database {
     }
 
     /// Get the current schema version (highest applied migration).
-    fn get_schema_version(&self) -> i64 {
+    pub fn schema_version(&self) -> Result<i64> {
+        let version = self.conn.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(version)
+    }
+
+    /// Check that `encryptor` uses the master key this database was encrypted with.
+    ///
+    /// The first call records the key fingerprint in the `meta` table; later calls
+    /// compare against it and fail with [`CryptoError::KeyMismatch`] so a wrong key
+    /// is reported once at startup instead of as per-credential decrypt failures.
+    pub fn verify_key_fingerprint(&self, encryptor: &FieldEncryptor) -> Result<()> {
+        let stored: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![KEY_FINGERPRINT_META_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match stored {
+            Some(fingerprint) if fingerprint == hex::encode(encryptor.key_fingerprint()) => Ok(()),
+            Some(_) => Err(CryptoError::KeyMismatch.into()),
+            None => {
+                Self::store_key_fingerprint(&self.conn, encryptor)?;
+                info!("Recorded master key fingerprint for database");
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that the master key was created by the same install as this database.
+    ///
+    /// `key_install_id` comes from
+    /// [`MasterKeyProvider::install_id`](crate::MasterKeyProvider::install_id);
+    /// providers that don't record one skip the check. The first call records the
+    /// id; later calls fail with [`CryptoError::KeyBelongsToDifferentInstall`] if it
+    /// differs, unless `allow_migration` is set, in which case the database is
+    /// rebound to the key's install.
+    pub fn verify_install_id(
+        &self,
+        key_install_id: Option<&str>,
+        allow_migration: bool,
+    ) -> Result<()> {
+        let Some(key_install_id) = key_install_id else {
+            return Ok(());
+        };
+        let stored: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![INSTALL_ID_META_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match stored {
+            Some(stored) if stored == key_install_id => return Ok(()),
+            Some(stored) if !allow_migration => {
+                return Err(CryptoError::KeyBelongsToDifferentInstall {
+                    key_install_id: key_install_id.to_string(),
+                    database_install_id: stored,
+                }
+                .into());
+            }
+            Some(stored) => warn!(
+                "Rebinding database from install {} to install {}",
+                stored, key_install_id
+            ),
+            None => info!("Recorded install id for database"),
+        }
+
+        self.conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![INSTALL_ID_META_KEY, key_install_id],
+        )?;
+        Ok(())
+    }
+
+    /// Record `encryptor`'s key fingerprint, replacing any previous one.
+    ///
+    /// Takes a connection so it can run inside a caller's transaction (key rotation).
+    pub(crate) fn store_key_fingerprint(
+        conn: &Connection,
+        encryptor: &FieldEncryptor,
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![
+                KEY_FINGERPRINT_META_KEY,
+                hex::encode(encryptor.key_fingerprint())
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Write a consistent snapshot of the database to `dest`.
+    ///
+    /// Uses SQLite's online backup API, so it is safe while other connections
+    /// (e.g. the gateway) keep writing. An existing file at `dest` is overwritten.
+    pub fn backup_to(&self, dest: &Path) -> Result<()> {
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create backup directory: {:?}", parent))?;
+        }
+
         self.conn
+            .backup(DatabaseName::Main, dest, None)
+            .with_context(|| format!("Failed to back up database to {:?}", dest))?;
+
+        info!("Database backed up to {:?}", dest);
+        Ok(())
+    }
+
+    /// Replace the database contents with the backup at `src`.
+    ///
+    /// The backup must be an mcpmux database no newer than this build and must
+    /// have been encrypted with the same master key as `encryptor`; otherwise
+    /// nothing is changed and [`CryptoError::KeyMismatch`] (or a validation
+    /// error) is returned. Older backups are migrated after restoring.
+    pub fn restore_from(&mut self, src: &Path, encryptor: &FieldEncryptor) -> Result<()> {
+        Self::validate_backup(src, encryptor)?;
+
+        self.conn
+            .restore(
+                DatabaseName::Main,
+                src,
+                None::<fn(rusqlite::backup::Progress)>,
+            )
+            .with_context(|| format!("Failed to restore database from {:?}", src))?;
+        self.conn.flush_prepared_statement_cache();
+
+        self.run_migrations()?;
+
+        info!("Database restored from {:?}", src);
+        Ok(())
+    }
+
+    /// Check that `src` is an mcpmux database encrypted with `encryptor`'s key.
+    fn validate_backup(src: &Path, encryptor: &FieldEncryptor) -> Result<()> {
+        let conn = Connection::open_with_flags(src, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open backup at {:?}", src))?;
+
+        let version: i64 = conn
             .query_row(
                 "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
                 [],
                 |row| row.get(0),
             )
-            .unwrap_or(0)
+            .with_context(|| format!("{:?} is not an mcpmux database", src))?;
+        let latest = MIGRATIONS.last().map(|m| m.version).unwrap_or(0);
+        if version == 0 || version > latest {
+            anyhow::bail!(
+                "Backup schema version {} is not supported (expected 1 to {})",
+                version,
+                latest
+            );
+        }
+
+        let fingerprint: Option<String> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![KEY_FINGERPRINT_META_KEY],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Backup has no key fingerprint table")?;
+
+        match fingerprint {
+            Some(fingerprint) if fingerprint == hex::encode(encryptor.key_fingerprint()) => Ok(()),
+            Some(_) => Err(CryptoError::KeyMismatch.into()),
+            None => anyhow::bail!("Backup has no key fingerprint"),
+        }
+    }
+
+    /// Check the database file for corruption and dangling foreign keys.
+    ///
+    /// Meant to run after an unclean shutdown so the user can decide whether to
+    /// restore from a backup. Reads the whole file, so avoid calling it on
+    /// every startup.
+    pub fn integrity_check(&self) -> Result<IntegrityReport> {
+        let integrity_errors = self
+            .conn
+            .prepare("PRAGMA integrity_check")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|message| message != "ok")
+            .collect();
+
+        let foreign_key_violations = self
+            .conn
+            .prepare("PRAGMA foreign_key_check")?
+            .query_map([], |row| {
+                Ok(ForeignKeyViolation {
+                    table: row.get(0)?,
+                    rowid: row.get(1)?,
+                    parent: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let report = IntegrityReport {
+            integrity_errors,
+            foreign_key_violations,
+        };
+        if report.is_ok() {
+            debug!("Database integrity check passed");
+        } else {
+            warn!(
+                "Database integrity check found {} integrity errors and {} foreign key violations",
+                report.integrity_errors.len(),
+                report.foreign_key_violations.len()
+            );
+        }
+        Ok(report)
+    }
+
+    /// Rebuild the database file, reclaiming all free pages.
+    ///
+    /// Also applies a changed [`DbOptions::auto_vacuum`] to an existing file.
+    /// Takes an exclusive lock for the whole rebuild, so every other
+    /// connection waits (or fails after its busy timeout), and may take a
+    /// while on a large database: it rewrites the entire file and needs up to
+    /// twice its size in free disk space. Run it rarely and off the request
+    /// path; prefer [`Database::incremental_vacuum`] for periodic trimming.
+    pub fn vacuum(&self) -> Result<VacuumReport> {
+        let size_before = self.size()?;
+        self.conn
+            .execute_batch("VACUUM")
+            .context("Failed to vacuum database")?;
+        let report = self.vacuum_report(size_before)?;
+
+        info!(
+            "Vacuumed database: {} -> {} bytes",
+            report.size_before, report.size_after
+        );
+        Ok(report)
+    }
+
+    /// Return up to `pages` free pages to the filesystem (all of them if
+    /// `pages` is 0).
+    ///
+    /// Cheap and safe to run periodically, but only works on a database
+    /// created with [`AutoVacuum::Incremental`]; for any other mode this
+    /// fails without changing anything. Call repeatedly until
+    /// [`VacuumReport::free_pages`] reaches zero to trim in small steps.
+    pub fn incremental_vacuum(&self, pages: u32) -> Result<VacuumReport> {
+        let mode = self.auto_vacuum()?;
+        if mode != AutoVacuum::Incremental {
+            anyhow::bail!(
+                "Incremental vacuum needs auto_vacuum=INCREMENTAL, database uses {}",
+                mode.as_str()
+            );
+        }
+
+        let size_before = self.size()?;
+        // Step it until done; run as a batch it frees only part of the pages
+        let mut stmt = self
+            .conn
+            .prepare(&format!("PRAGMA incremental_vacuum({})", pages))?;
+        let mut rows = stmt.query([])?;
+        while rows
+            .next()
+            .context("Failed to run incremental vacuum")?
+            .is_some()
+        {}
+        drop(rows);
+        drop(stmt);
+        let report = self.vacuum_report(size_before)?;
+
+        debug!(
+            "Incremental vacuum: {} -> {} bytes, {} free pages left",
+            report.size_before, report.size_after, report.free_pages
+        );
+        Ok(report)
+    }
+
+    /// The auto-vacuum mode the database file currently uses.
+    pub fn auto_vacuum(&self) -> Result<AutoVacuum> {
+        let value: i64 = self
+            .conn
+            .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+        AutoVacuum::from_pragma(value)
+    }
+
+    /// Current database size in bytes.
+    fn size(&self) -> Result<u64> {
+        let pragma = |name: &str| -> Result<u64> {
+            Ok(self
+                .conn
+                .query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))?)
+        };
+        Ok(pragma("page_count")? * pragma("page_size")?)
+    }
+
+    fn vacuum_report(&self, size_before: u64) -> Result<VacuumReport> {
+        Ok(VacuumReport {
+            size_before,
+            size_after: self.size()?,
+            free_pages: self
+                .conn
+                .query_row("PRAGMA freelist_count", [], |row| row.get(0))?,
+        })
     }
 
     /// Get a reference to the underlying connection.
@@ -217,6 +935,13 @@ impl Database {
     }
 }
 
+/// SQLCipher raw-key literal (`x'<hex>'`), so the key is used as-is instead of
+/// being run through SQLCipher's passphrase KDF.
+#[cfg(feature = "sqlcipher")]
+pub(crate) fn sqlcipher_key(key: &[u8; KEY_SIZE]) -> zeroize::Zeroizing<String> {
+    zeroize::Zeroizing::new(format!("x'{}'", hex::encode(key)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -268,4 +993,433 @@ mod tests {
 
         assert_eq!(name, "Test");
     }
+
+    #[test]
+    fn test_open_applies_options() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let pragma = |db: &Database, name: &str| -> String {
+            db.connection()
+                .query_row(&format!("PRAGMA {}", name), [], |row| {
+                    row.get::<_, rusqlite::types::Value>(0)
+                })
+                .map(|v| match v {
+                    rusqlite::types::Value::Text(s) => s.to_lowercase(),
+                    rusqlite::types::Value::Integer(i) => i.to_string(),
+                    other => format!("{:?}", other),
+                })
+                .unwrap()
+        };
+
+        // Defaults: WAL, synchronous=NORMAL (1), 5s busy timeout
+        let db = Database::open(&temp_dir.path().join("default.db")).unwrap();
+        assert_eq!(pragma(&db, "journal_mode"), "wal");
+        assert_eq!(pragma(&db, "synchronous"), "1");
+        assert_eq!(pragma(&db, "busy_timeout"), "5000");
+
+        let options = DbOptions {
+            busy_timeout: Duration::from_millis(250),
+            journal_mode: JournalMode::Delete,
+            synchronous: Synchronous::Full,
+            auto_vacuum: Some(AutoVacuum::Incremental),
+        };
+        let db = Database::open_with_options(&temp_dir.path().join("custom.db"), options).unwrap();
+        assert_eq!(pragma(&db, "journal_mode"), "delete");
+        assert_eq!(pragma(&db, "synchronous"), "2");
+        assert_eq!(pragma(&db, "busy_timeout"), "250");
+        assert_eq!(db.auto_vacuum().unwrap(), AutoVacuum::Incremental);
+    }
+
+    #[tokio::test]
+    async fn test_open_read_only() {
+        use crate::SqliteSpaceRepository;
+        use mcpmux_core::{Space, SpaceRepository, StorageError};
+        use std::sync::Arc;
+        use tokio::sync::Mutex;
+
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("live.db");
+        assert!(Database::open_read_only(&db_path).is_err());
+        assert!(!db_path.exists());
+
+        let writer =
+            SqliteSpaceRepository::new(Arc::new(Mutex::new(Database::open(&db_path).unwrap())));
+        writer.create(&Space::new("Work")).await.unwrap();
+
+        let db = Database::open_read_only(&db_path).unwrap();
+        assert!(db.is_read_only());
+        let reader = SqliteSpaceRepository::new(Arc::new(Mutex::new(db)));
+        assert_eq!(reader.list().await.unwrap().len(), 2);
+
+        assert!(matches!(
+            reader.create(&Space::new("Home")).await,
+            Err(StorageError::ReadOnly)
+        ));
+
+        // The app keeps writing while the reader is open, and the reader sees it
+        writer.create(&Space::new("Home")).await.unwrap();
+        assert_eq!(reader.list().await.unwrap().len(), 3);
+    }
+
+    /// Fill the database with `rows` padded rows in a scratch table.
+    fn fill(db: &Database, rows: usize) {
+        db.connection()
+            .execute_batch("CREATE TABLE IF NOT EXISTS churn (data BLOB NOT NULL)")
+            .unwrap();
+        db.transaction(|conn| {
+            for _ in 0..rows {
+                conn.execute("INSERT INTO churn (data) VALUES (zeroblob(4096))", [])?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_vacuum_reclaims_space() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Database::open(&temp_dir.path().join("vacuum.db")).unwrap();
+        assert_eq!(db.auto_vacuum().unwrap(), AutoVacuum::None);
+        assert!(db.incremental_vacuum(0).is_err());
+
+        fill(&db, 200);
+        db.connection().execute("DELETE FROM churn", []).unwrap();
+
+        let report = db.vacuum().unwrap();
+        assert!(report.reclaimed() > 200 * 4096, "{:?}", report);
+        assert_eq!(report.free_pages, 0);
+    }
+
+    #[test]
+    fn test_incremental_vacuum() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("incremental.db");
+        let options = DbOptions {
+            auto_vacuum: Some(AutoVacuum::Incremental),
+            ..Default::default()
+        };
+        let db = Database::open_with_options(&path, options).unwrap();
+        fill(&db, 100);
+        db.connection().execute("DELETE FROM churn", []).unwrap();
+
+        // Trim in steps until no free pages are left
+        let first = db.incremental_vacuum(10).unwrap();
+        assert!(first.reclaimed() > 0 && first.free_pages > 0, "{:?}", first);
+        let rest = db.incremental_vacuum(0).unwrap();
+        assert_eq!(rest.free_pages, 0);
+        assert!(rest.size_after < first.size_after);
+    }
+
+    #[test]
+    fn test_vacuum_switches_existing_file_to_incremental() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("existing.db");
+        drop(Database::open(&path).unwrap());
+
+        let options = DbOptions {
+            auto_vacuum: Some(AutoVacuum::Incremental),
+            ..Default::default()
+        };
+        let db = Database::open_with_options(&path, options).unwrap();
+        assert_eq!(db.auto_vacuum().unwrap(), AutoVacuum::None);
+
+        db.vacuum().unwrap();
+        assert_eq!(db.auto_vacuum().unwrap(), AutoVacuum::Incremental);
+    }
+
+    #[test]
+    fn test_schema_version_is_latest() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(
+            db.schema_version().unwrap(),
+            MIGRATIONS.last().unwrap().version
+        );
+
+        // Re-running is a no-op
+        db.run_migrations().unwrap();
+        assert_eq!(
+            db.schema_version().unwrap(),
+            MIGRATIONS.last().unwrap().version
+        );
+    }
+
+    #[test]
+    fn test_failed_migration_leaves_database_untouched() {
+        let db = Database::open_in_memory().unwrap();
+        let before = db.schema_version().unwrap();
+
+        let migrations = [
+            Migration {
+                version: before + 1,
+                name: "good",
+                sql: "CREATE TABLE migration_probe (id INTEGER PRIMARY KEY);",
+            },
+            Migration {
+                version: before + 2,
+                name: "bad",
+                sql: "ALTER TABLE no_such_table ADD COLUMN x TEXT;",
+            },
+        ];
+        let err = db.apply_migrations(&migrations).unwrap_err();
+        match err.downcast_ref::<DatabaseError>() {
+            Some(DatabaseError::MigrationFailed { version, .. }) => {
+                assert_eq!(*version, before + 2)
+            }
+            other => panic!("expected MigrationFailed, got {:?}", other),
+        }
+
+        // Neither migration of the failed run was kept
+        assert_eq!(db.schema_version().unwrap(), before);
+        let probe_exists: bool = db
+            .connection()
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE name = 'migration_probe'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!probe_exists);
+
+        // The database is still usable and later migrations apply cleanly
+        db.apply_migrations(&migrations[..1]).unwrap();
+        assert_eq!(db.schema_version().unwrap(), before + 1);
+    }
+
+    #[test]
+    fn test_migrations_invalidate_cached_statements() {
+        let db = Database::open_in_memory().unwrap();
+        let before = db.schema_version().unwrap();
+        let column_count = |db: &Database| {
+            db.connection()
+                .prepare_cached("SELECT * FROM migration_probe")
+                .unwrap()
+                .column_count()
+        };
+
+        db.apply_migrations(&[Migration {
+            version: before + 1,
+            name: "create",
+            sql: "CREATE TABLE migration_probe (id INTEGER PRIMARY KEY);",
+        }])
+        .unwrap();
+        assert_eq!(column_count(&db), 1);
+
+        db.apply_migrations(&[Migration {
+            version: before + 2,
+            name: "alter",
+            sql: "ALTER TABLE migration_probe ADD COLUMN name TEXT;",
+        }])
+        .unwrap();
+        assert_eq!(column_count(&db), 2);
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_path = temp_dir.path().join("backups").join("snapshot.db");
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        let mut db = Database::open(&temp_dir.path().join("live.db")).unwrap();
+        db.verify_key_fingerprint(&encryptor).unwrap();
+        db.connection()
+            .execute(
+                "INSERT INTO spaces (id, name, created_at, updated_at) VALUES ('before', 'Before', datetime('now'), datetime('now'))",
+                [],
+            )
+            .unwrap();
+
+        db.backup_to(&backup_path).unwrap();
+
+        // Changes after the backup are discarded by the restore
+        db.connection()
+            .execute(
+                "INSERT INTO spaces (id, name, created_at, updated_at) VALUES ('after', 'After', datetime('now'), datetime('now'))",
+                [],
+            )
+            .unwrap();
+
+        db.restore_from(&backup_path, &encryptor).unwrap();
+        let ids: Vec<String> = db
+            .connection()
+            .prepare("SELECT id FROM spaces WHERE id IN ('before', 'after')")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(ids, vec!["before".to_string()]);
+    }
+
+    #[test]
+    fn test_restore_rejects_wrong_key_and_foreign_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let backup_path = temp_dir.path().join("snapshot.db");
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        let source = Database::open_in_memory().unwrap();
+        source.verify_key_fingerprint(&encryptor).unwrap();
+        source.backup_to(&backup_path).unwrap();
+
+        let mut db = Database::open_in_memory().unwrap();
+        let other_key = crate::crypto::generate_master_key().unwrap();
+        let other = FieldEncryptor::new(&other_key).unwrap();
+        let err = db.restore_from(&backup_path, &other).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::KeyMismatch)
+        );
+
+        // A SQLite file that isn't an mcpmux database
+        let foreign_path = temp_dir.path().join("foreign.db");
+        Connection::open(&foreign_path)
+            .unwrap()
+            .execute_batch("CREATE TABLE t (x INTEGER);")
+            .unwrap();
+        assert!(db.restore_from(&foreign_path, &encryptor).is_err());
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_open_encrypted() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("encrypted.db");
+        let key = crate::crypto::generate_master_key().unwrap();
+
+        let db = Database::open_encrypted(&db_path, &key).unwrap();
+        db.connection()
+            .execute(
+                "INSERT INTO spaces (id, name, created_at, updated_at) VALUES ('secret-space', 'Secret', datetime('now'), datetime('now'))",
+                [],
+            )
+            .unwrap();
+        drop(db);
+
+        // Nothing readable on disk, and the plain and wrong-key paths fail
+        let raw = std::fs::read(&db_path).unwrap();
+        assert!(!raw.windows(12).any(|w| w == b"secret-space"));
+        assert!(Database::open(&db_path).is_err());
+        let other_key = crate::crypto::generate_master_key().unwrap();
+        let err = Database::open_encrypted(&db_path, &other_key)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::KeyMismatch)
+        );
+
+        let db = Database::open_encrypted(&db_path, &key).unwrap();
+        let name: String = db
+            .connection()
+            .query_row(
+                "SELECT name FROM spaces WHERE id = 'secret-space'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(name, "Secret");
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_encrypt_existing() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("plain.db");
+        let key = crate::crypto::generate_master_key().unwrap();
+
+        let db = Database::open(&db_path).unwrap();
+        db.connection()
+            .execute(
+                "INSERT INTO spaces (id, name, created_at, updated_at) VALUES ('kept', 'Kept', datetime('now'), datetime('now'))",
+                [],
+            )
+            .unwrap();
+        drop(db);
+
+        Database::encrypt_existing(&db_path, &key).unwrap();
+
+        assert!(Database::open(&db_path).is_err());
+        let db = Database::open_encrypted(&db_path, &key).unwrap();
+        let name: String = db
+            .connection()
+            .query_row("SELECT name FROM spaces WHERE id = 'kept'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(name, "Kept");
+    }
+
+    #[test]
+    fn test_integrity_check() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.integrity_check().unwrap().is_ok());
+
+        // Sneak in a credential for a space that doesn't exist
+        db.connection()
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO credentials (id, space_id, server_id, credential_type, credential_value, created_at, updated_at)
+                 VALUES ('orphan', 'missing-space', 'github', 'api_key', 'x', datetime('now'), datetime('now'));
+                 PRAGMA foreign_keys = ON;",
+            )
+            .unwrap();
+
+        let report = db.integrity_check().unwrap();
+        assert!(!report.is_ok());
+        assert!(report.integrity_errors.is_empty());
+        assert_eq!(
+            report.foreign_key_violations,
+            vec![ForeignKeyViolation {
+                table: "credentials".to_string(),
+                rowid: Some(1),
+                parent: "spaces".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_verify_install_id() {
+        let db = Database::open_in_memory().unwrap();
+
+        // Providers without an install id skip the check
+        db.verify_install_id(None, false).unwrap();
+
+        // First call records the id, second one matches it
+        db.verify_install_id(Some("install-a"), false).unwrap();
+        db.verify_install_id(Some("install-a"), false).unwrap();
+
+        let err = db.verify_install_id(Some("install-b"), false).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::KeyBelongsToDifferentInstall {
+                key_install_id: "install-b".to_string(),
+                database_install_id: "install-a".to_string(),
+            })
+        );
+
+        // The override rebinds the database to the key's install
+        db.verify_install_id(Some("install-b"), true).unwrap();
+        db.verify_install_id(Some("install-b"), false).unwrap();
+        assert!(db.verify_install_id(Some("install-a"), false).is_err());
+    }
+
+    #[test]
+    fn test_verify_key_fingerprint() {
+        let db = Database::open_in_memory().unwrap();
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = FieldEncryptor::new(&key).unwrap();
+
+        // First call records the fingerprint, second one matches it
+        db.verify_key_fingerprint(&encryptor).unwrap();
+        db.verify_key_fingerprint(&encryptor).unwrap();
+
+        let other_key = crate::crypto::generate_master_key().unwrap();
+        let other = FieldEncryptor::new(&other_key).unwrap();
+        let err = db.verify_key_fingerprint(&other).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CryptoError>(),
+            Some(&CryptoError::KeyMismatch)
+        );
+    }
 }
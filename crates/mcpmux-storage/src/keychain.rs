@@ -4,8 +4,15 @@
 //! - Windows: Credential Manager
 //! - macOS: Keychain
 //! - Linux: Secret Service (GNOME Keyring, KWallet)
+//!
+//! On macOS the master key can additionally be gated behind user presence
+//! (Touch ID or the device password), see [`KeychainKeyProvider::with_biometric_gate`].
+
+use std::fmt;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use keyring::Entry;
 use mcpmux_core::branding;
 use tracing::{debug, info, warn};
@@ -19,6 +26,100 @@ const MASTER_KEY_NAME: &str = "master-encryption-key";
 /// Key name for the JWT signing secret.
 const JWT_SIGNING_SECRET_NAME: &str = "jwt-signing-secret";
 
+/// Errors from accessing the OS keychain.
+///
+/// Returned wrapped in `anyhow::Error`; use `downcast_ref::<KeychainError>()` to match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeychainError {
+    /// The user dismissed the Touch ID / device password prompt.
+    UserCancelled,
+    /// Windows can no longer decrypt the DPAPI-protected master key, typically
+    /// because an administrator reset the account password. See
+    /// `DpapiKeyProvider::reset`.
+    DpapiUnrecoverable,
+}
+
+impl fmt::Display for KeychainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeychainError::UserCancelled => write!(f, "Keychain access was cancelled by the user"),
+            KeychainError::DpapiUnrecoverable => write!(
+                f,
+                "Windows can no longer decrypt the master key. This usually happens after an \
+                 administrator resets the account password. Restoring the old password makes \
+                 the key readable again; otherwise a new key must be generated and stored \
+                 credentials entered again"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KeychainError {}
+
+/// Naming and placement of a keychain entry.
+///
+/// Unset fields keep the built-in defaults, so an empty config finds the
+/// entries of existing installs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeychainConfig {
+    /// Service name (defaults to the app's keychain service).
+    pub service: Option<String>,
+    /// Account name (defaults to the entry's built-in key name).
+    pub account: Option<String>,
+    /// Label shown in tools like Seahorse. Linux only; applied when the entry is created.
+    pub label: Option<String>,
+    /// Secret Service collection to store the entry in, created if missing. Linux only.
+    pub collection: Option<String>,
+}
+
+impl KeychainConfig {
+    fn service(&self) -> &str {
+        self.service
+            .as_deref()
+            .unwrap_or(branding::KEYCHAIN_SERVICE)
+    }
+
+    fn account<'a>(&'a self, default_account: &'a str) -> &'a str {
+        self.account.as_deref().unwrap_or(default_account)
+    }
+
+    /// Create the keyring entry described by this config.
+    fn entry(&self, default_account: &str) -> Result<Entry> {
+        let (service, account) = (self.service(), self.account(default_account));
+
+        #[cfg(target_os = "linux")]
+        if self.label.is_some() || self.collection.is_some() {
+            let mut credential = keyring::secret_service::SsCredential::new_with_target(
+                self.collection.as_deref(),
+                service,
+                account,
+            )
+            .context("Failed to create Secret Service credential")?;
+            if let Some(label) = &self.label {
+                credential.label = label.clone();
+            }
+            return Ok(Entry::new_with_credential(Box::new(credential)));
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        if self.label.is_some() || self.collection.is_some() {
+            warn!("Keychain label and collection are only supported on Linux; ignoring them");
+        }
+
+        Entry::new(service, account).context("Failed to create keychain entry")
+    }
+}
+
+/// Access control applied to the master key's keychain item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeychainAccessControl {
+    /// Readable by the app without prompting.
+    #[default]
+    None,
+    /// Reading requires user presence: Touch ID or the device password (macOS only).
+    UserPresence,
+}
+
 /// Trait for providing the master encryption key.
 ///
 /// This abstraction allows for different key storage mechanisms:
@@ -33,6 +134,109 @@ pub trait MasterKeyProvider: Send + Sync {
 
     /// Delete the master key (for testing or reset).
     fn delete_key(&self) -> Result<()>;
+
+    /// Replace the stored master key (used by key rotation).
+    ///
+    /// Only call this once all data encrypted with the old key has been
+    /// re-encrypted, otherwise that data becomes unreadable.
+    fn replace_key(&self, new_key: &[u8; KEY_SIZE]) -> Result<()>;
+
+    /// Id of the install the stored key was created for, if this provider
+    /// records one.
+    ///
+    /// Compared against the database by
+    /// [`Database::verify_install_id`](crate::Database::verify_install_id).
+    fn install_id(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Short name for logs (the implementing type's name by default).
+    fn name(&self) -> &'static str {
+        let full = std::any::type_name::<Self>();
+        full.rsplit("::").next().unwrap_or(full)
+    }
+}
+
+/// Master key provider that tries other providers in order.
+///
+/// The first provider whose `get_or_create_key` succeeds wins and is used for
+/// every later call, so `key_exists`, `delete_key` and `replace_key` act on the
+/// backend that actually holds the key.
+pub struct CompositeKeyProvider {
+    providers: Vec<Box<dyn MasterKeyProvider>>,
+    active: std::sync::Mutex<Option<usize>>,
+}
+
+impl CompositeKeyProvider {
+    /// Create a provider trying `providers` in order.
+    pub fn new(providers: Vec<Box<dyn MasterKeyProvider>>) -> Self {
+        Self {
+            providers,
+            active: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Name of the provider that supplied the key, once one has.
+    pub fn active_provider(&self) -> Option<&'static str> {
+        self.active().map(|provider| provider.name())
+    }
+
+    fn active(&self) -> Option<&dyn MasterKeyProvider> {
+        let index = (*self.active.lock().unwrap())?;
+        Some(self.providers[index].as_ref())
+    }
+}
+
+impl MasterKeyProvider for CompositeKeyProvider {
+    fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+        let mut active = self.active.lock().unwrap();
+        if let Some(index) = *active {
+            return self.providers[index].get_or_create_key();
+        }
+
+        let mut errors = Vec::new();
+        for (index, provider) in self.providers.iter().enumerate() {
+            match provider.get_or_create_key() {
+                Ok(key) => {
+                    info!("Master key provided by {}", provider.name());
+                    *active = Some(index);
+                    return Ok(key);
+                }
+                Err(e) => {
+                    warn!("{} unavailable: {}", provider.name(), e);
+                    errors.push(format!("{}: {}", provider.name(), e));
+                }
+            }
+        }
+
+        anyhow::bail!("No master key provider available ({})", errors.join("; "))
+    }
+
+    fn key_exists(&self) -> bool {
+        match self.active() {
+            Some(provider) => provider.key_exists(),
+            None => self.providers.iter().any(|provider| provider.key_exists()),
+        }
+    }
+
+    fn delete_key(&self) -> Result<()> {
+        self.active()
+            .context("No master key provider selected yet; call get_or_create_key first")?
+            .delete_key()
+    }
+
+    fn replace_key(&self, new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        self.active()
+            .context("No master key provider selected yet; call get_or_create_key first")?
+            .replace_key(new_key)
+    }
+
+    fn install_id(&self) -> Result<Option<String>> {
+        match self.active() {
+            Some(provider) => provider.install_id(),
+            None => Ok(None),
+        }
+    }
 }
 
 /// OS Keychain-based master key provider.
@@ -40,45 +244,222 @@ pub trait MasterKeyProvider: Send + Sync {
 /// Stores the master key in the platform's native secure storage.
 pub struct KeychainKeyProvider {
     entry: Entry,
+    service: String,
+    key_name: String,
+    access_control: KeychainAccessControl,
+    /// Key read through the user-presence gate, so the prompt appears once per launch.
+    unlocked: std::sync::Mutex<Option<Zeroizing<[u8; KEY_SIZE]>>>,
 }
 
 impl KeychainKeyProvider {
     /// Create a new keychain key provider.
     pub fn new() -> Result<Self> {
-        let entry = Entry::new(branding::KEYCHAIN_SERVICE, MASTER_KEY_NAME)
-            .context("Failed to create keychain entry")?;
+        Self::with_config(KeychainConfig::default())
+    }
+
+    /// Create a keychain key provider with custom entry naming.
+    pub fn with_config(config: KeychainConfig) -> Result<Self> {
+        Ok(Self {
+            entry: config.entry(MASTER_KEY_NAME)?,
+            service: config.service().to_string(),
+            key_name: config.account(MASTER_KEY_NAME).to_string(),
+            access_control: KeychainAccessControl::None,
+            unlocked: std::sync::Mutex::new(None),
+        })
+    }
 
-        Ok(Self { entry })
+    /// Create a provider whose key can only be read after Touch ID or the device
+    /// password (macOS only).
+    ///
+    /// The prompt appears on the first read per launch; cancelling it fails with
+    /// [`KeychainError::UserCancelled`]. A key stored without the gate is moved
+    /// behind it on first use. The gated item lives in the data protection
+    /// keychain, so the app must be signed with a keychain access group.
+    pub fn with_biometric_gate() -> Result<Self> {
+        Ok(Self::new()?.with_access_control(KeychainAccessControl::UserPresence))
+    }
+
+    /// Set the access control for the master key item.
+    pub fn with_access_control(mut self, access_control: KeychainAccessControl) -> Self {
+        self.access_control = access_control;
+        self
     }
 
     /// Create with a custom service and key name (for testing).
     #[cfg(test)]
     pub fn with_names(service: &str, key_name: &str) -> Result<Self> {
-        let entry = Entry::new(service, key_name).context("Failed to create keychain entry")?;
+        Self::with_config(KeychainConfig {
+            service: Some(service.to_string()),
+            account: Some(key_name.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Read the key through the user-presence gate, creating or moving it there
+    /// if needed.
+    fn get_or_create_gated_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+        let mut unlocked = self.unlocked.lock().unwrap();
+        if let Some(key) = unlocked.as_ref() {
+            return Ok(key.clone());
+        }
+
+        let key = match presence_gate::read(&self.service, &self.key_name)? {
+            Some(hex_key) => {
+                debug!("Retrieved existing master key through user-presence gate");
+                decode_hex_key(&hex_key)?
+            }
+            None => {
+                // Move a key stored before the gate was enabled, or create one
+                let key = match self.entry.get_password() {
+                    Ok(hex_key) => {
+                        info!("Moving master key behind user-presence gate");
+                        decode_hex_key(&hex_key)?
+                    }
+                    Err(keyring::Error::NoEntry) => {
+                        info!("No master key found, generating new key");
+                        Zeroizing::new(generate_master_key()?)
+                    }
+                    Err(e) => return Err(anyhow::anyhow!("Failed to access keychain: {}", e)),
+                };
+                presence_gate::write(&self.service, &self.key_name, &hex::encode(*key))?;
+                match self.entry.delete_credential() {
+                    Ok(()) | Err(keyring::Error::NoEntry) => {}
+                    Err(e) => warn!("Failed to remove ungated master key from keychain: {}", e),
+                }
+                key
+            }
+        };
+
+        *unlocked = Some(key.clone());
+        Ok(key)
+    }
+}
+
+/// Decode a hex-encoded master key as stored in the keychain.
+fn decode_hex_key(hex_key: &str) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+    let key_bytes = Zeroizing::new(hex::decode(hex_key).context("Invalid key format in keychain")?);
+
+    if key_bytes.len() != KEY_SIZE {
+        anyhow::bail!(
+            "Invalid key size in keychain: expected {}, got {}",
+            KEY_SIZE,
+            key_bytes.len()
+        );
+    }
+
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+    key.copy_from_slice(&key_bytes);
+    Ok(key)
+}
+
+/// Keychain items readable only after user presence (Touch ID / device password).
+#[cfg(target_os = "macos")]
+mod presence_gate {
+    use anyhow::Result;
+    use security_framework::base::Error;
+    use security_framework::item::{ItemClass, ItemSearchOptions};
+    use security_framework::passwords::{
+        delete_generic_password_options, generic_password, set_generic_password_options,
+        AccessControlOptions, PasswordOptions,
+    };
+
+    use super::KeychainError;
+
+    /// `errSecUserCanceled`
+    const ERR_SEC_USER_CANCELED: i32 = -128;
+    /// `errSecItemNotFound`
+    const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+    fn options(service: &str, account: &str) -> PasswordOptions {
+        let mut options = PasswordOptions::new_generic_password(service, account);
+        options.use_protected_keychain();
+        options
+    }
 
-        Ok(Self { entry })
+    fn map_error(e: Error) -> anyhow::Error {
+        if e.code() == ERR_SEC_USER_CANCELED {
+            KeychainError::UserCancelled.into()
+        } else {
+            anyhow::anyhow!("Failed to access keychain: {}", e)
+        }
+    }
+
+    /// Read the item, prompting the user. `None` if it doesn't exist.
+    pub(super) fn read(service: &str, account: &str) -> Result<Option<String>> {
+        match generic_password(options(service, account)) {
+            Ok(data) => {
+                Ok(Some(String::from_utf8(data).map_err(|_| {
+                    anyhow::anyhow!("Invalid key format in keychain")
+                })?))
+            }
+            Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(None),
+            Err(e) => Err(map_error(e)),
+        }
+    }
+
+    /// Create or update the item with user-presence access control.
+    pub(super) fn write(service: &str, account: &str, value: &str) -> Result<()> {
+        let mut options = options(service, account);
+        options.set_access_control_options(AccessControlOptions::USER_PRESENCE);
+        set_generic_password_options(value.as_bytes(), options).map_err(map_error)
+    }
+
+    /// Whether the item exists, without prompting.
+    pub(super) fn exists(service: &str, account: &str) -> bool {
+        ItemSearchOptions::new()
+            .class(ItemClass::generic_password())
+            .service(service)
+            .account(account)
+            .ignore_legacy_keychains()
+            .load_attributes(true)
+            .search()
+            .map(|results| !results.is_empty())
+            .unwrap_or(false)
+    }
+
+    pub(super) fn delete(service: &str, account: &str) -> Result<()> {
+        match delete_generic_password_options(options(service, account)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => Ok(()),
+            Err(e) => Err(map_error(e)),
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod presence_gate {
+    use anyhow::Result;
+
+    const UNSUPPORTED: &str = "User-presence gating of the master key is only supported on macOS";
+
+    pub(super) fn read(_service: &str, _account: &str) -> Result<Option<String>> {
+        anyhow::bail!(UNSUPPORTED)
+    }
+
+    pub(super) fn write(_service: &str, _account: &str, _value: &str) -> Result<()> {
+        anyhow::bail!(UNSUPPORTED)
+    }
+
+    pub(super) fn exists(_service: &str, _account: &str) -> bool {
+        false
+    }
+
+    pub(super) fn delete(_service: &str, _account: &str) -> Result<()> {
+        Ok(())
     }
 }
 
 impl MasterKeyProvider for KeychainKeyProvider {
     fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+        if self.access_control == KeychainAccessControl::UserPresence {
+            return self.get_or_create_gated_key();
+        }
+
         // Try to get existing key
         match self.entry.get_password() {
             Ok(hex_key) => {
                 debug!("Retrieved existing master key from keychain");
-                let key_bytes = hex::decode(&hex_key).context("Invalid key format in keychain")?;
-
-                if key_bytes.len() != KEY_SIZE {
-                    anyhow::bail!(
-                        "Invalid key size in keychain: expected {}, got {}",
-                        KEY_SIZE,
-                        key_bytes.len()
-                    );
-                }
-
-                let mut key = Zeroizing::new([0u8; KEY_SIZE]);
-                key.copy_from_slice(&key_bytes);
-                Ok(key)
+                decode_hex_key(&hex_key)
             }
             Err(keyring::Error::NoEntry) => {
                 // No key exists, generate a new one
@@ -101,10 +482,20 @@ impl MasterKeyProvider for KeychainKeyProvider {
     }
 
     fn key_exists(&self) -> bool {
+        if self.access_control == KeychainAccessControl::UserPresence
+            && presence_gate::exists(&self.service, &self.key_name)
+        {
+            return true;
+        }
         self.entry.get_password().is_ok()
     }
 
     fn delete_key(&self) -> Result<()> {
+        if self.access_control == KeychainAccessControl::UserPresence {
+            presence_gate::delete(&self.service, &self.key_name)?;
+            *self.unlocked.lock().unwrap() = None;
+        }
+
         match self.entry.delete_credential() {
             Ok(()) => {
                 info!("Master key deleted from keychain");
@@ -117,6 +508,21 @@ impl MasterKeyProvider for KeychainKeyProvider {
             Err(e) => Err(anyhow::anyhow!("Failed to delete key from keychain: {}", e)),
         }
     }
+
+    fn replace_key(&self, new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        if self.access_control == KeychainAccessControl::UserPresence {
+            presence_gate::write(&self.service, &self.key_name, &hex::encode(new_key))?;
+            *self.unlocked.lock().unwrap() = Some(Zeroizing::new(*new_key));
+            info!("Master key replaced behind user-presence gate");
+            return Ok(());
+        }
+
+        self.entry
+            .set_password(&hex::encode(new_key))
+            .context("Failed to store rotated master key in keychain")?;
+        info!("Master key replaced in keychain");
+        Ok(())
+    }
 }
 
 impl Default for KeychainKeyProvider {
@@ -132,6 +538,47 @@ impl Default for KeychainKeyProvider {
 /// Size of the JWT signing secret (32 bytes = 256 bits for HS256).
 pub const JWT_SECRET_SIZE: usize = 32;
 
+/// Default grace period for [`JwtSecretProvider::rotate`]: the lifetime of a
+/// gateway refresh token, so no issued token is cut short.
+pub const DEFAULT_JWT_ROTATION_GRACE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// A JWT signing secret retired by [`JwtSecretProvider::rotate`].
+///
+/// Tokens it signed are still accepted until `expires_at`.
+#[derive(Clone)]
+pub struct PreviousJwtSecret {
+    pub secret: Zeroizing<[u8; JWT_SECRET_SIZE]>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PreviousJwtSecret {
+    /// Storage form: `expires_at` as big-endian Unix seconds, then the secret.
+    pub(crate) fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(self.expires_at.timestamp().to_be_bytes().to_vec());
+        bytes.extend_from_slice(&*self.secret);
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 8 + JWT_SECRET_SIZE {
+            anyhow::bail!(
+                "Invalid previous JWT secret size: expected {}, got {}",
+                8 + JWT_SECRET_SIZE,
+                bytes.len()
+            );
+        }
+        let (expires_at, secret_bytes) = bytes.split_at(8);
+        let expires_at = i64::from_be_bytes(expires_at.try_into()?);
+        let mut secret = Zeroizing::new([0u8; JWT_SECRET_SIZE]);
+        secret.copy_from_slice(secret_bytes);
+        Ok(Self {
+            secret,
+            expires_at: DateTime::from_timestamp(expires_at, 0)
+                .context("Invalid previous JWT secret expiry")?,
+        })
+    }
+}
+
 /// Trait for providing the JWT signing secret.
 ///
 /// The JWT signing secret is used for:
@@ -144,8 +591,57 @@ pub trait JwtSecretProvider: Send + Sync {
     /// Check if a JWT signing secret exists.
     fn secret_exists(&self) -> bool;
 
-    /// Delete the JWT signing secret (for testing or reset).
+    /// Delete the JWT signing secret and any retired one (for testing or reset).
     fn delete_secret(&self) -> Result<()>;
+
+    /// Store `secret` in place of the current signing secret.
+    fn replace_secret(&self, secret: &[u8; JWT_SECRET_SIZE]) -> Result<()>;
+
+    /// Load the secret retired by the last rotation, whether or not it has expired.
+    fn load_previous_secret(&self) -> Result<Option<PreviousJwtSecret>>;
+
+    /// Store the retired secret, or clear it with `None`.
+    fn store_previous_secret(&self, previous: Option<&PreviousJwtSecret>) -> Result<()>;
+
+    /// Generate a new signing secret, keeping the current one valid for
+    /// verification for `grace`.
+    ///
+    /// Deleting and regenerating the secret logs every client out; with a
+    /// rotation, tokens signed by the old secret keep working until they expire
+    /// or the grace period ends. New tokens are signed with the returned secret.
+    fn rotate(&self, grace: Duration) -> Result<Zeroizing<[u8; JWT_SECRET_SIZE]>> {
+        let current = self.get_or_create_secret()?;
+        let expires_at = Utc::now()
+            + chrono::Duration::from_std(grace).context("JWT rotation grace period too long")?;
+
+        // Retire the current secret first so a failure in between never loses it
+        self.store_previous_secret(Some(&PreviousJwtSecret {
+            secret: current,
+            expires_at,
+        }))?;
+        let secret = Zeroizing::new(generate_jwt_secret()?);
+        self.replace_secret(&secret)?;
+
+        info!(
+            "JWT signing secret rotated, previous secret accepted until {}",
+            expires_at
+        );
+        Ok(secret)
+    }
+
+    /// The secret retired by the last [`rotate`](Self::rotate), while its grace
+    /// period lasts. An expired one is cleared.
+    fn previous_secret(&self) -> Result<Option<PreviousJwtSecret>> {
+        match self.load_previous_secret()? {
+            Some(previous) if previous.expires_at > Utc::now() => Ok(Some(previous)),
+            Some(_) => {
+                debug!("Previous JWT signing secret expired, clearing it");
+                self.store_previous_secret(None)?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 /// OS Keychain-based JWT signing secret provider.
@@ -153,23 +649,46 @@ pub trait JwtSecretProvider: Send + Sync {
 /// Stores the JWT signing secret in the platform's native secure storage.
 pub struct KeychainJwtSecretProvider {
     entry: Entry,
+    /// Secret retired by the last rotation, under `<account>-previous`.
+    previous_entry: Entry,
 }
 
 impl KeychainJwtSecretProvider {
     /// Create a new keychain JWT secret provider.
     pub fn new() -> Result<Self> {
-        let entry = Entry::new(branding::KEYCHAIN_SERVICE, JWT_SIGNING_SECRET_NAME)
+        Self::with_config(KeychainConfig::default())
+    }
+
+    /// Create a keychain JWT secret provider with custom entry naming.
+    pub fn with_config(config: KeychainConfig) -> Result<Self> {
+        let entry = config
+            .entry(JWT_SIGNING_SECRET_NAME)
             .context("Failed to create keychain entry for JWT secret")?;
+        let previous_account = format!("{}-previous", config.account(JWT_SIGNING_SECRET_NAME));
+        let previous_entry = KeychainConfig {
+            account: Some(previous_account),
+            ..config
+        }
+        .entry(JWT_SIGNING_SECRET_NAME)
+        .context("Failed to create keychain entry for previous JWT secret")?;
 
-        Ok(Self { entry })
+        Ok(Self {
+            entry,
+            previous_entry,
+        })
     }
 
     /// Create with a custom service and key name (for testing).
     #[cfg(test)]
     pub fn with_names(service: &str, key_name: &str) -> Result<Self> {
         let entry = Entry::new(service, key_name).context("Failed to create keychain entry")?;
+        let previous_entry = Entry::new(service, &format!("{}-previous", key_name))
+            .context("Failed to create keychain entry")?;
 
-        Ok(Self { entry })
+        Ok(Self {
+            entry,
+            previous_entry,
+        })
     }
 }
 
@@ -243,18 +762,58 @@ impl JwtSecretProvider for KeychainJwtSecretProvider {
         match self.entry.delete_credential() {
             Ok(()) => {
                 info!("[Keychain] JWT signing secret deleted from keychain");
-                Ok(())
             }
             Err(keyring::Error::NoEntry) => {
                 debug!("[Keychain] No JWT secret to delete");
-                Ok(())
             }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to delete JWT secret from keychain: {}",
+                    e
+                ))
+            }
+        }
+        self.store_previous_secret(None)
+    }
+
+    fn replace_secret(&self, secret: &[u8; JWT_SECRET_SIZE]) -> Result<()> {
+        self.entry.set_password(&hex::encode(secret)).map_err(|e| {
+            anyhow::anyhow!("Failed to store JWT signing secret in keychain: {}", e)
+        })?;
+        info!("[Keychain] JWT signing secret replaced");
+        Ok(())
+    }
+
+    fn load_previous_secret(&self) -> Result<Option<PreviousJwtSecret>> {
+        match self.previous_entry.get_password() {
+            Ok(hex_previous) => {
+                let bytes = Zeroizing::new(
+                    hex::decode(&hex_previous)
+                        .context("Invalid previous JWT secret format in keychain")?,
+                );
+                PreviousJwtSecret::from_bytes(&bytes).map(Some)
+            }
+            Err(keyring::Error::NoEntry) => Ok(None),
             Err(e) => Err(anyhow::anyhow!(
-                "Failed to delete JWT secret from keychain: {}",
+                "Failed to access keychain for previous JWT secret: {}",
                 e
             )),
         }
     }
+
+    fn store_previous_secret(&self, previous: Option<&PreviousJwtSecret>) -> Result<()> {
+        let result = match previous {
+            Some(previous) => self
+                .previous_entry
+                .set_password(&hex::encode(&*previous.to_bytes())),
+            None => match self.previous_entry.delete_credential() {
+                Err(keyring::Error::NoEntry) => Ok(()),
+                result => result,
+            },
+        };
+        result
+            .map_err(|e| anyhow::anyhow!("Failed to store previous JWT secret in keychain: {}", e))
+    }
 }
 
 impl Default for KeychainJwtSecretProvider {
@@ -273,10 +832,24 @@ pub fn generate_jwt_secret() -> Result<[u8; JWT_SECRET_SIZE]> {
     Ok(secret)
 }
 
+/// Generate a JWT signing secret from `rng` instead of the OS.
+///
+/// Lets tests seed a known RNG for reproducible secrets. Secrets for real
+/// tokens come from [`generate_jwt_secret`].
+pub fn generate_jwt_secret_from(
+    rng: &mut impl rand_core::RngCore,
+) -> Result<[u8; JWT_SECRET_SIZE]> {
+    let mut secret = [0u8; JWT_SECRET_SIZE];
+    rng.try_fill_bytes(&mut secret)
+        .map_err(|e| anyhow::anyhow!("Failed to generate JWT secret: {}", e))?;
+    Ok(secret)
+}
+
 /// In-memory JWT secret provider for testing.
 #[cfg(test)]
 pub struct MemoryJwtSecretProvider {
     secret: std::sync::Mutex<Option<[u8; JWT_SECRET_SIZE]>>,
+    previous: std::sync::Mutex<Option<PreviousJwtSecret>>,
 }
 
 #[cfg(test)]
@@ -291,12 +864,14 @@ impl MemoryJwtSecretProvider {
     pub fn new() -> Self {
         Self {
             secret: std::sync::Mutex::new(None),
+            previous: std::sync::Mutex::new(None),
         }
     }
 
     pub fn with_secret(secret: [u8; JWT_SECRET_SIZE]) -> Self {
         Self {
             secret: std::sync::Mutex::new(Some(secret)),
+            previous: std::sync::Mutex::new(None),
         }
     }
 }
@@ -320,6 +895,21 @@ impl JwtSecretProvider for MemoryJwtSecretProvider {
 
     fn delete_secret(&self) -> Result<()> {
         *self.secret.lock().unwrap() = None;
+        *self.previous.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn replace_secret(&self, secret: &[u8; JWT_SECRET_SIZE]) -> Result<()> {
+        *self.secret.lock().unwrap() = Some(*secret);
+        Ok(())
+    }
+
+    fn load_previous_secret(&self) -> Result<Option<PreviousJwtSecret>> {
+        Ok(self.previous.lock().unwrap().clone())
+    }
+
+    fn store_previous_secret(&self, previous: Option<&PreviousJwtSecret>) -> Result<()> {
+        *self.previous.lock().unwrap() = previous.cloned();
         Ok(())
     }
 }
@@ -373,11 +963,29 @@ impl MasterKeyProvider for MemoryKeyProvider {
         *self.key.lock().unwrap() = None;
         Ok(())
     }
+
+    fn replace_key(&self, new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        *self.key.lock().unwrap() = Some(*new_key);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::test_util::CountingRng;
+
+    #[test]
+    fn test_jwt_secret_from_seeded_rng() {
+        let secret = generate_jwt_secret_from(&mut CountingRng(7)).unwrap();
+        assert_eq!(secret[0], 7);
+        assert_eq!(secret[JWT_SECRET_SIZE - 1], (7 + JWT_SECRET_SIZE - 1) as u8);
+        assert_eq!(
+            generate_jwt_secret_from(&mut CountingRng(7)).unwrap(),
+            secret
+        );
+        assert_ne!(generate_jwt_secret().unwrap(), secret);
+    }
 
     #[test]
     fn test_memory_provider() {
@@ -403,6 +1011,75 @@ mod tests {
         assert_ne!(&*key1, &*key3);
     }
 
+    /// Provider whose backend is unavailable.
+    struct UnavailableKeyProvider;
+
+    impl MasterKeyProvider for UnavailableKeyProvider {
+        fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+            anyhow::bail!("backend unavailable")
+        }
+
+        fn key_exists(&self) -> bool {
+            false
+        }
+
+        fn delete_key(&self) -> Result<()> {
+            anyhow::bail!("backend unavailable")
+        }
+
+        fn replace_key(&self, _new_key: &[u8; KEY_SIZE]) -> Result<()> {
+            anyhow::bail!("backend unavailable")
+        }
+    }
+
+    #[test]
+    fn test_keychain_config_defaults() {
+        let config = KeychainConfig::default();
+        assert_eq!(config.service(), branding::KEYCHAIN_SERVICE);
+        assert_eq!(config.account(MASTER_KEY_NAME), MASTER_KEY_NAME);
+
+        let config = KeychainConfig {
+            account: Some("mcpmux-master-key".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.account(MASTER_KEY_NAME), "mcpmux-master-key");
+    }
+
+    #[test]
+    fn test_composite_provider_falls_back_in_order() {
+        let stored = generate_master_key().unwrap();
+        let provider = CompositeKeyProvider::new(vec![
+            Box::new(UnavailableKeyProvider),
+            Box::new(MemoryKeyProvider::with_key(stored)),
+            Box::new(MemoryKeyProvider::new()),
+        ]);
+
+        assert_eq!(provider.active_provider(), None);
+        assert_eq!(&*provider.get_or_create_key().unwrap(), &stored);
+        assert_eq!(provider.active_provider(), Some("MemoryKeyProvider"));
+
+        // Later calls and key management go to the winning provider only
+        let rotated = generate_master_key().unwrap();
+        provider.replace_key(&rotated).unwrap();
+        assert_eq!(&*provider.get_or_create_key().unwrap(), &rotated);
+
+        provider.delete_key().unwrap();
+        assert!(!provider.key_exists());
+    }
+
+    #[test]
+    fn test_composite_provider_all_unavailable() {
+        let provider = CompositeKeyProvider::new(vec![
+            Box::new(UnavailableKeyProvider),
+            Box::new(UnavailableKeyProvider),
+        ]);
+
+        let err = provider.get_or_create_key().unwrap_err();
+        assert!(err.to_string().contains("UnavailableKeyProvider"));
+        assert_eq!(provider.active_provider(), None);
+        assert!(provider.delete_key().is_err());
+    }
+
     // Note: Keychain tests are integration tests that require the OS keychain
     // They should be run manually or in CI with proper setup
     #[test]
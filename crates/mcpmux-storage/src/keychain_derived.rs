@@ -0,0 +1,350 @@
+//! Master key and JWT signing secret derived from one root secret.
+//!
+//! By default the master key and the JWT secret are independent secrets,
+//! each with its own keychain item (or file, or DPAPI blob). Installs that opt
+//! in with `MCPMUX_KEY_DERIVATION=v1` store a single root secret instead, in
+//! the place the master key would go, and derive both from it with
+//! HKDF-SHA256 under distinct info labels (`"mcpmux-master"`, `"mcpmux-jwt"`).
+//!
+//! The derivation version is recorded in a `key-derivation` file in the data
+//! directory when the mode is first enabled, and later starts follow the
+//! recorded version rather than the environment. New versions can change the
+//! labels without changing the keys of installs on an older one.
+//!
+//! Installs that already have an independent master key keep it: deriving
+//! from the stored key would change the key their data is encrypted with.
+//! A derived key or secret can't be replaced on its own, so master key and
+//! JWT secret rotation are not available in this mode.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use ring::hkdf;
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::crypto::KEY_SIZE;
+use crate::keychain::{JwtSecretProvider, MasterKeyProvider, PreviousJwtSecret, JWT_SECRET_SIZE};
+
+/// Environment variable that enables derivation for a new install, naming
+/// the [`DerivationVersion`] to use (e.g. `v1`).
+pub const KEY_DERIVATION_ENV_VAR: &str = "MCPMUX_KEY_DERIVATION";
+
+/// File in the data directory recording the derivation version in use.
+const KEY_DERIVATION_FILE: &str = "key-derivation";
+
+/// How keys are derived from the root secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerivationVersion {
+    /// HKDF-SHA256, empty salt, info `"mcpmux-master"` / `"mcpmux-jwt"`.
+    V1,
+}
+
+impl DerivationVersion {
+    /// Version enabled for new installs.
+    pub const LATEST: Self = Self::V1;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "v1" => Some(Self::V1),
+            _ => None,
+        }
+    }
+
+    fn master_label(self) -> &'static [u8] {
+        match self {
+            Self::V1 => b"mcpmux-master",
+        }
+    }
+
+    fn jwt_label(self) -> &'static [u8] {
+        match self {
+            Self::V1 => b"mcpmux-jwt",
+        }
+    }
+}
+
+/// Output length for [`hkdf::Prk::expand`].
+struct OkmLen(usize);
+
+impl hkdf::KeyType for OkmLen {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+fn derive<const N: usize>(root: &[u8; KEY_SIZE], label: &[u8]) -> Result<Zeroizing<[u8; N]>> {
+    let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &[]).extract(root);
+    let mut out = Zeroizing::new([0u8; N]);
+    prk.expand(&[label], OkmLen(N))
+        .and_then(|okm| okm.fill(&mut *out))
+        .map_err(|_| anyhow::anyhow!("Failed to derive key from root secret"))?;
+    Ok(out)
+}
+
+/// Derive the master key from the root secret.
+pub fn derive_master_key(
+    root: &[u8; KEY_SIZE],
+    version: DerivationVersion,
+) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+    derive(root, version.master_label())
+}
+
+/// Derive the JWT signing secret from the root secret.
+pub fn derive_jwt_secret(
+    root: &[u8; KEY_SIZE],
+    version: DerivationVersion,
+) -> Result<Zeroizing<[u8; JWT_SECRET_SIZE]>> {
+    derive(root, version.jwt_label())
+}
+
+fn version_file(data_dir: &Path) -> PathBuf {
+    data_dir.join(KEY_DERIVATION_FILE)
+}
+
+/// The derivation version this install was set up with, if any.
+pub fn recorded_version(data_dir: &Path) -> Result<Option<DerivationVersion>> {
+    let path = version_file(data_dir);
+    let recorded = match std::fs::read_to_string(&path) {
+        Ok(recorded) => recorded,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    };
+    DerivationVersion::parse(&recorded)
+        .map(Some)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown key derivation version '{}' in {:?}; it was written by a newer version",
+                recorded.trim(),
+                path
+            )
+        })
+}
+
+/// Decide whether keys are derived from the root held by `stored`.
+///
+/// Follows the recorded version if there is one. Otherwise
+/// [`KEY_DERIVATION_ENV_VAR`] enables derivation, which is recorded, unless
+/// `stored` already holds an independent master key.
+pub fn resolve_version(
+    data_dir: &Path,
+    stored: &dyn MasterKeyProvider,
+) -> Result<Option<DerivationVersion>> {
+    if let Some(version) = recorded_version(data_dir)? {
+        return Ok(Some(version));
+    }
+    let Some(requested) = std::env::var_os(KEY_DERIVATION_ENV_VAR) else {
+        return Ok(None);
+    };
+    let requested = requested.to_string_lossy();
+    let version = DerivationVersion::parse(&requested).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid {}: '{}' (expected {})",
+            KEY_DERIVATION_ENV_VAR,
+            requested,
+            DerivationVersion::LATEST.as_str()
+        )
+    })?;
+
+    if stored.key_exists() {
+        warn!(
+            "{} is set but this install already has a master key; keeping independent secrets",
+            KEY_DERIVATION_ENV_VAR
+        );
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(data_dir)
+        .with_context(|| format!("Failed to create data directory {:?}", data_dir))?;
+    let path = version_file(data_dir);
+    std::fs::write(&path, version.as_str())
+        .with_context(|| format!("Failed to write {:?}", path))?;
+    info!(
+        "Deriving master key and JWT secret from one root secret ({})",
+        version.as_str()
+    );
+    Ok(Some(version))
+}
+
+/// Master key provider deriving the key from a root secret held by another
+/// provider.
+pub struct DerivedKeyProvider {
+    root: Arc<dyn MasterKeyProvider>,
+    version: DerivationVersion,
+}
+
+impl DerivedKeyProvider {
+    pub fn new(root: Arc<dyn MasterKeyProvider>, version: DerivationVersion) -> Self {
+        Self { root, version }
+    }
+}
+
+impl MasterKeyProvider for DerivedKeyProvider {
+    fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+        derive_master_key(&self.root.get_or_create_key()?, self.version)
+    }
+
+    fn key_exists(&self) -> bool {
+        self.root.key_exists()
+    }
+
+    fn delete_key(&self) -> Result<()> {
+        self.root.delete_key()
+    }
+
+    fn replace_key(&self, _new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        anyhow::bail!(
+            "The master key is derived from the root secret and cannot be replaced on its own"
+        )
+    }
+
+    fn install_id(&self) -> Result<Option<String>> {
+        self.root.install_id()
+    }
+}
+
+/// JWT secret provider deriving the secret from a root secret held by a
+/// master key provider.
+pub struct DerivedJwtSecretProvider {
+    root: Arc<dyn MasterKeyProvider>,
+    version: DerivationVersion,
+}
+
+impl DerivedJwtSecretProvider {
+    pub fn new(root: Arc<dyn MasterKeyProvider>, version: DerivationVersion) -> Self {
+        Self { root, version }
+    }
+}
+
+impl JwtSecretProvider for DerivedJwtSecretProvider {
+    fn get_or_create_secret(&self) -> Result<Zeroizing<[u8; JWT_SECRET_SIZE]>> {
+        derive_jwt_secret(&self.root.get_or_create_key()?, self.version)
+    }
+
+    fn secret_exists(&self) -> bool {
+        self.root.key_exists()
+    }
+
+    fn delete_secret(&self) -> Result<()> {
+        anyhow::bail!(
+            "The JWT secret is derived from the root secret and cannot be deleted on its own"
+        )
+    }
+
+    fn replace_secret(&self, _secret: &[u8; JWT_SECRET_SIZE]) -> Result<()> {
+        anyhow::bail!(
+            "The JWT secret is derived from the root secret and cannot be replaced on its own"
+        )
+    }
+
+    fn load_previous_secret(&self) -> Result<Option<PreviousJwtSecret>> {
+        Ok(None)
+    }
+
+    fn store_previous_secret(&self, previous: Option<&PreviousJwtSecret>) -> Result<()> {
+        match previous {
+            None => Ok(()),
+            Some(_) => anyhow::bail!("JWT secret rotation is not available with a root secret"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keychain::MemoryKeyProvider;
+
+    #[test]
+    fn test_derived_keys_are_distinct_and_stable() {
+        let root = Arc::new(MemoryKeyProvider::new());
+        let keys = DerivedKeyProvider::new(root.clone(), DerivationVersion::V1);
+        let jwt = DerivedJwtSecretProvider::new(root.clone(), DerivationVersion::V1);
+
+        let master = keys.get_or_create_key().unwrap();
+        let secret = jwt.get_or_create_secret().unwrap();
+        let root_key = root.get_or_create_key().unwrap();
+        assert_ne!(&*master, &*root_key);
+        assert_ne!(&master[..], &secret[..]);
+        assert_eq!(&*keys.get_or_create_key().unwrap(), &*master);
+        assert_eq!(
+            &*derive_jwt_secret(&root_key, DerivationVersion::V1).unwrap(),
+            &*secret
+        );
+
+        assert!(keys.replace_key(&[0u8; KEY_SIZE]).is_err());
+        assert!(jwt.rotate(std::time::Duration::from_secs(60)).is_err());
+        assert_eq!(&*jwt.get_or_create_secret().unwrap(), &*secret);
+    }
+
+    #[test]
+    fn test_derived_providers_end_to_end() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join(KEY_DERIVATION_FILE), "v1").unwrap();
+        let root: Arc<dyn MasterKeyProvider> = Arc::new(MemoryKeyProvider::new());
+        let version = resolve_version(temp_dir.path(), root.as_ref())
+            .unwrap()
+            .expect("recorded version");
+
+        let keys = DerivedKeyProvider::new(Arc::clone(&root), version);
+        let jwt = DerivedJwtSecretProvider::new(Arc::clone(&root), version);
+        assert!(!keys.key_exists());
+        let master = keys.get_or_create_key().unwrap();
+        assert!(keys.key_exists());
+        assert!(jwt.secret_exists());
+
+        let encryptor = crate::crypto::FieldEncryptor::new(&master).unwrap();
+        let ciphertext = encryptor.encrypt("token").unwrap();
+
+        // A later start over the same root gets the same key and secret
+        let restarted = DerivedKeyProvider::new(Arc::clone(&root), version);
+        let encryptor =
+            crate::crypto::FieldEncryptor::new(&restarted.get_or_create_key().unwrap()).unwrap();
+        assert_eq!(&*encryptor.decrypt(&ciphertext).unwrap(), "token");
+        assert_eq!(
+            &*DerivedJwtSecretProvider::new(root, version)
+                .get_or_create_secret()
+                .unwrap(),
+            &*jwt.get_or_create_secret().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hkdf_known_answer() {
+        // RFC 5869 test case 3 (empty salt and info), first 32 bytes
+        let ikm = [0x0bu8; 22];
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, &[]).extract(&ikm);
+        let mut okm = [0u8; 32];
+        prk.expand(&[], OkmLen(32)).unwrap().fill(&mut okm).unwrap();
+        assert_eq!(
+            hex::encode(okm),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d"
+        );
+    }
+
+    // Each test uses its own data directory; none sets the env var, which
+    // would leak into parallel tests.
+
+    #[test]
+    fn test_recorded_version_is_followed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let stored = MemoryKeyProvider::new();
+        assert_eq!(resolve_version(temp_dir.path(), &stored).unwrap(), None);
+
+        std::fs::write(temp_dir.path().join(KEY_DERIVATION_FILE), "v1\n").unwrap();
+        stored.get_or_create_key().unwrap();
+        assert_eq!(
+            resolve_version(temp_dir.path(), &stored).unwrap(),
+            Some(DerivationVersion::V1)
+        );
+
+        std::fs::write(temp_dir.path().join(KEY_DERIVATION_FILE), "v9").unwrap();
+        assert!(recorded_version(temp_dir.path()).is_err());
+    }
+}
@@ -5,6 +5,12 @@
 //! maintaining the same security guarantees (user-scope DPAPI protection).
 //!
 //! Key files are stored in `<data_dir>/keys/` as opaque encrypted blobs.
+//!
+//! User scope breaks when the app runs as a Windows service under a different account
+//! than the one that created the keys. [`DpapiScope::Machine`] protects them for the
+//! machine instead, and [`migrate_to_machine_scope`] re-protects existing files. Reads
+//! try the configured scope first and then the other one, so files keep working while
+//! a scope change is rolled out.
 
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -15,7 +21,10 @@ use windows_dpapi::{decrypt_data, encrypt_data, Scope};
 use zeroize::Zeroizing;
 
 use crate::crypto::{generate_master_key, KEY_SIZE};
-use crate::keychain::{generate_jwt_secret, JwtSecretProvider, MasterKeyProvider, JWT_SECRET_SIZE};
+use crate::keychain::{
+    generate_jwt_secret, JwtSecretProvider, KeychainError, MasterKeyProvider, PreviousJwtSecret,
+    JWT_SECRET_SIZE,
+};
 
 /// File name for the DPAPI-protected master encryption key.
 const MASTER_KEY_FILE: &str = "master.dpapi";
@@ -23,26 +32,150 @@ const MASTER_KEY_FILE: &str = "master.dpapi";
 /// File name for the DPAPI-protected JWT signing secret.
 const JWT_SECRET_FILE: &str = "jwt.dpapi";
 
+/// File name for the DPAPI-protected JWT signing secret retired by the last rotation.
+const JWT_PREVIOUS_SECRET_FILE: &str = "jwt.previous.dpapi";
+
+/// `NTE_BAD_KEY_STATE`, returned by `CryptUnprotectData` when the user's DPAPI
+/// master key itself can't be decrypted, as after an administrator password reset.
+const NTE_BAD_KEY_STATE: u32 = 0x8009_000B;
+
+/// Environment variable selecting the [`DpapiScope`] in [`crate::create_key_provider`]:
+/// `user` (the default) or `machine`.
+pub const DPAPI_SCOPE_ENV_VAR: &str = "MCPMUX_DPAPI_SCOPE";
+
+/// Which DPAPI key protects the key files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DpapiScope {
+    /// Only the Windows user that wrote a file can read it.
+    #[default]
+    User,
+    /// Any account on this machine can read the files (`CRYPTPROTECT_LOCAL_MACHINE`).
+    ///
+    /// Lets a Windows service running under another account use keys created
+    /// during setup. The tradeoff is that DPAPI no longer separates users: any
+    /// local process can unprotect the master key and decrypt every stored
+    /// credential, so the data directory's ACL is the only thing guarding it.
+    Machine,
+}
+
+impl DpapiScope {
+    /// Read [`DPAPI_SCOPE_ENV_VAR`], defaulting to [`DpapiScope::User`] when unset.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(DPAPI_SCOPE_ENV_VAR) {
+            Ok(value) => value.parse(),
+            Err(std::env::VarError::NotPresent) => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Invalid {}", DPAPI_SCOPE_ENV_VAR)),
+        }
+    }
+
+    fn to_dpapi(self) -> Scope {
+        match self {
+            DpapiScope::User => Scope::User,
+            DpapiScope::Machine => Scope::Machine,
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            DpapiScope::User => DpapiScope::Machine,
+            DpapiScope::Machine => DpapiScope::User,
+        }
+    }
+}
+
+impl std::str::FromStr for DpapiScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "user" => Ok(DpapiScope::User),
+            "machine" => Ok(DpapiScope::Machine),
+            other => anyhow::bail!(
+                "Invalid DPAPI scope '{}': expected 'user' or 'machine'",
+                other
+            ),
+        }
+    }
+}
+
+/// Whether a DPAPI decrypt failure is `NTE_BAD_KEY_STATE`, which retrying won't fix.
+fn is_bad_key_state(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let by_code = cause
+            .downcast_ref::<std::io::Error>()
+            .and_then(|e| e.raw_os_error())
+            .is_some_and(|code| code as u32 == NTE_BAD_KEY_STATE);
+        // Errors that don't keep the code numerically still print it
+        by_code || cause.to_string().to_ascii_uppercase().contains("8009000B")
+    })
+}
+
+/// Decrypt a key file's contents, trying `scope` first and then the other scope.
+fn unprotect(encrypted: &[u8], scope: DpapiScope, what: &str) -> Result<Zeroizing<Vec<u8>>> {
+    match decrypt_data(encrypted, scope.to_dpapi()) {
+        Ok(decrypted) => Ok(Zeroizing::new(decrypted)),
+        Err(e) => match decrypt_data(encrypted, scope.other().to_dpapi()) {
+            Ok(decrypted) => {
+                warn!(
+                    "{} is protected with {:?}-scope DPAPI, expected {:?}; it should be migrated",
+                    what,
+                    scope.other(),
+                    scope
+                );
+                Ok(Zeroizing::new(decrypted))
+            }
+            Err(_) => Err(e).with_context(|| format!("Failed to decrypt {} with DPAPI", what)),
+        },
+    }
+}
+
 /// DPAPI-based master key provider.
 ///
 /// Stores the master key in a DPAPI-protected file within the app's data directory.
-/// The key is encrypted with user-scope DPAPI, meaning only the current Windows user
-/// on this machine can decrypt it.
+/// By default the key is encrypted with user-scope DPAPI, meaning only the current
+/// Windows user on this machine can decrypt it; see [`DpapiScope`] for the alternative.
 pub struct DpapiKeyProvider {
     key_path: PathBuf,
+    scope: DpapiScope,
 }
 
 impl DpapiKeyProvider {
     /// Create a new DPAPI key provider that stores keys in the given data directory.
     pub fn new(data_dir: &Path) -> Result<Self> {
+        Self::with_scope(data_dir, DpapiScope::User)
+    }
+
+    /// Create a provider that protects the key with the given DPAPI scope.
+    pub fn with_scope(data_dir: &Path, scope: DpapiScope) -> Result<Self> {
         let keys_dir = data_dir.join("keys");
         fs::create_dir_all(&keys_dir)
             .with_context(|| format!("Failed to create keys directory: {:?}", keys_dir))?;
 
         Ok(Self {
             key_path: keys_dir.join(MASTER_KEY_FILE),
+            scope,
         })
     }
+
+    /// Replace an unreadable master key with a freshly generated one.
+    ///
+    /// For recovering from [`KeychainError::DpapiUnrecoverable`]. Everything
+    /// encrypted under the old key, including all stored credentials, becomes
+    /// unreadable and has to be entered again. The old file is kept next to
+    /// the new one as `master.dpapi.bak`, in case the old password comes back.
+    pub fn reset(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+        if self.key_path.exists() {
+            let backup_path = self.key_path.with_extension("dpapi.bak");
+            fs::rename(&self.key_path, &backup_path)
+                .with_context(|| format!("Failed to move key file to {:?}", backup_path))?;
+            warn!(
+                "Resetting DPAPI master key; data encrypted under the old key is now unreadable \
+                 (old key file kept at {:?})",
+                backup_path
+            );
+        }
+        self.get_or_create_key()
+    }
 }
 
 impl MasterKeyProvider for DpapiKeyProvider {
@@ -55,8 +188,14 @@ impl MasterKeyProvider for DpapiKeyProvider {
             let encrypted = fs::read(&self.key_path)
                 .with_context(|| format!("Failed to read key file: {:?}", self.key_path))?;
 
-            let decrypted = decrypt_data(&encrypted, Scope::User)
-                .context("Failed to decrypt master key with DPAPI")?;
+            let decrypted = unprotect(&encrypted, self.scope, "master key").map_err(|e| {
+                if is_bad_key_state(&e) {
+                    warn!("DPAPI can no longer decrypt the master key: {:#}", e);
+                    e.context(KeychainError::DpapiUnrecoverable)
+                } else {
+                    e
+                }
+            })?;
 
             if decrypted.len() != KEY_SIZE {
                 anyhow::bail!(
@@ -74,7 +213,7 @@ impl MasterKeyProvider for DpapiKeyProvider {
             info!("No master key found, generating new DPAPI-protected key");
             let key = generate_master_key()?;
 
-            let encrypted = encrypt_data(&key, Scope::User)
+            let encrypted = encrypt_data(&key, self.scope.to_dpapi())
                 .context("Failed to encrypt master key with DPAPI")?;
 
             fs::write(&self.key_path, &encrypted)
@@ -99,6 +238,17 @@ impl MasterKeyProvider for DpapiKeyProvider {
         }
         Ok(())
     }
+
+    fn replace_key(&self, new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        let encrypted = encrypt_data(new_key, self.scope.to_dpapi())
+            .context("Failed to encrypt rotated master key with DPAPI")?;
+
+        fs::write(&self.key_path, &encrypted)
+            .with_context(|| format!("Failed to write key file: {:?}", self.key_path))?;
+
+        info!("Master key replaced in DPAPI-protected file");
+        Ok(())
+    }
 }
 
 /// DPAPI-based JWT signing secret provider.
@@ -106,17 +256,26 @@ impl MasterKeyProvider for DpapiKeyProvider {
 /// Stores the JWT signing secret in a DPAPI-protected file.
 pub struct DpapiJwtSecretProvider {
     secret_path: PathBuf,
+    previous_path: PathBuf,
+    scope: DpapiScope,
 }
 
 impl DpapiJwtSecretProvider {
     /// Create a new DPAPI JWT secret provider that stores secrets in the given data directory.
     pub fn new(data_dir: &Path) -> Result<Self> {
+        Self::with_scope(data_dir, DpapiScope::User)
+    }
+
+    /// Create a provider that protects the secret with the given DPAPI scope.
+    pub fn with_scope(data_dir: &Path, scope: DpapiScope) -> Result<Self> {
         let keys_dir = data_dir.join("keys");
         fs::create_dir_all(&keys_dir)
             .with_context(|| format!("Failed to create keys directory: {:?}", keys_dir))?;
 
         Ok(Self {
             secret_path: keys_dir.join(JWT_SECRET_FILE),
+            previous_path: keys_dir.join(JWT_PREVIOUS_SECRET_FILE),
+            scope,
         })
     }
 }
@@ -132,8 +291,7 @@ impl JwtSecretProvider for DpapiJwtSecretProvider {
                 format!("Failed to read JWT secret file: {:?}", self.secret_path)
             })?;
 
-            let decrypted = decrypt_data(&encrypted, Scope::User)
-                .context("Failed to decrypt JWT secret with DPAPI")?;
+            let decrypted = unprotect(&encrypted, self.scope, "JWT secret")?;
 
             if decrypted.len() != JWT_SECRET_SIZE {
                 anyhow::bail!(
@@ -151,7 +309,7 @@ impl JwtSecretProvider for DpapiJwtSecretProvider {
             info!("No JWT secret found, generating new DPAPI-protected secret");
             let secret = generate_jwt_secret()?;
 
-            let encrypted = encrypt_data(&secret, Scope::User)
+            let encrypted = encrypt_data(&secret, self.scope.to_dpapi())
                 .context("Failed to encrypt JWT secret with DPAPI")?;
 
             fs::write(&self.secret_path, &encrypted).with_context(|| {
@@ -176,8 +334,46 @@ impl JwtSecretProvider for DpapiJwtSecretProvider {
         } else {
             debug!("No DPAPI JWT secret file to delete");
         }
+        self.store_previous_secret(None)
+    }
+
+    fn replace_secret(&self, secret: &[u8; JWT_SECRET_SIZE]) -> Result<()> {
+        let encrypted = encrypt_data(secret, self.scope.to_dpapi())
+            .context("Failed to encrypt rotated JWT secret with DPAPI")?;
+
+        fs::write(&self.secret_path, &encrypted)
+            .with_context(|| format!("Failed to write JWT secret file: {:?}", self.secret_path))?;
+
+        info!("JWT secret replaced in DPAPI-protected file");
         Ok(())
     }
+
+    fn load_previous_secret(&self) -> Result<Option<PreviousJwtSecret>> {
+        if !self.previous_path.exists() {
+            return Ok(None);
+        }
+        let encrypted = fs::read(&self.previous_path)
+            .with_context(|| format!("Failed to read JWT secret file: {:?}", self.previous_path))?;
+        let decrypted = unprotect(&encrypted, self.scope, "previous JWT secret")?;
+        PreviousJwtSecret::from_bytes(&decrypted).map(Some)
+    }
+
+    fn store_previous_secret(&self, previous: Option<&PreviousJwtSecret>) -> Result<()> {
+        match previous {
+            Some(previous) => {
+                let encrypted = encrypt_data(&previous.to_bytes(), self.scope.to_dpapi())
+                    .context("Failed to encrypt previous JWT secret with DPAPI")?;
+                fs::write(&self.previous_path, &encrypted).with_context(|| {
+                    format!("Failed to write JWT secret file: {:?}", self.previous_path)
+                })
+            }
+            None if self.previous_path.exists() => fs::remove_file(&self.previous_path)
+                .with_context(|| {
+                    format!("Failed to delete JWT secret file: {:?}", self.previous_path)
+                }),
+            None => Ok(()),
+        }
+    }
 }
 
 /// Migrate existing keys from Windows Credential Manager to DPAPI files.
@@ -257,6 +453,40 @@ pub fn migrate_from_credential_manager(data_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Re-protect existing key files under machine-scope DPAPI.
+///
+/// Must run as the Windows user that created the files, since only they can
+/// unprotect user-scoped files. Each file is decrypted, encrypted for the
+/// machine, read back and then swapped in with a rename, so a failure leaves
+/// it as it was. Files already under machine scope are simply re-protected,
+/// which makes the migration safe to run more than once.
+pub fn migrate_to_machine_scope(data_dir: &Path) -> Result<()> {
+    let keys_dir = data_dir.join("keys");
+    for name in [MASTER_KEY_FILE, JWT_SECRET_FILE, JWT_PREVIOUS_SECRET_FILE] {
+        let path = keys_dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+
+        let encrypted =
+            fs::read(&path).with_context(|| format!("Failed to read key file: {:?}", path))?;
+        let decrypted = unprotect(&encrypted, DpapiScope::User, name)?;
+        let reprotected = encrypt_data(&decrypted, Scope::Machine)
+            .with_context(|| format!("Failed to encrypt {} with machine-scope DPAPI", name))?;
+        let check = unprotect(&reprotected, DpapiScope::Machine, name)?;
+        if *check != *decrypted {
+            anyhow::bail!("Machine-scope DPAPI round trip of {} did not match", name);
+        }
+
+        let tmp_path = path.with_extension("dpapi.tmp");
+        fs::write(&tmp_path, &reprotected)
+            .and_then(|()| fs::rename(&tmp_path, &path))
+            .with_context(|| format!("Failed to write key file: {:?}", path))?;
+        info!("Re-protected {} with machine-scope DPAPI", name);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -323,4 +553,70 @@ mod tests {
         // The raw key bytes should not appear in the file
         assert!(!file_contents.windows(KEY_SIZE).any(|w| w == &*key));
     }
+
+    #[test]
+    fn test_dpapi_scope_parsing() {
+        assert_eq!("user".parse::<DpapiScope>().unwrap(), DpapiScope::User);
+        assert_eq!(
+            " Machine ".parse::<DpapiScope>().unwrap(),
+            DpapiScope::Machine
+        );
+        assert!("system".parse::<DpapiScope>().is_err());
+    }
+
+    #[test]
+    fn test_dpapi_machine_scope_migration() {
+        let tmp = tempfile::tempdir().unwrap();
+        let user = DpapiKeyProvider::new(tmp.path()).unwrap();
+        let key = user.get_or_create_key().unwrap();
+        let secret = DpapiJwtSecretProvider::new(tmp.path())
+            .unwrap()
+            .get_or_create_secret()
+            .unwrap();
+
+        // Before migrating, a machine-scope provider still reads the user-scoped files
+        let machine = DpapiKeyProvider::with_scope(tmp.path(), DpapiScope::Machine).unwrap();
+        assert_eq!(&*machine.get_or_create_key().unwrap(), &*key);
+
+        migrate_to_machine_scope(tmp.path()).unwrap();
+        assert_eq!(&*machine.get_or_create_key().unwrap(), &*key);
+        let machine_jwt =
+            DpapiJwtSecretProvider::with_scope(tmp.path(), DpapiScope::Machine).unwrap();
+        assert_eq!(&*machine_jwt.get_or_create_secret().unwrap(), &*secret);
+
+        // Running it again changes nothing
+        migrate_to_machine_scope(tmp.path()).unwrap();
+        assert_eq!(&*machine.get_or_create_key().unwrap(), &*key);
+    }
+
+    #[test]
+    fn test_bad_key_state_is_detected() {
+        let err = anyhow::Error::from(std::io::Error::from_raw_os_error(NTE_BAD_KEY_STATE as i32))
+            .context("Failed to decrypt master key with DPAPI");
+        assert!(is_bad_key_state(&err));
+
+        let err = anyhow::anyhow!("Key not valid for use in specified state. (0x8009000B)");
+        assert!(is_bad_key_state(&err));
+
+        let err = anyhow::Error::from(std::io::Error::from_raw_os_error(13));
+        assert!(!is_bad_key_state(&err));
+    }
+
+    #[test]
+    fn test_dpapi_reset() {
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = DpapiKeyProvider::new(tmp.path()).unwrap();
+        let key_path = tmp.path().join("keys").join(MASTER_KEY_FILE);
+
+        // A file DPAPI won't decrypt
+        fs::write(&key_path, b"not a dpapi blob").unwrap();
+        assert!(provider.get_or_create_key().is_err());
+
+        let key = provider.reset().unwrap();
+        assert_eq!(&*provider.get_or_create_key().unwrap(), &*key);
+        assert_eq!(
+            fs::read(key_path.with_extension("dpapi.bak")).unwrap(),
+            b"not a dpapi blob"
+        );
+    }
 }
@@ -0,0 +1,136 @@
+//! Environment-variable master key for containerized deployments.
+//!
+//! Lets orchestrators such as Kubernetes inject the master key from a secret
+//! instead of mounting a key file or running a Secret Service. The variable
+//! holds the base64-encoded key; it is never generated or written by us, so the
+//! deployment owns its lifecycle.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use tracing::debug;
+use zeroize::Zeroizing;
+
+use crate::crypto::KEY_SIZE;
+use crate::keychain::MasterKeyProvider;
+
+/// Environment variable read by [`EnvVarKeyProvider::new`].
+pub const MASTER_KEY_ENV_VAR: &str = "MCPMUX_MASTER_KEY";
+
+/// Master key provider reading a base64-encoded key from an environment variable.
+pub struct EnvVarKeyProvider {
+    var_name: String,
+}
+
+impl EnvVarKeyProvider {
+    /// Create a provider reading [`MASTER_KEY_ENV_VAR`].
+    pub fn new() -> Self {
+        Self::with_var(MASTER_KEY_ENV_VAR)
+    }
+
+    /// Create a provider reading the named environment variable.
+    pub fn with_var(var_name: impl Into<String>) -> Self {
+        Self {
+            var_name: var_name.into(),
+        }
+    }
+}
+
+impl Default for EnvVarKeyProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MasterKeyProvider for EnvVarKeyProvider {
+    fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+        let encoded = Zeroizing::new(std::env::var(&self.var_name).with_context(|| {
+            format!(
+                "Master key environment variable {} is not set",
+                self.var_name
+            )
+        })?);
+
+        let decoded = Zeroizing::new(
+            base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .with_context(|| format!("{} is not valid base64", self.var_name))?,
+        );
+
+        if decoded.len() != KEY_SIZE {
+            anyhow::bail!(
+                "Invalid key size in {}: expected {} bytes, got {}",
+                self.var_name,
+                KEY_SIZE,
+                decoded.len()
+            );
+        }
+
+        let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+        key.copy_from_slice(&decoded);
+        debug!("Master key loaded from {}", self.var_name);
+        Ok(key)
+    }
+
+    fn key_exists(&self) -> bool {
+        std::env::var_os(&self.var_name).is_some()
+    }
+
+    fn delete_key(&self) -> Result<()> {
+        anyhow::bail!(
+            "Master key from {} cannot be deleted by the application; remove it from the deployment's secrets instead",
+            self.var_name
+        )
+    }
+
+    fn replace_key(&self, _new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        anyhow::bail!(
+            "Master key from {} cannot be replaced by the application; update the deployment's secret instead",
+            self.var_name
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Each test uses its own variable so parallel tests don't interfere.
+
+    #[test]
+    fn test_env_key_roundtrip() {
+        let var = "MCPMUX_TEST_ENV_KEY_ROUNDTRIP";
+        let key = crate::crypto::generate_master_key().unwrap();
+        std::env::set_var(var, base64::engine::general_purpose::STANDARD.encode(key));
+
+        let provider = EnvVarKeyProvider::with_var(var);
+        assert!(provider.key_exists());
+        assert_eq!(&*provider.get_or_create_key().unwrap(), &key);
+
+        // Env-provided keys are managed by the deployment
+        assert!(provider.delete_key().is_err());
+        assert!(provider.key_exists());
+        std::env::remove_var(var);
+    }
+
+    #[test]
+    fn test_env_key_missing_or_invalid() {
+        let var = "MCPMUX_TEST_ENV_KEY_INVALID";
+        let provider = EnvVarKeyProvider::with_var(var);
+
+        std::env::remove_var(var);
+        assert!(!provider.key_exists());
+        assert!(provider.get_or_create_key().is_err());
+
+        std::env::set_var(var, "not base64!");
+        assert!(provider.get_or_create_key().is_err());
+
+        // Valid base64, wrong length
+        std::env::set_var(
+            var,
+            base64::engine::general_purpose::STANDARD.encode([0u8; 16]),
+        );
+        let err = provider.get_or_create_key().unwrap_err();
+        assert!(err.to_string().contains("expected 32 bytes, got 16"));
+        std::env::remove_var(var);
+    }
+}
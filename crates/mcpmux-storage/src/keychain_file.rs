@@ -7,23 +7,37 @@
 //! This is less secure than OS keychain or DPAPI — any process running as the same user
 //! can read the key files. For production deployments, install `gnome-keyring` or another
 //! Secret Service provider.
+//!
+//! The master key gets a `master.key.meta` sidecar binding it to an install id,
+//! which the database records too (see `Database::verify_install_id`). A key
+//! file copied in from another install is then reported as such at startup.
 
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use tracing::{debug, info};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
 use zeroize::Zeroizing;
 
 use crate::crypto::{generate_master_key, KEY_SIZE};
-use crate::keychain::{generate_jwt_secret, JwtSecretProvider, MasterKeyProvider, JWT_SECRET_SIZE};
+use crate::keychain::{
+    generate_jwt_secret, JwtSecretProvider, MasterKeyProvider, PreviousJwtSecret, JWT_SECRET_SIZE,
+};
 
 /// File name for the master encryption key.
 const MASTER_KEY_FILE: &str = "master.key";
 
+/// File name for the master key's install binding.
+const MASTER_KEY_META_FILE: &str = "master.key.meta";
+
 /// File name for the JWT signing secret.
 const JWT_SECRET_FILE: &str = "jwt.key";
 
+/// File name for the JWT signing secret retired by the last rotation.
+const JWT_PREVIOUS_SECRET_FILE: &str = "jwt.key.previous";
+
 /// Set restrictive file permissions (owner read/write only).
 fn set_owner_only_permissions(path: &Path) -> Result<()> {
     #[cfg(unix)]
@@ -39,18 +53,98 @@ fn set_owner_only_permissions(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Restrict an existing key file that other users can access back to 0600.
+///
+/// A bad backup restore can leave a key group or world readable. It may already
+/// have been read by then, so this warns loudly instead of fixing it silently.
+fn ensure_owner_only_permissions(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path)
+            .with_context(|| format!("Failed to read permissions of {:?}", path))?
+            .permissions()
+            .mode()
+            & 0o777;
+        if mode & 0o077 != 0 {
+            warn!(
+                "Key file {:?} is accessible to other users (mode {:04o}); restricting it \
+                 to 0600. Consider rotating the master key.",
+                path, mode
+            );
+            set_owner_only_permissions(path)?;
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+    }
+    Ok(())
+}
+
 /// Write data to a file with restrictive permissions.
-fn write_key_file(path: &Path, data: &[u8]) -> Result<()> {
-    fs::write(path, data).with_context(|| format!("Failed to write key file: {:?}", path))?;
-    set_owner_only_permissions(path)?;
+///
+/// The data is written to a temp file in the same directory, synced, and renamed
+/// over `path`, so a crash (e.g. mid key rotation) leaves either the old or the
+/// new contents rather than a truncated key.
+pub(crate) fn write_key_file(path: &Path, data: &[u8]) -> Result<()> {
+    let dir = path
+        .parent()
+        .with_context(|| format!("Key file has no parent directory: {:?}", path))?;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = dir.join(tmp_name);
+
+    let result = write_synced(&tmp_path, data).and_then(|()| {
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace key file: {:?}", path))
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result?;
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .with_context(|| format!("Failed to sync key directory: {:?}", dir))?;
     Ok(())
 }
 
+/// Write `data` to a new owner-only file at `path` and flush it to disk.
+fn write_synced(path: &Path, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to write key file: {:?}", path))?;
+    // A leftover temp file keeps its old mode, so set it explicitly
+    set_owner_only_permissions(path)?;
+    file.write_all(data)
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("Failed to write key file: {:?}", path))
+}
+
+/// Contents of `master.key.meta`.
+#[derive(Serialize, Deserialize)]
+struct KeyMeta {
+    install_id: String,
+}
+
 /// File-based master key provider.
 ///
 /// Stores the master key as a raw byte file protected by filesystem permissions.
 pub struct FileKeyProvider {
     key_path: PathBuf,
+    meta_path: PathBuf,
 }
 
 impl FileKeyProvider {
@@ -67,14 +161,26 @@ impl FileKeyProvider {
 
         Ok(Self {
             key_path: keys_dir.join(MASTER_KEY_FILE),
+            meta_path: keys_dir.join(MASTER_KEY_META_FILE),
         })
     }
+
+    /// Bind the key to a freshly generated install id.
+    fn write_meta(&self) -> Result<String> {
+        let install_id = Uuid::new_v4().to_string();
+        let meta = serde_json::to_vec(&KeyMeta {
+            install_id: install_id.clone(),
+        })?;
+        write_key_file(&self.meta_path, &meta)?;
+        Ok(install_id)
+    }
 }
 
 impl MasterKeyProvider for FileKeyProvider {
     fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
         if self.key_path.exists() {
             debug!("Reading master key from {:?}", self.key_path);
+            ensure_owner_only_permissions(&self.key_path)?;
             let data = fs::read(&self.key_path)
                 .with_context(|| format!("Failed to read key file: {:?}", self.key_path))?;
 
@@ -94,6 +200,7 @@ impl MasterKeyProvider for FileKeyProvider {
             info!("No master key found, generating new file-based key");
             let key = generate_master_key()?;
             write_key_file(&self.key_path, &key)?;
+            self.write_meta()?;
             info!("Master key generated and stored in {:?}", self.key_path);
             Ok(Zeroizing::new(key))
         }
@@ -111,8 +218,35 @@ impl MasterKeyProvider for FileKeyProvider {
         } else {
             debug!("No key file to delete");
         }
+        if self.meta_path.exists() {
+            fs::remove_file(&self.meta_path)
+                .with_context(|| format!("Failed to delete key meta file: {:?}", self.meta_path))?;
+        }
         Ok(())
     }
+
+    fn replace_key(&self, new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        write_key_file(&self.key_path, new_key)?;
+        info!("Master key replaced in {:?}", self.key_path);
+        Ok(())
+    }
+
+    fn install_id(&self) -> Result<Option<String>> {
+        if !self.key_path.exists() {
+            return Ok(None);
+        }
+        if !self.meta_path.exists() {
+            // Keys created before the sidecar existed are bound on first use
+            info!("Binding existing master key to a new install id");
+            return self.write_meta().map(Some);
+        }
+
+        let data = fs::read(&self.meta_path)
+            .with_context(|| format!("Failed to read key meta file: {:?}", self.meta_path))?;
+        let meta: KeyMeta = serde_json::from_slice(&data)
+            .with_context(|| format!("Invalid key meta file: {:?}", self.meta_path))?;
+        Ok(Some(meta.install_id))
+    }
 }
 
 /// File-based JWT signing secret provider.
@@ -120,6 +254,7 @@ impl MasterKeyProvider for FileKeyProvider {
 /// Stores the JWT signing secret as a raw byte file protected by filesystem permissions.
 pub struct FileJwtSecretProvider {
     secret_path: PathBuf,
+    previous_path: PathBuf,
 }
 
 impl FileJwtSecretProvider {
@@ -136,6 +271,7 @@ impl FileJwtSecretProvider {
 
         Ok(Self {
             secret_path: keys_dir.join(JWT_SECRET_FILE),
+            previous_path: keys_dir.join(JWT_PREVIOUS_SECRET_FILE),
         })
     }
 }
@@ -144,6 +280,7 @@ impl JwtSecretProvider for FileJwtSecretProvider {
     fn get_or_create_secret(&self) -> Result<Zeroizing<[u8; JWT_SECRET_SIZE]>> {
         if self.secret_path.exists() {
             debug!("Reading JWT secret from {:?}", self.secret_path);
+            ensure_owner_only_permissions(&self.secret_path)?;
             let data = fs::read(&self.secret_path).with_context(|| {
                 format!("Failed to read JWT secret file: {:?}", self.secret_path)
             })?;
@@ -182,13 +319,139 @@ impl JwtSecretProvider for FileJwtSecretProvider {
         } else {
             debug!("No JWT secret file to delete");
         }
+        self.store_previous_secret(None)
+    }
+
+    fn replace_secret(&self, secret: &[u8; JWT_SECRET_SIZE]) -> Result<()> {
+        write_key_file(&self.secret_path, secret)?;
+        info!("JWT secret replaced in {:?}", self.secret_path);
+        Ok(())
+    }
+
+    fn load_previous_secret(&self) -> Result<Option<PreviousJwtSecret>> {
+        if !self.previous_path.exists() {
+            return Ok(None);
+        }
+        ensure_owner_only_permissions(&self.previous_path)?;
+        let data = Zeroizing::new(fs::read(&self.previous_path).with_context(|| {
+            format!("Failed to read JWT secret file: {:?}", self.previous_path)
+        })?);
+        PreviousJwtSecret::from_bytes(&data).map(Some)
+    }
+
+    fn store_previous_secret(&self, previous: Option<&PreviousJwtSecret>) -> Result<()> {
+        match previous {
+            Some(previous) => write_key_file(&self.previous_path, &previous.to_bytes()),
+            None if self.previous_path.exists() => fs::remove_file(&self.previous_path)
+                .with_context(|| {
+                    format!("Failed to delete JWT secret file: {:?}", self.previous_path)
+                }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Move keys stored in files into the OS keychain once it is available.
+///
+/// An install that first ran without a keychain (headless, or before
+/// gnome-keyring was installed) keeps its keys in `<data_dir>/keys/`. For the
+/// master key and the JWT secret, a file value is copied into the keychain
+/// only if the keychain has none, read back and compared, and only then is
+/// the file deleted. This is a one-way, best-effort migration: whatever
+/// fails leaves the file in place and in use, and is retried next launch.
+pub fn migrate_to_keychain(data_dir: &Path) -> Result<()> {
+    use crate::keychain::{KeychainJwtSecretProvider, KeychainKeyProvider};
+
+    let file_key = FileKeyProvider::new(data_dir)?;
+    if file_key.key_exists() {
+        migrate_master_key(&file_key, &KeychainKeyProvider::new()?);
+    }
+
+    let file_secret = FileJwtSecretProvider::new(data_dir)?;
+    if file_secret.secret_exists() {
+        migrate_jwt_secret(&file_secret, &KeychainJwtSecretProvider::new()?);
+    }
+
+    Ok(())
+}
+
+/// Move the master key from `file` to `keychain`. Returns whether it moved.
+fn migrate_master_key(file: &FileKeyProvider, keychain: &dyn MasterKeyProvider) -> bool {
+    if keychain.key_exists() {
+        // The keychain is tried first, so its key is the one in use
+        debug!("Keychain already holds a master key; leaving the key file alone");
+        return false;
+    }
+
+    info!("Migrating master key from file to OS keychain");
+    let copied = file.get_or_create_key().and_then(|key| {
+        keychain.replace_key(&key)?;
+        let stored = keychain
+            .get_or_create_key()
+            .context("Failed to read master key back from keychain")?;
+        if *stored != *key {
+            // Don't leave a wrong key where it would be picked over the file
+            let _ = keychain.delete_key();
+            anyhow::bail!("Master key read back from keychain does not match");
+        }
+        Ok(())
+    });
+    if let Err(e) = copied {
+        info!("Master key stays in file; OS keychain not usable: {}", e);
+        return false;
+    }
+
+    match file.delete_key() {
+        Ok(()) => info!("Master key migrated to OS keychain and key file removed"),
+        Err(e) => warn!(
+            "Master key migrated to OS keychain but the key file could not be removed: {}",
+            e
+        ),
+    }
+    true
+}
+
+/// Move the JWT secret (and any previous secret still in its grace period)
+/// from `file` to `keychain`. Returns whether it moved.
+fn migrate_jwt_secret(file: &FileJwtSecretProvider, keychain: &dyn JwtSecretProvider) -> bool {
+    if keychain.secret_exists() {
+        debug!("Keychain already holds a JWT secret; leaving the secret file alone");
+        return false;
+    }
+
+    info!("Migrating JWT secret from file to OS keychain");
+    let copied = file.get_or_create_secret().and_then(|secret| {
+        keychain.store_previous_secret(file.load_previous_secret()?.as_ref())?;
+        keychain.replace_secret(&secret)?;
+        let stored = keychain
+            .get_or_create_secret()
+            .context("Failed to read JWT secret back from keychain")?;
+        if *stored != *secret {
+            let _ = keychain.delete_secret();
+            anyhow::bail!("JWT secret read back from keychain does not match");
+        }
         Ok(())
+    });
+    if let Err(e) = copied {
+        info!("JWT secret stays in file; OS keychain not usable: {}", e);
+        return false;
     }
+
+    match file.delete_secret() {
+        Ok(()) => info!("JWT secret migrated to OS keychain and secret file removed"),
+        Err(e) => warn!(
+            "JWT secret migrated to OS keychain but the secret file could not be removed: {}",
+            e
+        ),
+    }
+    true
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::keychain::{MemoryJwtSecretProvider, MemoryKeyProvider};
+    use std::time::Duration;
 
     #[test]
     fn test_file_master_key_provider() {
@@ -236,6 +499,33 @@ mod tests {
         assert!(!provider.secret_exists());
     }
 
+    #[test]
+    fn test_file_jwt_secret_rotation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = FileJwtSecretProvider::new(tmp.path()).unwrap();
+        let old = provider.get_or_create_secret().unwrap();
+        assert!(provider.previous_secret().unwrap().is_none());
+
+        let new = provider.rotate(Duration::from_secs(3600)).unwrap();
+        assert_ne!(&*new, &*old);
+        assert_eq!(&*provider.get_or_create_secret().unwrap(), &*new);
+
+        // A fresh provider sees the retired secret too
+        let reloaded = FileJwtSecretProvider::new(tmp.path()).unwrap();
+        let previous = reloaded.previous_secret().unwrap().unwrap();
+        assert_eq!(&*previous.secret, &*old);
+        assert!(previous.expires_at > chrono::Utc::now());
+
+        // An expired one is dropped
+        provider.rotate(Duration::ZERO).unwrap();
+        assert!(provider.previous_secret().unwrap().is_none());
+        assert!(!tmp
+            .path()
+            .join("keys")
+            .join(JWT_PREVIOUS_SECRET_FILE)
+            .exists());
+    }
+
     #[test]
     fn test_file_key_is_correct_size() {
         let tmp = tempfile::tempdir().unwrap();
@@ -246,4 +536,153 @@ mod tests {
         let file_contents = fs::read(tmp.path().join("keys").join(MASTER_KEY_FILE)).unwrap();
         assert_eq!(file_contents.len(), KEY_SIZE);
     }
+
+    #[test]
+    fn test_file_replace_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = FileKeyProvider::new(tmp.path()).unwrap();
+
+        let old_key = provider.get_or_create_key().unwrap();
+        let new_key = generate_master_key().unwrap();
+        provider.replace_key(&new_key).unwrap();
+
+        let loaded = provider.get_or_create_key().unwrap();
+        assert_eq!(&*loaded, &new_key);
+        assert_ne!(&*loaded, &*old_key);
+    }
+
+    #[test]
+    fn test_write_key_file_replaces_atomically() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(MASTER_KEY_FILE);
+
+        write_key_file(&path, b"old").unwrap();
+        write_key_file(&path, b"new").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+
+        // Only the key itself is left behind
+        let entries: Vec<_> = fs::read_dir(tmp.path()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_readable_key_is_tightened() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = FileKeyProvider::new(tmp.path()).unwrap();
+        let key = provider.get_or_create_key().unwrap();
+
+        let path = tmp.path().join("keys").join(MASTER_KEY_FILE);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert_eq!(&*provider.get_or_create_key().unwrap(), &*key);
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_file_key_install_id() {
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = FileKeyProvider::new(tmp.path()).unwrap();
+        assert_eq!(provider.install_id().unwrap(), None);
+
+        provider.get_or_create_key().unwrap();
+        let install_id = provider.install_id().unwrap().unwrap();
+        assert_eq!(provider.install_id().unwrap().as_ref(), Some(&install_id));
+
+        // Rotation keeps the binding
+        provider
+            .replace_key(&generate_master_key().unwrap())
+            .unwrap();
+        assert_eq!(provider.install_id().unwrap().as_ref(), Some(&install_id));
+
+        // A key without a sidecar gets a new binding
+        fs::remove_file(tmp.path().join("keys").join(MASTER_KEY_META_FILE)).unwrap();
+        let rebound = provider.install_id().unwrap().unwrap();
+        assert_ne!(rebound, install_id);
+
+        provider.delete_key().unwrap();
+        assert_eq!(provider.install_id().unwrap(), None);
+    }
+
+    /// A keychain that accepts writes but can't be read, like a locked one.
+    struct BrokenKeychain;
+
+    impl MasterKeyProvider for BrokenKeychain {
+        fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+            anyhow::bail!("keychain is locked")
+        }
+
+        fn key_exists(&self) -> bool {
+            false
+        }
+
+        fn delete_key(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn replace_key(&self, _new_key: &[u8; KEY_SIZE]) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_migrate_master_key_to_keychain() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = FileKeyProvider::new(tmp.path()).unwrap();
+        let key = file.get_or_create_key().unwrap();
+
+        let keychain = MemoryKeyProvider::new();
+        assert!(migrate_master_key(&file, &keychain));
+        assert_eq!(&*keychain.get_or_create_key().unwrap(), &*key);
+        assert!(!file.key_exists());
+        assert!(!tmp.path().join("keys").join(MASTER_KEY_META_FILE).exists());
+    }
+
+    #[test]
+    fn test_migrate_master_key_keeps_existing_keychain_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = FileKeyProvider::new(tmp.path()).unwrap();
+        file.get_or_create_key().unwrap();
+
+        let existing = generate_master_key().unwrap();
+        let keychain = MemoryKeyProvider::with_key(existing);
+        assert!(!migrate_master_key(&file, &keychain));
+        assert_eq!(&*keychain.get_or_create_key().unwrap(), &existing);
+        assert!(file.key_exists());
+    }
+
+    #[test]
+    fn test_migrate_master_key_to_broken_keychain_keeps_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = FileKeyProvider::new(tmp.path()).unwrap();
+        let key = file.get_or_create_key().unwrap();
+
+        assert!(!migrate_master_key(&file, &BrokenKeychain));
+        assert_eq!(&*file.get_or_create_key().unwrap(), &*key);
+    }
+
+    #[test]
+    fn test_migrate_jwt_secret_to_keychain() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = FileJwtSecretProvider::new(tmp.path()).unwrap();
+        let old = file.get_or_create_secret().unwrap();
+        let new = file.rotate(Duration::from_secs(3600)).unwrap();
+
+        let keychain = MemoryJwtSecretProvider::new();
+        assert!(migrate_jwt_secret(&file, &keychain));
+        assert_eq!(&*keychain.get_or_create_secret().unwrap(), &*new);
+        let previous = keychain.load_previous_secret().unwrap().unwrap();
+        assert_eq!(&*previous.secret, &*old);
+        assert!(!file.secret_exists());
+    }
 }
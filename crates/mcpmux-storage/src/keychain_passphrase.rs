@@ -0,0 +1,233 @@
+//! Passphrase-derived master key for headless deployments.
+//!
+//! Used on Linux/macOS hosts where neither an OS keychain nor a trusted location for a
+//! raw key file is available. The master key is derived with Argon2id from an
+//! operator-supplied passphrase and a per-install random salt stored in
+//! `<data_dir>/keys/kdf.salt` (0600 on Unix). Only the salt is written to disk —
+//! losing the passphrase means losing access to everything encrypted with the key.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use tracing::{debug, info};
+use zeroize::Zeroizing;
+
+use crate::crypto::{derive_key, KEY_SIZE};
+use crate::keychain::MasterKeyProvider;
+use crate::keychain_file::write_key_file;
+
+pub use crate::crypto::KdfParams;
+
+/// Environment variable read by [`PassphraseKeyProvider::from_env`].
+pub const PASSPHRASE_ENV_VAR: &str = "MCPMUX_MASTER_PASSPHRASE";
+
+/// File name for the KDF salt.
+const SALT_FILE: &str = "kdf.salt";
+
+/// Size of the random KDF salt (16 bytes = 128 bits).
+const SALT_SIZE: usize = 16;
+
+/// Where the passphrase comes from.
+pub enum PassphraseSource {
+    /// Read from the named environment variable.
+    EnvVar(String),
+    /// Ask a callback, e.g. an interactive prompt or a secrets agent.
+    Callback(Box<dyn Fn() -> Result<Zeroizing<String>> + Send + Sync>),
+}
+
+impl PassphraseSource {
+    fn passphrase(&self) -> Result<Zeroizing<String>> {
+        let passphrase = match self {
+            PassphraseSource::EnvVar(name) => Zeroizing::new(
+                std::env::var(name)
+                    .with_context(|| format!("Passphrase environment variable {} not set", name))?,
+            ),
+            PassphraseSource::Callback(callback) => callback()?,
+        };
+
+        if passphrase.is_empty() {
+            anyhow::bail!("Master key passphrase must not be empty");
+        }
+        Ok(passphrase)
+    }
+}
+
+/// Argon2id passphrase-derived master key provider.
+pub struct PassphraseKeyProvider {
+    salt_path: PathBuf,
+    source: PassphraseSource,
+    params: KdfParams,
+}
+
+impl PassphraseKeyProvider {
+    /// Create a provider that stores its salt in `<data_dir>/keys/`.
+    pub fn new(data_dir: &Path, source: PassphraseSource) -> Result<Self> {
+        let keys_dir = data_dir.join("keys");
+        fs::create_dir_all(&keys_dir)
+            .with_context(|| format!("Failed to create keys directory: {:?}", keys_dir))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&keys_dir, fs::Permissions::from_mode(0o700))?;
+        }
+
+        Ok(Self {
+            salt_path: keys_dir.join(SALT_FILE),
+            source,
+            params: KdfParams::default(),
+        })
+    }
+
+    /// Create a provider reading the passphrase from [`PASSPHRASE_ENV_VAR`].
+    pub fn from_env(data_dir: &Path) -> Result<Self> {
+        Self::new(
+            data_dir,
+            PassphraseSource::EnvVar(PASSPHRASE_ENV_VAR.to_string()),
+        )
+    }
+
+    /// Override the Argon2id cost parameters.
+    pub fn with_params(mut self, params: KdfParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Read the install's salt, generating one on first use.
+    fn get_or_create_salt(&self) -> Result<[u8; SALT_SIZE]> {
+        let mut salt = [0u8; SALT_SIZE];
+
+        if self.salt_path.exists() {
+            let data = fs::read(&self.salt_path)
+                .with_context(|| format!("Failed to read salt file: {:?}", self.salt_path))?;
+            if data.len() != SALT_SIZE {
+                anyhow::bail!(
+                    "Invalid salt size in file: expected {}, got {}",
+                    SALT_SIZE,
+                    data.len()
+                );
+            }
+            salt.copy_from_slice(&data);
+        } else {
+            info!("No KDF salt found, generating a new one");
+            SystemRandom::new()
+                .fill(&mut salt)
+                .map_err(|_| anyhow::anyhow!("Failed to generate random salt"))?;
+            write_key_file(&self.salt_path, &salt)?;
+        }
+
+        Ok(salt)
+    }
+}
+
+impl MasterKeyProvider for PassphraseKeyProvider {
+    fn get_or_create_key(&self) -> Result<Zeroizing<[u8; KEY_SIZE]>> {
+        let passphrase = self.source.passphrase()?;
+        let salt = self.get_or_create_salt()?;
+        let key = derive_key(&passphrase, &salt, self.params)?;
+
+        debug!("Master key derived from passphrase");
+        Ok(key)
+    }
+
+    fn key_exists(&self) -> bool {
+        self.salt_path.exists()
+    }
+
+    fn delete_key(&self) -> Result<()> {
+        if self.salt_path.exists() {
+            fs::remove_file(&self.salt_path)
+                .with_context(|| format!("Failed to delete salt file: {:?}", self.salt_path))?;
+            info!("KDF salt file deleted");
+        } else {
+            debug!("No KDF salt file to delete");
+        }
+        Ok(())
+    }
+
+    fn replace_key(&self, _new_key: &[u8; KEY_SIZE]) -> Result<()> {
+        anyhow::bail!(
+            "Passphrase-derived master keys cannot be replaced directly; change the passphrase instead"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap parameters so tests don't spend seconds in the KDF.
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn callback_provider(data_dir: &Path, passphrase: &'static str) -> PassphraseKeyProvider {
+        PassphraseKeyProvider::new(
+            data_dir,
+            PassphraseSource::Callback(Box::new(move || {
+                Ok(Zeroizing::new(passphrase.to_string()))
+            })),
+        )
+        .unwrap()
+        .with_params(TEST_PARAMS)
+    }
+
+    #[test]
+    fn test_passphrase_key_is_stable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = callback_provider(tmp.path(), "correct horse battery staple");
+
+        assert!(!provider.key_exists());
+        let key1 = provider.get_or_create_key().unwrap();
+        assert!(provider.key_exists());
+
+        // Same passphrase and salt derive the same key
+        let key2 = provider.get_or_create_key().unwrap();
+        assert_eq!(&*key1, &*key2);
+
+        // A different passphrase derives a different key
+        let other = callback_provider(tmp.path(), "wrong passphrase");
+        assert_ne!(&*key1, &*other.get_or_create_key().unwrap());
+    }
+
+    #[test]
+    fn test_salt_is_per_install() {
+        let tmp1 = tempfile::tempdir().unwrap();
+        let tmp2 = tempfile::tempdir().unwrap();
+
+        let key1 = callback_provider(tmp1.path(), "same")
+            .get_or_create_key()
+            .unwrap();
+        let key2 = callback_provider(tmp2.path(), "same")
+            .get_or_create_key()
+            .unwrap();
+        assert_ne!(&*key1, &*key2);
+    }
+
+    #[test]
+    fn test_empty_passphrase_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = callback_provider(tmp.path(), "");
+        assert!(provider.get_or_create_key().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_salt_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        callback_provider(tmp.path(), "passphrase")
+            .get_or_create_key()
+            .unwrap();
+
+        let mode = fs::metadata(tmp.path().join("keys").join(SALT_FILE))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
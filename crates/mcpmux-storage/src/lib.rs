@@ -14,7 +14,7 @@
 //! │            SQLite Implementations                    │
 //! │    (SqliteSpaceRepository, SqliteCredentialRepo)     │
 //! ├──────────────────────────────────────────────────────┤
-//! │         FieldEncryptor (AES-256-GCM)                 │
+//! │   FieldEncryptor (AES-256-GCM / ChaCha20-Poly1305)   │
 //! │        (Encrypts tokens/credentials)                 │
 //! ├──────────────────────────────────────────────────────┤
 //! │    DpapiKeyProvider (Windows) / KeychainKeyProvider   │
@@ -29,19 +29,17 @@
 //!
 //! ```rust,ignore
 //! use mcpmux_storage::{
-//!     Database, SqliteSpaceRepository, SqliteCredentialRepository,
+//!     DbPool, SqliteSpaceRepository, SqliteCredentialRepository,
 //!     FieldEncryptor, MasterKeyProvider,
 //! };
 //! use std::sync::Arc;
-//! use tokio::sync::Mutex;
 //!
 //! // Get master key (DPAPI on Windows, OS Keychain elsewhere)
 //! let key_provider = mcpmux_storage::create_key_provider(&data_dir)?;
 //! let master_key = key_provider.get_or_create_key()?;
 //!
-//! // Open database
-//! let db = Database::open(&path)?;
-//! let db = Arc::new(Mutex::new(db));
+//! // Open a connection pool (repositories also accept `Arc<Mutex<Database>>`)
+//! let db = DbPool::open(&path)?;
 //!
 //! // Create encryptor for sensitive fields
 //! let encryptor = Arc::new(FieldEncryptor::new(&master_key)?);
@@ -54,22 +52,46 @@
 pub mod crypto;
 mod database;
 pub mod keychain;
+pub mod keychain_derived;
 #[cfg(windows)]
 pub mod keychain_dpapi;
+pub mod keychain_env;
 #[cfg(not(windows))]
 pub mod keychain_file;
+#[cfg(not(windows))]
+pub mod keychain_passphrase;
+pub mod nonce;
+mod pool;
 mod repositories;
 
-pub use crypto::{generate_master_key, FieldEncryptor, KEY_SIZE};
-pub use database::Database;
+pub use crypto::{
+    generate_master_key, generate_master_key_from, CipherSuite, CryptoError, FieldEncryptor,
+    KdfParams, FINGERPRINT_SIZE, KEY_SIZE, STREAM_CHUNK_SIZE,
+};
+pub use database::{
+    AutoVacuum, Database, DatabaseError, DbOptions, ForeignKeyViolation, IntegrityReport,
+    JournalMode, Synchronous, VacuumReport, ALLOW_KEY_MIGRATION_ENV_VAR,
+};
 pub use keychain::{
-    generate_jwt_secret, JwtSecretProvider, KeychainJwtSecretProvider, KeychainKeyProvider,
-    MasterKeyProvider, JWT_SECRET_SIZE,
+    generate_jwt_secret, generate_jwt_secret_from, CompositeKeyProvider, JwtSecretProvider,
+    KeychainAccessControl, KeychainConfig, KeychainError, KeychainJwtSecretProvider,
+    KeychainKeyProvider, MasterKeyProvider, PreviousJwtSecret, DEFAULT_JWT_ROTATION_GRACE,
+    JWT_SECRET_SIZE,
+};
+pub use keychain_derived::{
+    DerivationVersion, DerivedJwtSecretProvider, DerivedKeyProvider, KEY_DERIVATION_ENV_VAR,
 };
 #[cfg(windows)]
-pub use keychain_dpapi::{DpapiJwtSecretProvider, DpapiKeyProvider};
+pub use keychain_dpapi::{
+    DpapiJwtSecretProvider, DpapiKeyProvider, DpapiScope, DPAPI_SCOPE_ENV_VAR,
+};
+pub use keychain_env::{EnvVarKeyProvider, MASTER_KEY_ENV_VAR};
 #[cfg(not(windows))]
 pub use keychain_file::{FileJwtSecretProvider, FileKeyProvider};
+#[cfg(not(windows))]
+pub use keychain_passphrase::{PassphraseKeyProvider, PassphraseSource, PASSPHRASE_ENV_VAR};
+pub use nonce::{FileNonceCounterStore, NonceCounterStore, NonceStrategy, NONCE_COUNTER_BLOCK};
+pub use pool::{DbConn, DbHandle, DbPool, DEFAULT_POOL_SIZE};
 pub use repositories::*;
 
 /// Default database file name.
@@ -82,61 +104,153 @@ pub fn default_database_path() -> Option<std::path::PathBuf> {
 
 /// Create the platform-appropriate master key provider.
 ///
+/// If `MCPMUX_MASTER_KEY` is set, the base64-encoded key it holds is used on every
+/// platform (containerized deployments). Otherwise:
+///
 /// - **Windows**: Uses DPAPI file-based storage (key not visible in Credential Manager UI).
-///   Also migrates existing keys from Credential Manager on first use.
-/// - **macOS/Linux**: Uses the OS keychain (Keychain / Secret Service).
+///   Also migrates existing keys from Credential Manager on first use. `MCPMUX_DPAPI_SCOPE=machine`
+///   selects machine-scope DPAPI for running as a service (see [`DpapiScope`]).
+/// - **macOS/Linux**: Uses the OS keychain (Keychain / Secret Service), falling back to
+///   file-based storage via a [`CompositeKeyProvider`]. If `MCPMUX_MASTER_PASSPHRASE` is set, the key is instead
+///   derived from that passphrase with Argon2id (headless deployments).
+///   Keys left in files from a run without a keychain are moved into it once it is available.
+///
+/// Installs set up with `MCPMUX_KEY_DERIVATION` store a root secret in that place
+/// instead and derive the master key from it (see [`keychain_derived`]).
 pub fn create_key_provider(
     data_dir: &std::path::Path,
 ) -> anyhow::Result<Box<dyn MasterKeyProvider>> {
+    let stored = create_stored_key_provider(data_dir)?;
+    match keychain_derived::resolve_version(data_dir, stored.as_ref())? {
+        Some(version) => Ok(Box::new(DerivedKeyProvider::new(stored.into(), version))),
+        None => Ok(stored),
+    }
+}
+
+/// The provider holding the master key, or the root secret it is derived from.
+fn create_stored_key_provider(
+    data_dir: &std::path::Path,
+) -> anyhow::Result<Box<dyn MasterKeyProvider>> {
+    // A key injected by the deployment takes precedence over anything stored
+    if std::env::var_os(MASTER_KEY_ENV_VAR).is_some() {
+        tracing::info!("Using master key from {}", MASTER_KEY_ENV_VAR);
+        return Ok(Box::new(EnvVarKeyProvider::new()));
+    }
+
     #[cfg(windows)]
     {
         // Migrate any existing keys from Credential Manager to DPAPI files
         if let Err(e) = keychain_dpapi::migrate_from_credential_manager(data_dir) {
             tracing::warn!("Credential Manager migration encountered an error: {}", e);
         }
-        Ok(Box::new(DpapiKeyProvider::new(data_dir)?))
+        Ok(Box::new(DpapiKeyProvider::with_scope(
+            data_dir,
+            DpapiScope::from_env()?,
+        )?))
     }
 
     #[cfg(not(windows))]
     {
+        // An operator-supplied passphrase takes precedence over any stored key
+        if std::env::var_os(PASSPHRASE_ENV_VAR).is_some() {
+            tracing::info!("Deriving master key from {}", PASSPHRASE_ENV_VAR);
+            return Ok(Box::new(PassphraseKeyProvider::from_env(data_dir)?));
+        }
+
         // Try OS keychain first, fall back to file-based storage if unavailable
+        let mut providers: Vec<Box<dyn MasterKeyProvider>> = Vec::new();
         match KeychainKeyProvider::new() {
-            Ok(provider) => match provider.get_or_create_key() {
-                Ok(_) => return Ok(Box::new(provider)),
-                Err(e) => tracing::warn!(
-                    "OS keychain unavailable ({e}), using file-based key storage. \
-                     For better security, install gnome-keyring or another Secret Service provider."
-                ),
-            },
+            Ok(provider) => {
+                // Keys stored while the keychain was missing move into it
+                if let Err(e) = keychain_file::migrate_to_keychain(data_dir) {
+                    tracing::warn!("Keychain migration encountered an error: {}", e);
+                }
+                providers.push(Box::new(provider))
+            }
             Err(e) => {
                 tracing::warn!("OS keychain unavailable ({e}), using file-based key storage.")
             }
         }
-        Ok(Box::new(FileKeyProvider::new(data_dir)?))
+        providers.push(Box::new(FileKeyProvider::new(data_dir)?));
+        Ok(Box::new(CompositeKeyProvider::new(providers)))
     }
 }
 
+/// Rotate the master key held by `provider`.
+///
+/// Generates a new key and hands the matching encryptor to `reencrypt`, which is
+/// expected to re-encrypt stored data (e.g. via
+/// [`SqliteCredentialRepository::reencrypt_all`]). The new key is persisted to
+/// DPAPI/keychain/file only after `reencrypt` succeeds, so a failed re-encryption
+/// leaves the old key in place.
+pub async fn rotate_master_key<F, Fut>(
+    provider: &dyn MasterKeyProvider,
+    reencrypt: F,
+) -> anyhow::Result<std::sync::Arc<FieldEncryptor>>
+where
+    F: FnOnce(std::sync::Arc<FieldEncryptor>) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<()>>,
+{
+    let current_key = provider.get_or_create_key()?;
+    let current = FieldEncryptor::new(&current_key)?;
+    let new_key = zeroize::Zeroizing::new(generate_master_key()?);
+    let new_encryptor = std::sync::Arc::new(current.rotate_key(&new_key)?);
+
+    reencrypt(new_encryptor.clone()).await?;
+
+    if let Err(e) = provider.replace_key(&new_key) {
+        tracing::error!(
+            "Stored data was re-encrypted but the rotated master key could not be persisted: {}",
+            e
+        );
+        return Err(e.context("Failed to persist rotated master key"));
+    }
+
+    tracing::info!("Master key rotated");
+    Ok(new_encryptor)
+}
+
 /// Create the platform-appropriate JWT secret provider.
 ///
-/// - **Windows**: Uses DPAPI file-based storage.
-/// - **macOS/Linux**: Uses the OS keychain, with file-based fallback if unavailable.
+/// - **Windows**: Uses DPAPI file-based storage, in the scope `MCPMUX_DPAPI_SCOPE` selects.
+/// - **macOS/Linux**: Uses the OS keychain, with file-based fallback if unavailable. A secret
+///   left in a file from a run without a keychain is moved into it first.
+///
+/// Installs whose master key is derived from a root secret derive the JWT secret
+/// from the same root (see [`keychain_derived`]).
 pub fn create_jwt_secret_provider(
     data_dir: &std::path::Path,
 ) -> anyhow::Result<Box<dyn JwtSecretProvider>> {
+    if let Some(version) = keychain_derived::recorded_version(data_dir)? {
+        return Ok(Box::new(DerivedJwtSecretProvider::new(
+            create_stored_key_provider(data_dir)?.into(),
+            version,
+        )));
+    }
+
     #[cfg(windows)]
     {
-        Ok(Box::new(DpapiJwtSecretProvider::new(data_dir)?))
+        Ok(Box::new(DpapiJwtSecretProvider::with_scope(
+            data_dir,
+            DpapiScope::from_env()?,
+        )?))
     }
 
     #[cfg(not(windows))]
     {
         match KeychainJwtSecretProvider::new() {
-            Ok(provider) => match provider.get_or_create_secret() {
-                Ok(_) => return Ok(Box::new(provider)),
-                Err(e) => tracing::warn!(
-                    "OS keychain unavailable for JWT secret ({e}), using file-based storage."
-                ),
-            },
+            Ok(provider) => {
+                // Before the first read, or a fresh keychain secret would shadow the file's
+                if let Err(e) = keychain_file::migrate_to_keychain(data_dir) {
+                    tracing::warn!("Keychain migration encountered an error: {}", e);
+                }
+                match provider.get_or_create_secret() {
+                    Ok(_) => return Ok(Box::new(provider)),
+                    Err(e) => tracing::warn!(
+                        "OS keychain unavailable for JWT secret ({e}), using file-based storage."
+                    ),
+                }
+            }
             Err(e) => tracing::warn!(
                 "OS keychain unavailable for JWT secret ({e}), using file-based storage."
             ),
@@ -144,3 +258,39 @@ pub fn create_jwt_secret_provider(
         Ok(Box::new(FileJwtSecretProvider::new(data_dir)?))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keychain::MemoryKeyProvider;
+
+    #[tokio::test]
+    async fn test_rotate_master_key_persists_after_reencrypt() {
+        let provider = MemoryKeyProvider::new();
+        let old_key = provider.get_or_create_key().unwrap();
+
+        let new_encryptor = rotate_master_key(&provider, |_| async { Ok(()) })
+            .await
+            .unwrap();
+
+        let stored = provider.get_or_create_key().unwrap();
+        assert_ne!(&*stored, &*old_key);
+        let ciphertext = new_encryptor.encrypt("after-rotation").unwrap();
+        let reloaded = FieldEncryptor::new(&stored).unwrap();
+        assert_eq!(*reloaded.decrypt(&ciphertext).unwrap(), "after-rotation");
+    }
+
+    #[tokio::test]
+    async fn test_rotate_master_key_keeps_old_key_on_failure() {
+        let provider = MemoryKeyProvider::new();
+        let old_key = provider.get_or_create_key().unwrap();
+
+        let result = rotate_master_key(&provider, |_| async {
+            Err(anyhow::anyhow!("re-encryption failed"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(&*provider.get_or_create_key().unwrap(), &*old_key);
+    }
+}
@@ -0,0 +1,9 @@
+-- McpMux Database Schema - Database metadata
+--
+-- Key/value facts about the database itself rather than user data,
+-- e.g. the fingerprint of the master key its secrets are encrypted with.
+
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+-- McpMux Database Schema - Soft delete
+--
+-- Deleting a space stamps deleted_at on it and on its credentials instead of
+-- removing the rows, so an accidental delete can be undone. Soft-deleted
+-- spaces are removed for good (cascading to everything in them) when purged.
+
+ALTER TABLE spaces ADD COLUMN deleted_at TEXT;
+ALTER TABLE credentials ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_spaces_deleted_at ON spaces(deleted_at);
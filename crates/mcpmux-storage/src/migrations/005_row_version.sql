@@ -0,0 +1,8 @@
+-- McpMux Database Schema - Row versions
+--
+-- Optimistic concurrency: every update bumps the row's version and only
+-- applies if the caller still has the version it loaded, so two windows
+-- editing the same record can't silently overwrite each other.
+
+ALTER TABLE spaces ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE credentials ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
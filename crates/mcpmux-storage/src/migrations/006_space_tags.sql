@@ -0,0 +1,13 @@
+-- McpMux Database Schema - Space tags
+--
+-- Free-form labels for grouping spaces. NOCASE makes the primary key treat
+-- "Work" and "work" as the same tag; the spelling added first is kept.
+
+CREATE TABLE IF NOT EXISTS space_tags (
+    space_id TEXT NOT NULL,
+    tag TEXT NOT NULL COLLATE NOCASE,
+    PRIMARY KEY (space_id, tag),
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_space_tags_tag ON space_tags(tag);
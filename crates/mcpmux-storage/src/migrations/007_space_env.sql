@@ -0,0 +1,13 @@
+-- McpMux Database Schema - Space environment
+--
+-- Environment variables inherited by every server in a space. Sensitive
+-- values are stored encrypted, bound to their space and name.
+
+CREATE TABLE IF NOT EXISTS space_env (
+    space_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    sensitive INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (space_id, name),
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE CASCADE
+);
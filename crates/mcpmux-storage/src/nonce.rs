@@ -0,0 +1,203 @@
+//! Nonce strategies for sealing under the master key.
+//!
+//! Every envelope seals its data key under the master key with a 96-bit nonce.
+//! By default that nonce is random, which is simple and needs no state, but
+//! two random nonces collide with probability around n²/2⁹⁷ after n
+//! encryptions. That stays negligible for any realistic credential store, yet
+//! the risk grows with every write for as long as the key lives.
+//!
+//! [`NonceStrategy::Counter`] removes that risk: nonces are a random 32-bit
+//! salt followed by a 64-bit counter whose high-water mark is persisted per
+//! key by a [`NonceCounterStore`], so they never repeat as long as the store
+//! only moves forward. The cost is state. Counter values are reserved in
+//! blocks of [`NONCE_COUNTER_BLOCK`], so a crash skips at most one block, and
+//! a store that is rolled back (say, restored from a backup) would hand out
+//! values again. The salt, drawn afresh by every encryptor, keeps that case no
+//! worse than random nonces.
+//!
+//! Payload nonces are unaffected: each payload is sealed once under its own
+//! fresh data key, so they can't repeat under the same key either way.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::crypto::FINGERPRINT_SIZE;
+
+/// Counter values reserved from the store at a time.
+pub const NONCE_COUNTER_BLOCK: u64 = 1024;
+
+/// How nonces for the master key are generated.
+#[derive(Clone, Default)]
+pub enum NonceStrategy {
+    /// 96 random bits per nonce. Stateless.
+    #[default]
+    Random,
+    /// Random 32-bit salt + 64-bit counter persisted in the given store.
+    Counter(Arc<dyn NonceCounterStore>),
+}
+
+/// Durable high-water marks for counter nonces, one per master key.
+pub trait NonceCounterStore: Send + Sync {
+    /// Reserve `count` consecutive counter values for the key with
+    /// `fingerprint` and return the first.
+    ///
+    /// Must never return a value twice for the same key, including across
+    /// restarts, so the new high-water mark has to be durable on return.
+    fn reserve(&self, fingerprint: &[u8; FINGERPRINT_SIZE], count: u64) -> Result<u64>;
+}
+
+/// Stores each key's high-water mark in a small file in a directory.
+pub struct FileNonceCounterStore {
+    dir: PathBuf,
+    // Serializes read-modify-write within the process
+    lock: Mutex<()>,
+}
+
+impl FileNonceCounterStore {
+    /// Create a store in `dir`, creating the directory if needed.
+    pub fn new(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create nonce counter directory {:?}", dir))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn path_for(&self, fingerprint: &[u8; FINGERPRINT_SIZE]) -> PathBuf {
+        self.dir
+            .join(format!("nonce-{}.counter", hex::encode(fingerprint)))
+    }
+}
+
+impl NonceCounterStore for FileNonceCounterStore {
+    fn reserve(&self, fingerprint: &[u8; FINGERPRINT_SIZE], count: u64) -> Result<u64> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let path = self.path_for(fingerprint);
+
+        let start = match fs::read_to_string(&path) {
+            Ok(text) => text
+                .trim()
+                .parse::<u64>()
+                .with_context(|| format!("Corrupt nonce counter file {:?}", path))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read nonce counter {:?}", path))
+            }
+        };
+        let end = start
+            .checked_add(count)
+            .context("Nonce counter exhausted; rotate the master key")?;
+
+        // Write-then-rename so a crash leaves the old or the new mark, never neither
+        let tmp_path = path.with_extension("counter.tmp");
+        fs::write(&tmp_path, end.to_string())
+            .and_then(|()| fs::File::open(&tmp_path)?.sync_all())
+            .and_then(|()| fs::rename(&tmp_path, &path))
+            .with_context(|| format!("Failed to persist nonce counter {:?}", path))?;
+        #[cfg(unix)]
+        fs::File::open(&self.dir)
+            .and_then(|dir| dir.sync_all())
+            .with_context(|| format!("Failed to sync nonce counter directory {:?}", self.dir))?;
+
+        Ok(start)
+    }
+}
+
+/// Hands out counter nonces for one master key.
+pub(crate) struct NonceCounter {
+    store: Arc<dyn NonceCounterStore>,
+    fingerprint: [u8; FINGERPRINT_SIZE],
+    salt: [u8; 4],
+    /// Next value to use and the end of the reserved block.
+    range: Mutex<(u64, u64)>,
+}
+
+impl NonceCounter {
+    pub(crate) fn new(
+        store: Arc<dyn NonceCounterStore>,
+        fingerprint: [u8; FINGERPRINT_SIZE],
+        rng: &SystemRandom,
+    ) -> Result<Self> {
+        let mut salt = [0u8; 4];
+        rng.fill(&mut salt)
+            .map_err(|_| anyhow::anyhow!("Failed to generate nonce salt"))?;
+        Ok(Self {
+            store,
+            fingerprint,
+            salt,
+            range: Mutex::new((0, 0)),
+        })
+    }
+
+    /// The store, to carry over to the encryptor of a rotated key.
+    pub(crate) fn store(&self) -> Arc<dyn NonceCounterStore> {
+        self.store.clone()
+    }
+
+    /// The next nonce: salt followed by the big-endian counter.
+    pub(crate) fn next(&self) -> Result<[u8; 12]> {
+        let mut range = self.range.lock().unwrap_or_else(|e| e.into_inner());
+        if range.0 == range.1 {
+            let start = self.store.reserve(&self.fingerprint, NONCE_COUNTER_BLOCK)?;
+            *range = (start, start + NONCE_COUNTER_BLOCK);
+        }
+        let counter = range.0;
+        range.0 += 1;
+
+        let mut nonce = [0u8; 12];
+        nonce[..4].copy_from_slice(&self.salt);
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        Ok(nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_file_store_survives_restart() {
+        let dir = TempDir::new().unwrap();
+        let fingerprint = [1u8; FINGERPRINT_SIZE];
+        let other = [2u8; FINGERPRINT_SIZE];
+
+        let store = FileNonceCounterStore::new(dir.path()).unwrap();
+        assert_eq!(store.reserve(&fingerprint, 10).unwrap(), 0);
+        assert_eq!(store.reserve(&fingerprint, 10).unwrap(), 10);
+        // Keys count independently
+        assert_eq!(store.reserve(&other, 10).unwrap(), 0);
+
+        let reopened = FileNonceCounterStore::new(dir.path()).unwrap();
+        assert_eq!(reopened.reserve(&fingerprint, 10).unwrap(), 20);
+    }
+
+    #[test]
+    fn test_counter_reserves_blocks() {
+        let dir = TempDir::new().unwrap();
+        let store: Arc<dyn NonceCounterStore> =
+            Arc::new(FileNonceCounterStore::new(dir.path()).unwrap());
+        let fingerprint = [3u8; FINGERPRINT_SIZE];
+        let rng = SystemRandom::new();
+
+        let counter = NonceCounter::new(store.clone(), fingerprint, &rng).unwrap();
+        let first = counter.next().unwrap();
+        let second = counter.next().unwrap();
+        assert_eq!(first[..4], second[..4]);
+        assert_eq!(u64::from_be_bytes(first[4..].try_into().unwrap()), 0);
+        assert_eq!(u64::from_be_bytes(second[4..].try_into().unwrap()), 1);
+
+        // A restart continues after the block the previous run reserved
+        let restarted = NonceCounter::new(store, fingerprint, &rng).unwrap();
+        let nonce = restarted.next().unwrap();
+        assert_eq!(
+            u64::from_be_bytes(nonce[4..].try_into().unwrap()),
+            NONCE_COUNTER_BLOCK
+        );
+    }
+}
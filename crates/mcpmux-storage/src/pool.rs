@@ -0,0 +1,282 @@
+//! Pooled database access.
+//!
+//! A single `Arc<Mutex<Database>>` makes every query wait for the one before
+//! it, so a slow write in the gateway stalls the UI's reads. [`DbPool`] hands
+//! out connections from an r2d2 pool instead. The file is in WAL mode, so
+//! readers never wait for the writer; writers are serialized by SQLite's own
+//! lock and wait up to the busy timeout for it.
+//!
+//! Repositories take a [`DbHandle`], which is either a pool or the shared
+//! [`Database`]. Both convert with `.into()`, so call sites can move to a pool
+//! one at a time.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use r2d2::PooledConnection;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::debug;
+
+#[cfg(feature = "sqlcipher")]
+use crate::crypto::KEY_SIZE;
+use crate::database::{Database, DbOptions, STATEMENT_CACHE_CAPACITY};
+
+/// Default maximum number of pooled connections.
+pub const DEFAULT_POOL_SIZE: u32 = 8;
+
+/// Pool of connections to a database file.
+///
+/// Cheap to clone; clones share the same connections.
+#[derive(Debug, Clone)]
+pub struct DbPool {
+    pool: r2d2::Pool<SqliteConnectionManager>,
+}
+
+impl DbPool {
+    /// Open a pool on the database at `path`, creating the file if needed.
+    ///
+    /// Pending migrations are applied first, exactly as by [`Database::open`].
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::open_with_options(path, DbOptions::default(), DEFAULT_POOL_SIZE)
+    }
+
+    /// Open a pool of at most `max_size` connections with explicit settings.
+    pub fn open_with_options(path: &Path, options: DbOptions, max_size: u32) -> Result<Self> {
+        Database::open_with_options(path, options)?;
+        Self::build(path, options, max_size, None)
+    }
+
+    /// Open a pool on a SQLCipher-encrypted database.
+    ///
+    /// Fails like [`Database::open_encrypted`] if `key` doesn't match.
+    #[cfg(feature = "sqlcipher")]
+    pub fn open_encrypted(path: &Path, key: &[u8; KEY_SIZE]) -> Result<Self> {
+        Self::open_encrypted_with_options(path, key, DbOptions::default(), DEFAULT_POOL_SIZE)
+    }
+
+    /// Open a pool on a SQLCipher-encrypted database with explicit settings.
+    #[cfg(feature = "sqlcipher")]
+    pub fn open_encrypted_with_options(
+        path: &Path,
+        key: &[u8; KEY_SIZE],
+        options: DbOptions,
+        max_size: u32,
+    ) -> Result<Self> {
+        Database::open_encrypted_with_options(path, key, options)?;
+        Self::build(
+            path,
+            options,
+            max_size,
+            Some(crate::database::sqlcipher_key(key)),
+        )
+    }
+
+    /// Build the pool on a file that is already migrated.
+    fn build(
+        path: &Path,
+        options: DbOptions,
+        max_size: u32,
+        key: Option<zeroize::Zeroizing<String>>,
+    ) -> Result<Self> {
+        let manager = SqliteConnectionManager::file(path).with_init(move |conn| {
+            // The key must be set before anything else touches the file
+            if let Some(key) = &key {
+                conn.pragma_update(None, "key", &**key)?;
+            }
+            conn.pragma_update(None, "foreign_keys", "ON")?;
+            conn.busy_timeout(options.busy_timeout)?;
+            conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+            conn.pragma_update(None, "journal_mode", options.journal_mode.as_str())?;
+            conn.pragma_update(None, "synchronous", options.synchronous.as_str())
+        });
+
+        // Open connections on demand rather than all at startup
+        let pool = r2d2::Pool::builder()
+            .max_size(max_size)
+            .min_idle(Some(1))
+            .build(manager)
+            .with_context(|| format!("Failed to create connection pool for {:?}", path))?;
+
+        debug!(
+            "Opened pool of up to {} connections to {:?}",
+            max_size, path
+        );
+        Ok(Self { pool })
+    }
+
+    /// Check out a connection, blocking while all of them are in use.
+    pub fn get(&self) -> Result<PooledConnection<SqliteConnectionManager>> {
+        self.pool
+            .get()
+            .context("Timed out waiting for a database connection")
+    }
+}
+
+/// Database access for repositories.
+///
+/// `Shared` is the single mutex-guarded connection repositories used before
+/// pooling; it keeps existing call sites and tests working unchanged.
+#[derive(Clone)]
+pub enum DbHandle {
+    Pool(DbPool),
+    Shared(Arc<Mutex<Database>>),
+}
+
+impl DbHandle {
+    /// Get a connection: a pooled one, or the shared one once it is unlocked.
+    pub async fn get(&self) -> Result<DbConn<'_>> {
+        match self {
+            DbHandle::Pool(pool) => {
+                // Only move to a blocking thread when every connection is checked out
+                let conn = match pool.pool.try_get() {
+                    Some(conn) => conn,
+                    None => {
+                        let pool = pool.clone();
+                        tokio::task::spawn_blocking(move || pool.get()).await??
+                    }
+                };
+                Ok(DbConn::Pooled(conn))
+            }
+            DbHandle::Shared(db) => Ok(DbConn::Shared(db.lock().await)),
+        }
+    }
+}
+
+impl From<DbPool> for DbHandle {
+    fn from(pool: DbPool) -> Self {
+        DbHandle::Pool(pool)
+    }
+}
+
+impl From<Arc<Mutex<Database>>> for DbHandle {
+    fn from(db: Arc<Mutex<Database>>) -> Self {
+        DbHandle::Shared(db)
+    }
+}
+
+/// A connection checked out from a [`DbHandle`], returned when dropped.
+pub enum DbConn<'a> {
+    Pooled(PooledConnection<SqliteConnectionManager>),
+    Shared(MutexGuard<'a, Database>),
+}
+
+impl DbConn<'_> {
+    /// Get a reference to the underlying connection.
+    pub fn connection(&self) -> &Connection {
+        match self {
+            DbConn::Pooled(conn) => conn,
+            DbConn::Shared(db) => db.connection(),
+        }
+    }
+
+    /// Execute a closure within a transaction.
+    ///
+    /// On a pooled connection the transaction takes the write lock up front
+    /// (`BEGIN IMMEDIATE`). A deferred transaction that reads before writing
+    /// would fail with `SQLITE_BUSY` instead of waiting if another connection
+    /// wrote in between.
+    pub fn transaction<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T>,
+    {
+        match self {
+            DbConn::Pooled(conn) => {
+                let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+                let result = f(conn)?;
+                tx.commit()?;
+                Ok(result)
+            }
+            DbConn::Shared(db) => db.transaction(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SqliteSpaceRepository;
+    use mcpmux_core::{Space, SpaceRepository};
+    use rusqlite::params;
+    use tempfile::TempDir;
+
+    fn open_pool(dir: &TempDir) -> DbPool {
+        let pool = DbPool::open(&dir.path().join("pool.db")).unwrap();
+        pool.get()
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE counter (value INTEGER NOT NULL); INSERT INTO counter VALUES (0);",
+            )
+            .unwrap();
+        pool
+    }
+
+    fn count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT value FROM counter", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_readers_do_not_wait_for_writer() {
+        let dir = TempDir::new().unwrap();
+        let pool = open_pool(&dir);
+
+        let writer = pool.get().unwrap();
+        let tx = Transaction::new_unchecked(&writer, TransactionBehavior::Immediate).unwrap();
+        writer.execute("UPDATE counter SET value = 1", []).unwrap();
+
+        // The open write transaction neither blocks nor leaks into a reader
+        let reader = pool.get().unwrap();
+        assert_eq!(count(&reader), 0);
+
+        tx.commit().unwrap();
+        assert_eq!(count(&reader), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_transactions_are_serialized() {
+        let dir = TempDir::new().unwrap();
+        let handle = DbHandle::from(open_pool(&dir));
+
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let handle = handle.clone();
+                tokio::spawn(async move {
+                    for _ in 0..10 {
+                        let conn = handle.get().await.unwrap();
+                        // Read-modify-write, which a deferred transaction can't upgrade safely
+                        conn.transaction(|conn| {
+                            let value = count(conn);
+                            conn.execute("UPDATE counter SET value = ?1", params![value + 1])?;
+                            Ok(())
+                        })
+                        .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(count(handle.get().await.unwrap().connection()), 40);
+    }
+
+    #[tokio::test]
+    async fn test_repository_works_on_pool_and_shared_database() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("repo.db");
+
+        let space = Space::new("Pooled");
+        SqliteSpaceRepository::new(DbPool::open(&path).unwrap())
+            .create(&space)
+            .await
+            .unwrap();
+
+        let db = Arc::new(Mutex::new(Database::open(&path).unwrap()));
+        let found = SqliteSpaceRepository::new(db).get(&space.id).await.unwrap();
+        assert_eq!(found.map(|s| s.name).as_deref(), Some("Pooled"));
+    }
+}
@@ -2,15 +2,12 @@
 //!
 //! Simple key-value store for application-wide settings.
 
-use std::sync::Arc;
-
 use anyhow::Result;
 use async_trait::async_trait;
-use mcpmux_core::AppSettingsRepository;
+use mcpmux_core::{AppSettingsRepository, RepoResult};
 use rusqlite::params;
-use tokio::sync::Mutex;
 
-use crate::Database;
+use crate::DbHandle;
 
 /// SQLite-backed app settings repository.
 ///
@@ -22,20 +19,20 @@ use crate::Database;
 /// - `ui.theme` - UI theme preference (string)
 /// - `ui.window_state` - Window position/size (JSON)
 pub struct SqliteAppSettingsRepository {
-    db: Arc<Mutex<Database>>,
+    db: DbHandle,
 }
 
 impl SqliteAppSettingsRepository {
     /// Create a new app settings repository.
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self { db: db.into() }
     }
 }
 
 #[async_trait]
 impl AppSettingsRepository for SqliteAppSettingsRepository {
-    async fn get(&self, key: &str) -> Result<Option<String>> {
-        let db = self.db.lock().await;
+    async fn get(&self, key: &str) -> RepoResult<Option<String>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let result = conn.query_row(
@@ -51,8 +48,8 @@ impl AppSettingsRepository for SqliteAppSettingsRepository {
         }
     }
 
-    async fn set(&self, key: &str, value: &str) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn set(&self, key: &str, value: &str) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -65,8 +62,8 @@ impl AppSettingsRepository for SqliteAppSettingsRepository {
         Ok(())
     }
 
-    async fn delete(&self, key: &str) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn delete(&self, key: &str) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute("DELETE FROM app_settings WHERE key = ?", params![key])?;
@@ -74,8 +71,8 @@ impl AppSettingsRepository for SqliteAppSettingsRepository {
         Ok(())
     }
 
-    async fn list(&self) -> Result<Vec<(String, String)>> {
-        let db = self.db.lock().await;
+    async fn list(&self) -> RepoResult<Vec<(String, String)>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare("SELECT key, value FROM app_settings ORDER BY key")?;
@@ -87,8 +84,8 @@ impl AppSettingsRepository for SqliteAppSettingsRepository {
         Ok(rows)
     }
 
-    async fn list_by_prefix(&self, prefix: &str) -> Result<Vec<(String, String)>> {
-        let db = self.db.lock().await;
+    async fn list_by_prefix(&self, prefix: &str) -> RepoResult<Vec<(String, String)>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Use LIKE with escaped prefix for prefix matching
@@ -110,6 +107,8 @@ impl AppSettingsRepository for SqliteAppSettingsRepository {
 mod tests {
     use super::*;
     use crate::Database;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     async fn setup_test_db() -> Arc<Mutex<Database>> {
         let db = Database::open_in_memory().expect("Failed to create test database");
@@ -0,0 +1,378 @@
+//! SQLite implementation of AuditRepository.
+//!
+//! Entries are hash-chained: each row stores the SHA-256 of its content and
+//! of the previous row's hash. Triggers reject UPDATE and DELETE, and
+//! [`SqliteAuditRepository::verify_chain`] detects rows edited, inserted or
+//! removed by anything that bypassed them (e.g. a raw SQLite shell).
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use mcpmux_core::{
+    AuditAction, AuditEntry, AuditFilter, AuditRepository, Page, PageCursor, RepoResult,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+
+use crate::DbHandle;
+
+/// SQLite-backed, append-only audit log.
+pub struct SqliteAuditRepository {
+    db: DbHandle,
+}
+
+impl SqliteAuditRepository {
+    /// Create a new SQLite audit repository.
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self { db: db.into() }
+    }
+
+    /// Standard column list for SELECT queries.
+    const SELECT_COLUMNS: &'static str =
+        "seq, id, timestamp, actor, action, target_id, detail, prev_hash, hash";
+
+    /// Append `entry` on a connection to this repository's database.
+    ///
+    /// For repositories that already hold the database lock and want the
+    /// entry written in the same transaction as the change it records.
+    pub(crate) fn append(&self, conn: &Connection, entry: &AuditEntry) -> Result<()> {
+        let prev_hash: String = conn
+            .query_row(
+                "SELECT hash FROM audit_log ORDER BY seq DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?
+            .unwrap_or_default();
+        let timestamp = Self::format_timestamp(entry.timestamp);
+        let hash = Self::entry_hash(&prev_hash, entry, &timestamp);
+
+        conn.execute(
+            "INSERT INTO audit_log (id, timestamp, actor, action, target_id, detail, prev_hash, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                entry.id.to_string(),
+                timestamp,
+                entry.actor,
+                entry.action.as_str(),
+                entry.target_id,
+                entry.detail,
+                prev_hash,
+                hash,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Check the hash chain over the whole log.
+    ///
+    /// Returns the number of entries checked, or an error naming the first
+    /// entry whose content or position doesn't match its hash.
+    pub async fn verify_chain(&self) -> Result<usize> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM audit_log ORDER BY seq",
+            Self::SELECT_COLUMNS
+        ))?;
+        let rows: Vec<(AuditEntry, String, String, String)> = stmt
+            .query_map([], |row| {
+                Ok((
+                    Self::row_to_entry(row)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(7)?,
+                    row.get::<_, String>(8)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut expected_prev = String::new();
+        for (entry, timestamp, prev_hash, hash) in &rows {
+            if *prev_hash != expected_prev || *hash != Self::entry_hash(prev_hash, entry, timestamp)
+            {
+                bail!("Audit log chain broken at entry {}", entry.id);
+            }
+            expected_prev = hash.clone();
+        }
+        Ok(rows.len())
+    }
+
+    /// SHA-256 (hex) over the previous hash and the entry's stored fields.
+    fn entry_hash(prev_hash: &str, entry: &AuditEntry, timestamp: &str) -> String {
+        let mut hasher = Sha256::new();
+        for field in [
+            prev_hash,
+            &entry.id.to_string(),
+            timestamp,
+            &entry.actor,
+            entry.action.as_str(),
+            &entry.target_id,
+        ] {
+            // Length-prefixed so field boundaries can't be shifted
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field.as_bytes());
+        }
+        match &entry.detail {
+            Some(detail) => {
+                hasher.update([1]);
+                hasher.update((detail.len() as u64).to_be_bytes());
+                hasher.update(detail.as_bytes());
+            }
+            None => hasher.update([0]),
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Fixed width, so the stored strings compare in time order.
+    fn format_timestamp(at: DateTime<Utc>) -> String {
+        at.to_rfc3339_opts(SecondsFormat::Micros, true)
+    }
+
+    /// Build an AuditEntry from a row selected with [`Self::SELECT_COLUMNS`].
+    fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<AuditEntry> {
+        let id: String = row.get(1)?;
+        let timestamp: String = row.get(2)?;
+        let action: String = row.get(4)?;
+        let invalid = |col: usize, what: &str, value: &str| {
+            rusqlite::Error::FromSqlConversionFailure(
+                col,
+                rusqlite::types::Type::Text,
+                anyhow!("Invalid audit {} '{}'", what, value).into(),
+            )
+        };
+
+        Ok(AuditEntry {
+            id: id.parse().map_err(|_| invalid(1, "id", &id))?,
+            timestamp: DateTime::parse_from_rfc3339(&timestamp)
+                .map_err(|_| invalid(2, "timestamp", &timestamp))?
+                .with_timezone(&Utc),
+            actor: row.get(3)?,
+            action: AuditAction::parse(&action).ok_or_else(|| invalid(4, "action", &action))?,
+            target_id: row.get(5)?,
+            detail: row.get(6)?,
+        })
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqliteAuditRepository {
+    async fn record(&self, entry: &AuditEntry) -> RepoResult<()> {
+        let db = self.db.get().await?;
+        Ok(db.transaction(|conn| self.append(conn, entry))?)
+    }
+
+    async fn list_audit(
+        &self,
+        filter: &AuditFilter,
+        page: &Page,
+    ) -> RepoResult<(Vec<AuditEntry>, Option<PageCursor>)> {
+        let before_seq = match &page.after {
+            Some(cursor) => match cursor.key()?.as_slice() {
+                [seq] => Some(
+                    seq.parse::<i64>()
+                        .map_err(|_| anyhow!("Invalid page cursor"))?,
+                ),
+                _ => return Err(anyhow!("Invalid page cursor").into()),
+            },
+            None => None,
+        };
+        let actions = (!filter.actions.is_empty())
+            .then(|| serde_json::to_string(&filter.actions))
+            .transpose()?;
+        let limit = page.effective_limit();
+
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        // Newest first, keyset on seq; one extra row tells us whether there
+        // is a next page
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM audit_log
+             WHERE (?1 IS NULL OR seq < ?1)
+               AND (?2 IS NULL OR action IN (SELECT value FROM json_each(?2)))
+               AND (?3 IS NULL OR timestamp >= ?3)
+               AND (?4 IS NULL OR timestamp < ?4)
+             ORDER BY seq DESC
+             LIMIT ?5",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let mut rows: Vec<(i64, AuditEntry)> = stmt
+            .query_map(
+                params![
+                    before_seq,
+                    actions,
+                    filter.since.map(Self::format_timestamp),
+                    filter.until.map(Self::format_timestamp),
+                    limit as i64 + 1
+                ],
+                |row| Ok((row.get(0)?, Self::row_to_entry(row)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let next = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last()
+                .map(|(seq, _)| PageCursor::from_key(&[seq.to_string()]))
+        } else {
+            None
+        };
+
+        Ok((rows.into_iter().map(|(_, entry)| entry).collect(), next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    fn setup() -> (Arc<Mutex<Database>>, SqliteAuditRepository) {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let repo = SqliteAuditRepository::new(db.clone());
+        (db, repo)
+    }
+
+    fn entry(action: AuditAction, target: &str, at: DateTime<Utc>) -> AuditEntry {
+        AuditEntry {
+            timestamp: at,
+            ..AuditEntry::new("test", action, target)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_filter() {
+        let (_db, repo) = setup();
+        let t0 = Utc::now();
+        let hour = chrono::Duration::hours(1);
+
+        repo.record(&entry(AuditAction::CredentialCreated, "a", t0))
+            .await
+            .unwrap();
+        repo.record(
+            &entry(AuditAction::CredentialRead, "a", t0 + hour).with_detail("1 credential"),
+        )
+        .await
+        .unwrap();
+        repo.record(&entry(AuditAction::CredentialDeleted, "a", t0 + hour * 2))
+            .await
+            .unwrap();
+
+        let (all, next) = repo
+            .list_audit(&AuditFilter::default(), &Page::first(10))
+            .await
+            .unwrap();
+        assert!(next.is_none());
+        let actions: Vec<_> = all.iter().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::CredentialDeleted,
+                AuditAction::CredentialRead,
+                AuditAction::CredentialCreated
+            ]
+        );
+        assert_eq!(all[1].detail.as_deref(), Some("1 credential"));
+
+        let filter = AuditFilter {
+            actions: vec![
+                AuditAction::CredentialCreated,
+                AuditAction::CredentialDeleted,
+            ],
+            ..Default::default()
+        };
+        let (rows, _) = repo.list_audit(&filter, &Page::first(10)).await.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|e| filter.matches(e)));
+
+        let filter = AuditFilter {
+            since: Some(t0 + hour),
+            until: Some(t0 + hour * 2),
+            ..Default::default()
+        };
+        let (rows, _) = repo.list_audit(&filter, &Page::first(10)).await.unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].action, AuditAction::CredentialRead);
+    }
+
+    #[tokio::test]
+    async fn test_pagination() {
+        let (_db, repo) = setup();
+        for i in 0..5 {
+            repo.record(&AuditEntry::new(
+                "test",
+                AuditAction::CredentialRead,
+                i.to_string(),
+            ))
+            .await
+            .unwrap();
+        }
+
+        let mut targets = Vec::new();
+        let mut page = Page::first(2);
+        loop {
+            let (rows, next) = repo
+                .list_audit(&AuditFilter::default(), &page)
+                .await
+                .unwrap();
+            targets.extend(rows.into_iter().map(|e| e.target_id));
+            match next {
+                Some(cursor) => page = Page::after(cursor, 2),
+                None => break,
+            }
+        }
+        assert_eq!(targets, vec!["4", "3", "2", "1", "0"]);
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_immutable() {
+        let (db, repo) = setup();
+        repo.record(&AuditEntry::new(
+            "test",
+            AuditAction::CredentialDeleted,
+            "a",
+        ))
+        .await
+        .unwrap();
+
+        let db = db.lock().await;
+        let conn = db.connection();
+        let err = conn
+            .execute("UPDATE audit_log SET actor = 'someone else'", [])
+            .unwrap_err();
+        assert!(err.to_string().contains("append-only"), "{}", err);
+        let err = conn.execute("DELETE FROM audit_log", []).unwrap_err();
+        assert!(err.to_string().contains("append-only"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_verify_chain_detects_tampering() {
+        let (db, repo) = setup();
+        for target in ["a", "b", "c"] {
+            repo.record(&AuditEntry::new(
+                "test",
+                AuditAction::CredentialRead,
+                target,
+            ))
+            .await
+            .unwrap();
+        }
+        assert_eq!(repo.verify_chain().await.unwrap(), 3);
+
+        // Someone with raw database access drops the trigger and edits a row
+        {
+            let db = db.lock().await;
+            db.connection()
+                .execute_batch(
+                    "DROP TRIGGER audit_log_no_update;
+                     UPDATE audit_log SET target_id = 'z' WHERE target_id = 'b';",
+                )
+                .unwrap();
+        }
+        let err = repo.verify_chain().await.unwrap_err().to_string();
+        assert!(err.contains("chain broken"), "{}", err);
+    }
+}
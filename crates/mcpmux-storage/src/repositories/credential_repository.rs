@@ -2,22 +2,42 @@
 //!
 //! Each credential is stored as a separate row per (space, server, type).
 //! Only the secret value is encrypted — metadata (type, expiry, scope) is plaintext.
+//! Each ciphertext is bound to its row (credential id + space id) as associated
+//! data, so a value copied into another row fails to decrypt.
+//!
+//! Reads are scoped to the space asking: rows are selected by space, and
+//! values are decrypted with the requesting space's id, so a row that ends up
+//! under another space is refused rather than handed over.
+//!
+//! With [`SqliteCredentialRepository::with_audit`], every create, read, update
+//! and delete is recorded in the audit log. Changes are recorded in the same
+//! transaction as the change itself. Reads are decrypted outside any
+//! transaction and recorded afterwards in one short write, so without an audit
+//! log a read never takes the write lock.
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mcpmux_core::{Credential, CredentialRepository, CredentialType};
-use rusqlite::{params, OptionalExtension};
-use tokio::sync::Mutex;
+use mcpmux_core::{
+    AuditAction, AuditEntry, Credential, CredentialRepository, CredentialType, ImportItemResult,
+    ImportMode, ImportReport, NewCredential, Page, PageCursor, RepoResult, StaleWrite,
+    StorageError, Upserted,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use tracing::debug;
 use uuid::Uuid;
+use zeroize::Zeroizing;
 
-use crate::crypto::FieldEncryptor;
-use crate::Database;
+use super::cached::CachedStatements;
+use crate::crypto::{into_utf8, FieldEncryptor};
+use crate::{Database, DbConn, DbHandle, SqliteAuditRepository};
 
 /// Raw row data extracted from SQLite before decryption.
 struct RawCredentialRow {
+    id: String,
     space_id: String,
     server_id: String,
     credential_type: String,
@@ -28,6 +48,7 @@ struct RawCredentialRow {
     last_used_at: Option<String>,
     created_at: String,
     updated_at: String,
+    version: i64,
 }
 
 /// SQLite-backed credential repository with field-level encryption.
@@ -35,28 +56,424 @@ struct RawCredentialRow {
 /// Only the secret value (token, key, password) is encrypted using AES-256-GCM.
 /// Metadata fields (type, expiry, scope) are stored as plaintext for queryability.
 pub struct SqliteCredentialRepository {
-    db: Arc<Mutex<Database>>,
-    encryptor: Arc<FieldEncryptor>,
+    db: DbHandle,
+    /// Swapped for the new encryptor once `reencrypt_all` commits.
+    encryptor: RwLock<Arc<FieldEncryptor>>,
+    /// Audit log and the actor recorded in its entries.
+    audit: Option<(Arc<SqliteAuditRepository>, String)>,
 }
 
 impl SqliteCredentialRepository {
     /// Create a new credential repository.
-    pub fn new(db: Arc<Mutex<Database>>, encryptor: Arc<FieldEncryptor>) -> Self {
-        Self { db, encryptor }
+    pub fn new(db: impl Into<DbHandle>, encryptor: Arc<FieldEncryptor>) -> Self {
+        Self {
+            db: db.into(),
+            encryptor: RwLock::new(encryptor),
+            audit: None,
+        }
+    }
+
+    /// Record credential access in `audit` as `actor`.
+    ///
+    /// `audit` must use the same database as this repository.
+    pub fn with_audit(
+        mut self,
+        audit: Arc<SqliteAuditRepository>,
+        actor: impl Into<String>,
+    ) -> Self {
+        self.audit = Some((audit, actor.into()));
+        self
+    }
+
+    /// Append an audit entry for one credential, if auditing is enabled.
+    fn audit(
+        &self,
+        conn: &Connection,
+        action: AuditAction,
+        space_id: &str,
+        server_id: &str,
+        credential_type: &str,
+        detail: Option<&str>,
+    ) -> Result<()> {
+        let Some((audit, actor)) = &self.audit else {
+            return Ok(());
+        };
+        let mut entry = AuditEntry::new(
+            actor.as_str(),
+            action,
+            format!("{}/{}/{}", space_id, server_id, credential_type),
+        );
+        entry.detail = detail.map(str::to_string);
+        audit.append(conn, &entry)
+    }
+
+    /// Decrypt rows read on behalf of `space_id` into credentials, auditing
+    /// each as read.
+    ///
+    /// Decryption happens outside any transaction; only the audit entries,
+    /// if auditing is enabled, are written in one.
+    fn read_credentials(
+        &self,
+        db: &DbConn,
+        space_id: &Uuid,
+        rows: Vec<RawCredentialRow>,
+    ) -> Result<Vec<Credential>> {
+        let space_id = space_id.to_string();
+        let mut credentials = Vec::with_capacity(rows.len());
+        for row in rows {
+            credentials.push(self.build_credential(row, &space_id)?);
+        }
+        if self.audit.is_some() && !credentials.is_empty() {
+            db.transaction(|conn| {
+                for credential in &credentials {
+                    self.audit(
+                        conn,
+                        AuditAction::CredentialRead,
+                        &space_id,
+                        &credential.server_id,
+                        credential.credential_type.as_str(),
+                        None,
+                    )?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(credentials)
+    }
+
+    /// Encrypt and upsert one credential on a locked connection, auditing it.
+    pub(super) fn save_in(&self, conn: &Connection, credential: &Credential) -> Result<()> {
+        self.upsert_in(conn, credential, true).map(|_| ())
+    }
+
+    /// Encrypt and upsert one credential, checking its version first if
+    /// `check_version` is set.
+    fn upsert_in(
+        &self,
+        conn: &Connection,
+        credential: &Credential,
+        check_version: bool,
+    ) -> Result<Upserted> {
+        let space_id = credential.space_id.to_string();
+
+        // Reuse the existing row id on update: the ciphertext is bound to it.
+        let existing: Option<(String, bool, i64)> = conn
+            .query_row_cached(
+                "SELECT id, deleted_at IS NULL, version FROM credentials
+                 WHERE space_id = ?1 AND server_id = ?2 AND credential_type = ?3",
+                params![
+                    space_id,
+                    credential.server_id,
+                    credential.credential_type.as_str()
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        // Saving over a soft-deleted row brings it back as a new credential
+        let (action, upserted) = match existing {
+            Some((_, true, current_version)) => {
+                if check_version && credential.version != 0 && credential.version != current_version
+                {
+                    return Err(StaleWrite { current_version }.into());
+                }
+                (AuditAction::CredentialUpdated, Upserted::Updated)
+            }
+            _ => (AuditAction::CredentialCreated, Upserted::Inserted),
+        };
+        let id = existing.map_or_else(|| Uuid::new_v4().to_string(), |(id, ..)| id);
+
+        let encrypted_value = self.encrypt_value(&credential.value, &id, &space_id)?;
+
+        conn.execute_cached(
+            "INSERT INTO credentials (id, space_id, server_id, credential_type, credential_value, expires_at, token_type, scope, last_used_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             ON CONFLICT(space_id, server_id, credential_type) DO UPDATE SET
+                credential_value = excluded.credential_value,
+                expires_at = excluded.expires_at,
+                token_type = excluded.token_type,
+                scope = excluded.scope,
+                updated_at = excluded.updated_at,
+                last_used_at = excluded.last_used_at,
+                deleted_at = NULL,
+                version = credentials.version + 1",
+            params![
+                id,
+                space_id,
+                credential.server_id,
+                credential.credential_type.as_str(),
+                encrypted_value,
+                credential.expires_at.map(|dt| dt.to_rfc3339()),
+                credential.token_type,
+                credential.scope,
+                credential.last_used.map(|dt| dt.to_rfc3339()),
+                credential.created_at.to_rfc3339(),
+                credential.updated_at.to_rfc3339(),
+            ],
+        )?;
+
+        self.audit(
+            conn,
+            action,
+            &space_id,
+            &credential.server_id,
+            credential.credential_type.as_str(),
+            None,
+        )?;
+        Ok(upserted)
+    }
+
+    /// Hard-delete credentials of one space with `sql`, a `DELETE ...
+    /// RETURNING server_id, credential_type` statement, auditing each row.
+    /// Returns the number of rows deleted.
+    fn delete_returning(
+        &self,
+        db: &DbConn,
+        sql: &str,
+        params: impl rusqlite::Params,
+        space_id: &Uuid,
+        detail: Option<&str>,
+    ) -> Result<usize> {
+        db.transaction(|conn| {
+            let deleted: Vec<(String, String)> = conn
+                .prepare_cached(sql)?
+                .query_map(params, |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (server_id, credential_type) in &deleted {
+                self.audit(
+                    conn,
+                    AuditAction::CredentialDeleted,
+                    &space_id.to_string(),
+                    server_id,
+                    credential_type,
+                    detail,
+                )?;
+            }
+            Ok(deleted.len())
+        })
+    }
+
+    /// Get the encryptor currently used for credential values.
+    fn encryptor(&self) -> Arc<FieldEncryptor> {
+        self.encryptor.read().unwrap().clone()
+    }
+
+    /// Re-encrypt every stored credential with `new_encryptor`.
+    ///
+    /// All rows are decrypted with the current encryptor and rewritten inside a
+    /// single transaction. If any row fails to decrypt, the whole operation is
+    /// rolled back and the error names the offending credential id. On success
+    /// the database's key fingerprint is updated, the repository switches to
+    /// `new_encryptor`, and the number of re-encrypted credentials is returned.
+    pub async fn reencrypt_all(&self, new_encryptor: Arc<FieldEncryptor>) -> Result<usize> {
+        let db = self.db.get().await?;
+        let current = self.encryptor();
+
+        let count = db.transaction(|conn| {
+            let rows: Vec<(String, String, String)> = conn
+                .prepare("SELECT id, space_id, credential_value FROM credentials")?
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for (id, space_id, encrypted) in &rows {
+                let value = Self::open_value(&current, encrypted, id, space_id).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to decrypt credential {} during re-encryption: {}",
+                        id,
+                        e
+                    )
+                })?;
+                let reencrypted = new_encryptor
+                    .encrypt_with_aad(value.as_bytes(), &Self::credential_aad(id, space_id))?;
+                conn.execute_cached(
+                    "UPDATE credentials SET credential_value = ?1 WHERE id = ?2",
+                    params![reencrypted, id],
+                )?;
+            }
+
+            Database::store_key_fingerprint(conn, &new_encryptor)?;
+
+            Ok(rows.len())
+        })?;
+
+        *self.encryptor.write().unwrap() = new_encryptor;
+        Ok(count)
+    }
+
+    /// Import many credentials in one transaction.
+    ///
+    /// Each item is validated (known type, non-empty server id and value, an
+    /// existing space, no duplicate within the batch) and then saved exactly
+    /// like [`save`](CredentialRepository::save) would. The report has one
+    /// result per item. In [`ImportMode::BestEffort`] the valid items are
+    /// committed even if others fail; in [`ImportMode::AllOrNothing`] any
+    /// failure rolls the whole batch back. Only database-level errors (not
+    /// item errors) are returned as `Err`.
+    pub async fn import(
+        &self,
+        items: Vec<NewCredential>,
+        mode: ImportMode,
+    ) -> Result<ImportReport> {
+        let db = self.db.get().await?;
+        let (results, committed) = db.transaction(|conn| {
+            // An all-or-nothing batch with failures is undone as a whole
+            conn.execute_batch("SAVEPOINT import_batch")?;
+
+            let mut spaces: HashMap<Uuid, bool> = HashMap::new();
+            let mut seen: HashMap<(Uuid, String, CredentialType), usize> = HashMap::new();
+            let mut results = Vec::with_capacity(items.len());
+
+            for (index, item) in items.into_iter().enumerate() {
+                let outcome =
+                    self.validate_import(conn, item, &mut spaces)
+                        .and_then(|credential| {
+                            let key = (
+                                credential.space_id,
+                                credential.server_id.clone(),
+                                credential.credential_type.clone(),
+                            );
+                            if let Some(first) = seen.insert(key, index) {
+                                anyhow::bail!("duplicate of item {}", first);
+                            }
+                            // A failed insert must not take the items before it along
+                            conn.execute_batch("SAVEPOINT import_item")?;
+                            match self.save_in(conn, &credential) {
+                                Ok(()) => conn.execute_batch("RELEASE import_item")?,
+                                Err(e) => {
+                                    conn.execute_batch(
+                                        "ROLLBACK TO import_item; RELEASE import_item",
+                                    )?;
+                                    return Err(e);
+                                }
+                            }
+                            Ok(())
+                        });
+                results.push(ImportItemResult {
+                    index,
+                    error: outcome.err().map(|e| e.to_string()),
+                });
+            }
+
+            let failed = results.iter().any(|r| r.error.is_some());
+            let committed = mode == ImportMode::BestEffort || !failed;
+            if committed {
+                conn.execute_batch("RELEASE import_batch")?;
+            } else {
+                conn.execute_batch("ROLLBACK TO import_batch; RELEASE import_batch")?;
+            }
+            Ok((results, committed))
+        })?;
+
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        debug!(
+            "[CredentialRepository] Imported {} of {} credentials ({} failed, committed: {})",
+            if committed { results.len() - failed } else { 0 },
+            results.len(),
+            failed,
+            committed
+        );
+
+        Ok(ImportReport { results, committed })
+    }
+
+    /// Check one import item and turn it into a credential.
+    fn validate_import(
+        &self,
+        conn: &Connection,
+        item: NewCredential,
+        spaces: &mut HashMap<Uuid, bool>,
+    ) -> Result<Credential> {
+        let credential_type = CredentialType::parse(item.credential_type.trim())
+            .ok_or_else(|| anyhow::anyhow!("unknown credential type '{}'", item.credential_type))?;
+        let server_id = item.server_id.trim();
+        if server_id.is_empty() {
+            anyhow::bail!("server_id is empty");
+        }
+        if item.value.is_empty() {
+            anyhow::bail!("value is empty");
+        }
+        let space_id: Uuid = item
+            .space_id
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid space id '{}'", item.space_id))?;
+
+        let exists = match spaces.get(&space_id) {
+            Some(exists) => *exists,
+            None => {
+                let exists = conn
+                    .query_row_cached(
+                        "SELECT 1 FROM spaces WHERE id = ?1 AND deleted_at IS NULL",
+                        params![space_id.to_string()],
+                        |_| Ok(()),
+                    )
+                    .optional()?
+                    .is_some();
+                *spaces.entry(space_id).or_insert(exists)
+            }
+        };
+        if !exists {
+            anyhow::bail!("space {} does not exist", space_id);
+        }
+
+        let now = Utc::now();
+        Ok(Credential {
+            space_id,
+            server_id: server_id.to_string(),
+            credential_type,
+            value: item.value,
+            expires_at: item.expires_at,
+            token_type: item.token_type,
+            scope: item.scope,
+            created_at: now,
+            updated_at: now,
+            last_used: None,
+            version: 0,
+        })
+    }
+
+    /// Associated data binding a credential ciphertext to its row.
+    fn credential_aad(id: &str, space_id: &str) -> Vec<u8> {
+        format!("credential:{}:{}", space_id, id).into_bytes()
+    }
+
+    /// Decrypt a stored value bound to its row.
+    ///
+    /// Values written before AAD binding existed fall back to empty AAD.
+    fn open_value(
+        encryptor: &FieldEncryptor,
+        encrypted: &str,
+        id: &str,
+        space_id: &str,
+    ) -> Result<Zeroizing<String>> {
+        let plaintext =
+            match encryptor.decrypt_with_aad(encrypted, &Self::credential_aad(id, space_id)) {
+                Ok(plaintext) => plaintext,
+                Err(e) => encryptor.decrypt_with_aad(encrypted, &[]).map_err(|_| e)?,
+            };
+        into_utf8(plaintext).context("Decrypted credential is not valid UTF-8")
     }
 
     /// Encrypt a credential value for storage.
-    fn encrypt_value(&self, value: &str) -> Result<String> {
-        self.encryptor
-            .encrypt(value)
-            .map_err(|e| anyhow::anyhow!("Failed to encrypt credential value: {}", e))
+    fn encrypt_value(&self, value: &str, id: &str, space_id: &str) -> Result<String> {
+        self.encryptor()
+            .encrypt_with_aad(value.as_bytes(), &Self::credential_aad(id, space_id))
+            .map_err(|e| {
+                StorageError::Crypto(anyhow::anyhow!("Failed to encrypt credential value: {}", e))
+                    .into()
+            })
     }
 
     /// Decrypt a credential value from storage.
-    fn decrypt_value(&self, encrypted: &str) -> Result<String> {
-        self.encryptor
-            .decrypt(encrypted)
-            .map_err(|e| anyhow::anyhow!("Failed to decrypt credential value: {}", e))
+    fn decrypt_value(
+        &self,
+        encrypted: &str,
+        id: &str,
+        space_id: &str,
+    ) -> Result<Zeroizing<String>> {
+        Self::open_value(&self.encryptor(), encrypted, id, space_id).map_err(|e| {
+            StorageError::Crypto(anyhow::anyhow!("Failed to decrypt credential value: {}", e))
+                .into()
+        })
     }
 
     /// Parse a datetime string to DateTime<Utc>.
@@ -77,7 +494,7 @@ impl SqliteCredentialRepository {
 
     /// Standard column list for SELECT queries.
     const SELECT_COLUMNS: &'static str =
-        "space_id, server_id, credential_type, credential_value, expires_at, token_type, scope, last_used_at, created_at, updated_at";
+        "space_id, server_id, credential_type, credential_value, expires_at, token_type, scope, last_used_at, created_at, updated_at, id, version";
 
     /// Extract raw row data from a rusqlite Row.
     fn extract_row(row: &rusqlite::Row) -> rusqlite::Result<RawCredentialRow> {
@@ -92,12 +509,20 @@ impl SqliteCredentialRepository {
             last_used_at: row.get(7)?,
             created_at: row.get(8)?,
             updated_at: row.get(9)?,
+            id: row.get(10)?,
+            version: row.get(11)?,
         })
     }
 
-    /// Build a Credential from extracted row data (needs &self for decryption).
-    fn build_credential(&self, row: RawCredentialRow) -> Result<Credential> {
-        let value = self.decrypt_value(&row.credential_value)?;
+    /// Build a Credential from row data read on behalf of `space_id`.
+    ///
+    /// The value is decrypted as bound to `space_id` rather than the space the
+    /// row claims, so a row moved into another space fails to decrypt.
+    fn build_credential(&self, row: RawCredentialRow, space_id: &str) -> Result<Credential> {
+        if row.space_id != space_id {
+            return Err(StorageError::NotFound(format!("Credential {}", row.id)).into());
+        }
+        let mut value = self.decrypt_value(&row.credential_value, &row.id, space_id)?;
         let credential_type = CredentialType::parse(&row.credential_type)
             .ok_or_else(|| anyhow::anyhow!("Unknown credential type: {}", row.credential_type))?;
 
@@ -105,17 +530,46 @@ impl SqliteCredentialRepository {
             space_id: row.space_id.parse().unwrap_or_else(|_| Uuid::new_v4()),
             server_id: row.server_id,
             credential_type,
-            value,
+            // Moves the buffer; the credential wipes it on drop
+            value: std::mem::take(&mut *value),
             expires_at: Self::parse_optional_datetime(row.expires_at),
             token_type: row.token_type,
             scope: row.scope,
             created_at: Self::parse_datetime(&row.created_at),
             updated_at: Self::parse_datetime(&row.updated_at),
             last_used: Self::parse_optional_datetime(row.last_used_at),
+            version: row.version,
         })
     }
 }
 
+impl SqliteCredentialRepository {
+    /// Get a credential by its row id, as long as it belongs to `space_id`.
+    ///
+    /// Fails with [`StorageError::NotFound`] for a credential in another
+    /// space, exactly as for one that doesn't exist.
+    pub async fn get_by_id(&self, space_id: &Uuid, credential_id: &str) -> RepoResult<Credential> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM credentials WHERE id = ?1 AND space_id = ?2 AND deleted_at IS NULL",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let row = stmt
+            .query_row(
+                params![credential_id, space_id.to_string()],
+                Self::extract_row,
+            )
+            .optional()?
+            .ok_or_else(|| StorageError::NotFound(format!("Credential {}", credential_id)))?;
+
+        let mut found = self.read_credentials(&db, space_id, vec![row])?;
+        Ok(found.remove(0))
+    }
+}
+
 #[async_trait]
 impl CredentialRepository for SqliteCredentialRepository {
     async fn get(
@@ -123,12 +577,12 @@ impl CredentialRepository for SqliteCredentialRepository {
         space_id: &Uuid,
         server_id: &str,
         credential_type: &CredentialType,
-    ) -> Result<Option<Credential>> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<Option<Credential>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
-        let mut stmt = conn.prepare(&format!(
-            "SELECT {} FROM credentials WHERE space_id = ?1 AND server_id = ?2 AND credential_type = ?3",
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM credentials WHERE space_id = ?1 AND server_id = ?2 AND credential_type = ?3 AND deleted_at IS NULL",
             Self::SELECT_COLUMNS
         ))?;
 
@@ -139,18 +593,16 @@ impl CredentialRepository for SqliteCredentialRepository {
             )
             .optional()?;
 
-        match row {
-            Some(raw) => Ok(Some(self.build_credential(raw)?)),
-            None => Ok(None),
-        }
+        let mut found = self.read_credentials(&db, space_id, row.into_iter().collect())?;
+        Ok(found.pop())
     }
 
-    async fn get_all(&self, space_id: &Uuid, server_id: &str) -> Result<Vec<Credential>> {
-        let db = self.db.lock().await;
+    async fn get_all(&self, space_id: &Uuid, server_id: &str) -> RepoResult<Vec<Credential>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
-        let mut stmt = conn.prepare(&format!(
-            "SELECT {} FROM credentials WHERE space_id = ?1 AND server_id = ?2 ORDER BY credential_type",
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM credentials WHERE space_id = ?1 AND server_id = ?2 AND deleted_at IS NULL ORDER BY credential_type",
             Self::SELECT_COLUMNS
         ))?;
 
@@ -158,41 +610,17 @@ impl CredentialRepository for SqliteCredentialRepository {
             .query_map(params![space_id.to_string(), server_id], Self::extract_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
-        rows.into_iter().map(|r| self.build_credential(r)).collect()
+        Ok(self.read_credentials(&db, space_id, rows)?)
     }
 
-    async fn save(&self, credential: &Credential) -> Result<()> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
-
-        let encrypted_value = self.encrypt_value(&credential.value)?;
-
-        conn.execute(
-            "INSERT INTO credentials (id, space_id, server_id, credential_type, credential_value, expires_at, token_type, scope, last_used_at, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-             ON CONFLICT(space_id, server_id, credential_type) DO UPDATE SET
-                credential_value = excluded.credential_value,
-                expires_at = excluded.expires_at,
-                token_type = excluded.token_type,
-                scope = excluded.scope,
-                updated_at = excluded.updated_at,
-                last_used_at = excluded.last_used_at",
-            params![
-                Uuid::new_v4().to_string(),
-                credential.space_id.to_string(),
-                credential.server_id,
-                credential.credential_type.as_str(),
-                encrypted_value,
-                credential.expires_at.map(|dt| dt.to_rfc3339()),
-                credential.token_type,
-                credential.scope,
-                credential.last_used.map(|dt| dt.to_rfc3339()),
-                credential.created_at.to_rfc3339(),
-                credential.updated_at.to_rfc3339(),
-            ],
-        )?;
+    async fn save(&self, credential: &Credential) -> RepoResult<()> {
+        let db = self.db.get().await?;
+        Ok(db.transaction(|conn| self.save_in(conn, credential))?)
+    }
 
-        Ok(())
+    async fn upsert(&self, credential: &Credential) -> RepoResult<Upserted> {
+        let db = self.db.get().await?;
+        Ok(db.transaction(|conn| self.upsert_in(conn, credential, false))?)
     }
 
     async fn delete(
@@ -200,49 +628,120 @@ impl CredentialRepository for SqliteCredentialRepository {
         space_id: &Uuid,
         server_id: &str,
         credential_type: &CredentialType,
-    ) -> Result<()> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
 
-        conn.execute(
-            "DELETE FROM credentials WHERE space_id = ?1 AND server_id = ?2 AND credential_type = ?3",
+        self.delete_returning(
+            &db,
+            "DELETE FROM credentials WHERE space_id = ?1 AND server_id = ?2 AND credential_type = ?3
+             RETURNING server_id, credential_type",
             params![space_id.to_string(), server_id, credential_type.as_str()],
+            space_id,
+            None,
         )?;
 
         Ok(())
     }
 
-    async fn delete_all(&self, space_id: &Uuid, server_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
+    async fn delete_all(&self, space_id: &Uuid, server_id: &str) -> RepoResult<()> {
+        let db = self.db.get().await?;
 
-        conn.execute(
-            "DELETE FROM credentials WHERE space_id = ?1 AND server_id = ?2",
+        self.delete_returning(
+            &db,
+            "DELETE FROM credentials WHERE space_id = ?1 AND server_id = ?2
+             RETURNING server_id, credential_type",
             params![space_id.to_string(), server_id],
+            space_id,
+            None,
         )?;
 
         Ok(())
     }
 
-    async fn clear_tokens(&self, space_id: &Uuid, server_id: &str) -> Result<bool> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
+    async fn clear_tokens(&self, space_id: &Uuid, server_id: &str) -> RepoResult<bool> {
+        let db = self.db.get().await?;
 
         // Delete only OAuth tokens (access_token + refresh_token), preserve API keys etc.
-        let deleted = conn.execute(
-            "DELETE FROM credentials WHERE space_id = ?1 AND server_id = ?2 AND credential_type IN ('access_token', 'refresh_token')",
+        let deleted = self.delete_returning(
+            &db,
+            "DELETE FROM credentials WHERE space_id = ?1 AND server_id = ?2 AND credential_type IN ('access_token', 'refresh_token')
+             RETURNING server_id, credential_type",
             params![space_id.to_string(), server_id],
+            space_id,
+            Some("tokens cleared"),
         )?;
 
         Ok(deleted > 0)
     }
 
-    async fn list_for_space(&self, space_id: &Uuid) -> Result<Vec<Credential>> {
-        let db = self.db.lock().await;
+    async fn sweep_expired(&self) -> RepoResult<usize> {
+        let db = self.db.get().await?;
+        let now = Utc::now();
+
+        Ok(db.transaction(|conn| {
+            let rows: Vec<(String, String, String, String, String)> = conn
+                .prepare(
+                    "SELECT id, space_id, server_id, credential_type, expires_at FROM credentials
+                     WHERE expires_at IS NOT NULL AND deleted_at IS NULL",
+                )?
+                .query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // Servers whose refresh token can still renew an expired access token
+            let refresh_tokens: Vec<(String, String, Option<String>)> = conn
+                .prepare(
+                    "SELECT space_id, server_id, expires_at FROM credentials
+                     WHERE credential_type = 'refresh_token' AND deleted_at IS NULL",
+                )?
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let refreshable: HashSet<(String, String)> = refresh_tokens
+                .into_iter()
+                .filter(|(_, _, exp)| exp.as_ref().is_none_or(|e| Self::parse_datetime(e) >= now))
+                .map(|(space_id, server_id, _)| (space_id, server_id))
+                .collect();
+
+            let mut swept = 0;
+            for (id, space_id, server_id, credential_type, expires_at) in rows {
+                if Self::parse_datetime(&expires_at) >= now
+                    || (credential_type == CredentialType::AccessToken.as_str()
+                        && refreshable.contains(&(space_id.clone(), server_id.clone())))
+                {
+                    continue;
+                }
+                conn.execute_cached("DELETE FROM credentials WHERE id = ?1", params![id])?;
+                self.audit(
+                    conn,
+                    AuditAction::CredentialDeleted,
+                    &space_id,
+                    &server_id,
+                    &credential_type,
+                    Some("expired"),
+                )?;
+                swept += 1;
+            }
+
+            if swept > 0 {
+                debug!("[CredentialRepository] Swept {} expired credentials", swept);
+            }
+            Ok(swept)
+        })?)
+    }
+
+    async fn list_for_space(&self, space_id: &Uuid) -> RepoResult<Vec<Credential>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
-        let mut stmt = conn.prepare(&format!(
-            "SELECT {} FROM credentials WHERE space_id = ?1 ORDER BY server_id, credential_type",
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM credentials WHERE space_id = ?1 AND deleted_at IS NULL ORDER BY server_id, credential_type",
             Self::SELECT_COLUMNS
         ))?;
 
@@ -250,13 +749,67 @@ impl CredentialRepository for SqliteCredentialRepository {
             .query_map(params![space_id.to_string()], Self::extract_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
-        rows.into_iter().map(|r| self.build_credential(r)).collect()
+        Ok(self.read_credentials(&db, space_id, rows)?)
+    }
+
+    async fn list_for_space_page(
+        &self,
+        space_id: &Uuid,
+        page: &Page,
+    ) -> RepoResult<(Vec<Credential>, Option<PageCursor>)> {
+        let after = match &page.after {
+            Some(cursor) => match cursor.key()?.as_slice() {
+                [server_id, credential_type] => Some((server_id.clone(), credential_type.clone())),
+                _ => return Err(anyhow::anyhow!("Invalid page cursor").into()),
+            },
+            None => None,
+        };
+        let (after_server, after_type) = after.unzip();
+        let limit = page.effective_limit();
+
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        // Keyset on (server_id, credential_type); one extra row tells us
+        // whether there is a next page
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM credentials
+             WHERE space_id = ?1 AND deleted_at IS NULL
+               AND (?2 IS NULL OR server_id > ?2 OR (server_id = ?2 AND credential_type > ?3))
+             ORDER BY server_id, credential_type
+             LIMIT ?4",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let mut rows: Vec<_> = stmt
+            .query_map(
+                params![
+                    space_id.to_string(),
+                    after_server,
+                    after_type,
+                    limit as i64 + 1
+                ],
+                Self::extract_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let next = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last()
+                .map(|r| PageCursor::from_key(&[&r.server_id, &r.credential_type]))
+        } else {
+            None
+        };
+
+        let credentials = self.read_credentials(&db, space_id, rows)?;
+        Ok((credentials, next))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::sync::Mutex;
 
     /// Helper to create a space in the database (for foreign key constraints)
     async fn create_test_space(db: &Arc<Mutex<Database>>, space_id: &Uuid) {
@@ -465,6 +1018,52 @@ mod tests {
         assert_eq!(repo.list_for_space(&space2).await.unwrap().len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_list_for_space_page() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        create_test_space(&db, &space).await;
+        create_test_space(&db, &other).await;
+        for server in ["a", "b", "c"] {
+            repo.save(&Credential::api_key(space, server, "key"))
+                .await
+                .unwrap();
+        }
+        repo.save(&Credential::access_token(space, "b", "tok", None))
+            .await
+            .unwrap();
+        repo.save(&Credential::api_key(other, "a", "key"))
+            .await
+            .unwrap();
+
+        let (first, next) = repo
+            .list_for_space_page(&space, &Page::first(2))
+            .await
+            .unwrap();
+        let keys: Vec<_> = first
+            .iter()
+            .map(|c| (c.server_id.as_str(), c.credential_type.as_str()))
+            .collect();
+        assert_eq!(keys, vec![("a", "api_key"), ("b", "access_token")]);
+        assert_eq!(first[0].value, "key");
+
+        let (second, next) = repo
+            .list_for_space_page(&space, &Page::after(next.unwrap(), 2))
+            .await
+            .unwrap();
+        let keys: Vec<_> = second
+            .iter()
+            .map(|c| (c.server_id.as_str(), c.credential_type.as_str()))
+            .collect();
+        assert_eq!(keys, vec![("b", "api_key"), ("c", "api_key")]);
+        assert!(next.is_none());
+    }
+
     #[tokio::test]
     async fn test_encryption_is_applied() {
         let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
@@ -508,4 +1107,574 @@ mod tests {
         assert_eq!(cred_type, "api_key");
         assert!(expires_at.is_none()); // API keys don't expire
     }
+
+    #[tokio::test]
+    async fn test_ciphertext_copied_between_rows_is_rejected() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        repo.save(&Credential::api_key(space_id, "github", "github-token"))
+            .await
+            .unwrap();
+        repo.save(&Credential::api_key(space_id, "gitlab", "gitlab-token"))
+            .await
+            .unwrap();
+
+        // Updating keeps the row id, so the value stays readable
+        repo.save(&Credential::api_key(space_id, "github", "github-token-2"))
+            .await
+            .unwrap();
+        let found = repo
+            .get(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.value, "github-token-2");
+
+        // Copy github's ciphertext into gitlab's row
+        {
+            let db_lock = db.lock().await;
+            db_lock
+                .connection()
+                .execute(
+                    "UPDATE credentials SET credential_value =
+                        (SELECT credential_value FROM credentials WHERE server_id = 'github')
+                     WHERE server_id = 'gitlab'",
+                    [],
+                )
+                .unwrap();
+        }
+
+        let result = repo.get(&space_id, "gitlab", &CredentialType::ApiKey).await;
+        assert!(matches!(result, Err(StorageError::Crypto(_))));
+    }
+
+    /// Row id of a stored credential.
+    async fn credential_id(db: &Arc<Mutex<Database>>, space_id: &Uuid, server_id: &str) -> String {
+        let db_lock = db.lock().await;
+        db_lock
+            .connection()
+            .query_row(
+                "SELECT id FROM credentials WHERE space_id = ?1 AND server_id = ?2",
+                params![space_id.to_string(), server_id],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_credential_not_visible_from_other_space() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_a = Uuid::new_v4();
+        let space_b = Uuid::new_v4();
+        create_test_space(&db, &space_a).await;
+        create_test_space(&db, &space_b).await;
+        repo.save(&Credential::api_key(space_a, "github", "a-token"))
+            .await
+            .unwrap();
+        let id = credential_id(&db, &space_a, "github").await;
+
+        let found = repo.get_by_id(&space_a, &id).await.unwrap();
+        assert_eq!(found.value, "a-token");
+
+        let result = repo.get_by_id(&space_b, &id).await;
+        assert!(matches!(result, Err(StorageError::NotFound(_))));
+        let result = repo.get_by_id(&space_a, "no-such-id").await;
+        assert!(matches!(result, Err(StorageError::NotFound(_))));
+
+        assert!(repo
+            .get(&space_b, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repo.list_for_space(&space_b).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_credential_moved_to_other_space_is_rejected() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_a = Uuid::new_v4();
+        let space_b = Uuid::new_v4();
+        create_test_space(&db, &space_a).await;
+        create_test_space(&db, &space_b).await;
+        repo.save(&Credential::api_key(space_a, "github", "a-token"))
+            .await
+            .unwrap();
+        let id = credential_id(&db, &space_a, "github").await;
+
+        // Re-home space A's row under space B, ciphertext and all
+        {
+            let db_lock = db.lock().await;
+            db_lock
+                .connection()
+                .execute(
+                    "UPDATE credentials SET space_id = ?1 WHERE id = ?2",
+                    params![space_b.to_string(), id],
+                )
+                .unwrap();
+        }
+
+        let result = repo.get(&space_b, "github", &CredentialType::ApiKey).await;
+        assert!(matches!(result, Err(StorageError::Crypto(_))));
+        let result = repo.get_by_id(&space_b, &id).await;
+        assert!(matches!(result, Err(StorageError::Crypto(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reencrypt_all() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let old_encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), old_encryptor.clone());
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        repo.save(&Credential::api_key(space_id, "github", "token1"))
+            .await
+            .unwrap();
+        repo.save(&Credential::access_token(
+            space_id,
+            "atlassian",
+            "access",
+            None,
+        ))
+        .await
+        .unwrap();
+
+        let new_key = crate::crypto::generate_master_key().unwrap();
+        let new_encryptor = Arc::new(old_encryptor.rotate_key(&new_key).unwrap());
+        let count = repo.reencrypt_all(new_encryptor.clone()).await.unwrap();
+        assert_eq!(count, 2);
+        db.lock()
+            .await
+            .verify_key_fingerprint(&new_encryptor)
+            .unwrap();
+
+        // Repository now reads with the new key
+        let found = repo
+            .get(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.value, "token1");
+
+        // Stored values no longer open with the old key
+        let db_lock = db.lock().await;
+        let raw_value: String = db_lock
+            .connection()
+            .query_row(
+                "SELECT credential_value FROM credentials WHERE server_id = 'github'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(old_encryptor.decrypt(&raw_value).is_err());
+        assert!(new_encryptor.decrypt(&raw_value).is_err()); // bound to the row
+    }
+
+    #[tokio::test]
+    async fn test_reencrypt_all_rolls_back_on_corrupt_row() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let old_encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), old_encryptor.clone());
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        repo.save(&Credential::api_key(space_id, "github", "token1"))
+            .await
+            .unwrap();
+        repo.save(&Credential::api_key(space_id, "broken", "token2"))
+            .await
+            .unwrap();
+
+        let broken_id: String = {
+            let db_lock = db.lock().await;
+            let conn = db_lock.connection();
+            conn.execute(
+                "UPDATE credentials SET credential_value = 'deadbeef' WHERE server_id = 'broken'",
+                [],
+            )
+            .unwrap();
+            conn.query_row(
+                "SELECT id FROM credentials WHERE server_id = 'broken'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        let new_key = crate::crypto::generate_master_key().unwrap();
+        let new_encryptor = Arc::new(old_encryptor.rotate_key(&new_key).unwrap());
+        let err = repo.reencrypt_all(new_encryptor).await.unwrap_err();
+        assert!(err.to_string().contains(&broken_id));
+
+        // Nothing was rewritten and the old key is still in use
+        let found = repo
+            .get(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.value, "token1");
+    }
+
+    #[tokio::test]
+    async fn test_versioned_save() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        repo.save(&Credential::api_key(space_id, "github", "v1"))
+            .await
+            .unwrap();
+
+        let load = || repo.get(&space_id, "github", &CredentialType::ApiKey);
+        let mut first = load().await.unwrap().unwrap();
+        let mut second = first.clone();
+        assert_eq!(first.version, 1);
+
+        first.value = "v2".to_string();
+        repo.save(&first).await.unwrap();
+
+        second.value = "stale".to_string();
+        let err = repo.save(&second).await.unwrap_err();
+        assert!(matches!(
+            err,
+            StorageError::Stale(StaleWrite { current_version: 2 })
+        ));
+        assert_eq!(load().await.unwrap().unwrap().value, "v2");
+
+        // Fresh credentials (version 0) overwrite unconditionally
+        repo.save(&Credential::api_key(space_id, "github", "v3"))
+            .await
+            .unwrap();
+        let found = load().await.unwrap().unwrap();
+        assert_eq!((found.value.as_str(), found.version), ("v3", 3));
+    }
+
+    #[tokio::test]
+    async fn test_upsert() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        let load = || repo.get(&space_id, "github", &CredentialType::ApiKey);
+
+        let credential = Credential::api_key(space_id, "github", "v1");
+        assert_eq!(repo.upsert(&credential).await.unwrap(), Upserted::Inserted);
+
+        // A second window saves its stale copy over the first: no conflict
+        let mut stale = load().await.unwrap().unwrap();
+        repo.upsert(&Credential::api_key(space_id, "github", "v2"))
+            .await
+            .unwrap();
+        stale.value = "v3".to_string();
+        assert_eq!(repo.upsert(&stale).await.unwrap(), Upserted::Updated);
+        let found = load().await.unwrap().unwrap();
+        assert_eq!((found.value.as_str(), found.version), ("v3", 3));
+
+        // Upserting over a deleted credential recreates it
+        repo.delete(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap();
+        assert_eq!(repo.upsert(&credential).await.unwrap(), Upserted::Inserted);
+        assert_eq!(load().await.unwrap().unwrap().value, "v1");
+    }
+
+    fn import_item(space_id: &str, server_id: &str, credential_type: &str) -> NewCredential {
+        NewCredential {
+            space_id: space_id.to_string(),
+            server_id: server_id.to_string(),
+            credential_type: credential_type.to_string(),
+            value: format!("secret-{}", server_id),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_best_effort() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        let space = space_id.to_string();
+
+        let report = repo
+            .import(
+                vec![
+                    import_item(&space, "github", "api_key"),
+                    import_item(&space, "  ", "api_key"),
+                    import_item("not-a-uuid", "slack", "api_key"),
+                    import_item(&Uuid::new_v4().to_string(), "slack", "api_key"),
+                    import_item(&space, "jira", "password"),
+                    import_item(&space, "github", "api_key"),
+                    NewCredential {
+                        value: String::new(),
+                        ..import_item(&space, "linear", "api_key")
+                    },
+                    import_item(&space, "atlassian", "refresh_token"),
+                ],
+                ImportMode::BestEffort,
+            )
+            .await
+            .unwrap();
+
+        assert!(report.committed);
+        assert_eq!(report.imported(), 2);
+        let errors: Vec<_> = report
+            .results
+            .iter()
+            .map(|r| r.error.as_deref().unwrap_or("ok"))
+            .collect();
+        assert_eq!(errors[0], "ok");
+        assert_eq!(errors[1], "server_id is empty");
+        assert!(errors[2].contains("invalid space id"), "{}", errors[2]);
+        assert!(errors[3].contains("does not exist"), "{}", errors[3]);
+        assert!(
+            errors[4].contains("unknown credential type"),
+            "{}",
+            errors[4]
+        );
+        assert_eq!(errors[5], "duplicate of item 0");
+        assert_eq!(errors[6], "value is empty");
+        assert_eq!(errors[7], "ok");
+
+        // Stored encrypted and bound to the row, like a single save
+        let found = repo
+            .get(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.value, "secret-github");
+        let stored: String = db
+            .lock()
+            .await
+            .connection()
+            .query_row(
+                "SELECT credential_value FROM credentials WHERE server_id = 'github'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!stored.contains("secret-github"));
+        assert_eq!(repo.list_for_space(&space_id).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_all_or_nothing() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        let space = space_id.to_string();
+
+        let report = repo
+            .import(
+                vec![
+                    import_item(&space, "github", "api_key"),
+                    import_item(&space, "", "api_key"),
+                ],
+                ImportMode::AllOrNothing,
+            )
+            .await
+            .unwrap();
+        assert!(!report.committed);
+        assert_eq!(report.imported(), 0);
+        assert_eq!(report.failures().count(), 1);
+        assert!(repo.list_for_space(&space_id).await.unwrap().is_empty());
+
+        let report = repo
+            .import(
+                vec![import_item(&space, "github", "api_key")],
+                ImportMode::AllOrNothing,
+            )
+            .await
+            .unwrap();
+        assert!(report.committed);
+        assert_eq!(report.imported(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_audit_records_credential_access() {
+        use mcpmux_core::{AuditAction, AuditFilter, AuditRepository};
+
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let audit = Arc::new(SqliteAuditRepository::new(db.clone()));
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor)
+            .with_audit(audit.clone(), "test");
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+
+        repo.save(&Credential::api_key(space_id, "github", "ghp_secret"))
+            .await
+            .unwrap();
+        repo.save(&Credential::api_key(space_id, "github", "ghp_rotated"))
+            .await
+            .unwrap();
+        repo.get(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .unwrap();
+        // Misses are not reads
+        repo.get(&space_id, "missing", &CredentialType::ApiKey)
+            .await
+            .unwrap();
+        repo.delete(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap();
+        // Nothing left to delete, nothing recorded
+        repo.delete_all(&space_id, "github").await.unwrap();
+
+        let (entries, _) = audit
+            .list_audit(&AuditFilter::default(), &Page::first(10))
+            .await
+            .unwrap();
+        let actions: Vec<_> = entries.iter().rev().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::CredentialCreated,
+                AuditAction::CredentialUpdated,
+                AuditAction::CredentialRead,
+                AuditAction::CredentialDeleted,
+            ]
+        );
+        let target = format!("{}/github/api_key", space_id);
+        assert!(entries
+            .iter()
+            .all(|e| e.target_id == target && e.actor == "test"));
+        // Secrets never end up in the log
+        assert!(entries
+            .iter()
+            .all(|e| e.detail.as_deref().is_none_or(|d| !d.contains("ghp_"))));
+        assert_eq!(audit.verify_chain().await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_reads_do_not_wait_for_writer() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let options = crate::DbOptions {
+            busy_timeout: std::time::Duration::from_millis(50),
+            ..Default::default()
+        };
+        let pool =
+            crate::DbPool::open_with_options(&dir.path().join("creds.db"), options, 4).unwrap();
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(pool.clone(), encryptor);
+
+        let space_id = Uuid::new_v4();
+        pool.get().unwrap().execute(
+            "INSERT INTO spaces (id, name, created_at, updated_at) VALUES (?, 'Test', datetime('now'), datetime('now'))",
+            params![space_id.to_string()],
+        ).unwrap();
+        repo.save(&Credential::api_key(space_id, "github", "ghp_key"))
+            .await
+            .unwrap();
+
+        // Another connection holds the write lock for longer than the busy timeout
+        let writer = pool.get().unwrap();
+        let tx =
+            rusqlite::Transaction::new_unchecked(&writer, rusqlite::TransactionBehavior::Immediate)
+                .unwrap();
+
+        let found = repo
+            .get(&space_id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap();
+        assert_eq!(found.as_ref().map(|c| c.value.as_str()), Some("ghp_key"));
+        assert_eq!(repo.list_for_space(&space_id).await.unwrap().len(), 1);
+        tx.rollback().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sweep_expired() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let key = crate::crypto::generate_master_key().unwrap();
+        let encryptor = Arc::new(FieldEncryptor::new(&key).unwrap());
+        let repo = SqliteCredentialRepository::new(db.clone(), encryptor);
+
+        let space_id = Uuid::new_v4();
+        create_test_space(&db, &space_id).await;
+        let past = Some(Utc::now() - chrono::Duration::hours(1));
+        let future = Some(Utc::now() + chrono::Duration::hours(1));
+
+        // Expired access token, still refreshable: kept
+        repo.save(&Credential::access_token(
+            space_id,
+            "renewable",
+            "at1",
+            past,
+        ))
+        .await
+        .unwrap();
+        repo.save(&Credential::refresh_token(
+            space_id,
+            "renewable",
+            "rt1",
+            None,
+        ))
+        .await
+        .unwrap();
+        // Expired access and refresh tokens: both swept
+        repo.save(&Credential::access_token(space_id, "stale", "at2", past))
+            .await
+            .unwrap();
+        repo.save(&Credential::refresh_token(space_id, "stale", "rt2", past))
+            .await
+            .unwrap();
+        // Unexpired and non-expiring credentials: kept
+        repo.save(&Credential::access_token(space_id, "fresh", "at3", future))
+            .await
+            .unwrap();
+        repo.save(&Credential::api_key(space_id, "github", "ghp_key"))
+            .await
+            .unwrap();
+
+        let expired = repo
+            .get(&space_id, "renewable", &CredentialType::AccessToken)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(expired.is_expired());
+        assert!(repo
+            .get_unexpired(&space_id, "renewable", &CredentialType::AccessToken)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert_eq!(repo.sweep_expired().await.unwrap(), 2);
+        assert!(repo.get_all(&space_id, "stale").await.unwrap().is_empty());
+        assert_eq!(repo.list_for_space(&space_id).await.unwrap().len(), 4);
+
+        // A second pass finds nothing left to do
+        assert_eq!(repo.sweep_expired().await.unwrap(), 0);
+    }
 }
@@ -2,28 +2,26 @@
 //!
 //! Updated for the new schema with feature_set_type, space_id, and composition.
 
-use std::sync::Arc;
-
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use mcpmux_core::{
     FeatureSet, FeatureSetMember, FeatureSetRepository, FeatureSetType, MemberMode, MemberType,
+    RepoResult, StorageError,
 };
 use rusqlite::{params, OptionalExtension};
-use tokio::sync::Mutex;
 
-use crate::Database;
+use crate::DbHandle;
 
 /// SQLite-backed implementation of FeatureSetRepository.
 pub struct SqliteFeatureSetRepository {
-    db: Arc<Mutex<Database>>,
+    db: DbHandle,
 }
 
 impl SqliteFeatureSetRepository {
     /// Create a new SQLite feature set repository.
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self { db: db.into() }
     }
 
     /// Parse a datetime string to DateTime<Utc>.
@@ -72,7 +70,7 @@ impl SqliteFeatureSetRepository {
 
     /// Load members for a feature set
     async fn load_members(&self, feature_set_id: &str) -> Result<Vec<FeatureSetMember>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -111,8 +109,8 @@ impl SqliteFeatureSetRepository {
 
 #[async_trait]
 impl FeatureSetRepository for SqliteFeatureSetRepository {
-    async fn list(&self) -> Result<Vec<FeatureSet>> {
-        let db = self.db.lock().await;
+    async fn list(&self) -> RepoResult<Vec<FeatureSet>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -130,8 +128,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(feature_sets)
     }
 
-    async fn list_by_space(&self, space_id: &str) -> Result<Vec<FeatureSet>> {
-        let db = self.db.lock().await;
+    async fn list_by_space(&self, space_id: &str) -> RepoResult<Vec<FeatureSet>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -154,8 +152,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(feature_sets)
     }
 
-    async fn get(&self, id: &str) -> Result<Option<FeatureSet>> {
-        let db = self.db.lock().await;
+    async fn get(&self, id: &str) -> RepoResult<Option<FeatureSet>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let result = conn
@@ -172,7 +170,7 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(result)
     }
 
-    async fn get_with_members(&self, id: &str) -> Result<Option<FeatureSet>> {
+    async fn get_with_members(&self, id: &str) -> RepoResult<Option<FeatureSet>> {
         let feature_set = self.get(id).await?;
         if let Some(mut fs) = feature_set {
             fs.members = self.load_members(id).await?;
@@ -182,8 +180,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         }
     }
 
-    async fn create(&self, feature_set: &FeatureSet) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn create(&self, feature_set: &FeatureSet) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -226,8 +224,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(())
     }
 
-    async fn update(&self, feature_set: &FeatureSet) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn update(&self, feature_set: &FeatureSet) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let rows_affected = conn.execute(
@@ -244,7 +242,10 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         )?;
 
         if rows_affected == 0 {
-            anyhow::bail!("FeatureSet not found: {}", feature_set.id);
+            return Err(StorageError::NotFound(format!(
+                "FeatureSet {}",
+                feature_set.id
+            )));
         }
 
         // Update members: delete old, insert new
@@ -272,8 +273,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(())
     }
 
-    async fn delete(&self, id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn delete(&self, id: &str) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Don't allow deleting builtin feature sets
@@ -286,7 +287,7 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
             .unwrap_or(0);
 
         if is_builtin == 1 {
-            anyhow::bail!("Cannot delete builtin FeatureSet: {}", id);
+            return Err(anyhow::anyhow!("Cannot delete builtin FeatureSet: {}", id).into());
         }
 
         // Soft delete
@@ -298,8 +299,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(())
     }
 
-    async fn list_builtin(&self, space_id: &str) -> Result<Vec<FeatureSet>> {
-        let db = self.db.lock().await;
+    async fn list_builtin(&self, space_id: &str) -> RepoResult<Vec<FeatureSet>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -317,8 +318,12 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(feature_sets)
     }
 
-    async fn get_server_all(&self, space_id: &str, server_id: &str) -> Result<Option<FeatureSet>> {
-        let db = self.db.lock().await;
+    async fn get_server_all(
+        &self,
+        space_id: &str,
+        server_id: &str,
+    ) -> RepoResult<Option<FeatureSet>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let result = conn
@@ -340,7 +345,7 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         space_id: &str,
         server_id: &str,
         server_name: &str,
-    ) -> Result<FeatureSet> {
+    ) -> RepoResult<FeatureSet> {
         // Check if it already exists
         if let Some(existing) = self.get_server_all(space_id, server_id).await? {
             return Ok(existing);
@@ -352,8 +357,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(fs)
     }
 
-    async fn get_default_for_space(&self, space_id: &str) -> Result<Option<FeatureSet>> {
-        let db = self.db.lock().await;
+    async fn get_default_for_space(&self, space_id: &str) -> RepoResult<Option<FeatureSet>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let result = conn
@@ -370,8 +375,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(result)
     }
 
-    async fn get_all_for_space(&self, space_id: &str) -> Result<Option<FeatureSet>> {
-        let db = self.db.lock().await;
+    async fn get_all_for_space(&self, space_id: &str) -> RepoResult<Option<FeatureSet>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let result = conn
@@ -388,8 +393,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(result)
     }
 
-    async fn delete_server_all(&self, space_id: &str, server_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn delete_server_all(&self, space_id: &str, server_id: &str) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Hard delete server-all feature set for this server (used during uninstall)
@@ -403,7 +408,7 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         Ok(())
     }
 
-    async fn ensure_builtin_for_space(&self, space_id: &str) -> Result<()> {
+    async fn ensure_builtin_for_space(&self, space_id: &str) -> RepoResult<()> {
         // Check if "All" exists
         if self.get_all_for_space(space_id).await?.is_none() {
             let all = FeatureSet::new_all(space_id);
@@ -425,8 +430,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
         feature_set_id: &str,
         feature_id: &str,
         mode: MemberMode,
-    ) -> Result<()> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let member = FeatureSetMember {
@@ -454,8 +459,12 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
     }
 
     /// Remove an individual feature from a feature set
-    async fn remove_feature_member(&self, feature_set_id: &str, feature_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn remove_feature_member(
+        &self,
+        feature_set_id: &str,
+        feature_id: &str,
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -468,8 +477,8 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
     }
 
     /// Get all feature members (not feature_set members) of a feature set
-    async fn get_feature_members(&self, feature_set_id: &str) -> Result<Vec<FeatureSetMember>> {
-        let db = self.db.lock().await;
+    async fn get_feature_members(&self, feature_set_id: &str) -> RepoResult<Vec<FeatureSetMember>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -490,6 +499,9 @@ impl FeatureSetRepository for SqliteFeatureSetRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Database;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     /// Default space ID created by migration
     const DEFAULT_SPACE_ID: &str = "00000000-0000-0000-0000-000000000001";
@@ -14,11 +14,9 @@ use anyhow::Result;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::sync::Arc;
-use tokio::sync::Mutex;
 use tracing::{debug, info};
 
-use crate::Database;
+use crate::DbHandle;
 
 /// Client registration type (per MCP spec 2025-11-25)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -148,13 +146,13 @@ pub struct TokenRecord {
 
 /// OAuth Repository with database persistence
 pub struct InboundClientRepository {
-    db: Arc<Mutex<Database>>,
+    db: DbHandle,
 }
 
 impl InboundClientRepository {
     /// Create a new inbound client repository with a database
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self { db: db.into() }
     }
 
     // =========================================================================
@@ -226,7 +224,7 @@ impl InboundClientRepository {
 
     /// Register or update an inbound client (supports CIMD, DCR, pre-registered)
     pub async fn save_client(&self, client: &InboundClient) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         conn.execute(
             "INSERT INTO inbound_clients (
@@ -280,7 +278,7 @@ impl InboundClientRepository {
 
     /// Get a client by ID
     pub async fn get_client(&self, client_id: &str) -> Result<Option<InboundClient>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let mut stmt = conn.prepare(&format!(
             "SELECT {} FROM inbound_clients WHERE client_id = ?1",
@@ -300,7 +298,7 @@ impl InboundClientRepository {
     ///
     /// Allows a client to register with different redirect_uris
     pub async fn find_client_by_name(&self, name: &str) -> Result<Option<InboundClient>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(&format!(
@@ -328,7 +326,7 @@ impl InboundClientRepository {
 
     /// List all registered OAuth clients
     pub async fn list_clients(&self) -> Result<Vec<InboundClient>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let mut stmt = conn.prepare(&format!(
             "SELECT {} FROM inbound_clients ORDER BY created_at DESC",
@@ -344,7 +342,7 @@ impl InboundClientRepository {
 
     /// Update a client's last_seen timestamp
     pub async fn update_client_last_seen(&self, client_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         conn.execute(
@@ -360,7 +358,7 @@ impl InboundClientRepository {
     /// This is called when user explicitly approves the OAuth consent.
     /// Only approved clients get silent re-authentication.
     pub async fn approve_client(&self, client_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
         conn.execute(
@@ -373,7 +371,7 @@ impl InboundClientRepository {
 
     /// Check if a client has been approved by the user
     pub async fn is_client_approved(&self, client_id: &str) -> Result<bool> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let approved: i32 = conn
             .query_row(
@@ -404,7 +402,7 @@ impl InboundClientRepository {
         }
 
         // Update in database
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let uris_json = serde_json::to_string(&merged_uris)?;
         let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
@@ -432,7 +430,7 @@ impl InboundClientRepository {
 
         // Update timestamp
         {
-            let db = self.db.lock().await;
+            let db = self.db.get().await?;
             let conn = db.connection();
             conn.execute(
                 "UPDATE inbound_clients SET updated_at = ?1 WHERE client_id = ?2",
@@ -442,7 +440,7 @@ impl InboundClientRepository {
 
         // Update alias if provided
         if let Some(alias) = &client_alias {
-            let db = self.db.lock().await;
+            let db = self.db.get().await?;
             let conn = db.connection();
             conn.execute(
                 "UPDATE inbound_clients SET client_alias = ?1 WHERE client_id = ?2",
@@ -452,7 +450,7 @@ impl InboundClientRepository {
 
         // Update connection mode if provided
         if let Some(mode) = &connection_mode {
-            let db = self.db.lock().await;
+            let db = self.db.get().await?;
             let conn = db.connection();
             conn.execute(
                 "UPDATE inbound_clients SET connection_mode = ?1 WHERE client_id = ?2",
@@ -462,7 +460,7 @@ impl InboundClientRepository {
 
         // Update locked_space_id if provided
         if let Some(space_id) = &locked_space_id {
-            let db = self.db.lock().await;
+            let db = self.db.get().await?;
             let conn = db.connection();
             conn.execute(
                 "UPDATE inbound_clients SET locked_space_id = ?1 WHERE client_id = ?2",
@@ -478,7 +476,7 @@ impl InboundClientRepository {
 
     /// Delete a client and all associated tokens
     pub async fn delete_client(&self, client_id: &str) -> Result<bool> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Tokens and codes will be deleted via CASCADE
@@ -502,7 +500,7 @@ impl InboundClientRepository {
 
     /// Save an authorization code
     pub async fn save_authorization_code(&self, code: &AuthorizationCode) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         conn.execute(
             "INSERT INTO oauth_authorization_codes 
@@ -531,7 +529,7 @@ impl InboundClientRepository {
         &self,
         code: &str,
     ) -> Result<Option<AuthorizationCode>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Get the code
@@ -573,7 +571,7 @@ impl InboundClientRepository {
 
     /// Clean up expired authorization codes
     pub async fn cleanup_expired_codes(&self) -> Result<usize> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let deleted = conn.execute(
             "DELETE FROM oauth_authorization_codes WHERE expires_at < datetime('now')",
@@ -598,7 +596,7 @@ impl InboundClientRepository {
 
     /// Save a token record
     pub async fn save_token(&self, record: &TokenRecord) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         conn.execute(
             "INSERT INTO oauth_tokens (id, client_id, token_type, token_hash, scope, expires_at, revoked, created_at, parent_token_id)
@@ -625,7 +623,7 @@ impl InboundClientRepository {
 
     /// Find a token by its hash
     pub async fn find_token_by_hash(&self, token_hash: &str) -> Result<Option<TokenRecord>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let mut stmt = conn.prepare(
             "SELECT id, client_id, token_type, token_hash, scope, expires_at, revoked, created_at, parent_token_id
@@ -685,7 +683,7 @@ impl InboundClientRepository {
 
     /// Revoke a token (and all child tokens)
     pub async fn revoke_token(&self, token_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Revoke the token itself
@@ -706,7 +704,7 @@ impl InboundClientRepository {
 
     /// Revoke all tokens for a client
     pub async fn revoke_client_tokens(&self, client_id: &str) -> Result<usize> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let count = conn.execute(
             "UPDATE oauth_tokens SET revoked = 1 WHERE client_id = ?1",
@@ -718,7 +716,7 @@ impl InboundClientRepository {
 
     /// Clean up expired tokens
     pub async fn cleanup_expired_tokens(&self) -> Result<usize> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
         let deleted = conn.execute(
             "DELETE FROM oauth_tokens WHERE expires_at < datetime('now') AND expires_at IS NOT NULL",
@@ -741,7 +739,7 @@ impl InboundClientRepository {
         space_id: &str,
         feature_set_id: &str,
     ) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -760,7 +758,7 @@ impl InboundClientRepository {
         space_id: &str,
         feature_set_id: &str,
     ) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -778,7 +776,7 @@ impl InboundClientRepository {
         client_id: &str,
         space_id: &str,
     ) -> Result<Vec<String>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -798,7 +796,7 @@ impl InboundClientRepository {
         &self,
         client_id: &str,
     ) -> Result<std::collections::HashMap<String, Vec<String>>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
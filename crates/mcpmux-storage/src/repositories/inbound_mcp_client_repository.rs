@@ -4,30 +4,28 @@
 //! Works with the unified `inbound_clients` table.
 
 use std::collections::HashMap;
-use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mcpmux_core::{Client, ConnectionMode, InboundMcpClientRepository};
+use mcpmux_core::{Client, ConnectionMode, InboundMcpClientRepository, RepoResult, StorageError};
 use rusqlite::{params, OptionalExtension};
-use tokio::sync::Mutex;
 use uuid::Uuid;
 
-use crate::Database;
+use crate::DbHandle;
 
 /// SQLite-backed implementation of InboundMcpClientRepository.
 ///
 /// Works with the unified `inbound_clients` table which stores both
 /// OAuth registration data and MCP client preferences.
 pub struct SqliteInboundMcpClientRepository {
-    db: Arc<Mutex<Database>>,
+    db: DbHandle,
 }
 
 impl SqliteInboundMcpClientRepository {
     /// Create a new SQLite client repository.
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self { db: db.into() }
     }
 
     /// Parse a datetime string to DateTime<Utc>.
@@ -96,8 +94,8 @@ impl SqliteInboundMcpClientRepository {
 
 #[async_trait]
 impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
-    async fn list(&self) -> Result<Vec<Client>> {
-        let db = self.db.lock().await;
+    async fn list(&self) -> RepoResult<Vec<Client>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -133,8 +131,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         Ok(clients)
     }
 
-    async fn get(&self, id: &Uuid) -> Result<Option<Client>> {
-        let db = self.db.lock().await;
+    async fn get(&self, id: &Uuid) -> RepoResult<Option<Client>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -170,8 +168,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         Ok(client)
     }
 
-    async fn get_by_access_key(&self, key_hash: &str) -> Result<Option<Client>> {
-        let db = self.db.lock().await;
+    async fn get_by_access_key(&self, key_hash: &str) -> RepoResult<Option<Client>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -207,8 +205,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         Ok(client)
     }
 
-    async fn create(&self, client: &Client) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn create(&self, client: &Client) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let (mode_str, locked_space_id) = Self::connection_mode_to_strings(&client.connection_mode);
@@ -240,8 +238,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         Ok(())
     }
 
-    async fn update(&self, client: &Client) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn update(&self, client: &Client) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let (mode_str, locked_space_id) = Self::connection_mode_to_strings(&client.connection_mode);
@@ -262,14 +260,14 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         )?;
 
         if rows_affected == 0 {
-            anyhow::bail!("Client not found: {}", client.id);
+            return Err(StorageError::NotFound(format!("Client {}", client.id)));
         }
 
         Ok(())
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn delete(&self, id: &Uuid) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -285,8 +283,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         client_id: &Uuid,
         space_id: &str,
         feature_set_id: &str,
-    ) -> Result<()> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -303,8 +301,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         client_id: &Uuid,
         space_id: &str,
         feature_set_id: &str,
-    ) -> Result<()> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -316,8 +314,12 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         Ok(())
     }
 
-    async fn get_grants_for_space(&self, client_id: &Uuid, space_id: &str) -> Result<Vec<String>> {
-        let db = self.db.lock().await;
+    async fn get_grants_for_space(
+        &self,
+        client_id: &Uuid,
+        space_id: &str,
+    ) -> RepoResult<Vec<String>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -337,8 +339,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
     async fn get_all_grants(
         &self,
         client_id: &Uuid,
-    ) -> Result<std::collections::HashMap<String, Vec<String>>> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<std::collections::HashMap<String, Vec<String>>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -367,8 +369,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         client_id: &Uuid,
         space_id: &str,
         feature_set_ids: &[String],
-    ) -> Result<()> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Remove existing grants for this space
@@ -389,8 +391,8 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
         Ok(())
     }
 
-    async fn has_grants_for_space(&self, client_id: &Uuid, space_id: &str) -> Result<bool> {
-        let db = self.db.lock().await;
+    async fn has_grants_for_space(&self, client_id: &Uuid, space_id: &str) -> RepoResult<bool> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let count: i32 = conn.query_row(
@@ -407,6 +409,9 @@ impl InboundMcpClientRepository for SqliteInboundMcpClientRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Database;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     /// Default space ID created by migration
     const DEFAULT_SPACE_ID: &str = "00000000-0000-0000-0000-000000000001";
@@ -7,12 +7,11 @@ use std::sync::Arc;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mcpmux_core::{InstallationSource, InstalledServer, InstalledServerRepository};
-use rusqlite::{params, OptionalExtension};
-use tokio::sync::Mutex;
+use mcpmux_core::{InstallationSource, InstalledServer, InstalledServerRepository, RepoResult};
+use rusqlite::{params, Connection, OptionalExtension};
 use uuid::Uuid;
 
-use crate::{crypto::FieldEncryptor, Database};
+use crate::{crypto::FieldEncryptor, DbHandle};
 
 /// Raw row data extracted from SQLite before decryption.
 struct RawServerRow {
@@ -34,14 +33,17 @@ struct RawServerRow {
 
 /// SQLite-backed implementation of InstalledServerRepository.
 pub struct SqliteInstalledServerRepository {
-    db: Arc<Mutex<Database>>,
+    db: DbHandle,
     encryptor: Arc<FieldEncryptor>,
 }
 
 impl SqliteInstalledServerRepository {
     /// Create a new SQLite installed server repository.
-    pub fn new(db: Arc<Mutex<Database>>, encryptor: Arc<FieldEncryptor>) -> Self {
-        Self { db, encryptor }
+    pub fn new(db: impl Into<DbHandle>, encryptor: Arc<FieldEncryptor>) -> Self {
+        Self {
+            db: db.into(),
+            encryptor,
+        }
     }
 
     /// Encrypt input values for storage.
@@ -172,17 +174,52 @@ impl SqliteInstalledServerRepository {
             updated_at: Self::parse_datetime(&row.updated_at),
         }
     }
+
+    /// Insert a server on a locked connection.
+    pub(super) fn install_in(&self, conn: &Connection, server: &InstalledServer) -> Result<()> {
+        let encrypted_inputs = self.encrypt_input_values(&server.input_values)?;
+
+        conn.execute(
+            "INSERT INTO installed_servers
+             (id, space_id, server_id, server_name, cached_definition, input_values, enabled, env_overrides,
+              args_append, extra_headers, oauth_connected, created_at, updated_at, source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                server.id.to_string(),
+                server.space_id,
+                server.server_id,
+                server.server_name,
+                server.cached_definition,
+                encrypted_inputs,
+                server.enabled,
+                Self::serialize_json_map(&server.env_overrides),
+                Self::serialize_json_vec(&server.args_append),
+                Self::serialize_json_map(&server.extra_headers),
+                server.oauth_connected,
+                server.created_at.to_rfc3339(),
+                server.updated_at.to_rfc3339(),
+                Self::serialize_source(&server.source),
+            ],
+        )?;
+        Ok(())
+    }
 }
 
+/// Filter for listings across spaces: servers in soft-deleted spaces stay in
+/// the table until the space is purged, but must not be started or shown.
+const NOT_IN_DELETED_SPACE: &str =
+    "space_id NOT IN (SELECT id FROM spaces WHERE deleted_at IS NOT NULL)";
+
 #[async_trait]
 impl InstalledServerRepository for SqliteInstalledServerRepository {
-    async fn list(&self) -> Result<Vec<InstalledServer>> {
-        let db = self.db.lock().await;
+    async fn list(&self) -> RepoResult<Vec<InstalledServer>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(&format!(
-            "SELECT {} FROM installed_servers ORDER BY created_at DESC",
-            Self::SELECT_COLUMNS
+            "SELECT {} FROM installed_servers WHERE {} ORDER BY created_at DESC",
+            Self::SELECT_COLUMNS,
+            NOT_IN_DELETED_SPACE
         ))?;
 
         let rows: Vec<_> = stmt
@@ -192,8 +229,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(rows.into_iter().map(|r| self.build_server(r)).collect())
     }
 
-    async fn list_for_space(&self, space_id: &str) -> Result<Vec<InstalledServer>> {
-        let db = self.db.lock().await;
+    async fn list_for_space(&self, space_id: &str) -> RepoResult<Vec<InstalledServer>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(&format!(
@@ -211,8 +248,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
     async fn list_by_source_file(
         &self,
         file_path: &std::path::Path,
-    ) -> Result<Vec<InstalledServer>> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<Vec<InstalledServer>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Source format is "user_config:/path/to/file.json"
@@ -230,8 +267,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(rows.into_iter().map(|r| self.build_server(r)).collect())
     }
 
-    async fn get(&self, id: &Uuid) -> Result<Option<InstalledServer>> {
-        let db = self.db.lock().await;
+    async fn get(&self, id: &Uuid) -> RepoResult<Option<InstalledServer>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(&format!(
@@ -250,8 +287,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         &self,
         space_id: &str,
         server_id: &str,
-    ) -> Result<Option<InstalledServer>> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<Option<InstalledServer>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(&format!(
@@ -266,39 +303,13 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(row.map(|r| self.build_server(r)))
     }
 
-    async fn install(&self, server: &InstalledServer) -> Result<()> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
-
-        let encrypted_inputs = self.encrypt_input_values(&server.input_values)?;
-
-        conn.execute(
-            "INSERT INTO installed_servers
-             (id, space_id, server_id, server_name, cached_definition, input_values, enabled, env_overrides,
-              args_append, extra_headers, oauth_connected, created_at, updated_at, source)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
-            params![
-                server.id.to_string(),
-                server.space_id,
-                server.server_id,
-                server.server_name,
-                server.cached_definition,
-                encrypted_inputs,
-                server.enabled,
-                Self::serialize_json_map(&server.env_overrides),
-                Self::serialize_json_vec(&server.args_append),
-                Self::serialize_json_map(&server.extra_headers),
-                server.oauth_connected,
-                server.created_at.to_rfc3339(),
-                server.updated_at.to_rfc3339(),
-                Self::serialize_source(&server.source),
-            ],
-        )?;
-        Ok(())
+    async fn install(&self, server: &InstalledServer) -> RepoResult<()> {
+        let db = self.db.get().await?;
+        Ok(self.install_in(db.connection(), server)?)
     }
 
-    async fn update(&self, server: &InstalledServer) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn update(&self, server: &InstalledServer) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let encrypted_inputs = self.encrypt_input_values(&server.input_values)?;
@@ -326,8 +337,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(())
     }
 
-    async fn uninstall(&self, id: &Uuid) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn uninstall(&self, id: &Uuid) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -337,8 +348,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(())
     }
 
-    async fn list_enabled(&self, space_id: &str) -> Result<Vec<InstalledServer>> {
-        let db = self.db.lock().await;
+    async fn list_enabled(&self, space_id: &str) -> RepoResult<Vec<InstalledServer>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(&format!(
@@ -353,13 +364,14 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(rows.into_iter().map(|r| self.build_server(r)).collect())
     }
 
-    async fn list_enabled_all(&self) -> Result<Vec<InstalledServer>> {
-        let db = self.db.lock().await;
+    async fn list_enabled_all(&self) -> RepoResult<Vec<InstalledServer>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(&format!(
-            "SELECT {} FROM installed_servers WHERE enabled = 1 ORDER BY created_at DESC",
-            Self::SELECT_COLUMNS
+            "SELECT {} FROM installed_servers WHERE enabled = 1 AND {} ORDER BY created_at DESC",
+            Self::SELECT_COLUMNS,
+            NOT_IN_DELETED_SPACE
         ))?;
 
         let rows: Vec<_> = stmt
@@ -369,8 +381,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(rows.into_iter().map(|r| self.build_server(r)).collect())
     }
 
-    async fn set_enabled(&self, id: &Uuid, enabled: bool) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn set_enabled(&self, id: &Uuid, enabled: bool) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -380,8 +392,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         Ok(())
     }
 
-    async fn set_oauth_connected(&self, id: &Uuid, connected: bool) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn set_oauth_connected(&self, id: &Uuid, connected: bool) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -395,8 +407,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         &self,
         id: &Uuid,
         input_values: std::collections::HashMap<String, String>,
-    ) -> Result<()> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let encrypted_inputs = self.encrypt_input_values(&input_values)?;
@@ -424,8 +436,8 @@ impl InstalledServerRepository for SqliteInstalledServerRepository {
         id: &Uuid,
         server_name: Option<String>,
         cached_definition: Option<String>,
-    ) -> Result<()> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
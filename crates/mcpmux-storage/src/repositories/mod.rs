@@ -1,6 +1,8 @@
 //! Repository implementations using SQLite.
 
 mod app_settings_repository;
+mod audit_repository;
+mod cached;
 mod credential_repository;
 mod feature_set_repository;
 mod inbound_client_repository;
@@ -8,9 +10,11 @@ mod inbound_mcp_client_repository;
 mod installed_server_repository;
 mod outbound_oauth_client_repository;
 mod server_feature_repository;
+mod space_export;
 mod space_repository;
 
 pub use app_settings_repository::SqliteAppSettingsRepository;
+pub use audit_repository::SqliteAuditRepository;
 pub use credential_repository::SqliteCredentialRepository;
 pub use feature_set_repository::SqliteFeatureSetRepository;
 pub use inbound_client_repository::{
@@ -23,4 +27,5 @@ pub use outbound_oauth_client_repository::SqliteOutboundOAuthRepository;
 pub use server_feature_repository::{
     FeatureType, ServerFeature, ServerFeatureRepository, SqliteServerFeatureRepository,
 };
+pub use space_export::SPACE_EXPORT_VERSION;
 pub use space_repository::SqliteSpaceRepository;
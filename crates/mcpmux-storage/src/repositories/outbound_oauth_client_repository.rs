@@ -3,27 +3,25 @@
 //! Manages OUTBOUND OAuth registrations where McpMux acts as OAuth client
 //! connecting TO backend MCP servers (e.g., Cloudflare, Atlassian).
 
-use std::sync::Arc;
-
-use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mcpmux_core::{OutboundOAuthRegistration, OutboundOAuthRepository, StoredOAuthMetadata};
+use mcpmux_core::{
+    OutboundOAuthRegistration, OutboundOAuthRepository, RepoResult, StoredOAuthMetadata,
+};
 use rusqlite::{params, OptionalExtension};
-use tokio::sync::Mutex;
 use tracing::warn;
 use uuid::Uuid;
 
-use crate::Database;
+use crate::DbHandle;
 
 /// SQLite-backed outbound OAuth client repository.
 pub struct SqliteOutboundOAuthRepository {
-    db: Arc<Mutex<Database>>,
+    db: DbHandle,
 }
 
 impl SqliteOutboundOAuthRepository {
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self { db: db.into() }
     }
 
     fn parse_datetime(s: &str) -> DateTime<Utc> {
@@ -43,8 +41,8 @@ impl OutboundOAuthRepository for SqliteOutboundOAuthRepository {
         &self,
         space_id: &Uuid,
         server_id: &str,
-    ) -> Result<Option<OutboundOAuthRegistration>> {
-        let db = self.db.lock().await;
+    ) -> RepoResult<Option<OutboundOAuthRegistration>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -107,8 +105,8 @@ impl OutboundOAuthRepository for SqliteOutboundOAuthRepository {
         }
     }
 
-    async fn save(&self, reg: &OutboundOAuthRegistration) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn save(&self, reg: &OutboundOAuthRegistration) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         // Serialize metadata to JSON if present
@@ -143,8 +141,8 @@ impl OutboundOAuthRepository for SqliteOutboundOAuthRepository {
         Ok(())
     }
 
-    async fn delete(&self, space_id: &Uuid, server_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+    async fn delete(&self, space_id: &Uuid, server_id: &str) -> RepoResult<()> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -155,8 +153,8 @@ impl OutboundOAuthRepository for SqliteOutboundOAuthRepository {
         Ok(())
     }
 
-    async fn list_for_space(&self, space_id: &Uuid) -> Result<Vec<OutboundOAuthRegistration>> {
-        let db = self.db.lock().await;
+    async fn list_for_space(&self, space_id: &Uuid) -> RepoResult<Vec<OutboundOAuthRegistration>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -218,6 +216,9 @@ impl OutboundOAuthRepository for SqliteOutboundOAuthRepository {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Database;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     async fn create_test_space(db: &Arc<Mutex<Database>>, space_id: &Uuid) {
         let db_lock = db.lock().await;
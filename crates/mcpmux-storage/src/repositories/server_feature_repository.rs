@@ -3,16 +3,13 @@
 //! Manages server_features table - stores discovered MCP features (tools, prompts, resources)
 //! from connected servers, scoped to each space.
 
-use std::sync::Arc;
-
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use rusqlite::{params, OptionalExtension};
-use tokio::sync::Mutex;
 use uuid::Uuid;
 
-use crate::Database;
+use crate::DbHandle;
 
 /// Feature type enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -193,12 +190,12 @@ pub trait ServerFeatureRepository: Send + Sync {
 
 /// SQLite-backed implementation of ServerFeatureRepository
 pub struct SqliteServerFeatureRepository {
-    db: Arc<Mutex<Database>>,
+    db: DbHandle,
 }
 
 impl SqliteServerFeatureRepository {
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self { db: db.into() }
     }
 
     fn parse_datetime(s: &str) -> DateTime<Utc> {
@@ -235,7 +232,7 @@ impl SqliteServerFeatureRepository {
 #[async_trait]
 impl ServerFeatureRepository for SqliteServerFeatureRepository {
     async fn list_by_space(&self, space_id: &str) -> Result<Vec<ServerFeature>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -255,7 +252,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
     }
 
     async fn list_by_server(&self, space_id: &str, server_id: &str) -> Result<Vec<ServerFeature>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -280,7 +277,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
         server_id: &str,
         feature_type: FeatureType,
     ) -> Result<Vec<ServerFeature>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let mut stmt = conn.prepare(
@@ -303,7 +300,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
     }
 
     async fn get(&self, id: &str) -> Result<Option<ServerFeature>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let result = conn
@@ -328,7 +325,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
         feature_type: FeatureType,
         name: &str,
     ) -> Result<Option<ServerFeature>> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let result = conn
@@ -347,7 +344,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
     }
 
     async fn upsert(&self, feature: &ServerFeature) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         let raw_json_str = feature
@@ -399,7 +396,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
         feature_type: FeatureType,
         available_names: &[String],
     ) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         if available_names.is_empty() {
@@ -445,7 +442,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
     }
 
     async fn delete(&self, id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute("DELETE FROM server_features WHERE id = ?", params![id])?;
@@ -453,7 +450,7 @@ impl ServerFeatureRepository for SqliteServerFeatureRepository {
     }
 
     async fn delete_by_server(&self, space_id: &str, server_id: &str) -> Result<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -542,7 +539,7 @@ impl mcpmux_core::ServerFeatureRepository for SqliteServerFeatureRepository {
 
     async fn upsert(&self, feature: &mcpmux_core::ServerFeature) -> mcpmux_core::RepoResult<()> {
         let storage_feature: ServerFeature = feature.clone().into();
-        ServerFeatureRepository::upsert(self, &storage_feature).await
+        Ok(ServerFeatureRepository::upsert(self, &storage_feature).await?)
     }
 
     async fn upsert_many(
@@ -551,11 +548,11 @@ impl mcpmux_core::ServerFeatureRepository for SqliteServerFeatureRepository {
     ) -> mcpmux_core::RepoResult<()> {
         let storage_features: Vec<ServerFeature> =
             features.iter().map(|f| f.clone().into()).collect();
-        ServerFeatureRepository::upsert_many(self, &storage_features).await
+        Ok(ServerFeatureRepository::upsert_many(self, &storage_features).await?)
     }
 
     async fn delete(&self, id: &uuid::Uuid) -> mcpmux_core::RepoResult<()> {
-        ServerFeatureRepository::delete(self, &id.to_string()).await
+        Ok(ServerFeatureRepository::delete(self, &id.to_string()).await?)
     }
 
     async fn mark_unavailable(
@@ -563,7 +560,7 @@ impl mcpmux_core::ServerFeatureRepository for SqliteServerFeatureRepository {
         space_id: &str,
         server_id: &str,
     ) -> mcpmux_core::RepoResult<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -579,7 +576,7 @@ impl mcpmux_core::ServerFeatureRepository for SqliteServerFeatureRepository {
         space_id: &str,
         server_id: &str,
     ) -> mcpmux_core::RepoResult<()> {
-        let db = self.db.lock().await;
+        let db = self.db.get().await?;
         let conn = db.connection();
 
         conn.execute(
@@ -0,0 +1,474 @@
+//! Passphrase-protected space export for moving a setup between machines.
+//!
+//! The master key never leaves the machine it was created on, so an export
+//! can't carry secrets encrypted under it. Instead the space, its installed
+//! servers and its credentials are decrypted, serialized, and sealed as a
+//! whole under a key derived from a passphrase with Argon2id. On import they
+//! are re-encrypted under the destination's master key.
+//!
+//! ## Format
+//!
+//! A JSON envelope:
+//!
+//! ```text
+//! {
+//!   "format": "mcpmux-space-export",
+//!   "version": 1,
+//!   "kdf": { "algorithm": "argon2id", "memory_kib": .., "iterations": ..,
+//!            "parallelism": .., "salt": "<hex>" },
+//!   "payload": "<FieldEncryptor ciphertext>"
+//! }
+//! ```
+//!
+//! `format` and `version` are read before anything else, so a newer export
+//! fails with a clear message rather than a parse error. The envelope
+//! header is bound to the payload as associated data.
+//!
+//! Feature sets, client grants and OAuth client registrations are not
+//! exported; OAuth servers may need to be reconnected after an import.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use mcpmux_core::{
+    Credential, CredentialRepository, CredentialType, InstalledServer, InstalledServerRepository,
+    Space, SpaceRepository,
+};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use super::{SqliteCredentialRepository, SqliteInstalledServerRepository, SqliteSpaceRepository};
+use crate::crypto::{derive_key, FieldEncryptor, KdfParams};
+
+/// Value of the envelope's `format` field.
+const EXPORT_FORMAT: &str = "mcpmux-space-export";
+
+/// Newest export format version this build writes and reads.
+pub const SPACE_EXPORT_VERSION: u32 = 1;
+
+/// Size of the random KDF salt (16 bytes = 128 bits).
+const SALT_SIZE: usize = 16;
+
+/// Fields read first, before committing to a format version.
+#[derive(Deserialize)]
+struct EnvelopeHeader {
+    format: String,
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    format: String,
+    version: u32,
+    kdf: ExportKdf,
+    payload: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportKdf {
+    algorithm: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String,
+}
+
+impl ExportKdf {
+    fn params(&self) -> KdfParams {
+        KdfParams {
+            memory_kib: self.memory_kib,
+            iterations: self.iterations,
+            parallelism: self.parallelism,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    space: Space,
+    /// Values of the space's sensitive env vars, which `Space` serializes
+    /// masked.
+    #[serde(default)]
+    sensitive_env: HashMap<String, Zeroizing<String>>,
+    servers: Vec<InstalledServer>,
+    credentials: Vec<ExportedCredential>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedCredential {
+    server_id: String,
+    credential_type: CredentialType,
+    value: Zeroizing<String>,
+    expires_at: Option<DateTime<Utc>>,
+    token_type: Option<String>,
+    scope: Option<String>,
+}
+
+/// Associated data binding the payload to the envelope around it.
+fn envelope_aad(version: u32, kdf: &ExportKdf) -> Result<Vec<u8>> {
+    let mut aad = format!("{}/v{}/", EXPORT_FORMAT, version).into_bytes();
+    aad.extend(serde_json::to_vec(kdf)?);
+    Ok(aad)
+}
+
+fn passphrase_encryptor(passphrase: &str, kdf: &ExportKdf) -> Result<FieldEncryptor> {
+    if passphrase.is_empty() {
+        anyhow::bail!("Export passphrase must not be empty");
+    }
+    let salt = hex::decode(&kdf.salt).context("Invalid salt in space export")?;
+    let key = derive_key(passphrase, &salt, kdf.params())?;
+    FieldEncryptor::new(&key)
+}
+
+impl SqliteSpaceRepository {
+    /// Export a space with its installed servers and credentials, sealed
+    /// under `passphrase`.
+    ///
+    /// `master` is the encryptor the secrets are currently stored under.
+    pub async fn export_space(
+        &self,
+        id: &Uuid,
+        passphrase: &str,
+        master: Arc<FieldEncryptor>,
+    ) -> Result<Vec<u8>> {
+        self.export_space_with(id, passphrase, master, KdfParams::default())
+            .await
+    }
+
+    async fn export_space_with(
+        &self,
+        id: &Uuid,
+        passphrase: &str,
+        master: Arc<FieldEncryptor>,
+        params: KdfParams,
+    ) -> Result<Vec<u8>> {
+        // Read with `master` too, so sensitive space env is exported decrypted
+        let space = SqliteSpaceRepository::new(self.db.clone())
+            .with_encryptor(master.clone())
+            .get(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Space not found: {}", id))?;
+        let servers = SqliteInstalledServerRepository::new(self.db.clone(), master.clone())
+            .list_for_space(&id.to_string())
+            .await?;
+        let credentials = SqliteCredentialRepository::new(self.db.clone(), master)
+            .list_for_space(id)
+            .await?
+            .iter()
+            .map(|c| ExportedCredential {
+                server_id: c.server_id.clone(),
+                credential_type: c.credential_type.clone(),
+                value: Zeroizing::new(c.value.clone()),
+                expires_at: c.expires_at,
+                token_type: c.token_type.clone(),
+                scope: c.scope.clone(),
+            })
+            .collect::<Vec<_>>();
+        let (server_count, credential_count) = (servers.len(), credentials.len());
+
+        let mut salt = [0u8; SALT_SIZE];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| anyhow::anyhow!("Failed to generate random salt"))?;
+        let kdf = ExportKdf {
+            algorithm: "argon2id".to_string(),
+            memory_kib: params.memory_kib,
+            iterations: params.iterations,
+            parallelism: params.parallelism,
+            salt: hex::encode(salt),
+        };
+
+        let sensitive_env = space
+            .space_env
+            .iter()
+            .filter(|(name, _)| space.sensitive_env.contains(*name))
+            .map(|(name, value)| (name.clone(), Zeroizing::new(value.clone())))
+            .collect();
+        let plaintext = Zeroizing::new(serde_json::to_vec(&Payload {
+            space,
+            sensitive_env,
+            servers,
+            credentials,
+        })?);
+        let payload = passphrase_encryptor(passphrase, &kdf)?
+            .encrypt_with_aad(&plaintext, &envelope_aad(SPACE_EXPORT_VERSION, &kdf)?)?;
+
+        info!(
+            "[SpaceRepository] Exported space {} ({} servers, {} credentials)",
+            id, server_count, credential_count
+        );
+        Ok(serde_json::to_vec_pretty(&Envelope {
+            format: EXPORT_FORMAT.to_string(),
+            version: SPACE_EXPORT_VERSION,
+            kdf,
+            payload,
+        })?)
+    }
+
+    /// Import a space written by [`export_space`](Self::export_space),
+    /// re-encrypting its secrets under `master`.
+    ///
+    /// The space and its servers get new ids, so importing never collides
+    /// with existing data (every install has a default space with the same
+    /// id), and the imported space is never the default. Everything is
+    /// written in one transaction. Returns the new space.
+    pub async fn import_space(
+        &self,
+        bytes: &[u8],
+        passphrase: &str,
+        master: Arc<FieldEncryptor>,
+    ) -> Result<Space> {
+        let header: EnvelopeHeader =
+            serde_json::from_slice(bytes).context("Not a McpMux space export")?;
+        if header.format != EXPORT_FORMAT {
+            anyhow::bail!("Not a McpMux space export");
+        }
+        if header.version > SPACE_EXPORT_VERSION {
+            anyhow::bail!(
+                "Space export format version {} is newer than this version of McpMux supports ({})",
+                header.version,
+                SPACE_EXPORT_VERSION
+            );
+        }
+
+        let envelope: Envelope =
+            serde_json::from_slice(bytes).context("Malformed McpMux space export")?;
+        if envelope.kdf.algorithm != "argon2id" {
+            anyhow::bail!(
+                "Unsupported key derivation in space export: {}",
+                envelope.kdf.algorithm
+            );
+        }
+        let plaintext = passphrase_encryptor(passphrase, &envelope.kdf)?
+            .decrypt_with_aad(
+                &envelope.payload,
+                &envelope_aad(envelope.version, &envelope.kdf)?,
+            )
+            .map_err(|_| anyhow::anyhow!("Wrong passphrase or corrupted space export"))?;
+        let payload: Payload =
+            serde_json::from_slice(&plaintext).context("Malformed McpMux space export")?;
+
+        let now = Utc::now();
+        let mut space = Space {
+            id: Uuid::new_v4(),
+            is_default: false,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            version: 1,
+            ..payload.space
+        };
+        for (name, value) in &payload.sensitive_env {
+            if space.sensitive_env.contains(name) {
+                space.space_env.insert(name.clone(), value.to_string());
+            }
+        }
+        let space_id = space.id.to_string();
+
+        let servers = SqliteInstalledServerRepository::new(self.db.clone(), master.clone());
+        let credentials = SqliteCredentialRepository::new(self.db.clone(), master.clone());
+
+        let db = self.db.get().await?;
+        db.transaction(|conn| {
+            Self::create_in(conn, &space, Some(&master))?;
+            for server in &payload.servers {
+                servers.install_in(
+                    conn,
+                    &InstalledServer {
+                        id: Uuid::new_v4(),
+                        space_id: space_id.clone(),
+                        ..server.clone()
+                    },
+                )?;
+            }
+            for c in &payload.credentials {
+                credentials.save_in(
+                    conn,
+                    &Credential {
+                        space_id: space.id,
+                        server_id: c.server_id.clone(),
+                        credential_type: c.credential_type.clone(),
+                        value: c.value.to_string(),
+                        expires_at: c.expires_at,
+                        token_type: c.token_type.clone(),
+                        scope: c.scope.clone(),
+                        created_at: now,
+                        updated_at: now,
+                        last_used: None,
+                        version: 0,
+                    },
+                )?;
+            }
+            Ok(())
+        })?;
+
+        info!(
+            "[SpaceRepository] Imported space '{}' as {} ({} servers, {} credentials)",
+            space.name,
+            space.id,
+            payload.servers.len(),
+            payload.credentials.len()
+        );
+        Ok(space)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use tokio::sync::Mutex;
+
+    /// Cheap parameters so tests don't spend seconds in the KDF.
+    const TEST_PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    struct Machine {
+        db: Arc<Mutex<Database>>,
+        master: Arc<FieldEncryptor>,
+        spaces: SqliteSpaceRepository,
+    }
+
+    impl Machine {
+        fn new() -> Self {
+            let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+            let key = crate::crypto::generate_master_key().unwrap();
+            let master = Arc::new(FieldEncryptor::new(&key).unwrap());
+            Self {
+                spaces: SqliteSpaceRepository::new(db.clone()).with_encryptor(master.clone()),
+                master,
+                db,
+            }
+        }
+
+        fn credentials(&self) -> SqliteCredentialRepository {
+            SqliteCredentialRepository::new(self.db.clone(), self.master.clone())
+        }
+
+        fn servers(&self) -> SqliteInstalledServerRepository {
+            SqliteInstalledServerRepository::new(self.db.clone(), self.master.clone())
+        }
+    }
+
+    async fn export_fixture(source: &Machine) -> Vec<u8> {
+        let space = Space::new("Work")
+            .with_icon("💼")
+            .with_env("REGION", "eu-west-1")
+            .with_sensitive_env("API_TOKEN", "tok_secret");
+        source.spaces.create(&space).await.unwrap();
+
+        let mut server = InstalledServer::new(space.id.to_string(), "github");
+        server.input_values = HashMap::from([("TOKEN".to_string(), "ghp_input".to_string())]);
+        source.servers().install(&server).await.unwrap();
+        source
+            .credentials()
+            .save(&Credential::api_key(space.id, "github", "ghp_secret"))
+            .await
+            .unwrap();
+
+        source
+            .spaces
+            .export_space_with(
+                &space.id,
+                "correct horse",
+                source.master.clone(),
+                TEST_PARAMS,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_between_machines() {
+        let source = Machine::new();
+        let bytes = export_fixture(&source).await;
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.contains("\"version\": 1"), "{}", text);
+        assert!(!text.contains("ghp_secret") && !text.contains("ghp_input"));
+        assert!(!text.contains("tok_secret"));
+
+        let dest = Machine::new();
+        let space = dest
+            .spaces
+            .import_space(&bytes, "correct horse", dest.master.clone())
+            .await
+            .unwrap();
+        assert_eq!(space.name, "Work");
+        assert!(!space.is_default);
+        assert_eq!(dest.spaces.list().await.unwrap().len(), 2);
+
+        let servers = dest
+            .servers()
+            .list_for_space(&space.id.to_string())
+            .await
+            .unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].input_values["TOKEN"], "ghp_input");
+
+        // Re-encrypted under the destination's key
+        let found = dest
+            .credentials()
+            .get(&space.id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.value, "ghp_secret");
+
+        // Sensitive space env survives although `Space` serializes it masked
+        let imported = dest.spaces.get(&space.id).await.unwrap().unwrap();
+        assert_eq!(imported.space_env["API_TOKEN"], "tok_secret");
+        assert_eq!(imported.space_env["REGION"], "eu-west-1");
+        assert!(imported.sensitive_env.contains("API_TOKEN"));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_passphrase_imports_nothing() {
+        let source = Machine::new();
+        let bytes = export_fixture(&source).await;
+
+        let dest = Machine::new();
+        let err = dest
+            .spaces
+            .import_space(&bytes, "wrong", dest.master.clone())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Wrong passphrase"), "{}", err);
+        assert_eq!(dest.spaces.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_newer_and_foreign_formats() {
+        let dest = Machine::new();
+
+        let newer = br#"{"format": "mcpmux-space-export", "version": 99, "future": true}"#;
+        let err = dest
+            .spaces
+            .import_space(newer, "pw", dest.master.clone())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("version 99 is newer"), "{}", err);
+
+        let err = dest
+            .spaces
+            .import_space(
+                b"{\"format\": \"other\", \"version\": 1}",
+                "pw",
+                dest.master.clone(),
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Not a McpMux space export"),
+            "{}",
+            err
+        );
+    }
+}
@@ -1,131 +1,198 @@
 //! SQLite implementation of SpaceRepository.
 
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
-use mcpmux_core::{Space, SpaceRepository};
-use rusqlite::{params, OptionalExtension};
-use tokio::sync::Mutex;
+use chrono::{DateTime, SecondsFormat, Utc};
+use mcpmux_core::{
+    normalize_tag, Page, PageCursor, RepoResult, Space, SpaceRepository, StaleWrite, StorageError,
+    TagMatch,
+};
+use rusqlite::{params, Connection, OptionalExtension};
 use uuid::Uuid;
 
-use crate::Database;
+use super::cached::CachedStatements;
+use crate::crypto::{into_utf8, FieldEncryptor};
+use crate::DbHandle;
 
 /// SQLite-backed implementation of SpaceRepository.
+///
+/// `delete` is a soft delete: it stamps `deleted_at` on the space and on its
+/// live credentials with the same timestamp, which is how `restore` knows
+/// which credentials to bring back. Everything else in the space is left in
+/// place until `purge_older_than` removes the row and the foreign keys cascade.
+///
+/// Sensitive space env values are encrypted with the encryptor given to
+/// [`with_encryptor`](Self::with_encryptor), bound to their space and name.
+/// Without one, sensitive values can't be set and stored ones are left out
+/// of the spaces read.
 pub struct SqliteSpaceRepository {
-    db: Arc<Mutex<Database>>,
+    pub(super) db: DbHandle,
+    encryptor: Option<Arc<FieldEncryptor>>,
 }
 
 impl SqliteSpaceRepository {
     /// Create a new SQLite space repository.
-    pub fn new(db: Arc<Mutex<Database>>) -> Self {
-        Self { db }
+    pub fn new(db: impl Into<DbHandle>) -> Self {
+        Self {
+            db: db.into(),
+            encryptor: None,
+        }
     }
 
-    /// Parse a datetime string to DateTime<Utc>.
-    /// Handles both RFC3339 format and SQLite's `datetime('now')` format.
-    fn parse_datetime(s: &str) -> DateTime<Utc> {
-        // Try RFC3339 first (e.g., "2024-01-01T00:00:00Z")
-        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
-            return dt.with_timezone(&Utc);
-        }
+    /// Encrypt sensitive space env values with `encryptor`.
+    pub fn with_encryptor(mut self, encryptor: Arc<FieldEncryptor>) -> Self {
+        self.encryptor = Some(encryptor);
+        self
+    }
 
-        // Try SQLite's datetime format (e.g., "2024-01-01 00:00:00")
-        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
-            return dt.and_utc();
-        }
+    /// Standard column list for SELECT queries (from `spaces`, unaliased).
+    const SELECT_COLUMNS: &'static str =
+        "id, name, icon, description, is_default, sort_order, created_at, updated_at, deleted_at, version,
+         (SELECT json_group_array(tag) FROM space_tags WHERE space_id = spaces.id),
+         (SELECT json_group_array(json_array(name, value, sensitive)) FROM space_env WHERE space_id = spaces.id)";
 
-        // Fallback to current time
-        Utc::now()
+    /// Associated data binding a sensitive env value to its space and name.
+    fn env_aad(space_id: &str, name: &str) -> Vec<u8> {
+        format!("space_env:{}:{}", space_id, name).into_bytes()
     }
-}
 
-#[async_trait]
-impl SpaceRepository for SqliteSpaceRepository {
-    async fn list(&self) -> Result<Vec<Space>> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
+    /// Encrypt a space env value for storage if it is sensitive.
+    fn encode_env_value(
+        encryptor: Option<&FieldEncryptor>,
+        space_id: &str,
+        name: &str,
+        value: &str,
+        sensitive: bool,
+    ) -> Result<String> {
+        if !sensitive {
+            return Ok(value.to_string());
+        }
+        let encryptor = encryptor.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Sensitive space env '{}' needs an encryptor to be stored",
+                name
+            )
+        })?;
+        encryptor.encrypt_with_aad(value.as_bytes(), &Self::env_aad(space_id, name))
+    }
 
-        tracing::debug!("[SpaceRepository::list] Querying spaces...");
+    /// Build a Space from a row selected with [`Self::SELECT_COLUMNS`].
+    fn row_to_space(&self, row: &rusqlite::Row) -> rusqlite::Result<Space> {
+        let id_str: String = row.get(0)?;
+        let (space_env, sensitive_env) = self.decode_env(&id_str, &row.get::<_, String>(11)?)?;
+        Ok(Space {
+            id: id_str.parse().unwrap_or_else(|e| {
+                tracing::warn!("[SpaceRepository] Failed to parse UUID '{}': {}", id_str, e);
+                Uuid::new_v4()
+            }),
+            name: row.get(1)?,
+            icon: row.get(2)?,
+            description: row.get(3)?,
+            is_default: row.get::<_, i32>(4)? == 1,
+            sort_order: row.get(5)?,
+            created_at: Self::parse_datetime(&row.get::<_, String>(6)?),
+            updated_at: Self::parse_datetime(&row.get::<_, String>(7)?),
+            deleted_at: row
+                .get::<_, Option<String>>(8)?
+                .map(|s| Self::parse_datetime(&s)),
+            version: row.get(9)?,
+            tags: {
+                let json: String = row.get(10)?;
+                let mut tags: Vec<String> = serde_json::from_str(&json).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        10,
+                        rusqlite::types::Type::Text,
+                        e.into(),
+                    )
+                })?;
+                tags.sort_by_key(|t| t.to_ascii_lowercase());
+                tags
+            },
+            space_env,
+            sensitive_env,
+        })
+    }
 
-        let mut stmt = conn.prepare(
-            "SELECT id, name, icon, description, is_default, sort_order, created_at, updated_at 
-             FROM spaces 
-             ORDER BY sort_order ASC, name ASC",
-        )?;
+    /// Decode the `[name, value, sensitive]` triples of a space's env,
+    /// decrypting sensitive values.
+    fn decode_env(
+        &self,
+        space_id: &str,
+        json: &str,
+    ) -> rusqlite::Result<(HashMap<String, String>, HashSet<String>)> {
+        let conversion_error = |e: anyhow::Error| {
+            rusqlite::Error::FromSqlConversionFailure(11, rusqlite::types::Type::Text, e.into())
+        };
+        let entries: Vec<(String, String, i64)> =
+            serde_json::from_str(json).map_err(|e| conversion_error(e.into()))?;
 
-        let spaces = stmt
-            .query_map([], |row| {
-                let id_str: String = row.get(0)?;
-                let name: String = row.get(1)?;
-                tracing::debug!("[SpaceRepository::list] Found space: {} ({})", name, id_str);
-
-                Ok(Space {
-                    id: id_str.parse().unwrap_or_else(|e| {
-                        tracing::warn!(
-                            "[SpaceRepository::list] Failed to parse UUID '{}': {}",
-                            id_str,
-                            e
-                        );
-                        Uuid::new_v4()
-                    }),
+        let mut env = HashMap::with_capacity(entries.len());
+        let mut sensitive = HashSet::new();
+        for (name, value, is_sensitive) in entries {
+            if is_sensitive == 0 {
+                env.insert(name, value);
+                continue;
+            }
+            let Some(encryptor) = &self.encryptor else {
+                tracing::warn!(
+                    "[SpaceRepository] No encryptor configured; leaving out sensitive env '{}' of space {}",
                     name,
-                    icon: row.get(2)?,
-                    description: row.get(3)?,
-                    is_default: row.get::<_, i32>(4)? == 1,
-                    sort_order: row.get(5)?,
-                    created_at: Self::parse_datetime(&row.get::<_, String>(6)?),
-                    updated_at: Self::parse_datetime(&row.get::<_, String>(7)?),
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        tracing::info!("[SpaceRepository::list] Returning {} spaces", spaces.len());
-
-        Ok(spaces)
+                    space_id
+                );
+                continue;
+            };
+            let plaintext = encryptor
+                .decrypt_with_aad(&value, &Self::env_aad(space_id, &name))
+                .and_then(into_utf8)
+                .map_err(conversion_error)?;
+            env.insert(name.clone(), plaintext.to_string());
+            sensitive.insert(name);
+        }
+        Ok((env, sensitive))
     }
 
-    async fn get(&self, id: &Uuid) -> Result<Option<Space>> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
-
-        let mut stmt = conn.prepare(
-            "SELECT id, name, icon, description, is_default, sort_order, created_at, updated_at 
-             FROM spaces 
-             WHERE id = ?",
+    /// Fail with [`StorageError::NotFound`] unless the space exists and isn't
+    /// deleted.
+    fn ensure_active(conn: &Connection, id: &Uuid) -> Result<()> {
+        let exists: bool = conn.query_row_cached(
+            "SELECT EXISTS(SELECT 1 FROM spaces WHERE id = ?1 AND deleted_at IS NULL)",
+            params![id.to_string()],
+            |row| row.get(0),
         )?;
-
-        let space = stmt
-            .query_row(params![id.to_string()], |row| {
-                Ok(Space {
-                    id: row
-                        .get::<_, String>(0)?
-                        .parse()
-                        .unwrap_or_else(|_| Uuid::new_v4()),
-                    name: row.get(1)?,
-                    icon: row.get(2)?,
-                    description: row.get(3)?,
-                    is_default: row.get::<_, i32>(4)? == 1,
-                    sort_order: row.get(5)?,
-                    created_at: Self::parse_datetime(&row.get::<_, String>(6)?),
-                    updated_at: Self::parse_datetime(&row.get::<_, String>(7)?),
-                })
-            })
-            .optional()?;
-
-        Ok(space)
+        if !exists {
+            return Err(StorageError::NotFound(format!("Space {}", id)).into());
+        }
+        Ok(())
     }
 
-    async fn create(&self, space: &Space) -> Result<()> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
+    /// Insert a space and its builtin feature sets on a locked connection,
+    /// encrypting sensitive env values with `encryptor`.
+    pub(super) fn create_in(
+        conn: &Connection,
+        space: &Space,
+        encryptor: Option<&FieldEncryptor>,
+    ) -> Result<()> {
         let space_id = space.id.to_string();
         let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        // Encrypted before anything is inserted, so a missing encryptor fails
+        // without touching the database
+        let env = space
+            .space_env
+            .iter()
+            .map(|(name, value)| {
+                let sensitive = space.sensitive_env.contains(name);
+                let stored = Self::encode_env_value(encryptor, &space_id, name, value, sensitive)?;
+                Ok((name, stored, sensitive))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        conn.execute(
-            "INSERT INTO spaces (id, name, icon, description, is_default, sort_order, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        conn.execute_cached(
+            "INSERT INTO spaces (id, name, icon, description, is_default, sort_order, created_at, updated_at, version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 space_id,
                 space.name,
@@ -135,12 +202,25 @@ impl SpaceRepository for SqliteSpaceRepository {
                 space.sort_order,
                 space.created_at.to_rfc3339(),
                 space.updated_at.to_rfc3339(),
+                space.version,
             ],
         )?;
+        for tag in space.tags.iter().filter_map(|t| normalize_tag(t)) {
+            conn.execute_cached(
+                "INSERT OR IGNORE INTO space_tags (space_id, tag) VALUES (?1, ?2)",
+                params![space_id, tag],
+            )?;
+        }
+        for (name, stored, sensitive) in env {
+            conn.execute_cached(
+                "INSERT INTO space_env (space_id, name, value, sensitive) VALUES (?1, ?2, ?3, ?4)",
+                params![space_id, name, stored, sensitive],
+            )?;
+        }
 
         // Auto-create builtin featuresets for this space
         // "All Features" - contains all features from all servers in this space
-        conn.execute(
+        conn.execute_cached(
             "INSERT OR IGNORE INTO feature_sets (id, name, description, icon, space_id, feature_set_type, is_builtin, created_at, updated_at)
              VALUES (?1, 'All Features', 'All features from all connected MCP servers in this space', '🌐', ?2, 'all', 1, ?3, ?3)",
             params![
@@ -151,7 +231,7 @@ impl SpaceRepository for SqliteSpaceRepository {
         )?;
 
         // "Default" - auto-granted to all clients in this space
-        conn.execute(
+        conn.execute_cached(
             "INSERT OR IGNORE INTO feature_sets (id, name, description, icon, space_id, feature_set_type, is_builtin, created_at, updated_at)
              VALUES (?1, 'Default', 'Features automatically granted to all connected clients in this space', '⭐', ?2, 'default', 1, ?3, ?3)",
             params![
@@ -164,14 +244,124 @@ impl SpaceRepository for SqliteSpaceRepository {
         Ok(())
     }
 
-    async fn update(&self, space: &Space) -> Result<()> {
-        let db = self.db.lock().await;
+    /// Format a `deleted_at` timestamp. Fixed width, so the stored strings
+    /// compare in time order.
+    fn format_deleted_at(at: DateTime<Utc>) -> String {
+        at.to_rfc3339_opts(SecondsFormat::Micros, true)
+    }
+
+    /// Parse a datetime string to DateTime<Utc>.
+    /// Handles both RFC3339 format and SQLite's `datetime('now')` format.
+    fn parse_datetime(s: &str) -> DateTime<Utc> {
+        // Try RFC3339 first (e.g., "2024-01-01T00:00:00Z")
+        if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+            return dt.with_timezone(&Utc);
+        }
+
+        // Try SQLite's datetime format (e.g., "2024-01-01 00:00:00")
+        if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+            return dt.and_utc();
+        }
+
+        // Fallback to current time
+        Utc::now()
+    }
+}
+
+#[async_trait]
+impl SpaceRepository for SqliteSpaceRepository {
+    async fn list(&self) -> RepoResult<Vec<Space>> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        tracing::debug!("[SpaceRepository::list] Querying spaces...");
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM spaces
+             WHERE deleted_at IS NULL
+             ORDER BY sort_order ASC, name ASC",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let spaces = stmt
+            .query_map([], |row| self.row_to_space(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        tracing::info!("[SpaceRepository::list] Returning {} spaces", spaces.len());
+
+        Ok(spaces)
+    }
+
+    async fn list_page(&self, page: &Page) -> RepoResult<(Vec<Space>, Option<PageCursor>)> {
+        let after = match &page.after {
+            Some(cursor) => match cursor.key()?.as_slice() {
+                [id] => Some(id.clone()),
+                _ => return Err(anyhow::anyhow!("Invalid page cursor").into()),
+            },
+            None => None,
+        };
+        let limit = page.effective_limit();
+
+        let db = self.db.get().await?;
         let conn = db.connection();
 
-        let rows_affected = conn.execute(
+        // Fetch one extra row to learn whether there is a next page
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM spaces
+             WHERE deleted_at IS NULL AND (?1 IS NULL OR id > ?1)
+             ORDER BY id ASC
+             LIMIT ?2",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let mut spaces = stmt
+            .query_map(params![after, limit as i64 + 1], |row| {
+                self.row_to_space(row)
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let next = if spaces.len() > limit {
+            spaces.truncate(limit);
+            spaces
+                .last()
+                .map(|s| PageCursor::from_key(&[s.id.to_string()]))
+        } else {
+            None
+        };
+
+        Ok((spaces, next))
+    }
+
+    async fn get(&self, id: &Uuid) -> RepoResult<Option<Space>> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM spaces WHERE id = ? AND deleted_at IS NULL",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let space = stmt
+            .query_row(params![id.to_string()], |row| self.row_to_space(row))
+            .optional()?;
+
+        Ok(space)
+    }
+
+    async fn create(&self, space: &Space) -> RepoResult<()> {
+        let db = self.db.get().await?;
+        Ok(db.transaction(|conn| Self::create_in(conn, space, self.encryptor.as_deref()))?)
+    }
+
+    async fn update(&self, space: &Space) -> RepoResult<()> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        let rows_affected = conn.execute_cached(
             "UPDATE spaces 
-             SET name = ?2, icon = ?3, description = ?4, is_default = ?5, sort_order = ?6, updated_at = ?7
-             WHERE id = ?1",
+             SET name = ?2, icon = ?3, description = ?4, is_default = ?5, sort_order = ?6, updated_at = ?7,
+                 version = version + 1
+             WHERE id = ?1 AND version = ?8 AND deleted_at IS NULL",
             params![
                 space.id.to_string(),
                 space.name,
@@ -180,86 +370,288 @@ impl SpaceRepository for SqliteSpaceRepository {
                 if space.is_default { 1 } else { 0 },
                 space.sort_order,
                 space.updated_at.to_rfc3339(),
+                space.version,
             ],
         )?;
 
         if rows_affected == 0 {
-            anyhow::bail!("Space not found: {}", space.id);
+            let current_version: Option<i64> = conn
+                .query_row_cached(
+                    "SELECT version FROM spaces WHERE id = ?1 AND deleted_at IS NULL",
+                    params![space.id.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            return Err(match current_version {
+                Some(current_version) => StaleWrite { current_version }.into(),
+                None => StorageError::NotFound(format!("Space {}", space.id)),
+            });
         }
 
         Ok(())
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<()> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
+    async fn delete(&self, id: &Uuid) -> RepoResult<()> {
+        let db = self.db.get().await?;
+        let deleted_at = Self::format_deleted_at(Utc::now());
 
-        conn.execute("DELETE FROM spaces WHERE id = ?", params![id.to_string()])?;
+        db.transaction(|conn| {
+            let rows_affected = conn.execute_cached(
+                "UPDATE spaces SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+                params![id.to_string(), deleted_at],
+            )?;
+            // Same stamp on the credentials, so restore brings back exactly these
+            if rows_affected > 0 {
+                conn.execute_cached(
+                    "UPDATE credentials SET deleted_at = ?2 WHERE space_id = ?1 AND deleted_at IS NULL",
+                    params![id.to_string(), deleted_at],
+                )?;
+            }
+            Ok(())
+        })?;
 
         Ok(())
     }
 
-    async fn get_default(&self) -> Result<Option<Space>> {
-        let db = self.db.lock().await;
+    async fn get_default(&self) -> RepoResult<Option<Space>> {
+        let db = self.db.get().await?;
         let conn = db.connection();
 
-        let mut stmt = conn.prepare(
-            "SELECT id, name, icon, description, is_default, sort_order, created_at, updated_at
-             FROM spaces
-             WHERE is_default = 1
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM spaces
+             WHERE is_default = 1 AND deleted_at IS NULL
              LIMIT 1",
-        )?;
+            Self::SELECT_COLUMNS
+        ))?;
 
         let space = stmt
-            .query_row([], |row| {
-                let id_str: String = row.get(0)?;
-                let name: String = row.get(1)?;
-
-                Ok(Space {
-                    id: id_str.parse().unwrap_or_else(|_| Uuid::new_v4()),
-                    name,
-                    icon: row.get(2)?,
-                    description: row.get(3)?,
-                    is_default: true,
-                    sort_order: row.get(5)?,
-                    created_at: Self::parse_datetime(&row.get::<_, String>(6)?),
-                    updated_at: Self::parse_datetime(&row.get::<_, String>(7)?),
-                })
-            })
+            .query_row([], |row| self.row_to_space(row))
             .optional()?;
 
         Ok(space)
     }
 
-    async fn set_default(&self, id: &Uuid) -> Result<()> {
-        let db = self.db.lock().await;
-        let conn = db.connection();
+    async fn set_default(&self, id: &Uuid) -> RepoResult<()> {
+        let db = self.db.get().await?;
 
         // Use a transaction to ensure atomicity
-        let tx = conn.unchecked_transaction()?;
+        db.transaction(|conn| {
+            // Clear the other defaults; rows that change get a new version
+            conn.execute_cached(
+                "UPDATE spaces SET is_default = 0, version = version + 1
+                 WHERE is_default = 1 AND id != ?1",
+                params![id.to_string()],
+            )?;
 
-        // Clear all defaults
-        tx.execute("UPDATE spaces SET is_default = 0", [])?;
+            // Set the new default
+            let rows_affected = conn.execute_cached(
+                "UPDATE spaces SET version = version + (1 - is_default), is_default = 1
+                 WHERE id = ?1 AND deleted_at IS NULL",
+                params![id.to_string()],
+            )?;
 
-        // Set the new default
-        let rows_affected = tx.execute(
-            "UPDATE spaces SET is_default = 1 WHERE id = ?",
-            params![id.to_string()],
+            if rows_affected == 0 {
+                return Err(StorageError::NotFound(format!("Space {}", id)).into());
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    async fn restore(&self, id: &Uuid) -> RepoResult<()> {
+        let db = self.db.get().await?;
+
+        db.transaction(|conn| {
+            let deleted_at: Option<String> = conn
+                .query_row_cached(
+                    "SELECT deleted_at FROM spaces WHERE id = ?1 AND deleted_at IS NOT NULL",
+                    params![id.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(deleted_at) = deleted_at else {
+                return Err(StorageError::NotFound(format!("Deleted space {}", id)).into());
+            };
+
+            conn.execute_cached(
+                "UPDATE credentials SET deleted_at = NULL WHERE space_id = ?1 AND deleted_at = ?2",
+                params![id.to_string(), deleted_at],
+            )?;
+            conn.execute_cached(
+                "UPDATE spaces SET deleted_at = NULL WHERE id = ?1",
+                params![id.to_string()],
+            )?;
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    async fn list_deleted(&self) -> RepoResult<Vec<Space>> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM spaces
+             WHERE deleted_at IS NOT NULL
+             ORDER BY deleted_at DESC",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let spaces = stmt
+            .query_map([], |row| self.row_to_space(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(spaces)
+    }
+
+    async fn purge_older_than(&self, age: Duration) -> RepoResult<usize> {
+        let cutoff = Utc::now()
+            - chrono::Duration::from_std(age).context("Retention period out of range")?;
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        // Foreign keys cascade to the space's credentials, servers and feature sets
+        let purged = conn.execute_cached(
+            "DELETE FROM spaces WHERE deleted_at IS NOT NULL AND deleted_at <= ?1",
+            params![Self::format_deleted_at(cutoff)],
         )?;
 
-        if rows_affected == 0 {
-            anyhow::bail!("Space not found: {}", id);
+        if purged > 0 {
+            tracing::info!("[SpaceRepository] Purged {} deleted space(s)", purged);
         }
 
-        tx.commit()?;
+        Ok(purged)
+    }
+
+    async fn add_tag(&self, id: &Uuid, tag: &str) -> RepoResult<bool> {
+        let tag = normalize_tag(tag).ok_or_else(|| anyhow::anyhow!("Tag must not be empty"))?;
+        let db = self.db.get().await?;
+
+        // Checked in the same transaction, so a concurrent delete can't slip in
+        let added = db.transaction(|conn| {
+            Self::ensure_active(conn, id)?;
+            Ok(conn.execute_cached(
+                "INSERT OR IGNORE INTO space_tags (space_id, tag) VALUES (?1, ?2)",
+                params![id.to_string(), tag],
+            )?)
+        })?;
+        Ok(added > 0)
+    }
 
+    async fn remove_tag(&self, id: &Uuid, tag: &str) -> RepoResult<bool> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        // The column's NOCASE collation makes this match any spelling
+        let removed = conn.execute_cached(
+            "DELETE FROM space_tags WHERE space_id = ?1 AND tag = ?2",
+            params![id.to_string(), tag.trim()],
+        )?;
+        Ok(removed > 0)
+    }
+
+    async fn set_env(&self, id: &Uuid, name: &str, value: &str, sensitive: bool) -> RepoResult<()> {
+        let space_id = id.to_string();
+        let stored =
+            Self::encode_env_value(self.encryptor.as_deref(), &space_id, name, value, sensitive)?;
+        let db = self.db.get().await?;
+
+        db.transaction(|conn| {
+            Self::ensure_active(conn, id)?;
+            conn.execute_cached(
+                "INSERT INTO space_env (space_id, name, value, sensitive) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (space_id, name) DO UPDATE SET value = excluded.value, sensitive = excluded.sensitive",
+                params![space_id, name, stored, sensitive],
+            )?;
+            Ok(())
+        })?;
         Ok(())
     }
+
+    async fn remove_env(&self, id: &Uuid, name: &str) -> RepoResult<bool> {
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        let removed = conn.execute_cached(
+            "DELETE FROM space_env WHERE space_id = ?1 AND name = ?2",
+            params![id.to_string(), name],
+        )?;
+        Ok(removed > 0)
+    }
+
+    async fn list_spaces_by_tag(
+        &self,
+        tags: &[String],
+        mode: TagMatch,
+        page: &Page,
+    ) -> RepoResult<(Vec<Space>, Option<PageCursor>)> {
+        let after = match &page.after {
+            Some(cursor) => match cursor.key()?.as_slice() {
+                [id] => Some(id.clone()),
+                _ => return Err(anyhow::anyhow!("Invalid page cursor").into()),
+            },
+            None => None,
+        };
+        let mut wanted: Vec<String> = tags.iter().filter_map(|t| normalize_tag(t)).collect();
+        wanted.sort_by_key(|t| t.to_ascii_lowercase());
+        wanted.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        if wanted.is_empty() {
+            return Ok((Vec::new(), None));
+        }
+        // A space matches when it has at least this many of the wanted tags
+        let required = match mode {
+            TagMatch::Any => 1,
+            TagMatch::All => wanted.len(),
+        };
+        let limit = page.effective_limit();
+
+        let db = self.db.get().await?;
+        let conn = db.connection();
+
+        // Fetch one extra row to learn whether there is a next page
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM spaces
+             WHERE deleted_at IS NULL AND (?1 IS NULL OR id > ?1)
+               AND (SELECT COUNT(*) FROM space_tags
+                    WHERE space_id = spaces.id AND tag IN (SELECT value FROM json_each(?2))) >= ?3
+             ORDER BY id ASC
+             LIMIT ?4",
+            Self::SELECT_COLUMNS
+        ))?;
+
+        let mut spaces = stmt
+            .query_map(
+                params![
+                    after,
+                    serde_json::to_string(&wanted)?,
+                    required as i64,
+                    limit as i64 + 1
+                ],
+                |row| self.row_to_space(row),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let next = if spaces.len() > limit {
+            spaces.truncate(limit);
+            spaces
+                .last()
+                .map(|s| PageCursor::from_key(&[s.id.to_string()]))
+        } else {
+            None
+        };
+
+        Ok((spaces, next))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Database;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     /// Default space ID created by migration
     const DEFAULT_SPACE_ID: &str = "00000000-0000-0000-0000-000000000001";
@@ -327,4 +719,325 @@ mod tests {
         let default = repo.get_default().await.unwrap();
         assert_eq!(default.unwrap().name, "My Space");
     }
+
+    #[tokio::test]
+    async fn test_stale_update_is_rejected() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let repo = SqliteSpaceRepository::new(db);
+
+        let space = Space::new("Shared");
+        repo.create(&space).await.unwrap();
+
+        // Two windows load the same version
+        let mut first = repo.get(&space.id).await.unwrap().unwrap();
+        let mut second = first.clone();
+        assert_eq!(first.version, 1);
+
+        first.name = "Renamed in window 1".to_string();
+        repo.update(&first).await.unwrap();
+
+        second.description = Some("Edited in window 2".to_string());
+        let err = repo.update(&second).await.unwrap_err();
+        assert!(matches!(
+            err,
+            StorageError::Stale(StaleWrite { current_version: 2 })
+        ));
+
+        // After reloading, the merge goes through
+        let mut reloaded = repo.get(&space.id).await.unwrap().unwrap();
+        assert_eq!(reloaded.name, "Renamed in window 1");
+        reloaded.description = second.description;
+        repo.update(&reloaded).await.unwrap();
+        assert_eq!(repo.get(&space.id).await.unwrap().unwrap().version, 3);
+
+        // Becoming (or stopping being) the default is a change too
+        repo.set_default(&space.id).await.unwrap();
+        assert_eq!(repo.get(&space.id).await.unwrap().unwrap().version, 4);
+        let err = repo.update(&reloaded).await.unwrap_err();
+        assert!(matches!(err, StorageError::Stale(_)));
+
+        // Missing spaces are still reported as such
+        let err = repo.update(&Space::new("Nowhere")).await.unwrap_err();
+        assert!(matches!(err, StorageError::NotFound(_)), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_list_page() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let repo = SqliteSpaceRepository::new(db);
+        for i in 0..4 {
+            repo.create(&Space::new(format!("Space {}", i)))
+                .await
+                .unwrap();
+        }
+
+        // Default + 4 = 5 spaces, in pages of 2
+        let (first, next) = repo.list_page(&Page::first(2)).await.unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].id.to_string(), DEFAULT_SPACE_ID);
+
+        // A space inserted mid-pagination doesn't shift later pages
+        repo.create(&Space::new("Late")).await.unwrap();
+
+        let mut seen: Vec<Uuid> = first.iter().map(|s| s.id).collect();
+        let mut cursor = next;
+        while let Some(after) = cursor {
+            let (rows, next) = repo.list_page(&Page::after(after, 2)).await.unwrap();
+            assert!(!rows.is_empty());
+            seen.extend(rows.iter().map(|s| s.id));
+            cursor = next;
+        }
+
+        let mut sorted = seen.clone();
+        sorted.sort_by_key(|id| id.to_string());
+        sorted.dedup();
+        assert_eq!(seen, sorted, "pages must be ordered with no duplicates");
+        assert!(seen.len() >= 5);
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_and_restore() {
+        use crate::crypto::{generate_master_key, FieldEncryptor};
+        use crate::SqliteCredentialRepository;
+        use mcpmux_core::{Credential, CredentialRepository, CredentialType};
+
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let repo = SqliteSpaceRepository::new(db.clone());
+        let encryptor = Arc::new(FieldEncryptor::new(&generate_master_key().unwrap()).unwrap());
+        let creds = SqliteCredentialRepository::new(db, encryptor);
+
+        let space = Space::new("Work");
+        repo.create(&space).await.unwrap();
+        creds
+            .save(&Credential::api_key(space.id, "github", "ghp_1"))
+            .await
+            .unwrap();
+
+        repo.delete(&space.id).await.unwrap();
+        assert!(repo.get(&space.id).await.unwrap().is_none());
+        assert_eq!(repo.list().await.unwrap().len(), 1);
+        assert!(creds.list_for_space(&space.id).await.unwrap().is_empty());
+        assert!(creds
+            .get(&space.id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .is_none());
+
+        let deleted = repo.list_deleted().await.unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].id, space.id);
+        assert!(deleted[0].deleted_at.is_some());
+
+        repo.restore(&space.id).await.unwrap();
+        let restored = repo.get(&space.id).await.unwrap().unwrap();
+        assert!(restored.deleted_at.is_none());
+        let cred = creds
+            .get(&space.id, "github", &CredentialType::ApiKey)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cred.value, "ghp_1");
+        assert!(repo.list_deleted().await.unwrap().is_empty());
+
+        // Only deleted spaces can be restored
+        assert!(repo.restore(&space.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_purge_older_than() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let repo = SqliteSpaceRepository::new(db.clone());
+
+        let old = Space::new("Old");
+        let recent = Space::new("Recent");
+        repo.create(&old).await.unwrap();
+        repo.create(&recent).await.unwrap();
+        repo.delete(&old.id).await.unwrap();
+        repo.delete(&recent.id).await.unwrap();
+
+        // Backdate the first delete by two days
+        let two_days_ago =
+            SqliteSpaceRepository::format_deleted_at(Utc::now() - chrono::Duration::days(2));
+        db.lock()
+            .await
+            .connection()
+            .execute(
+                "UPDATE spaces SET deleted_at = ?2 WHERE id = ?1",
+                params![old.id.to_string(), two_days_ago],
+            )
+            .unwrap();
+
+        let purged = repo
+            .purge_older_than(Duration::from_secs(24 * 60 * 60))
+            .await
+            .unwrap();
+        assert_eq!(purged, 1);
+
+        let deleted = repo.list_deleted().await.unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].id, recent.id);
+        assert!(repo.restore(&old.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tags() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let repo = SqliteSpaceRepository::new(db);
+
+        let work = Space::new("Work").with_tag("work").with_tag("client-x");
+        let home = Space::new("Home");
+        let both = Space::new("Both");
+        repo.create(&work).await.unwrap();
+        repo.create(&home).await.unwrap();
+        repo.create(&both).await.unwrap();
+
+        assert!(repo.add_tag(&home.id, "Personal").await.unwrap());
+        // Duplicates are detected ignoring case and surrounding space
+        assert!(!repo.add_tag(&home.id, " personal ").await.unwrap());
+        assert!(repo.add_tag(&home.id, "  ").await.is_err());
+        assert!(repo.add_tag(&Uuid::new_v4(), "work").await.is_err());
+        assert!(repo.add_tag(&both.id, "WORK").await.unwrap());
+        assert!(repo.add_tag(&both.id, "personal").await.unwrap());
+
+        let found = repo.get(&work.id).await.unwrap().unwrap();
+        assert_eq!(found.tags, vec!["client-x", "work"]);
+        assert_eq!(
+            repo.get(&home.id).await.unwrap().unwrap().tags,
+            vec!["Personal"]
+        );
+
+        let ids = |spaces: Vec<Space>| {
+            let mut ids: Vec<_> = spaces.into_iter().map(|s| s.id).collect();
+            ids.sort();
+            ids
+        };
+        let sorted = |mut v: Vec<Uuid>| {
+            v.sort();
+            v
+        };
+        let tags = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let (any, _) = repo
+            .list_spaces_by_tag(
+                &tags(&["work", "PERSONAL"]),
+                TagMatch::Any,
+                &Page::first(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids(any), sorted(vec![work.id, home.id, both.id]));
+
+        let (all, _) = repo
+            .list_spaces_by_tag(
+                &tags(&["work", "personal", "Work"]),
+                TagMatch::All,
+                &Page::first(10),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids(all), vec![both.id]);
+
+        let (none, next) = repo
+            .list_spaces_by_tag(&[], TagMatch::Any, &Page::first(10))
+            .await
+            .unwrap();
+        assert!(none.is_empty() && next.is_none());
+
+        // Pages follow the same cursor scheme as list_page
+        let (first, next) = repo
+            .list_spaces_by_tag(&tags(&["work"]), TagMatch::Any, &Page::first(1))
+            .await
+            .unwrap();
+        let (second, last) = repo
+            .list_spaces_by_tag(
+                &tags(&["work"]),
+                TagMatch::Any,
+                &Page::after(next.unwrap(), 1),
+            )
+            .await
+            .unwrap();
+        assert!(last.is_none());
+        assert_eq!(
+            ids(first.into_iter().chain(second).collect()),
+            sorted(vec![work.id, both.id])
+        );
+
+        assert!(repo.remove_tag(&both.id, "Work").await.unwrap());
+        assert!(!repo.remove_tag(&both.id, "work").await.unwrap());
+        assert_eq!(
+            repo.get(&both.id).await.unwrap().unwrap().tags,
+            vec!["personal"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_space_env() {
+        use crate::crypto::{generate_master_key, FieldEncryptor};
+
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let encryptor = Arc::new(FieldEncryptor::new(&generate_master_key().unwrap()).unwrap());
+        let repo = SqliteSpaceRepository::new(db.clone()).with_encryptor(encryptor);
+
+        let space = Space::new("Work")
+            .with_env("HTTP_PROXY", "http://proxy:3128")
+            .with_sensitive_env("NPM_TOKEN", "npm_secret");
+        repo.create(&space).await.unwrap();
+
+        let found = repo.get(&space.id).await.unwrap().unwrap();
+        assert_eq!(found.space_env, space.space_env);
+        assert!(found.sensitive_env.contains("NPM_TOKEN"));
+        assert!(!found.sensitive_env.contains("HTTP_PROXY"));
+
+        // Sensitive values are encrypted at rest
+        let stored: String = db
+            .lock()
+            .await
+            .connection()
+            .query_row(
+                "SELECT value FROM space_env WHERE name = 'NPM_TOKEN'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!stored.contains("npm_secret"));
+
+        repo.set_env(&space.id, "HTTP_PROXY", "http://other:8080", false)
+            .await
+            .unwrap();
+        assert!(repo.remove_env(&space.id, "NPM_TOKEN").await.unwrap());
+        assert!(!repo.remove_env(&space.id, "NPM_TOKEN").await.unwrap());
+        assert!(repo
+            .set_env(&Uuid::new_v4(), "HTTP_PROXY", "x", false)
+            .await
+            .is_err());
+
+        let found = repo.get(&space.id).await.unwrap().unwrap();
+        assert_eq!(
+            found.space_env,
+            HashMap::from([("HTTP_PROXY".to_string(), "http://other:8080".to_string())])
+        );
+        assert!(found.sensitive_env.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_space_env_needs_encryptor() {
+        let db = Arc::new(Mutex::new(Database::open_in_memory().unwrap()));
+        let repo = SqliteSpaceRepository::new(db);
+
+        let space = Space::new("Work").with_env("HTTP_PROXY", "http://proxy:3128");
+        repo.create(&space).await.unwrap();
+
+        assert!(repo
+            .set_env(&space.id, "NPM_TOKEN", "npm_secret", true)
+            .await
+            .is_err());
+        assert!(repo
+            .create(&Space::new("Secret").with_sensitive_env("NPM_TOKEN", "npm_secret"))
+            .await
+            .is_err());
+        assert_eq!(
+            repo.get(&space.id).await.unwrap().unwrap().space_env.len(),
+            1
+        );
+    }
 }
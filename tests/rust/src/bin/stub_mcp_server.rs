@@ -0,0 +1,114 @@
+//! Stub MCP server for STDIO transport tests
+//!
+//! Speaks just enough newline-delimited JSON-RPC to complete the MCP
+//! handshake and answer a few requests, so tests can drive `StdioTransport`
+//! through a real connect without depending on Node, Python or the network.
+//!
+//! Cargo builds it alongside the integration tests, which find it with
+//! `env!("CARGO_BIN_EXE_stub-mcp-server")` (see `tests/gateway/stub_server.rs`).
+//!
+//! Supported methods:
+//! - `initialize`: echoes the client's protocol version and advertises tools
+//! - `ping`
+//! - `tools/list`: the `echo` and `crash` tools
+//! - `tools/call`: `echo` returns its `text` argument; `crash` exits the
+//!   process with status 1 without answering, to exercise restarts
+//!
+//! Anything else gets a "method not found" error. Notifications are ignored.
+//! The server exits cleanly when stdin closes.
+
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn main() {
+    eprintln!("stub-mcp-server started");
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Ok(request) = serde_json::from_str::<Value>(&line) else {
+            eprintln!("stub-mcp-server: ignoring malformed line: {line}");
+            continue;
+        };
+        // Notifications carry no id and get no response
+        let Some(id) = request.get("id").cloned() else {
+            continue;
+        };
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        let response = match handle(method, &params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        if writeln!(stdout, "{response}")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+fn handle(method: &str, params: &Value) -> Result<Value, (i64, String)> {
+    match method {
+        "initialize" => {
+            let version = params
+                .get("protocolVersion")
+                .cloned()
+                .unwrap_or_else(|| json!("2025-03-26"));
+            Ok(json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": { "name": "stub-mcp-server", "version": "0.0.0" },
+            }))
+        }
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({
+            "tools": [
+                {
+                    "name": "echo",
+                    "description": "Return the given text",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": { "text": { "type": "string" } },
+                    },
+                },
+                {
+                    "name": "crash",
+                    "description": "Exit the server process with status 1",
+                    "inputSchema": { "type": "object" },
+                },
+            ],
+        })),
+        "tools/call" => match params.get("name").and_then(Value::as_str) {
+            Some("echo") => {
+                let text = params
+                    .pointer("/arguments/text")
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                Ok(json!({
+                    "content": [{ "type": "text", "text": text }],
+                    "isError": false,
+                }))
+            }
+            Some("crash") => {
+                eprintln!("stub-mcp-server: crashing on request");
+                std::process::exit(1);
+            }
+            other => Err((INVALID_PARAMS, format!("Unknown tool: {other:?}"))),
+        },
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {method}"))),
+    }
+}
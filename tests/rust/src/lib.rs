@@ -199,7 +199,7 @@ pub mod db {
     /// Create a temporary database for testing
     pub struct TestDatabase {
         pub db: Database,
-        _temp_dir: TempDir,
+        _temp_dir: Option<TempDir>,
         db_path: PathBuf,
     }
 
@@ -212,24 +212,23 @@ pub mod db {
             Self {
                 db,
                 db_path,
-                _temp_dir: temp_dir,
+                _temp_dir: Some(temp_dir),
             }
         }
 
-        /// Create an in-memory database for fast tests
+        /// Create an in-memory database for fast tests (nothing touches disk)
         pub fn in_memory() -> Self {
-            let temp_dir = TempDir::new().expect("Failed to create temp dir");
             let db = Database::open_in_memory().expect("Failed to open in-memory database");
             Self {
                 db,
                 db_path: PathBuf::new(),
-                _temp_dir: temp_dir,
+                _temp_dir: None,
             }
         }
 
-        /// Get the database directory path
-        pub fn path(&self) -> &Path {
-            self._temp_dir.path()
+        /// Get the database directory path (`None` for in-memory databases)
+        pub fn path(&self) -> Option<&Path> {
+            self._temp_dir.as_ref().map(|dir| dir.path())
         }
 
         /// Get the full database file path
@@ -15,7 +15,7 @@ use mcpmux_core::{
     repository::{
         AppSettingsRepository, CredentialRepository, FeatureSetRepository,
         InboundMcpClientRepository, InstalledServerRepository, OutboundOAuthRepository, RepoResult,
-        ServerFeatureRepository, SpaceRepository,
+        ServerFeatureRepository, SpaceRepository, StaleWrite, StorageError,
     },
 };
 
@@ -26,6 +26,7 @@ use mcpmux_core::{
 #[derive(Default)]
 pub struct MockSpaceRepository {
     spaces: RwLock<HashMap<Uuid, Space>>,
+    deleted: RwLock<HashMap<Uuid, Space>>,
     default_id: RwLock<Option<Uuid>>,
 }
 
@@ -56,12 +57,26 @@ impl SpaceRepository for MockSpaceRepository {
     }
 
     async fn update(&self, space: &Space) -> RepoResult<()> {
-        self.spaces.write().unwrap().insert(space.id, space.clone());
+        let mut spaces = self.spaces.write().unwrap();
+        if let Some(stored) = spaces.get(&space.id) {
+            if stored.version != space.version {
+                return Err(StaleWrite {
+                    current_version: stored.version,
+                }
+                .into());
+            }
+        }
+        let mut space = space.clone();
+        space.version += 1;
+        spaces.insert(space.id, space);
         Ok(())
     }
 
     async fn delete(&self, id: &Uuid) -> RepoResult<()> {
-        self.spaces.write().unwrap().remove(id);
+        if let Some(mut space) = self.spaces.write().unwrap().remove(id) {
+            space.deleted_at = Some(chrono::Utc::now());
+            self.deleted.write().unwrap().insert(*id, space);
+        }
         Ok(())
     }
 
@@ -77,6 +92,81 @@ impl SpaceRepository for MockSpaceRepository {
         *self.default_id.write().unwrap() = Some(*id);
         Ok(())
     }
+
+    async fn restore(&self, id: &Uuid) -> RepoResult<()> {
+        let mut space = self
+            .deleted
+            .write()
+            .unwrap()
+            .remove(id)
+            .ok_or_else(|| StorageError::NotFound(format!("Deleted space {}", id)))?;
+        space.deleted_at = None;
+        self.spaces.write().unwrap().insert(*id, space);
+        Ok(())
+    }
+
+    async fn list_deleted(&self) -> RepoResult<Vec<Space>> {
+        let mut spaces: Vec<_> = self.deleted.read().unwrap().values().cloned().collect();
+        spaces.sort_by_key(|s| std::cmp::Reverse(s.deleted_at));
+        Ok(spaces)
+    }
+
+    async fn purge_older_than(&self, age: std::time::Duration) -> RepoResult<usize> {
+        let cutoff = chrono::Utc::now()
+            - chrono::Duration::from_std(age).map_err(|e| StorageError::Other(e.into()))?;
+        let mut deleted = self.deleted.write().unwrap();
+        let before = deleted.len();
+        deleted.retain(|_, space| space.deleted_at.is_none_or(|at| at > cutoff));
+        Ok(before - deleted.len())
+    }
+
+    async fn add_tag(&self, id: &Uuid, tag: &str) -> RepoResult<bool> {
+        let mut spaces = self.spaces.write().unwrap();
+        let space = spaces
+            .get_mut(id)
+            .ok_or_else(|| StorageError::NotFound(format!("Space {}", id)))?;
+        let tag = mcpmux_core::normalize_tag(tag).ok_or_else(|| {
+            StorageError::Other(std::io::Error::other("Tag must not be empty").into())
+        })?;
+        if space.has_tag(&tag) {
+            return Ok(false);
+        }
+        space.tags.push(tag);
+        Ok(true)
+    }
+
+    async fn remove_tag(&self, id: &Uuid, tag: &str) -> RepoResult<bool> {
+        let mut spaces = self.spaces.write().unwrap();
+        let Some(space) = spaces.get_mut(id) else {
+            return Ok(false);
+        };
+        let before = space.tags.len();
+        space.tags.retain(|t| !t.eq_ignore_ascii_case(tag.trim()));
+        Ok(space.tags.len() < before)
+    }
+
+    async fn set_env(&self, id: &Uuid, name: &str, value: &str, sensitive: bool) -> RepoResult<()> {
+        let mut spaces = self.spaces.write().unwrap();
+        let space = spaces
+            .get_mut(id)
+            .ok_or_else(|| StorageError::NotFound(format!("Space {}", id)))?;
+        if sensitive {
+            space.sensitive_env.insert(name.to_string());
+        } else {
+            space.sensitive_env.remove(name);
+        }
+        space.space_env.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    async fn remove_env(&self, id: &Uuid, name: &str) -> RepoResult<bool> {
+        let mut spaces = self.spaces.write().unwrap();
+        let Some(space) = spaces.get_mut(id) else {
+            return Ok(false);
+        };
+        space.sensitive_env.remove(name);
+        Ok(space.space_env.remove(name).is_some())
+    }
 }
 
 // ============================================================================
@@ -780,6 +870,22 @@ impl CredentialRepository for MockCredentialRepository {
         Ok(creds.len() < before)
     }
 
+    async fn sweep_expired(&self) -> RepoResult<usize> {
+        let mut creds = self.credentials.write().unwrap();
+        let refreshable: Vec<_> = creds
+            .values()
+            .filter(|c| c.credential_type == CredentialType::RefreshToken && !c.is_expired())
+            .map(|c| (c.space_id, c.server_id.clone()))
+            .collect();
+        let before = creds.len();
+        creds.retain(|_, c| {
+            !c.is_expired()
+                || (c.credential_type == CredentialType::AccessToken
+                    && refreshable.contains(&(c.space_id, c.server_id.clone())))
+        });
+        Ok(before - creds.len())
+    }
+
     async fn list_for_space(&self, space_id: &Uuid) -> RepoResult<Vec<Credential>> {
         Ok(self
             .credentials
@@ -111,7 +111,7 @@ impl ServerManagerTestHarness {
 /// This creates a real ConnectionService with mock repositories.
 /// For unit tests that don't need actual connections, this provides
 /// enough infrastructure for the state machine to work.
-fn create_mock_connection_service(
+pub fn create_mock_connection_service(
     credential_repo: Arc<MockCredentialRepository>,
     oauth_repo: Arc<MockOutboundOAuthRepository>,
     prefix_cache: Arc<PrefixCacheService>,
@@ -16,7 +16,7 @@ use tokio::sync::Mutex;
 
 #[tokio::test]
 async fn test_create_and_get_feature_set() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -43,7 +43,7 @@ async fn test_create_and_get_feature_set() {
 
 #[tokio::test]
 async fn test_list_by_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -83,7 +83,7 @@ async fn test_list_by_space() {
 
 #[tokio::test]
 async fn test_update_feature_set() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -114,7 +114,7 @@ async fn test_update_feature_set() {
 
 #[tokio::test]
 async fn test_delete_feature_set() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -145,7 +145,7 @@ async fn test_delete_feature_set() {
 
 #[tokio::test]
 async fn test_ensure_builtin_for_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -179,7 +179,7 @@ async fn test_ensure_builtin_for_space() {
 
 #[tokio::test]
 async fn test_ensure_builtin_idempotent() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -204,7 +204,7 @@ async fn test_ensure_builtin_idempotent() {
 
 #[tokio::test]
 async fn test_server_all_feature_set() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -236,7 +236,7 @@ async fn test_server_all_feature_set() {
 
 #[tokio::test]
 async fn test_delete_server_all() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -266,7 +266,7 @@ async fn test_delete_server_all() {
 
 #[tokio::test]
 async fn test_add_feature_member() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -300,7 +300,7 @@ async fn test_add_feature_member() {
 
 #[tokio::test]
 async fn test_add_multiple_feature_members() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -341,7 +341,7 @@ async fn test_add_multiple_feature_members() {
 
 #[tokio::test]
 async fn test_remove_feature_member() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -386,7 +386,7 @@ async fn test_remove_feature_member() {
 
 #[tokio::test]
 async fn test_get_with_members() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -422,7 +422,7 @@ async fn test_get_with_members() {
 
 #[tokio::test]
 async fn test_feature_set_types() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -480,7 +480,7 @@ async fn test_feature_set_types() {
 
 #[tokio::test]
 async fn test_feature_set_space_isolation() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let feature_repo = SqliteFeatureSetRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -49,7 +49,7 @@ fn create_test_client(name: &str) -> InboundClient {
 
 #[tokio::test]
 async fn test_save_and_get_client() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -73,7 +73,7 @@ async fn test_save_and_get_client() {
 
 #[tokio::test]
 async fn test_find_client_by_name() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -98,7 +98,7 @@ async fn test_find_client_by_name() {
 
 #[tokio::test]
 async fn test_client_update_preserves_fields() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -117,7 +117,7 @@ async fn test_client_update_preserves_fields() {
 
 #[tokio::test]
 async fn test_approve_client() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -142,7 +142,7 @@ async fn test_approve_client() {
 
 #[tokio::test]
 async fn test_list_clients() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -157,7 +157,7 @@ async fn test_list_clients() {
 
 #[tokio::test]
 async fn test_delete_client() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -183,7 +183,7 @@ async fn test_delete_client() {
 
 #[tokio::test]
 async fn test_validate_redirect_uri() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -213,7 +213,7 @@ async fn test_validate_redirect_uri() {
 
 #[tokio::test]
 async fn test_merge_redirect_uris() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -245,7 +245,7 @@ async fn test_merge_redirect_uris() {
 
 #[tokio::test]
 async fn test_authorization_code_save_and_consume() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -288,7 +288,7 @@ async fn test_authorization_code_save_and_consume() {
 
 #[tokio::test]
 async fn test_authorization_code_not_found() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -316,7 +316,7 @@ async fn test_token_hash_consistency() {
 
 #[tokio::test]
 async fn test_save_and_find_token() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -356,7 +356,7 @@ async fn test_save_and_find_token() {
 
 #[tokio::test]
 async fn test_validate_token() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -391,7 +391,7 @@ async fn test_validate_token() {
 
 #[tokio::test]
 async fn test_validate_expired_token() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -419,7 +419,7 @@ async fn test_validate_expired_token() {
 
 #[tokio::test]
 async fn test_validate_revoked_token() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -447,7 +447,7 @@ async fn test_validate_revoked_token() {
 
 #[tokio::test]
 async fn test_revoke_token_and_children() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -507,7 +507,7 @@ async fn test_revoke_token_and_children() {
 
 #[tokio::test]
 async fn test_revoke_client_tokens() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -550,7 +550,7 @@ async fn test_revoke_client_tokens() {
 
 #[tokio::test]
 async fn test_grant_feature_set() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -579,7 +579,7 @@ async fn test_grant_feature_set() {
 
 #[tokio::test]
 async fn test_grant_multiple_feature_sets() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -625,7 +625,7 @@ async fn test_grant_multiple_feature_sets() {
 
 #[tokio::test]
 async fn test_grant_idempotent() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -656,7 +656,7 @@ async fn test_grant_idempotent() {
 
 #[tokio::test]
 async fn test_revoke_feature_set() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -693,7 +693,7 @@ async fn test_revoke_feature_set() {
 
 #[tokio::test]
 async fn test_get_all_grants() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -732,7 +732,7 @@ async fn test_get_all_grants() {
 
 #[tokio::test]
 async fn test_grants_per_space_isolation() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -784,7 +784,7 @@ async fn test_grants_per_space_isolation() {
 
 #[tokio::test]
 async fn test_update_client_settings() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -816,7 +816,7 @@ async fn test_update_client_settings() {
 
 #[tokio::test]
 async fn test_update_last_seen() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = InboundClientRepository::new(db);
 
@@ -17,7 +17,7 @@ fn test_encryptor() -> Arc<FieldEncryptor> {
 
 #[tokio::test]
 async fn test_installed_server_install_and_get() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -46,7 +46,7 @@ async fn test_installed_server_install_and_get() {
 
 #[tokio::test]
 async fn test_installed_server_get_by_server_id() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -83,7 +83,7 @@ async fn test_installed_server_get_by_server_id() {
 
 #[tokio::test]
 async fn test_installed_server_list_for_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -126,7 +126,7 @@ async fn test_installed_server_list_for_space() {
 
 #[tokio::test]
 async fn test_installed_server_uninstall() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -154,7 +154,7 @@ async fn test_installed_server_uninstall() {
 
 #[tokio::test]
 async fn test_installed_server_set_enabled() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -198,7 +198,7 @@ async fn test_installed_server_set_enabled() {
 
 #[tokio::test]
 async fn test_installed_server_list_enabled() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -232,7 +232,7 @@ async fn test_installed_server_list_enabled() {
 
 #[tokio::test]
 async fn test_installed_server_set_oauth_connected() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -266,7 +266,7 @@ async fn test_installed_server_set_oauth_connected() {
 
 #[tokio::test]
 async fn test_installed_server_update_inputs() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -306,7 +306,7 @@ async fn test_installed_server_update_inputs() {
 
 #[tokio::test]
 async fn test_installed_server_update_cached_definition() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -342,7 +342,7 @@ async fn test_installed_server_update_cached_definition() {
 
 #[tokio::test]
 async fn test_installed_server_list_all() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -371,7 +371,7 @@ async fn test_installed_server_list_all() {
 
 #[tokio::test]
 async fn test_installed_server_list_enabled_all() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -407,7 +407,7 @@ async fn test_installed_server_list_enabled_all() {
 
 #[tokio::test]
 async fn test_installed_server_env_overrides_persist() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -443,7 +443,7 @@ async fn test_installed_server_env_overrides_persist() {
 
 #[tokio::test]
 async fn test_installed_server_args_append_persist() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -476,7 +476,7 @@ async fn test_installed_server_args_append_persist() {
 
 #[tokio::test]
 async fn test_installed_server_extra_headers_persist() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -515,7 +515,7 @@ async fn test_installed_server_extra_headers_persist() {
 
 #[tokio::test]
 async fn test_installed_server_update_preserves_custom_fields() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -585,7 +585,7 @@ async fn test_installed_server_update_preserves_custom_fields() {
 
 #[tokio::test]
 async fn test_installed_server_empty_custom_fields_by_default() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -612,7 +612,7 @@ async fn test_installed_server_empty_custom_fields_by_default() {
 
 #[tokio::test]
 async fn test_installed_server_clear_custom_fields_via_update() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -675,7 +675,7 @@ async fn test_installed_server_clear_custom_fields_via_update() {
 
 #[tokio::test]
 async fn test_installed_server_special_characters_persist() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let server_repo = SqliteInstalledServerRepository::new(Arc::clone(&db), test_encryptor());
     let space_repo = SqliteSpaceRepository::new(db);
@@ -38,9 +38,12 @@ fn test_database_creates_file() {
 
 #[test]
 fn test_in_memory_database() {
-    // In-memory database should also run migrations
+    // In-memory database should run the same migrations as a file database
     let db = Database::open_in_memory().expect("Failed to open in-memory database");
+    let file_db = TestDatabase::new();
 
-    // Verify it's usable (we can't really check much else)
-    drop(db);
+    assert_eq!(
+        db.schema_version().unwrap(),
+        file_db.db.schema_version().unwrap()
+    );
 }
@@ -37,7 +37,7 @@ fn create_test_registration(space_id: Uuid, server_id: &str) -> OutboundOAuthReg
 
 #[tokio::test]
 async fn test_save_and_get_registration() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let oauth_repo = SqliteOutboundOAuthRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -61,7 +61,7 @@ async fn test_save_and_get_registration() {
 
 #[tokio::test]
 async fn test_registration_not_found() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let oauth_repo = SqliteOutboundOAuthRepository::new(db);
 
@@ -73,7 +73,7 @@ async fn test_registration_not_found() {
 
 #[tokio::test]
 async fn test_update_registration() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let oauth_repo = SqliteOutboundOAuthRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -104,7 +104,7 @@ async fn test_update_registration() {
 
 #[tokio::test]
 async fn test_delete_registration() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let oauth_repo = SqliteOutboundOAuthRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -129,7 +129,7 @@ async fn test_delete_registration() {
 
 #[tokio::test]
 async fn test_list_registrations_for_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let oauth_repo = SqliteOutboundOAuthRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -152,7 +152,7 @@ async fn test_list_registrations_for_space() {
 
 #[tokio::test]
 async fn test_registrations_isolated_by_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let oauth_repo = SqliteOutboundOAuthRepository::new(Arc::clone(&db));
     let space_repo = SqliteSpaceRepository::new(db);
@@ -204,7 +204,7 @@ fn create_api_key_credential(space_id: Uuid, server_id: &str, api_key: &str) ->
 
 #[tokio::test]
 async fn test_save_and_get_credential() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -247,7 +247,7 @@ async fn test_save_and_get_credential() {
 
 #[tokio::test]
 async fn test_credential_not_found() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(db, encryptor);
@@ -265,7 +265,7 @@ async fn test_credential_not_found() {
 
 #[tokio::test]
 async fn test_save_api_key_credential() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -289,7 +289,7 @@ async fn test_save_api_key_credential() {
 
 #[tokio::test]
 async fn test_update_credential() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -323,7 +323,7 @@ async fn test_update_credential() {
 
 #[tokio::test]
 async fn test_delete_credential() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -373,7 +373,7 @@ async fn test_delete_credential() {
 
 #[tokio::test]
 async fn test_list_credentials_for_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -392,7 +392,7 @@ async fn test_list_credentials_for_space() {
 
 #[tokio::test]
 async fn test_credentials_isolated_by_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -422,7 +422,7 @@ async fn test_credentials_isolated_by_space() {
 
 #[tokio::test]
 async fn test_credential_expiration() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -454,7 +454,7 @@ async fn test_credential_expiration() {
 
 #[tokio::test]
 async fn test_clear_tokens_preserves_api_keys() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor = test_encryptor();
     let cred_repo = SqliteCredentialRepository::new(Arc::clone(&db), encryptor);
@@ -485,7 +485,7 @@ async fn test_clear_tokens_preserves_api_keys() {
 
 #[tokio::test]
 async fn test_different_encryptors_cannot_read_each_others_data() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let encryptor1 = test_encryptor();
     let space_repo = SqliteSpaceRepository::new(Arc::clone(&db));
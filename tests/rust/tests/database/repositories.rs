@@ -11,7 +11,7 @@ use mcpmux_core::repository::SpaceRepository;
 
 #[tokio::test]
 async fn test_space_repository_create_and_get() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = SqliteSpaceRepository::new(db);
 
@@ -35,7 +35,7 @@ async fn test_space_repository_create_and_get() {
 
 #[tokio::test]
 async fn test_space_repository_update() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = SqliteSpaceRepository::new(db);
 
@@ -65,7 +65,7 @@ async fn test_space_repository_update() {
 
 #[tokio::test]
 async fn test_space_repository_delete() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = SqliteSpaceRepository::new(db);
 
@@ -87,7 +87,7 @@ async fn test_space_repository_delete() {
 
 #[tokio::test]
 async fn test_space_repository_list_all() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = SqliteSpaceRepository::new(db);
 
@@ -115,7 +115,7 @@ async fn test_space_repository_list_all() {
 
 #[tokio::test]
 async fn test_space_repository_default_space() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = SqliteSpaceRepository::new(db);
 
@@ -143,7 +143,7 @@ async fn test_space_repository_default_space() {
 
 #[tokio::test]
 async fn test_space_repository_set_default() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = SqliteSpaceRepository::new(db);
 
@@ -182,7 +182,7 @@ async fn test_space_repository_set_default() {
 
 #[tokio::test]
 async fn test_space_repository_concurrent_reads() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = Arc::new(SqliteSpaceRepository::new(db));
 
@@ -213,7 +213,7 @@ async fn test_space_repository_concurrent_reads() {
 
 #[tokio::test]
 async fn test_space_repository_concurrent_writes() {
-    let test_db = TestDatabase::new();
+    let test_db = TestDatabase::in_memory();
     let db = Arc::new(Mutex::new(test_db.db));
     let repo = Arc::new(SqliteSpaceRepository::new(db));
 
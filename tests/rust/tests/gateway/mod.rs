@@ -4,3 +4,4 @@
 
 mod server_manager;
 mod stdio_transport;
+mod stub_server;
@@ -5,10 +5,11 @@
 //! - Event emission
 //! - Lock management
 //! - OAuth flow states
+//! - Connection state transitions and recovery
 //! - Error handling
 
-use mcpmux_core::{ConnectionStatus, DomainEvent};
-use mcpmux_gateway::pool::ServerKey;
+use mcpmux_core::{ConnectionState, ConnectionStatus, DomainEvent};
+use mcpmux_gateway::pool::{CachedFeatures, ServerKey};
 use std::time::Duration;
 use tests::ServerManagerTestHarness;
 use uuid::Uuid;
@@ -351,11 +352,13 @@ async fn test_subscribe_receives_events() {
 
     harness.manager.enable_server(key).await.unwrap();
 
-    // Should receive the event
+    // Should receive the state transition, then the status
     let event = tokio::time::timeout(Duration::from_millis(100), rx.recv()).await;
     assert!(event.is_ok(), "Should receive event");
 
     let event = event.unwrap().unwrap();
+    assert!(matches!(event, DomainEvent::StateChanged { .. }));
+    let event = rx.recv().await.unwrap();
     assert!(matches!(event, DomainEvent::ServerStatusChanged { .. }));
 }
 
@@ -402,10 +405,145 @@ async fn test_cancel_auth_requires_authenticating_state() {
     assert!(result.is_err());
 }
 
+// ============================================================================
+// Connection State
+// ============================================================================
+
+#[tokio::test]
+async fn test_state_changed_follows_lifecycle() {
+    let mut harness = ServerManagerTestHarness::new().await;
+    let key = test_key("server-1");
+
+    harness.manager.enable_server(key.clone()).await.unwrap();
+    harness.manager.set_auth_required(&key, None).await;
+    harness
+        .manager
+        .set_connected(&key, CachedFeatures::default())
+        .await;
+    // Same state again: no transition
+    harness
+        .manager
+        .set_connected(&key, CachedFeatures::default())
+        .await;
+    harness.manager.disable_server(&key).await.unwrap();
+
+    let events = harness.collect_events().await;
+    assert_eq!(
+        state_transitions(&events),
+        vec![
+            (ConnectionState::Idle, ConnectionState::Connecting),
+            (ConnectionState::Connecting, ConnectionState::OAuthPending),
+            (ConnectionState::OAuthPending, ConnectionState::Connected),
+            (ConnectionState::Connected, ConnectionState::Disabled),
+        ]
+    );
+    assert_eq!(
+        harness.manager.connection_state(&key).await,
+        ConnectionState::Disabled
+    );
+}
+
+#[tokio::test]
+async fn test_set_disabled_differs_from_disconnected() {
+    let harness = ServerManagerTestHarness::new().await;
+    let key1 = test_key("server-1");
+    let key2 = test_key("server-2");
+
+    harness.manager.set_error(&key1, "boom".to_string()).await;
+    harness.manager.set_error(&key2, "boom".to_string()).await;
+    assert_eq!(
+        harness.manager.connection_state(&key1).await,
+        ConnectionState::Failed
+    );
+
+    harness.manager.set_disconnected(&key1).await;
+    harness.manager.set_disabled(&key2).await;
+    assert_eq!(
+        harness.manager.connection_state(&key1).await,
+        ConnectionState::Idle
+    );
+    assert_eq!(
+        harness.manager.connection_state(&key2).await,
+        ConnectionState::Disabled
+    );
+}
+
+#[tokio::test]
+async fn test_recovery_watch_tracks_restarts() {
+    let harness = ServerManagerTestHarness::new().await;
+    let key = test_key("server-1");
+    let _watch = harness.manager.clone().start_recovery_watch();
+
+    harness
+        .manager
+        .set_connected(&key, CachedFeatures::default())
+        .await;
+
+    harness
+        .event_tx
+        .send(DomainEvent::ServerRestarting {
+            space_id: key.space_id,
+            server_id: key.server_id.clone(),
+            attempt: 1,
+            max_restarts: 3,
+            delay_ms: 10,
+        })
+        .unwrap();
+    wait_for_state(&harness, &key, ConnectionState::Reconnecting).await;
+
+    harness
+        .event_tx
+        .send(DomainEvent::ServerRecovered {
+            space_id: key.space_id,
+            server_id: key.server_id.clone(),
+        })
+        .unwrap();
+    wait_for_state(&harness, &key, ConnectionState::Connected).await;
+
+    harness
+        .event_tx
+        .send(DomainEvent::ServerFailed {
+            space_id: key.space_id,
+            server_id: key.server_id.clone(),
+            restarts: 3,
+            message: "gave up".to_string(),
+        })
+        .unwrap();
+    wait_for_state(&harness, &key, ConnectionState::Failed).await;
+
+    let (status, _, _, error) = harness.manager.get_status(&key).await.unwrap();
+    assert_eq!(status, mcpmux_gateway::pool::ConnectionStatus::Error);
+    assert_eq!(error.as_deref(), Some("gave up"));
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+fn state_transitions(events: &[DomainEvent]) -> Vec<(ConnectionState, ConnectionState)> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            DomainEvent::StateChanged { from, to, .. } => Some((*from, *to)),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn wait_for_state(
+    harness: &ServerManagerTestHarness,
+    key: &ServerKey,
+    expected: ConnectionState,
+) {
+    for _ in 0..50 {
+        if harness.manager.connection_state(key).await == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Server never reached {:?}", expected);
+}
+
 fn test_key(server_id: &str) -> ServerKey {
     ServerKey {
         space_id: Uuid::new_v4(),
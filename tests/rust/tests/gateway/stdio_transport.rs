@@ -362,9 +362,10 @@ async fn test_stderr_capture_logs_to_server_log_manager() {
 
     // Read logs back
     let logs = log_manager
-        .read_logs(&space_id.to_string(), &server_id, 100, None)
+        .read_logs(&space_id.to_string(), &server_id, 100, None, None)
         .await
-        .expect("Failed to read logs");
+        .expect("Failed to read logs")
+        .entries;
 
     assert!(
         !logs.is_empty(),
@@ -441,7 +442,7 @@ fn test_shell_path_can_find_system_commands() {
         "Shell PATH should be resolved on Unix"
     );
 
-    let path_str = shell_path.unwrap().to_string_lossy();
+    let path_str = shell_path.as_deref().unwrap().to_string_lossy();
 
     // Verify common directories where system commands live are in the PATH
     let has_bin = path_str.split(':').any(|entry| {
@@ -467,7 +468,7 @@ fn test_shell_path_richer_than_minimal() {
     let shell_path = shell_env::get_shell_path();
     assert!(shell_path.is_some());
 
-    let path_str = shell_path.unwrap().to_string_lossy();
+    let path_str = shell_path.as_deref().unwrap().to_string_lossy();
     let entry_count = path_str.split(':').count();
 
     // A minimal PATH has ~4 entries (/usr/bin:/bin:/usr/sbin:/sbin).
@@ -497,7 +498,7 @@ async fn test_child_process_receives_shell_path() {
     let mut env: HashMap<String, String> = HashMap::new();
     env.insert("FOO".to_string(), "bar".to_string());
 
-    if let Some(path) = shell_path {
+    if let Some(path) = &shell_path {
         if let Some(path_str) = path.to_str() {
             env.insert("PATH".to_string(), path_str.to_string());
         }
@@ -519,7 +520,7 @@ async fn test_child_process_receives_shell_path() {
     let child_path = child_path.trim();
 
     // Verify the child's PATH matches what we injected
-    let expected = shell_path.unwrap().to_string_lossy();
+    let expected = shell_path.as_deref().unwrap().to_string_lossy();
     assert_eq!(
         child_path,
         expected.as_ref(),
@@ -544,7 +545,7 @@ async fn test_user_path_override_not_clobbered() {
 
     // Simulate inject_shell_path behavior: should NOT override
     if !env.contains_key("PATH") {
-        if let Some(path) = shell_path {
+        if let Some(path) = &shell_path {
             if let Some(path_str) = path.to_str() {
                 env.insert("PATH".to_string(), path_str.to_string());
             }
@@ -611,3 +612,99 @@ async fn test_stdio_transport_resolves_command_via_shell_path() {
         _ => {}
     }
 }
+
+/// Verify that a missing working directory fails before the process is spawned
+#[tokio::test]
+async fn test_stdio_transport_connect_cwd_not_found() {
+    use mcpmux_gateway::pool::transport::StdioTransport;
+    use mcpmux_gateway::pool::{Transport, TransportConnectResult};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    #[cfg(windows)]
+    let program = "cmd";
+    #[cfg(unix)]
+    let program = "sh";
+
+    let missing = tempfile::tempdir()
+        .expect("Failed to create temp dir")
+        .path()
+        .join("does-not-exist");
+
+    let transport = StdioTransport::new(
+        program.to_string(),
+        vec![],
+        HashMap::new(),
+        Uuid::new_v4(),
+        "test-server".to_string(),
+        None,
+        Duration::from_secs(5),
+        None,
+    )
+    .with_cwd(&missing);
+
+    match transport.connect().await {
+        TransportConnectResult::Failed(msg) => {
+            assert!(
+                msg.contains("Working directory not found"),
+                "Expected 'Working directory not found', got: {msg}"
+            );
+        }
+        _ => panic!("Expected TransportConnectResult::Failed for missing cwd"),
+    }
+}
+
+/// Verify that a successful connect reports its startup timing breakdown
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stdio_transport_reports_startup_timing() {
+    use mcpmux_core::DomainEvent;
+    use mcpmux_gateway::pool::transport::StdioTransport;
+    use mcpmux_gateway::pool::{Transport, TransportConnectResult};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    // Answers `initialize` (echoing the request id), then idles
+    let fake_server = r#"read line
+id=$(printf '%s' "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-03-26","capabilities":{},"serverInfo":{"name":"fake","version":"1.0.0"}}}\n' "$id"
+while read line; do :; done"#;
+
+    let (event_tx, mut events) = tokio::sync::broadcast::channel(16);
+    let transport = StdioTransport::new(
+        "sh".to_string(),
+        vec!["-c".to_string(), fake_server.to_string()],
+        HashMap::new(),
+        Uuid::new_v4(),
+        "test-timed-server".to_string(),
+        None,
+        Duration::from_secs(10),
+        Some(event_tx),
+    );
+
+    match transport.connect().await {
+        TransportConnectResult::Connected(_client) => {}
+        TransportConnectResult::Failed(msg) => panic!("Expected connect, got: {msg}"),
+        TransportConnectResult::OAuthRequired { .. } => panic!("Unexpected OAuth"),
+    }
+
+    match events.try_recv().expect("No event emitted") {
+        DomainEvent::ServerConnected {
+            server_id,
+            startup_ms,
+            resolve_ms,
+            spawn_ms,
+            handshake_ms,
+            ..
+        } => {
+            assert_eq!(server_id, "test-timed-server");
+            // Phases are measured between the same instants, so they add up
+            // to the total up to millisecond truncation
+            let phases = resolve_ms + spawn_ms + handshake_ms;
+            assert!(phases <= startup_ms && startup_ms <= phases + 2);
+        }
+        other => panic!("Unexpected event: {other:?}"),
+    }
+}
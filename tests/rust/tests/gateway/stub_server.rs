@@ -0,0 +1,225 @@
+//! End-to-end STDIO transport tests against the stub MCP server
+//!
+//! `src/bin/stub_mcp_server.rs` is built with the integration tests, and
+//! Cargo exposes its path as `CARGO_BIN_EXE_stub-mcp-server`. Point a
+//! `StdioTransport` (or a `ResolvedTransport::Stdio`) at that path as the
+//! command to get a server that completes the handshake every time:
+//!
+//! ```ignore
+//! let transport = StdioTransport::new(
+//!     env!("CARGO_BIN_EXE_stub-mcp-server").to_string(),
+//!     vec![],
+//!     HashMap::new(),
+//!     space_id,
+//!     "stub".to_string(),
+//!     None,
+//!     Duration::from_secs(10),
+//!     None,
+//! );
+//! ```
+//!
+//! Its `echo` tool returns its `text` argument and its `crash` tool kills the
+//! process, for exercising restarts.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use mcpmux_core::DomainEvent;
+use mcpmux_gateway::pool::transport::{RestartPolicy, StdioTransport};
+use mcpmux_gateway::pool::{
+    ConnectionContext, ConnectionResult, ResolvedTransport, Transport, TransportConnectResult,
+};
+use rmcp::model::{CallToolRequestParams, CallToolResult};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use tests::mocks::{MockCredentialRepository, MockOutboundOAuthRepository};
+use tests::services::{create_mock_connection_service, test_feature_service, test_prefix_cache};
+
+const STUB_SERVER: &str = env!("CARGO_BIN_EXE_stub-mcp-server");
+
+fn stub_transport(event_tx: Option<broadcast::Sender<DomainEvent>>) -> StdioTransport {
+    StdioTransport::new(
+        STUB_SERVER.to_string(),
+        vec![],
+        HashMap::new(),
+        Uuid::new_v4(),
+        "stub".to_string(),
+        None,
+        Duration::from_secs(10),
+        event_tx,
+    )
+}
+
+fn echo(text: &str) -> CallToolRequestParams {
+    CallToolRequestParams {
+        name: "echo".into(),
+        arguments: serde_json::json!({ "text": text }).as_object().cloned(),
+        meta: None,
+        task: None,
+    }
+}
+
+/// Text of the first content item of a tool result.
+fn text_of(result: &CallToolResult) -> String {
+    let content = serde_json::to_value(&result.content[0]).unwrap();
+    content["text"].as_str().unwrap_or_default().to_string()
+}
+
+#[tokio::test]
+async fn test_stub_server_connects_and_serves_tools() {
+    let transport = stub_transport(None);
+
+    let TransportConnectResult::Connected(client) = transport.connect().await else {
+        panic!("Expected the stub server to complete the handshake");
+    };
+
+    let tools = client
+        .list_tools(Default::default())
+        .await
+        .expect("list_tools");
+    let names: Vec<_> = tools.tools.iter().map(|t| t.name.to_string()).collect();
+    assert_eq!(names, vec!["echo", "crash"]);
+
+    let result = client.call_tool(echo("hello")).await.expect("call_tool");
+    assert_eq!(text_of(&result), "hello");
+
+    drop(client);
+    assert!(transport.shutdown(Duration::from_secs(5)).await);
+}
+
+#[tokio::test]
+async fn test_stub_server_shuts_down_gracefully() {
+    let transport = stub_transport(None);
+    let TransportConnectResult::Connected(client) = transport.connect().await else {
+        panic!("Expected the stub server to complete the handshake");
+    };
+
+    // Still connected: the server has to be stopped, not just notice EOF
+    assert!(transport.shutdown(Duration::from_secs(5)).await);
+    drop(client);
+
+    // Nothing left to stop
+    assert!(transport.shutdown(Duration::from_secs(5)).await);
+}
+
+#[tokio::test]
+async fn test_stub_server_liveness() {
+    let transport = stub_transport(None);
+    assert!(!transport.is_alive().await);
+
+    let TransportConnectResult::Connected(client) = transport.connect().await else {
+        panic!("Expected the stub server to complete the handshake");
+    };
+    assert!(transport.is_alive().await);
+
+    // Without a restart policy the crashed process stays down
+    let peer = client.peer().clone();
+    tokio::spawn(async move {
+        let _ = peer
+            .call_tool(CallToolRequestParams {
+                name: "crash".into(),
+                arguments: None,
+                meta: None,
+                task: None,
+            })
+            .await;
+    });
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while transport.is_alive().await {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("crashed stub server still reported alive");
+
+    drop(client);
+    transport.shutdown(Duration::from_secs(5)).await;
+}
+
+#[tokio::test]
+async fn test_stub_server_is_restarted_after_crash() {
+    let (event_tx, mut events) = broadcast::channel(16);
+    let transport = stub_transport(Some(event_tx)).with_restart_policy(RestartPolicy {
+        max_restarts: 2,
+        base_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        stable_period: Duration::from_secs(60),
+    });
+    let TransportConnectResult::Connected(client) = transport.connect().await else {
+        panic!("Expected the stub server to complete the handshake");
+    };
+
+    // The crash call is lost with the process, so don't wait for its answer
+    let peer = client.peer().clone();
+    tokio::spawn(async move {
+        let _ = peer
+            .call_tool(CallToolRequestParams {
+                name: "crash".into(),
+                arguments: None,
+                meta: None,
+                task: None,
+            })
+            .await;
+    });
+
+    let mut exit_code = None;
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match events.recv().await.expect("event channel closed") {
+                DomainEvent::ServerExited { code, .. } => exit_code = code,
+                DomainEvent::ServerRecovered { .. } => break,
+                DomainEvent::ServerFailed { .. } => panic!("Supervisor gave up"),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("stub server was not restarted");
+    assert_eq!(exit_code, Some(1));
+
+    // The same client keeps working against the restarted process
+    let result = client.call_tool(echo("again")).await.expect("call_tool");
+    assert_eq!(text_of(&result), "again");
+
+    drop(client);
+    transport.shutdown(Duration::from_secs(5)).await;
+}
+
+#[tokio::test]
+async fn test_connection_service_caches_stub_capabilities() {
+    let credential_repo = Arc::new(MockCredentialRepository::new());
+    let oauth_repo = Arc::new(MockOutboundOAuthRepository::new());
+    let connection_service =
+        create_mock_connection_service(credential_repo, oauth_repo, test_prefix_cache());
+    let (feature_service, _, _) = test_feature_service();
+
+    let space_id = Uuid::new_v4();
+    let ctx = ConnectionContext::new(
+        space_id,
+        "stub",
+        ResolvedTransport::Stdio {
+            command: STUB_SERVER.to_string(),
+            args: vec![],
+            env: HashMap::new(),
+            secrets: vec![],
+            connect_timeout: Some(Duration::from_secs(10)),
+        },
+    );
+
+    let ConnectionResult::Connected { features, .. } =
+        connection_service.connect(&ctx, &feature_service).await
+    else {
+        panic!("Expected the stub server to connect");
+    };
+    assert_eq!(features.tools.len(), 2);
+
+    let cached = connection_service
+        .capabilities()
+        .get(space_id, "stub")
+        .expect("capabilities should be cached after connecting");
+    assert_eq!(cached.features.tools.len(), 2);
+    assert_eq!(cached.config_hash, ctx.transport.config_hash());
+    assert!(!cached.is_stale());
+}
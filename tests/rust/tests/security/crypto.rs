@@ -29,7 +29,7 @@ fn test_encrypt_decrypt_roundtrip() {
     let ciphertext = encryptor.encrypt(plaintext).expect("Failed to encrypt");
     let decrypted = encryptor.decrypt(&ciphertext).expect("Failed to decrypt");
 
-    assert_eq!(decrypted, plaintext);
+    assert_eq!(*decrypted, plaintext);
 }
 
 #[test]
@@ -50,7 +50,7 @@ fn test_encrypt_produces_different_ciphertext() {
     let decrypted1 = encryptor.decrypt(&ciphertext1).expect("Failed to decrypt");
     let decrypted2 = encryptor.decrypt(&ciphertext2).expect("Failed to decrypt");
     assert_eq!(decrypted1, decrypted2);
-    assert_eq!(decrypted1, plaintext);
+    assert_eq!(*decrypted1, plaintext);
 }
 
 #[test]
@@ -84,7 +84,7 @@ fn test_encrypt_empty_string() {
         .decrypt(&ciphertext)
         .expect("Failed to decrypt empty");
 
-    assert_eq!(decrypted, plaintext);
+    assert_eq!(*decrypted, plaintext);
 }
 
 #[test]
@@ -101,7 +101,7 @@ fn test_encrypt_unicode() {
         .decrypt(&ciphertext)
         .expect("Failed to decrypt unicode");
 
-    assert_eq!(decrypted, plaintext);
+    assert_eq!(*decrypted, plaintext);
 }
 
 #[test]
@@ -121,7 +121,7 @@ fn test_encrypt_large_data() {
         .decrypt(&ciphertext)
         .expect("Failed to decrypt large");
 
-    assert_eq!(decrypted, plaintext);
+    assert_eq!(*decrypted, plaintext);
 }
 
 #[test]
@@ -111,6 +111,11 @@ impl TestGateway {
             sort_order: 0,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
+            deleted_at: None,
+            version: 1,
+            tags: Vec::new(),
+            space_env: Default::default(),
+            sensitive_env: Default::default(),
         };
         mcpmux_core::SpaceRepository::create(&*space_repo, &space)
             .await